@@ -0,0 +1,80 @@
+//! Tracking of activity of game engine window: focus, minimization and suspension.
+//!
+//! Rendering is paused while the window is minimized or the application is suspended
+//! (and optionally while the window is unfocused), so the engine does not spin
+//! and never acquires images of zero-sized swapchain.
+
+use crate::window::{Event, Size};
+
+mod tests;
+
+/// Activity state of the window.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct WindowActivity {
+    focused: bool,
+    minimized: bool,
+    suspended: bool,
+}
+
+impl Default for WindowActivity {
+    fn default() -> Self {
+        Self {
+            focused: true,
+            minimized: false,
+            suspended: false,
+        }
+    }
+}
+
+impl WindowActivity {
+    /// If the window has input focus.
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    /// If the window is minimized.
+    pub fn is_minimized(&self) -> bool {
+        self.minimized
+    }
+
+    /// If the application is suspended by the system.
+    pub fn is_suspended(&self) -> bool {
+        self.suspended
+    }
+
+    /// Checks if frames should be rendered in the current state.
+    pub fn should_render(&self, render_when_unfocused: bool) -> bool {
+        !self.minimized && !self.suspended && (self.focused || render_when_unfocused)
+    }
+
+    /// Updates focus of the window, returning the event for the callback.
+    pub fn focus(&mut self, focused: bool) -> Event {
+        self.focused = focused;
+        Event::Focused(focused)
+    }
+
+    /// Updates size of the window, returning the event if the window was minimized or restored.
+    ///
+    /// Window is considered minimized while any of its dimensions is zero.
+    ///
+    pub fn resize(&mut self, size: Size) -> Option<Event> {
+        let minimized = size.width == 0 || size.height == 0;
+        if minimized == self.minimized {
+            return None;
+        }
+        self.minimized = minimized;
+        Some(match minimized {
+            true => Event::Minimized,
+            false => Event::Restored,
+        })
+    }
+
+    /// Updates suspension of the application, returning the event for the callback.
+    pub fn suspend(&mut self, suspended: bool) -> Event {
+        self.suspended = suspended;
+        match suspended {
+            true => Event::Suspended,
+            false => Event::Resumed,
+        }
+    }
+}
@@ -0,0 +1,41 @@
+#![cfg(test)]
+
+use super::WindowActivity;
+use crate::window::{Event, Size};
+
+#[test]
+fn minimized_window_is_not_rendered() {
+    let mut activity = WindowActivity::default();
+    assert!(activity.should_render(false));
+
+    assert!(matches!(
+        activity.resize(Size::new(0, 0)),
+        Some(Event::Minimized)
+    ));
+    assert!(activity.resize(Size::new(0, 0)).is_none());
+    assert!(!activity.should_render(true));
+
+    assert!(matches!(
+        activity.resize(Size::new(800, 600)),
+        Some(Event::Restored)
+    ));
+    assert!(activity.resize(Size::new(1024, 768)).is_none());
+    assert!(activity.should_render(true));
+}
+
+#[test]
+fn unfocused_window_is_rendered_if_configured() {
+    let mut activity = WindowActivity::default();
+    assert!(matches!(activity.focus(false), Event::Focused(false)));
+    assert!(activity.should_render(true));
+    assert!(!activity.should_render(false));
+}
+
+#[test]
+fn suspended_application_is_not_rendered() {
+    let mut activity = WindowActivity::default();
+    assert!(matches!(activity.suspend(true), Event::Suspended));
+    assert!(!activity.should_render(true));
+    assert!(matches!(activity.suspend(false), Event::Resumed));
+    assert!(activity.should_render(true));
+}
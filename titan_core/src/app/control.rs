@@ -0,0 +1,79 @@
+//! Control of the event loop of game engine from the user callback.
+
+use winit::event_loop::ControlFlow;
+
+use super::stats::FrameStats;
+
+mod tests;
+
+/// Mode of the event loop when there are no new events.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum LoopMode {
+    /// Loop runs continuously, rendering frames as fast as possible
+    /// (or as allowed by [frame limiter](crate::config::Config::with_target_fps)).
+    #[default]
+    Poll,
+    /// Loop waits for new events, rendering one frame after each batch of events.
+    Wait,
+}
+
+/// Handle which is provided to the user callback to control the event loop.
+///
+/// Changes are applied after the callback returns.
+///
+#[derive(Debug, Copy, Clone, Default)]
+pub struct LoopControl {
+    mode: LoopMode,
+    exit: bool,
+    frame_stats: FrameStats,
+}
+
+impl LoopControl {
+    /// Requests the engine to shut down.
+    ///
+    /// [`Destroyed`](crate::window::Event::Destroyed) event is still delivered
+    /// and all resources of the engine are dropped before the process exits.
+    ///
+    pub fn exit(&mut self) {
+        self.exit = true;
+    }
+
+    /// Checks if the engine was requested to shut down.
+    pub fn is_exiting(&self) -> bool {
+        self.exit
+    }
+
+    /// Makes the event loop wait for new events.
+    pub fn set_wait(&mut self) {
+        self.mode = LoopMode::Wait;
+    }
+
+    /// Makes the event loop run continuously, which is default.
+    pub fn set_poll(&mut self) {
+        self.mode = LoopMode::Poll;
+    }
+
+    /// Current mode of the event loop.
+    pub fn mode(&self) -> LoopMode {
+        self.mode
+    }
+
+    /// Statistics of frames which were rendered before the current event.
+    pub fn frame_stats(&self) -> FrameStats {
+        self.frame_stats
+    }
+
+    /// Updates statistics which are visible to the user callback.
+    pub(crate) fn set_frame_stats(&mut self, frame_stats: FrameStats) {
+        self.frame_stats = frame_stats;
+    }
+
+    /// Control flow of winit event loop which corresponds to this state.
+    pub(crate) fn control_flow(&self) -> ControlFlow {
+        match (self.exit, self.mode) {
+            (true, _) => ControlFlow::Exit,
+            (false, LoopMode::Poll) => ControlFlow::Poll,
+            (false, LoopMode::Wait) => ControlFlow::Wait,
+        }
+    }
+}
@@ -0,0 +1,40 @@
+#![cfg(test)]
+
+use std::time::Duration;
+
+use winit::event_loop::ControlFlow;
+
+use super::{LoopControl, LoopMode};
+use crate::app::stats::FrameStats;
+
+#[test]
+fn loop_polls_by_default() {
+    let control = LoopControl::default();
+    assert_eq!(control.mode(), LoopMode::Poll);
+    assert!(!control.is_exiting());
+    assert_eq!(control.control_flow(), ControlFlow::Poll);
+}
+
+#[test]
+fn exit_overrides_mode() {
+    let mut control = LoopControl::default();
+    control.set_wait();
+    assert_eq!(control.control_flow(), ControlFlow::Wait);
+    control.exit();
+    control.set_poll();
+    assert!(control.is_exiting());
+    assert_eq!(control.control_flow(), ControlFlow::Exit);
+}
+
+#[test]
+fn frame_stats_are_visible_to_callback() {
+    let mut control = LoopControl::default();
+    assert_eq!(control.frame_stats(), FrameStats::default());
+    let stats = FrameStats {
+        frame_count: 1,
+        reclaimed_time: Some(Duration::from_millis(2)),
+        ..FrameStats::default()
+    };
+    control.set_frame_stats(stats);
+    assert_eq!(control.frame_stats(), stats);
+}
@@ -1,23 +1,78 @@
 //! Utilities for engine initialization.
 
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::path::Path;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Receiver;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
-use egui::TextureId;
+use egui::{ClippedMesh, Texture, TextureId};
 use egui_winit_platform::{Platform, PlatformDescriptor};
-use image::RgbaImage;
+use image::{ImageError, RgbaImage};
 use thiserror::Error;
 use ultraviolet::{Mat4, Vec3};
-use winit::event::{Event, StartCause, WindowEvent};
+use vulkano::buffer::CpuAccessibleBuffer;
+use vulkano::format::Format;
+use vulkano::image::{ImageCreationError, StorageImage};
+use vulkano::memory::DeviceMemoryAllocError;
+use winit::event::{DeviceEvent, Event, StartCause, WindowEvent};
 use winit::event_loop::{ControlFlow, EventLoop};
 use winit::window::Window;
 
+use self::activity::WindowActivity;
+use self::control::LoopControl;
+use self::pacing::FrameLimiter;
+use self::proxy::{EventProxy, LoopEvent};
+use self::spike::{SpikeDetector, SpikeReport, SpikeSource};
+use self::stats::FrameStats;
+use self::timestep::FixedTimestep;
 use crate::{
-    config::Config,
-    graphics::{camera::CameraUBO, error::ImageRegisterError, Renderer, RendererCreationError},
-    window::{Event as MyEvent, Size},
+    adapter::{DeviceFeatures, DeviceInfo, PhysicalDeviceInfo},
+    asset::{error::AssetError, AssetSource},
+    atlas::AtlasBuilder,
+    color::{Color, ColorHandle},
+    compute::{
+        BindingKind, ComputeDescriptor, ComputePipeline, ComputePipelineCreationError,
+        DispatchError,
+    },
+    config::{CompositeAlpha, Config, ConfigError, PresentMode, SwapchainUsage},
+    external::{ExternalPassDesc, ExternalPassId},
+    failure::{AssetFailure, FailedAsset, FailureKind, OBJECT_MATERIAL},
+    graphics::{
+        camera::CameraUBO,
+        error::{
+            ImageRegisterError, MeshError, ObjectPipelineError, ObjectShaderError, RenderError,
+            WindowCreationError,
+        },
+        memory::HeapMap,
+        Indices, PickResult, PickTicket, Renderer, RendererCreationError, TransformHandle, Vertex,
+    },
+    params::{error::ParamBlockError, ParamBlock, ParamValue},
+    pipeline::{GraphicsPipelineDescriptor, PipelineId},
+    pipeline_cache::PipelineCacheError,
+    post::{error::PostEffectError, PostProcessChain},
+    readback::ReadbackRegion,
+    screenshot::{Screenshot, ScreenshotError},
+    semaphore::{TimelineSemaphore, TimelineSemaphoreError},
+    spirv::{Shader, ShaderLoadError, ShaderStage},
+    submit::{OneTimeBuilder, SubmitOnceError},
+    texture::{SamplerOptions, TextureError, TextureQuality, TextureStats},
+    window::{
+        input, Event as MyEvent, KeyCode, Keyboard, MonitorInfo, Position, Size, WindowDescriptor,
+        WindowHandle, WindowId,
+    },
 };
 
+pub mod activity;
+pub mod control;
+pub mod pacing;
+pub mod proxy;
+pub mod spike;
+pub mod stats;
+pub mod timestep;
+
 pub type Result<T> = std::result::Result<T, AppCreationError>;
 
 #[derive(Debug, Error)]
@@ -27,6 +82,22 @@ pub enum AppCreationError {
 
     #[error("graphics initialization error: {0}")]
     Graphics(#[from] RendererCreationError),
+
+    #[error("invalid configuration: {0}")]
+    Config(#[from] ConfigError),
+}
+
+/// Error that can happen when loading an image for UI from the file.
+#[derive(Debug, Error)]
+pub enum UiImageLoadError {
+    #[error("image asset loading failure: {0}")]
+    Asset(#[from] AssetError),
+
+    #[error("image decoding failure: {0}")]
+    Decode(#[from] ImageError),
+
+    #[error("image registering failure: {0}")]
+    Register(#[from] ImageRegisterError),
 }
 
 /// Type which represents duration between two frames.
@@ -37,39 +108,230 @@ pub enum AppCreationError {
 /// Can be created using [`init`] function.
 ///
 pub struct Application {
-    _config: Config,
+    config: Config,
+    scale_factor: f64,
+    assets: AssetSource,
     renderer: Renderer,
+    timestep: FixedTimestep,
+    spike_detector: Option<SpikeDetector>,
+    gpu_spike_detector: Option<SpikeDetector>,
+    frame_limiter: FrameLimiter,
+    transform: TransformHandle,
+    keyboard: Keyboard,
+    activity: WindowActivity,
+    windows: HashMap<WindowId, WindowState>,
     egui: Option<Platform>,
-    event_loop: Option<EventLoop<()>>,
+    event_loop: Option<EventLoop<LoopEvent>>,
+    headless_events: Option<Receiver<LoopEvent>>,
+    proxy: EventProxy,
 }
 
 impl Application {
     fn new(config: Config) -> Result<Self> {
+        config.validate()?;
         let event_loop = EventLoop::with_user_event();
         let renderer = Renderer::new(&config, &event_loop)?;
 
-        let window = renderer.window();
-        let size = window.inner_size();
+        let scale_factor = renderer.window().map_or(1.0, Window::scale_factor);
+        let proxy = EventProxy::new(event_loop.create_proxy());
+        let mut application = Self::with_renderer(config, renderer, scale_factor, proxy);
+        application.event_loop = Some(event_loop);
+        Ok(application)
+    }
+
+    fn new_headless(config: Config, size: Size) -> Result<Self> {
+        config.validate()?;
+        let renderer = Renderer::new_headless(&config, size)?;
+
+        let (proxy, events) = EventProxy::channel();
+        let mut application = Self::with_renderer(config, renderer, 1.0, proxy);
+        application.headless_events = Some(events);
+        Ok(application)
+    }
+
+    fn with_renderer(
+        config: Config,
+        renderer: Renderer,
+        scale_factor: f64,
+        proxy: EventProxy,
+    ) -> Self {
+        let size = renderer.output_size();
         let egui = Platform::new(PlatformDescriptor {
             physical_width: size.width,
             physical_height: size.height,
-            scale_factor: window.scale_factor(),
+            scale_factor,
             ..Default::default()
         });
-
-        Ok(Self {
+        Self {
             renderer,
+            timestep: FixedTimestep::new(config.fixed_timestep()),
+            spike_detector: config.spike_threshold().map(SpikeDetector::new),
+            gpu_spike_detector: config.spike_threshold().map(SpikeDetector::new),
+            frame_limiter: FrameLimiter::new(config.target_fps()),
+            transform: TransformHandle::default(),
+            keyboard: Keyboard::default(),
+            activity: WindowActivity::default(),
+            windows: HashMap::new(),
             egui: Some(egui),
-            _config: config,
-            event_loop: Some(event_loop),
-        })
+            assets: AssetSource::new(config.asset_roots().iter().cloned()),
+            config,
+            scale_factor,
+            event_loop: None,
+            headless_events: None,
+            proxy,
+        }
     }
 
-    /// Returns underlying window of this application.
-    pub fn window(&self) -> &Window {
+    /// Returns underlying window of this application,
+    /// or `None` if it was created by [`init_headless`].
+    pub fn window(&self) -> Option<&Window> {
         self.renderer.window()
     }
 
+    /// Size of images which frames are rendered into, in physical pixels.
+    pub fn output_size(&self) -> Size {
+        self.renderer.output_size()
+    }
+
+    /// Description of physical device (GPU) which is used for rendering.
+    pub fn physical_device_info(&self) -> &PhysicalDeviceInfo {
+        self.renderer.physical_device_info()
+    }
+
+    /// Properties, limits and features of physical device which is used for rendering.
+    pub fn device_info(&self) -> &DeviceInfo {
+        self.renderer.device_info()
+    }
+
+    /// Features which are enabled on the device.
+    pub fn enabled_features(&self) -> DeviceFeatures {
+        self.renderer.enabled_features()
+    }
+
+    /// Creates timeline semaphore with provided initial value of the counter.
+    ///
+    /// Requires `timeline_semaphore` device feature, see [`Config::with_device_requirements`].
+    ///
+    pub fn create_timeline_semaphore(
+        &self,
+        initial_value: u64,
+    ) -> std::result::Result<TimelineSemaphore, TimelineSemaphoreError> {
+        self.renderer.create_timeline_semaphore(initial_value)
+    }
+
+    /// Creates compute pipeline from provided compute shader
+    /// with provided bindings of its descriptor set.
+    pub fn create_compute_pipeline(
+        &self,
+        shader: &Shader,
+        bindings: &[BindingKind],
+    ) -> std::result::Result<ComputePipeline, ComputePipelineCreationError> {
+        self.renderer.create_compute_pipeline(shader, bindings)
+    }
+
+    /// Creates host visible storage buffer with provided data,
+    /// so it could be read back right after the dispatch.
+    pub fn create_storage_buffer<T>(
+        &self,
+        data: &[T],
+    ) -> std::result::Result<Arc<CpuAccessibleBuffer<[T]>>, DeviceMemoryAllocError>
+    where
+        T: Copy + Send + Sync + 'static,
+    {
+        self.renderer.create_storage_buffer(data)
+    }
+
+    /// Creates 2D image with provided format which could be used
+    /// for [storage image](BindingKind::StorageImage) bindings of compute pipelines.
+    pub fn create_storage_image(
+        &self,
+        size: Size,
+        format: Format,
+    ) -> std::result::Result<Arc<StorageImage>, ImageCreationError> {
+        self.renderer.create_storage_image(size, format)
+    }
+
+    /// Dispatches compute pipeline with provided count of work groups and descriptors
+    /// and waits until the dispatch is finished.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the device has no queue family which supports compute operations.
+    ///
+    pub fn dispatch_compute(
+        &self,
+        pipeline: &ComputePipeline,
+        groups: [u32; 3],
+        descriptors: Vec<ComputeDescriptor>,
+    ) -> std::result::Result<(), DispatchError> {
+        self.renderer
+            .dispatch_compute(pipeline, groups, descriptors)
+    }
+
+    /// Records commands into one-time command buffer, submits it to the graphics queue
+    /// and waits until its execution is finished.
+    ///
+    /// See [`submit_once`](crate::submit::submit_once) for details.
+    ///
+    pub fn submit_once<E>(
+        &self,
+        record: impl FnOnce(&mut OneTimeBuilder) -> std::result::Result<(), E>,
+    ) -> std::result::Result<(), E>
+    where
+        E: From<SubmitOnceError>,
+    {
+        self.renderer.submit_once(record)
+    }
+
+    /// Checks if the extension with provided name is enabled on the device.
+    pub fn is_extension_enabled(&self, name: &CStr) -> bool {
+        self.renderer.is_extension_enabled(name)
+    }
+
+    /// Cloneable handle to control the window (title, size, fullscreen, cursor),
+    /// which could be moved into the callback of [`run`](Self::run).
+    ///
+    /// Returns `None` if the application was created by [`init_headless`].
+    ///
+    pub fn window_handle(&self) -> Option<WindowHandle> {
+        self.renderer.window_handle()
+    }
+
+    /// Creates additional window which presents the same frames as the main window,
+    /// sharing the device and its resources.
+    ///
+    /// Additional windows are mirrors of the main window: they have no scene of their own.
+    /// Their input, focus and resizes are reported by
+    /// [`AdditionalWindow`](MyEvent::AdditionalWindow) events with id of the window.
+    /// Windows must be created before the application is [run](Self::run).
+    ///
+    /// When additional window is closed, only its resources are destroyed
+    /// and [`WindowClosed`](MyEvent::WindowClosed) event is emitted.
+    /// Window which fails to present is destroyed without affecting the main window.
+    ///
+    pub fn create_window(
+        &mut self,
+        descriptor: WindowDescriptor,
+    ) -> std::result::Result<WindowId, WindowCreationError> {
+        let event_loop = self
+            .event_loop
+            .as_ref()
+            .ok_or(WindowCreationError::Headless)?;
+        let id = self
+            .renderer
+            .create_window(&self.config, event_loop, &descriptor)?;
+        self.windows.insert(id, WindowState::default());
+        Ok(id)
+    }
+
+    /// Monitors which are available to the window with their video modes,
+    /// or nothing if the application was created by [`init_headless`].
+    pub fn monitors(&self) -> Vec<MonitorInfo> {
+        self.window_handle()
+            .map(|window| window.monitor_infos())
+            .unwrap_or_default()
+    }
+
     pub fn register_ui_image(
         &mut self,
         image: &RgbaImage,
@@ -77,18 +339,596 @@ pub fn register_ui_image(
         self.renderer.register_ui_image(image)
     }
 
-    /// Starts execution of game engine.
-    pub fn run(mut self, mut callback: impl FnMut(MyEvent) + 'static) -> ! {
-        let event_loop = self.event_loop.take().unwrap();
+    /// Loads an image for UI from the asset file and registers it.
+    ///
+    /// Relative path is resolved by [asset source](Self::assets) of this application.
+    ///
+    pub fn register_ui_image_from_file(
+        &mut self,
+        path: impl AsRef<Path>,
+    ) -> std::result::Result<TextureId, UiImageLoadError> {
+        let data = self.assets.read(path)?;
+        let image = image::load_from_memory(&data)?.to_rgba8();
+        Ok(self.register_ui_image(&image)?)
+    }
+
+    /// Loads shader from provided bytes of SPIR-V module.
+    ///
+    /// Failure is also reported as [event](MyEvent::ShaderCompileFailed)
+    /// with the name of entry point as the name of the shader.
+    ///
+    pub fn load_shader(
+        &mut self,
+        bytes: &[u8],
+        stage: ShaderStage,
+        entry_point: &str,
+    ) -> std::result::Result<Shader, ShaderLoadError> {
+        let shader = self.renderer.load_shader(bytes, stage, entry_point);
+        self.check_shader(entry_point, stage, shader)
+    }
+
+    /// Compiles shader from GLSL source with provided name,
+    /// resolving its `#include` directives by provided resolver, if any.
+    ///
+    /// Failure is also reported as [event](MyEvent::ShaderCompileFailed)
+    /// with provided name as the name of the shader.
+    ///
+    #[cfg(feature = "glsl")]
+    pub fn compile_shader(
+        &mut self,
+        source: &str,
+        stage: ShaderStage,
+        name: &str,
+        resolver: Option<crate::spirv::GlslIncludeResolver>,
+    ) -> std::result::Result<Shader, ShaderLoadError> {
+        let shader = self.renderer.compile_shader(source, stage, name, resolver);
+        self.check_shader(name, stage, shader)
+    }
+
+    /// Loads shader from the SPIR-V asset file.
+    ///
+    /// Relative path is resolved by [asset source](Self::assets) of this application.
+    ///
+    pub fn load_shader_from_file(
+        &mut self,
+        path: impl AsRef<Path>,
+        stage: ShaderStage,
+        entry_point: &str,
+    ) -> std::result::Result<Shader, ShaderLoadError> {
+        let path = path.as_ref();
+        let shader = self
+            .assets
+            .read(path)
+            .map_err(ShaderLoadError::from)
+            .and_then(|bytes| self.renderer.load_shader(&bytes, stage, entry_point));
+        self.check_shader(&path.display().to_string(), stage, shader)
+    }
+
+    /// Compiles shader from the GLSL asset file.
+    ///
+    /// Relative path is resolved by [asset source](Self::assets) of this application.
+    /// `#include` directives are resolved next to the including file first,
+    /// then by the asset source, see [`AssetSource::glsl_include`].
+    ///
+    #[cfg(feature = "glsl")]
+    pub fn compile_shader_from_file(
+        &mut self,
+        path: impl AsRef<Path>,
+        stage: ShaderStage,
+    ) -> std::result::Result<Shader, ShaderLoadError> {
+        let path = path.as_ref();
+        let assets = &self.assets;
+        let shader = assets
+            .resolve(path)
+            .and_then(|resolved| Ok((assets.read_to_string(&resolved)?, resolved)))
+            .map_err(ShaderLoadError::from)
+            .and_then(|(source, resolved)| {
+                let name = resolved.display().to_string();
+                let resolver =
+                    |requested: &str, including: &str| assets.glsl_include(requested, including);
+                self.renderer
+                    .compile_shader(&source, stage, &name, Some(&resolver))
+            });
+        self.check_shader(&path.display().to_string(), stage, shader)
+    }
+
+    /// Replaces built-in shader of game objects by provided shader of the same stage.
+    ///
+    /// Shader must declare the same inputs, outputs, descriptors and push constants
+    /// as the built-in shader of its stage.
+    ///
+    pub fn set_object_shader(
+        &mut self,
+        shader: Shader,
+    ) -> std::result::Result<(), ObjectShaderError> {
+        let result = self.renderer.set_object_shader(shader);
+        self.check_pipeline(OBJECT_MATERIAL, result)
+    }
+
+    /// Creates graphics pipeline of game objects with provided state,
+    /// for example to draw transparent game objects with blending.
+    ///
+    /// Pipeline with the same state is created only once, so its id is returned again.
+    ///
+    pub fn create_object_pipeline(
+        &mut self,
+        descriptor: GraphicsPipelineDescriptor,
+    ) -> std::result::Result<PipelineId, ObjectShaderError> {
+        self.renderer.create_object_pipeline(descriptor)
+    }
+
+    /// Assigns graphics pipeline to the game object with provided id.
+    ///
+    /// Game objects are drawn in order of their ids, so objects which are blended
+    /// should have greater ids than objects behind them.
+    /// Assignments are reset when the mesh is replaced.
+    ///
+    pub fn set_object_pipeline(
+        &mut self,
+        object: u32,
+        pipeline: PipelineId,
+    ) -> std::result::Result<(), ObjectPipelineError> {
+        self.renderer.set_object_pipeline(object, pipeline)
+    }
+
+    /// Restores built-in shader of game objects of provided stage.
+    pub fn reset_object_shader(
+        &mut self,
+        stage: ShaderStage,
+    ) -> std::result::Result<(), ObjectShaderError> {
+        let result = self.renderer.reset_object_shader(stage);
+        self.check_pipeline(OBJECT_MATERIAL, result)
+    }
+
+    /// Enables hot reloading of shaders of game objects from provided files.
+    ///
+    /// Relative paths are resolved by [asset source](Self::assets) of this application,
+    /// and `#include` directives of GLSL shaders are resolved the same way
+    /// as by [`compile_shader_from_file`](Self::compile_shader_from_file).
+    /// Stage and format of each shader are inferred from its extension,
+    /// see [`shader_file_kind`](crate::hot_reload::shader_file_kind).
+    /// Modified shaders are reloaded between frames. If shader fails to load,
+    /// previous one is kept; if pipeline fails to build, game objects are drawn
+    /// with the error material. Failures are reported as events.
+    ///
+    #[cfg(feature = "hot-reload")]
+    pub fn enable_shader_hot_reload(
+        &mut self,
+        paths: &[std::path::PathBuf],
+    ) -> std::result::Result<(), crate::hot_reload::HotReloadError> {
+        self.renderer
+            .enable_shader_hot_reload(paths, self.assets.clone())
+    }
+
+    /// Present mode which is currently used to present rendered images.
+    pub fn present_mode(&self) -> PresentMode {
+        self.renderer.present_mode()
+    }
+
+    /// Sets preferred present mode, or `None` to let the engine choose it.
+    ///
+    /// New present mode is used from the next frame. See also [`Config::with_present_mode`].
+    ///
+    pub fn set_present_mode(&mut self, present_mode: Option<PresentMode>) {
+        self.renderer.set_present_mode(present_mode)
+    }
+
+    /// Saves pipeline cache to its [file](Config::with_pipeline_cache_path) right away.
+    ///
+    /// Pipeline cache is also saved when the application is destroyed.
+    ///
+    pub fn save_pipeline_cache(&self) -> std::result::Result<(), PipelineCacheError> {
+        self.renderer.save_pipeline_cache()
+    }
+
+    /// Shaders and pipelines whose last attempt to load has failed.
+    ///
+    /// Useful for debug UI which lists broken assets.
+    ///
+    pub fn failed_assets(&self) -> &[FailedAsset] {
+        self.renderer.failed_assets()
+    }
+
+    fn check_shader(
+        &mut self,
+        name: &str,
+        stage: ShaderStage,
+        shader: std::result::Result<Shader, ShaderLoadError>,
+    ) -> std::result::Result<Shader, ShaderLoadError> {
+        match &shader {
+            Ok(_) => self
+                .renderer
+                .resolve_failure(name, FailureKind::ShaderCompile { stage }),
+            Err(error) => self.report_failure(AssetFailure::shader(name, stage, error)),
+        }
+        shader
+    }
+
+    fn check_pipeline<T, E: std::fmt::Display>(
+        &mut self,
+        material: &str,
+        result: std::result::Result<T, E>,
+    ) -> std::result::Result<T, E> {
+        match &result {
+            Ok(_) => self
+                .renderer
+                .resolve_failure(material, FailureKind::PipelineBuild),
+            Err(error) => self.report_failure(AssetFailure::pipeline(material, error)),
+        }
+        result
+    }
+
+    /// Sends failure to the event loop, unless it is identical to the previous one.
+    fn report_failure(&mut self, failure: AssetFailure) {
+        log::error!("{}", failure);
+        if self.renderer.record_failure(failure.clone()) {
+            self.proxy.send_failure(failure);
+        }
+    }
+
+    /// Requests readback of provided region at the end of the next rendered frame.
+    ///
+    /// Callback is invoked with bytes of the region when that frame is finished by the device.
+    ///
+    pub fn request_readback(
+        &mut self,
+        region: ReadbackRegion,
+        callback: impl FnOnce(&[u8]) + Send + 'static,
+    ) {
+        self.renderer.request_readback(region, callback)
+    }
+
+    /// Requests screenshot of the next rendered frame.
+    ///
+    /// Callback is invoked with the screenshot when that frame is finished by the device,
+    /// on the thread which runs the engine. Saving the screenshot into the file
+    /// (see [`Screenshot::save_png`]) could take a while, so consider doing it on another thread.
+    ///
+    pub fn capture_screenshot(
+        &mut self,
+        callback: impl FnOnce(Screenshot) + Send + 'static,
+    ) -> std::result::Result<(), ScreenshotError> {
+        self.renderer.capture_screenshot(callback)
+    }
+
+    /// Source of assets which resolves their paths by roots of the configuration.
+    pub fn assets(&self) -> &AssetSource {
+        &self.assets
+    }
+
+    /// Unregisters the image which was [registered](Self::register_ui_image) for UI,
+    /// so its texture identifier must not be used by UI anymore.
+    pub fn unregister_ui_image(&mut self, texture_id: TextureId) {
+        self.renderer.unregister_ui_image(texture_id)
+    }
+
+    /// Uploads pages of the atlas which were changed since the last upload.
+    ///
+    /// Each changed page is registered as new UI image, and the previous image of the page
+    /// is unregistered. Textures of pages could be retrieved by [`AtlasBuilder::texture`].
+    ///
+    pub fn upload_atlas(
+        &mut self,
+        atlas: &mut AtlasBuilder,
+    ) -> std::result::Result<(), ImageRegisterError> {
+        let dirty: Vec<_> = atlas
+            .dirty_pages()
+            .map(|(index, image)| Ok((index, self.register_ui_image(image)?)))
+            .collect::<std::result::Result<_, ImageRegisterError>>()?;
+        for (index, texture_id) in dirty {
+            if let Some(old) = atlas.set_texture(index, texture_id) {
+                self.unregister_ui_image(old);
+            }
+        }
+        Ok(())
+    }
+
+    /// Count of frames which could be processed by the device at the same time.
+    pub fn frames_in_flight(&self) -> u32 {
+        self.renderer.frames_in_flight()
+    }
+
+    /// Count of images which frames are rendered into,
+    /// which could differ from count of frames in flight.
+    pub fn image_count(&self) -> usize {
+        self.renderer.image_count()
+    }
+
+    /// Additional usage which swapchain images were created with, or `None` without a window.
+    pub fn swapchain_usage(&self) -> Option<SwapchainUsage> {
+        self.renderer.swapchain_usage()
+    }
+
+    /// Way the compositor blends presented images, or `None` without a window.
+    pub fn composite_alpha(&self) -> Option<CompositeAlpha> {
+        self.renderer.composite_alpha()
+    }
+
+    /// Count of frames which were skipped because the device was behind
+    /// longer than [frame wait timeout](Config::with_frame_wait_timeout).
+    pub fn skipped_frames(&self) -> u64 {
+        self.renderer.skipped_frames()
+    }
+
+    /// Count of frames which were submitted to the device.
+    pub fn frame_counter(&self) -> u64 {
+        self.renderer.frame_counter()
+    }
+
+    /// Handle which could be sent to other threads
+    /// to post [user events](MyEvent::User) into the event loop.
+    pub fn event_proxy(&self) -> EventProxy {
+        self.proxy.clone()
+    }
+
+    /// Target count of frames per second, if frame rate is limited.
+    pub fn target_fps(&self) -> Option<u32> {
+        self.frame_limiter.target_fps()
+    }
+
+    /// Limits frame rate to provided count of frames per second, `None` means uncapped.
+    pub fn set_target_fps(&mut self, target_fps: Option<u32>) {
+        self.frame_limiter.set_target_fps(target_fps)
+    }
+
+    /// Statistics of recently rendered frames: frame time, FPS and GPU time.
+    pub fn frame_stats(&self) -> FrameStats {
+        self.renderer.frame_stats()
+    }
+
+    /// Number of the latest frame which is confirmed to be finished by the device.
+    pub fn last_retired_frame(&self) -> Option<u64> {
+        self.renderer.last_retired_frame()
+    }
+
+    /// Registers callback which will be invoked when the frame with provided number is finished.
+    ///
+    /// See [`frame_counter`](Self::frame_counter) for numbering of frames.
+    ///
+    pub fn on_frame_retired(&mut self, frame: u64, callback: impl FnOnce() + Send + 'static) {
+        self.renderer.on_frame_retired(frame, callback)
+    }
+
+    /// Collects memory blocks of each heap with ranges used by engine resources and free ranges.
+    ///
+    /// Map could be serialized into JSON with [`memory_map_to_json`](crate::memory::memory_map_to_json)
+    /// if `serde` feature is enabled.
+    ///
+    pub fn memory_map(&self) -> Vec<HeapMap> {
+        self.renderer.memory_map()
+    }
+
+    /// Replaces mesh of game objects by provided vertices and indices.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if mesh is empty, if some index is out of bounds
+    /// or if buffers cannot be uploaded to the GPU.
+    ///
+    pub fn set_mesh(
+        &mut self,
+        vertices: Vec<Vertex>,
+        indices: impl Into<Indices>,
+    ) -> std::result::Result<(), MeshError> {
+        self.renderer.set_mesh(vertices, indices.into())
+    }
+
+    /// Replaces texture of game objects by provided image.
+    ///
+    /// Texture is sampled by UV positions of mesh vertices, see [`Vertex::with_uv`].
+    /// Image of any size is supported, including non-power-of-two ones.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if image is empty or it cannot be uploaded to the GPU.
+    ///
+    pub fn set_object_texture(
+        &mut self,
+        image: &RgbaImage,
+        options: SamplerOptions,
+    ) -> std::result::Result<(), TextureError> {
+        self.renderer.set_object_texture(image, options)
+    }
+
+    /// Sets global quality tier of textures (for example, from graphics settings of the game).
+    ///
+    /// Lower tiers skip highest mip levels of textures when sampling.
+    ///
+    pub fn set_texture_quality(
+        &mut self,
+        quality: TextureQuality,
+    ) -> std::result::Result<(), TextureError> {
+        self.renderer.set_texture_quality(quality)
+    }
+
+    /// Sets global bias of the level of detail of texture samplers.
+    ///
+    /// Positive bias makes textures blurrier, negative bias makes them sharper.
+    ///
+    pub fn set_texture_lod_bias(&mut self, lod_bias: f32) -> std::result::Result<(), TextureError> {
+        self.renderer.set_texture_lod_bias(lod_bias)
+    }
+
+    /// Statistics of the texture of game objects, including its effective quality tier.
+    pub fn object_texture_stats(&self) -> TextureStats {
+        self.renderer.object_texture_stats()
+    }
+
+    /// Sets color which the window is cleared with before drawing.
+    ///
+    /// Color is converted into encoding of the window surface,
+    /// so it looks the same on surfaces with sRGB and UNORM formats.
+    ///
+    pub fn set_clear_color(&mut self, clear_color: Color) {
+        self.renderer.set_clear_color(clear_color)
+    }
+
+    /// Replaces the chain of effects which the scene is post processed with.
+    ///
+    /// Post processing must be [enabled](crate::config::Config::with_post_processing)
+    /// by configuration of the application. Empty chain renders the scene directly.
+    ///
+    pub fn set_post_chain(
+        &mut self,
+        chain: &PostProcessChain,
+    ) -> std::result::Result<(), PostEffectError> {
+        self.renderer.set_post_chain(chain)
+    }
+
+    /// Replaces parameters of the effect of the post processing chain with provided index.
+    ///
+    /// Parameters are passed by push constants, so they could be changed each frame.
+    ///
+    pub fn set_post_params(
+        &mut self,
+        index: usize,
+        params: [f32; 4],
+    ) -> std::result::Result<(), PostEffectError> {
+        self.renderer.set_post_params(index, params)
+    }
+
+    /// Parameters of the material of game objects.
+    pub fn material_params(&self) -> &ParamBlock {
+        self.renderer.material_params()
+    }
+
+    /// Sets the value of material parameter of game objects with provided name.
+    ///
+    /// Parameters are packed by `std140` rules and uploaded into the uniform buffer
+    /// of each frame, so they could be changed each frame.
+    ///
+    pub fn set_material_param<V>(
+        &mut self,
+        name: &str,
+        value: V,
+    ) -> std::result::Result<(), ParamBlockError>
+    where
+        V: ParamValue,
+    {
+        self.renderer.set_material_param(name, value)
+    }
+
+    /// Handle of tint which colors of game objects are multiplied by.
+    ///
+    /// Tint is passed to the shader by push constants, so it could be changed each frame.
+    ///
+    pub fn object_tint(&self) -> ColorHandle {
+        self.renderer.object_tint()
+    }
+
+    /// Registers pass with commands recorded by the user which is executed inside of the frame.
+    ///
+    /// Passes of the same stage are executed in order of registration.
+    /// See [`external`](crate::external) module for synchronization contract.
+    ///
+    pub fn register_external_pass(&mut self, desc: ExternalPassDesc) -> ExternalPassId {
+        self.renderer.register_external_pass(desc)
+    }
+
+    /// Unregisters external pass, returns `false` if it was not registered.
+    pub fn unregister_external_pass(&mut self, id: ExternalPassId) -> bool {
+        self.renderer.unregister_external_pass(id)
+    }
+
+    /// Schedules picking of the game object under the pixel of the window.
+    ///
+    /// Coordinates are in physical pixels of the window.
+    ///
+    pub fn pick(&mut self, x: u32, y: u32) -> PickTicket {
+        self.renderer.pick(x, y)
+    }
+
+    /// Retrieves the result of the pick request, if it is ready.
+    pub fn pick_result(&mut self, ticket: PickTicket) -> PickResult {
+        self.renderer.pick_result(ticket)
+    }
+
+    /// Handle of model transform which could be updated by the simulation.
+    ///
+    /// Handle could be moved into the callback of [`run`](Self::run):
+    /// states pushed on [fixed updates](MyEvent::FixedUpdate)
+    /// are interpolated automatically on rendering.
+    ///
+    pub fn model_transform(&self) -> TransformHandle {
+        self.transform.clone()
+    }
+
+    /// Checks if frames should be rendered according to activity of the window.
+    ///
+    /// Focus of any additional window counts as focus of the main window,
+    /// because they present the same frames.
+    ///
+    fn should_render(&self) -> bool {
+        let window_focused = self
+            .windows
+            .values()
+            .any(|state| state.activity.is_focused());
+        let render_when_unfocused = self.config.render_when_unfocused() || window_focused;
+        self.activity.should_render(render_when_unfocused)
+    }
+
+    /// Reports the last rendered frame if its CPU time is a spike.
+    fn detect_cpu_spike(&mut self, frame_time: Duration) -> Option<SpikeReport> {
+        let median = self.spike_detector.as_mut()?.observe(frame_time)?;
+        Some(SpikeReport {
+            frame: self.renderer.rendered_frame(),
+            source: SpikeSource::Cpu,
+            frame_time,
+            median,
+            counters: self.renderer.frame_counters(),
+        })
+    }
+
+    /// Reports the frame whose GPU time was read while rendering the last frame
+    /// if this time is a spike.
+    ///
+    /// GPU time belongs to the frame which was previously rendered into the same image,
+    /// so that frame is reported with its own counters.
+    ///
+    fn detect_gpu_spike(&mut self) -> Option<SpikeReport> {
+        let gpu_time = self.renderer.measured_gpu_time()?;
+        let detector = self.gpu_spike_detector.as_mut()?;
+        let median = detector.observe(gpu_time.duration)?;
+        Some(SpikeReport {
+            frame: gpu_time.frame,
+            source: SpikeSource::Gpu,
+            frame_time: gpu_time.duration,
+            median,
+            counters: gpu_time.counters,
+        })
+    }
+
+    /// Runs the event loop of the engine, calling provided callback for each event.
+    ///
+    /// Use [`run_with_control`](Self::run_with_control) to shut down the engine from the callback.
+    ///
+    pub fn run(self, mut callback: impl FnMut(MyEvent) + 'static) -> ! {
+        self.run_with_control(move |event, _| callback(event))
+    }
+
+    /// Runs the event loop of the engine, calling provided callback for each event
+    /// with the handle to control the event loop.
+    pub fn run_with_control(
+        mut self,
+        mut user_callback: impl FnMut(MyEvent, &mut LoopControl) + 'static,
+    ) -> ! {
+        let event_loop = self
+            .event_loop
+            .take()
+            .expect("headless application must be run by `run_headless`");
+        let mut loop_control = LoopControl::default();
 
         let mut start_time = Instant::now();
+        let mut last_frame = Instant::now();
         event_loop.run(move |event, _, control_flow| {
             // Have the closure take ownership of `self`.
             // `event_loop.run` never returns, therefore we must do this to ensure
             // the resources are properly cleaned up.
             let _ = &self;
 
-            *control_flow = ControlFlow::Poll;
+            *control_flow = loop_control.control_flow();
+            loop_control.set_frame_stats(self.renderer.frame_stats());
+            let mut callback = |event| user_callback(event, &mut loop_control);
 
             // Take `Platform` object from `self` to workaround about borrow checker.
             let mut egui = self.egui.take().unwrap();
@@ -99,96 +939,190 @@ pub fn run(mut self, mut callback: impl FnMut(MyEvent) + 'static) -> ! {
                 egui.handle_event(&event);
                 egui.update_time(start_time.elapsed().as_secs_f64());
 
-                let window = self.window();
+                let window = self.renderer.window().unwrap();
                 match event {
                     Event::NewEvents(StartCause::Init) => {
                         start_time = Instant::now();
+                        last_frame = start_time;
                         callback(MyEvent::Created);
                         window.set_visible(true);
                     }
+                    // Events of additional windows are reported along with their ids.
+                    Event::WindowEvent { event, window_id }
+                        if self.renderer.has_window(window_id) =>
+                    {
+                        let state = self.windows.entry(window_id).or_default();
+                        let mut events = Vec::new();
+                        match event {
+                            WindowEvent::Resized(size) => {
+                                self.renderer.invalidate_window(window_id);
+                                let size = Size::new(size.width, size.height);
+                                events.push(MyEvent::Resized(size));
+                                events.extend(state.activity.resize(size));
+                            }
+                            WindowEvent::ScaleFactorChanged { .. } => {
+                                self.renderer.invalidate_window(window_id)
+                            }
+                            WindowEvent::CloseRequested => {
+                                self.renderer.destroy_window(window_id);
+                                self.windows.remove(&window_id);
+                                callback(MyEvent::WindowClosed(window_id));
+                            }
+                            event => events.extend(self::input_event(
+                                &mut state.keyboard,
+                                &mut state.activity,
+                                &event,
+                            )),
+                        }
+                        for event in events {
+                            callback(MyEvent::AdditionalWindow {
+                                window: window_id,
+                                event: Box::new(event),
+                            });
+                        }
+                    }
                     Event::WindowEvent { event, window_id } if window_id == window.id() => {
                         match event {
                             WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
                             WindowEvent::Resized(size) => {
-                                if size.width == 0 || size.height == 0 {
-                                    callback(MyEvent::Resized(Size::default()));
+                                let size = Size::new(size.width, size.height);
+                                if let Err(error) = self.renderer.resize(size) {
+                                    log::error!("window resizing error: {}", error);
+                                    *control_flow = ControlFlow::Exit;
                                     return;
                                 }
-                                if let Err(error) = self.renderer.resize() {
+                                callback(MyEvent::Resized(size));
+                                if let Some(event) = self.activity.resize(size) {
+                                    callback(event);
+                                }
+                                // Surface could not be restored on resume if the window was empty.
+                                if self.renderer.is_surface_lost() && !self.activity.is_suspended()
+                                {
+                                    match self.renderer.restore_surface() {
+                                        Ok(true) => callback(MyEvent::SurfaceRestored),
+                                        Ok(false) => (),
+                                        Err(error) => {
+                                            log::error!("surface restoration error: {}", error);
+                                            *control_flow = ControlFlow::Exit;
+                                        }
+                                    }
+                                }
+                            }
+                            WindowEvent::ScaleFactorChanged {
+                                scale_factor,
+                                new_inner_size,
+                            } => {
+                                // Suggested size must be answered synchronously.
+                                let suggested =
+                                    Size::new(new_inner_size.width, new_inner_size.height);
+                                let current = window.inner_size();
+                                let current = Size::new(current.width, current.height);
+                                let size = self
+                                    .config
+                                    .scale_factor_policy()
+                                    .resolve(suggested, current);
+                                new_inner_size.width = size.width;
+                                new_inner_size.height = size.height;
+
+                                let old = std::mem::replace(&mut self.scale_factor, scale_factor);
+                                if let Err(error) = self.renderer.resize(size) {
                                     log::error!("window resizing error: {}", error);
                                     *control_flow = ControlFlow::Exit;
                                     return;
                                 }
-                                let size = (size.width, size.height);
-                                callback(MyEvent::Resized(size.into()));
+                                callback(MyEvent::ScaleFactorChanged {
+                                    old,
+                                    new: scale_factor,
+                                    new_size: size,
+                                });
+                                callback(MyEvent::Resized(size));
                             }
-                            WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
-                                let size = *new_inner_size;
-                                if size.width == 0 || size.height == 0 {
-                                    callback(MyEvent::Resized(Size::default()));
-                                    return;
+                            event => {
+                                let keyboard = &mut self.keyboard;
+                                let activity = &mut self.activity;
+                                if let Some(event) = self::input_event(keyboard, activity, &event) {
+                                    callback(event);
                                 }
-                                if let Err(error) = self.renderer.resize() {
-                                    log::error!("window resizing error: {}", error);
+                            }
+                        }
+                    }
+                    Event::DeviceEvent {
+                        event: DeviceEvent::MouseMotion { delta: (dx, dy) },
+                        ..
+                    } => callback(MyEvent::MouseDelta { dx, dy }),
+                    Event::Suspended => {
+                        callback(self.activity.suspend(true));
+                        // Native window is destroyed on suspension on Android, so is its surface.
+                        if cfg!(target_os = "android") {
+                            match self.renderer.suspend_surface() {
+                                Ok(true) => callback(MyEvent::SurfaceLost),
+                                Ok(false) => (),
+                                Err(error) => {
+                                    log::error!("surface suspension error: {}", error);
                                     *control_flow = ControlFlow::Exit;
-                                    return;
                                 }
-                                let size = (size.width, size.height);
-                                callback(MyEvent::Resized(size.into()));
                             }
-                            _ => (),
                         }
                     }
+                    Event::Resumed => {
+                        match self.renderer.restore_surface() {
+                            Ok(true) => callback(MyEvent::SurfaceRestored),
+                            Ok(false) => (),
+                            Err(error) => {
+                                log::error!("surface restoration error: {}", error);
+                                *control_flow = ControlFlow::Exit;
+                                return;
+                            }
+                        }
+                        callback(self.activity.suspend(false));
+                    }
                     Event::MainEventsCleared => {
                         let size = window.inner_size();
-                        if size.width == 0 || size.height == 0 {
+                        if size.width == 0 || size.height == 0 || !self.should_render() {
                             return;
                         }
                         window.request_redraw();
                     }
                     Event::RedrawRequested(window_id) if window_id == window.id() => {
                         let size = window.inner_size();
-                        if size.width == 0 || size.height == 0 {
+                        if size.width == 0 || size.height == 0 || !self.should_render() {
                             return;
                         }
                         let frame_start = Instant::now();
+                        let frame_delta =
+                            timestep::clamp_frame_delta(frame_start.duration_since(last_frame));
+                        last_frame = frame_start;
+                        let size = Size::new(size.width, size.height);
+                        let elapsed = frame_start.duration_since(start_time);
+                        let ui = self.prepare_frame(
+                            &mut egui,
+                            frame_delta,
+                            elapsed,
+                            size,
+                            &mut callback,
+                        );
 
-                        egui.begin_frame();
-                        let context = egui.context();
-                        callback(MyEvent::UI(context.clone()));
-                        let (_output, shapes) = egui.end_frame(Some(window));
-                        let meshes = context.tessellate(shapes);
-                        let texture = context.texture();
-
-                        if let Err(error) = self.renderer.render(Some((meshes, texture))) {
+                        if let Err(error) = self.renderer.render(Some(ui)) {
                             log::error!("rendering error: {}", error);
                             *control_flow = ControlFlow::Exit;
                             return;
                         }
+                        // Additional windows which failed to present were destroyed by renderer.
+                        let renderer = &self.renderer;
+                        self.windows.retain(|&id, _| renderer.has_window(id));
                         let delta_time = Instant::now().duration_since(frame_start);
-                        callback(MyEvent::Update(delta_time));
-
-                        let ubo = {
-                            let duration = Instant::now().duration_since(start_time);
-                            let elapsed = duration.as_millis() as f32;
-
-                            use ultraviolet::projection::perspective_vk as perspective;
-                            let projection = perspective(
-                                45f32.to_radians(),
-                                (size.width as f32) / (size.height as f32),
-                                1.0,
-                                10.0,
-                            );
-                            let model = Mat4::from_rotation_z(elapsed * 0.1f32.to_radians());
-                            let view = Mat4::look_at(
-                                Vec3::new(2.0, 2.0, 2.0),
-                                Vec3::zero(),
-                                Vec3::unit_z(),
-                            );
-                            CameraUBO::new(projection, model, view)
-                        };
-                        self.renderer.set_camera_ubo(ubo);
+                        let cpu_spike = self.detect_cpu_spike(delta_time);
+                        let gpu_spike = self.detect_gpu_spike();
+                        for report in cpu_spike.into_iter().chain(gpu_spike) {
+                            log::warn!("frame spike: {}", report);
+                            callback(MyEvent::FrameSpike(report));
+                        }
+                        self.frame_limiter.wait();
                     }
+                    Event::UserEvent(LoopEvent::Failure(failure)) => {
+                        callback(self::failure_event(failure))
+                    }
+                    Event::UserEvent(LoopEvent::User(event)) => callback(MyEvent::User(event)),
                     Event::LoopDestroyed => {
                         callback(MyEvent::Destroyed);
                         log::info!("closing this application");
@@ -197,6 +1131,12 @@ pub fn run(mut self, mut callback: impl FnMut(MyEvent) + 'static) -> ! {
                 }
             };
             action();
+            if loop_control.is_exiting() {
+                *control_flow = ControlFlow::Exit;
+            } else if *control_flow == ControlFlow::Poll && !self.should_render() {
+                // Nothing is rendered, so wait for the window to become active again.
+                *control_flow = ControlFlow::Wait;
+            }
 
             // Assign `Platform` object back to `self`.
             self.egui = Some(egui);
@@ -204,6 +1144,193 @@ pub fn run(mut self, mut callback: impl FnMut(MyEvent) + 'static) -> ! {
     }
 }
 
+impl Application {
+    /// Runs the engine without an event loop, rendering provided count of frames offscreen.
+    ///
+    /// The callback receives the same events as in [`run_with_control`](Self::run_with_control),
+    /// except events of the window and input devices. Each frame lasts exactly one
+    /// [fixed timestep](Config::with_fixed_timestep), so results do not depend on the speed
+    /// of the machine. User events posted by the [event proxy](Self::event_proxy)
+    /// are delivered before each frame. Rendering stops early if the callback requests exit,
+    /// but at least one frame is rendered.
+    ///
+    /// Returns pixels of the last rendered frame: tightly packed rows of RGBA values
+    /// (in sRGB encoding) of [output size](Self::output_size).
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the application was not created by [`init_headless`].
+    ///
+    pub fn run_headless(
+        mut self,
+        frames: u64,
+        mut user_callback: impl FnMut(MyEvent, &mut LoopControl),
+    ) -> std::result::Result<Vec<u8>, RenderError> {
+        let events = self
+            .headless_events
+            .take()
+            .expect("windowed application must be run by `run` or `run_with_control`");
+        let mut egui = self.egui.take().unwrap();
+        let mut loop_control = LoopControl::default();
+        let frame_delta = self.config.fixed_timestep();
+        let size = self.renderer.output_size();
+
+        user_callback(MyEvent::Created, &mut loop_control);
+        let mut elapsed = Duration::ZERO;
+        let mut pixels = Ok(Vec::new());
+        for frame in 0..frames.max(1) {
+            for event in events.try_iter() {
+                let event = match event {
+                    LoopEvent::Failure(failure) => self::failure_event(failure),
+                    LoopEvent::User(event) => MyEvent::User(event),
+                };
+                user_callback(event, &mut loop_control);
+            }
+            egui.update_time(elapsed.as_secs_f64());
+            loop_control.set_frame_stats(self.renderer.frame_stats());
+            let ui = {
+                let mut callback = |event| user_callback(event, &mut loop_control);
+                self.prepare_frame(&mut egui, frame_delta, elapsed, size, &mut callback)
+            };
+            elapsed += frame_delta;
+
+            // Pixels are read back only from the last frame, which waits for the device.
+            let last = frame + 1 >= frames || loop_control.is_exiting();
+            if !last {
+                if let Err(error) = self.renderer.render(Some(ui)) {
+                    pixels = Err(error);
+                    break;
+                }
+                continue;
+            }
+            pixels = self.renderer.render_frame_to_buffer(Some(ui));
+            break;
+        }
+        if let Err(error) = &pixels {
+            log::error!("rendering error: {}", error);
+        }
+        user_callback(MyEvent::Destroyed, &mut loop_control);
+        log::info!("closing this application");
+        pixels
+    }
+
+    /// Delivers events of the new frame to the callback and prepares UI to be rendered.
+    fn prepare_frame(
+        &mut self,
+        egui: &mut Platform,
+        frame_delta: DeltaTime,
+        elapsed: Duration,
+        size: Size,
+        callback: &mut impl FnMut(MyEvent),
+    ) -> (Vec<ClippedMesh>, Arc<Texture>) {
+        callback(MyEvent::Update(frame_delta));
+
+        let info = self
+            .timestep
+            .advance(frame_delta, |step| callback(MyEvent::FixedUpdate(step)));
+        callback(MyEvent::Render(info));
+
+        #[cfg(feature = "hot-reload")]
+        for failure in self.renderer.reload_shaders() {
+            callback(self::failure_event(failure));
+        }
+
+        let ubo = {
+            let elapsed = elapsed.as_millis() as f32;
+
+            use ultraviolet::projection::perspective_vk as perspective;
+            let projection = perspective(
+                45f32.to_radians(),
+                (size.width as f32) / (size.height as f32),
+                1.0,
+                10.0,
+            );
+            let model = match self.transform.get() {
+                Some(transform) => transform.matrix(info.alpha),
+                None => Mat4::from_rotation_z(elapsed * 0.1f32.to_radians()),
+            };
+            let view = Mat4::look_at(Vec3::new(2.0, 2.0, 2.0), Vec3::zero(), Vec3::unit_z());
+            CameraUBO::new(projection, model, view)
+        };
+        self.renderer.set_camera_ubo(ubo);
+
+        egui.begin_frame();
+        let context = egui.context();
+        callback(MyEvent::UI(context.clone()));
+        let (_output, shapes) = egui.end_frame(self.renderer.window());
+        let meshes = context.tessellate(shapes);
+        (meshes, context.texture())
+    }
+}
+
+/// Input and activity state of additional window.
+#[derive(Debug)]
+struct WindowState {
+    keyboard: Keyboard,
+    activity: WindowActivity,
+}
+
+impl Default for WindowState {
+    fn default() -> Self {
+        // Additional window is not focused until the system reports it.
+        let mut activity = WindowActivity::default();
+        activity.focus(false);
+        Self {
+            keyboard: Keyboard::default(),
+            activity,
+        }
+    }
+}
+
+/// Translates input or focus event of the window into event of the main callback,
+/// updating state of keyboard and activity of this window.
+fn input_event(
+    keyboard: &mut Keyboard,
+    activity: &mut WindowActivity,
+    event: &WindowEvent,
+) -> Option<MyEvent> {
+    let event = match *event {
+        WindowEvent::KeyboardInput { input, .. } => {
+            let key = KeyCode::from_input(&input);
+            keyboard.input(key, input.state)
+        }
+        WindowEvent::ModifiersChanged(state) => keyboard.modifiers_changed(state.into()),
+        WindowEvent::CursorMoved { position, .. } => {
+            MyEvent::CursorMoved(Position::new(position.x, position.y))
+        }
+        WindowEvent::CursorEntered { .. } => MyEvent::CursorEntered,
+        WindowEvent::CursorLeft { .. } => MyEvent::CursorLeft,
+        WindowEvent::MouseInput { state, button, .. } => {
+            input::mouse_button_event(button.into(), state)
+        }
+        WindowEvent::MouseWheel { delta, .. } => MyEvent::MouseWheel(delta.into()),
+        // Keys which are released outside of the window are never reported.
+        WindowEvent::Focused(focused) => {
+            if !focused {
+                keyboard.reset();
+            }
+            activity.focus(focused)
+        }
+        _ => return None,
+    };
+    Some(event)
+}
+
+/// Converts failure of the asset into event of the main callback.
+fn failure_event(failure: AssetFailure) -> MyEvent {
+    match failure.kind {
+        FailureKind::ShaderCompile { stage } => MyEvent::ShaderCompileFailed {
+            name: failure.name,
+            stage,
+            diagnostics: failure.diagnostics,
+        },
+        FailureKind::PipelineBuild => MyEvent::PipelineBuildFailed {
+            material: failure.name,
+            error: failure.diagnostics,
+        },
+    }
+}
+
 /// Creates a unique [`Application`] instance.
 /// If application instance was created earlier, function call will return an error.
 ///
@@ -232,5 +1359,31 @@ pub fn init(config: Config) -> Result<Application> {
     if initialized {
         return Err(AppCreationError::Initialized);
     }
-    Application::new(config)
+
+    Application::new(self::with_env_overrides(config))
+}
+
+/// Creates an [`Application`] which renders offscreen into the image of provided size,
+/// without a window and an event loop.
+///
+/// Such application should be run by [`Application::run_headless`].
+/// Unlike [`init`], multiple headless applications could be created on any thread.
+///
+/// # Errors
+///
+/// An error is returned if configuration is invalid or graphics initialization fails.
+///
+pub fn init_headless(config: Config, size: Size) -> Result<Application> {
+    Application::new_headless(self::with_env_overrides(config), size)
+}
+
+/// Applies environment overrides to the configuration if they are enabled.
+fn with_env_overrides(mut config: Config) -> Config {
+    if config.env_overrides() {
+        let applied = config.apply_env_overrides();
+        if !applied.is_empty() {
+            log::info!("configuration overridden by environment: {:?}", applied);
+        }
+    }
+    config
 }
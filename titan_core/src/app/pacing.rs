@@ -0,0 +1,93 @@
+//! Frame rate limiting of game engine.
+//!
+//! Frames are paced against a schedule of deadlines on the monotonic clock,
+//! so time which was spent rendering is accounted for and small overshoots
+//! of one frame are compensated by the next one.
+//!
+//! Waiting is hybrid: the thread sleeps until shortly before the deadline,
+//! then spins for the rest of time, because sleep of the system could overshoot
+//! by the granularity of its timer (up to several milliseconds on Windows).
+
+use std::time::{Duration, Instant};
+
+mod tests;
+
+/// Time before the deadline which is spent spinning instead of sleeping.
+pub const SPIN_MARGIN: Duration = if cfg!(windows) {
+    Duration::from_millis(2)
+} else {
+    Duration::from_micros(500)
+};
+
+/// Limiter of frame rate to the target count of frames per second.
+#[derive(Debug, Clone)]
+pub struct FrameLimiter {
+    interval: Option<Duration>,
+    deadline: Option<Instant>,
+}
+
+impl FrameLimiter {
+    /// Creates new limiter with provided target frame rate.
+    ///
+    /// `None` or zero means uncapped frame rate.
+    ///
+    pub fn new(target_fps: Option<u32>) -> Self {
+        let mut limiter = Self {
+            interval: None,
+            deadline: None,
+        };
+        limiter.set_target_fps(target_fps);
+        limiter
+    }
+
+    /// Target count of frames per second, if frame rate is limited.
+    pub fn target_fps(&self) -> Option<u32> {
+        self.interval
+            .map(|interval| (1.0 / interval.as_secs_f64()).round() as u32)
+    }
+
+    /// Sets target count of frames per second, `None` or zero means uncapped frame rate.
+    pub fn set_target_fps(&mut self, target_fps: Option<u32>) {
+        self.interval = target_fps
+            .filter(|&fps| fps > 0)
+            .map(|fps| Duration::from_secs(1) / fps);
+        self.deadline = None;
+    }
+
+    /// Computes the deadline of the current frame and schedules the next one.
+    ///
+    /// If the frame is late by more than one interval, the schedule is restarted from now
+    /// instead of rendering a burst of frames to catch up.
+    ///
+    pub fn next_deadline(&mut self, now: Instant) -> Option<Instant> {
+        let interval = self.interval?;
+        let deadline = match self.deadline {
+            Some(deadline) if now.saturating_duration_since(deadline) < interval => deadline,
+            _ => now,
+        };
+        self.deadline = Some(deadline + interval);
+        Some(deadline)
+    }
+
+    /// Waits until the deadline of the current frame, if frame rate is limited.
+    pub fn wait(&mut self) {
+        let deadline = match self.next_deadline(Instant::now()) {
+            Some(deadline) => deadline,
+            None => return,
+        };
+        let (sleep, _) = self::split_wait(Instant::now(), deadline);
+        if !sleep.is_zero() {
+            std::thread::sleep(sleep);
+        }
+        while Instant::now() < deadline {
+            std::hint::spin_loop();
+        }
+    }
+}
+
+/// Splits time until the deadline into the parts which are slept and spun.
+pub fn split_wait(now: Instant, deadline: Instant) -> (Duration, Duration) {
+    let remaining = deadline.saturating_duration_since(now);
+    let sleep = remaining.saturating_sub(SPIN_MARGIN);
+    (sleep, remaining - sleep)
+}
@@ -0,0 +1,53 @@
+#![cfg(test)]
+
+use std::time::{Duration, Instant};
+
+use super::{split_wait, FrameLimiter, SPIN_MARGIN};
+
+#[test]
+fn uncapped_limiter_never_waits() {
+    let mut limiter = FrameLimiter::new(None);
+    assert_eq!(limiter.next_deadline(Instant::now()), None);
+    let mut limiter = FrameLimiter::new(Some(0));
+    assert_eq!(limiter.target_fps(), None);
+    assert_eq!(limiter.next_deadline(Instant::now()), None);
+}
+
+#[test]
+fn deadlines_follow_target_interval() {
+    let mut limiter = FrameLimiter::new(Some(50));
+    assert_eq!(limiter.target_fps(), Some(50));
+    let interval = Duration::from_millis(20);
+    let start = Instant::now();
+
+    assert_eq!(limiter.next_deadline(start), Some(start));
+    // Time spent rendering is accounted for: deadline does not depend on when frame ends.
+    let now = start + Duration::from_millis(5);
+    assert_eq!(limiter.next_deadline(now), Some(start + interval));
+    // Small overshoot is compensated by the next frame.
+    let now = start + Duration::from_millis(43);
+    assert_eq!(limiter.next_deadline(now), Some(start + interval * 2));
+}
+
+#[test]
+fn long_frame_restarts_schedule() {
+    let mut limiter = FrameLimiter::new(Some(100));
+    let start = Instant::now();
+    limiter.next_deadline(start);
+    let now = start + Duration::from_millis(100);
+    assert_eq!(limiter.next_deadline(now), Some(now));
+    assert_eq!(
+        limiter.next_deadline(now),
+        Some(now + Duration::from_millis(10))
+    );
+}
+
+#[test]
+fn wait_spins_only_near_deadline() {
+    let now = Instant::now();
+    let deadline = now + Duration::from_millis(10);
+    let (sleep, spin) = split_wait(now, deadline);
+    assert_eq!(spin, SPIN_MARGIN);
+    assert_eq!(sleep + spin, Duration::from_millis(10));
+    assert_eq!(split_wait(deadline, now), (Duration::ZERO, Duration::ZERO));
+}
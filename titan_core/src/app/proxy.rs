@@ -0,0 +1,106 @@
+//! Posting of events into the event loop of game engine from other threads.
+
+use std::any::Any;
+use std::fmt;
+use std::sync::mpsc::{self, Receiver, Sender};
+
+use thiserror::Error;
+use winit::event_loop::EventLoopProxy;
+
+use crate::failure::AssetFailure;
+
+mod tests;
+
+/// Event which is posted by the user and delivered to the callback
+/// as [`User`](crate::window::Event::User) event.
+///
+/// Use [`downcast`](Box::downcast) to get the value of the event back.
+///
+pub type UserEvent = Box<dyn Any + Send>;
+
+/// Event which is delivered to the event loop of the engine from outside.
+pub(crate) enum LoopEvent {
+    /// Asset has failed to load or build.
+    Failure(AssetFailure),
+    /// Event which was posted by the user.
+    User(UserEvent),
+}
+
+/// Error that is returned when the event is posted after the event loop was destroyed.
+///
+/// Event which could not be delivered is returned back.
+///
+#[derive(Error)]
+#[error("event loop is closed")]
+pub struct EventLoopClosed(pub UserEvent);
+
+impl fmt::Debug for EventLoopClosed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("EventLoopClosed").finish_non_exhaustive()
+    }
+}
+
+/// Destination of events which are posted by the proxy.
+#[derive(Clone)]
+enum Sink {
+    /// Event loop of the window.
+    Loop(EventLoopProxy<LoopEvent>),
+    /// Channel which is drained by the headless application before each frame.
+    Channel(Sender<LoopEvent>),
+}
+
+/// Cloneable handle which could be sent to other threads to post events into the event loop.
+///
+/// Posting the event wakes the event loop up, even if it [waits](super::control::LoopControl::set_wait)
+/// for new events.
+///
+#[derive(Clone)]
+pub struct EventProxy {
+    sink: Sink,
+}
+
+impl EventProxy {
+    pub(crate) fn new(proxy: EventLoopProxy<LoopEvent>) -> Self {
+        let sink = Sink::Loop(proxy);
+        Self { sink }
+    }
+
+    /// Creates proxy for the application without an event loop,
+    /// together with the receiver of posted events.
+    pub(crate) fn channel() -> (Self, Receiver<LoopEvent>) {
+        let (sender, receiver) = mpsc::channel();
+        let sink = Sink::Channel(sender);
+        (Self { sink }, receiver)
+    }
+
+    /// Posts event to the destination, returning it back if the destination is closed.
+    fn post(&self, event: LoopEvent) -> Result<(), LoopEvent> {
+        match &self.sink {
+            Sink::Loop(proxy) => proxy.send_event(event).map_err(|closed| closed.0),
+            Sink::Channel(sender) => sender.send(event).map_err(|error| error.0),
+        }
+    }
+
+    /// Posts provided event into the event loop.
+    ///
+    /// Returns an error with the event if the event loop was already destroyed.
+    ///
+    pub fn send(&self, event: impl Any + Send) -> Result<(), EventLoopClosed> {
+        self.send_boxed(Box::new(event))
+    }
+
+    /// Posts already boxed event into the event loop.
+    pub fn send_boxed(&self, event: UserEvent) -> Result<(), EventLoopClosed> {
+        self.post(LoopEvent::User(event))
+            .map_err(|closed| match closed {
+                LoopEvent::User(event) => EventLoopClosed(event),
+                LoopEvent::Failure(_) => unreachable!("only user events are sent by the proxy"),
+            })
+    }
+
+    /// Posts failure of the asset into the event loop.
+    pub(crate) fn send_failure(&self, failure: AssetFailure) {
+        // Event loop is only closed when the application is destroyed.
+        let _ = self.post(LoopEvent::Failure(failure));
+    }
+}
@@ -0,0 +1,33 @@
+#![cfg(test)]
+
+use super::{EventLoopClosed, EventProxy, LoopEvent};
+
+#[test]
+fn proxy_could_be_sent_to_other_threads() {
+    fn assert_send<T: Send + Clone>() {}
+
+    assert_send::<EventProxy>();
+}
+
+#[test]
+fn closed_loop_returns_event_back() {
+    let EventLoopClosed(event) = EventLoopClosed(Box::new(42u32));
+    assert_eq!(event.downcast::<u32>().ok().map(|event| *event), Some(42));
+    let error = EventLoopClosed(Box::new(()));
+    assert_eq!(error.to_string(), "event loop is closed");
+}
+
+#[test]
+fn channel_proxy_delivers_events_until_receiver_is_dropped() {
+    let (proxy, receiver) = EventProxy::channel();
+    proxy.send(7u8).unwrap();
+    let event = match receiver.try_recv() {
+        Ok(LoopEvent::User(event)) => event.downcast::<u8>().ok().map(|event| *event),
+        _ => None,
+    };
+    assert_eq!(event, Some(7));
+
+    drop(receiver);
+    let EventLoopClosed(event) = proxy.send(8u8).unwrap_err();
+    assert_eq!(event.downcast::<u8>().ok().map(|event| *event), Some(8));
+}
@@ -0,0 +1,161 @@
+//! Detection of spikes of frame time of game engine.
+//!
+//! Each frame is compared with the rolling median of recent frames,
+//! separately for CPU and GPU time, and frames which are much longer than the median
+//! are reported along with the counters of the work done in that frame.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::Duration;
+
+use vulkano::DeviceSize;
+
+mod tests;
+
+/// Default count of recent frames which the median is computed from.
+pub const DEFAULT_WINDOW: usize = 120;
+
+/// Default count of frames which must be observed before spikes are detected.
+pub const DEFAULT_MIN_SAMPLES: usize = 30;
+
+/// Counters of the work which was done by the renderer in one frame.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct FrameCounters {
+    /// Count of draw calls which were recorded by the engine, excluding external passes.
+    pub draw_calls: u32,
+    /// Count of bytes which were uploaded to the device for this frame.
+    pub upload_bytes: DeviceSize,
+    /// Count of callbacks of retired frames which were invoked in this frame.
+    pub retired_callbacks: usize,
+    /// Count of draw calls which used the error material because their pipeline has failed.
+    pub fallback_draws: u32,
+}
+
+/// Source of the time which spiked.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum SpikeSource {
+    /// CPU time of the frame.
+    Cpu,
+    /// GPU time of the frame.
+    ///
+    /// GPU time is read without waiting for the device,
+    /// so it is reported a few frames after the frame it was measured in,
+    /// but with the number and counters of that frame.
+    ///
+    Gpu,
+}
+
+impl fmt::Display for SpikeSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpikeSource::Cpu => f.write_str("cpu"),
+            SpikeSource::Gpu => f.write_str("gpu"),
+        }
+    }
+}
+
+/// Report about the frame which took much longer than recent frames.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SpikeReport {
+    /// Number of the frame.
+    pub frame: u64,
+    /// Source of the time which spiked.
+    pub source: SpikeSource,
+    /// Time of the frame measured on the source.
+    pub frame_time: Duration,
+    /// Rolling median of time of recent frames measured on the same source.
+    pub median: Duration,
+    /// Counters of the work which was done in the frame.
+    pub counters: FrameCounters,
+}
+
+impl SpikeReport {
+    /// How many times the frame is longer than the median.
+    pub fn ratio(&self) -> f32 {
+        (self.frame_time.as_secs_f64() / self.median.as_secs_f64()) as f32
+    }
+}
+
+impl fmt::Display for SpikeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "frame={} source={} time={:?} median={:?} ratio={:.1} draw_calls={} upload_bytes={} retired_callbacks={} fallback_draws={}",
+            self.frame,
+            self.source,
+            self.frame_time,
+            self.median,
+            self.ratio(),
+            self.counters.draw_calls,
+            self.counters.upload_bytes,
+            self.counters.retired_callbacks,
+            self.counters.fallback_draws,
+        )
+    }
+}
+
+/// Detector of frames which are longer than provided multiple of the rolling median.
+#[derive(Debug, Clone)]
+pub struct SpikeDetector {
+    threshold: f32,
+    window: usize,
+    min_samples: usize,
+    frame_times: VecDeque<Duration>,
+}
+
+impl SpikeDetector {
+    /// Creates new detector with provided multiple of the median.
+    pub fn new(threshold: f32) -> Self {
+        Self {
+            threshold,
+            window: DEFAULT_WINDOW,
+            min_samples: DEFAULT_MIN_SAMPLES,
+            frame_times: VecDeque::with_capacity(DEFAULT_WINDOW),
+        }
+    }
+
+    /// Sets count of recent frames which the median is computed from.
+    pub fn with_window(mut self, window: usize) -> Self {
+        self.window = window.max(1);
+        self.min_samples = self.min_samples.min(self.window);
+        self
+    }
+
+    /// Sets count of frames which must be observed before spikes are detected.
+    pub fn with_min_samples(mut self, min_samples: usize) -> Self {
+        self.min_samples = min_samples.clamp(1, self.window);
+        self
+    }
+
+    /// Multiple of the median which frame must exceed to be a spike.
+    pub fn threshold(&self) -> f32 {
+        self.threshold
+    }
+
+    /// Rolling median of recent frames, if any frame was observed.
+    pub fn median(&self) -> Option<Duration> {
+        let mut frame_times: Vec<_> = self.frame_times.iter().copied().collect();
+        frame_times.sort_unstable();
+        frame_times.get(frame_times.len() / 2).copied()
+    }
+
+    /// Observes time of the next frame.
+    ///
+    /// Returns rolling median of previous frames if provided frame is a spike.
+    /// Spikes are added into the window too, so a lasting slowdown becomes the new normal.
+    ///
+    pub fn observe(&mut self, frame_time: Duration) -> Option<Duration> {
+        let spike = match self.median() {
+            Some(median) if self.frame_times.len() >= self.min_samples => {
+                let limit = median.as_secs_f64() * self.threshold as f64;
+                (frame_time.as_secs_f64() > limit).then_some(median)
+            }
+            _ => None,
+        };
+        if self.frame_times.len() == self.window {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(frame_time);
+        spike
+    }
+}
@@ -0,0 +1,62 @@
+#![cfg(test)]
+
+use std::time::Duration;
+
+use super::{FrameCounters, SpikeDetector, SpikeReport, SpikeSource};
+
+const fn ms(millis: u64) -> Duration {
+    Duration::from_millis(millis)
+}
+
+#[test]
+fn no_spikes_before_min_samples() {
+    let mut detector = SpikeDetector::new(2.0).with_min_samples(3);
+    assert_eq!(detector.observe(ms(10)), None);
+    assert_eq!(detector.observe(ms(10)), None);
+    assert_eq!(detector.observe(ms(80)), None);
+    assert_eq!(detector.observe(ms(80)), Some(ms(10)));
+}
+
+#[test]
+fn spike_exceeds_multiple_of_median() {
+    let mut detector = SpikeDetector::new(4.0).with_min_samples(5);
+    for frame_time in [16, 17, 15, 16, 50, 16] {
+        assert_eq!(detector.observe(ms(frame_time)), None);
+    }
+    assert_eq!(detector.median(), Some(ms(16)));
+    assert_eq!(detector.observe(ms(64)), None);
+    assert_eq!(detector.observe(ms(80)), Some(ms(16)));
+}
+
+#[test]
+fn lasting_slowdown_becomes_new_normal() {
+    let mut detector = SpikeDetector::new(2.0).with_window(4).with_min_samples(4);
+    for _ in 0..4 {
+        detector.observe(ms(10));
+    }
+    assert_eq!(detector.observe(ms(30)), Some(ms(10)));
+    assert_eq!(detector.observe(ms(30)), Some(ms(10)));
+    assert_eq!(detector.observe(ms(30)), None);
+    assert_eq!(detector.median(), Some(ms(30)));
+}
+
+#[test]
+fn report_is_single_record() {
+    let report = SpikeReport {
+        frame: 42,
+        source: SpikeSource::Gpu,
+        frame_time: ms(80),
+        median: ms(16),
+        counters: FrameCounters {
+            draw_calls: 3,
+            upload_bytes: 1024,
+            retired_callbacks: 2,
+            fallback_draws: 1,
+        },
+    };
+    assert_eq!(report.ratio(), 5.0);
+    assert_eq!(
+        report.to_string(),
+        "frame=42 source=gpu time=80ms median=16ms ratio=5.0 draw_calls=3 upload_bytes=1024 retired_callbacks=2 fallback_draws=1",
+    );
+}
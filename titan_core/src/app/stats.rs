@@ -0,0 +1,125 @@
+//! Statistics of frames of game engine: frame time, FPS, GPU time, samples of the nominated draw
+//! and time reclaimed by the present thread.
+//!
+//! Statistics are computed over a rolling window of recent frames
+//! in constant time per frame, so they could be left enabled in release builds.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+mod tests;
+
+/// Default count of recent frames which the average is computed from.
+pub const DEFAULT_STATS_WINDOW: usize = 60;
+
+/// Statistics of the last rendered frame.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct FrameStats {
+    /// Count of frames which were rendered so far.
+    pub frame_count: u64,
+    /// CPU time between the starts of the last two frames.
+    pub frame_time: Duration,
+    /// Average CPU frame time over the window of recent frames.
+    pub average_frame_time: Duration,
+    /// Frames per second computed from the average frame time.
+    pub fps: f32,
+    /// Time which the device spent executing commands of the most recent measured frame,
+    /// or `None` if timestamps are not supported or not measured yet.
+    pub gpu_time: Option<Duration>,
+    /// Count of samples which passed depth and stencil tests in the nominated draw
+    /// of the most recent measured frame, or `None` if no draw was nominated yet.
+    ///
+    /// Draw is nominated by wrapping it into
+    /// [occlusion query](crate::graphics::external::FrameContext::occlusion_query) of the frame.
+    ///
+    pub occlusion_samples: Option<u64>,
+    /// Time which the present thread spent presenting the last frame
+    /// and acquiring the image for the next one,
+    /// or `None` if images are presented from the main thread.
+    pub present_time: Option<Duration>,
+    /// Part of the [present time](Self::present_time) which the main thread did not wait for,
+    /// so it was spent on the work of the next frame instead.
+    pub reclaimed_time: Option<Duration>,
+}
+
+/// Tracker of rolling frame statistics.
+#[derive(Debug, Clone)]
+pub struct FrameStatsTracker {
+    window: usize,
+    frame_times: VecDeque<Duration>,
+    sum: Duration,
+    stats: FrameStats,
+}
+
+impl FrameStatsTracker {
+    /// Creates new tracker with provided count of recent frames which the average is computed from.
+    pub fn new(window: usize) -> Self {
+        let window = window.max(1);
+        Self {
+            window,
+            frame_times: VecDeque::with_capacity(window),
+            sum: Duration::ZERO,
+            stats: FrameStats::default(),
+        }
+    }
+
+    /// Count of recent frames which the average is computed from.
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// Statistics of the last observed frame.
+    pub fn stats(&self) -> FrameStats {
+        self.stats
+    }
+
+    /// Observes CPU time of the next frame.
+    pub fn observe(&mut self, frame_time: Duration) {
+        if self.frame_times.len() == self.window {
+            if let Some(oldest) = self.frame_times.pop_front() {
+                self.sum -= oldest;
+            }
+        }
+        self.frame_times.push_back(frame_time);
+        self.sum += frame_time;
+
+        let average_frame_time = self.sum / self.frame_times.len() as u32;
+        let fps = match average_frame_time.as_secs_f64() {
+            secs if secs > 0.0 => (1.0 / secs) as f32,
+            _ => 0.0,
+        };
+        self.stats = FrameStats {
+            frame_count: self.stats.frame_count + 1,
+            frame_time,
+            average_frame_time,
+            fps,
+            gpu_time: self.stats.gpu_time,
+            occlusion_samples: self.stats.occlusion_samples,
+            present_time: self.stats.present_time,
+            reclaimed_time: self.stats.reclaimed_time,
+        };
+    }
+
+    /// Observes time which the device spent executing commands of the recently finished frame.
+    pub fn observe_gpu_time(&mut self, gpu_time: Duration) {
+        self.stats.gpu_time = Some(gpu_time);
+    }
+
+    /// Observes time of the present thread for the last presented frame,
+    /// with the part of it which the main thread did not wait for.
+    pub fn observe_present_time(&mut self, present_time: Duration, waited: Duration) {
+        self.stats.present_time = Some(present_time);
+        self.stats.reclaimed_time = Some(present_time.saturating_sub(waited));
+    }
+
+    /// Observes count of samples of the nominated draw of the recently finished frame.
+    pub fn observe_occlusion_samples(&mut self, samples: u64) {
+        self.stats.occlusion_samples = Some(samples);
+    }
+}
+
+impl Default for FrameStatsTracker {
+    fn default() -> Self {
+        Self::new(DEFAULT_STATS_WINDOW)
+    }
+}
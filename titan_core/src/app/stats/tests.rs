@@ -0,0 +1,59 @@
+#![cfg(test)]
+
+use std::time::Duration;
+
+use super::FrameStatsTracker;
+
+#[test]
+fn average_is_computed_over_window() {
+    let mut tracker = FrameStatsTracker::new(2);
+    tracker.observe(Duration::from_millis(10));
+    tracker.observe(Duration::from_millis(20));
+    tracker.observe(Duration::from_millis(40));
+
+    let stats = tracker.stats();
+    assert_eq!(stats.frame_count, 3);
+    assert_eq!(stats.frame_time, Duration::from_millis(40));
+    assert_eq!(stats.average_frame_time, Duration::from_millis(30));
+    assert!((stats.fps - 33.333).abs() < 0.01);
+}
+
+#[test]
+fn gpu_time_is_kept_between_frames() {
+    let mut tracker = FrameStatsTracker::default();
+    assert_eq!(tracker.stats().gpu_time, None);
+    tracker.observe_gpu_time(Duration::from_micros(500));
+    tracker.observe(Duration::from_millis(16));
+    assert_eq!(tracker.stats().gpu_time, Some(Duration::from_micros(500)));
+}
+
+#[test]
+fn occlusion_samples_are_kept_between_frames() {
+    let mut tracker = FrameStatsTracker::default();
+    assert_eq!(tracker.stats().occlusion_samples, None);
+    tracker.observe_occlusion_samples(1024);
+    tracker.observe(Duration::from_millis(16));
+    assert_eq!(tracker.stats().occlusion_samples, Some(1024));
+}
+
+#[test]
+fn reclaimed_time_excludes_waiting() {
+    let mut tracker = FrameStatsTracker::default();
+    assert_eq!(tracker.stats().present_time, None);
+    tracker.observe_present_time(Duration::from_millis(12), Duration::from_millis(5));
+    tracker.observe(Duration::from_millis(16));
+    let stats = tracker.stats();
+    assert_eq!(stats.present_time, Some(Duration::from_millis(12)));
+    assert_eq!(stats.reclaimed_time, Some(Duration::from_millis(7)));
+
+    tracker.observe_present_time(Duration::from_millis(3), Duration::from_millis(5));
+    assert_eq!(tracker.stats().reclaimed_time, Some(Duration::ZERO));
+}
+
+#[test]
+fn zero_frame_time_gives_zero_fps() {
+    let mut tracker = FrameStatsTracker::new(0);
+    assert_eq!(tracker.window(), 1);
+    tracker.observe(Duration::ZERO);
+    assert_eq!(tracker.stats().fps, 0.0);
+}
@@ -0,0 +1,122 @@
+//! Fixed timestep utilities for simulation of game engine.
+
+use std::time::Duration;
+
+mod tests;
+
+/// Default maximal count of fixed steps which could be simulated in one frame.
+pub const DEFAULT_MAX_STEPS: u32 = 5;
+
+/// Maximal real duration of one frame which is passed to the game.
+///
+/// Longer frames (the first frame after startup, window dragging, breakpoints)
+/// are clamped, so the game never observes a huge jump of time.
+///
+pub const MAX_FRAME_DELTA: Duration = Duration::from_millis(250);
+
+/// Clamps real duration of the frame to [`MAX_FRAME_DELTA`].
+pub fn clamp_frame_delta(delta: Duration) -> Duration {
+    delta.min(MAX_FRAME_DELTA)
+}
+
+/// Timing information of the frame which is passed to the render-side callback.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct RenderFrameInfo {
+    /// Interpolation factor between previous and current simulation states, in range `[0; 1)`.
+    pub alpha: f32,
+    /// Time of the current simulation state.
+    pub sim_time: Duration,
+    /// Real time which was elapsed since the start of the simulation.
+    pub render_time: Duration,
+    /// Count of fixed steps which were simulated in this frame.
+    pub fixed_steps_this_frame: u32,
+}
+
+/// Clock which splits real time of frames into fixed steps of simulation.
+///
+/// If the frame is too long, at most [`max_steps`](Self::max_steps) are simulated
+/// and the rest of whole steps is dropped, so simulation slows down
+/// instead of spiraling out of control.
+///
+#[derive(Debug, Clone)]
+pub struct FixedTimestep {
+    step: Duration,
+    max_steps: u32,
+    accumulator: Duration,
+    sim_time: Duration,
+    render_time: Duration,
+}
+
+impl FixedTimestep {
+    /// Creates new clock with provided duration of fixed step.
+    ///
+    /// # Panics
+    ///
+    /// Panics if duration of the step is zero.
+    ///
+    pub fn new(step: Duration) -> Self {
+        assert!(!step.is_zero(), "duration of fixed step must not be zero");
+        Self {
+            step,
+            max_steps: DEFAULT_MAX_STEPS,
+            accumulator: Duration::ZERO,
+            sim_time: Duration::ZERO,
+            render_time: Duration::ZERO,
+        }
+    }
+
+    /// Sets maximal count of fixed steps which could be simulated in one frame.
+    pub fn with_max_steps(mut self, max_steps: u32) -> Self {
+        self.max_steps = max_steps.max(1);
+        self
+    }
+
+    /// Duration of fixed step.
+    pub fn step(&self) -> Duration {
+        self.step
+    }
+
+    /// Maximal count of fixed steps which could be simulated in one frame.
+    pub fn max_steps(&self) -> u32 {
+        self.max_steps
+    }
+
+    /// Advances the clock by real duration of the frame.
+    ///
+    /// `on_step` is called once for each fixed step which must be simulated in this frame.
+    ///
+    pub fn advance(
+        &mut self,
+        delta: Duration,
+        mut on_step: impl FnMut(Duration),
+    ) -> RenderFrameInfo {
+        self.render_time += delta;
+        self.accumulator += delta;
+
+        let mut steps = 0;
+        while self.accumulator >= self.step && steps < self.max_steps {
+            self.accumulator -= self.step;
+            self.sim_time += self.step;
+            steps += 1;
+            on_step(self.step);
+        }
+        if self.accumulator >= self.step {
+            // Drop whole steps which cannot be simulated, keeping the fraction of the step.
+            let step = self.step.as_nanos();
+            let remainder = self.accumulator.as_nanos() % step;
+            self.accumulator = Duration::from_nanos(remainder as u64);
+        }
+
+        RenderFrameInfo {
+            alpha: self.alpha(),
+            sim_time: self.sim_time,
+            render_time: self.render_time,
+            fixed_steps_this_frame: steps,
+        }
+    }
+
+    /// Interpolation factor between previous and current simulation states.
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator.as_secs_f64() / self.step.as_secs_f64()) as f32
+    }
+}
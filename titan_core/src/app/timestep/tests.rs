@@ -0,0 +1,74 @@
+#![cfg(test)]
+
+use std::time::Duration;
+
+use super::{clamp_frame_delta, FixedTimestep, MAX_FRAME_DELTA};
+
+const EPSILON: f32 = 1e-4;
+
+/// Virtual clock of the display with constant refresh rate.
+fn frames(rate: u64, count: usize) -> impl Iterator<Item = Duration> {
+    std::iter::repeat_n(Duration::from_nanos(1_000_000_000 / rate), count)
+}
+
+#[test]
+fn alpha_is_continuous_across_frames() {
+    let step = Duration::from_millis(50);
+    let mut timestep = FixedTimestep::new(step);
+    let mut total_steps = 0;
+    let mut previous = 0.0;
+    for delta in frames(144, 1000) {
+        let info = timestep.advance(delta, |_| total_steps += 1);
+        assert!((0.0..1.0).contains(&info.alpha));
+
+        // Alpha moves forward by the fraction of the step and wraps on each fixed step.
+        let expected = previous + (delta.as_secs_f32() / step.as_secs_f32());
+        let expected = expected - info.fixed_steps_this_frame as f32;
+        assert!((info.alpha - expected).abs() < EPSILON);
+        previous = info.alpha;
+
+        let interpolated = info.sim_time.as_secs_f32() + info.alpha * step.as_secs_f32();
+        assert!((interpolated - info.render_time.as_secs_f32()).abs() < EPSILON);
+    }
+    assert_eq!(timestep.step() * total_steps, timestep.sim_time);
+}
+
+#[test]
+fn long_frame_is_clamped() {
+    let step = Duration::from_millis(50);
+    let mut timestep = FixedTimestep::new(step).with_max_steps(3);
+
+    let mut steps = 0;
+    let delta = Duration::from_millis(1025);
+    let info = timestep.advance(delta, |_| steps += 1);
+    assert_eq!(steps, 3);
+    assert_eq!(info.fixed_steps_this_frame, 3);
+    assert_eq!(info.sim_time, step * 3);
+    assert_eq!(info.render_time, delta);
+    // Only the fraction of the step is kept after clamping.
+    assert!((info.alpha - 0.5).abs() < EPSILON);
+
+    // Next frames continue from the kept fraction without bursts of steps.
+    let info = timestep.advance(Duration::from_millis(20), |_| ());
+    assert_eq!(info.fixed_steps_this_frame, 0);
+    assert!((info.alpha - 0.9).abs() < EPSILON);
+    let info = timestep.advance(Duration::from_millis(20), |_| ());
+    assert_eq!(info.fixed_steps_this_frame, 1);
+    assert!((info.alpha - 0.3).abs() < EPSILON);
+}
+
+#[test]
+fn short_frames_do_not_step() {
+    let mut timestep = FixedTimestep::new(Duration::from_millis(50));
+    let info = timestep.advance(Duration::from_millis(10), |_| panic!("unexpected step"));
+    assert_eq!(info.fixed_steps_this_frame, 0);
+    assert_eq!(info.sim_time, Duration::ZERO);
+    assert!((info.alpha - 0.2).abs() < EPSILON);
+}
+
+#[test]
+fn long_frame_delta_is_clamped() {
+    let delta = Duration::from_millis(16);
+    assert_eq!(clamp_frame_delta(delta), delta);
+    assert_eq!(clamp_frame_delta(Duration::from_secs(3)), MAX_FRAME_DELTA);
+}
@@ -0,0 +1,16 @@
+//! Error types of asset utilities.
+
+use std::io;
+use std::path::PathBuf;
+
+use thiserror::Error;
+
+/// Error that can happen when loading an asset.
+#[derive(Debug, Error)]
+pub enum AssetError {
+    #[error("asset {path:?} was not found, tried: {tried:?}")]
+    NotFound { path: PathBuf, tried: Vec<PathBuf> },
+
+    #[error("failed to read asset {path:?}: {source}")]
+    Read { path: PathBuf, source: io::Error },
+}
@@ -0,0 +1,121 @@
+//! Asset path resolution utilities for game engine.
+//!
+//! Relative paths of assets are resolved against asset roots of [`Config`](crate::config::Config)
+//! in order, falling back to the directory of the executable and current working directory.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use error::AssetError;
+
+#[cfg(feature = "glsl")]
+use crate::spirv::GlslInclude;
+
+pub mod error;
+mod tests;
+
+/// Source of assets which resolves their paths against search directories.
+#[derive(Debug, Clone)]
+pub struct AssetSource {
+    search_paths: Vec<PathBuf>,
+}
+
+impl AssetSource {
+    /// Creates new asset source with provided roots.
+    ///
+    /// Directory of the executable and current working directory
+    /// are searched after all of the roots.
+    ///
+    pub fn new(roots: impl IntoIterator<Item = PathBuf>) -> Self {
+        let exe_dir = env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(Path::to_path_buf));
+        let current_dir = env::current_dir().ok();
+
+        let mut search_paths: Vec<PathBuf> = Vec::new();
+        for path in roots.into_iter().chain(exe_dir).chain(current_dir) {
+            if !search_paths.contains(&path) {
+                search_paths.push(path);
+            }
+        }
+        Self { search_paths }
+    }
+
+    /// All directories which are searched, in order.
+    pub fn search_paths(&self) -> &[PathBuf] {
+        &self.search_paths
+    }
+
+    /// Resolves path of the asset.
+    ///
+    /// Absolute paths are returned as is if file exists.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if there is no such file in any of search directories.
+    /// It contains every path that was tried.
+    ///
+    pub fn resolve(&self, path: impl AsRef<Path>) -> Result<PathBuf, AssetError> {
+        let path = path.as_ref();
+        let candidates: Vec<PathBuf> = if path.is_absolute() {
+            vec![path.to_path_buf()]
+        } else {
+            self.search_paths
+                .iter()
+                .map(|root| root.join(path))
+                .collect()
+        };
+        match candidates.iter().find(|candidate| candidate.is_file()) {
+            Some(resolved) => {
+                log::debug!("asset {:?} resolved as {:?}", path, resolved);
+                Ok(resolved.clone())
+            }
+            None => Err(AssetError::NotFound {
+                path: path.to_path_buf(),
+                tried: candidates,
+            }),
+        }
+    }
+
+    /// Resolves path of the asset and reads its content.
+    pub fn read(&self, path: impl AsRef<Path>) -> Result<Vec<u8>, AssetError> {
+        let resolved = self.resolve(path)?;
+        fs::read(&resolved).map_err(|source| AssetError::Read {
+            path: resolved,
+            source,
+        })
+    }
+
+    /// Resolves path of the asset and reads its content as UTF-8 text.
+    pub fn read_to_string(&self, path: impl AsRef<Path>) -> Result<String, AssetError> {
+        let resolved = self.resolve(path)?;
+        fs::read_to_string(&resolved).map_err(|source| AssetError::Read {
+            path: resolved,
+            source,
+        })
+    }
+
+    /// Resolves `#include` directive of GLSL shader, so it could be used
+    /// as [include resolver](crate::spirv::GlslIncludeResolver) of shaders loaded from assets.
+    ///
+    /// Requested file is searched next to the including file first,
+    /// then it is resolved as any other asset. Name of the include is its resolved path.
+    ///
+    #[cfg(feature = "glsl")]
+    pub fn glsl_include(&self, requested: &str, including: &str) -> Result<GlslInclude, String> {
+        let sibling = Path::new(including)
+            .parent()
+            .map(|parent| parent.join(requested))
+            .filter(|sibling| sibling.is_file());
+        let path = match sibling {
+            Some(sibling) => sibling,
+            None => self.resolve(requested).map_err(|error| error.to_string())?,
+        };
+        let source = fs::read_to_string(&path).map_err(|error| error.to_string())?;
+        Ok(GlslInclude {
+            name: path.display().to_string(),
+            source,
+        })
+    }
+}
@@ -0,0 +1,114 @@
+#![cfg(test)]
+
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::{error::AssetError, AssetSource};
+
+/// Temporary directory which is removed on drop.
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new() -> Self {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let id = COUNTER.fetch_add(1, Ordering::SeqCst);
+        let path = std::env::temp_dir().join(format!("titan_asset_{}_{}", process::id(), id));
+        fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+
+    fn file(&self, name: &str, content: &str) -> PathBuf {
+        let path = self.0.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, content).unwrap();
+        path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn roots_are_searched_in_order() {
+    let first = TempDir::new();
+    let second = TempDir::new();
+    second.file("shaders/default.vert", "second");
+    let source = AssetSource::new([first.0.clone(), second.0.clone()]);
+
+    let resolved = source.resolve("shaders/default.vert").unwrap();
+    assert_eq!(resolved, second.0.join("shaders/default.vert"));
+
+    first.file("shaders/default.vert", "first");
+    let content = source.read("shaders/default.vert").unwrap();
+    assert_eq!(content, b"first");
+}
+
+#[test]
+fn fallbacks_go_after_roots() {
+    let root = TempDir::new();
+    let source = AssetSource::new([root.0.clone()]);
+
+    let search_paths = source.search_paths();
+    assert_eq!(search_paths[0], root.0);
+    assert!(search_paths.contains(&std::env::current_dir().unwrap()));
+}
+
+#[test]
+fn absolute_path_is_used_as_is() {
+    let root = TempDir::new();
+    let path = root.file("texture.png", "png");
+    let source = AssetSource::new([]);
+
+    assert_eq!(source.resolve(&path).unwrap(), path);
+}
+
+#[test]
+fn missing_file_lists_tried_paths() {
+    let first = TempDir::new();
+    let second = TempDir::new();
+    let source = AssetSource::new([first.0.clone(), second.0.clone()]);
+
+    match source.resolve("missing.png") {
+        Err(AssetError::NotFound { path, tried }) => {
+            assert_eq!(path, PathBuf::from("missing.png"));
+            assert_eq!(tried.len(), source.search_paths().len());
+            assert_eq!(tried[0], first.0.join("missing.png"));
+            assert_eq!(tried[1], second.0.join("missing.png"));
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn text_is_read() {
+    let root = TempDir::new();
+    root.file("shaders/object.frag", "void main() {}");
+    let source = AssetSource::new([root.0.clone()]);
+
+    let content = source.read_to_string("shaders/object.frag").unwrap();
+    assert_eq!(content, "void main() {}");
+}
+
+#[cfg(feature = "glsl")]
+#[test]
+fn includes_are_searched_next_to_including_file() {
+    let root = TempDir::new();
+    let including = root.file("shaders/object.frag", "#include \"common.glsl\"");
+    let sibling = root.file("shaders/common.glsl", "sibling");
+    root.file("common.glsl", "root");
+    let source = AssetSource::new([root.0.clone()]);
+
+    let including = including.display().to_string();
+    let include = source.glsl_include("common.glsl", &including).unwrap();
+    assert_eq!(include.name, sibling.display().to_string());
+    assert_eq!(include.source, "sibling");
+
+    let include = source.glsl_include("common.glsl", "object.frag").unwrap();
+    assert_eq!(include.source, "root");
+    assert!(source.glsl_include("missing.glsl", &including).is_err());
+}
@@ -0,0 +1,68 @@
+//! Builder of game engine configuration.
+
+use semver::Version;
+
+use super::{Config, ConfigError};
+
+/// Builder of [`Config`] which validates the configuration when it is built.
+///
+/// Options which are not covered by the builder could be set
+/// with [`configure`](Self::configure) using setters of [`Config`].
+///
+#[derive(Debug, Clone)]
+pub struct ConfigBuilder {
+    config: Config,
+}
+
+impl ConfigBuilder {
+    /// Creates new builder with given name and version of your game.
+    ///
+    /// Validation is enabled in debug builds by default.
+    ///
+    pub fn new(name: impl Into<String>, version: Version) -> Self {
+        let config = Config::new(name.into(), version, cfg!(debug_assertions));
+        Self { config }
+    }
+
+    /// Sets name of your game.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.config.name = name.into();
+        self
+    }
+
+    /// Sets version of your game.
+    pub fn version(mut self, version: Version) -> Self {
+        self.config.version = version;
+        self
+    }
+
+    /// Enables or disables validation (useful for debugging).
+    pub fn validation(mut self, enable_validation: bool) -> Self {
+        self.config = self.config.with_validation(enable_validation);
+        self
+    }
+
+    /// Sets count of frames which could be processed by the device at the same time.
+    pub fn frames_in_flight(mut self, frames_in_flight: u32) -> Self {
+        self.config = self.config.with_frames_in_flight(frames_in_flight);
+        self
+    }
+
+    /// Sets title of the window.
+    pub fn window_title(mut self, window_title: impl Into<String>) -> Self {
+        self.config = self.config.with_window_title(window_title.into());
+        self
+    }
+
+    /// Applies setters of [`Config`] to the configuration being built.
+    pub fn configure(mut self, configure: impl FnOnce(Config) -> Config) -> Self {
+        self.config = configure(self.config);
+        self
+    }
+
+    /// Validates and builds the configuration.
+    pub fn build(self) -> Result<Config, ConfigError> {
+        self.config.validate()?;
+        Ok(self.config)
+    }
+}
@@ -0,0 +1,177 @@
+//! Overrides of configuration from environment variables.
+//!
+//! This allows to change settings of the build which cannot be recompiled (for example, by QA).
+//!
+//! Supported variables are:
+//! - `TITAN_VALIDATION`: `1`, `true`, `on`, `yes` or `0`, `false`, `off`, `no`;
+//! - `TITAN_PRESENT_THREAD`: same values as `TITAN_VALIDATION`;
+//! - `TITAN_PRESENT_MODE`: `immediate`, `mailbox`, `fifo` or `fifo_relaxed`;
+//! - `TITAN_ADAPTER`: index of physical device;
+//! - `TITAN_GPU`: `default`, `discrete`, `integrated`, index or part of name of physical device;
+//! - `TITAN_FRAMES_IN_FLIGHT`: count of frames in flight, at least 1.
+//!
+//! Both `TITAN_ADAPTER` and `TITAN_GPU` select physical device. If both of them are set,
+//! `TITAN_GPU` takes precedence regardless of their order, and `TITAN_ADAPTER` is ignored
+//! with a warning.
+//!
+//! Overrides are applied on initialization of the engine, after configuration is created,
+//! so they take precedence over values set both in code and in the configuration file.
+//!
+//! Not every field of configuration could be overridden yet. Notably, `TITAN_RENDER_SCALE`
+//! is not supported because renderer has no render scale; it is ignored with a warning
+//! as any other unknown `TITAN_*` variable.
+
+use std::env;
+
+use super::{Config, DevicePreference, PresentMode};
+
+/// Prefix of all environment variables which override configuration.
+const PREFIX: &str = "TITAN_";
+
+const VALIDATION: &str = "TITAN_VALIDATION";
+const PRESENT_THREAD: &str = "TITAN_PRESENT_THREAD";
+const PRESENT_MODE: &str = "TITAN_PRESENT_MODE";
+const ADAPTER: &str = "TITAN_ADAPTER";
+const GPU: &str = "TITAN_GPU";
+const FRAMES_IN_FLIGHT: &str = "TITAN_FRAMES_IN_FLIGHT";
+
+impl Config {
+    /// Overrides fields of this configuration by values of environment variables.
+    ///
+    /// Every applied override is logged, and malformed values are ignored with a warning.
+    /// Returns names of variables which were applied.
+    ///
+    /// Supported variables are `TITAN_VALIDATION`, `TITAN_PRESENT_THREAD` (boolean values
+    /// like `1`/`0`, `true`/`false`, `on`/`off`, `yes`/`no`),
+    /// `TITAN_PRESENT_MODE` (`immediate`, `mailbox`, `fifo` or `fifo_relaxed`)
+    /// `TITAN_ADAPTER` (index of physical device),
+    /// `TITAN_GPU` (`default`, `discrete`, `integrated`, index or part of name of physical device)
+    /// and `TITAN_FRAMES_IN_FLIGHT` (count of frames in flight, at least 1).
+    /// `TITAN_GPU` takes precedence over `TITAN_ADAPTER` if both of them are set.
+    ///
+    pub fn apply_env_overrides(&mut self) -> Vec<&'static str> {
+        self.apply_overrides(env::vars())
+    }
+
+    /// Overrides fields of this configuration by provided variables.
+    pub(crate) fn apply_overrides<I, K, V>(&mut self, vars: I) -> Vec<&'static str>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let mut applied = Vec::new();
+        let mut gpu_applied = false;
+        for (key, value) in vars {
+            let (key, value) = (key.as_ref(), value.as_ref().trim());
+            if !key.starts_with(PREFIX) {
+                continue;
+            }
+            let name = match key {
+                VALIDATION => parse_bool(value).map(|value| {
+                    self.enable_validation = value;
+                    VALIDATION
+                }),
+                PRESENT_THREAD => parse_bool(value).map(|value| {
+                    self.present_thread = value;
+                    PRESENT_THREAD
+                }),
+                PRESENT_MODE => parse_present_mode(value).map(|value| {
+                    self.present_mode = Some(value);
+                    PRESENT_MODE
+                }),
+                ADAPTER if gpu_applied => {
+                    self::warn_device_conflict();
+                    continue;
+                }
+                ADAPTER => value.parse().ok().map(|value| {
+                    self.device_preference = DevicePreference::ByIndex(value);
+                    ADAPTER
+                }),
+                GPU => parse_device_preference(value).map(|value| {
+                    self.device_preference = value;
+                    GPU
+                }),
+                FRAMES_IN_FLIGHT => parse_frames_in_flight(value).map(|value| {
+                    self.frames_in_flight = value;
+                    FRAMES_IN_FLIGHT
+                }),
+                _ => {
+                    log::warn!("unknown configuration override {} is ignored", key);
+                    continue;
+                }
+            };
+            match name {
+                Some(name) => {
+                    log::info!("configuration override applied: {}={}", key, value);
+                    if name == GPU {
+                        gpu_applied = true;
+                        if let Some(index) = applied.iter().position(|&name| name == ADAPTER) {
+                            self::warn_device_conflict();
+                            applied.remove(index);
+                        }
+                    }
+                    applied.push(name);
+                }
+                None => log::warn!("malformed value of {} is ignored: {:?}", key, value),
+            }
+        }
+        applied
+    }
+}
+
+/// Reports that `TITAN_ADAPTER` is ignored because `TITAN_GPU` is set too.
+fn warn_device_conflict() {
+    log::warn!(
+        "both {} and {} are set, {} takes precedence and {} is ignored",
+        ADAPTER,
+        GPU,
+        GPU,
+        ADAPTER,
+    );
+}
+
+/// Parses boolean value of environment variable.
+pub(super) fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_ascii_lowercase().as_str() {
+        "1" | "true" | "on" | "yes" => Some(true),
+        "0" | "false" | "off" | "no" => Some(false),
+        _ => None,
+    }
+}
+
+/// Parses present mode from environment variable.
+pub(super) fn parse_present_mode(value: &str) -> Option<PresentMode> {
+    match value.to_ascii_lowercase().as_str() {
+        "immediate" => Some(PresentMode::Immediate),
+        "mailbox" => Some(PresentMode::Mailbox),
+        "fifo" | "vsync" => Some(PresentMode::Fifo),
+        "fifo_relaxed" | "relaxed" => Some(PresentMode::FifoRelaxed),
+        _ => None,
+    }
+}
+
+/// Parses preference of physical device from environment variable.
+///
+/// Values which are not keywords or indices are treated as part of device name.
+///
+pub(super) fn parse_device_preference(value: &str) -> Option<DevicePreference> {
+    if value.is_empty() {
+        return None;
+    }
+    let preference = match value.to_ascii_lowercase().as_str() {
+        "default" | "auto" => DevicePreference::Default,
+        "discrete" => DevicePreference::PreferDiscrete,
+        "integrated" => DevicePreference::PreferIntegrated,
+        _ => match value.parse() {
+            Ok(index) => DevicePreference::ByIndex(index),
+            Err(_) => DevicePreference::ByNameSubstring(value.to_string()),
+        },
+    };
+    Some(preference)
+}
+
+/// Parses count of frames in flight from environment variable.
+pub(super) fn parse_frames_in_flight(value: &str) -> Option<u32> {
+    value.parse().ok().filter(|&value| value > 0)
+}
@@ -0,0 +1,158 @@
+//! Loading of game engine configuration from TOML and JSON files.
+
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::{Config, ConfigError, WindowSize};
+use crate::window::Size;
+
+mod tests;
+
+impl Config {
+    /// Loads configuration from TOML string.
+    pub fn from_toml_str(toml: &str) -> Result<Self, ConfigError> {
+        Ok(toml::from_str(toml)?)
+    }
+
+    /// Loads configuration from JSON string.
+    pub fn from_json_str(json: &str) -> Result<Self, ConfigError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Loads configuration from the file, which format is determined by its extension
+    /// (`.toml` or `.json`).
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let extension = path.extension().and_then(|extension| extension.to_str());
+        match extension.map(str::to_ascii_lowercase).as_deref() {
+            Some("toml") => Self::from_toml_str(&fs::read_to_string(path)?),
+            Some("json") => Self::from_json_str(&fs::read_to_string(path)?),
+            _ => Err(ConfigError::UnknownFormat(path.to_path_buf())),
+        }
+    }
+
+    /// Serializes configuration into TOML string.
+    pub fn to_toml_string(&self) -> Result<String, ConfigError> {
+        // Serialization through the value puts tables after plain values as TOML requires.
+        Ok(toml::Value::try_from(self)?.to_string())
+    }
+
+    /// Serializes configuration into JSON string.
+    pub fn to_json_string(&self) -> Result<String, ConfigError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+/// Window size is serialized as `{ width, height }` table,
+/// with `logical = true` if the size is in logical pixels.
+///
+/// TOML has no representation of enum variants with data, so the size is flattened.
+///
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct WindowSizeRepr {
+    width: u32,
+    height: u32,
+    #[serde(default, skip_serializing_if = "is_false")]
+    logical: bool,
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+impl From<WindowSize> for WindowSizeRepr {
+    fn from(size: WindowSize) -> Self {
+        let (Size { width, height }, logical) = match size {
+            WindowSize::Physical(size) => (size, false),
+            WindowSize::Logical(size) => (size, true),
+        };
+        Self {
+            width,
+            height,
+            logical,
+        }
+    }
+}
+
+impl From<WindowSizeRepr> for WindowSize {
+    fn from(repr: WindowSizeRepr) -> Self {
+        let size = Size::new(repr.width, repr.height);
+        if repr.logical {
+            Self::Logical(size)
+        } else {
+            Self::Physical(size)
+        }
+    }
+}
+
+/// Version is serialized as `"1.2.3"` string and deserialized either from such string
+/// or from `{ major, minor, patch }` table.
+pub(super) mod version {
+    use semver::Version;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Repr {
+        String(String),
+        Table { major: u64, minor: u64, patch: u64 },
+    }
+
+    pub fn serialize<S: Serializer>(version: &Version, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(version)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Version, D::Error> {
+        match Repr::deserialize(deserializer)? {
+            Repr::String(version) => version.parse().map_err(D::Error::custom),
+            Repr::Table {
+                major,
+                minor,
+                patch,
+            } => Ok(Version::new(major, minor, patch)),
+        }
+    }
+}
+
+/// Path of pipeline cache is serialized as path string, or as `false` if persistence is disabled.
+/// Missing value means the default path.
+pub(super) mod cache_path {
+    use std::path::PathBuf;
+
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(untagged)]
+    enum Repr {
+        Enabled(bool),
+        Path(PathBuf),
+    }
+
+    pub fn serialize<S: Serializer>(
+        path: &Option<Option<PathBuf>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match path {
+            Some(Some(path)) => Repr::Path(path.clone()),
+            Some(None) => Repr::Enabled(false),
+            None => Repr::Enabled(true),
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Option<PathBuf>>, D::Error> {
+        match Repr::deserialize(deserializer)? {
+            Repr::Path(path) if path.as_os_str().is_empty() => {
+                Err(D::Error::custom("path of pipeline cache must not be empty"))
+            }
+            Repr::Path(path) => Ok(Some(Some(path))),
+            Repr::Enabled(false) => Ok(Some(None)),
+            Repr::Enabled(true) => Ok(None),
+        }
+    }
+}
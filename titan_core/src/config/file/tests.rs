@@ -0,0 +1,104 @@
+#![cfg(test)]
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use semver::Version;
+
+use crate::color::Color;
+use crate::config::{Config, ConfigError, PresentMode, SurfaceFormat, WindowSize};
+use crate::window::Size;
+
+fn custom_config() -> Config {
+    Config::new("game".to_string(), Version::new(1, 2, 3), true)
+        .with_present_mode(PresentMode::Mailbox)
+        .with_surface_formats(vec![SurfaceFormat::Hdr10, SurfaceFormat::Srgb])
+        .with_clear_color(Color::linear(0.25, 0.5, 0.75, 1.0))
+        .with_fixed_timestep(Duration::from_millis(50))
+        .with_window_size(Size::new(1280, 720))
+        .with_asset_roots(vec![PathBuf::from("assets")])
+        .with_pipeline_cache_path(None)
+}
+
+#[test]
+fn toml_round_trip() {
+    for config in [Config::default(), custom_config()] {
+        let toml = config.to_toml_string().unwrap();
+        assert_eq!(Config::from_toml_str(&toml).unwrap(), config, "{}", toml);
+    }
+}
+
+#[test]
+fn logical_window_size_round_trip() {
+    let config = custom_config().with_window_size(WindowSize::Logical(Size::new(800, 600)));
+    let toml = config.to_toml_string().unwrap();
+    assert_eq!(Config::from_toml_str(&toml).unwrap(), config, "{}", toml);
+    let json = config.to_json_string().unwrap();
+    assert_eq!(Config::from_json_str(&json).unwrap(), config, "{}", json);
+}
+
+#[test]
+fn window_size_is_table() {
+    let config = Config::from_toml_str("window_size = { width = 800, height = 600 }").unwrap();
+    assert_eq!(
+        config.window_size(),
+        Some(WindowSize::Physical(Size::new(800, 600)))
+    );
+    let toml = "window_size = { width = 800, height = 600, logical = true }";
+    let config = Config::from_toml_str(toml).unwrap();
+    assert_eq!(
+        config.window_size(),
+        Some(WindowSize::Logical(Size::new(800, 600)))
+    );
+}
+
+#[test]
+fn json_round_trip() {
+    for config in [Config::default(), custom_config()] {
+        let json = config.to_json_string().unwrap();
+        assert_eq!(Config::from_json_str(&json).unwrap(), config, "{}", json);
+    }
+}
+
+#[test]
+fn missing_fields_are_defaults() {
+    let config = Config::from_toml_str("name = \"game\"\nversion = \"0.3.0\"").unwrap();
+    let expected = Config::new(
+        "game".to_string(),
+        Version::new(0, 3, 0),
+        cfg!(debug_assertions),
+    );
+    assert_eq!(config, expected);
+}
+
+#[test]
+fn version_is_accepted_as_table() {
+    let json = r#"{ "version": { "major": 2, "minor": 0, "patch": 1 } }"#;
+    let config = Config::from_json_str(json).unwrap();
+    assert_eq!(config.version(), &Version::new(2, 0, 1));
+}
+
+#[test]
+fn unknown_fields_are_rejected() {
+    let result = Config::from_toml_str("frames_in_fligth = 3");
+    assert!(matches!(result, Err(ConfigError::Toml(_))));
+    let result = Config::from_json_str(r#"{ "depth_bufer": false }"#);
+    assert!(matches!(result, Err(ConfigError::Json(_))));
+}
+
+#[test]
+fn env_overrides_file() {
+    let toml = "frames_in_flight = 3\npresent_thread = true";
+    let mut config = Config::from_toml_str(toml).unwrap();
+    let applied = config.apply_overrides([("TITAN_FRAMES_IN_FLIGHT", "1")]);
+
+    assert_eq!(applied, ["TITAN_FRAMES_IN_FLIGHT"]);
+    assert_eq!(config.frames_in_flight(), 1);
+    assert!(config.present_thread());
+}
+
+#[test]
+fn unknown_file_format_is_rejected() {
+    let result = Config::from_file("config.yaml");
+    assert!(matches!(result, Err(ConfigError::UnknownFormat(_))));
+}
@@ -1,13 +1,301 @@
 //! Configuration utilities for game engine and your game.
 
+use std::path::PathBuf;
+use std::time::Duration;
+
 use semver::Version;
+use thiserror::Error;
+
+use crate::adapter::{DeviceFeatures, DeviceRequirements};
+use crate::app::stats::DEFAULT_STATS_WINDOW;
+use crate::color::Color;
+use crate::debug::{DebugFilter, DebugMessage, DebugMessageCallback};
+use crate::pipeline_cache::{self, PIPELINE_CACHE_FILE};
+use crate::window::Size;
+
+pub use self::builder::ConfigBuilder;
+
+mod builder;
+mod env;
+#[cfg(feature = "serde")]
+mod file;
+pub mod settings;
+mod tests;
+
+/// Mode of presentation of rendered images to the window.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PresentMode {
+    /// Images are presented immediately, which may cause tearing.
+    Immediate,
+    /// Images are presented on vertical blank, replacing the waiting image.
+    Mailbox,
+    /// Images are presented on vertical blank in order of rendering (vertical sync).
+    Fifo,
+    /// Same as [`Fifo`](Self::Fifo), but late images are presented immediately.
+    FifoRelaxed,
+}
+
+/// Format of swapchain images together with the color space they are presented in.
+///
+/// Note that shaders of the engine write linear colors, so with [`Unorm`](Self::Unorm)
+/// format the image is not gamma-encoded by the hardware.
+///
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SurfaceFormat {
+    /// 8-bit sRGB format (`B8G8R8A8_SRGB` or `R8G8B8A8_SRGB`) in sRGB non-linear color space.
+    Srgb,
+    /// 8-bit UNORM format (`B8G8R8A8_UNORM` or `R8G8B8A8_UNORM`) in sRGB non-linear color space.
+    Unorm,
+    /// 10-bit format (`A2B10G10R10_UNORM_PACK32` or `A2R10G10B10_UNORM_PACK32`)
+    /// in HDR10 (ST 2084) color space.
+    Hdr10,
+    /// 16-bit float format (`R16G16B16A16_SFLOAT`) in extended linear sRGB (scRGB) color space.
+    ScRgb,
+}
+
+impl SurfaceFormat {
+    /// If this format requires the surface to advertise HDR color spaces.
+    pub const fn is_hdr(self) -> bool {
+        matches!(self, Self::Hdr10 | Self::ScRgb)
+    }
+}
+
+/// Surface formats which are preferred by default: sRGB first, then UNORM.
+pub const DEFAULT_SURFACE_FORMATS: &[SurfaceFormat] = &[SurfaceFormat::Srgb, SurfaceFormat::Unorm];
+
+/// Way the compositor blends presented images with the contents behind the window.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CompositeAlpha {
+    /// Alpha channel is ignored, the window is fully opaque.
+    #[default]
+    Opaque,
+    /// Alpha channel is respected, colors are already multiplied by alpha.
+    PreMultiplied,
+    /// Alpha channel is respected, colors are multiplied by alpha by the compositor.
+    PostMultiplied,
+    /// Blending is chosen by the platform.
+    Inherit,
+}
+
+/// Usage of swapchain images in addition to rendering into them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct SwapchainUsage {
+    /// Images could be copied from, for example, into screenshots.
+    pub transfer_src: bool,
+    /// Images could be copied or blitted into.
+    pub transfer_dst: bool,
+}
+
+impl SwapchainUsage {
+    /// No additional usage.
+    pub const NONE: Self = Self {
+        transfer_src: false,
+        transfer_dst: false,
+    };
+
+    /// Usage which is requested here, but missing in the supported usage.
+    pub const fn difference(self, supported: Self) -> Self {
+        Self {
+            transfer_src: self.transfer_src && !supported.transfer_src,
+            transfer_dst: self.transfer_dst && !supported.transfer_dst,
+        }
+    }
+}
+
+/// Preference of physical device (GPU) which is used for rendering.
+#[derive(Debug, Clone, Eq, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DevicePreference {
+    /// Engine chooses the device, preferring discrete GPUs.
+    #[default]
+    Default,
+    /// Discrete GPU is used if there is one.
+    PreferDiscrete,
+    /// Integrated GPU is used if there is one (for example, to save battery of a laptop).
+    PreferIntegrated,
+    /// Device with provided index in the list of all physical devices must be used.
+    ByIndex(usize),
+    /// Device which name contains provided string (ignoring case) must be used.
+    ByNameSubstring(String),
+}
+
+/// Policy of window size when scale factor of the window is changed
+/// (for example, when the window is moved between monitors with different DPI).
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ScaleFactorPolicy {
+    /// Window keeps its logical size, so its physical size is changed
+    /// as suggested by the system.
+    #[default]
+    KeepLogicalSize,
+    /// Window keeps its physical size, so its contents become smaller or larger.
+    KeepPhysicalSize,
+}
+
+impl ScaleFactorPolicy {
+    /// Resolves new physical size of the window from the size suggested by the system
+    /// and the current physical size of the window.
+    pub fn resolve(self, suggested: Size, current: Size) -> Size {
+        match self {
+            Self::KeepLogicalSize => suggested,
+            Self::KeepPhysicalSize => current,
+        }
+    }
+}
+
+/// Initial size of the client area of the window.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(from = "file::WindowSizeRepr", into = "file::WindowSizeRepr")
+)]
+pub enum WindowSize {
+    /// Size in physical pixels, which is the size of swapchain images.
+    Physical(Size),
+    /// Size in logical pixels, which is multiplied by the scale factor of the monitor.
+    Logical(Size),
+}
+
+impl WindowSize {
+    /// Converts the size into physical pixels with provided scale factor.
+    pub fn to_physical(self, scale_factor: f64) -> Size {
+        match self {
+            Self::Physical(size) => size,
+            Self::Logical(size) => {
+                let scale = |value: u32| (value as f64 * scale_factor).round() as u32;
+                Size::new(scale(size.width), scale(size.height))
+            }
+        }
+    }
+}
+
+impl From<Size> for WindowSize {
+    fn from(size: Size) -> Self {
+        Self::Physical(size)
+    }
+}
+
+/// Icon of game engine window as RGBA image with 8 bits per channel.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WindowIcon {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+impl WindowIcon {
+    /// Checks that length of pixel data matches size of the icon.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let expected = self.width as u64 * self.height as u64 * 4;
+        if self.rgba.len() as u64 != expected {
+            return Err(ConfigError::InvalidWindowIcon {
+                width: self.width,
+                height: self.height,
+                len: self.rgba.len(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Error that can happen when validating the configuration.
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("name of the game must not be empty")]
+    EmptyName,
+
+    #[error("version {0} cannot be packed into Vulkan version (10, 10 and 12 bits at most)")]
+    VersionOverflow(Version),
+
+    #[error("count of frames in flight must be at least 1")]
+    ZeroFramesInFlight,
+
+    #[error("duration of fixed timestep must not be zero")]
+    ZeroFixedTimestep,
+
+    #[cfg(feature = "serde")]
+    #[error("failed to read configuration file: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[cfg(feature = "serde")]
+    #[error("failed to parse TOML configuration: {0}")]
+    Toml(#[from] toml::de::Error),
+
+    #[cfg(feature = "serde")]
+    #[error("failed to serialize TOML configuration: {0}")]
+    TomlSerialize(#[from] toml::ser::Error),
+
+    #[cfg(feature = "serde")]
+    #[error("failed to parse JSON configuration: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[cfg(feature = "serde")]
+    #[error("unknown format of configuration file {0:?}, expected `.toml` or `.json`")]
+    UnknownFormat(PathBuf),
+
+    #[error("window icon of {width}x{height} must have {} bytes of RGBA data, got {len}", *width as u64 * *height as u64 * 4)]
+    InvalidWindowIcon { width: u32, height: u32, len: usize },
+}
 
 /// This struct represents general configuration of game engine.
-#[derive(Debug, Clone)]
+///
+/// With `serde` feature, configuration could be [loaded from the file](Self::from_file).
+/// Missing fields of the file are set to their defaults, unknown fields are rejected.
+///
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
 pub struct Config {
     name: String,
+    #[cfg_attr(feature = "serde", serde(with = "file::version"))]
     version: Version,
     enable_validation: bool,
+    present_thread: bool,
+    present_mode: Option<PresentMode>,
+    surface_formats: Vec<SurfaceFormat>,
+    clear_color: Color,
+    device_preference: DevicePreference,
+    device_requirements: DeviceRequirements,
+    frames_in_flight: u32,
+    swapchain_image_count: Option<u32>,
+    swapchain_usage: SwapchainUsage,
+    composite_alpha: CompositeAlpha,
+    frame_wait_timeout: Option<Duration>,
+    depth_buffer: bool,
+    msaa_samples: u32,
+    post_processing: bool,
+    spike_threshold: Option<f32>,
+    stats_window: usize,
+    target_fps: Option<u32>,
+    render_when_unfocused: bool,
+    fixed_timestep: Duration,
+    scale_factor_policy: ScaleFactorPolicy,
+    asset_roots: Vec<PathBuf>,
+    app_id: Option<String>,
+    app_user_model_id: Option<String>,
+    /// Path of pipeline cache file, or `None` if the default path is used.
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "file::cache_path", skip_serializing_if = "Option::is_none")
+    )]
+    pipeline_cache_path: Option<Option<PathBuf>>,
+    env_overrides: bool,
+    window_size: Option<WindowSize>,
+    window_title: Option<String>,
+    resizable: bool,
+    decorations: bool,
+    maximized: bool,
+    window_icon: Option<WindowIcon>,
+    debug_filter: DebugFilter,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    debug_callback: Option<DebugMessageCallback>,
 }
 
 pub const ENGINE_NAME: &str = env!("CARGO_CRATE_NAME", "library must be compiled by Cargo");
@@ -17,6 +305,18 @@ pub struct Config {
     pub static ref ENGINE_VERSION: Version = ENGINE_VERSION_STR.parse().unwrap();
 }
 
+/// Checks if the version can be packed into Vulkan version
+/// with 10 bits of major, 10 bits of minor and 12 bits of patch component.
+pub const fn fits_vk_version(version: &Version) -> bool {
+    version.major < 1 << 10 && version.minor < 1 << 10 && version.patch < 1 << 12
+}
+
+/// Default duration of fixed step of simulation (60 steps per second).
+pub const DEFAULT_FIXED_TIMESTEP: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+/// Default multiple of the rolling median of frame time which frame must exceed to be a spike.
+pub const DEFAULT_SPIKE_THRESHOLD: f32 = 4.0;
+
 impl Config {
     /// Creates new configuration with given name, version and validation usage.
     pub const fn new(name: String, version: Version, enable_validation: bool) -> Self {
@@ -24,7 +324,399 @@ pub const fn new(name: String, version: Version, enable_validation: bool) -> Sel
             name,
             version,
             enable_validation,
+            present_thread: false,
+            present_mode: None,
+            surface_formats: Vec::new(),
+            clear_color: Color::BLACK,
+            device_preference: DevicePreference::Default,
+            device_requirements: DeviceRequirements {
+                required_features: DeviceFeatures::NONE,
+                optional_features: DeviceFeatures::NONE,
+                required_extensions: Vec::new(),
+                optional_extensions: Vec::new(),
+            },
+            frames_in_flight: 2,
+            swapchain_image_count: None,
+            swapchain_usage: SwapchainUsage::NONE,
+            composite_alpha: CompositeAlpha::Opaque,
+            frame_wait_timeout: None,
+            depth_buffer: true,
+            msaa_samples: 1,
+            post_processing: false,
+            spike_threshold: Some(DEFAULT_SPIKE_THRESHOLD),
+            stats_window: DEFAULT_STATS_WINDOW,
+            target_fps: None,
+            render_when_unfocused: true,
+            fixed_timestep: DEFAULT_FIXED_TIMESTEP,
+            scale_factor_policy: ScaleFactorPolicy::KeepLogicalSize,
+            asset_roots: Vec::new(),
+            app_id: None,
+            app_user_model_id: None,
+            pipeline_cache_path: None,
+            env_overrides: true,
+            window_size: None,
+            window_title: None,
+            resizable: true,
+            decorations: true,
+            maximized: false,
+            window_icon: None,
+            debug_filter: DebugFilter::ALL,
+            debug_callback: None,
+        }
+    }
+
+    /// Enables or disables validation (useful for debugging).
+    pub const fn with_validation(mut self, enable_validation: bool) -> Self {
+        self.enable_validation = enable_validation;
+        self
+    }
+
+    /// Enables or disables presentation of images from the dedicated thread.
+    pub const fn with_present_thread(mut self, present_thread: bool) -> Self {
+        self.present_thread = present_thread;
+        self
+    }
+
+    /// Sets preferred present mode.
+    ///
+    /// If this mode is not supported by the surface, it will be replaced by supported one.
+    ///
+    pub const fn with_present_mode(mut self, present_mode: PresentMode) -> Self {
+        self.present_mode = Some(present_mode);
+        self
+    }
+
+    /// Sets surface formats in order of preference.
+    ///
+    /// The first format which is supported by the surface is used for swapchain images.
+    /// If none of them are supported, the first format supported by the surface is used.
+    /// Add [`SurfaceFormat::Hdr10`] or [`SurfaceFormat::ScRgb`] to opt into HDR output.
+    ///
+    /// Empty list means [default preference](DEFAULT_SURFACE_FORMATS).
+    ///
+    pub fn with_surface_formats(mut self, surface_formats: Vec<SurfaceFormat>) -> Self {
+        self.surface_formats = surface_formats;
+        self
+    }
+
+    /// Sets initial color which the window is cleared with before drawing.
+    ///
+    /// Default is [`Color::BLACK`]. Color could be changed later at runtime.
+    ///
+    pub const fn with_clear_color(mut self, clear_color: Color) -> Self {
+        self.clear_color = clear_color;
+        self
+    }
+
+    /// Sets index of physical device (adapter) which should be used for rendering.
+    ///
+    /// This is the same as [`DevicePreference::ByIndex`].
+    ///
+    pub fn with_adapter(self, adapter: usize) -> Self {
+        self.with_device_preference(DevicePreference::ByIndex(adapter))
+    }
+
+    /// Sets features and extensions which are requested from the device.
+    ///
+    /// Devices without required items are not suitable,
+    /// optional items are enabled if they are supported.
+    /// By default, nothing is requested.
+    ///
+    pub fn with_device_requirements(mut self, device_requirements: DeviceRequirements) -> Self {
+        self.device_requirements = device_requirements;
+        self
+    }
+
+    /// Sets preference of physical device which is used for rendering.
+    ///
+    /// If the device requested by index or name is missing or not suitable,
+    /// initialization fails with the list of suitable devices.
+    /// Default is [`DevicePreference::Default`].
+    ///
+    pub fn with_device_preference(mut self, device_preference: DevicePreference) -> Self {
+        self.device_preference = device_preference;
+        self
+    }
+
+    /// Sets count of frames which could be processed by the device at the same time.
+    ///
+    /// Must be between 1 and maximal count of swapchain images. Default is 2.
+    ///
+    pub const fn with_frames_in_flight(mut self, frames_in_flight: u32) -> Self {
+        self.frames_in_flight = frames_in_flight;
+        self
+    }
+
+    /// Sets desired count of swapchain images, for example, 3 for triple buffering.
+    ///
+    /// The count is clamped by capabilities of the surface and is never less than
+    /// [count of frames in flight](Self::with_frames_in_flight).
+    /// `None` means one image more than the minimum of the surface. Default is `None`.
+    ///
+    pub const fn with_swapchain_image_count(mut self, swapchain_image_count: Option<u32>) -> Self {
+        self.swapchain_image_count = swapchain_image_count;
+        self
+    }
+
+    /// Sets usage of swapchain images in addition to rendering into them.
+    ///
+    /// Renderer fails to initialize if the surface does not support requested usage.
+    /// Images could be copied from for screenshots whenever the surface allows it,
+    /// even without requested usage. Default is [`SwapchainUsage::NONE`].
+    ///
+    pub const fn with_swapchain_usage(mut self, swapchain_usage: SwapchainUsage) -> Self {
+        self.swapchain_usage = swapchain_usage;
+        self
+    }
+
+    /// Sets preferred way the compositor blends presented images with the contents behind the window.
+    ///
+    /// If this mode is not supported by the surface, it will be replaced by supported one.
+    /// Default is [`CompositeAlpha::Opaque`].
+    ///
+    pub const fn with_composite_alpha(mut self, composite_alpha: CompositeAlpha) -> Self {
+        self.composite_alpha = composite_alpha;
+        self
+    }
+
+    /// Sets how long rendering waits for the oldest frame in flight to be finished by the device.
+    ///
+    /// If the device is still busy when the timeout has elapsed, the new frame is skipped,
+    /// so the game loop is not blocked when the device is behind.
+    /// `None` means that rendering always waits. Default is `None`.
+    ///
+    pub const fn with_frame_wait_timeout(mut self, frame_wait_timeout: Option<Duration>) -> Self {
+        self.frame_wait_timeout = frame_wait_timeout;
+        self
+    }
+
+    /// Enables or disables depth buffer of the scene.
+    ///
+    /// Without depth buffer game objects are drawn in order of their indices,
+    /// which is enough for 2D games.
+    /// Default is `true`.
+    ///
+    pub const fn with_depth_buffer(mut self, depth_buffer: bool) -> Self {
+        self.depth_buffer = depth_buffer;
+        self
+    }
+
+    /// Sets count of samples per pixel for multisample anti-aliasing of the scene.
+    ///
+    /// Count is clamped to the highest count supported by the device,
+    /// `1` disables anti-aliasing. Default is `1`.
+    ///
+    pub const fn with_msaa_samples(mut self, msaa_samples: u32) -> Self {
+        self.msaa_samples = msaa_samples;
+        self
+    }
+
+    /// Sets if the scene is rendered offscreen and post processed before UI is drawn.
+    ///
+    /// Post processing costs one more pass over the whole final image,
+    /// its effect could be changed at runtime. Default is `false`.
+    ///
+    pub const fn with_post_processing(mut self, post_processing: bool) -> Self {
+        self.post_processing = post_processing;
+        self
+    }
+
+    /// Sets multiple of the rolling median of CPU or GPU frame time which frame must exceed
+    /// to be reported as a [spike](crate::window::Event::FrameSpike).
+    ///
+    /// `None` disables spike detection. Default is [`DEFAULT_SPIKE_THRESHOLD`].
+    ///
+    pub const fn with_spike_threshold(mut self, spike_threshold: Option<f32>) -> Self {
+        self.spike_threshold = spike_threshold;
+        self
+    }
+
+    /// Sets count of recent frames which average frame time and FPS
+    /// of [frame statistics](crate::app::stats::FrameStats) are computed from.
+    ///
+    /// Default is [`DEFAULT_STATS_WINDOW`].
+    ///
+    pub const fn with_stats_window(mut self, stats_window: usize) -> Self {
+        self.stats_window = stats_window;
+        self
+    }
+
+    /// Limits frame rate to provided count of frames per second.
+    ///
+    /// `None` means uncapped frame rate, which is default.
+    /// Limit could be changed later at runtime.
+    ///
+    pub const fn with_target_fps(mut self, target_fps: Option<u32>) -> Self {
+        self.target_fps = target_fps;
+        self
+    }
+
+    /// Enables or disables rendering while the window has no input focus.
+    ///
+    /// Default is `true`. Minimized windows are never rendered.
+    ///
+    pub const fn with_render_when_unfocused(mut self, render_when_unfocused: bool) -> Self {
+        self.render_when_unfocused = render_when_unfocused;
+        self
+    }
+
+    /// Sets duration of fixed step of simulation.
+    ///
+    /// Must not be zero. Default is [`DEFAULT_FIXED_TIMESTEP`].
+    ///
+    pub const fn with_fixed_timestep(mut self, fixed_timestep: Duration) -> Self {
+        self.fixed_timestep = fixed_timestep;
+        self
+    }
+
+    /// Sets policy of window size when scale factor of the window is changed.
+    pub const fn with_scale_factor_policy(
+        mut self,
+        scale_factor_policy: ScaleFactorPolicy,
+    ) -> Self {
+        self.scale_factor_policy = scale_factor_policy;
+        self
+    }
+
+    /// Sets directories which are searched for assets, in order.
+    ///
+    /// Directory of the executable and current working directory are searched after them.
+    ///
+    pub fn with_asset_roots(mut self, asset_roots: Vec<PathBuf>) -> Self {
+        self.asset_roots = asset_roots;
+        self
+    }
+
+    /// Sets application identifier which desktop environment associates the window with.
+    ///
+    /// It is used as `WM_CLASS` on X11 and as app ID on Wayland, and should match
+    /// the name of desktop entry of your game. Ignored on other platforms.
+    /// By default, the name of the executable is used.
+    ///
+    pub fn with_app_id(mut self, app_id: String) -> Self {
+        self.app_id = Some(app_id);
+        self
+    }
+
+    /// Sets application user model ID which Windows taskbar groups windows of your game by.
+    ///
+    /// Ignored on other platforms. By default, Windows derives it from the executable.
+    ///
+    pub fn with_windows_app_user_model_id(mut self, app_user_model_id: String) -> Self {
+        self.app_user_model_id = Some(app_user_model_id);
+        self
+    }
+
+    /// Sets path of the file which pipeline cache is loaded from and saved to.
+    ///
+    /// `None` disables persistence of pipeline cache.
+    /// By default, the cache is stored in the platform cache directory,
+    /// see [`default_cache_dir`](crate::pipeline_cache::default_cache_dir).
+    ///
+    pub fn with_pipeline_cache_path(mut self, pipeline_cache_path: Option<PathBuf>) -> Self {
+        self.pipeline_cache_path = Some(pipeline_cache_path);
+        self
+    }
+
+    /// Enables or disables [environment overrides](Self::apply_env_overrides)
+    /// which are applied on application initialization.
+    pub const fn with_env_overrides(mut self, env_overrides: bool) -> Self {
+        self.env_overrides = env_overrides;
+        self
+    }
+
+    /// Sets initial size of the client area of the window
+    /// in physical pixels (if [`Size`] is provided) or in [logical](WindowSize::Logical) pixels.
+    ///
+    /// Logical size is converted with the scale factor of the monitor when the window is created.
+    /// By default, the size is chosen by the platform.
+    ///
+    pub fn with_window_size(mut self, window_size: impl Into<WindowSize>) -> Self {
+        self.window_size = Some(window_size.into());
+        self
+    }
+
+    /// Sets title of the window. By default, the name of your game is used.
+    pub fn with_window_title(mut self, window_title: String) -> Self {
+        self.window_title = Some(window_title);
+        self
+    }
+
+    /// Sets if the window could be resized by the user. Default is `true`.
+    pub const fn with_resizable(mut self, resizable: bool) -> Self {
+        self.resizable = resizable;
+        self
+    }
+
+    /// Sets if the window has decorations (title bar, borders). Default is `true`.
+    pub const fn with_decorations(mut self, decorations: bool) -> Self {
+        self.decorations = decorations;
+        self
+    }
+
+    /// Sets if the window is initially maximized. Default is `false`.
+    pub const fn with_maximized(mut self, maximized: bool) -> Self {
+        self.maximized = maximized;
+        self
+    }
+
+    /// Sets icon of the window from RGBA pixel data with 8 bits per channel.
+    ///
+    /// Length of data must be `width * height * 4`, which is checked on [validation](Self::validate).
+    ///
+    pub fn with_window_icon(mut self, width: u32, height: u32, rgba: Vec<u8>) -> Self {
+        self.window_icon = Some(WindowIcon {
+            width,
+            height,
+            rgba,
+        });
+        self
+    }
+
+    /// Creates [builder](ConfigBuilder) of configuration with given name and version.
+    pub fn builder(name: impl Into<String>, version: Version) -> ConfigBuilder {
+        ConfigBuilder::new(name, version)
+    }
+
+    /// Sets severities and types of messages which are reported by validation layers.
+    ///
+    /// Default is [`DebugFilter::ALL`].
+    ///
+    pub const fn with_debug_filter(mut self, debug_filter: DebugFilter) -> Self {
+        self.debug_filter = debug_filter;
+        self
+    }
+
+    /// Sets callback which receives messages of validation layers.
+    ///
+    /// If the callback returns `true`, the message is not logged by the engine.
+    ///
+    pub fn with_debug_callback(
+        mut self,
+        debug_callback: impl Fn(DebugMessage) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.debug_callback = Some(DebugMessageCallback::new(debug_callback));
+        self
+    }
+
+    /// Checks the configuration for values which cannot be applied.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.name.is_empty() {
+            return Err(ConfigError::EmptyName);
+        }
+        if !self::fits_vk_version(&self.version) {
+            return Err(ConfigError::VersionOverflow(self.version.clone()));
+        }
+        if self.frames_in_flight == 0 {
+            return Err(ConfigError::ZeroFramesInFlight);
+        }
+        if self.fixed_timestep.is_zero() {
+            return Err(ConfigError::ZeroFixedTimestep);
+        }
+        if let Some(icon) = &self.window_icon {
+            icon.validate()?;
         }
+        Ok(())
     }
 
     /// Name of your game.
@@ -41,6 +733,190 @@ pub fn version(&self) -> &Version {
     pub fn enable_validation(&self) -> bool {
         self.enable_validation
     }
+
+    /// If game will present images from the dedicated thread.
+    ///
+    /// This can reduce time which main thread spends waiting for presentation,
+    /// but it is not supported on all platforms.
+    ///
+    pub fn present_thread(&self) -> bool {
+        self.present_thread
+    }
+
+    /// Preferred present mode, if any.
+    pub fn present_mode(&self) -> Option<PresentMode> {
+        self.present_mode
+    }
+
+    /// Surface formats in order of preference.
+    pub fn surface_formats(&self) -> &[SurfaceFormat] {
+        match self.surface_formats.as_slice() {
+            [] => DEFAULT_SURFACE_FORMATS,
+            surface_formats => surface_formats,
+        }
+    }
+
+    /// Initial color which the window is cleared with.
+    pub fn clear_color(&self) -> Color {
+        self.clear_color
+    }
+
+    /// Index of requested physical device (adapter), if any.
+    pub fn adapter(&self) -> Option<usize> {
+        match self.device_preference {
+            DevicePreference::ByIndex(index) => Some(index),
+            _ => None,
+        }
+    }
+
+    /// Features and extensions which are requested from the device.
+    pub fn device_requirements(&self) -> &DeviceRequirements {
+        &self.device_requirements
+    }
+
+    /// Preference of physical device which is used for rendering.
+    pub fn device_preference(&self) -> &DevicePreference {
+        &self.device_preference
+    }
+
+    /// Count of frames which could be processed by the device at the same time.
+    pub fn frames_in_flight(&self) -> u32 {
+        self.frames_in_flight
+    }
+
+    /// Desired count of swapchain images, if any.
+    pub fn swapchain_image_count(&self) -> Option<u32> {
+        self.swapchain_image_count
+    }
+
+    /// Usage of swapchain images in addition to rendering into them.
+    pub fn swapchain_usage(&self) -> SwapchainUsage {
+        self.swapchain_usage
+    }
+
+    /// Preferred way the compositor blends presented images with the contents behind the window.
+    pub fn composite_alpha(&self) -> CompositeAlpha {
+        self.composite_alpha
+    }
+
+    /// How long rendering waits for the oldest frame in flight, `None` if the wait is infinite.
+    pub fn frame_wait_timeout(&self) -> Option<Duration> {
+        self.frame_wait_timeout
+    }
+
+    /// If the scene is rendered with depth buffer.
+    pub fn depth_buffer(&self) -> bool {
+        self.depth_buffer
+    }
+
+    /// Requested count of samples per pixel for multisample anti-aliasing of the scene.
+    pub fn msaa_samples(&self) -> u32 {
+        self.msaa_samples
+    }
+
+    /// If the scene is post processed before UI is drawn.
+    pub fn post_processing(&self) -> bool {
+        self.post_processing
+    }
+
+    /// Count of recent frames which frame statistics are computed from.
+    pub fn stats_window(&self) -> usize {
+        self.stats_window
+    }
+
+    /// Target count of frames per second, if frame rate is limited.
+    pub fn target_fps(&self) -> Option<u32> {
+        self.target_fps
+    }
+
+    /// If the window is rendered while it has no input focus.
+    pub fn render_when_unfocused(&self) -> bool {
+        self.render_when_unfocused
+    }
+
+    /// Multiple of the rolling median of CPU or GPU frame time which frame must exceed to be a spike.
+    pub fn spike_threshold(&self) -> Option<f32> {
+        self.spike_threshold
+    }
+
+    /// Duration of fixed step of simulation.
+    pub fn fixed_timestep(&self) -> Duration {
+        self.fixed_timestep
+    }
+
+    /// Policy of window size when scale factor of the window is changed.
+    pub fn scale_factor_policy(&self) -> ScaleFactorPolicy {
+        self.scale_factor_policy
+    }
+
+    /// Directories which are searched for assets.
+    pub fn asset_roots(&self) -> &[PathBuf] {
+        &self.asset_roots
+    }
+
+    /// Application identifier of the window on X11 and Wayland, if any.
+    pub fn app_id(&self) -> Option<&str> {
+        self.app_id.as_deref()
+    }
+
+    /// Application user model ID of the window on Windows, if any.
+    pub fn windows_app_user_model_id(&self) -> Option<&str> {
+        self.app_user_model_id.as_deref()
+    }
+
+    /// Path of the file which pipeline cache is loaded from and saved to, if any.
+    pub fn pipeline_cache_path(&self) -> Option<PathBuf> {
+        match &self.pipeline_cache_path {
+            Some(path) => path.clone(),
+            None => pipeline_cache::default_cache_dir(&self.name)
+                .map(|dir| dir.join(PIPELINE_CACHE_FILE)),
+        }
+    }
+
+    /// If environment overrides will be applied on application initialization.
+    pub fn env_overrides(&self) -> bool {
+        self.env_overrides
+    }
+
+    /// Initial size of the window, if any.
+    pub fn window_size(&self) -> Option<WindowSize> {
+        self.window_size
+    }
+
+    /// Title of the window.
+    pub fn window_title(&self) -> &str {
+        self.window_title.as_deref().unwrap_or(&self.name)
+    }
+
+    /// If the window could be resized by the user.
+    pub fn resizable(&self) -> bool {
+        self.resizable
+    }
+
+    /// If the window has decorations.
+    pub fn decorations(&self) -> bool {
+        self.decorations
+    }
+
+    /// If the window is initially maximized.
+    pub fn maximized(&self) -> bool {
+        self.maximized
+    }
+
+    /// Icon of the window, if any.
+    pub fn window_icon(&self) -> Option<&WindowIcon> {
+        self.window_icon.as_ref()
+    }
+
+    /// Filter of messages of validation layers.
+    pub fn debug_filter(&self) -> DebugFilter {
+        self.debug_filter
+    }
+
+    /// Callback which receives messages of validation layers, if any.
+    pub fn debug_callback(&self) -> Option<&DebugMessageCallback> {
+        self.debug_callback.as_ref()
+    }
 }
 
 impl Default for Config {
@@ -0,0 +1,451 @@
+//! Persistent user settings of graphics which are negotiated with capabilities of the system.
+//!
+//! On the first run, settings are chosen from probed capabilities and written to the file.
+//! On subsequent runs, stored settings are validated against current capabilities,
+//! because hardware may have changed, and invalid entries are repaired.
+//!
+//! Settings file consists of `key = value` lines and `#` comments, for example:
+//!
+//! ```text
+//! version = 1
+//! adapter = auto
+//! msaa_samples = 4
+//! present_mode = fifo
+//! depth_buffer = true
+//! ```
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+use vulkano::instance::InstanceCreationError;
+
+use super::env::{parse_bool, parse_present_mode};
+use super::{Config, DevicePreference, PresentMode};
+
+mod tests;
+
+/// Current version of the schema of settings file.
+pub const SETTINGS_VERSION: u32 = 1;
+
+/// Name of user settings file in the configuration directory of your game.
+pub const SETTINGS_FILE: &str = "settings.cfg";
+
+/// Sample count which is chosen by default if it is supported.
+const DEFAULT_MSAA_SAMPLES: u32 = 4;
+
+const VERSION: &str = "version";
+const ADAPTER: &str = "adapter";
+const MSAA_SAMPLES: &str = "msaa_samples";
+const PRESENT_MODE: &str = "present_mode";
+const DEPTH_BUFFER: &str = "depth_buffer";
+
+/// Entries of settings file in order of their appearance.
+pub type Entries = Vec<(String, String)>;
+
+/// Migration of entries of settings file from one version of the schema to the next one.
+pub type Migration = fn(&mut Entries);
+
+/// Migrations of settings file: migration at index `i` upgrades version `i + 1` to `i + 2`.
+const MIGRATIONS: &[Migration] = &[];
+
+/// Capabilities of the physical device (adapter) which settings depend on.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AdapterCapabilities {
+    /// Name of the adapter.
+    pub name: String,
+    /// Highest count of samples per pixel which is supported for the scene.
+    pub max_msaa_samples: u32,
+}
+
+/// Capabilities of the system which settings are validated against.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct GraphicsCapabilities {
+    /// Adapters of the system in order of their indices.
+    pub adapters: Vec<AdapterCapabilities>,
+    /// Supported present modes, or `None` if they are not known without a window.
+    pub present_modes: Option<Vec<PresentMode>>,
+    /// If images could be presented from the
+    /// [dedicated thread](crate::config::Config::with_present_thread) on this platform.
+    pub present_thread: bool,
+}
+
+impl GraphicsCapabilities {
+    /// Highest sample count which is supported by provided adapter, or the first one if `None`.
+    fn max_msaa_samples(&self, adapter: Option<usize>) -> u32 {
+        self.adapters
+            .get(adapter.unwrap_or(0))
+            .map_or(1, |adapter| adapter.max_msaa_samples.max(1))
+    }
+
+    fn supports_present_mode(&self, present_mode: PresentMode) -> bool {
+        match &self.present_modes {
+            Some(present_modes) => present_modes.contains(&present_mode),
+            None => true,
+        }
+    }
+}
+
+/// User settings of graphics.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct UserSettings {
+    /// Index of preferred adapter, or `None` to let the engine choose it.
+    pub adapter: Option<usize>,
+    /// Count of samples per pixel for multisample anti-aliasing.
+    pub msaa_samples: u32,
+    /// Preferred present mode, or `None` to let the engine choose it.
+    pub present_mode: Option<PresentMode>,
+    /// If the scene is rendered with depth buffer.
+    pub depth_buffer: bool,
+}
+
+/// Setting whose stored value was replaced because it is invalid for the system.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Substitution {
+    /// Key of the setting.
+    pub key: &'static str,
+    /// Stored value of the setting.
+    pub stored: String,
+    /// Value which is used instead.
+    pub used: String,
+}
+
+impl fmt::Display for Substitution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} = {} replaced by {}",
+            self.key, self.stored, self.used
+        )
+    }
+}
+
+/// Result of negotiation of user settings.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Negotiated {
+    /// Settings which are valid for the system.
+    pub settings: UserSettings,
+    /// Stored settings which were repaired.
+    pub substitutions: Vec<Substitution>,
+    /// If settings file must be written again, because it is missing, outdated or repaired.
+    pub rewrite: bool,
+}
+
+/// Error that can happen when loading user settings.
+#[derive(Debug, Error)]
+pub enum SettingsError {
+    #[error("settings file access failure: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("capabilities probing failure: {0}")]
+    Probe(#[from] InstanceCreationError),
+
+    #[error("settings version {0} is newer than supported version {SETTINGS_VERSION}")]
+    UnsupportedVersion(u32),
+}
+
+impl UserSettings {
+    /// Chooses sensible settings for provided capabilities.
+    pub fn negotiate(capabilities: &GraphicsCapabilities) -> Self {
+        let present_mode = capabilities.present_modes.as_ref().map(|_| {
+            match capabilities.supports_present_mode(PresentMode::Mailbox) {
+                true => PresentMode::Mailbox,
+                false => PresentMode::Fifo,
+            }
+        });
+        Self {
+            adapter: None,
+            msaa_samples: DEFAULT_MSAA_SAMPLES.min(capabilities.max_msaa_samples(None)),
+            present_mode,
+            depth_buffer: true,
+        }
+    }
+
+    /// Loads settings from the contents of settings file, if any,
+    /// and repairs them for provided capabilities.
+    ///
+    /// Missing or malformed values are replaced by [negotiated](Self::negotiate) ones.
+    ///
+    pub fn resolve(
+        contents: Option<&str>,
+        capabilities: &GraphicsCapabilities,
+    ) -> Result<Negotiated, SettingsError> {
+        let defaults = Self::negotiate(capabilities);
+        let contents = match contents {
+            Some(contents) => contents,
+            None => {
+                return Ok(Negotiated {
+                    settings: defaults,
+                    substitutions: Vec::new(),
+                    rewrite: true,
+                })
+            }
+        };
+
+        let mut entries = self::parse_entries(contents);
+        let version = self::take(&mut entries, VERSION).and_then(|value| value.parse().ok());
+        let migrated = self::migrate(&mut entries, version.unwrap_or(1), MIGRATIONS)?;
+
+        let mut substitutions = Vec::new();
+        let mut setting =
+            |key, default: String, parse: &dyn Fn(&str) -> Option<String>| match self::take(
+                &mut entries,
+                key,
+            ) {
+                Some(stored) => parse(&stored).unwrap_or_else(|| {
+                    substitutions.push(Substitution {
+                        key,
+                        stored,
+                        used: default.clone(),
+                    });
+                    default
+                }),
+                None => default,
+            };
+        let adapter = setting(ADAPTER, self::format_auto(defaults.adapter), &|value| {
+            self::parse_auto(value, |value| value.parse::<usize>().ok()).map(self::format_auto)
+        });
+        let msaa_samples = setting(MSAA_SAMPLES, defaults.msaa_samples.to_string(), &|value| {
+            value.parse::<u32>().ok().map(|value| value.to_string())
+        });
+        let present_mode = setting(
+            PRESENT_MODE,
+            self::format_auto(defaults.present_mode.map(present_mode_name)),
+            &|value| {
+                self::parse_auto(value, parse_present_mode)
+                    .map(|mode| self::format_auto(mode.map(present_mode_name)))
+            },
+        );
+        let depth_buffer = setting(DEPTH_BUFFER, defaults.depth_buffer.to_string(), &|value| {
+            parse_bool(value).map(|value| value.to_string())
+        });
+
+        // All values were normalized above, so they are parsed back without failure.
+        let mut settings = Self {
+            adapter: self::parse_auto(&adapter, |value| value.parse().ok()).flatten(),
+            msaa_samples: msaa_samples.parse().unwrap_or(defaults.msaa_samples),
+            present_mode: self::parse_auto(&present_mode, parse_present_mode).flatten(),
+            depth_buffer: parse_bool(&depth_buffer).unwrap_or(defaults.depth_buffer),
+        };
+        substitutions.extend(settings.repair(capabilities));
+        for (key, _) in &entries {
+            log::warn!("unknown user setting {} is ignored", key);
+        }
+        let rewrite = migrated || version.is_none() || !substitutions.is_empty();
+        Ok(Negotiated {
+            settings,
+            substitutions,
+            rewrite,
+        })
+    }
+
+    /// Replaces settings which are not supported by provided capabilities.
+    ///
+    /// Returns substitutions which were made.
+    ///
+    pub fn repair(&mut self, capabilities: &GraphicsCapabilities) -> Vec<Substitution> {
+        let mut substitutions = Vec::new();
+        if let Some(adapter) = self.adapter {
+            if adapter >= capabilities.adapters.len() {
+                self.adapter = None;
+                substitutions.push(Substitution {
+                    key: ADAPTER,
+                    stored: adapter.to_string(),
+                    used: self::format_auto(self.adapter),
+                });
+            }
+        }
+
+        let max = capabilities.max_msaa_samples(self.adapter);
+        let msaa_samples = self::highest_power_of_two(self.msaa_samples.clamp(1, max));
+        if msaa_samples != self.msaa_samples {
+            substitutions.push(Substitution {
+                key: MSAA_SAMPLES,
+                stored: self.msaa_samples.to_string(),
+                used: msaa_samples.to_string(),
+            });
+            self.msaa_samples = msaa_samples;
+        }
+
+        if let Some(present_mode) = self.present_mode {
+            if !capabilities.supports_present_mode(present_mode) {
+                self.present_mode = None;
+                substitutions.push(Substitution {
+                    key: PRESENT_MODE,
+                    stored: present_mode_name(present_mode).to_string(),
+                    used: self::format_auto(self.present_mode.map(present_mode_name)),
+                });
+            }
+        }
+        substitutions
+    }
+
+    /// Contents of settings file with these settings.
+    pub fn to_file_contents(&self) -> String {
+        format!(
+            "# Graphics settings, generated by {engine}.\n\
+             {VERSION} = {SETTINGS_VERSION}\n\
+             {ADAPTER} = {adapter}\n\
+             {MSAA_SAMPLES} = {msaa_samples}\n\
+             {PRESENT_MODE} = {present_mode}\n\
+             {DEPTH_BUFFER} = {depth_buffer}\n",
+            engine = super::ENGINE_NAME,
+            adapter = self::format_auto(self.adapter),
+            msaa_samples = self.msaa_samples,
+            present_mode = self::format_auto(self.present_mode.map(present_mode_name)),
+            depth_buffer = self.depth_buffer,
+        )
+    }
+}
+
+impl Config {
+    /// Probes capabilities of the system with this configuration.
+    ///
+    /// Creates temporary instance without a window, so supported present modes remain unknown.
+    ///
+    pub fn probe_capabilities(&self) -> Result<GraphicsCapabilities, InstanceCreationError> {
+        crate::graphics::probe_capabilities(self)
+    }
+
+    /// Loads user settings from the file with provided path, or initializes it on the first run,
+    /// and applies them to this configuration.
+    ///
+    /// Capabilities of the system are probed before the renderer is created,
+    /// stored settings are validated against them, and invalid entries are repaired
+    /// with a warning. File is written again if it is missing, outdated or was repaired.
+    ///
+    pub fn load_or_init_user_settings(
+        &mut self,
+        path: impl AsRef<Path>,
+    ) -> Result<UserSettings, SettingsError> {
+        let path = path.as_ref();
+        let capabilities = crate::graphics::probe_capabilities(self)?;
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => Some(contents),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => None,
+            Err(error) => return Err(error.into()),
+        };
+        let negotiated = UserSettings::resolve(contents.as_deref(), &capabilities)?;
+        for substitution in &negotiated.substitutions {
+            log::warn!("user setting is substituted: {}", substitution);
+        }
+        if negotiated.rewrite {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, negotiated.settings.to_file_contents())?;
+            log::info!("user settings were written to {}", path.display());
+        }
+
+        let settings = negotiated.settings;
+        // Automatic adapter of user settings keeps the preference of your game.
+        if let Some(adapter) = settings.adapter {
+            self.device_preference = DevicePreference::ByIndex(adapter);
+        }
+        self.msaa_samples = settings.msaa_samples;
+        self.present_mode = settings.present_mode;
+        self.depth_buffer = settings.depth_buffer;
+        Ok(settings)
+    }
+}
+
+/// Default path of user settings file of your game with provided name.
+///
+/// It is in the platform configuration directory: `$XDG_CONFIG_HOME` or `~/.config` on Linux,
+/// `~/Library/Application Support` on macOS and `%APPDATA%` on Windows.
+///
+pub fn default_settings_path(name: &str) -> Option<PathBuf> {
+    let var = |key: &str| std::env::var_os(key).filter(|value| !value.is_empty());
+    let base = if cfg!(windows) {
+        var("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        var("HOME").map(|home| Path::new(&home).join("Library").join("Application Support"))
+    } else {
+        var("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| var("HOME").map(|home| Path::new(&home).join(".config")))
+    };
+    Some(base?.join(name).join(SETTINGS_FILE))
+}
+
+/// Upgrades entries of settings file of provided version to the current version.
+///
+/// Returns `true` if any migration was applied.
+///
+pub fn migrate(
+    entries: &mut Entries,
+    version: u32,
+    migrations: &[Migration],
+) -> Result<bool, SettingsError> {
+    let current = migrations.len() as u32 + 1;
+    if version > current {
+        return Err(SettingsError::UnsupportedVersion(version));
+    }
+    let pending = &migrations[version.saturating_sub(1) as usize..];
+    for migration in pending {
+        migration(entries);
+    }
+    Ok(!pending.is_empty())
+}
+
+/// Parses `key = value` lines of settings file, skipping comments and malformed lines.
+fn parse_entries(contents: &str) -> Entries {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let entry = line.split_once('=');
+            if entry.is_none() {
+                log::warn!("malformed user settings line is ignored: {:?}", line);
+            }
+            let (key, value) = entry?;
+            Some((key.trim().to_string(), value.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Removes all entries with provided key, returning the value of the last one.
+fn take(entries: &mut Entries, key: &str) -> Option<String> {
+    let mut value = None;
+    entries.retain(|(entry, entry_value)| {
+        let matches = entry == key;
+        if matches {
+            value = Some(entry_value.clone());
+        }
+        !matches
+    });
+    value
+}
+
+/// Parses value which could be `auto`, meaning `None`.
+fn parse_auto<T>(value: &str, parse: impl Fn(&str) -> Option<T>) -> Option<Option<T>> {
+    match value.eq_ignore_ascii_case("auto") {
+        true => Some(None),
+        false => parse(value).map(Some),
+    }
+}
+
+/// Formats value which could be `None`, meaning `auto`.
+fn format_auto(value: Option<impl ToString>) -> String {
+    value.map_or_else(|| "auto".to_string(), |value| value.to_string())
+}
+
+fn present_mode_name(present_mode: PresentMode) -> &'static str {
+    match present_mode {
+        PresentMode::Immediate => "immediate",
+        PresentMode::Mailbox => "mailbox",
+        PresentMode::Fifo => "fifo",
+        PresentMode::FifoRelaxed => "fifo_relaxed",
+    }
+}
+
+/// Highest power of two which is not greater than provided value (at least one).
+fn highest_power_of_two(value: u32) -> u32 {
+    match value {
+        0 => 1,
+        value => 1 << (u32::BITS - 1 - value.leading_zeros()),
+    }
+}
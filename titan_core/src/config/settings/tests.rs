@@ -0,0 +1,100 @@
+#![cfg(test)]
+
+use super::*;
+
+fn capabilities(max_msaa_samples: &[u32]) -> GraphicsCapabilities {
+    let adapters = max_msaa_samples
+        .iter()
+        .enumerate()
+        .map(|(index, &max_msaa_samples)| AdapterCapabilities {
+            name: format!("adapter {}", index),
+            max_msaa_samples,
+        })
+        .collect();
+    GraphicsCapabilities {
+        adapters,
+        present_modes: None,
+        present_thread: true,
+    }
+}
+
+#[test]
+fn first_run_negotiates_and_writes_settings() {
+    let capabilities = self::capabilities(&[2]);
+    let negotiated = UserSettings::resolve(None, &capabilities).unwrap();
+    assert!(negotiated.rewrite);
+    assert!(negotiated.substitutions.is_empty());
+    assert_eq!(negotiated.settings.msaa_samples, 2);
+    assert_eq!(negotiated.settings.adapter, None);
+    assert_eq!(negotiated.settings.present_mode, None);
+}
+
+#[test]
+fn negotiation_prefers_mailbox_when_supported() {
+    let mut capabilities = self::capabilities(&[8]);
+    capabilities.present_modes = Some(vec![PresentMode::Fifo, PresentMode::Mailbox]);
+    let settings = UserSettings::negotiate(&capabilities);
+    assert_eq!(settings.present_mode, Some(PresentMode::Mailbox));
+    assert_eq!(settings.msaa_samples, 4);
+
+    capabilities.present_modes = Some(vec![PresentMode::Fifo]);
+    let settings = UserSettings::negotiate(&capabilities);
+    assert_eq!(settings.present_mode, Some(PresentMode::Fifo));
+}
+
+#[test]
+fn settings_round_trip_through_file() {
+    let capabilities = self::capabilities(&[8, 8]);
+    let settings = UserSettings {
+        adapter: Some(1),
+        msaa_samples: 8,
+        present_mode: Some(PresentMode::FifoRelaxed),
+        depth_buffer: false,
+    };
+    let contents = settings.to_file_contents();
+    let negotiated = UserSettings::resolve(Some(&contents), &capabilities).unwrap();
+    assert_eq!(negotiated.settings, settings);
+    assert!(!negotiated.rewrite);
+}
+
+#[test]
+fn unsupported_settings_are_repaired() {
+    let capabilities = self::capabilities(&[4]);
+    let contents = "version = 1\nadapter = 3\nmsaa_samples = 16\npresent_mode = bogus\n";
+    let negotiated = UserSettings::resolve(Some(contents), &capabilities).unwrap();
+    assert!(negotiated.rewrite);
+    assert_eq!(negotiated.settings.adapter, None);
+    assert_eq!(negotiated.settings.msaa_samples, 4);
+    assert_eq!(negotiated.settings.present_mode, None);
+    let keys: Vec<_> = negotiated.substitutions.iter().map(|s| s.key).collect();
+    assert_eq!(keys, [PRESENT_MODE, ADAPTER, MSAA_SAMPLES]);
+}
+
+#[test]
+fn sample_count_is_rounded_down_to_power_of_two() {
+    let mut settings = UserSettings::negotiate(&self::capabilities(&[8]));
+    settings.msaa_samples = 6;
+    let substitutions = settings.repair(&self::capabilities(&[8]));
+    assert_eq!(settings.msaa_samples, 4);
+    assert_eq!(substitutions.len(), 1);
+}
+
+#[test]
+fn migrations_upgrade_older_versions() {
+    fn rename(entries: &mut Entries) {
+        for (key, _) in entries.iter_mut() {
+            if key == "samples" {
+                *key = MSAA_SAMPLES.to_string();
+            }
+        }
+    }
+    let migrations: &[Migration] = &[rename];
+    let mut entries = vec![("samples".to_string(), "2".to_string())];
+    assert!(migrate(&mut entries, 1, migrations).unwrap());
+    assert_eq!(entries[0].0, MSAA_SAMPLES);
+    assert!(!migrate(&mut entries, 2, migrations).unwrap());
+    assert!(matches!(
+        migrate(&mut entries, 3, migrations),
+        Err(SettingsError::UnsupportedVersion(3)),
+    ));
+}
@@ -0,0 +1,355 @@
+#![cfg(test)]
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use semver::Version;
+
+use super::env::{parse_bool, parse_device_preference, parse_frames_in_flight, parse_present_mode};
+use super::{
+    fits_vk_version, CompositeAlpha, Config, ConfigError, DevicePreference, PresentMode,
+    ScaleFactorPolicy, SurfaceFormat, SwapchainUsage, WindowSize, DEFAULT_SPIKE_THRESHOLD,
+    DEFAULT_SURFACE_FORMATS, ENGINE_VERSION,
+};
+use crate::color::Color;
+use crate::window::Size;
+
+#[test]
+fn parse_bool_values() {
+    for value in ["1", "true", "ON", "Yes"] {
+        assert_eq!(parse_bool(value), Some(true), "{}", value);
+    }
+    for value in ["0", "false", "Off", "NO"] {
+        assert_eq!(parse_bool(value), Some(false), "{}", value);
+    }
+    assert_eq!(parse_bool("2"), None);
+    assert_eq!(parse_bool(""), None);
+}
+
+#[test]
+fn parse_present_mode_values() {
+    assert_eq!(
+        parse_present_mode("immediate"),
+        Some(PresentMode::Immediate)
+    );
+    assert_eq!(parse_present_mode("Mailbox"), Some(PresentMode::Mailbox));
+    assert_eq!(parse_present_mode("fifo"), Some(PresentMode::Fifo));
+    assert_eq!(
+        parse_present_mode("fifo_relaxed"),
+        Some(PresentMode::FifoRelaxed),
+    );
+    assert_eq!(parse_present_mode("tearing"), None);
+}
+
+#[test]
+fn parse_frames_in_flight_values() {
+    assert_eq!(parse_frames_in_flight("2"), Some(2));
+    assert_eq!(parse_frames_in_flight("0"), None);
+    assert_eq!(parse_frames_in_flight("two"), None);
+}
+
+#[test]
+fn parse_device_preference_values() {
+    assert_eq!(
+        parse_device_preference("Discrete"),
+        Some(DevicePreference::PreferDiscrete)
+    );
+    assert_eq!(
+        parse_device_preference("integrated"),
+        Some(DevicePreference::PreferIntegrated)
+    );
+    assert_eq!(
+        parse_device_preference("auto"),
+        Some(DevicePreference::Default)
+    );
+    assert_eq!(
+        parse_device_preference("2"),
+        Some(DevicePreference::ByIndex(2))
+    );
+    assert_eq!(
+        parse_device_preference("GeForce"),
+        Some(DevicePreference::ByNameSubstring("GeForce".to_string()))
+    );
+    assert_eq!(parse_device_preference(""), None);
+}
+
+#[test]
+fn env_overrides_code() {
+    let mut config = Config::default()
+        .with_validation(false)
+        .with_present_mode(PresentMode::Fifo)
+        .with_adapter(0);
+    let applied = config.apply_overrides([
+        ("TITAN_VALIDATION", "1"),
+        ("TITAN_PRESENT_MODE", "immediate"),
+        ("TITAN_ADAPTER", " 1 "),
+        ("TITAN_PRESENT_THREAD", "yes"),
+    ]);
+
+    assert_eq!(
+        applied,
+        [
+            "TITAN_VALIDATION",
+            "TITAN_PRESENT_MODE",
+            "TITAN_ADAPTER",
+            "TITAN_PRESENT_THREAD",
+        ],
+    );
+    assert!(config.enable_validation());
+    assert_eq!(config.present_mode(), Some(PresentMode::Immediate));
+    assert_eq!(config.adapter(), Some(1));
+    assert!(config.present_thread());
+}
+
+#[test]
+fn malformed_values_are_ignored() {
+    let mut config = Config::default()
+        .with_validation(true)
+        .with_present_mode(PresentMode::Mailbox);
+    let applied = config.apply_overrides([
+        ("TITAN_VALIDATION", "maybe"),
+        ("TITAN_PRESENT_MODE", "tearing"),
+        ("TITAN_ADAPTER", "-1"),
+        ("TITAN_UNKNOWN", "1"),
+    ]);
+
+    assert!(applied.is_empty());
+    assert!(config.enable_validation());
+    assert_eq!(config.present_mode(), Some(PresentMode::Mailbox));
+    assert_eq!(config.adapter(), None);
+}
+
+#[test]
+fn gpu_takes_precedence_over_adapter() {
+    let orders = [
+        [("TITAN_ADAPTER", "1"), ("TITAN_GPU", "discrete")],
+        [("TITAN_GPU", "discrete"), ("TITAN_ADAPTER", "1")],
+    ];
+    for vars in orders {
+        let mut config = Config::default();
+        let applied = config.apply_overrides(vars);
+
+        assert_eq!(applied, ["TITAN_GPU"], "{:?}", vars);
+        assert_eq!(
+            config.device_preference(),
+            &DevicePreference::PreferDiscrete,
+            "{:?}",
+            vars,
+        );
+    }
+}
+
+#[test]
+fn adapter_is_applied_if_gpu_is_malformed() {
+    let mut config = Config::default();
+    let applied = config.apply_overrides([("TITAN_GPU", " "), ("TITAN_ADAPTER", "1")]);
+
+    assert_eq!(applied, ["TITAN_ADAPTER"]);
+    assert_eq!(config.adapter(), Some(1));
+}
+
+#[test]
+fn unrelated_variables_are_skipped() {
+    let mut config = Config::default().with_validation(false);
+    let applied = config.apply_overrides([("PATH", "/usr/bin"), ("VALIDATION", "1")]);
+
+    assert!(applied.is_empty());
+    assert!(!config.enable_validation());
+}
+
+#[test]
+fn scale_factor_policy_resolves_size() {
+    let suggested = Size::new(1600, 1200);
+    let current = Size::new(800, 600);
+    let keep_logical = ScaleFactorPolicy::KeepLogicalSize.resolve(suggested, current);
+    let keep_physical = ScaleFactorPolicy::KeepPhysicalSize.resolve(suggested, current);
+    assert_eq!(<[u32; 2]>::from(keep_logical), [1600, 1200]);
+    assert_eq!(<[u32; 2]>::from(keep_physical), [800, 600]);
+    assert_eq!(
+        Config::default().scale_factor_policy(),
+        ScaleFactorPolicy::KeepLogicalSize,
+    );
+}
+
+#[test]
+fn depth_buffer_is_enabled_by_default() {
+    let config = Config::default();
+    assert!(config.depth_buffer());
+    assert!(!config.with_depth_buffer(false).depth_buffer());
+}
+
+#[test]
+fn post_processing_is_disabled_by_default() {
+    let config = Config::default();
+    assert!(!config.post_processing());
+    assert!(config.with_post_processing(true).post_processing());
+}
+
+#[test]
+fn multisampling_is_disabled_by_default() {
+    let config = Config::default();
+    assert_eq!(config.msaa_samples(), 1);
+    assert_eq!(config.with_msaa_samples(4).msaa_samples(), 4);
+}
+
+#[test]
+fn frame_wait_is_infinite_by_default() {
+    let config = Config::default();
+    assert_eq!(config.frame_wait_timeout(), None);
+    let timeout = Some(Duration::from_millis(5));
+    let config = config.with_frame_wait_timeout(timeout);
+    assert_eq!(config.frame_wait_timeout(), timeout);
+}
+
+#[test]
+fn spike_detection_is_enabled_by_default() {
+    let config = Config::default();
+    assert_eq!(config.spike_threshold(), Some(DEFAULT_SPIKE_THRESHOLD));
+    assert_eq!(config.with_spike_threshold(None).spike_threshold(), None);
+}
+
+#[test]
+fn desktop_integration_is_unset_by_default() {
+    let config = Config::default();
+    assert_eq!(config.app_id(), None);
+    assert_eq!(config.windows_app_user_model_id(), None);
+    let config = config
+        .with_app_id("org.titan.Game".to_string())
+        .with_windows_app_user_model_id("Titan.Game".to_string());
+    assert_eq!(config.app_id(), Some("org.titan.Game"));
+    assert_eq!(config.windows_app_user_model_id(), Some("Titan.Game"));
+}
+
+#[test]
+fn pipeline_cache_path_could_be_disabled() {
+    let path = PathBuf::from("cache/pipelines.bin");
+    let config = Config::default().with_pipeline_cache_path(Some(path.clone()));
+    assert_eq!(config.pipeline_cache_path(), Some(path));
+    let config = config.with_pipeline_cache_path(None);
+    assert_eq!(config.pipeline_cache_path(), None);
+}
+
+#[test]
+fn surface_formats_prefer_srgb_by_default() {
+    let config = Config::default();
+    assert_eq!(config.surface_formats(), DEFAULT_SURFACE_FORMATS);
+    let config = config.with_surface_formats(vec![SurfaceFormat::Hdr10, SurfaceFormat::Srgb]);
+    assert_eq!(
+        config.surface_formats(),
+        [SurfaceFormat::Hdr10, SurfaceFormat::Srgb]
+    );
+}
+
+#[test]
+fn clear_color_is_black_by_default() {
+    let config = Config::default();
+    assert_eq!(config.clear_color(), Color::BLACK);
+    assert_eq!(
+        config.with_clear_color(Color::WHITE).clear_color(),
+        Color::WHITE
+    );
+}
+
+#[test]
+fn window_title_defaults_to_name() {
+    let config = Config::default();
+    assert_eq!(config.window_title(), config.name());
+    let config = config.with_window_title("Custom".to_string());
+    assert_eq!(config.window_title(), "Custom");
+}
+
+#[test]
+fn window_icon_with_mismatched_length_is_rejected() {
+    let config = Config::default().with_window_icon(2, 2, vec![0; 16]);
+    assert!(config.validate().is_ok());
+    let config = Config::default().with_window_icon(2, 2, vec![0; 15]);
+    assert!(matches!(
+        config.validate(),
+        Err(ConfigError::InvalidWindowIcon {
+            width: 2,
+            height: 2,
+            len: 15
+        })
+    ));
+}
+
+#[test]
+fn vk_version_limits() {
+    assert!(fits_vk_version(&Version::new(1023, 1023, 4095)));
+    assert!(!fits_vk_version(&Version::new(1024, 0, 0)));
+    assert!(!fits_vk_version(&Version::new(0, 1024, 0)));
+    assert!(!fits_vk_version(&Version::new(0, 0, 4096)));
+}
+
+#[test]
+fn builder_validates_configuration() {
+    let config = Config::builder("game", Version::new(1, 2, 3))
+        .window_title(String::from("Game"))
+        .configure(|config| config.with_depth_buffer(false))
+        .build()
+        .unwrap();
+    assert_eq!(config.name(), "game");
+    assert_eq!(config.window_title(), "Game");
+    assert!(!config.depth_buffer());
+
+    let result = Config::builder("", Version::new(1, 0, 0)).build();
+    assert!(matches!(result, Err(ConfigError::EmptyName)));
+    let result = Config::builder("game", Version::new(0, 0, 5000)).build();
+    assert!(matches!(result, Err(ConfigError::VersionOverflow(_))));
+    let result = Config::builder("game", Version::new(1, 0, 0))
+        .frames_in_flight(0)
+        .build();
+    assert!(matches!(result, Err(ConfigError::ZeroFramesInFlight)));
+    let result = Config::builder("game", Version::new(1, 0, 0))
+        .configure(|config| config.with_fixed_timestep(Duration::ZERO))
+        .build();
+    assert!(matches!(result, Err(ConfigError::ZeroFixedTimestep)));
+}
+
+#[test]
+fn engine_version_matches_package() {
+    let major: u64 = env!("CARGO_PKG_VERSION_MAJOR").parse().unwrap();
+    let minor: u64 = env!("CARGO_PKG_VERSION_MINOR").parse().unwrap();
+    let patch: u64 = env!("CARGO_PKG_VERSION_PATCH").parse().unwrap();
+    assert_eq!(
+        (
+            ENGINE_VERSION.major,
+            ENGINE_VERSION.minor,
+            ENGINE_VERSION.patch
+        ),
+        (major, minor, patch)
+    );
+    assert!(fits_vk_version(&ENGINE_VERSION));
+}
+
+#[test]
+fn swapchain_defaults() {
+    let config = Config::default();
+    assert_eq!(config.swapchain_image_count(), None);
+    assert_eq!(config.swapchain_usage(), SwapchainUsage::NONE);
+    assert_eq!(config.composite_alpha(), CompositeAlpha::Opaque);
+}
+
+#[test]
+fn swapchain_usage_difference() {
+    let requested = SwapchainUsage {
+        transfer_src: true,
+        transfer_dst: true,
+    };
+    let supported = SwapchainUsage {
+        transfer_src: true,
+        transfer_dst: false,
+    };
+    let missing = requested.difference(supported);
+    assert!(!missing.transfer_src && missing.transfer_dst);
+    assert_eq!(requested.difference(requested), SwapchainUsage::NONE);
+}
+
+#[test]
+fn logical_window_size_is_scaled() {
+    let size = WindowSize::Logical(Size::new(1280, 720));
+    assert_eq!(size.to_physical(1.25), Size::new(1600, 900));
+    assert_eq!(size.to_physical(1.5), Size::new(1920, 1080));
+    let size = WindowSize::from(Size::new(1280, 720));
+    assert_eq!(size.to_physical(1.5), Size::new(1280, 720));
+}
@@ -0,0 +1,314 @@
+//! Selection of physical device (adapter) which is used for rendering.
+
+use std::fmt;
+
+use semver::Version;
+use thiserror::Error;
+use vulkano::device::physical::{PhysicalDevice, PhysicalDeviceType};
+
+use crate::config::DevicePreference;
+use crate::graphics::utils;
+
+pub use self::requirements::{
+    DeviceFeatures, DeviceRequirements, EnabledCapabilities, MissingRequirementsError,
+};
+
+mod requirements;
+mod tests;
+
+/// Type of physical device.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DeviceType {
+    Discrete,
+    Integrated,
+    Virtual,
+    Cpu,
+    Other,
+}
+
+impl DeviceType {
+    /// Rank of the device type when the engine chooses the device by itself.
+    const fn rank(self) -> u8 {
+        match self {
+            Self::Discrete => 4,
+            Self::Integrated => 3,
+            Self::Virtual => 2,
+            Self::Cpu => 1,
+            Self::Other => 0,
+        }
+    }
+}
+
+impl From<PhysicalDeviceType> for DeviceType {
+    fn from(device_type: PhysicalDeviceType) -> Self {
+        match device_type {
+            PhysicalDeviceType::DiscreteGpu => Self::Discrete,
+            PhysicalDeviceType::IntegratedGpu => Self::Integrated,
+            PhysicalDeviceType::VirtualGpu => Self::Virtual,
+            PhysicalDeviceType::Cpu => Self::Cpu,
+            PhysicalDeviceType::Other => Self::Other,
+        }
+    }
+}
+
+impl fmt::Display for DeviceType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Discrete => "discrete",
+            Self::Integrated => "integrated",
+            Self::Virtual => "virtual",
+            Self::Cpu => "CPU",
+            Self::Other => "other",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Description of physical device which is suitable for rendering.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PhysicalDeviceInfo {
+    /// Index of the device in the list of all physical devices.
+    pub index: usize,
+    pub name: String,
+    pub device_type: DeviceType,
+    /// Vendor-specific version of the driver.
+    pub driver_version: u32,
+    pub api_version: Version,
+    /// Size of device local memory in bytes.
+    pub vram: u64,
+}
+
+impl PhysicalDeviceInfo {
+    /// Describes provided physical device.
+    pub(crate) fn new(physical_device: PhysicalDevice) -> Self {
+        let properties = physical_device.properties();
+        let api_version = physical_device.api_version();
+        let vram = physical_device
+            .memory_heaps()
+            .filter(|heap| heap.is_device_local())
+            .map(|heap| heap.size())
+            .sum();
+        Self {
+            index: physical_device.index(),
+            name: properties.device_name.clone(),
+            device_type: properties.device_type.into(),
+            driver_version: properties.driver_version,
+            api_version: Version::new(
+                api_version.major.into(),
+                api_version.minor.into(),
+                api_version.patch.into(),
+            ),
+            vram,
+        }
+    }
+}
+
+impl fmt::Display for PhysicalDeviceInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            r#"#{} "{}" ({}, driver {}, Vulkan {}, {} MiB VRAM)"#,
+            self.index,
+            self.name,
+            self.device_type,
+            self.driver_version,
+            self.api_version,
+            self.vram / (1024 * 1024),
+        )
+    }
+}
+
+/// Converts version in packed representation of Vulkan into [`Version`].
+///
+/// Note that some vendors (for example, NVIDIA) pack versions of their drivers differently.
+///
+pub const fn from_vk_version(version: u32) -> Version {
+    Version::new(
+        (version >> 22) as u64,
+        ((version >> 12) & 0x3ff) as u64,
+        (version & 0xfff) as u64,
+    )
+}
+
+/// Selected limits of physical device.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct DeviceLimits {
+    pub max_image_dimension_2d: u32,
+    pub max_image_array_layers: u32,
+    pub max_push_constants_size: u32,
+    pub max_uniform_buffer_range: u32,
+    pub max_storage_buffer_range: u32,
+    pub max_bound_descriptor_sets: u32,
+    pub max_color_attachments: u32,
+    pub max_memory_allocation_count: u32,
+    pub max_sampler_anisotropy: f32,
+    /// Highest count of samples per pixel which is supported for both color and depth.
+    pub max_msaa_samples: u32,
+}
+
+/// Heap of device memory.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct MemoryHeapInfo {
+    /// Size of the heap in bytes.
+    pub size: u64,
+    pub device_local: bool,
+}
+
+/// Properties, limits and features of physical device which is used for rendering.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeviceInfo {
+    pub name: String,
+    pub vendor_id: u32,
+    pub device_id: u32,
+    pub device_type: DeviceType,
+    pub api_version: Version,
+    pub driver_version: Version,
+    pub limits: DeviceLimits,
+    pub memory_heaps: Vec<MemoryHeapInfo>,
+    pub features: DeviceFeatures,
+}
+
+impl DeviceInfo {
+    /// Describes provided physical device.
+    pub(crate) fn new(physical_device: PhysicalDevice) -> Self {
+        let properties = physical_device.properties();
+        let api_version = physical_device.api_version();
+        let features = physical_device.supported_features();
+        let max_msaa_samples = utils::supported_sample_count(
+            u32::MAX,
+            properties.framebuffer_color_sample_counts,
+            properties.framebuffer_depth_sample_counts,
+        );
+        Self {
+            name: properties.device_name.clone(),
+            vendor_id: properties.vendor_id,
+            device_id: properties.device_id,
+            device_type: properties.device_type.into(),
+            api_version: Version::new(
+                api_version.major.into(),
+                api_version.minor.into(),
+                api_version.patch.into(),
+            ),
+            driver_version: self::from_vk_version(properties.driver_version),
+            limits: DeviceLimits {
+                max_image_dimension_2d: properties.max_image_dimension2_d,
+                max_image_array_layers: properties.max_image_array_layers,
+                max_push_constants_size: properties.max_push_constants_size,
+                max_uniform_buffer_range: properties.max_uniform_buffer_range,
+                max_storage_buffer_range: properties.max_storage_buffer_range,
+                max_bound_descriptor_sets: properties.max_bound_descriptor_sets,
+                max_color_attachments: properties.max_color_attachments,
+                max_memory_allocation_count: properties.max_memory_allocation_count,
+                max_sampler_anisotropy: properties.max_sampler_anisotropy,
+                max_msaa_samples: max_msaa_samples as u32,
+            },
+            memory_heaps: physical_device
+                .memory_heaps()
+                .map(|heap| MemoryHeapInfo {
+                    size: heap.size(),
+                    device_local: heap.is_device_local(),
+                })
+                .collect(),
+            features: DeviceFeatures::from(features),
+        }
+    }
+}
+
+impl fmt::Display for DeviceInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const MIB: u64 = 1024 * 1024;
+
+        let limits = &self.limits;
+        writeln!(
+            f,
+            r#""{}" ({} device, vendor ID {:#06x}, device ID {:#06x})"#,
+            self.name, self.device_type, self.vendor_id, self.device_id,
+        )?;
+        writeln!(
+            f,
+            "  Vulkan {}, driver {}",
+            self.api_version, self.driver_version,
+        )?;
+        writeln!(
+            f,
+            "  max texture size {}, max push constants {} bytes, max MSAA samples {}",
+            limits.max_image_dimension_2d, limits.max_push_constants_size, limits.max_msaa_samples,
+        )?;
+        let heaps: Vec<_> = self
+            .memory_heaps
+            .iter()
+            .map(|heap| {
+                let kind = if heap.device_local { "device" } else { "host" };
+                format!("{} MiB {}", heap.size / MIB, kind)
+            })
+            .collect();
+        writeln!(f, "  memory heaps: {}", heaps.join(", "))?;
+        write!(f, "  features: {:?}", self.features)
+    }
+}
+
+/// Error that can happen when the preferred device is not found among suitable devices.
+#[derive(Debug, Error)]
+#[error("no suitable device matches preference {preference:?}, candidates are: {}", self.candidates())]
+pub struct DeviceSelectionError {
+    pub preference: DevicePreference,
+    pub candidates: Vec<PhysicalDeviceInfo>,
+}
+
+impl DeviceSelectionError {
+    fn candidates(&self) -> String {
+        let candidates: Vec<_> = self.candidates.iter().map(ToString::to_string).collect();
+        candidates.join("; ")
+    }
+}
+
+/// Selects position of the device among suitable devices according to the preference.
+///
+/// Engine chooses the device by its type (discrete, integrated, virtual, CPU)
+/// and then by size of its memory. Preferred type is used if there is such device,
+/// while devices requested by index or name must exist.
+///
+/// Returns `None` if there are no suitable devices.
+///
+pub fn select_device(
+    candidates: &[PhysicalDeviceInfo],
+    preference: &DevicePreference,
+) -> Option<Result<usize, DeviceSelectionError>> {
+    if candidates.is_empty() {
+        return None;
+    }
+    // Preferred type goes first, then better type, then more memory.
+    let best = |preferred: Option<DeviceType>| {
+        let (position, _) = candidates
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, info)| {
+                let preferred = Some(info.device_type) == preferred;
+                (preferred, info.device_type.rank(), info.vram)
+            })
+            .expect("candidates are not empty");
+        position
+    };
+    let requested = |position: Option<usize>| {
+        position.ok_or_else(|| DeviceSelectionError {
+            preference: preference.clone(),
+            candidates: candidates.to_vec(),
+        })
+    };
+    let position = match preference {
+        DevicePreference::Default => Ok(best(None)),
+        DevicePreference::PreferDiscrete => Ok(best(Some(DeviceType::Discrete))),
+        DevicePreference::PreferIntegrated => Ok(best(Some(DeviceType::Integrated))),
+        DevicePreference::ByIndex(index) => {
+            requested(candidates.iter().position(|info| info.index == *index))
+        }
+        DevicePreference::ByNameSubstring(name) => {
+            let name = name.to_lowercase();
+            let position = candidates
+                .iter()
+                .position(|info| info.name.to_lowercase().contains(&name));
+            requested(position)
+        }
+    };
+    Some(position)
+}
@@ -0,0 +1,161 @@
+//! Negotiation of optional features and extensions of the device.
+
+use std::fmt;
+
+use thiserror::Error;
+use vulkano::device::Features;
+
+mod tests;
+
+/// Defines [`DeviceFeatures`] with conversions from and into features of the backend.
+macro_rules! device_features {
+    ($($(#[$meta:meta])* $name:ident),* $(,)?) => {
+        /// Optional features of physical device.
+        #[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        #[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+        pub struct DeviceFeatures {
+            $($(#[$meta])* pub $name: bool,)*
+        }
+
+        impl DeviceFeatures {
+            /// No features.
+            pub const NONE: Self = Self { $($name: false,)* };
+
+            /// Features which are present in either of feature sets.
+            pub const fn union(self, other: Self) -> Self {
+                Self { $($name: self.$name || other.$name,)* }
+            }
+
+            /// Features which are present in both feature sets.
+            pub const fn intersection(self, other: Self) -> Self {
+                Self { $($name: self.$name && other.$name,)* }
+            }
+
+            /// Features which are present in this feature set, but not in the other.
+            pub const fn difference(self, other: Self) -> Self {
+                Self { $($name: self.$name && !other.$name,)* }
+            }
+
+            /// Names of features which are present.
+            pub fn names(self) -> Vec<&'static str> {
+                let mut names = Vec::new();
+                $(if self.$name {
+                    names.push(stringify!($name));
+                })*
+                names
+            }
+
+            pub(crate) fn to_vk(self) -> Features {
+                Features {
+                    $($name: self.$name,)*
+                    ..Features::none()
+                }
+            }
+        }
+
+        impl From<&Features> for DeviceFeatures {
+            fn from(features: &Features) -> Self {
+                Self { $($name: features.$name,)* }
+            }
+        }
+    };
+}
+
+device_features!(
+    /// Anisotropic filtering of textures.
+    sampler_anisotropy,
+    /// Wireframe and point rendering of polygons.
+    fill_mode_non_solid,
+    /// Lines wider than one pixel.
+    wide_lines,
+    geometry_shader,
+    tessellation_shader,
+    multi_draw_indirect,
+    /// Queries of statistics of the pipeline, such as count of shader invocations.
+    pipeline_statistics_query,
+    /// Occlusion queries which report the exact count of samples.
+    occlusion_query_precise,
+    /// BC compressed texture formats.
+    texture_compression_bc,
+    shader_float64,
+    timeline_semaphore,
+);
+
+/// Features and extensions which are requested from the device.
+///
+/// Physical devices without required items are not suitable for rendering,
+/// optional items are enabled only if they are supported.
+///
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct DeviceRequirements {
+    pub required_features: DeviceFeatures,
+    pub optional_features: DeviceFeatures,
+    /// Names of required extensions, such as `VK_KHR_timeline_semaphore`.
+    pub required_extensions: Vec<String>,
+    pub optional_extensions: Vec<String>,
+}
+
+impl DeviceRequirements {
+    /// Required items which are not supported,
+    /// as `feature <name>` or `extension <name>` strings.
+    pub fn missing(&self, features: DeviceFeatures, extensions: &[String]) -> Vec<String> {
+        let missing_features = self.required_features.difference(features);
+        let missing_features = missing_features
+            .names()
+            .into_iter()
+            .map(|name| format!("feature {}", name));
+        let missing_extensions = self
+            .required_extensions
+            .iter()
+            .filter(|name| !extensions.contains(name))
+            .map(|name| format!("extension {}", name));
+        missing_features.chain(missing_extensions).collect()
+    }
+
+    /// Features and extensions which should be enabled on the device
+    /// which supports provided items.
+    pub fn negotiate(
+        &self,
+        features: DeviceFeatures,
+        extensions: &[String],
+    ) -> EnabledCapabilities {
+        let optional_features = self.optional_features.intersection(features);
+        let mut enabled_extensions = self.required_extensions.clone();
+        for name in &self.optional_extensions {
+            if extensions.contains(name) && !enabled_extensions.contains(name) {
+                enabled_extensions.push(name.clone());
+            }
+        }
+        EnabledCapabilities {
+            features: self.required_features.union(optional_features),
+            extensions: enabled_extensions,
+        }
+    }
+}
+
+/// Features and extensions which were enabled on the device by requirements.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct EnabledCapabilities {
+    pub features: DeviceFeatures,
+    pub extensions: Vec<String>,
+}
+
+/// Error that can happen when no physical device meets the requirements.
+#[derive(Debug, Error)]
+pub struct MissingRequirementsError {
+    /// Names of devices with items which they are missing.
+    pub devices: Vec<(String, Vec<String>)>,
+}
+
+impl fmt::Display for MissingRequirementsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "no physical device meets the requirements")?;
+        for (device, missing) in &self.devices {
+            write!(f, r#"; device "{}" lacks {}"#, device, missing.join(", "))?;
+        }
+        Ok(())
+    }
+}
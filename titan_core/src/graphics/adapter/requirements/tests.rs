@@ -0,0 +1,86 @@
+#![cfg(test)]
+
+use super::{DeviceFeatures, DeviceRequirements, MissingRequirementsError};
+
+fn supported() -> (DeviceFeatures, Vec<String>) {
+    let features = DeviceFeatures {
+        sampler_anisotropy: true,
+        wide_lines: true,
+        ..DeviceFeatures::NONE
+    };
+    let extensions = vec!["VK_KHR_swapchain".to_string()];
+    (features, extensions)
+}
+
+#[test]
+fn feature_set_operations() {
+    let (features, _) = supported();
+    let other = DeviceFeatures {
+        wide_lines: true,
+        fill_mode_non_solid: true,
+        ..DeviceFeatures::NONE
+    };
+    assert_eq!(
+        features.union(other).names(),
+        ["sampler_anisotropy", "fill_mode_non_solid", "wide_lines"]
+    );
+    assert_eq!(features.intersection(other).names(), ["wide_lines"]);
+    assert_eq!(features.difference(other).names(), ["sampler_anisotropy"]);
+    assert!(DeviceFeatures::NONE.names().is_empty());
+}
+
+#[test]
+fn missing_required_items_are_named() {
+    let (features, extensions) = supported();
+    let requirements = DeviceRequirements {
+        required_features: DeviceFeatures {
+            sampler_anisotropy: true,
+            fill_mode_non_solid: true,
+            ..DeviceFeatures::NONE
+        },
+        required_extensions: vec![
+            "VK_KHR_swapchain".to_string(),
+            "VK_KHR_timeline_semaphore".to_string(),
+        ],
+        ..DeviceRequirements::default()
+    };
+    let missing = requirements.missing(features, &extensions);
+    assert_eq!(
+        missing,
+        [
+            "feature fill_mode_non_solid",
+            "extension VK_KHR_timeline_semaphore"
+        ]
+    );
+
+    let error = MissingRequirementsError {
+        devices: vec![("GPU".to_string(), missing)],
+    };
+    assert!(error
+        .to_string()
+        .contains(r#"device "GPU" lacks feature fill_mode_non_solid"#));
+}
+
+#[test]
+fn optional_items_are_enabled_if_supported() {
+    let (features, extensions) = supported();
+    let requirements = DeviceRequirements {
+        required_features: DeviceFeatures {
+            wide_lines: true,
+            ..DeviceFeatures::NONE
+        },
+        optional_features: DeviceFeatures {
+            sampler_anisotropy: true,
+            geometry_shader: true,
+            ..DeviceFeatures::NONE
+        },
+        optional_extensions: vec!["VK_KHR_swapchain".to_string(), "VK_EXT_unknown".to_string()],
+        ..DeviceRequirements::default()
+    };
+    let enabled = requirements.negotiate(features, &extensions);
+    assert_eq!(
+        enabled.features.names(),
+        ["sampler_anisotropy", "wide_lines"]
+    );
+    assert_eq!(enabled.extensions, ["VK_KHR_swapchain"]);
+}
@@ -0,0 +1,68 @@
+#![cfg(test)]
+
+use semver::Version;
+
+use super::{from_vk_version, select_device, DeviceType, PhysicalDeviceInfo};
+use crate::config::DevicePreference;
+
+fn device(index: usize, name: &str, device_type: DeviceType, vram: u64) -> PhysicalDeviceInfo {
+    PhysicalDeviceInfo {
+        index,
+        name: name.to_string(),
+        device_type,
+        driver_version: 1,
+        api_version: Version::new(1, 2, 0),
+        vram,
+    }
+}
+
+fn laptop() -> Vec<PhysicalDeviceInfo> {
+    vec![
+        device(0, "Intel UHD Graphics", DeviceType::Integrated, 1 << 30),
+        device(2, "NVIDIA GeForce RTX", DeviceType::Discrete, 6 << 30),
+        device(3, "llvmpipe", DeviceType::Cpu, 1 << 32),
+    ]
+}
+
+#[test]
+fn type_preferences() {
+    let candidates = laptop();
+    let select = |preference| select_device(&candidates, &preference).unwrap().unwrap();
+    assert_eq!(select(DevicePreference::Default), 1);
+    assert_eq!(select(DevicePreference::PreferDiscrete), 1);
+    assert_eq!(select(DevicePreference::PreferIntegrated), 0);
+
+    let desktop = &candidates[1..];
+    let preference = DevicePreference::PreferIntegrated;
+    assert_eq!(select_device(desktop, &preference).unwrap().unwrap(), 0);
+}
+
+#[test]
+fn requested_devices_must_exist() {
+    let candidates = laptop();
+    let select = |preference| select_device(&candidates, &preference).unwrap();
+    assert_eq!(select(DevicePreference::ByIndex(3)).unwrap(), 2);
+    assert_eq!(
+        select(DevicePreference::ByNameSubstring("geforce".into())).unwrap(),
+        1
+    );
+
+    let error = select(DevicePreference::ByIndex(1)).unwrap_err();
+    assert_eq!(error.candidates.len(), 3);
+    assert!(error.to_string().contains("llvmpipe"));
+    assert!(select(DevicePreference::ByNameSubstring("Radeon".into())).is_err());
+}
+
+#[test]
+fn no_candidates() {
+    assert!(select_device(&[], &DevicePreference::Default).is_none());
+}
+
+#[test]
+fn packed_versions_are_converted() {
+    assert_eq!(
+        from_vk_version((1 << 22) | (2 << 12) | 189),
+        Version::new(1, 2, 189)
+    );
+    assert_eq!(from_vk_version(0), Version::new(0, 0, 0));
+}
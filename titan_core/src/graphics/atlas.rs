@@ -0,0 +1,372 @@
+//! Texture atlas utilities for sprites and UI images of game engine.
+//!
+//! Images are packed on the CPU into one or more pages with skyline algorithm,
+//! then pages are uploaded to the GPU as ordinary UI images of the engine.
+
+use egui::TextureId;
+use image::RgbaImage;
+use slotmap::{new_key_type, SlotMap};
+use thiserror::Error;
+
+mod tests;
+
+new_key_type! {
+    /// Unique key of the image inserted into the atlas.
+    pub struct AtlasKey;
+}
+
+/// Error that can happen when inserting image into the atlas.
+#[derive(Debug, Error, Eq, PartialEq)]
+pub enum AtlasError {
+    #[error("image of size {size:?} with padding {padding} does not fit atlas page of size {page_size:?}")]
+    TooLarge {
+        size: [u32; 2],
+        padding: u32,
+        page_size: [u32; 2],
+    },
+
+    #[error("image must not be empty")]
+    Empty,
+}
+
+/// Rectangle of texture coordinates in range `[0; 1]`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct UvRect {
+    pub min: [f32; 2],
+    pub max: [f32; 2],
+}
+
+/// Placement of the image inserted into the atlas.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AtlasRegion {
+    /// Index of the page which contains the image.
+    pub page: usize,
+    /// Position of the image on the page in pixels (without padding).
+    pub position: [u32; 2],
+    /// Size of the image in pixels.
+    pub size: [u32; 2],
+    /// Texture coordinates of the image on the page.
+    pub uv: UvRect,
+}
+
+/// Segment of the skyline: horizontal line at `y` from `x` of `width` pixels.
+#[derive(Debug, Copy, Clone)]
+struct Segment {
+    x: u32,
+    y: u32,
+    width: u32,
+}
+
+/// Skyline rectangle packer which uses bottom-left heuristic.
+#[derive(Debug, Clone)]
+struct Skyline {
+    size: [u32; 2],
+    segments: Vec<Segment>,
+}
+
+impl Skyline {
+    fn new(size: [u32; 2]) -> Self {
+        let [width, _] = size;
+        Self {
+            size,
+            segments: vec![Segment { x: 0, y: 0, width }],
+        }
+    }
+
+    /// Finds the lowest position where rectangle with provided size fits.
+    fn find(&self, size: [u32; 2]) -> Option<(usize, [u32; 2])> {
+        let [width, height] = size;
+        let [page_width, page_height] = self.size;
+        let mut best: Option<(usize, [u32; 2])> = None;
+        for (index, segment) in self.segments.iter().enumerate() {
+            if segment.x + width > page_width {
+                break;
+            }
+            // Rectangle lies on the highest segment it spans over.
+            let mut y = 0;
+            let mut remaining = width as i64;
+            for next in &self.segments[index..] {
+                if remaining <= 0 {
+                    break;
+                }
+                y = y.max(next.y);
+                remaining -= next.width as i64;
+            }
+            if y + height > page_height {
+                continue;
+            }
+            if best.is_none_or(|(_, [_, best_y])| y < best_y) {
+                best = Some((index, [segment.x, y]));
+            }
+        }
+        best
+    }
+
+    /// Allocates rectangle with provided size and returns its position.
+    fn allocate(&mut self, size: [u32; 2]) -> Option<[u32; 2]> {
+        let (index, position) = self.find(size)?;
+        let [width, height] = size;
+        let [x, y] = position;
+        let new = Segment {
+            x,
+            y: y + height,
+            width,
+        };
+        self.segments.insert(index, new);
+
+        // Shrink or remove segments which are covered by the new one.
+        let right = x + width;
+        let next = index + 1;
+        while next < self.segments.len() {
+            let segment = &mut self.segments[next];
+            if segment.x >= right {
+                break;
+            }
+            let end = segment.x + segment.width;
+            if end <= right {
+                self.segments.remove(next);
+                continue;
+            }
+            segment.width = end - right;
+            segment.x = right;
+            break;
+        }
+
+        // Merge neighbour segments of the same height.
+        let mut index = 0;
+        while index + 1 < self.segments.len() {
+            let (current, next) = (self.segments[index], self.segments[index + 1]);
+            if current.y == next.y {
+                self.segments[index].width += next.width;
+                self.segments.remove(index + 1);
+            } else {
+                index += 1;
+            }
+        }
+        Some(position)
+    }
+}
+
+/// Single page of the atlas.
+struct Page {
+    image: RgbaImage,
+    skyline: Skyline,
+    used_area: u64,
+    dirty: bool,
+    texture: Option<TextureId>,
+}
+
+/// Image inserted into the atlas.
+struct Entry {
+    image: RgbaImage,
+    region: AtlasRegion,
+}
+
+/// Builder of texture atlas which packs images at runtime.
+///
+/// Each image is surrounded by padding filled with its edge pixels (bleed),
+/// so linear filtering does not pick up texels of neighbour images.
+///
+pub struct AtlasBuilder {
+    page_size: [u32; 2],
+    padding: u32,
+    pages: Vec<Page>,
+    entries: SlotMap<AtlasKey, Entry>,
+}
+
+impl AtlasBuilder {
+    /// Creates new empty atlas with provided size of pages and padding around images.
+    pub fn new(page_size: [u32; 2], padding: u32) -> Self {
+        Self {
+            page_size,
+            padding,
+            pages: Vec::new(),
+            entries: SlotMap::with_key(),
+        }
+    }
+
+    /// Size of each page of the atlas in pixels.
+    pub fn page_size(&self) -> [u32; 2] {
+        self.page_size
+    }
+
+    /// Count of pages of the atlas.
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Image of the page with provided index.
+    pub fn page(&self, index: usize) -> Option<&RgbaImage> {
+        self.pages.get(index).map(|page| &page.image)
+    }
+
+    /// Inserts new image into the atlas.
+    ///
+    /// New page is created if the image does not fit any of existing pages.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the image is empty or it is larger than the page.
+    ///
+    pub fn insert(&mut self, image: RgbaImage) -> Result<AtlasKey, AtlasError> {
+        let region = self.place(&image)?;
+        Ok(self.entries.insert(Entry { image, region }))
+    }
+
+    /// Removes the image from the atlas.
+    ///
+    /// Space of removed image is not reused until the atlas is [repacked](Self::repack).
+    ///
+    pub fn remove(&mut self, key: AtlasKey) -> Option<RgbaImage> {
+        let entry = self.entries.remove(key)?;
+        let [width, height] = self.padded_size(entry.region.size);
+        let page = &mut self.pages[entry.region.page];
+        page.used_area -= width as u64 * height as u64;
+        Some(entry.image)
+    }
+
+    /// Placement of the image with provided key.
+    pub fn region(&self, key: AtlasKey) -> Option<AtlasRegion> {
+        self.entries.get(key).map(|entry| entry.region)
+    }
+
+    /// Ratio of area used by images (with padding) to the total area of all pages.
+    pub fn fill_ratio(&self) -> f32 {
+        let [width, height] = self.page_size;
+        let total = self.pages.len() as u64 * width as u64 * height as u64;
+        if total == 0 {
+            return 0.0;
+        }
+        let used: u64 = self.pages.iter().map(|page| page.used_area).sum();
+        used as f32 / total as f32
+    }
+
+    /// Packs all remaining images into new pages, releasing space of removed images.
+    ///
+    /// Images are inserted from the tallest to the shortest for better fill ratio.
+    /// Regions of all images are changed, so they must be retrieved again.
+    /// Textures of the old pages are returned and should be unregistered by the caller.
+    ///
+    pub fn repack(&mut self) -> Vec<TextureId> {
+        let textures = self
+            .pages
+            .drain(..)
+            .filter_map(|page| page.texture)
+            .collect();
+        let mut keys: Vec<_> = self.entries.keys().collect();
+        keys.sort_by_key(|&key| {
+            let [width, height] = self.entries[key].region.size;
+            std::cmp::Reverse((height, width))
+        });
+        for key in keys {
+            let image = std::mem::take(&mut self.entries[key].image);
+            // Each image fitted the page before, so it still fits.
+            let region = self.place(&image).unwrap();
+            let entry = &mut self.entries[key];
+            entry.image = image;
+            entry.region = region;
+        }
+        textures
+    }
+
+    /// Texture of the page which was uploaded to the GPU.
+    pub fn texture(&self, page: usize) -> Option<TextureId> {
+        self.pages.get(page).and_then(|page| page.texture)
+    }
+
+    /// Pages which were changed since the last upload to the GPU.
+    pub(crate) fn dirty_pages(&self) -> impl Iterator<Item = (usize, &RgbaImage)> + '_ {
+        self.pages
+            .iter()
+            .enumerate()
+            .filter(|(_, page)| page.dirty)
+            .map(|(index, page)| (index, &page.image))
+    }
+
+    /// Replaces texture of the page after its upload, returning the previous one.
+    pub(crate) fn set_texture(&mut self, page: usize, texture: TextureId) -> Option<TextureId> {
+        let page = &mut self.pages[page];
+        page.dirty = false;
+        page.texture.replace(texture)
+    }
+
+    fn padded_size(&self, size: [u32; 2]) -> [u32; 2] {
+        let [width, height] = size;
+        [width + 2 * self.padding, height + 2 * self.padding]
+    }
+
+    fn place(&mut self, image: &RgbaImage) -> Result<AtlasRegion, AtlasError> {
+        let size = [image.width(), image.height()];
+        if size.contains(&0) {
+            return Err(AtlasError::Empty);
+        }
+        let padded = self.padded_size(size);
+        let [page_width, page_height] = self.page_size;
+        if padded[0] > page_width || padded[1] > page_height {
+            return Err(AtlasError::TooLarge {
+                size,
+                padding: self.padding,
+                page_size: self.page_size,
+            });
+        }
+
+        let found = self
+            .pages
+            .iter_mut()
+            .enumerate()
+            .find_map(|(index, page)| Some((index, page.skyline.allocate(padded)?)));
+        let (index, [x, y]) = match found {
+            Some(found) => found,
+            None => {
+                let mut skyline = Skyline::new(self.page_size);
+                let position = skyline.allocate(padded).unwrap();
+                self.pages.push(Page {
+                    image: RgbaImage::new(page_width, page_height),
+                    skyline,
+                    used_area: 0,
+                    dirty: true,
+                    texture: None,
+                });
+                (self.pages.len() - 1, position)
+            }
+        };
+
+        let page = &mut self.pages[index];
+        page.used_area += padded[0] as u64 * padded[1] as u64;
+        page.dirty = true;
+        self::blit_with_bleed(&mut page.image, image, [x, y], self.padding);
+
+        let position = [x + self.padding, y + self.padding];
+        let [width, height] = size;
+        let uv = UvRect {
+            min: [
+                position[0] as f32 / page_width as f32,
+                position[1] as f32 / page_height as f32,
+            ],
+            max: [
+                (position[0] + width) as f32 / page_width as f32,
+                (position[1] + height) as f32 / page_height as f32,
+            ],
+        };
+        Ok(AtlasRegion {
+            page: index,
+            position,
+            size,
+            uv,
+        })
+    }
+}
+
+/// Copies the image into the page at provided position of padded rectangle,
+/// filling padding with edge pixels of the image.
+fn blit_with_bleed(page: &mut RgbaImage, image: &RgbaImage, position: [u32; 2], padding: u32) {
+    let [x, y] = position;
+    let (width, height) = image.dimensions();
+    for dy in 0..height + 2 * padding {
+        for dx in 0..width + 2 * padding {
+            let sx = dx.saturating_sub(padding).min(width - 1);
+            let sy = dy.saturating_sub(padding).min(height - 1);
+            page.put_pixel(x + dx, y + dy, *image.get_pixel(sx, sy));
+        }
+    }
+}
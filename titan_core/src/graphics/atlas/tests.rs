@@ -0,0 +1,122 @@
+#![cfg(test)]
+
+use image::{Rgba, RgbaImage};
+
+use super::{AtlasBuilder, AtlasError, AtlasRegion};
+
+fn image(width: u32, height: u32, value: u8) -> RgbaImage {
+    RgbaImage::from_pixel(width, height, Rgba([value, value, value, 255]))
+}
+
+/// Checks if padded rectangles of two regions on the same page overlap.
+fn overlap(a: &AtlasRegion, b: &AtlasRegion, padding: u32) -> bool {
+    let rect = |region: &AtlasRegion| {
+        let [x, y] = region.position;
+        let [width, height] = region.size;
+        (
+            x - padding,
+            y - padding,
+            x + width + padding,
+            y + height + padding,
+        )
+    };
+    let (a_left, a_top, a_right, a_bottom) = rect(a);
+    let (b_left, b_top, b_right, b_bottom) = rect(b);
+    a.page == b.page && a_left < b_right && b_left < a_right && a_top < b_bottom && b_top < a_bottom
+}
+
+#[test]
+fn images_do_not_overlap() {
+    let padding = 1;
+    let mut atlas = AtlasBuilder::new([256, 256], padding);
+    let keys: Vec<_> = (1..=40)
+        .map(|index| {
+            let size = 8 + (index * 7) % 29;
+            atlas.insert(image(size, 40 - size, index as u8)).unwrap()
+        })
+        .collect();
+    let regions: Vec<_> = keys.iter().map(|&key| atlas.region(key).unwrap()).collect();
+    for (index, a) in regions.iter().enumerate() {
+        let [x, y] = a.position;
+        let [width, height] = a.size;
+        assert!(x >= padding && y >= padding);
+        assert!(x + width + padding <= 256 && y + height + padding <= 256);
+        for b in &regions[index + 1..] {
+            assert!(!overlap(a, b, padding), "{:?} overlaps {:?}", a, b);
+        }
+    }
+}
+
+#[test]
+fn equal_images_fill_page_completely() {
+    let mut atlas = AtlasBuilder::new([64, 64], 0);
+    for _ in 0..16 {
+        atlas.insert(image(16, 16, 0)).unwrap();
+    }
+    assert_eq!(atlas.page_count(), 1);
+    assert_eq!(atlas.fill_ratio(), 1.0);
+
+    // Next image does not fit, so new page is created.
+    let key = atlas.insert(image(16, 16, 0)).unwrap();
+    assert_eq!(atlas.page_count(), 2);
+    assert_eq!(atlas.region(key).unwrap().page, 1);
+}
+
+#[test]
+fn uv_rect_matches_position() {
+    let mut atlas = AtlasBuilder::new([100, 50], 2);
+    let key = atlas.insert(image(10, 5, 0)).unwrap();
+    let region = atlas.region(key).unwrap();
+    assert_eq!(region.position, [2, 2]);
+    assert_eq!(region.uv.min, [0.02, 0.04]);
+    assert_eq!(region.uv.max, [0.12, 0.14]);
+}
+
+#[test]
+fn padding_is_filled_with_edge_pixels() {
+    let mut atlas = AtlasBuilder::new([16, 16], 2);
+    let mut source = image(2, 2, 0);
+    source.put_pixel(1, 1, Rgba([255, 0, 0, 255]));
+    atlas.insert(source).unwrap();
+
+    let page = atlas.page(0).unwrap();
+    assert_eq!(*page.get_pixel(0, 0), Rgba([0, 0, 0, 255]));
+    assert_eq!(*page.get_pixel(5, 5), Rgba([255, 0, 0, 255]));
+    assert_eq!(*page.get_pixel(3, 5), Rgba([255, 0, 0, 255]));
+    // Pixels outside of padding are left untouched.
+    assert_eq!(*page.get_pixel(6, 6), Rgba([0, 0, 0, 0]));
+}
+
+#[test]
+fn invalid_images_are_rejected() {
+    let mut atlas = AtlasBuilder::new([32, 32], 1);
+    assert_eq!(
+        atlas.insert(image(31, 4, 0)),
+        Err(AtlasError::TooLarge {
+            size: [31, 4],
+            padding: 1,
+            page_size: [32, 32],
+        }),
+    );
+    assert_eq!(atlas.insert(RgbaImage::new(0, 4)), Err(AtlasError::Empty));
+    assert_eq!(atlas.page_count(), 0);
+}
+
+#[test]
+fn repack_releases_removed_space() {
+    let mut atlas = AtlasBuilder::new([64, 64], 0);
+    let keys: Vec<_> = (0..32)
+        .map(|_| atlas.insert(image(16, 16, 0)).unwrap())
+        .collect();
+    assert_eq!(atlas.page_count(), 2);
+    for &key in keys.iter().step_by(2) {
+        atlas.remove(key).unwrap();
+    }
+    assert_eq!(atlas.fill_ratio(), 0.5);
+
+    assert!(atlas.repack().is_empty());
+    assert_eq!(atlas.page_count(), 1);
+    assert_eq!(atlas.fill_ratio(), 1.0);
+    assert!(atlas.region(keys[0]).is_none());
+    assert_eq!(atlas.region(keys[1]).unwrap().page, 0);
+}
@@ -0,0 +1,356 @@
+//! Pipeline barriers and image layout transitions of commands recorded through Vulkan API directly.
+//!
+//! Command buffers built by vulkano are synchronized automatically,
+//! but commands recorded into raw command buffers (for example, by external libraries)
+//! are not tracked by vulkano, so their barriers must be recorded explicitly.
+//! Instead of writing `vk::ImageMemoryBarrier` by hand, each transition is described
+//! by the pair of layouts, and access masks with pipeline stages are derived from it
+//! by the canonical [mapping table](ImageLayout::access), with an escape hatch
+//! for [explicit stages](ImageTransition::with_stages).
+
+use std::ops::Range;
+
+use ash::vk;
+use thiserror::Error;
+use vulkano::device::Device;
+
+mod tests;
+
+/// Error that can happen when describing pipeline barrier.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Error)]
+pub enum BarrierError {
+    #[error("image cannot be transitioned into {0:?} layout")]
+    InvalidTarget(ImageLayout),
+
+    #[error("barrier must cover at least one mip level, array layer and byte")]
+    EmptyRange,
+
+    #[error("barrier must contain at least one image transition or buffer barrier")]
+    Empty,
+}
+
+/// Layout of the image in the device memory.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ImageLayout {
+    /// Contents of the image are discarded, valid only as the source layout.
+    Undefined,
+    /// Image was written by the host before the first use, valid only as the source layout.
+    Preinitialized,
+    /// Image could be used by any command, but access could be slower.
+    General,
+    ColorAttachment,
+    DepthStencilAttachment,
+    DepthStencilReadOnly,
+    /// Image is sampled or read as input attachment by shaders.
+    ShaderReadOnly,
+    TransferSrc,
+    TransferDst,
+    /// Image is presented to the surface.
+    PresentSrc,
+}
+
+/// Pipeline stages and memory accesses of one side of the barrier.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Access {
+    pub stages: vk::PipelineStageFlags,
+    pub access: vk::AccessFlags,
+}
+
+impl Access {
+    pub const fn new(stages: vk::PipelineStageFlags, access: vk::AccessFlags) -> Self {
+        Self { stages, access }
+    }
+
+    /// Stages and accesses of both sides.
+    pub fn union(self, other: Self) -> Self {
+        Self::new(self.stages | other.stages, self.access | other.access)
+    }
+}
+
+impl ImageLayout {
+    /// Layout of Vulkan API.
+    pub const fn to_vk(self) -> vk::ImageLayout {
+        match self {
+            Self::Undefined => vk::ImageLayout::UNDEFINED,
+            Self::Preinitialized => vk::ImageLayout::PREINITIALIZED,
+            Self::General => vk::ImageLayout::GENERAL,
+            Self::ColorAttachment => vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+            Self::DepthStencilAttachment => vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL,
+            Self::DepthStencilReadOnly => vk::ImageLayout::DEPTH_STENCIL_READ_ONLY_OPTIMAL,
+            Self::ShaderReadOnly => vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+            Self::TransferSrc => vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+            Self::TransferDst => vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+            Self::PresentSrc => vk::ImageLayout::PRESENT_SRC_KHR,
+        }
+    }
+
+    /// Canonical stages and accesses of the image in this layout.
+    ///
+    /// If `source` is `true`, they are the ones which must complete before the transition:
+    /// only writes have to be made available, reads only have to be finished.
+    /// Otherwise they are the ones which wait for the transition.
+    ///
+    pub fn access(self, source: bool) -> Access {
+        use vk::{AccessFlags as A, PipelineStageFlags as S};
+
+        let shaders = S::VERTEX_SHADER | S::FRAGMENT_SHADER | S::COMPUTE_SHADER;
+        let tests = S::EARLY_FRAGMENT_TESTS | S::LATE_FRAGMENT_TESTS;
+        let (stages, reads, writes) = match self {
+            Self::Undefined => (S::TOP_OF_PIPE, A::empty(), A::empty()),
+            Self::Preinitialized => (S::HOST, A::empty(), A::HOST_WRITE),
+            Self::General => (S::ALL_COMMANDS, A::MEMORY_READ, A::MEMORY_WRITE),
+            Self::ColorAttachment => (
+                S::COLOR_ATTACHMENT_OUTPUT,
+                A::COLOR_ATTACHMENT_READ,
+                A::COLOR_ATTACHMENT_WRITE,
+            ),
+            Self::DepthStencilAttachment => (
+                tests,
+                A::DEPTH_STENCIL_ATTACHMENT_READ,
+                A::DEPTH_STENCIL_ATTACHMENT_WRITE,
+            ),
+            Self::DepthStencilReadOnly => (
+                tests | shaders,
+                A::DEPTH_STENCIL_ATTACHMENT_READ | A::SHADER_READ,
+                A::empty(),
+            ),
+            Self::ShaderReadOnly => (
+                shaders,
+                A::SHADER_READ | A::INPUT_ATTACHMENT_READ,
+                A::empty(),
+            ),
+            Self::TransferSrc => (S::TRANSFER, A::TRANSFER_READ, A::empty()),
+            Self::TransferDst => (S::TRANSFER, A::empty(), A::TRANSFER_WRITE),
+            // Presentation engine is synchronized by semaphores, not by memory accesses:
+            // acquired image is waited for at the stage of color output.
+            Self::PresentSrc if source => (S::COLOR_ATTACHMENT_OUTPUT, A::empty(), A::empty()),
+            Self::PresentSrc => (S::BOTTOM_OF_PIPE, A::empty(), A::empty()),
+        };
+        let access = if source { writes } else { reads | writes };
+        Access::new(stages, access)
+    }
+
+    /// If image could be transitioned into this layout.
+    pub const fn is_valid_target(self) -> bool {
+        !matches!(self, Self::Undefined | Self::Preinitialized)
+    }
+}
+
+/// Transition of the subresource range of the image from one layout into another.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ImageTransition {
+    pub image: vk::Image,
+    pub from: ImageLayout,
+    pub to: ImageLayout,
+    pub aspect: vk::ImageAspectFlags,
+    pub mip_range: Range<u32>,
+    pub layer_range: Range<u32>,
+    /// Stages which override the canonical ones of the layouts, if any.
+    pub stages: Option<(vk::PipelineStageFlags, vk::PipelineStageFlags)>,
+}
+
+impl ImageTransition {
+    /// Creates transition of the first mip level and array layer of the color image.
+    pub const fn new(image: vk::Image, from: ImageLayout, to: ImageLayout) -> Self {
+        Self {
+            image,
+            from,
+            to,
+            aspect: vk::ImageAspectFlags::COLOR,
+            mip_range: 0..1,
+            layer_range: 0..1,
+            stages: None,
+        }
+    }
+
+    pub const fn with_aspect(mut self, aspect: vk::ImageAspectFlags) -> Self {
+        self.aspect = aspect;
+        self
+    }
+
+    pub const fn with_mip_range(mut self, mip_range: Range<u32>) -> Self {
+        self.mip_range = mip_range;
+        self
+    }
+
+    pub const fn with_layer_range(mut self, layer_range: Range<u32>) -> Self {
+        self.layer_range = layer_range;
+        self
+    }
+
+    /// Overrides canonical source and destination stages of the layouts,
+    /// for example, when the image is sampled only by compute shader.
+    pub const fn with_stages(
+        mut self,
+        src_stages: vk::PipelineStageFlags,
+        dst_stages: vk::PipelineStageFlags,
+    ) -> Self {
+        self.stages = Some((src_stages, dst_stages));
+        self
+    }
+
+    /// Source and destination stages and accesses of the transition.
+    pub fn accesses(&self) -> (Access, Access) {
+        let mut src = self.from.access(true);
+        let mut dst = self.to.access(false);
+        if let Some((src_stages, dst_stages)) = self.stages {
+            src.stages = src_stages;
+            dst.stages = dst_stages;
+        }
+        (src, dst)
+    }
+
+    /// Memory barrier of Vulkan API which performs this transition.
+    pub fn to_vk(&self) -> Result<vk::ImageMemoryBarrier, BarrierError> {
+        if !self.to.is_valid_target() {
+            return Err(BarrierError::InvalidTarget(self.to));
+        }
+        if self.mip_range.is_empty() || self.layer_range.is_empty() {
+            return Err(BarrierError::EmptyRange);
+        }
+        let (src, dst) = self.accesses();
+        Ok(vk::ImageMemoryBarrier {
+            src_access_mask: src.access,
+            dst_access_mask: dst.access,
+            old_layout: self.from.to_vk(),
+            new_layout: self.to.to_vk(),
+            src_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+            dst_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+            image: self.image,
+            subresource_range: vk::ImageSubresourceRange {
+                aspect_mask: self.aspect,
+                base_mip_level: self.mip_range.start,
+                level_count: self.mip_range.end - self.mip_range.start,
+                base_array_layer: self.layer_range.start,
+                layer_count: self.layer_range.end - self.layer_range.start,
+            },
+            ..Default::default()
+        })
+    }
+}
+
+/// Memory barrier of the range of the buffer between previous and next accesses.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BufferBarrier {
+    pub buffer: vk::Buffer,
+    pub src: Access,
+    pub dst: Access,
+    /// Range of bytes of the buffer, the whole buffer by default.
+    pub range: Option<Range<vk::DeviceSize>>,
+}
+
+impl BufferBarrier {
+    /// Creates barrier of the whole buffer.
+    pub const fn new(buffer: vk::Buffer, src: Access, dst: Access) -> Self {
+        Self {
+            buffer,
+            src,
+            dst,
+            range: None,
+        }
+    }
+
+    pub const fn with_range(mut self, range: Range<vk::DeviceSize>) -> Self {
+        self.range = Some(range);
+        self
+    }
+
+    /// Memory barrier of Vulkan API.
+    pub fn to_vk(&self) -> Result<vk::BufferMemoryBarrier, BarrierError> {
+        let (offset, size) = match &self.range {
+            Some(range) if range.is_empty() => return Err(BarrierError::EmptyRange),
+            Some(range) => (range.start, range.end - range.start),
+            None => (0, vk::WHOLE_SIZE),
+        };
+        Ok(vk::BufferMemoryBarrier {
+            src_access_mask: self.src.access,
+            dst_access_mask: self.dst.access,
+            src_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+            dst_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+            buffer: self.buffer,
+            offset,
+            size,
+            ..Default::default()
+        })
+    }
+}
+
+/// Pipeline barrier of Vulkan API with all its transitions and barriers.
+pub struct PipelineBarrier {
+    pub src_stages: vk::PipelineStageFlags,
+    pub dst_stages: vk::PipelineStageFlags,
+    pub images: Vec<vk::ImageMemoryBarrier>,
+    pub buffers: Vec<vk::BufferMemoryBarrier>,
+}
+
+impl PipelineBarrier {
+    /// Combines transitions of images and barriers of buffers into one pipeline barrier,
+    /// which waits for the union of their source stages.
+    pub fn new(
+        images: &[ImageTransition],
+        buffers: &[BufferBarrier],
+    ) -> Result<Self, BarrierError> {
+        if images.is_empty() && buffers.is_empty() {
+            return Err(BarrierError::Empty);
+        }
+        let empty = Access::new(vk::PipelineStageFlags::empty(), vk::AccessFlags::empty());
+        let (src, dst) = images
+            .iter()
+            .map(ImageTransition::accesses)
+            .chain(buffers.iter().map(|barrier| (barrier.src, barrier.dst)))
+            .fold((empty, empty), |(src, dst), (other_src, other_dst)| {
+                (src.union(other_src), dst.union(other_dst))
+            });
+        Ok(Self {
+            src_stages: src.stages,
+            dst_stages: dst.stages,
+            images: images
+                .iter()
+                .map(ImageTransition::to_vk)
+                .collect::<Result<_, _>>()?,
+            buffers: buffers
+                .iter()
+                .map(BufferBarrier::to_vk)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// Records this barrier into the command buffer.
+    ///
+    /// # Safety
+    ///
+    /// - command buffer must be allocated from the device and be in the recording state;
+    /// - images and buffers of the barrier must be alive until the commands are executed,
+    ///   and images must be in the source layouts of their transitions.
+    ///
+    pub unsafe fn record(&self, device: &Device, command_buffer: vk::CommandBuffer) {
+        let fns = &device.fns().v1_0;
+        fns.cmd_pipeline_barrier(
+            command_buffer,
+            self.src_stages,
+            self.dst_stages,
+            vk::DependencyFlags::empty(),
+            0,
+            std::ptr::null(),
+            self.buffers.len() as u32,
+            self.buffers.as_ptr(),
+            self.images.len() as u32,
+            self.images.as_ptr(),
+        );
+    }
+}
+
+/// Records layout transitions of images into the command buffer as one pipeline barrier.
+///
+/// # Safety
+///
+/// Same as of [`PipelineBarrier::record`].
+///
+pub unsafe fn transition_images(
+    device: &Device,
+    command_buffer: vk::CommandBuffer,
+    transitions: &[ImageTransition],
+) -> Result<(), BarrierError> {
+    PipelineBarrier::new(transitions, &[])?.record(device, command_buffer);
+    Ok(())
+}
@@ -0,0 +1,130 @@
+#![cfg(test)]
+
+use ash::vk::{self, AccessFlags as A, PipelineStageFlags as S};
+
+use super::{Access, BarrierError, BufferBarrier, ImageLayout, ImageTransition, PipelineBarrier};
+
+fn transition(from: ImageLayout, to: ImageLayout) -> ImageTransition {
+    ImageTransition::new(vk::Image::null(), from, to)
+}
+
+#[test]
+fn undefined_to_transfer_dst() {
+    let (src, dst) = transition(ImageLayout::Undefined, ImageLayout::TransferDst).accesses();
+    assert_eq!(src, Access::new(S::TOP_OF_PIPE, A::empty()));
+    assert_eq!(dst, Access::new(S::TRANSFER, A::TRANSFER_WRITE));
+}
+
+#[test]
+fn transfer_dst_to_shader_read() {
+    let (src, dst) = transition(ImageLayout::TransferDst, ImageLayout::ShaderReadOnly).accesses();
+    assert_eq!(src, Access::new(S::TRANSFER, A::TRANSFER_WRITE));
+    assert_eq!(
+        dst,
+        Access::new(
+            S::VERTEX_SHADER | S::FRAGMENT_SHADER | S::COMPUTE_SHADER,
+            A::SHADER_READ | A::INPUT_ATTACHMENT_READ,
+        )
+    );
+}
+
+#[test]
+fn color_attachment_to_present() {
+    let (src, dst) = transition(ImageLayout::ColorAttachment, ImageLayout::PresentSrc).accesses();
+    assert_eq!(
+        src,
+        Access::new(S::COLOR_ATTACHMENT_OUTPUT, A::COLOR_ATTACHMENT_WRITE)
+    );
+    assert_eq!(dst, Access::new(S::BOTTOM_OF_PIPE, A::empty()));
+}
+
+#[test]
+fn shader_read_to_color_attachment_waits_for_reads_only() {
+    let (src, dst) =
+        transition(ImageLayout::ShaderReadOnly, ImageLayout::ColorAttachment).accesses();
+    assert_eq!(src.access, A::empty());
+    assert!(src.stages.contains(S::FRAGMENT_SHADER));
+    assert_eq!(
+        dst,
+        Access::new(
+            S::COLOR_ATTACHMENT_OUTPUT,
+            A::COLOR_ATTACHMENT_READ | A::COLOR_ATTACHMENT_WRITE,
+        )
+    );
+}
+
+#[test]
+fn explicit_stages_override_canonical_ones() {
+    let (src, dst) = transition(ImageLayout::General, ImageLayout::ShaderReadOnly)
+        .with_stages(S::COMPUTE_SHADER, S::COMPUTE_SHADER)
+        .accesses();
+    assert_eq!(src, Access::new(S::COMPUTE_SHADER, A::MEMORY_WRITE));
+    assert_eq!(dst.stages, S::COMPUTE_SHADER);
+    assert_eq!(dst.access, A::SHADER_READ | A::INPUT_ATTACHMENT_READ);
+}
+
+#[test]
+fn transition_into_undefined_is_rejected() {
+    let result = transition(ImageLayout::ShaderReadOnly, ImageLayout::Undefined).to_vk();
+    assert_eq!(
+        result.unwrap_err(),
+        BarrierError::InvalidTarget(ImageLayout::Undefined)
+    );
+}
+
+#[test]
+fn subresource_range_of_transition() {
+    let barrier = transition(ImageLayout::Undefined, ImageLayout::DepthStencilAttachment)
+        .with_aspect(vk::ImageAspectFlags::DEPTH)
+        .with_mip_range(1..4)
+        .with_layer_range(2..3)
+        .to_vk()
+        .unwrap();
+    assert_eq!(
+        barrier.new_layout,
+        vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL
+    );
+    let range = barrier.subresource_range;
+    assert_eq!(range.aspect_mask, vk::ImageAspectFlags::DEPTH);
+    assert_eq!((range.base_mip_level, range.level_count), (1, 3));
+    assert_eq!((range.base_array_layer, range.layer_count), (2, 1));
+
+    let result = transition(ImageLayout::Undefined, ImageLayout::General)
+        .with_mip_range(2..2)
+        .to_vk();
+    assert_eq!(result.unwrap_err(), BarrierError::EmptyRange);
+}
+
+#[test]
+fn buffer_barrier_covers_whole_buffer_by_default() {
+    let src = Access::new(S::COMPUTE_SHADER, A::SHADER_WRITE);
+    let dst = Access::new(S::DRAW_INDIRECT, A::INDIRECT_COMMAND_READ);
+    let barrier = BufferBarrier::new(vk::Buffer::null(), src, dst);
+    let whole = barrier.to_vk().unwrap();
+    assert_eq!((whole.offset, whole.size), (0, vk::WHOLE_SIZE));
+    let part = barrier.with_range(16..48).to_vk().unwrap();
+    assert_eq!((part.offset, part.size), (16, 32));
+}
+
+#[test]
+fn pipeline_barrier_unites_stages() {
+    let images = [transition(
+        ImageLayout::TransferDst,
+        ImageLayout::ShaderReadOnly,
+    )];
+    let buffers = [BufferBarrier::new(
+        vk::Buffer::null(),
+        Access::new(S::COMPUTE_SHADER, A::SHADER_WRITE),
+        Access::new(S::DRAW_INDIRECT, A::INDIRECT_COMMAND_READ),
+    )];
+    let barrier = PipelineBarrier::new(&images, &buffers).unwrap();
+    assert_eq!(barrier.src_stages, S::TRANSFER | S::COMPUTE_SHADER);
+    assert!(barrier
+        .dst_stages
+        .contains(S::DRAW_INDIRECT | S::FRAGMENT_SHADER));
+    assert_eq!((barrier.images.len(), barrier.buffers.len()), (1, 1));
+    assert_eq!(
+        PipelineBarrier::new(&[], &[]).err(),
+        Some(BarrierError::Empty)
+    );
+}
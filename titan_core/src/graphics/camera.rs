@@ -1,9 +1,18 @@
 //! Internal camera utilities for game engine.
 
-use ultraviolet::Mat4;
+use std::sync::{Arc, Mutex};
+
+use ultraviolet::{Lerp, Mat4, Similarity3};
+
+mod tests;
 
 /// Camera uniform buffer object (UBO) that will be passed into uniform buffer.
+///
+/// Bytes of the struct are copied into the buffer as is,
+/// so its fields are laid out in order of the uniform block of the shaders.
+///
 #[derive(Default, Copy, Clone)]
+#[repr(C)]
 pub struct CameraUBO {
     /// Projection 4x4 matrix.
     pub projection: Mat4,
@@ -22,3 +31,85 @@ pub fn new(projection: Mat4, model: Mat4, view: Mat4) -> Self {
         }
     }
 }
+
+/// Transform of the model which is interpolated between two simulation states.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct ModelTransform {
+    previous: Option<Similarity3>,
+    current: Similarity3,
+}
+
+impl ModelTransform {
+    /// Creates new transform with only current state, which is used without interpolation.
+    pub fn new(current: Similarity3) -> Self {
+        Self {
+            previous: None,
+            current,
+        }
+    }
+
+    /// Creates new transform which is interpolated from previous to current state.
+    pub fn with_previous(previous: Similarity3, current: Similarity3) -> Self {
+        Self {
+            previous: Some(previous),
+            current,
+        }
+    }
+
+    /// Makes current state previous and replaces it by provided one.
+    pub fn push(&mut self, current: Similarity3) {
+        self.previous = Some(std::mem::replace(&mut self.current, current));
+    }
+
+    /// Interpolates between previous and current states by the factor in range `[0; 1]`.
+    pub fn interpolate(&self, alpha: f32) -> Similarity3 {
+        let previous = match self.previous {
+            Some(previous) => previous,
+            None => return self.current,
+        };
+        let current = self.current;
+        Similarity3::new(
+            previous.translation.lerp(current.translation, alpha),
+            previous.rotation.lerp(current.rotation, alpha).normalized(),
+            previous.scale.lerp(current.scale, alpha),
+        )
+    }
+
+    /// Model matrix of the interpolated state.
+    pub fn matrix(&self, alpha: f32) -> Mat4 {
+        self.interpolate(alpha).into_homogeneous_matrix()
+    }
+}
+
+/// Handle of model transform which is shared between simulation and rendering.
+///
+/// Until any transform is set, the engine renders its default animation.
+///
+#[derive(Debug, Clone, Default)]
+pub struct TransformHandle(Arc<Mutex<Option<ModelTransform>>>);
+
+impl TransformHandle {
+    /// Pushes new simulation state, so the previous one is used for interpolation.
+    pub fn push(&self, current: Similarity3) {
+        let mut transform = self.0.lock().unwrap();
+        match transform.as_mut() {
+            Some(transform) => transform.push(current),
+            None => *transform = Some(ModelTransform::new(current)),
+        }
+    }
+
+    /// Replaces the transform by provided one.
+    pub fn set(&self, transform: ModelTransform) {
+        *self.0.lock().unwrap() = Some(transform);
+    }
+
+    /// Resets the transform, so the default animation of the engine is rendered.
+    pub fn clear(&self) {
+        *self.0.lock().unwrap() = None;
+    }
+
+    /// Current transform, if any.
+    pub fn get(&self) -> Option<ModelTransform> {
+        *self.0.lock().unwrap()
+    }
+}
@@ -0,0 +1,36 @@
+#![cfg(test)]
+
+use ultraviolet::{Rotor3, Similarity3, Vec3};
+
+use super::{ModelTransform, TransformHandle};
+
+fn at(x: f32) -> Similarity3 {
+    Similarity3::new(Vec3::new(x, 0.0, 0.0), Rotor3::identity(), 1.0)
+}
+
+#[test]
+fn single_state_is_not_interpolated() {
+    let transform = ModelTransform::new(at(2.0));
+    assert_eq!(transform.interpolate(0.0), at(2.0));
+    assert_eq!(transform.interpolate(0.5), at(2.0));
+}
+
+#[test]
+fn states_are_interpolated() {
+    let transform = ModelTransform::with_previous(at(0.0), at(2.0));
+    assert_eq!(transform.interpolate(0.0), at(0.0));
+    assert_eq!(transform.interpolate(0.25), at(0.5));
+    assert_eq!(transform.interpolate(1.0), at(2.0));
+}
+
+#[test]
+fn pushed_state_becomes_current() {
+    let handle = TransformHandle::default();
+    assert_eq!(handle.get(), None);
+    handle.push(at(1.0));
+    handle.push(at(3.0));
+    let transform = handle.get().unwrap();
+    assert_eq!(transform, ModelTransform::with_previous(at(1.0), at(3.0)));
+    handle.clear();
+    assert_eq!(handle.get(), None);
+}
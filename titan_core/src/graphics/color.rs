@@ -0,0 +1,174 @@
+//! Color utilities with explicit color space of the values.
+//!
+//! Colors are stored in linear space and converted into the encoding of the render target
+//! at use time: attachments with sRGB formats encode linear values by themselves,
+//! while values for UNORM attachments must be encoded by the engine.
+
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+use thiserror::Error;
+use vulkano::format::{Format, NumericType};
+
+mod tests;
+
+/// Error that can happen when parsing color from hex string.
+#[derive(Debug, Error, Eq, PartialEq)]
+pub enum ColorParseError {
+    #[error("hex color must contain 6 or 8 digits, got {0}")]
+    InvalidLength(usize),
+
+    #[error("invalid hex digit in color {0:?}")]
+    InvalidDigit(String),
+}
+
+/// Color with alpha channel.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Color {
+    /// Red, green, blue and alpha components in linear space.
+    linear: [f32; 4],
+}
+
+impl Color {
+    pub const BLACK: Self = Self::linear(0.0, 0.0, 0.0, 1.0);
+    pub const WHITE: Self = Self::linear(1.0, 1.0, 1.0, 1.0);
+    pub const TRANSPARENT: Self = Self::linear(0.0, 0.0, 0.0, 0.0);
+
+    /// Creates new color from components in linear space.
+    pub const fn linear(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self {
+            linear: [r, g, b, a],
+        }
+    }
+
+    /// Creates new color from sRGB encoded components in range `[0; 1]`.
+    ///
+    /// Alpha is always linear.
+    ///
+    pub fn srgb(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self::linear(
+            self::srgb_to_linear(r),
+            self::srgb_to_linear(g),
+            self::srgb_to_linear(b),
+            a,
+        )
+    }
+
+    /// Creates new color from 8-bit sRGB encoded components.
+    ///
+    /// Alpha is always linear.
+    ///
+    pub fn srgb8(r: u8, g: u8, b: u8, a: u8) -> Self {
+        let unorm = |value: u8| value as f32 / 255.0;
+        Self::srgb(unorm(r), unorm(g), unorm(b), unorm(a))
+    }
+
+    /// Parses sRGB color from hex string in form of `#RRGGBB` or `#RRGGBBAA`.
+    ///
+    /// Leading `#` is optional.
+    ///
+    pub fn from_hex(hex: &str) -> Result<Self, ColorParseError> {
+        let digits = hex.strip_prefix('#').unwrap_or(hex);
+        if digits.len() != 6 && digits.len() != 8 {
+            return Err(ColorParseError::InvalidLength(digits.len()));
+        }
+        let invalid = || ColorParseError::InvalidDigit(hex.to_string());
+        let component = |index: usize| {
+            let digits = digits.get(index * 2..index * 2 + 2).ok_or_else(invalid)?;
+            u8::from_str_radix(digits, 16).map_err(|_| invalid())
+        };
+        let alpha = if digits.len() == 8 {
+            component(3)?
+        } else {
+            255
+        };
+        Ok(Self::srgb8(
+            component(0)?,
+            component(1)?,
+            component(2)?,
+            alpha,
+        ))
+    }
+
+    /// Components of this color in linear space.
+    pub const fn to_linear(&self) -> [f32; 4] {
+        self.linear
+    }
+
+    /// Components of this color encoded in sRGB.
+    pub fn to_srgb(&self) -> [f32; 4] {
+        let [r, g, b, a] = self.linear;
+        [
+            self::linear_to_srgb(r),
+            self::linear_to_srgb(g),
+            self::linear_to_srgb(b),
+            a,
+        ]
+    }
+
+    /// Components of this color which must be written into the render target of provided format.
+    ///
+    /// Formats with sRGB numeric type encode linear values by themselves,
+    /// for any other format values are encoded in sRGB by the engine,
+    /// so both kinds of targets show the same color on the screen.
+    ///
+    pub fn for_format(&self, format: Format) -> [f32; 4] {
+        match format.type_color() {
+            Some(NumericType::SRGB) => self.to_linear(),
+            _ => self.to_srgb(),
+        }
+    }
+}
+
+impl FromStr for Color {
+    type Err = ColorParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_hex(s)
+    }
+}
+
+/// Handle of color which is shared between the game and rendering.
+///
+/// Initial color of the handle is white.
+///
+#[derive(Debug, Clone)]
+pub struct ColorHandle(Arc<Mutex<Color>>);
+
+impl Default for ColorHandle {
+    fn default() -> Self {
+        Self(Arc::new(Mutex::new(Color::WHITE)))
+    }
+}
+
+impl ColorHandle {
+    /// Replaces the color of this handle.
+    pub fn set(&self, color: Color) {
+        *self.0.lock().unwrap() = color;
+    }
+
+    /// Current color of this handle.
+    pub fn get(&self) -> Color {
+        *self.0.lock().unwrap()
+    }
+}
+
+/// Decodes sRGB encoded component into linear space.
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Encodes linear component into sRGB.
+pub fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
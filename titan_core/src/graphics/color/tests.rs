@@ -0,0 +1,74 @@
+#![cfg(test)]
+
+use vulkano::format::Format;
+
+use super::{linear_to_srgb, srgb_to_linear, Color, ColorParseError};
+
+const EPSILON: f32 = 1e-6;
+
+fn assert_close(actual: [f32; 4], expected: [f32; 4]) {
+    for (actual, expected) in actual.iter().zip(expected) {
+        assert!(
+            (actual - expected).abs() < EPSILON,
+            "{:?} != {:?}",
+            actual,
+            expected,
+        );
+    }
+}
+
+#[test]
+fn conversion_matches_reference_values() {
+    assert_eq!(srgb_to_linear(0.0), 0.0);
+    assert_eq!(srgb_to_linear(1.0), 1.0);
+    assert!((srgb_to_linear(0.5) - 0.21404114).abs() < EPSILON);
+    assert!((srgb_to_linear(0.04) - 0.003095975).abs() < EPSILON);
+    assert!((linear_to_srgb(0.21404114) - 0.5).abs() < EPSILON);
+    assert!((linear_to_srgb(0.002) - 0.02584).abs() < EPSILON);
+}
+
+#[test]
+fn conversion_round_trips() {
+    for value in 0..=255 {
+        let value = value as f32 / 255.0;
+        assert!((linear_to_srgb(srgb_to_linear(value)) - value).abs() < 1e-5);
+    }
+}
+
+#[test]
+fn srgb8_is_decoded_but_alpha_is_not() {
+    let color = Color::srgb8(128, 0, 255, 128);
+    assert_close(color.to_linear(), [0.21586053, 0.0, 1.0, 128.0 / 255.0]);
+    assert_close(color.to_srgb(), [128.0 / 255.0, 0.0, 1.0, 128.0 / 255.0]);
+}
+
+#[test]
+fn hex_is_parsed_as_srgb() {
+    assert_eq!(
+        Color::from_hex("#808080"),
+        Ok(Color::srgb8(128, 128, 128, 255))
+    );
+    assert_eq!("ff000080".parse(), Ok(Color::srgb8(255, 0, 0, 128)));
+    assert_eq!(
+        Color::from_hex("#12345"),
+        Err(ColorParseError::InvalidLength(5)),
+    );
+    assert_eq!(
+        Color::from_hex("#12345g"),
+        Err(ColorParseError::InvalidDigit("#12345g".to_string())),
+    );
+}
+
+#[test]
+fn value_depends_on_target_format() {
+    let grey = Color::srgb(0.5, 0.5, 0.5, 1.0);
+    assert_close(
+        grey.for_format(Format::B8G8R8A8_UNORM),
+        [0.5, 0.5, 0.5, 1.0],
+    );
+    let linear = srgb_to_linear(0.5);
+    assert_close(
+        grey.for_format(Format::B8G8R8A8_SRGB),
+        [linear, linear, linear, 1.0],
+    );
+}
@@ -0,0 +1,245 @@
+//! Compute pipelines and dispatch utilities of game engine.
+//!
+//! Descriptors of shaders which are loaded at runtime cannot be reflected,
+//! so layout of the only descriptor set (set `0`) of compute shader
+//! is described by kinds of its bindings, in order of binding numbers.
+
+use std::sync::Arc;
+
+use thiserror::Error;
+use vulkano::buffer::{BufferAccess, BufferUsage};
+use vulkano::command_buffer::DispatchError as VkDispatchError;
+use vulkano::descriptor_set::layout::{
+    DescriptorDesc, DescriptorDescImage, DescriptorDescTy, DescriptorSetDesc,
+};
+use vulkano::descriptor_set::{DescriptorSetError, PersistentDescriptorSet};
+use vulkano::device::Device;
+use vulkano::image::view::{ImageViewAbstract, ImageViewType};
+use vulkano::image::ImageUsage;
+use vulkano::pipeline::cache::PipelineCache;
+use vulkano::pipeline::shader::ShaderStages;
+use vulkano::pipeline::{
+    ComputePipeline as VkComputePipeline, ComputePipelineCreationError as VkCreationError,
+    PipelineBindPoint,
+};
+
+use crate::graphics::spirv::{Shader, ShaderStage};
+use crate::graphics::submit::{OneTimeBuilder, SubmitOnceError};
+
+mod tests;
+
+/// Kind of the resource which is bound to the binding of compute shader.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum BindingKind {
+    /// Buffer which is only read by the shader (`uniform` block in GLSL).
+    UniformBuffer,
+    /// Buffer which could be read and written by the shader (`buffer` block in GLSL).
+    StorageBuffer,
+    /// 2D image which could be read and written by the shader (`image2D` in GLSL).
+    ///
+    /// Image is accessed in `GENERAL` layout.
+    ///
+    StorageImage,
+}
+
+impl BindingKind {
+    fn descriptor_desc(self) -> DescriptorDesc {
+        let (ty, mutable) = match self {
+            Self::UniformBuffer => (DescriptorDescTy::UniformBuffer, false),
+            Self::StorageBuffer => (DescriptorDescTy::StorageBuffer, true),
+            Self::StorageImage => {
+                let image_desc = DescriptorDescImage {
+                    format: None,
+                    multisampled: false,
+                    view_type: ImageViewType::Dim2d,
+                };
+                (DescriptorDescTy::StorageImage { image_desc }, true)
+            }
+        };
+        DescriptorDesc {
+            ty,
+            descriptor_count: 1,
+            stages: ShaderStages::compute(),
+            variable_count: false,
+            mutable,
+        }
+    }
+}
+
+/// Describes descriptor set of compute shader with provided bindings,
+/// or `None` if there are no bindings.
+fn descriptor_set_desc(bindings: &[BindingKind]) -> Option<DescriptorSetDesc> {
+    if bindings.is_empty() {
+        return None;
+    }
+    let descriptors = bindings.iter().map(|kind| Some(kind.descriptor_desc()));
+    Some(DescriptorSetDesc::new(descriptors))
+}
+
+/// Error that can happen when creating compute pipeline.
+#[derive(Debug, Error)]
+pub enum ComputePipelineCreationError {
+    #[error("compute pipeline cannot be created from {0} shader")]
+    NotComputeStage(ShaderStage),
+
+    #[error("compute pipeline creation failure: {0}")]
+    Creation(#[from] VkCreationError),
+}
+
+/// Error that can happen when dispatching compute pipeline.
+#[derive(Debug, Error)]
+pub enum DispatchError {
+    #[error("device has no queue family which supports compute operations")]
+    Unsupported,
+
+    #[error("compute pipeline has {expected} bindings, but {actual} descriptors were provided")]
+    DescriptorCount { expected: usize, actual: usize },
+
+    #[error("descriptor of binding {binding} is not suitable for {kind:?} binding")]
+    DescriptorKind { binding: usize, kind: BindingKind },
+
+    #[error(
+        "resource of binding {binding} was created without usage required by {kind:?} binding"
+    )]
+    MissingUsage { binding: usize, kind: BindingKind },
+
+    #[error("descriptor set creation failure: {0}")]
+    DescriptorSet(#[from] DescriptorSetError),
+
+    #[error("dispatch command failure: {0}")]
+    Dispatch(#[from] VkDispatchError),
+
+    #[error("dispatch submit failure: {0}")]
+    Submit(#[from] SubmitOnceError),
+}
+
+/// Checks that count of provided descriptors matches count of bindings of the pipeline.
+fn check_descriptor_count(expected: usize, actual: usize) -> Result<(), DispatchError> {
+    if expected != actual {
+        return Err(DispatchError::DescriptorCount { expected, actual });
+    }
+    Ok(())
+}
+
+/// Usage which resource of the descriptor was created with.
+#[derive(Debug, Copy, Clone)]
+enum ResourceUsage {
+    Buffer(BufferUsage),
+    Image(ImageUsage),
+}
+
+/// Checks that the resource is suitable for the binding of provided kind,
+/// so misuse is reported before the descriptor reaches the driver.
+fn check_descriptor(
+    binding: usize,
+    kind: BindingKind,
+    usage: ResourceUsage,
+) -> Result<(), DispatchError> {
+    let supported = match (kind, usage) {
+        (BindingKind::UniformBuffer, ResourceUsage::Buffer(usage)) => usage.uniform_buffer,
+        (BindingKind::StorageBuffer, ResourceUsage::Buffer(usage)) => usage.storage_buffer,
+        (BindingKind::StorageImage, ResourceUsage::Image(usage)) => usage.storage,
+        _ => return Err(DispatchError::DescriptorKind { binding, kind }),
+    };
+    if !supported {
+        return Err(DispatchError::MissingUsage { binding, kind });
+    }
+    Ok(())
+}
+
+/// Resource which is bound to the binding of compute shader on dispatch.
+#[derive(Clone)]
+pub enum ComputeDescriptor {
+    /// Buffer for [uniform](BindingKind::UniformBuffer)
+    /// or [storage](BindingKind::StorageBuffer) binding.
+    Buffer(Arc<dyn BufferAccess>),
+    /// View of the image for [storage image](BindingKind::StorageImage) binding.
+    Image(Arc<dyn ImageViewAbstract>),
+}
+
+impl ComputeDescriptor {
+    fn usage(&self) -> ResourceUsage {
+        match self {
+            Self::Buffer(buffer) => ResourceUsage::Buffer(buffer.inner().buffer.usage()),
+            Self::Image(view) => ResourceUsage::Image(view.image().inner().image.usage()),
+        }
+    }
+}
+
+/// Pipeline which executes compute shader.
+#[derive(Clone)]
+pub struct ComputePipeline {
+    pipeline: Arc<VkComputePipeline>,
+    bindings: Vec<BindingKind>,
+}
+
+impl ComputePipeline {
+    /// Creates compute pipeline from provided shader with provided bindings of its descriptor set.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the shader is not a compute shader.
+    ///
+    pub fn new(
+        device: Arc<Device>,
+        shader: &Shader,
+        bindings: &[BindingKind],
+        cache: Option<Arc<PipelineCache>>,
+    ) -> Result<Self, ComputePipelineCreationError> {
+        if shader.stage() != ShaderStage::Compute {
+            return Err(ComputePipelineCreationError::NotComputeStage(
+                shader.stage(),
+            ));
+        }
+        let set_desc = self::descriptor_set_desc(bindings);
+        // SAFETY: entry point of the shader was checked when the shader was loaded,
+        // and the caller describes bindings which are declared by the shader.
+        let entry_point = unsafe {
+            let module = shader.module();
+            module.compute_entry_point(shader.entry_point(), set_desc, None, &[])
+        };
+        let pipeline = VkComputePipeline::new(device, &entry_point, &(), cache, |_| {})?;
+        Ok(Self {
+            pipeline: Arc::new(pipeline),
+            bindings: bindings.to_vec(),
+        })
+    }
+
+    /// Kinds of bindings of the descriptor set of this pipeline.
+    pub fn bindings(&self) -> &[BindingKind] {
+        &self.bindings
+    }
+
+    /// Records dispatch of this pipeline with provided count of work groups,
+    /// binding provided descriptors in order of their binding numbers.
+    ///
+    /// Barriers between this dispatch and other commands are inserted by the builder.
+    ///
+    pub fn record_dispatch(
+        &self,
+        builder: &mut OneTimeBuilder,
+        groups: [u32; 3],
+        descriptors: Vec<ComputeDescriptor>,
+    ) -> Result<(), DispatchError> {
+        self::check_descriptor_count(self.bindings.len(), descriptors.len())?;
+        let bindings = self.bindings.iter().zip(&descriptors).enumerate();
+        for (binding, (kind, descriptor)) in bindings {
+            self::check_descriptor(binding, *kind, descriptor.usage())?;
+        }
+        let layout = self.pipeline.layout().clone();
+        builder.bind_pipeline_compute(self.pipeline.clone());
+        if let Some(set_layout) = layout.descriptor_set_layouts().first() {
+            let mut set = PersistentDescriptorSet::start(set_layout.clone());
+            for descriptor in descriptors {
+                match descriptor {
+                    ComputeDescriptor::Buffer(buffer) => set.add_buffer(buffer)?,
+                    ComputeDescriptor::Image(view) => set.add_image(view)?,
+                };
+            }
+            let set = Arc::new(set.build()?);
+            builder.bind_descriptor_sets(PipelineBindPoint::Compute, layout, 0, set);
+        }
+        builder.dispatch(groups)?;
+        Ok(())
+    }
+}
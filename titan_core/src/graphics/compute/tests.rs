@@ -0,0 +1,94 @@
+#![cfg(test)]
+
+use vulkano::buffer::BufferUsage;
+use vulkano::descriptor_set::layout::DescriptorDescTy;
+use vulkano::image::ImageUsage;
+use vulkano::pipeline::shader::ShaderStages;
+
+use super::{
+    check_descriptor, check_descriptor_count, descriptor_set_desc, BindingKind, DispatchError,
+    ResourceUsage,
+};
+
+#[test]
+fn bindings_are_numbered_in_order() {
+    let bindings = [BindingKind::StorageBuffer, BindingKind::UniformBuffer];
+    let desc = descriptor_set_desc(&bindings).unwrap();
+    assert_eq!(desc.bindings().len(), 2);
+
+    let storage = desc.descriptor(0).unwrap();
+    assert_eq!(storage.ty, DescriptorDescTy::StorageBuffer);
+    assert_eq!(storage.stages, ShaderStages::compute());
+    assert!(storage.mutable);
+
+    let uniform = desc.descriptor(1).unwrap();
+    assert_eq!(uniform.ty, DescriptorDescTy::UniformBuffer);
+    assert!(!uniform.mutable);
+}
+
+#[test]
+fn no_bindings_means_no_descriptor_set() {
+    assert!(descriptor_set_desc(&[]).is_none());
+}
+
+#[test]
+fn descriptor_count_must_match_bindings() {
+    assert!(check_descriptor_count(2, 2).is_ok());
+    assert!(matches!(
+        check_descriptor_count(2, 1),
+        Err(DispatchError::DescriptorCount {
+            expected: 2,
+            actual: 1
+        }),
+    ));
+}
+
+#[test]
+fn storage_image_is_mutable() {
+    let desc = descriptor_set_desc(&[BindingKind::StorageImage]).unwrap();
+    let image = desc.descriptor(0).unwrap();
+    assert!(matches!(image.ty, DescriptorDescTy::StorageImage { .. }));
+    assert!(image.mutable);
+}
+
+#[test]
+fn resource_must_have_usage_of_binding() {
+    let storage = ResourceUsage::Buffer(BufferUsage {
+        storage_buffer: true,
+        ..BufferUsage::none()
+    });
+    assert!(check_descriptor(0, BindingKind::StorageBuffer, storage).is_ok());
+    assert!(matches!(
+        check_descriptor(1, BindingKind::UniformBuffer, storage),
+        Err(DispatchError::MissingUsage {
+            binding: 1,
+            kind: BindingKind::UniformBuffer,
+        }),
+    ));
+
+    let image = ResourceUsage::Image(ImageUsage {
+        storage: true,
+        ..ImageUsage::none()
+    });
+    assert!(check_descriptor(0, BindingKind::StorageImage, image).is_ok());
+    let sampled = ResourceUsage::Image(ImageUsage::none());
+    assert!(matches!(
+        check_descriptor(0, BindingKind::StorageImage, sampled),
+        Err(DispatchError::MissingUsage { .. }),
+    ));
+}
+
+#[test]
+fn resource_must_match_kind_of_binding() {
+    let image = ResourceUsage::Image(ImageUsage {
+        storage: true,
+        ..ImageUsage::none()
+    });
+    assert!(matches!(
+        check_descriptor(2, BindingKind::StorageBuffer, image),
+        Err(DispatchError::DescriptorKind {
+            binding: 2,
+            kind: BindingKind::StorageBuffer,
+        }),
+    ));
+}
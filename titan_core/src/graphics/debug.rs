@@ -0,0 +1,417 @@
+//! Graphics debugging utilities for game engine.
+
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::panic::AssertUnwindSafe;
+use std::sync::{Arc, Mutex};
+
+use log::Level;
+use vulkano::buffer::sys::UnsafeBuffer;
+use vulkano::command_buffer::sys::UnsafeCommandBuffer;
+use vulkano::command_buffer::AutoCommandBufferBuilder;
+use vulkano::device::{Device, DeviceOwned};
+use vulkano::image::sys::UnsafeImage;
+use vulkano::instance::debug::{
+    DebugCallback, DebugCallbackCreationError, Message, MessageSeverity, MessageType,
+};
+use vulkano::instance::Instance;
+use vulkano::pipeline::{ComputePipeline, GraphicsPipeline};
+use vulkano::sampler::Sampler;
+use vulkano::swapchain::Swapchain;
+use vulkano::sync::{Fence, Semaphore};
+use vulkano::{Handle, VulkanObject};
+
+mod tests;
+
+/// Filter of messages which are reported by validation layers.
+///
+/// By default, messages of all severities and types are reported.
+///
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct DebugFilter {
+    pub error: bool,
+    pub warning: bool,
+    pub info: bool,
+    pub verbose: bool,
+    pub general: bool,
+    pub validation: bool,
+    pub performance: bool,
+}
+
+impl DebugFilter {
+    /// Filter which reports all messages.
+    pub const ALL: Self = Self {
+        error: true,
+        warning: true,
+        info: true,
+        verbose: true,
+        general: true,
+        validation: true,
+        performance: true,
+    };
+
+    /// Filter which reports errors and warnings of all types.
+    pub const WARNINGS: Self = Self {
+        info: false,
+        verbose: false,
+        ..Self::ALL
+    };
+
+    fn severity(self) -> MessageSeverity {
+        MessageSeverity {
+            error: self.error,
+            warning: self.warning,
+            information: self.info,
+            verbose: self.verbose,
+        }
+    }
+
+    fn ty(self) -> MessageType {
+        MessageType {
+            general: self.general,
+            validation: self.validation,
+            performance: self.performance,
+        }
+    }
+}
+
+impl Default for DebugFilter {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+/// Severity of message reported by validation layers.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub enum DebugSeverity {
+    Verbose,
+    Info,
+    Warning,
+    Error,
+}
+
+impl DebugSeverity {
+    /// Level of the global logger which messages of this severity are logged with.
+    pub const fn log_level(self) -> Level {
+        match self {
+            Self::Verbose => Level::Trace,
+            Self::Info => Level::Info,
+            Self::Warning => Level::Warn,
+            Self::Error => Level::Error,
+        }
+    }
+}
+
+/// Type of message reported by validation layers.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DebugMessageType {
+    General,
+    Validation,
+    Performance,
+}
+
+impl fmt::Display for DebugMessageType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::General => "GENERAL",
+            Self::Validation => "VALIDATION",
+            Self::Performance => "PERFORMANCE",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Message reported by validation layers, which could outlive the debug callback.
+///
+/// Labels of queues, command buffers and objects are not provided by the backend,
+/// but validation layers include object names into the [text](Self::text).
+///
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DebugMessage {
+    pub severity: DebugSeverity,
+    pub ty: DebugMessageType,
+    /// Name of message ID, such as `VUID-vkCmdDraw-None-02859`.
+    pub id_name: Option<String>,
+    /// Number of message ID, if it was found in the text of the message.
+    pub id_number: Option<u32>,
+    pub text: String,
+}
+
+impl DebugMessage {
+    fn new(message: &Message) -> Self {
+        Self {
+            severity: self::severity(message.severity),
+            ty: self::message_type(message.ty),
+            id_name: message.layer_prefix.map(str::to_string),
+            id_number: self::parse_message_id(message.description),
+            text: message.description.to_string(),
+        }
+    }
+}
+
+/// User callback which receives messages of validation layers.
+///
+/// Returning `true` suppresses default logging of the message.
+///
+#[derive(Clone)]
+pub struct DebugMessageCallback(Arc<dyn Fn(DebugMessage) -> bool + Send + Sync>);
+
+impl DebugMessageCallback {
+    /// Creates new callback from the function.
+    pub fn new(callback: impl Fn(DebugMessage) -> bool + Send + Sync + 'static) -> Self {
+        Self(Arc::new(callback))
+    }
+}
+
+impl fmt::Debug for DebugMessageCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("DebugMessageCallback")
+            .finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for DebugMessageCallback {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// Objects which could be given a name shown by validation layers and debugging tools.
+pub trait Nameable {
+    /// Sets debug name of the object.
+    ///
+    /// Does nothing if `VK_EXT_debug_utils` extension is not enabled.
+    ///
+    fn set_name(&self, name: &str);
+}
+
+/// Implements [`Nameable`] for types which are owned by the device.
+macro_rules! nameable {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl Nameable for $ty {
+                fn set_name(&self, name: &str) {
+                    self::set_object_name(self.device(), self, name)
+                }
+            }
+        )*
+    };
+}
+
+nameable!(
+    UnsafeBuffer,
+    UnsafeCommandBuffer,
+    UnsafeImage,
+    GraphicsPipeline,
+    ComputePipeline,
+    Sampler,
+    Fence,
+    Semaphore,
+);
+
+impl<W> Nameable for Swapchain<W> {
+    fn set_name(&self, name: &str) {
+        self::set_object_name(self.device(), self, name)
+    }
+}
+
+fn set_object_name<T: VulkanObject>(device: &Device, object: &T, name: &str) {
+    if !device.instance().enabled_extensions().ext_debug_utils {
+        return;
+    }
+    let name = self::object_name(name);
+    // SAFETY: object is owned by the device and its type is described by its handle.
+    let result = unsafe {
+        device.set_object_name_raw(T::Object::TYPE, object.internal_object().as_raw(), &name)
+    };
+    if let Err(error) = result {
+        log::debug!("failed to set name {:?} of object: {}", name, error);
+    }
+}
+
+/// Converts name into C string, replacing interior nul characters.
+fn object_name(name: &str) -> CString {
+    let name = name.replace('\0', "\u{fffd}");
+    CString::new(name).expect("interior nul characters were replaced")
+}
+
+/// Command buffers which could contain debug labels shown by debugging tools.
+///
+/// Labels are not recorded if `VK_EXT_debug_utils` extension is not enabled.
+///
+pub trait DebugLabels<L> {
+    /// Opens labeled region of commands which is closed when returned scope is dropped.
+    ///
+    /// Commands of the region are recorded through the scope.
+    ///
+    fn debug_scope(&mut self, name: &'static str, color: [f32; 4]) -> DebugScope<'_, L>;
+
+    /// Inserts single label into the command buffer.
+    fn debug_label(&mut self, name: &'static str, color: [f32; 4]);
+}
+
+impl<L> DebugLabels<L> for AutoCommandBufferBuilder<L> {
+    fn debug_scope(&mut self, name: &'static str, color: [f32; 4]) -> DebugScope<'_, L> {
+        let active = self::begin_label(self, name, color);
+        DebugScope {
+            builder: self,
+            active,
+        }
+    }
+
+    fn debug_label(&mut self, name: &'static str, color: [f32; 4]) {
+        if !self::labels_enabled(self) {
+            return;
+        }
+        if let Err(error) = self.debug_marker_insert(self::label_name(name), color) {
+            log::debug!("failed to insert debug label {:?}: {}", name, error);
+        }
+    }
+}
+
+/// Labeled region of commands which is closed on drop.
+pub struct DebugScope<'a, L> {
+    builder: &'a mut AutoCommandBufferBuilder<L>,
+    active: bool,
+}
+
+impl<L> Deref for DebugScope<'_, L> {
+    type Target = AutoCommandBufferBuilder<L>;
+
+    fn deref(&self) -> &Self::Target {
+        self.builder
+    }
+}
+
+impl<L> DerefMut for DebugScope<'_, L> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.builder
+    }
+}
+
+impl<L> Drop for DebugScope<'_, L> {
+    fn drop(&mut self) {
+        if self.active {
+            self::end_label(self.builder);
+        }
+    }
+}
+
+fn labels_enabled<L>(builder: &AutoCommandBufferBuilder<L>) -> bool {
+    builder
+        .device()
+        .instance()
+        .enabled_extensions()
+        .ext_debug_utils
+}
+
+/// Opens labeled region, returns `true` if the region was opened and must be closed.
+pub(crate) fn begin_label<L>(
+    builder: &mut AutoCommandBufferBuilder<L>,
+    name: &'static str,
+    color: [f32; 4],
+) -> bool {
+    if !self::labels_enabled(builder) {
+        return false;
+    }
+    match builder.debug_marker_begin(self::label_name(name), color) {
+        Ok(_) => true,
+        Err(error) => {
+            log::debug!("failed to begin debug label {:?}: {}", name, error);
+            false
+        }
+    }
+}
+
+/// Closes labeled region which was opened by [`begin_label`].
+pub(crate) fn end_label<L>(builder: &mut AutoCommandBufferBuilder<L>) {
+    if let Err(error) = builder.debug_marker_end() {
+        log::debug!("failed to end debug label: {}", error);
+    }
+}
+
+/// Interns name of the label as C string, which is allocated once per distinct name.
+fn label_name(name: &'static str) -> &'static CStr {
+    lazy_static::lazy_static! {
+        static ref NAMES: Mutex<HashMap<&'static str, &'static CStr>> = Mutex::default();
+    }
+
+    let mut names = NAMES.lock().unwrap();
+    names
+        .entry(name)
+        .or_insert_with(|| Box::leak(self::object_name(name).into_boxed_c_str()))
+}
+
+/// Create debug callback for validation via Vulkan SDK.
+///
+/// Note that Khronos validation layer must be enabled.
+///
+pub fn create_debug_callback(
+    instance: &Arc<Instance>,
+    filter: DebugFilter,
+    user_callback: Option<DebugMessageCallback>,
+) -> Result<DebugCallback, DebugCallbackCreationError> {
+    let user_callback = AssertUnwindSafe(user_callback);
+    DebugCallback::new(instance, filter.severity(), filter.ty(), move |message| {
+        let message = DebugMessage::new(message);
+        let suppressed = match &*user_callback {
+            Some(DebugMessageCallback(callback)) => callback(message.clone()),
+            None => false,
+        };
+        if !suppressed {
+            self::log_message(&message);
+        }
+    })
+}
+
+/// Logs message into global logger.
+fn log_message(message: &DebugMessage) {
+    let id_name = message.id_name.as_deref().unwrap_or("Unknown");
+    log::log!(
+        message.severity.log_level(),
+        r#"{} [id "{}"]: "{}""#,
+        message.ty,
+        id_name,
+        message.text,
+    );
+}
+
+fn severity(severity: MessageSeverity) -> DebugSeverity {
+    match severity {
+        MessageSeverity { error: true, .. } => DebugSeverity::Error,
+        MessageSeverity { warning: true, .. } => DebugSeverity::Warning,
+        MessageSeverity {
+            information: true, ..
+        } => DebugSeverity::Info,
+        _ => DebugSeverity::Verbose,
+    }
+}
+
+fn message_type(ty: MessageType) -> DebugMessageType {
+    match ty {
+        MessageType {
+            validation: true, ..
+        } => DebugMessageType::Validation,
+        MessageType {
+            performance: true, ..
+        } => DebugMessageType::Performance,
+        _ => DebugMessageType::General,
+    }
+}
+
+/// Parses number of message ID from the text of the message,
+/// which validation layers write as `MessageID = 0x...`.
+fn parse_message_id(text: &str) -> Option<u32> {
+    const PREFIX: &str = "MessageID = 0x";
+
+    let start = text.find(PREFIX)? + PREFIX.len();
+    let digits = &text[start..];
+    let end = digits
+        .find(|c: char| !c.is_ascii_hexdigit())
+        .unwrap_or(digits.len());
+    u32::from_str_radix(&digits[..end], 16).ok()
+}
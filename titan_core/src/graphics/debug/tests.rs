@@ -0,0 +1,59 @@
+#![cfg(test)]
+
+use vulkano::instance::debug::{MessageSeverity, MessageType};
+
+use super::{
+    label_name, message_type, object_name, parse_message_id, severity, DebugFilter,
+    DebugMessageType, DebugSeverity,
+};
+
+#[test]
+fn message_id_is_parsed_from_text() {
+    let text = "Validation Error: [ VUID-vkCmdDraw-None-02859 ] Object 0: handle = 0x55, \
+                type = VK_OBJECT_TYPE_COMMAND_BUFFER; | MessageID = 0x6294cbd7 | vkCmdDraw()";
+    assert_eq!(parse_message_id(text), Some(0x6294cbd7));
+    assert_eq!(parse_message_id("MessageID = 0x1f"), Some(0x1f));
+    assert_eq!(parse_message_id("no id here"), None);
+}
+
+#[test]
+fn severities_are_mapped_to_log_levels() {
+    let mut flags = MessageSeverity::none();
+    flags.error = true;
+    assert_eq!(severity(flags), DebugSeverity::Error);
+    assert_eq!(DebugSeverity::Error.log_level(), log::Level::Error);
+    assert_eq!(DebugSeverity::Warning.log_level(), log::Level::Warn);
+    assert_eq!(DebugSeverity::Info.log_level(), log::Level::Info);
+    assert_eq!(severity(MessageSeverity::none()), DebugSeverity::Verbose);
+    assert_eq!(DebugSeverity::Verbose.log_level(), log::Level::Trace);
+}
+
+#[test]
+fn message_types_are_mapped() {
+    let mut flags = MessageType::general();
+    assert_eq!(message_type(flags), DebugMessageType::General);
+    flags.performance = true;
+    assert_eq!(message_type(flags), DebugMessageType::Performance);
+}
+
+#[test]
+fn filter_converts_into_flags() {
+    let filter = DebugFilter::WARNINGS;
+    let severity = filter.severity();
+    assert!(severity.error && severity.warning);
+    assert!(!severity.information && !severity.verbose);
+    assert_eq!(filter.ty(), MessageType::all());
+}
+
+#[test]
+fn object_name_replaces_nul() {
+    assert_eq!(object_name("swapchain").to_str(), Ok("swapchain"));
+    assert_eq!(object_name("a\0b").to_str(), Ok("a\u{fffd}b"));
+}
+
+#[test]
+fn label_names_are_interned() {
+    let name = label_name("main pass");
+    assert_eq!(name.to_str(), Ok("main pass"));
+    assert!(std::ptr::eq(name, label_name("main pass")));
+}
@@ -0,0 +1,142 @@
+//! Reporting of failures of shaders and pipelines of game engine.
+//!
+//! Failures are delivered to the main callback as [events](crate::window::Event)
+//! and are kept in the registry until the asset is loaded successfully,
+//! so debug UI could list them. Repeated identical failures are counted instead of reported.
+
+use std::fmt;
+
+use crate::graphics::spirv::ShaderStage;
+
+mod tests;
+
+/// Name of the material of game objects in reported pipeline failures.
+pub const OBJECT_MATERIAL: &str = "object";
+
+/// Kind of the asset which has failed.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum FailureKind {
+    /// Shader of provided stage could not be loaded.
+    ShaderCompile { stage: ShaderStage },
+    /// Pipeline of the material could not be built.
+    PipelineBuild,
+}
+
+/// Failure of the asset with its diagnostics.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AssetFailure {
+    /// Path or name of the shader, or name of the material.
+    pub name: String,
+    /// Kind of the asset.
+    pub kind: FailureKind,
+    /// Diagnostics of the failure.
+    pub diagnostics: String,
+}
+
+impl AssetFailure {
+    /// Failure of the shader with provided path or name.
+    pub fn shader(name: impl Into<String>, stage: ShaderStage, error: &impl fmt::Display) -> Self {
+        Self {
+            name: name.into(),
+            kind: FailureKind::ShaderCompile { stage },
+            diagnostics: error.to_string(),
+        }
+    }
+
+    /// Failure of the pipeline of the material with provided name.
+    pub fn pipeline(material: impl Into<String>, error: &impl fmt::Display) -> Self {
+        Self {
+            name: material.into(),
+            kind: FailureKind::PipelineBuild,
+            diagnostics: error.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for AssetFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            FailureKind::ShaderCompile { stage } => {
+                write!(f, "{} shader \"{}\"", stage, self.name)?
+            }
+            FailureKind::PipelineBuild => write!(f, "pipeline of material \"{}\"", self.name)?,
+        }
+        write!(f, " failed: {}", self.diagnostics)
+    }
+}
+
+/// Failed asset with count of its identical failures.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FailedAsset {
+    /// The last failure of the asset.
+    pub failure: AssetFailure,
+    /// Count of identical failures in a row.
+    pub count: u32,
+    /// Count of draw calls which used the error material instead of the failed pipeline.
+    pub fallback_draws: u64,
+}
+
+impl FailedAsset {
+    fn new(failure: AssetFailure) -> Self {
+        Self {
+            failure,
+            count: 1,
+            fallback_draws: 0,
+        }
+    }
+}
+
+/// Registry of assets whose last attempt to load has failed.
+#[derive(Debug, Default)]
+pub struct FailureRegistry {
+    failed: Vec<FailedAsset>,
+}
+
+impl FailureRegistry {
+    /// Records failure of the asset.
+    ///
+    /// Returns `true` if the failure should be reported,
+    /// or `false` if the previous failure of the asset was identical, so it is only counted.
+    ///
+    pub fn record(&mut self, failure: AssetFailure) -> bool {
+        let previous = self.failed.iter_mut().find(|failed| {
+            failed.failure.name == failure.name && failed.failure.kind == failure.kind
+        });
+        match previous {
+            Some(previous) if previous.failure.diagnostics == failure.diagnostics => {
+                previous.count += 1;
+                false
+            }
+            Some(previous) => {
+                *previous = FailedAsset::new(failure);
+                true
+            }
+            None => {
+                self.failed.push(FailedAsset::new(failure));
+                true
+            }
+        }
+    }
+
+    /// Forgets failures of the asset because it was loaded successfully.
+    pub fn resolve(&mut self, name: &str, kind: FailureKind) {
+        self.failed
+            .retain(|failed| failed.failure.name != name || failed.failure.kind != kind);
+    }
+
+    /// Counts draw calls which used the error material
+    /// instead of the failed pipeline of the material.
+    pub fn count_fallback_draws(&mut self, material: &str, draws: u64) {
+        let failed = self.failed.iter_mut().find(|failed| {
+            failed.failure.name == material && failed.failure.kind == FailureKind::PipelineBuild
+        });
+        if let Some(failed) = failed {
+            failed.fallback_draws += draws;
+        }
+    }
+
+    /// Assets whose last attempt to load has failed, in order of their first failure.
+    pub fn failed(&self) -> &[FailedAsset] {
+        &self.failed
+    }
+}
@@ -0,0 +1,84 @@
+#![cfg(test)]
+
+use crate::graphics::spirv::{self, ShaderStage};
+
+use super::{AssetFailure, FailureKind, FailureRegistry};
+
+/// Failure of the shader which is loaded from provided bytes.
+fn broken_shader(name: &str, bytes: &[u8]) -> AssetFailure {
+    let error = spirv::spirv_words(bytes).unwrap_err();
+    AssetFailure::shader(name, ShaderStage::Fragment, &error)
+}
+
+#[test]
+fn malformed_spirv_is_described() {
+    let failure = broken_shader("broken.frag.spv", b"\x03\x02\x23");
+    assert_eq!(
+        failure.kind,
+        FailureKind::ShaderCompile {
+            stage: ShaderStage::Fragment
+        }
+    );
+    assert_eq!(
+        failure.to_string(),
+        "fragment shader \"broken.frag.spv\" failed: \
+         length of SPIR-V module must be multiple of 4, but it is 3",
+    );
+}
+
+#[test]
+fn identical_failures_are_counted() {
+    let mut registry = FailureRegistry::default();
+    assert!(registry.record(broken_shader("a.spv", b"")));
+    assert!(!registry.record(broken_shader("a.spv", b"")));
+    assert!(!registry.record(broken_shader("a.spv", b"")));
+    assert!(registry.record(broken_shader("b.spv", b"")));
+    assert_eq!(registry.failed().len(), 2);
+    assert_eq!(registry.failed()[0].count, 3);
+    assert_eq!(registry.failed()[1].count, 1);
+}
+
+#[test]
+fn changed_failure_is_reported_again() {
+    let mut registry = FailureRegistry::default();
+    assert!(registry.record(broken_shader("a.spv", b"")));
+    assert!(registry.record(broken_shader("a.spv", b"\x7fELF")));
+    let failed = &registry.failed()[0];
+    assert_eq!(failed.count, 1);
+    assert!(failed.failure.diagnostics.contains("magic number"));
+}
+
+#[test]
+fn resolved_asset_is_forgotten() {
+    let mut registry = FailureRegistry::default();
+    let kind = FailureKind::ShaderCompile {
+        stage: ShaderStage::Fragment,
+    };
+    registry.record(broken_shader("a.spv", b""));
+    registry.record(AssetFailure::pipeline("a.spv", &"no memory"));
+    registry.resolve("a.spv", kind);
+    assert_eq!(registry.failed().len(), 1);
+    assert_eq!(
+        registry.failed()[0].failure.kind,
+        FailureKind::PipelineBuild
+    );
+    // Failure after resolve is reported again.
+    assert!(registry.record(broken_shader("a.spv", b"")));
+}
+
+#[test]
+fn fallback_draws_are_counted_for_failed_pipelines() {
+    let mut registry = FailureRegistry::default();
+    registry.record(broken_shader("object", b""));
+    registry.count_fallback_draws("object", 2);
+    assert_eq!(registry.failed()[0].fallback_draws, 0);
+
+    registry.record(AssetFailure::pipeline("object", &"interface mismatch"));
+    registry.count_fallback_draws("object", 2);
+    registry.count_fallback_draws("object", 3);
+    assert_eq!(registry.failed()[1].fallback_draws, 5);
+    // Fallback draws of resolved material are not counted.
+    registry.resolve("object", FailureKind::PipelineBuild);
+    registry.count_fallback_draws("object", 1);
+    assert_eq!(registry.failed().len(), 1);
+}
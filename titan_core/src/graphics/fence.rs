@@ -0,0 +1,89 @@
+//! Fence utilities for graphics backend of game engine.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use vulkano::sync::{FenceSignalFuture, FlushError, GpuFuture};
+
+mod tests;
+
+/// Result of waiting for the fence with the timeout.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum WaitResult {
+    /// Fence was signaled before the timeout has elapsed.
+    Signaled,
+    /// Timeout has elapsed before the fence was signaled.
+    TimedOut,
+}
+
+/// Fence which is signaled when the device finished the frame.
+pub trait FrameFence: Send + Sync {
+    /// Blocks the current thread until the frame is finished or the timeout has elapsed.
+    ///
+    /// `None` means that the wait is infinite.
+    ///
+    fn wait_timeout(&self, timeout: Option<Duration>) -> Result<WaitResult, FlushError>;
+
+    /// Blocks the current thread until the frame is finished.
+    fn wait(&self) -> Result<(), FlushError> {
+        self.wait_timeout(None).map(drop)
+    }
+
+    /// Checks if the frame is finished without blocking.
+    fn is_signaled(&self) -> Result<bool, FlushError> {
+        let result = self.wait_timeout(Some(Duration::ZERO))?;
+        Ok(result == WaitResult::Signaled)
+    }
+}
+
+impl<F> FrameFence for FenceSignalFuture<F>
+where
+    F: GpuFuture + Send + Sync,
+{
+    fn wait_timeout(&self, timeout: Option<Duration>) -> Result<WaitResult, FlushError> {
+        match FenceSignalFuture::wait(self, timeout) {
+            Ok(()) => Ok(WaitResult::Signaled),
+            Err(FlushError::Timeout) => Ok(WaitResult::TimedOut),
+            Err(error) => Err(error),
+        }
+    }
+}
+
+/// Blocks the current thread until all (if `wait_all` is `true`) or any of provided fences
+/// are signaled, or until the timeout has elapsed.
+///
+/// Empty set of fences is considered to be signaled. `None` timeout means that the wait is infinite.
+///
+pub fn wait_all<'a>(
+    fences: impl IntoIterator<Item = &'a dyn FrameFence>,
+    wait_all: bool,
+    timeout: Option<Duration>,
+) -> Result<WaitResult, FlushError> {
+    let fences: Vec<_> = fences.into_iter().collect();
+    let deadline = timeout.and_then(|timeout| Instant::now().checked_add(timeout));
+    let remaining = || deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+
+    if wait_all {
+        for fence in fences {
+            if fence.wait_timeout(remaining())? == WaitResult::TimedOut {
+                return Ok(WaitResult::TimedOut);
+            }
+        }
+        return Ok(WaitResult::Signaled);
+    }
+    if fences.is_empty() {
+        return Ok(WaitResult::Signaled);
+    }
+    // Futures of vulkano do not expose their fences, so they are polled in turns.
+    loop {
+        for fence in &fences {
+            if fence.is_signaled()? {
+                return Ok(WaitResult::Signaled);
+            }
+        }
+        if remaining() == Some(Duration::ZERO) {
+            return Ok(WaitResult::TimedOut);
+        }
+        thread::yield_now();
+    }
+}
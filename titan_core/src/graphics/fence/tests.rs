@@ -0,0 +1,81 @@
+#![cfg(test)]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use vulkano::sync::FlushError;
+
+use super::{wait_all, FrameFence, WaitResult};
+
+/// Fence which is signaled after it was polled provided count of times.
+struct CountdownFence {
+    polls_left: AtomicUsize,
+}
+
+impl CountdownFence {
+    fn new(polls: usize) -> Self {
+        let polls_left = AtomicUsize::new(polls);
+        Self { polls_left }
+    }
+}
+
+impl FrameFence for CountdownFence {
+    fn wait_timeout(&self, timeout: Option<Duration>) -> Result<WaitResult, FlushError> {
+        match timeout {
+            None => {
+                self.polls_left.store(0, Ordering::SeqCst);
+                Ok(WaitResult::Signaled)
+            }
+            Some(_) => {
+                let polled =
+                    self.polls_left
+                        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |left| {
+                            left.checked_sub(1)
+                        });
+                match polled {
+                    Ok(_) => Ok(WaitResult::TimedOut),
+                    Err(_) => Ok(WaitResult::Signaled),
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn unsignaled_fence_times_out() {
+    let fence = CountdownFence::new(usize::MAX);
+    assert_eq!(fence.is_signaled(), Ok(false));
+    let result = fence.wait_timeout(Some(Duration::ZERO));
+    assert_eq!(result, Ok(WaitResult::TimedOut));
+    assert_eq!(fence.wait(), Ok(()));
+    assert_eq!(fence.is_signaled(), Ok(true));
+}
+
+#[test]
+fn wait_all_requires_every_fence() {
+    let signaled = CountdownFence::new(0);
+    let pending = CountdownFence::new(usize::MAX);
+    let fences: [&dyn FrameFence; 2] = [&signaled, &pending];
+    let result = wait_all(fences, true, Some(Duration::ZERO));
+    assert_eq!(result, Ok(WaitResult::TimedOut));
+    let result = wait_all(fences, true, None);
+    assert_eq!(result, Ok(WaitResult::Signaled));
+}
+
+#[test]
+fn wait_any_polls_until_some_fence_is_signaled() {
+    let pending = CountdownFence::new(usize::MAX);
+    let late = CountdownFence::new(3);
+    let fences: [&dyn FrameFence; 2] = [&pending, &late];
+    assert_eq!(wait_all(fences, false, None), Ok(WaitResult::Signaled));
+
+    let fences: [&dyn FrameFence; 1] = [&pending];
+    let result = wait_all(fences, false, Some(Duration::ZERO));
+    assert_eq!(result, Ok(WaitResult::TimedOut));
+}
+
+#[test]
+fn empty_set_is_signaled() {
+    assert_eq!(wait_all([], true, None), Ok(WaitResult::Signaled));
+    assert_eq!(wait_all([], false, None), Ok(WaitResult::Signaled));
+}
@@ -0,0 +1,23 @@
+use std::error::Error as StdError;
+
+use thiserror::Error;
+use vulkano::command_buffer::BuildError;
+use vulkano::OomError;
+
+/// Error which is returned by callback of external pass.
+pub type ExternalRecordError = Box<dyn StdError + Send + Sync>;
+
+#[derive(Debug, Error)]
+pub enum ExternalPassError {
+    #[error("command buffer allocation failure: {0}")]
+    OutOfMemory(#[from] OomError),
+
+    #[error("external pass {id} recording failure: {source}")]
+    Record {
+        id: u64,
+        source: ExternalRecordError,
+    },
+
+    #[error("external pass {id} command buffer build failure: {source}")]
+    CommandBufferBuild { id: u64, source: BuildError },
+}
@@ -0,0 +1,288 @@
+//! Passes with commands which are recorded by the user and executed inside of engine frame.
+//!
+//! # Synchronization contract
+//!
+//! Callback of external pass records commands into a secondary command buffer
+//! which inherits the current subpass of the engine render pass:
+//!
+//! - render pass cannot be begun or ended inside of the callback,
+//!   so the state of the render pass always stays balanced;
+//! - resources which are used by the commands must be kept alive by `Arc`,
+//!   vulkano keeps them alive until the frame is finished by the device;
+//! - commands are executed after all commands of the previous passes of the same stage
+//!   and before the commands of the next ones, there is no need for additional barriers
+//!   inside of one subpass;
+//! - callback must not submit anything to the queues by itself.
+//!
+//! # Parallel recording
+//!
+//! Callback could split its commands between several worker threads:
+//! [`FrameContext`] is cheap to clone and could be sent to other threads,
+//! where each thread begins its own secondary command buffer
+//! with [`FrameContext::begin_secondary`]. Built command buffers are handed back
+//! to the callback with [`ExternalRecordCtx::execute_secondary`] before it returns,
+//! so they are executed from the primary command buffer right after the commands
+//! of the callback, in order of submission.
+//!
+//! # Nominated draw
+//!
+//! Commands of one pass could be wrapped into [occlusion query](FrameContext::occlusion_query)
+//! of the frame, so their count of samples which passed depth and stencil tests
+//! is reported in [frame statistics](crate::app::stats::FrameStats::occlusion_samples).
+
+use std::sync::Arc;
+
+use vulkano::command_buffer::{
+    AutoCommandBufferBuilder, CommandBufferUsage, SecondaryAutoCommandBuffer,
+};
+use vulkano::device::Queue;
+use vulkano::pipeline::viewport::Viewport;
+use vulkano::render_pass::Subpass;
+use vulkano::OomError;
+
+use error::{ExternalPassError, ExternalRecordError};
+
+use crate::graphics::query::FrameQuery;
+use crate::window::Size;
+
+pub mod error;
+mod tests;
+
+/// Point of engine frame where the external pass is executed.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ExternalPassStage {
+    /// Scene subpass, before game objects are drawn.
+    BeforeScene,
+    /// Scene subpass, after game objects are drawn.
+    AfterScene,
+    /// Last subpass of the frame, after UI is drawn.
+    ///
+    /// UI is drawn over the post processed scene, so this is the latest point of the frame.
+    ///
+    AfterPost,
+}
+
+/// Unique identifier of registered external pass.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ExternalPassId(u64);
+
+/// Context which is provided to the callback of external pass.
+pub struct ExternalRecordCtx<'a> {
+    /// Secondary command buffer which commands of the pass are recorded into.
+    pub builder: &'a mut AutoCommandBufferBuilder<SecondaryAutoCommandBuffer>,
+    /// Subpass of the engine render pass which commands are executed in.
+    pub subpass: Subpass,
+    /// Extent of the framebuffer in pixels.
+    pub extent: Size,
+    /// Number of the frame which is recorded.
+    pub frame_index: u64,
+    /// Context of the frame which could be shared with worker threads.
+    frame: FrameContext,
+    /// Secondary command buffers which were recorded in parallel.
+    secondary: &'a mut Vec<SecondaryAutoCommandBuffer>,
+}
+
+impl ExternalRecordCtx<'_> {
+    /// Viewport which covers the whole framebuffer.
+    pub fn viewport(&self) -> Viewport {
+        self.frame.viewport()
+    }
+
+    /// Context of the frame which could be sent to worker threads
+    /// to record secondary command buffers in parallel.
+    pub fn frame_context(&self) -> &FrameContext {
+        &self.frame
+    }
+
+    /// Executes secondary command buffer which was begun with [`FrameContext::begin_secondary`]
+    /// after the commands of the callback.
+    pub fn execute_secondary(&mut self, command_buffer: SecondaryAutoCommandBuffer) {
+        self.secondary.push(command_buffer)
+    }
+}
+
+/// Handles of the frame which are needed to record commands inside of engine render pass.
+///
+/// Context is `Send` and `Sync` and only holds shared handles,
+/// so recording threads never need exclusive access to the renderer.
+///
+#[derive(Clone)]
+pub struct FrameContext {
+    /// Queue which the frame is submitted to.
+    queue: Arc<Queue>,
+    /// Subpass of the engine render pass which commands are executed in.
+    pub subpass: Subpass,
+    /// Extent of the framebuffer in pixels.
+    pub extent: Size,
+    /// Number of the frame which is recorded.
+    pub frame_index: u64,
+    /// Occlusion query of the nominated draw of the frame.
+    occlusion_query: FrameQuery,
+}
+
+impl FrameContext {
+    /// Occlusion query which the nominated draw of the frame could be wrapped into.
+    ///
+    /// Query could be used only once per frame, so only one pass could nominate its draw.
+    ///
+    pub fn occlusion_query(&self) -> &FrameQuery {
+        &self.occlusion_query
+    }
+
+    /// Viewport which covers the whole framebuffer.
+    pub fn viewport(&self) -> Viewport {
+        Viewport {
+            origin: [0.0, 0.0],
+            dimensions: [self.extent.width as f32, self.extent.height as f32],
+            depth_range: 0.0..1.0,
+        }
+    }
+
+    /// Begins secondary command buffer which inherits the subpass of this frame.
+    ///
+    /// Could be called from any thread: each thread allocates from its own command pool.
+    ///
+    pub fn begin_secondary(
+        &self,
+    ) -> Result<AutoCommandBufferBuilder<SecondaryAutoCommandBuffer>, OomError> {
+        AutoCommandBufferBuilder::secondary_graphics(
+            self.queue.device().clone(),
+            self.queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+            self.subpass.clone(),
+        )
+    }
+}
+
+/// Callback which records commands of external pass.
+pub type ExternalRecordFn =
+    Box<dyn FnMut(ExternalRecordCtx<'_>) -> Result<(), ExternalRecordError>>;
+
+/// Description of external pass.
+pub struct ExternalPassDesc {
+    /// Point of engine frame where the pass is executed.
+    pub stage: ExternalPassStage,
+    /// Callback which records commands of the pass.
+    pub record: ExternalRecordFn,
+}
+
+/// Registry which keeps external passes in order of their registration.
+pub(crate) struct ExternalPassRegistry<T> {
+    next_id: u64,
+    passes: Vec<(ExternalPassId, ExternalPassStage, T)>,
+}
+
+impl<T> Default for ExternalPassRegistry<T> {
+    fn default() -> Self {
+        Self {
+            next_id: 0,
+            passes: Vec::new(),
+        }
+    }
+}
+
+impl<T> ExternalPassRegistry<T> {
+    /// Registers new pass which is executed at provided stage.
+    pub fn register(&mut self, stage: ExternalPassStage, pass: T) -> ExternalPassId {
+        let id = ExternalPassId(self.next_id);
+        self.next_id += 1;
+        self.passes.push((id, stage, pass));
+        id
+    }
+
+    /// Unregisters the pass, returning it if it was registered.
+    pub fn unregister(&mut self, id: ExternalPassId) -> Option<T> {
+        let index = self.passes.iter().position(|(other, ..)| *other == id)?;
+        let (_, _, pass) = self.passes.remove(index);
+        Some(pass)
+    }
+
+    /// Checks if there are any passes at provided stage.
+    pub fn has_stage(&self, stage: ExternalPassStage) -> bool {
+        self.passes.iter().any(|(_, other, _)| *other == stage)
+    }
+
+    /// Iterates over passes of provided stage in order of registration.
+    pub fn stage_mut(
+        &mut self,
+        stage: ExternalPassStage,
+    ) -> impl Iterator<Item = (ExternalPassId, &mut T)> + '_ {
+        self.passes
+            .iter_mut()
+            .filter(move |(_, other, _)| *other == stage)
+            .map(|(id, _, pass)| (*id, pass))
+    }
+}
+
+/// System that records external passes into secondary command buffers.
+pub(crate) struct ExternalPassSystem {
+    /// Queue to render.
+    graphics_queue: Arc<Queue>,
+
+    /// All registered passes.
+    passes: ExternalPassRegistry<ExternalRecordFn>,
+}
+
+impl ExternalPassSystem {
+    /// Creates new external pass system.
+    pub fn new(graphics_queue: Arc<Queue>) -> Self {
+        Self {
+            graphics_queue,
+            passes: ExternalPassRegistry::default(),
+        }
+    }
+
+    /// Registers new external pass.
+    ///
+    /// Passes of the same stage are executed in order of registration.
+    ///
+    pub fn register(&mut self, desc: ExternalPassDesc) -> ExternalPassId {
+        self.passes.register(desc.stage, desc.record)
+    }
+
+    /// Unregisters external pass, so it will not be executed anymore.
+    pub fn unregister(&mut self, id: ExternalPassId) -> bool {
+        self.passes.unregister(id).is_some()
+    }
+
+    /// Records all passes of provided stage into secondary command buffers.
+    pub fn record(
+        &mut self,
+        stage: ExternalPassStage,
+        subpass: Subpass,
+        extent: Size,
+        frame_index: u64,
+        occlusion_query: &FrameQuery,
+    ) -> Result<Vec<SecondaryAutoCommandBuffer>, ExternalPassError> {
+        if !self.passes.has_stage(stage) {
+            return Ok(Vec::new());
+        }
+        let frame = FrameContext {
+            queue: self.graphics_queue.clone(),
+            subpass,
+            extent,
+            frame_index,
+            occlusion_query: occlusion_query.clone(),
+        };
+        let mut command_buffers = Vec::new();
+        for (ExternalPassId(id), record) in self.passes.stage_mut(stage) {
+            let mut builder = frame.begin_secondary()?;
+            let mut secondary = Vec::new();
+            let ctx = ExternalRecordCtx {
+                builder: &mut builder,
+                subpass: frame.subpass.clone(),
+                extent,
+                frame_index,
+                frame: frame.clone(),
+                secondary: &mut secondary,
+            };
+            record(ctx).map_err(|source| ExternalPassError::Record { id, source })?;
+            let command_buffer = builder
+                .build()
+                .map_err(|source| ExternalPassError::CommandBufferBuild { id, source })?;
+            command_buffers.push(command_buffer);
+            command_buffers.extend(secondary);
+        }
+        Ok(command_buffers)
+    }
+}
@@ -0,0 +1,46 @@
+#![cfg(test)]
+
+use vulkano::command_buffer::SecondaryAutoCommandBuffer;
+
+use super::{ExternalPassRegistry, ExternalPassStage, FrameContext};
+
+#[test]
+fn passes_follow_registration_order() {
+    let mut registry = ExternalPassRegistry::default();
+    registry.register(ExternalPassStage::AfterScene, "first");
+    registry.register(ExternalPassStage::BeforeScene, "before");
+    registry.register(ExternalPassStage::AfterScene, "second");
+
+    let passes: Vec<_> = registry
+        .stage_mut(ExternalPassStage::AfterScene)
+        .map(|(_, pass)| *pass)
+        .collect();
+    assert_eq!(passes, ["first", "second"]);
+    assert!(!registry.has_stage(ExternalPassStage::AfterPost));
+}
+
+#[test]
+fn unregistered_pass_is_not_executed() {
+    let mut registry = ExternalPassRegistry::default();
+    let first = registry.register(ExternalPassStage::AfterPost, 1);
+    let second = registry.register(ExternalPassStage::AfterPost, 2);
+    assert_ne!(first, second);
+
+    assert_eq!(registry.unregister(first), Some(1));
+    assert_eq!(registry.unregister(first), None);
+    let passes: Vec<_> = registry
+        .stage_mut(ExternalPassStage::AfterPost)
+        .map(|(id, pass)| (id, *pass))
+        .collect();
+    assert_eq!(passes, [(second, 2)]);
+}
+
+#[test]
+fn frame_context_could_be_shared_with_worker_threads() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    fn assert_send<T: Send>() {}
+
+    assert_send_sync::<FrameContext>();
+    // Builder stays on its recording thread, only built command buffer is sent back.
+    assert_send::<SecondaryAutoCommandBuffer>();
+}
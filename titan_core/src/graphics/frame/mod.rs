@@ -1,3 +1,6 @@
+pub mod external;
 pub mod object_draw;
+pub mod picking;
+pub mod post;
 pub mod system;
 pub mod ui_draw;
@@ -1,28 +1,83 @@
 use thiserror::Error;
 use vulkano::command_buffer::{BuildError, DrawIndexedError};
-use vulkano::memory::DeviceMemoryAllocError;
 use vulkano::pipeline::GraphicsPipelineCreationError;
-use vulkano::sync::FlushError;
 use vulkano::OomError;
 
+use crate::graphics::index::IndexRangeError;
+use crate::graphics::pipeline::{PipelineId, Topology};
+use crate::graphics::push::{PushConstantsError, PushConstantsLayoutError};
 use crate::graphics::renderer::error::DescriptorSetCreationError;
+use crate::graphics::spirv::ShaderStage;
+use crate::graphics::texture::TextureError;
+use crate::graphics::upload::UploadError;
 
 #[derive(Debug, Error)]
 pub enum ObjectDrawSystemCreationError {
-    #[error("shader module allocation failure: {0}")]
-    OutOfMemory(#[from] OomError),
-
     #[error("queue family must support graphics operations")]
     QueueFamilyNotSupported,
 
+    #[error("vertex/index buffer upload failure: {0}")]
+    BufferUpload(#[from] UploadError),
+
+    #[error("push constants layout validation failure: {0}")]
+    PushConstantsLayout(#[from] PushConstantsLayoutError),
+
+    #[error("default texture creation failure: {0}")]
+    Texture(#[from] TextureError),
+
+    #[error("pipeline creation failure: {0}")]
+    Shader(#[from] ObjectShaderError),
+}
+
+/// Error that can happen when building pipelines of game objects with their shaders.
+#[derive(Debug, Error)]
+pub enum ObjectShaderError {
+    #[error("{0} shader cannot be used to draw game objects")]
+    NotGraphicsStage(ShaderStage),
+
+    #[error("count of blend modes {actual} does not match count of color attachments {expected}")]
+    BlendModeCount { expected: u32, actual: usize },
+
+    #[error("pipeline requires device features {0:?} which are not enabled")]
+    MissingFeatures(Vec<&'static str>),
+
+    #[error("line width {0} must be positive")]
+    InvalidLineWidth(f32),
+
+    #[error("primitive restart is not supported by topology {0:?}")]
+    PrimitiveRestart(Topology),
+
+    #[error("topology {0:?} is not supported by the device")]
+    UnsupportedTopology(Topology),
+
+    #[error("built-in shader module allocation failure: {0}")]
+    OutOfMemory(#[from] OomError),
+
     #[error("graphics pipeline creation failure: {0}")]
     GraphicsPipelineCreation(#[from] GraphicsPipelineCreationError),
+}
+
+/// Error that can happen when assigning graphics pipeline to game object.
+#[derive(Debug, Error)]
+pub enum ObjectPipelineError {
+    #[error("there is no game object with id {0}")]
+    UnknownObject(u32),
 
-    #[error("vertex/index buffer creation failure: {0}")]
-    BufferCreation(#[from] FlushError),
+    #[error("there is no graphics pipeline with id {0:?}")]
+    UnknownPipeline(PipelineId),
+}
+
+/// Error that can happen when uploading user mesh.
+#[derive(Debug, Error)]
+pub enum MeshError {
+    #[error("mesh must contain at least one vertex and one index")]
+    Empty,
 
-    #[error("vertex/index buffer allocation failure: {0}")]
-    BufferAllocation(#[from] DeviceMemoryAllocError),
+    #[error("index {index} is out of bounds for mesh of {len} vertices")]
+    IndexOutOfBounds { index: u32, len: usize },
+
+    #[error("vertex/index buffer upload failure: {0}")]
+    BufferUpload(#[from] UploadError),
 }
 
 #[derive(Debug, Error)]
@@ -30,6 +85,12 @@ pub enum ObjectDrawError {
     #[error("command buffer allocation failure: {0}")]
     OutOfMemory(#[from] OomError),
 
+    #[error("index range validation failure: {0}")]
+    IndexRange(#[from] IndexRangeError),
+
+    #[error("push constants validation failure: {0}")]
+    PushConstants(#[from] PushConstantsError),
+
     #[error("draw indexed command failure: {0}")]
     DrawIndexed(#[from] DrawIndexedError),
 
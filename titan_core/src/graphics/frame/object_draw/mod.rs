@@ -1,23 +1,49 @@
+use std::collections::HashMap;
+use std::ops::Range;
 use std::sync::Arc;
 
 use palette::Srgba;
-use ultraviolet::Vec3;
-use vulkano::buffer::{BufferUsage, ImmutableBuffer, TypedBufferAccess};
+use ultraviolet::{Vec3, Vec4};
+use vulkano::buffer::{BufferAccess, BufferUsage, DeviceLocalBuffer, TypedBufferAccess};
 use vulkano::command_buffer::{
     AutoCommandBufferBuilder, CommandBufferUsage, SecondaryAutoCommandBuffer,
 };
 use vulkano::descriptor_set::SingleLayoutDescSetPool;
-use vulkano::device::Queue;
-use vulkano::pipeline::viewport::Viewport;
+use vulkano::device::{Device, Queue};
+use vulkano::format::Format;
+use vulkano::image::view::ImageView;
+use vulkano::image::{ImageAccess, ImageViewAbstract, ImmutableImage};
+use vulkano::pipeline::cache::PipelineCache;
+use vulkano::pipeline::shader::{EntryPointAbstract, GraphicsEntryPoint, ShaderStages};
+use vulkano::pipeline::viewport::{Scissor, Viewport};
 use vulkano::pipeline::{GraphicsPipeline, PipelineBindPoint};
 use vulkano::render_pass::Subpass;
-use vulkano::sync::GpuFuture;
+use vulkano::sampler::Sampler;
 
 use crate::{
     graphics::{
+        adapter::DeviceFeatures,
         camera::CameraUBO,
-        frame::object_draw::error::{ObjectDrawError, ObjectDrawSystemCreationError},
+        color::Color,
+        frame::object_draw::error::{
+            MeshError, ObjectDrawError, ObjectDrawSystemCreationError, ObjectPipelineError,
+            ObjectShaderError,
+        },
+        index::{self, IndexBuffer, Indices},
+        memory::{self, MemoryTracker, ResourceCategory},
+        params::{
+            error::ParamBlockError, BlockLayout, ParamBlock, ParamBlockBuilder, ParamType,
+            ParamValue,
+        },
+        pipeline::{
+            CullMode, FrontFace, GraphicsPipelineDescriptor, PipelineAssignments, PipelineId,
+            PolygonMode, PrimitiveState, Topology,
+        },
+        push::{self, PushConstantsLayout},
         renderer::error::DescriptorSetCreationError,
+        spirv::{Shader, ShaderStage},
+        texture::{self, SamplerOptions, TextureError, TextureQuality, TextureStats},
+        upload::{StagingUploader, UploadError},
         vertex::Vertex,
     },
     window::Size,
@@ -25,8 +51,13 @@
 
 pub mod error;
 
-const fn indices() -> [u32; 12] {
-    [0, 1, 2, 2, 3, 0, 4, 5, 6, 6, 7, 4]
+fn indices() -> Indices {
+    Indices::U16(vec![0, 1, 2, 2, 3, 0, 4, 5, 6, 6, 7, 4])
+}
+
+/// Ranges of indices of each game object. Id of the object is its position in this array.
+const fn objects() -> [Range<u32>; 2] {
+    [0..6, 6..12]
 }
 
 fn vertices() -> [Vertex; 8] {
@@ -42,22 +73,163 @@
     ]
 }
 
+/// Viewport and scissor which cover the whole framebuffer of provided size.
+///
+/// Both of them are dynamic state of pipelines, so pipelines are not rebuilt on resize.
+///
+fn viewport_scissor(viewport_size: Size) -> (Viewport, Scissor) {
+    let dimensions = [viewport_size.width, viewport_size.height];
+    let viewport = Viewport {
+        origin: [0.0, 0.0],
+        dimensions: dimensions.map(|dimension| dimension as f32),
+        depth_range: 0.0..1.0,
+    };
+    let scissor = Scissor {
+        origin: [0, 0],
+        dimensions,
+    };
+    (viewport, scissor)
+}
+
+/// Entry point of provided shader with the interface and layout of built-in entry point,
+/// or built-in entry point if there is no shader.
+pub(crate) fn entry_point<'a>(
+    builtin: GraphicsEntryPoint<'a>,
+    shader: Option<&'a Shader>,
+) -> GraphicsEntryPoint<'a> {
+    let shader = match shader {
+        Some(shader) => shader,
+        None => return builtin,
+    };
+    // SAFETY: entry point of the shader was checked when the shader was loaded,
+    // and shader must have the same interface and layout as the built-in one.
+    unsafe {
+        shader.module().graphics_entry_point(
+            shader.entry_point(),
+            builtin.descriptor_set_layout_descs().iter().cloned(),
+            *builtin.push_constant_range(),
+            &[],
+            builtin.input().clone(),
+            builtin.output().clone(),
+            builtin.ty(),
+        )
+    }
+}
+
+/// Parameters of the material of game objects, as declared by the built-in fragment shader.
+fn material_params() -> ParamBlock {
+    let mut params = ParamBlockBuilder::new(BlockLayout::Std140)
+        .param("color", ParamType::Vec4)
+        .build()
+        .expect("parameters of the built-in material are valid");
+    params
+        .set("color", Vec4::one())
+        .expect("built-in material has color parameter");
+    params
+}
+
+/// Stages which push constants of game objects are accessible by.
+fn fragment() -> ShaderStages {
+    ShaderStages {
+        fragment: true,
+        ..ShaderStages::none()
+    }
+}
+
 /// System that contains the necessary facilities for rendering game objects.
 pub struct ObjectDrawSystem {
     /// Queue to render.
     graphics_queue: Arc<Queue>,
 
     /// Buffer for all vertices of game objects.
-    vertex_buffer: Arc<ImmutableBuffer<[Vertex]>>,
+    vertex_buffer: Arc<DeviceLocalBuffer<[Vertex]>>,
 
     /// Buffer for all indices of vertices in game object.
-    index_buffer: Arc<ImmutableBuffer<[u32]>>,
+    index_buffer: IndexBuffer,
+
+    /// Graphics pipelines used for rendering of game objects with their descriptors,
+    /// the first one is the default pipeline.
+    pipelines: Vec<(GraphicsPipelineDescriptor, Arc<GraphicsPipeline>)>,
 
-    /// Graphics pipeline used for rendering of game objects.
-    pipeline: Arc<GraphicsPipeline>,
+    /// Pipelines which are assigned to game objects.
+    assignments: PipelineAssignments,
 
     /// Pool of descriptor sets of uniform buffers with data for vertex shader.
     descriptor_set_pool: SingleLayoutDescSetPool,
+
+    /// Expected layout of material push constants of the pipeline.
+    material_layout: PushConstantsLayout,
+
+    /// Parameters of the material which are uploaded into the uniform ring each frame.
+    material_params: ParamBlock,
+
+    /// Pool of descriptor sets of material parameters (set `1` of the pipeline).
+    material_descriptor_set_pool: SingleLayoutDescSetPool,
+
+    /// Vertex shader which replaces the built-in one, if any.
+    vertex_shader: Option<Shader>,
+
+    /// Fragment shader which replaces the built-in one, if any.
+    fragment_shader: Option<Shader>,
+
+    /// Pipelines of the error material which are used while the pipeline of game objects
+    /// has failed, one for each primitive state of pipelines of game objects.
+    error_pipelines: HashMap<PrimitiveState, Arc<GraphicsPipeline>>,
+
+    /// Pool of descriptor sets of uniform buffers for the error pipeline.
+    error_descriptor_set_pool: SingleLayoutDescSetPool,
+
+    /// If the last attempt to build pipeline of game objects has failed.
+    failed: bool,
+
+    /// Graphics pipelines used for rendering of game object ids,
+    /// one for each primitive state of pipelines of game objects.
+    picking_pipelines: HashMap<PrimitiveState, Arc<GraphicsPipeline>>,
+
+    /// Subpass which game object ids are rendered on.
+    picking_subpass: Subpass,
+
+    /// Pool of descriptor sets of uniform buffers for picking pipeline.
+    picking_descriptor_set_pool: SingleLayoutDescSetPool,
+
+    /// Expected layout of object id push constants of picking pipeline.
+    picking_layout: PushConstantsLayout,
+
+    /// Ranges of indices of each game object.
+    objects: Vec<Range<u32>>,
+
+    /// If the mesh contains restart indices, so it cannot be drawn without primitive restart.
+    restart_indices: bool,
+
+    /// If skipped game objects were reported since the mesh or pipelines were changed.
+    skipped_reported: bool,
+
+    /// Texture which is sampled by game objects with all its mip levels.
+    texture_image: Arc<ImmutableImage>,
+
+    /// View of the texture which starts from the base mip level of texture quality.
+    texture: Arc<dyn ImageViewAbstract + Send + Sync>,
+
+    /// Options of the sampler of the texture.
+    sampler_options: SamplerOptions,
+
+    /// Sampler of the texture of game objects.
+    sampler: Arc<Sampler>,
+
+    /// Global quality tier of textures.
+    quality: TextureQuality,
+
+    /// Global bias of the level of detail of samplers.
+    lod_bias: f32,
+
+    /// Uploader of vertex and index data into device local buffers.
+    uploader: StagingUploader,
+
+    /// Tracker of allocations of vertex and index buffers.
+    memory: Arc<MemoryTracker>,
+
+    /// Cache which pipelines are built with.
+    pipeline_cache: Arc<PipelineCache>,
 }
 
 impl ObjectDrawSystem {
@@ -65,74 +237,630 @@ impl ObjectDrawSystem {
     pub fn new(
         graphics_queue: Arc<Queue>,
         subpass: Subpass,
+        picking_subpass: Subpass,
+        uploader: StagingUploader,
+        memory: Arc<MemoryTracker>,
+        pipeline_cache: Arc<PipelineCache>,
     ) -> Result<Self, ObjectDrawSystemCreationError> {
         // Check queue for graphics support.
         if !graphics_queue.family().supports_graphics() {
             return Err(ObjectDrawSystemCreationError::QueueFamilyNotSupported);
         }
 
-        let pipeline = {
-            use crate::graphics::shader::default::{fragment, vertex};
-
-            let device = graphics_queue.device().clone();
-
-            let vert_shader_module = vertex::Shader::load(device.clone())?;
-            let frag_shader_module = fragment::Shader::load(device.clone())?;
+        let device = graphics_queue.device().clone();
+        let cache = &pipeline_cache;
+        let descriptor = GraphicsPipelineDescriptor::new();
+        let state = descriptor.primitive_state();
+        let pipeline = Self::object_pipeline(
+            device.clone(),
+            cache,
+            subpass.clone(),
+            &descriptor,
+            None,
+            None,
+        )?;
+        let error_pipeline = Self::error_pipeline(device.clone(), cache, subpass, state)?;
+        let picking_pipeline =
+            Self::picking_pipeline(device, cache, picking_subpass.clone(), state, None)?;
 
-            Arc::new(
-                GraphicsPipeline::start()
-                    .vertex_input_single_buffer::<Vertex>()
-                    .vertex_shader(vert_shader_module.main_entry_point(), ())
-                    .fragment_shader(frag_shader_module.main_entry_point(), ())
-                    .triangle_list()
-                    .primitive_restart(false)
-                    .viewports_dynamic_scissors_irrelevant(1)
-                    .depth_stencil_simple_depth()
-                    .cull_mode_back()
-                    .render_pass(subpass)
-                    .build(device)?,
-            )
-        };
+        let material_layout = PushConstantsLayout::of::<[f32; 4]>("default.frag", self::fragment());
+        material_layout.validate(pipeline.layout().push_constant_ranges())?;
+        let picking_layout = PushConstantsLayout::of::<u32>("picking.frag", self::fragment());
+        picking_layout.validate(picking_pipeline.layout().push_constant_ranges())?;
 
         let vertex_buffer = {
-            let (vertex_buffer, future) = ImmutableBuffer::from_iter(
-                self::vertices(),
-                BufferUsage::vertex_buffer(),
-                graphics_queue.clone(),
-            )?;
-            future.flush()?;
+            let vertex_buffer = uploader.upload(&self::vertices(), BufferUsage::vertex_buffer())?;
+            let size = vertex_buffer.size();
+            memory.track(
+                &vertex_buffer,
+                "object vertices",
+                ResourceCategory::VertexBuffer,
+                size,
+            );
             vertex_buffer
         };
 
         let index_buffer = {
-            let (index_buffer, future) = ImmutableBuffer::from_iter(
-                self::indices(),
-                BufferUsage::index_buffer(),
-                graphics_queue.clone(),
-            )?;
-            future.flush()?;
+            let index_buffer = IndexBuffer::from_indices(&self::indices(), &uploader)?;
+            index_buffer.track(&memory, "object indices");
             index_buffer
         };
 
+        // White texture does not change colors of game objects until user texture is set.
+        let texture_image = Self::upload_texture(&uploader, &memory, [1, 1], &[u8::MAX; 4], false)?;
+        let quality = TextureQuality::default();
+        let texture = Self::texture_view(texture_image.clone(), quality)?;
+        let sampler_options = SamplerOptions::new();
+        let sampler = sampler_options
+            .create_sampler(graphics_queue.device().clone(), 0.0)
+            .map_err(TextureError::from)?;
+
         let descriptor_set_pool = {
             let layout = &pipeline.layout().descriptor_set_layouts()[0];
             SingleLayoutDescSetPool::new(layout.clone())
         };
 
+        let material_descriptor_set_pool = {
+            let layout = &pipeline.layout().descriptor_set_layouts()[1];
+            SingleLayoutDescSetPool::new(layout.clone())
+        };
+
+        let error_descriptor_set_pool = {
+            let layout = &error_pipeline.layout().descriptor_set_layouts()[0];
+            SingleLayoutDescSetPool::new(layout.clone())
+        };
+
+        let picking_descriptor_set_pool = {
+            let layout = &picking_pipeline.layout().descriptor_set_layouts()[0];
+            SingleLayoutDescSetPool::new(layout.clone())
+        };
+
         Ok(Self {
             graphics_queue,
             vertex_buffer,
             index_buffer,
-            pipeline,
+            pipelines: vec![(descriptor, pipeline)],
+            assignments: PipelineAssignments::default(),
             descriptor_set_pool,
+            material_layout,
+            material_params: self::material_params(),
+            material_descriptor_set_pool,
+            vertex_shader: None,
+            fragment_shader: None,
+            error_pipelines: HashMap::from([(state, error_pipeline)]),
+            error_descriptor_set_pool,
+            failed: false,
+            picking_pipelines: HashMap::from([(state, picking_pipeline)]),
+            picking_subpass,
+            picking_descriptor_set_pool,
+            picking_layout,
+            objects: self::objects().to_vec(),
+            restart_indices: false,
+            skipped_reported: false,
+            texture_image,
+            texture,
+            sampler_options,
+            sampler,
+            quality,
+            lod_bias: 0.0,
+            uploader,
+            memory,
+            pipeline_cache,
         })
     }
 
+    /// Builds pipeline of game objects with provided state,
+    /// replacing built-in shaders by provided ones.
+    fn object_pipeline(
+        device: Arc<Device>,
+        cache: &Arc<PipelineCache>,
+        subpass: Subpass,
+        descriptor: &GraphicsPipelineDescriptor,
+        vertex: Option<&Shader>,
+        fragment: Option<&Shader>,
+    ) -> Result<Arc<GraphicsPipeline>, ObjectShaderError> {
+        use crate::graphics::shader::default;
+
+        let line_width = descriptor.line_width();
+        if !(line_width.is_finite() && line_width > 0.0) {
+            return Err(ObjectShaderError::InvalidLineWidth(line_width));
+        }
+        let topology = descriptor.topology();
+        if descriptor.primitive_restart() && !topology.supports_primitive_restart() {
+            return Err(ObjectShaderError::PrimitiveRestart(topology));
+        }
+        // Portability subset may lack triangle fans, and its features are not exposed yet.
+        if topology == Topology::TriangleFan && device.enabled_extensions().khr_portability_subset {
+            return Err(ObjectShaderError::UnsupportedTopology(topology));
+        }
+        let enabled_features = DeviceFeatures::from(device.enabled_features());
+        let missing_features = descriptor.required_features().difference(enabled_features);
+        if missing_features != DeviceFeatures::NONE {
+            return Err(ObjectShaderError::MissingFeatures(missing_features.names()));
+        }
+
+        let vert_shader_module = default::vertex::Shader::load(device.clone())?;
+        let frag_shader_module = default::fragment::Shader::load(device.clone())?;
+        let vertex = self::entry_point(vert_shader_module.main_entry_point(), vertex);
+        let fragment = self::entry_point(frag_shader_module.main_entry_point(), fragment);
+
+        let builder = GraphicsPipeline::start()
+            .vertex_input_single_buffer::<Vertex>()
+            .vertex_shader(vertex, ())
+            .fragment_shader(fragment, ())
+            .primitive_restart(descriptor.primitive_restart())
+            .viewports_scissors_dynamic(1);
+        let builder = match topology {
+            Topology::PointList => builder.point_list(),
+            Topology::LineList => builder.line_list(),
+            Topology::LineStrip => builder.line_strip(),
+            Topology::TriangleList => builder.triangle_list(),
+            Topology::TriangleStrip => builder.triangle_strip(),
+            Topology::TriangleFan => builder.triangle_fan(),
+        };
+        let builder = match descriptor.cull_mode() {
+            CullMode::None => builder.cull_mode_disabled(),
+            CullMode::Front => builder.cull_mode_front(),
+            CullMode::Back => builder.cull_mode_back(),
+        };
+        let builder = match descriptor.polygon_mode() {
+            PolygonMode::Fill => builder.polygon_mode_fill(),
+            PolygonMode::Line => builder.polygon_mode_line(),
+            PolygonMode::Point => builder.polygon_mode_point(),
+        };
+        let builder = match descriptor.front_face() {
+            FrontFace::CounterClockwise => builder.front_face_counter_clockwise(),
+            FrontFace::Clockwise => builder.front_face_clockwise(),
+        };
+        let builder = builder.line_width(line_width);
+        let color_attachments = subpass.num_color_attachments();
+        let blends = descriptor.attachment_blends(color_attachments).ok_or(
+            ObjectShaderError::BlendModeCount {
+                expected: color_attachments,
+                actual: descriptor.attachment_blend_modes().len(),
+            },
+        )?;
+        let builder = builder.blend_individual(blends);
+        // Depth test is used only if the subpass has depth attachment.
+        let builder = if subpass.has_depth() {
+            builder
+                .depth_stencil_simple_depth()
+                .depth_write(descriptor.depth_write())
+        } else {
+            builder
+        };
+        let builder = builder.render_pass(subpass).build_with_cache(cache.clone());
+        Ok(Arc::new(builder.build(device)?))
+    }
+
+    /// Builds pipeline of the error material with provided primitive state,
+    /// which draws game objects in solid magenta.
+    ///
+    /// Its vertex shader consumes only position of vertices,
+    /// so it is compatible with any vertex layout which has position.
+    ///
+    fn error_pipeline(
+        device: Arc<Device>,
+        cache: &Arc<PipelineCache>,
+        subpass: Subpass,
+        state: PrimitiveState,
+    ) -> Result<Arc<GraphicsPipeline>, ObjectShaderError> {
+        use crate::graphics::shader::error;
+
+        let vert_shader_module = error::vertex::Shader::load(device.clone())?;
+        let frag_shader_module = error::fragment::Shader::load(device.clone())?;
+
+        let builder = GraphicsPipeline::start()
+            .vertex_input_single_buffer::<Vertex>()
+            .vertex_shader(vert_shader_module.main_entry_point(), ())
+            .fragment_shader(frag_shader_module.main_entry_point(), ())
+            .primitive_restart(state.primitive_restart)
+            .viewports_scissors_dynamic(1)
+            .cull_mode_back();
+        let builder = match state.topology {
+            Topology::PointList => builder.point_list(),
+            Topology::LineList => builder.line_list(),
+            Topology::LineStrip => builder.line_strip(),
+            Topology::TriangleList => builder.triangle_list(),
+            Topology::TriangleStrip => builder.triangle_strip(),
+            Topology::TriangleFan => builder.triangle_fan(),
+        };
+        let builder = if subpass.has_depth() {
+            builder.depth_stencil_simple_depth()
+        } else {
+            builder
+        };
+        let builder = builder.render_pass(subpass).build_with_cache(cache.clone());
+        Ok(Arc::new(builder.build(device)?))
+    }
+
+    /// Builds pipeline of game object ids with provided primitive state,
+    /// replacing built-in vertex shader by provided one.
+    ///
+    /// Picking pipeline has the same vertex layout, but writes object ids instead of colors.
+    ///
+    fn picking_pipeline(
+        device: Arc<Device>,
+        cache: &Arc<PipelineCache>,
+        subpass: Subpass,
+        state: PrimitiveState,
+        vertex: Option<&Shader>,
+    ) -> Result<Arc<GraphicsPipeline>, ObjectShaderError> {
+        use crate::graphics::shader::{default, picking};
+
+        let vert_shader_module = default::vertex::Shader::load(device.clone())?;
+        let frag_shader_module = picking::fragment::Shader::load(device.clone())?;
+        let vertex = self::entry_point(vert_shader_module.main_entry_point(), vertex);
+
+        let builder = GraphicsPipeline::start()
+            .vertex_input_single_buffer::<Vertex>()
+            .vertex_shader(vertex, ())
+            .fragment_shader(frag_shader_module.main_entry_point(), ())
+            .primitive_restart(state.primitive_restart)
+            .viewports_scissors_dynamic(1)
+            .depth_stencil_simple_depth()
+            .cull_mode_back();
+        let builder = match state.topology {
+            Topology::PointList => builder.point_list(),
+            Topology::LineList => builder.line_list(),
+            Topology::LineStrip => builder.line_strip(),
+            Topology::TriangleList => builder.triangle_list(),
+            Topology::TriangleStrip => builder.triangle_strip(),
+            Topology::TriangleFan => builder.triangle_fan(),
+        };
+        let pipeline = builder
+            .render_pass(subpass)
+            .build_with_cache(cache.clone())
+            .build(device)?;
+        Ok(Arc::new(pipeline))
+    }
+
+    /// Replaces built-in shader of game objects by provided shader of the same stage.
+    ///
+    /// Shader must declare the same inputs, outputs, descriptors and push constants
+    /// as the built-in shader of its stage, because the layout of the pipeline is not changed.
+    /// Vertex shader is used for picking of game objects too.
+    ///
+    /// If pipeline fails to build, game objects are drawn with the error material
+    /// until shaders are replaced successfully.
+    ///
+    pub fn set_shader(&mut self, shader: Shader) -> Result<(), ObjectShaderError> {
+        self.replace_shader(shader.stage(), Some(shader))
+    }
+
+    /// Restores built-in shader of game objects of provided stage.
+    pub fn reset_shader(&mut self, stage: ShaderStage) -> Result<(), ObjectShaderError> {
+        self.replace_shader(stage, None)
+    }
+
+    /// If the last attempt to build pipeline of game objects has failed,
+    /// so they are drawn with the error material.
+    pub fn is_failed(&self) -> bool {
+        self.failed
+    }
+
+    fn replace_shader(
+        &mut self,
+        stage: ShaderStage,
+        shader: Option<Shader>,
+    ) -> Result<(), ObjectShaderError> {
+        if stage == ShaderStage::Compute {
+            return Err(ObjectShaderError::NotGraphicsStage(stage));
+        }
+        let result = self.try_replace_shader(stage, shader);
+        self.failed = result.is_err();
+        result
+    }
+
+    fn try_replace_shader(
+        &mut self,
+        stage: ShaderStage,
+        shader: Option<Shader>,
+    ) -> Result<(), ObjectShaderError> {
+        let (mut vertex, mut fragment) = (self.vertex_shader.clone(), self.fragment_shader.clone());
+        match stage {
+            ShaderStage::Vertex => vertex = shader,
+            ShaderStage::Fragment => fragment = shader,
+            ShaderStage::Compute => unreachable!("compute shaders are rejected before"),
+        }
+
+        let device = self.graphics_queue.device().clone();
+        let subpass = self.default_pipeline().subpass().clone();
+        let cache = &self.pipeline_cache;
+        let pipelines = self
+            .pipelines
+            .iter()
+            .map(|(descriptor, _)| {
+                let pipeline = Self::object_pipeline(
+                    device.clone(),
+                    cache,
+                    subpass.clone(),
+                    descriptor,
+                    vertex.as_ref(),
+                    fragment.as_ref(),
+                )?;
+                Ok((descriptor.clone(), pipeline))
+            })
+            .collect::<Result<Vec<_>, ObjectShaderError>>()?;
+        let picking_pipelines = match stage {
+            ShaderStage::Vertex => {
+                let pipelines = self
+                    .picking_pipelines
+                    .keys()
+                    .map(|&state| {
+                        let pipeline = Self::picking_pipeline(
+                            device.clone(),
+                            cache,
+                            self.picking_subpass.clone(),
+                            state,
+                            vertex.as_ref(),
+                        )?;
+                        Ok((state, pipeline))
+                    })
+                    .collect::<Result<HashMap<_, _>, ObjectShaderError>>()?;
+                Some(pipelines)
+            }
+            ShaderStage::Fragment | ShaderStage::Compute => None,
+        };
+
+        self.pipelines = pipelines;
+        if let Some(picking_pipelines) = picking_pipelines {
+            self.picking_pipelines = picking_pipelines;
+        }
+        self.vertex_shader = vertex;
+        self.fragment_shader = fragment;
+        Ok(())
+    }
+
+    /// Creates graphics pipeline of game objects with provided state,
+    /// or returns the existing one with the same state.
+    ///
+    /// Pipeline is built with current shaders of game objects.
+    /// Pipelines of the error material and of picking are built
+    /// for its primitive state too, if there are no such pipelines yet.
+    ///
+    pub fn create_pipeline(
+        &mut self,
+        descriptor: GraphicsPipelineDescriptor,
+    ) -> Result<PipelineId, ObjectShaderError> {
+        let existing = self.pipelines.iter().position(|(it, _)| it == &descriptor);
+        if let Some(index) = existing {
+            return Ok(PipelineId(index));
+        }
+        let device = self.graphics_queue.device().clone();
+        let cache = &self.pipeline_cache;
+        let subpass = self.default_pipeline().subpass().clone();
+        let pipeline = Self::object_pipeline(
+            device.clone(),
+            cache,
+            subpass.clone(),
+            &descriptor,
+            self.vertex_shader.as_ref(),
+            self.fragment_shader.as_ref(),
+        )?;
+        let state = descriptor.primitive_state();
+        if !self.error_pipelines.contains_key(&state) {
+            let error_pipeline = Self::error_pipeline(device.clone(), cache, subpass, state)?;
+            self.error_pipelines.insert(state, error_pipeline);
+        }
+        if !self.picking_pipelines.contains_key(&state) {
+            let picking_pipeline = Self::picking_pipeline(
+                device,
+                cache,
+                self.picking_subpass.clone(),
+                state,
+                self.vertex_shader.as_ref(),
+            )?;
+            self.picking_pipelines.insert(state, picking_pipeline);
+        }
+        self.pipelines.push((descriptor, pipeline));
+        Ok(PipelineId(self.pipelines.len() - 1))
+    }
+
+    /// Assigns pipeline to the game object with provided id,
+    /// so it is drawn with this pipeline until the mesh is replaced.
+    pub fn set_object_pipeline(
+        &mut self,
+        object: u32,
+        pipeline: PipelineId,
+    ) -> Result<(), ObjectPipelineError> {
+        if object >= self.object_count() {
+            return Err(ObjectPipelineError::UnknownObject(object));
+        }
+        if pipeline.0 >= self.pipelines.len() {
+            return Err(ObjectPipelineError::UnknownPipeline(pipeline));
+        }
+        self.assignments.set(object as usize, pipeline);
+        self.skipped_reported = false;
+        Ok(())
+    }
+
+    /// Pipeline which game objects are drawn with by default.
+    fn default_pipeline(&self) -> &Arc<GraphicsPipeline> {
+        &self.pipelines[PipelineId::DEFAULT.0].1
+    }
+
+    /// Replaces mesh of game objects by provided vertices and indices.
+    ///
+    /// Whole mesh is treated as a single game object with id `0`,
+    /// which is drawn with the default pipeline.
+    /// If the mesh contains restart indices, it is not drawn
+    /// until pipeline which enables primitive restart is assigned to it.
+    ///
+    pub fn set_mesh(&mut self, vertices: Vec<Vertex>, indices: Indices) -> Result<(), MeshError> {
+        if vertices.is_empty() || indices.is_empty() {
+            return Err(MeshError::Empty);
+        }
+        let len = vertices.len();
+        if let Some(index) = indices.find_out_of_bounds(len) {
+            return Err(MeshError::IndexOutOfBounds { index, len });
+        }
+        let index_count = indices.len() as u32;
+        let restart_indices = indices.contains_restart();
+
+        let vertex_buffer = self
+            .uploader
+            .upload(&vertices, BufferUsage::vertex_buffer())?;
+        let index_buffer = IndexBuffer::from_indices(&indices, &self.uploader)?;
+
+        let size = vertex_buffer.size();
+        self.memory.track(
+            &vertex_buffer,
+            "mesh vertices",
+            ResourceCategory::VertexBuffer,
+            size,
+        );
+        index_buffer.track(&self.memory, "mesh indices");
+
+        self.vertex_buffer = vertex_buffer;
+        self.index_buffer = index_buffer;
+        self.objects.clear();
+        self.objects.push(0..index_count);
+        self.restart_indices = restart_indices;
+        self.skipped_reported = false;
+        self.assignments.clear();
+        Ok(())
+    }
+
+    /// Replaces texture of game objects by provided RGBA8 pixels in sRGB encoding.
+    ///
+    /// Texture is sampled by UV positions of vertices with sampler of provided options.
+    ///
+    pub fn set_texture(
+        &mut self,
+        dimensions: [u32; 2],
+        pixels: &[u8],
+        options: SamplerOptions,
+    ) -> Result<(), TextureError> {
+        let texture_image = Self::upload_texture(
+            &self.uploader,
+            &self.memory,
+            dimensions,
+            pixels,
+            options.mipmaps(),
+        )?;
+        let texture = Self::texture_view(texture_image.clone(), self.quality)?;
+        let sampler =
+            options.create_sampler(self.graphics_queue.device().clone(), self.lod_bias)?;
+        self.texture_image = texture_image;
+        self.texture = texture;
+        self.sampler_options = options;
+        self.sampler = sampler;
+        Ok(())
+    }
+
+    /// Sets global quality tier of textures.
+    ///
+    /// View of the texture is recreated to start from the base mip level of the tier,
+    /// all mip levels are kept in memory. Descriptor sets are created for each draw,
+    /// and previous view is kept alive by command buffers of frames in flight,
+    /// so it is released only after these frames are retired.
+    ///
+    pub fn set_texture_quality(&mut self, quality: TextureQuality) -> Result<(), TextureError> {
+        if quality == self.quality {
+            return Ok(());
+        }
+        self.texture = Self::texture_view(self.texture_image.clone(), quality)?;
+        self.quality = quality;
+        Ok(())
+    }
+
+    /// Sets global bias of the level of detail which is added to samplers of textures.
+    pub fn set_texture_lod_bias(&mut self, lod_bias: f32) -> Result<(), TextureError> {
+        let device = self.graphics_queue.device().clone();
+        self.sampler = self.sampler_options.create_sampler(device, lod_bias)?;
+        self.lod_bias = lod_bias;
+        Ok(())
+    }
+
+    /// Parameters of the material of game objects.
+    pub fn material_params(&self) -> &ParamBlock {
+        &self.material_params
+    }
+
+    /// Sets the value of material parameter with provided name.
+    ///
+    /// Parameters are uploaded into the uniform ring each frame,
+    /// so the new value is used starting from the next frame.
+    ///
+    pub fn set_material_param<V>(&mut self, name: &str, value: V) -> Result<(), ParamBlockError>
+    where
+        V: ParamValue,
+    {
+        self.material_params.set(name, value)
+    }
+
+    /// Statistics of the texture of game objects.
+    pub fn texture_stats(&self) -> TextureStats {
+        let mip_levels = self.texture_image.mipmap_levels();
+        TextureStats {
+            dimensions: self.texture_image.dimensions().width_height(),
+            mip_levels,
+            base_mip_level: self.quality.base_mip_level(mip_levels),
+            quality: self.quality,
+        }
+    }
+
+    fn texture_view(
+        image: Arc<ImmutableImage>,
+        quality: TextureQuality,
+    ) -> Result<Arc<dyn ImageViewAbstract + Send + Sync>, TextureError> {
+        let mip_levels = image.mipmap_levels();
+        let base_mip_level = quality.base_mip_level(mip_levels);
+        let view = ImageView::start(image)
+            .with_mipmap_levels(base_mip_level..mip_levels)
+            .build()?;
+        Ok(view)
+    }
+
+    fn upload_texture(
+        uploader: &StagingUploader,
+        memory: &MemoryTracker,
+        dimensions: [u32; 2],
+        pixels: &[u8],
+        mipmaps: bool,
+    ) -> Result<Arc<ImmutableImage>, TextureError> {
+        texture::check_rgba8(dimensions, pixels.len())?;
+        let format = Format::R8G8B8A8_SRGB;
+        let image = if mipmaps {
+            match uploader.upload_image_with_mipmaps(dimensions, format, pixels) {
+                Err(UploadError::MipmapsNotSupported(format)) => {
+                    log::warn!(
+                        "format {:?} cannot be blitted, mipmaps are disabled",
+                        format
+                    );
+                    uploader.upload_image(dimensions, format, pixels)?
+                }
+                result => result?,
+            }
+        } else {
+            uploader.upload_image(dimensions, format, pixels)?
+        };
+        // Full mip chain takes about one third of the base level.
+        let base_size = memory::image_size(dimensions, format);
+        let size = match image.mipmap_levels() {
+            1 => base_size,
+            _ => base_size + base_size / 3,
+        };
+        memory.track(&image, "object texture", ResourceCategory::Texture, size);
+        Ok(image)
+    }
+
     /// Builds a secondary command buffer that draws game objects on the current subpass.
+    ///
+    /// Colors of game objects are multiplied by provided tint
+    /// and by material parameters which were uploaded into provided uniform buffer.
+    /// Game objects are drawn in order of their ids, switching pipelines between them if needed.
+    /// If pipeline of game objects [has failed](Self::is_failed),
+    /// they are drawn with the error material instead.
+    /// Objects whose pipelines cannot draw restart indices of the mesh are skipped.
+    ///
     pub fn draw<B>(
         &mut self,
         viewport_size: Size,
         uniform_buffer: Arc<B>,
+        material: Arc<dyn BufferAccess>,
+        tint: Color,
     ) -> Result<SecondaryAutoCommandBuffer, ObjectDrawError>
     where
         B: TypedBufferAccess<Content = CameraUBO> + Send + Sync + 'static,
@@ -141,11 +869,79 @@ pub fn draw<B>(
             self.graphics_queue.device().clone(),
             self.graphics_queue.family(),
             CommandBufferUsage::OneTimeSubmit,
-            self.pipeline.subpass().clone(),
+            self.default_pipeline().subpass().clone(),
         )?;
+        if self.failed {
+            self.draw_error(&mut builder, viewport_size, uniform_buffer)?;
+            return Ok(builder.build()?);
+        }
 
         let descriptor_sets = {
             let mut builder = self.descriptor_set_pool.next();
+            builder
+                .add_buffer(uniform_buffer)
+                .map_err(DescriptorSetCreationError::from)?
+                .add_sampled_image(self.texture.clone(), self.sampler.clone())
+                .map_err(DescriptorSetCreationError::from)?;
+            let descriptor_set = builder.build().map_err(DescriptorSetCreationError::from)?;
+            let camera_set = Arc::new(descriptor_set);
+
+            let mut builder = self.material_descriptor_set_pool.next();
+            builder
+                .add_buffer(material)
+                .map_err(DescriptorSetCreationError::from)?;
+            let descriptor_set = builder.build().map_err(DescriptorSetCreationError::from)?;
+            (camera_set, Arc::new(descriptor_set))
+        };
+
+        let (viewport, scissor) = self::viewport_scissor(viewport_size);
+        let index_count = self.index_buffer.len();
+        for indices in &self.objects {
+            index::check_index_range(indices, index_count)?;
+        }
+        builder
+            .set_viewport(0, std::iter::once(viewport))
+            .set_scissor(0, std::iter::once(scissor))
+            .bind_vertex_buffers(0, self.vertex_buffer.clone());
+        self.index_buffer.bind(&mut builder);
+        let mut bound = None;
+        for (_, indices, pipeline_id, _) in self.drawn_objects() {
+            if bound != Some(pipeline_id) {
+                let (_, pipeline) = &self.pipelines[pipeline_id.0];
+                builder.bind_pipeline_graphics(pipeline.clone());
+                builder.bind_descriptor_sets(
+                    PipelineBindPoint::Graphics,
+                    pipeline.layout().clone(),
+                    0,
+                    descriptor_sets.clone(),
+                );
+                push::push_constants(
+                    &mut builder,
+                    pipeline.layout().clone(),
+                    &self.material_layout,
+                    self::fragment(),
+                    tint.to_linear(),
+                )?;
+                bound = Some(pipeline_id);
+            }
+            let index_count = indices.end - indices.start;
+            builder.draw_indexed(index_count, 1, indices.start, 0, 0)?;
+        }
+        Ok(builder.build()?)
+    }
+
+    /// Records draw commands of game objects with the error material.
+    fn draw_error<B>(
+        &mut self,
+        builder: &mut AutoCommandBufferBuilder<SecondaryAutoCommandBuffer>,
+        viewport_size: Size,
+        uniform_buffer: Arc<B>,
+    ) -> Result<(), ObjectDrawError>
+    where
+        B: TypedBufferAccess<Content = CameraUBO> + Send + Sync + 'static,
+    {
+        let descriptor_sets = {
+            let mut builder = self.error_descriptor_set_pool.next();
             builder
                 .add_buffer(uniform_buffer)
                 .map_err(DescriptorSetCreationError::from)?;
@@ -153,23 +949,150 @@ pub fn draw<B>(
             Arc::new(descriptor_set)
         };
 
-        let viewport = Viewport {
-            origin: [0.0, 0.0],
-            dimensions: [viewport_size.width as f32, viewport_size.height as f32],
-            depth_range: 0.0..1.0,
+        let (viewport, scissor) = self::viewport_scissor(viewport_size);
+        let index_count = self.index_buffer.len();
+        for indices in &self.objects {
+            index::check_index_range(indices, index_count)?;
+        }
+        builder
+            .set_viewport(0, std::iter::once(viewport))
+            .set_scissor(0, std::iter::once(scissor))
+            .bind_vertex_buffers(0, self.vertex_buffer.clone());
+        self.index_buffer.bind(builder);
+        let mut bound = None;
+        for (_, indices, _, state) in self.drawn_objects() {
+            if bound != Some(state) {
+                // Pipelines of the error material are built with each pipeline of game objects.
+                let pipeline = &self.error_pipelines[&state];
+                builder.bind_pipeline_graphics(pipeline.clone());
+                builder.bind_descriptor_sets(
+                    PipelineBindPoint::Graphics,
+                    pipeline.layout().clone(),
+                    0,
+                    descriptor_sets.clone(),
+                );
+                bound = Some(state);
+            }
+            let index_count = indices.end - indices.start;
+            builder.draw_indexed(index_count, 1, indices.start, 0, 0)?;
+        }
+        Ok(())
+    }
+
+    /// Game objects which could be drawn in order of their ids
+    /// with their ranges of indices, pipelines and primitive states of these pipelines.
+    ///
+    /// Objects are skipped if the mesh contains restart indices which their pipelines
+    /// do not enable. It is reported once until the mesh or assigned pipelines are changed.
+    ///
+    fn drawn_objects(&mut self) -> Vec<(u32, Range<u32>, PipelineId, PrimitiveState)> {
+        let states: Vec<_> = self
+            .pipelines
+            .iter()
+            .map(|(descriptor, _)| descriptor.primitive_state())
+            .collect();
+        let mut skipped = 0;
+        let drawn = self
+            .objects
+            .iter()
+            .enumerate()
+            .filter_map(|(id, indices)| {
+                let resolved = self.assignments.resolve(id, &states, self.restart_indices);
+                if resolved.is_none() {
+                    skipped += 1;
+                }
+                resolved.map(|(pipeline, state)| (id as u32, indices.clone(), pipeline, state))
+            })
+            .collect();
+        if skipped > 0 && !self.skipped_reported {
+            log::warn!(
+                "{} game objects are not drawn: mesh contains restart indices, \
+                 but their pipelines do not enable primitive restart",
+                skipped,
+            );
+            self.skipped_reported = true;
+        }
+        drawn
+    }
+
+    /// Count of game objects which are drawn with one draw call each.
+    pub fn object_count(&self) -> u32 {
+        self.objects.len() as u32
+    }
+
+    /// Count of game objects which are actually drawn, without objects which are skipped
+    /// because the mesh contains restart indices which their pipelines do not enable.
+    pub fn drawn_object_count(&self) -> u32 {
+        let count = (0..self.objects.len())
+            .map(|id| &self.pipelines[self.assignments.get(id).0].0)
+            .filter(|descriptor| descriptor.primitive_state().draws(self.restart_indices))
+            .count();
+        count as u32
+    }
+
+    /// Builds a secondary command buffer that draws ids of game objects on the picking subpass.
+    ///
+    /// Each game object is drawn with the same primitive state as its pipeline,
+    /// so it covers the same pixels as when it is drawn on the scene.
+    ///
+    pub fn draw_ids<B>(
+        &mut self,
+        viewport_size: Size,
+        uniform_buffer: Arc<B>,
+    ) -> Result<SecondaryAutoCommandBuffer, ObjectDrawError>
+    where
+        B: TypedBufferAccess<Content = CameraUBO> + Send + Sync + 'static,
+    {
+        let mut builder = AutoCommandBufferBuilder::secondary_graphics(
+            self.graphics_queue.device().clone(),
+            self.graphics_queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+            self.picking_subpass.clone(),
+        )?;
+
+        let descriptor_sets = {
+            let mut builder = self.picking_descriptor_set_pool.next();
+            builder
+                .add_buffer(uniform_buffer)
+                .map_err(DescriptorSetCreationError::from)?;
+            let descriptor_set = builder.build().map_err(DescriptorSetCreationError::from)?;
+            Arc::new(descriptor_set)
         };
+
+        let (viewport, scissor) = self::viewport_scissor(viewport_size);
+        let index_count = self.index_buffer.len();
+        for indices in &self.objects {
+            index::check_index_range(indices, index_count)?;
+        }
         builder
             .set_viewport(0, std::iter::once(viewport))
-            .bind_pipeline_graphics(self.pipeline.clone())
-            .bind_vertex_buffers(0, self.vertex_buffer.clone())
-            .bind_index_buffer(self.index_buffer.clone())
-            .bind_descriptor_sets(
-                PipelineBindPoint::Graphics,
-                self.pipeline.layout().clone(),
-                0,
-                descriptor_sets,
-            )
-            .draw_indexed(self.index_buffer.len() as u32, 1, 0, 0, 0)?;
+            .set_scissor(0, std::iter::once(scissor))
+            .bind_vertex_buffers(0, self.vertex_buffer.clone());
+        self.index_buffer.bind(&mut builder);
+        let mut bound = None;
+        for (id, indices, _, state) in self.drawn_objects() {
+            // Pipelines of picking are built with each pipeline of game objects.
+            let pipeline = &self.picking_pipelines[&state];
+            if bound != Some(state) {
+                builder.bind_pipeline_graphics(pipeline.clone());
+                builder.bind_descriptor_sets(
+                    PipelineBindPoint::Graphics,
+                    pipeline.layout().clone(),
+                    0,
+                    descriptor_sets.clone(),
+                );
+                bound = Some(state);
+            }
+            let index_count = indices.end - indices.start;
+            push::push_constants(
+                &mut builder,
+                pipeline.layout().clone(),
+                &self.picking_layout,
+                self::fragment(),
+                id,
+            )?;
+            builder.draw_indexed(index_count, 1, indices.start, 0, 0)?;
+        }
         Ok(builder.build()?)
     }
 }
@@ -0,0 +1,52 @@
+use thiserror::Error;
+use vulkano::command_buffer::{
+    AutoCommandBufferBuilderContextError, BeginRenderPassError, BuildError, CommandBufferExecError,
+    ExecuteCommandsError,
+};
+use vulkano::image::view::ImageViewCreationError;
+use vulkano::render_pass::{FramebufferCreationError, RenderPassCreationError};
+use vulkano::OomError;
+
+use crate::graphics::target::TargetError;
+
+#[derive(Debug, Error)]
+pub enum PickingSystemCreationError {
+    #[error("queue family must support graphics operations")]
+    QueueFamilyNotSupported,
+
+    #[error("render pass creation failure: {0}")]
+    RenderPassCreation(#[from] RenderPassCreationError),
+
+    #[error("render targets declaration failure: {0}")]
+    Targets(#[from] TargetError),
+}
+
+#[derive(Debug, Error)]
+pub enum PickError {
+    #[error("picking command buffer allocation failure: {0}")]
+    OutOfMemory(#[from] OomError),
+
+    #[error("failed to recreate render targets of the picking pass: {0}")]
+    Targets(#[from] TargetError),
+
+    #[error("failed to create an image view for the picking pass: {0}")]
+    ImageViewCreation(#[from] ImageViewCreationError),
+
+    #[error("failed to create framebuffer for the picking pass: {0}")]
+    FramebufferCreation(#[from] FramebufferCreationError),
+
+    #[error("begin render pass command failure: {0}")]
+    BeginRenderPass(#[from] BeginRenderPassError),
+
+    #[error("picking command buffer building error: {0}")]
+    WrongUsage(#[from] AutoCommandBufferBuilderContextError),
+
+    #[error("picking draw command buffer execution failure: {0}")]
+    ExecuteCommands(#[from] ExecuteCommandsError),
+
+    #[error("picking command buffer build failure: {0}")]
+    Build(#[from] BuildError),
+
+    #[error("picking command buffer execution failure: {0}")]
+    Execution(#[from] CommandBufferExecError),
+}
@@ -0,0 +1,326 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use vulkano::command_buffer::{
+    AutoCommandBufferBuilder, CommandBufferUsage, SecondaryCommandBuffer, SubpassContents,
+};
+use vulkano::device::Queue;
+use vulkano::format::{ClearValue, Format};
+use vulkano::image::view::ImageView;
+use vulkano::image::{ImageUsage, SampleCount};
+use vulkano::render_pass::{Framebuffer, RenderPass, Subpass};
+use vulkano::sync::GpuFuture;
+
+use error::{PickError, PickingSystemCreationError};
+
+use crate::graphics::{
+    memory::MemoryTracker,
+    readback::{ReadbackRegion, Readbacks},
+    target::{SizePolicy, TargetDesc, TargetGroup},
+    utils,
+};
+
+pub mod error;
+mod tests;
+
+/// Format of the image which contains id of the object for each pixel.
+const ID_FORMAT: Format = Format::R32_UINT;
+
+/// Value of the id image for pixels which are not covered by any object.
+const BACKGROUND_ID: u32 = u32::MAX;
+
+/// Name of the id buffer in the render targets of the picking pass.
+const ID_BUFFER: &str = "id buffer";
+
+/// Name of the depth buffer in the render targets of the picking pass.
+const DEPTH_BUFFER: &str = "depth buffer";
+
+/// Count of frames after frames in flight which results of pick requests are kept for.
+///
+/// Results which were not retrieved during this time are dropped,
+/// so tickets which are never polled do not leak their results.
+///
+const RESULT_FRAMES: u64 = 60;
+
+/// Handle of the pick request which can be used to retrieve its result.
+#[derive(Debug, Eq, PartialEq, Hash)]
+pub struct PickTicket(u64);
+
+/// Result of the pick request.
+#[derive(Debug)]
+pub enum PickResult {
+    /// Request was not resolved yet, so ticket is returned back.
+    Pending(PickTicket),
+    /// Id of the object under the requested pixel or `None` for background.
+    Resolved(Option<u32>),
+    /// Result was not retrieved in time after it was resolved, so it was dropped.
+    Expired,
+}
+
+/// Results of pick requests which were read back, with frames which requests were executed on.
+#[derive(Debug, Default)]
+struct PickResults {
+    /// Results with frames of their picking passes by tickets.
+    results: HashMap<u64, (u64, Option<u32>)>,
+    /// Tickets before this one were either retrieved or dropped.
+    expired: u64,
+}
+
+impl PickResults {
+    /// Stores result of the request which was executed on provided frame.
+    fn insert(&mut self, ticket: u64, frame: u64, id: Option<u32>) {
+        self.results.insert(ticket, (frame, id));
+    }
+
+    /// Removes result of the request, if it is ready.
+    fn take(&mut self, ticket: PickTicket) -> PickResult {
+        match self.results.remove(&ticket.0) {
+            Some((_, id)) => PickResult::Resolved(id),
+            None if ticket.0 < self.expired => PickResult::Expired,
+            None => PickResult::Pending(ticket),
+        }
+    }
+
+    /// Drops results of requests which were executed more than `lifetime` frames before.
+    ///
+    /// Requests are executed in order of their tickets, so all tickets before
+    /// the last dropped one are expired too.
+    ///
+    fn prune(&mut self, frame: u64, lifetime: u64) {
+        let expired = &mut self.expired;
+        self.results.retain(|&ticket, &mut (executed, _)| {
+            let keep = executed + lifetime >= frame;
+            if !keep {
+                *expired = (*expired).max(ticket + 1);
+            }
+            keep
+        });
+    }
+
+    /// Count of results which were not retrieved yet.
+    fn len(&self) -> usize {
+        self.results.len()
+    }
+}
+
+/// System that renders ids of game objects and reads them back for pick requests.
+///
+/// Picking pass is executed only on frames with outstanding pick requests,
+/// so there is no cost when nothing is picked.
+///
+pub struct PickingSystem {
+    /// Queue to render ids and read them back.
+    graphics_queue: Arc<Queue>,
+
+    /// Render pass used for the drawing of ids.
+    render_pass: Arc<RenderPass>,
+
+    /// Render targets of the picking pass which follow the size of the final image.
+    ///
+    /// Id buffer contains the id of the object for each pixel.
+    /// Depth buffer is owned by the picking pass because depth buffer of the scene
+    /// could be absent or multisampled.
+    ///
+    targets: TargetGroup,
+
+    /// Id of the next pick request.
+    next_ticket: u64,
+
+    /// Requests which will be executed in the next picking pass.
+    queued: Vec<(u64, [u32; 2])>,
+
+    /// Results of requests which were read back from the id buffer.
+    resolved: Arc<Mutex<PickResults>>,
+
+    /// Count of frames which results are kept for after the frame of their request.
+    result_lifetime: u64,
+
+    /// Tracker of allocations of render targets.
+    memory: Arc<MemoryTracker>,
+}
+
+impl PickingSystem {
+    /// Creates the picking system.
+    ///
+    /// Results of requests are kept for provided count of frames in flight
+    /// and some more frames after their requests were executed.
+    ///
+    pub fn new(
+        graphics_queue: Arc<Queue>,
+        memory: Arc<MemoryTracker>,
+        frames_in_flight: u32,
+    ) -> Result<Self, PickingSystemCreationError> {
+        // Check queue for graphics support.
+        if !graphics_queue.family().supports_graphics() {
+            return Err(PickingSystemCreationError::QueueFamilyNotSupported);
+        }
+
+        let device = graphics_queue.device().clone();
+        let depth_format = utils::suitable_depth_stencil_format(device.physical_device());
+
+        let render_pass = Arc::new(vulkano::single_pass_renderpass! {
+            device,
+            attachments: {
+                id: {
+                    load: Clear,
+                    store: Store,
+                    format: ID_FORMAT,
+                    samples: 1,
+                },
+                depth: {
+                    load: Clear,
+                    store: DontCare,
+                    format: depth_format,
+                    samples: 1,
+                    initial_layout: ImageLayout::Undefined,
+                    final_layout: ImageLayout::DepthStencilAttachmentOptimal,
+                }
+            },
+            pass: { color: [id], depth_stencil: {depth} }
+        }?);
+
+        let mut targets = TargetGroup::new("picking");
+        targets.declare(TargetDesc {
+            name: ID_BUFFER.to_string(),
+            format: ID_FORMAT,
+            usage: ImageUsage {
+                color_attachment: true,
+                transfer_source: true,
+                ..ImageUsage::none()
+            },
+            samples: SampleCount::Sample1,
+            policy: SizePolicy::SwapchainRelative(1.0),
+        })?;
+        targets.declare(TargetDesc {
+            name: DEPTH_BUFFER.to_string(),
+            format: depth_format,
+            usage: ImageUsage {
+                depth_stencil_attachment: true,
+                transient_attachment: true,
+                ..ImageUsage::none()
+            },
+            samples: SampleCount::Sample1,
+            policy: SizePolicy::SwapchainRelative(1.0),
+        })?;
+
+        Ok(Self {
+            graphics_queue,
+            render_pass,
+            targets,
+            next_ticket: 0,
+            queued: Vec::new(),
+            resolved: Arc::default(),
+            result_lifetime: frames_in_flight as u64 + RESULT_FRAMES,
+            memory,
+        })
+    }
+
+    /// Retrieve subpass for id rendering.
+    pub fn subpass(&self) -> Subpass {
+        Subpass::from(self.render_pass.clone(), 0).unwrap()
+    }
+
+    /// Schedules readback of the object id under the pixel with provided coordinates.
+    pub fn request(&mut self, x: u32, y: u32) -> PickTicket {
+        let ticket = self.next_ticket;
+        self.next_ticket += 1;
+        self.queued.push((ticket, [x, y]));
+        PickTicket(ticket)
+    }
+
+    /// Checks if there are requests which should be executed in the next picking pass.
+    pub fn has_requests(&self) -> bool {
+        !self.queued.is_empty()
+    }
+
+    /// Retrieves the result of the pick request, if it is ready.
+    pub fn result(&mut self, ticket: PickTicket) -> PickResult {
+        self.resolved.lock().unwrap().take(ticket)
+    }
+
+    /// Drops results which were not retrieved for too long before provided frame.
+    pub fn prune(&mut self, frame: u64) {
+        let mut resolved = self.resolved.lock().unwrap();
+        let before = resolved.len();
+        resolved.prune(frame, self.result_lifetime);
+        let dropped = before - resolved.len();
+        if dropped > 0 {
+            log::debug!("{} results of pick requests expired", dropped);
+        }
+    }
+
+    /// Executes the picking pass for all queued requests of provided frame after provided future.
+    ///
+    /// Picking pass is rendered with provided dimensions of the final image,
+    /// and `draw_command_buffer` must draw ids of objects on the [`subpass`](Self::subpass).
+    /// Pixels of requests are read back by `readbacks` when the frame is retired.
+    ///
+    pub fn execute<F, C>(
+        &mut self,
+        before_future: F,
+        frame: u64,
+        dimensions: [u32; 2],
+        draw_command_buffer: C,
+        readbacks: &mut Readbacks,
+    ) -> Result<Box<dyn GpuFuture + Send + Sync>, PickError>
+    where
+        F: GpuFuture + Send + Sync + 'static,
+        C: SecondaryCommandBuffer + Send + Sync + 'static,
+    {
+        let device = self.graphics_queue.device().clone();
+
+        self.targets
+            .update(device.clone(), dimensions, &self.memory)?;
+        let id_buffer = self.targets.image(ID_BUFFER).unwrap();
+        let depth_buffer = self.targets.view(DEPTH_BUFFER).unwrap();
+
+        let framebuffer = Arc::new(
+            Framebuffer::start(self.render_pass.clone())
+                .add(ImageView::new(id_buffer.clone())?)?
+                .add(depth_buffer)?
+                .build()?,
+        );
+
+        let clear_values = [
+            ClearValue::Uint([BACKGROUND_ID, 0, 0, 0]),
+            ClearValue::Depth(1.0),
+        ];
+
+        let mut builder = AutoCommandBufferBuilder::primary(
+            device.clone(),
+            self.graphics_queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+        )?;
+        builder
+            .begin_render_pass(
+                framebuffer,
+                SubpassContents::SecondaryCommandBuffers,
+                clear_values,
+            )?
+            .execute_commands(draw_command_buffer)?
+            .end_render_pass()?;
+
+        for (ticket, [x, y]) in self.queued.drain(..) {
+            if x >= dimensions[0] || y >= dimensions[1] {
+                self.resolved.lock().unwrap().insert(ticket, frame, None);
+                continue;
+            }
+            let region = ReadbackRegion::Image {
+                image: id_buffer.clone(),
+                origin: [x, y],
+                extent: [1, 1],
+            };
+            let resolved = self.resolved.clone();
+            readbacks.request_region(region, move |bytes| {
+                let id = u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+                let id = (id != BACKGROUND_ID).then_some(id);
+                resolved.lock().unwrap().insert(ticket, frame, id);
+            });
+        }
+
+        let command_buffer = builder.build()?;
+        let after_future =
+            before_future.then_execute(self.graphics_queue.clone(), command_buffer)?;
+        Ok(Box::new(after_future))
+    }
+}
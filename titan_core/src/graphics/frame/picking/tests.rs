@@ -0,0 +1,58 @@
+#![cfg(test)]
+
+use super::{PickResult, PickResults, PickTicket};
+
+#[test]
+fn result_is_taken_once() {
+    let mut results = PickResults::default();
+    results.insert(0, 10, Some(3));
+    assert!(matches!(
+        results.take(PickTicket(0)),
+        PickResult::Resolved(Some(3))
+    ));
+    assert_eq!(results.len(), 0);
+}
+
+#[test]
+fn unresolved_ticket_is_pending() {
+    let mut results = PickResults::default();
+    results.insert(0, 10, None);
+    assert!(matches!(
+        results.take(PickTicket(1)),
+        PickResult::Pending(PickTicket(1))
+    ));
+}
+
+#[test]
+fn results_are_kept_for_lifetime() {
+    let mut results = PickResults::default();
+    results.insert(0, 10, Some(1));
+    results.prune(15, 5);
+    assert_eq!(results.len(), 1);
+    assert!(matches!(
+        results.take(PickTicket(0)),
+        PickResult::Resolved(Some(1))
+    ));
+}
+
+#[test]
+fn never_polled_results_are_pruned() {
+    let mut results = PickResults::default();
+    results.insert(0, 10, Some(1));
+    results.insert(1, 10, None);
+    results.insert(2, 12, Some(2));
+    results.prune(16, 5);
+    assert_eq!(results.len(), 1);
+
+    assert!(matches!(results.take(PickTicket(0)), PickResult::Expired));
+    assert!(matches!(results.take(PickTicket(1)), PickResult::Expired));
+    assert!(matches!(
+        results.take(PickTicket(2)),
+        PickResult::Resolved(Some(2))
+    ));
+    // Requests after pruned ones are still pending.
+    assert!(matches!(
+        results.take(PickTicket(3)),
+        PickResult::Pending(PickTicket(3))
+    ));
+}
@@ -0,0 +1,51 @@
+use thiserror::Error;
+use vulkano::command_buffer::{BuildError, DrawError};
+use vulkano::pipeline::GraphicsPipelineCreationError;
+use vulkano::sampler::SamplerCreationError;
+use vulkano::OomError;
+
+use crate::graphics::renderer::error::DescriptorSetCreationError;
+use crate::graphics::spirv::ShaderStage;
+
+#[derive(Debug, Error)]
+pub enum PostProcessSystemCreationError {
+    #[error("queue family must support graphics operations")]
+    QueueFamilyNotSupported,
+
+    #[error("scene sampler creation failure: {0}")]
+    SamplerCreation(#[from] SamplerCreationError),
+}
+
+/// Error that can happen when building pipeline of [post effect](super::PostEffect).
+#[derive(Debug, Error)]
+pub enum PostEffectError {
+    #[error("{0} shader cannot be used as post effect, fragment shader is required")]
+    NotFragmentStage(ShaderStage),
+
+    #[error("post processing is disabled by configuration")]
+    Disabled,
+
+    #[error("post effect index {index} is out of range of the chain of {len} effects")]
+    EffectOutOfRange { index: usize, len: usize },
+
+    #[error("built-in shader module allocation failure: {0}")]
+    OutOfMemory(#[from] OomError),
+
+    #[error("graphics pipeline creation failure: {0}")]
+    GraphicsPipelineCreation(#[from] GraphicsPipelineCreationError),
+}
+
+#[derive(Debug, Error)]
+pub enum PostDrawError {
+    #[error("command buffer allocation failure: {0}")]
+    OutOfMemory(#[from] OomError),
+
+    #[error("scene descriptor set creation failure: {0}")]
+    DescriptorSetCreation(#[from] DescriptorSetCreationError),
+
+    #[error("draw command failure: {0}")]
+    Draw(#[from] DrawError),
+
+    #[error("draw command buffer build failure: {0}")]
+    CommandBufferBuild(#[from] BuildError),
+}
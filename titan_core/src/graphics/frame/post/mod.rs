@@ -0,0 +1,363 @@
+//! Post processing of the scene before UI is drawn.
+//!
+//! With post processing [enabled](crate::config::Config::with_post_processing),
+//! the scene is processed by [chain](PostProcessChain) of [effects](PostEffect),
+//! each of them is one fullscreen pass which samples the output of the previous one.
+//! Game objects are rendered into the first of two ping-pong color targets
+//! of the size of the final image, effects render into them in turn,
+//! and the last effect renders into the final image, then UI is drawn over the result.
+//! Scene of empty chain is rendered directly into the final image without any copies.
+//!
+//! Custom effect is a fragment shader with the following interface:
+//!
+//! ```glsl
+//! layout(location = 0) in vec2 uv;
+//! layout(location = 0) out vec4 outColor;
+//! layout(binding = 0, set = 0) uniform sampler2D scene;
+//! layout(push_constant) uniform PushConstants {
+//!     vec4 params;
+//! } effect;
+//! ```
+//!
+//! Parameters of the effect are passed by push constants,
+//! so they could be [changed](PostProcessChain::set_params) each frame.
+
+use std::sync::Arc;
+
+use vulkano::command_buffer::{
+    AutoCommandBufferBuilder, CommandBufferUsage, SecondaryAutoCommandBuffer,
+};
+use vulkano::descriptor_set::PersistentDescriptorSet;
+use vulkano::device::Queue;
+use vulkano::image::view::ImageView;
+use vulkano::image::AttachmentImage;
+use vulkano::pipeline::cache::PipelineCache;
+use vulkano::pipeline::vertex::BuffersDefinition;
+use vulkano::pipeline::viewport::Viewport;
+use vulkano::pipeline::{GraphicsPipeline, PipelineBindPoint};
+use vulkano::render_pass::Subpass;
+use vulkano::sampler::{Filter, MipmapMode, Sampler, SamplerAddressMode};
+
+use crate::{
+    graphics::{
+        frame::object_draw,
+        renderer::error::DescriptorSetCreationError,
+        spirv::{Shader, ShaderStage},
+    },
+    window::Size,
+};
+
+use self::error::{PostDrawError, PostEffectError, PostProcessSystemCreationError};
+
+pub mod error;
+mod tests;
+
+/// Effect which is applied to the whole scene by post processing.
+#[derive(Clone, Default)]
+pub enum PostEffect {
+    /// Scene is copied as is.
+    #[default]
+    Passthrough,
+    /// Colors of the scene are inverted.
+    Invert,
+    /// Corners of the scene are darkened.
+    ///
+    /// Parameters are the outer radius of the vignette and the softness of its edge,
+    /// both relative to the size of the screen.
+    ///
+    Vignette,
+    /// Scene is processed by custom fragment shader.
+    Custom(Shader),
+}
+
+impl PostEffect {
+    /// Parameters which the effect is added to the chain with by default.
+    pub const fn default_params(&self) -> [f32; 4] {
+        match self {
+            Self::Vignette => [0.75, 0.4, 0.0, 0.0],
+            Self::Passthrough | Self::Invert | Self::Custom(_) => [0.0; 4],
+        }
+    }
+}
+
+/// Ordered list of effects which the scene is processed with, one fullscreen pass per effect.
+#[derive(Clone, Default)]
+pub struct PostProcessChain {
+    effects: Vec<(PostEffect, [f32; 4])>,
+}
+
+impl PostProcessChain {
+    /// Creates empty chain, which renders the scene directly into the final image.
+    pub const fn new() -> Self {
+        Self {
+            effects: Vec::new(),
+        }
+    }
+
+    /// Appends effect to the end of the chain
+    /// with its [default parameters](PostEffect::default_params).
+    pub fn with_effect(self, effect: PostEffect) -> Self {
+        let params = effect.default_params();
+        self.with_effect_params(effect, params)
+    }
+
+    /// Appends effect to the end of the chain with provided parameters.
+    pub fn with_effect_params(mut self, effect: PostEffect, params: [f32; 4]) -> Self {
+        self.effects.push((effect, params));
+        self
+    }
+
+    /// Replaces parameters of the effect with provided index.
+    pub fn set_params(&mut self, index: usize, params: [f32; 4]) -> Result<(), PostEffectError> {
+        let len = self.effects.len();
+        let (_, old) = self
+            .effects
+            .get_mut(index)
+            .ok_or(PostEffectError::EffectOutOfRange { index, len })?;
+        *old = params;
+        Ok(())
+    }
+
+    /// Count of effects in the chain.
+    pub fn len(&self) -> usize {
+        self.effects.len()
+    }
+
+    /// If the chain has no effects.
+    pub fn is_empty(&self) -> bool {
+        self.effects.is_empty()
+    }
+
+    /// Effects of the chain with their parameters, in order of their passes.
+    pub fn effects(&self) -> impl Iterator<Item = (&PostEffect, [f32; 4])> {
+        self.effects
+            .iter()
+            .map(|(effect, params)| (effect, *params))
+    }
+}
+
+/// Ping-pong target which each pass of the chain samples,
+/// and target which it renders into, or `None` for the final image.
+///
+/// Scene itself is rendered into the first target if the chain is not empty.
+///
+pub(crate) fn ping_pong(effects: usize) -> impl Iterator<Item = (usize, Option<usize>)> {
+    (0..effects).map(move |index| {
+        let output = (index + 1 < effects).then(|| (index + 1) % 2);
+        (index % 2, output)
+    })
+}
+
+/// Scene image with descriptor set which samples it.
+type SceneSet = (
+    Arc<ImageView<Arc<AttachmentImage>>>,
+    Arc<PersistentDescriptorSet>,
+);
+
+pub struct PostProcessSystem {
+    /// Queue to render.
+    graphics_queue: Arc<Queue>,
+
+    /// Subpass which post processing is drawn in.
+    subpass: Subpass,
+
+    /// Cache which pipelines of effects are built with.
+    pipeline_cache: Arc<PipelineCache>,
+
+    /// Graphics pipelines of effects of the chain with their parameters, in order of passes.
+    effects: Vec<(Arc<GraphicsPipeline>, [f32; 4])>,
+
+    /// A sampler for the scene image.
+    sampler: Arc<Sampler>,
+
+    /// Descriptor sets for ping-pong targets which were sampled recently.
+    scene_sets: Vec<SceneSet>,
+}
+
+impl PostProcessSystem {
+    /// Creates new post process system with empty chain.
+    pub fn new(
+        graphics_queue: Arc<Queue>,
+        subpass: Subpass,
+        pipeline_cache: Arc<PipelineCache>,
+    ) -> Result<Self, PostProcessSystemCreationError> {
+        // Check queue for graphics support.
+        if !graphics_queue.family().supports_graphics() {
+            return Err(PostProcessSystemCreationError::QueueFamilyNotSupported);
+        }
+
+        let sampler = Sampler::new(
+            graphics_queue.device().clone(),
+            Filter::Linear,
+            Filter::Linear,
+            MipmapMode::Nearest,
+            SamplerAddressMode::ClampToEdge,
+            SamplerAddressMode::ClampToEdge,
+            SamplerAddressMode::ClampToEdge,
+            0.0,
+            1.0,
+            0.0,
+            0.0,
+        )?;
+
+        Ok(Self {
+            graphics_queue,
+            subpass,
+            pipeline_cache,
+            effects: Vec::new(),
+            sampler,
+            scene_sets: Vec::new(),
+        })
+    }
+
+    /// Replaces the chain which the scene is processed with.
+    ///
+    /// Previous chain stays active if pipeline of any effect cannot be built.
+    ///
+    pub fn set_chain(&mut self, chain: &PostProcessChain) -> Result<(), PostEffectError> {
+        self.effects = chain
+            .effects()
+            .map(|(effect, params)| {
+                let pipeline = self::pipeline(
+                    &self.graphics_queue,
+                    self.subpass.clone(),
+                    self.pipeline_cache.clone(),
+                    effect,
+                )?;
+                Ok((pipeline, params))
+            })
+            .collect::<Result<_, PostEffectError>>()?;
+        Ok(())
+    }
+
+    /// Replaces parameters of the effect of the chain with provided index.
+    pub fn set_params(&mut self, index: usize, params: [f32; 4]) -> Result<(), PostEffectError> {
+        let len = self.effects.len();
+        let (_, old) = self
+            .effects
+            .get_mut(index)
+            .ok_or(PostEffectError::EffectOutOfRange { index, len })?;
+        *old = params;
+        Ok(())
+    }
+
+    /// Count of effects of the chain, which is the count of post processing passes.
+    pub fn effect_count(&self) -> usize {
+        self.effects.len()
+    }
+
+    /// Builds a secondary command buffer that draws the scene processed by the effect
+    /// with provided index over the current subpass.
+    pub fn draw(
+        &mut self,
+        index: usize,
+        viewport_size: Size,
+        scene: Arc<ImageView<Arc<AttachmentImage>>>,
+    ) -> Result<SecondaryAutoCommandBuffer, PostDrawError> {
+        use crate::graphics::shader::post::passthrough;
+
+        let (pipeline, params) = self.effects[index].clone();
+        let descriptor_set = self.scene_set(&pipeline, scene)?;
+
+        let mut builder = AutoCommandBufferBuilder::secondary_graphics(
+            self.graphics_queue.device().clone(),
+            self.graphics_queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+            self.subpass.clone(),
+        )?;
+        let viewport = Viewport {
+            origin: [0.0, 0.0],
+            dimensions: [viewport_size.width as f32, viewport_size.height as f32],
+            depth_range: 0.0..1.0,
+        };
+        let push_constants = passthrough::ty::PushConstants { params };
+        // Single triangle which covers the whole viewport is generated by vertex shader.
+        builder
+            .set_viewport(0, std::iter::once(viewport))
+            .bind_pipeline_graphics(pipeline.clone())
+            .bind_descriptor_sets(
+                PipelineBindPoint::Graphics,
+                pipeline.layout().clone(),
+                0,
+                descriptor_set,
+            )
+            .push_constants(pipeline.layout().clone(), 0, push_constants)
+            .draw(3, 1, 0, 0)?;
+        Ok(builder.build()?)
+    }
+
+    /// Descriptor set which samples provided scene image, reused while the image is alive.
+    fn scene_set(
+        &mut self,
+        pipeline: &GraphicsPipeline,
+        scene: Arc<ImageView<Arc<AttachmentImage>>>,
+    ) -> Result<Arc<PersistentDescriptorSet>, DescriptorSetCreationError> {
+        // At most two ping-pong targets are sampled each frame.
+        const MAX_SCENE_SETS: usize = 2;
+
+        if let Some((_, set)) = self
+            .scene_sets
+            .iter()
+            .find(|(image, _)| Arc::ptr_eq(image, &scene))
+        {
+            return Ok(set.clone());
+        }
+        // Layouts of all effects are the same, so sets are shared between them.
+        let layout = pipeline.layout().descriptor_set_layouts()[0].clone();
+        let mut builder = PersistentDescriptorSet::start(layout);
+        builder.add_sampled_image(scene.clone(), self.sampler.clone())?;
+        let set = Arc::new(builder.build()?);
+        if self.scene_sets.len() == MAX_SCENE_SETS {
+            self.scene_sets.remove(0);
+        }
+        self.scene_sets.push((scene, set.clone()));
+        Ok(set)
+    }
+}
+
+/// Builds graphics pipeline which draws the scene with provided effect.
+fn pipeline(
+    graphics_queue: &Queue,
+    subpass: Subpass,
+    pipeline_cache: Arc<PipelineCache>,
+    effect: &PostEffect,
+) -> Result<Arc<GraphicsPipeline>, PostEffectError> {
+    use crate::graphics::shader::post::{invert, passthrough, vertex, vignette};
+
+    let device = graphics_queue.device().clone();
+    let vert_shader_module = vertex::Shader::load(device.clone())?;
+    let passthrough_module = passthrough::Shader::load(device.clone())?;
+    let invert_module;
+    let vignette_module;
+    let fragment = match effect {
+        PostEffect::Passthrough => passthrough_module.main_entry_point(),
+        PostEffect::Invert => {
+            invert_module = invert::Shader::load(device.clone())?;
+            invert_module.main_entry_point()
+        }
+        PostEffect::Vignette => {
+            vignette_module = vignette::Shader::load(device.clone())?;
+            vignette_module.main_entry_point()
+        }
+        PostEffect::Custom(shader) => {
+            if shader.stage() != ShaderStage::Fragment {
+                return Err(PostEffectError::NotFragmentStage(shader.stage()));
+            }
+            let builtin = passthrough_module.main_entry_point();
+            object_draw::entry_point(builtin, Some(shader))
+        }
+    };
+
+    let pipeline = GraphicsPipeline::start()
+        .vertex_input(BuffersDefinition::new())
+        .vertex_shader(vert_shader_module.main_entry_point(), ())
+        .fragment_shader(fragment, ())
+        .triangle_list()
+        .viewports_dynamic_scissors_irrelevant(1)
+        .cull_mode_disabled()
+        .blend_pass_through()
+        .render_pass(subpass)
+        .build_with_cache(pipeline_cache)
+        .build(device)?;
+    Ok(Arc::new(pipeline))
+}
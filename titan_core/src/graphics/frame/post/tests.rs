@@ -0,0 +1,48 @@
+#![cfg(test)]
+
+use super::{ping_pong, PostEffect, PostProcessChain};
+use crate::graphics::frame::post::error::PostEffectError;
+
+#[test]
+fn chain_appends_effects_in_order() {
+    let chain = PostProcessChain::new()
+        .with_effect(PostEffect::Invert)
+        .with_effect(PostEffect::Vignette)
+        .with_effect_params(PostEffect::Passthrough, [1.0, 2.0, 3.0, 4.0]);
+    assert_eq!(chain.len(), 3);
+    let params: Vec<_> = chain.effects().map(|(_, params)| params).collect();
+    assert_eq!(
+        params,
+        [
+            [0.0; 4],
+            PostEffect::Vignette.default_params(),
+            [1.0, 2.0, 3.0, 4.0]
+        ],
+    );
+}
+
+#[test]
+fn chain_params_are_checked_by_index() {
+    let mut chain = PostProcessChain::new().with_effect(PostEffect::Vignette);
+    assert!(chain.set_params(0, [0.5, 0.1, 0.0, 0.0]).is_ok());
+    assert_eq!(chain.effects().next().unwrap().1, [0.5, 0.1, 0.0, 0.0]);
+    assert!(matches!(
+        chain.set_params(1, [0.0; 4]),
+        Err(PostEffectError::EffectOutOfRange { index: 1, len: 1 }),
+    ));
+}
+
+#[test]
+fn empty_chain_has_no_passes() {
+    assert!(PostProcessChain::default().is_empty());
+    assert_eq!(ping_pong(0).count(), 0);
+}
+
+#[test]
+fn effects_alternate_between_targets() {
+    assert_eq!(ping_pong(1).collect::<Vec<_>>(), [(0, None)]);
+    assert_eq!(
+        ping_pong(4).collect::<Vec<_>>(),
+        [(0, Some(1)), (1, Some(0)), (0, Some(1)), (1, None)],
+    );
+}
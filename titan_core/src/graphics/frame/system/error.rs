@@ -4,17 +4,34 @@
     ExecuteCommandsError,
 };
 use vulkano::image::view::ImageViewCreationError;
-use vulkano::image::ImageCreationError;
+use vulkano::query::QueryPoolCreationError;
 use vulkano::render_pass::{FramebufferCreationError, RenderPassCreationError};
 use vulkano::OomError;
 
+use crate::graphics::gpu_timer::GpuTimerError;
+use crate::graphics::query::{FrameQueryPoolCreationError, QueryError};
+use crate::graphics::render_pass::RenderPassDescriptorError;
+use crate::graphics::target::TargetError;
+
 #[derive(Debug, Error)]
 pub enum FrameSystemCreationError {
     #[error("queue family must support graphics operations")]
     QueueFamilyNotSupported,
 
+    #[error("render pass description failure: {0}")]
+    RenderPassDescriptor(#[from] RenderPassDescriptorError),
+
     #[error("render pass creation failure: {0}")]
     RenderPassCreation(#[from] RenderPassCreationError),
+
+    #[error("render targets declaration failure: {0}")]
+    Targets(#[from] TargetError),
+
+    #[error("timestamp query pool creation failure: {0}")]
+    QueryPoolCreation(#[from] QueryPoolCreationError),
+
+    #[error("occlusion query pool creation failure: {0}")]
+    OcclusionQueryPoolCreation(#[from] FrameQueryPoolCreationError),
 }
 
 #[derive(Debug, Error)]
@@ -25,14 +42,20 @@ pub enum FrameCreationError {
     #[error("begin render pass command failure: {0}")]
     BeginRenderPass(#[from] BeginRenderPassError),
 
-    #[error("failed to recreate an image for the frame: {0}")]
-    ImageCreation(#[from] ImageCreationError),
+    #[error("failed to recreate render targets of the frame: {0}")]
+    Targets(#[from] TargetError),
 
     #[error("failed to create an image view for the frame: {0}")]
     ImageViewCreation(#[from] ImageViewCreationError),
 
     #[error("failed to create framebuffer for the frame: {0}")]
     FramebufferCreation(#[from] FramebufferCreationError),
+
+    #[error("failed to begin GPU time measurement: {0}")]
+    GpuTimer(#[from] GpuTimerError),
+
+    #[error("failed to reset occlusion queries of the frame: {0}")]
+    OcclusionQuery(#[from] QueryError),
 }
 
 #[derive(Debug, Error)]
@@ -40,11 +63,17 @@ pub enum NextPassError {
     #[error("next pass command buffer building error: {0}")]
     WrongUsage(#[from] AutoCommandBufferBuilderContextError),
 
+    #[error("begin render pass of the next pass failure: {0}")]
+    BeginRenderPass(#[from] BeginRenderPassError),
+
     #[error("next pass command buffer build failure: {0}")]
     Build(#[from] BuildError),
 
     #[error("next pass command buffer execution failure: {0}")]
     Execution(#[from] CommandBufferExecError),
+
+    #[error("failed to end GPU time measurement: {0}")]
+    GpuTimer(#[from] GpuTimerError),
 }
 
 #[derive(Debug, Error)]
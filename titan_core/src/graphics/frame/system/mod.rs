@@ -6,17 +6,46 @@
 };
 use vulkano::device::Queue;
 use vulkano::format::{ClearValue, Format};
-use vulkano::image::view::ImageView;
-use vulkano::image::{AttachmentImage, ImageAccess, ImageUsage};
-use vulkano::render_pass::{Framebuffer, FramebufferAbstract, RenderPass, Subpass};
+use vulkano::image::view::{ImageView, ImageViewAbstract};
+use vulkano::image::{AttachmentImage, ImageAccess, ImageUsage, SampleCount};
+use vulkano::render_pass::{FramebufferAbstract, LoadOp, RenderPass, Subpass};
 use vulkano::sync::GpuFuture;
 
 use error::{DrawPassExecuteError, FrameCreationError, FrameSystemCreationError, NextPassError};
 
-use crate::{graphics::utils, window::Size};
+use crate::{
+    app::spike::FrameCounters,
+    graphics::{
+        color::Color,
+        debug,
+        frame::post,
+        gpu_timer::{GpuTime, GpuTimer},
+        memory::MemoryTracker,
+        query::{FrameQuery, FrameQueryPool, QueryKind},
+        render_pass::{self, RenderPassDescriptor},
+        target::{SizePolicy, TargetDesc, TargetGroup},
+        utils,
+    },
+    window::Size,
+};
 
 pub mod error;
 
+/// Name of the depth buffer in the render targets of the frame.
+const DEPTH_BUFFER: &str = "depth buffer";
+
+/// Name of the multisampled color buffer in the render targets of the frame.
+const COLOR_BUFFER: &str = "color buffer";
+
+/// Names of ping-pong color targets which effects of post processing render into.
+const POST_TARGETS: [&str; 2] = ["post ping", "post pong"];
+
+/// Debug label of the render pass of the frame.
+const MAIN_PASS_LABEL: &str = "main pass";
+
+/// Color of debug label of the render pass of the frame.
+const MAIN_PASS_COLOR: [f32; 4] = [0.2, 0.6, 1.0, 1.0];
+
 /// System that contains the necessary facilities for rendering a single frame.
 pub struct FrameSystem {
     /// Queue to render everything.
@@ -25,16 +54,69 @@ pub struct FrameSystem {
     /// Render pass used for the drawing.
     render_pass: Arc<RenderPass>,
 
-    /// Intermediate render target that will contain the depth of each pixel of the scene.
-    /// This is a traditional depth buffer. `0.0` means "near", and `1.0` means "far".
-    depth_buffer: Option<Arc<AttachmentImage>>,
+    /// Format of the final image which is used to encode clear color.
+    final_output_format: Format,
+
+    /// Color which the final image is cleared with.
+    clear_color: Color,
+
+    /// Intermediate render targets of the frame which follow the size of the final image.
+    ///
+    /// Depth buffer of the scene contains depth of each pixel, `0.0` means "near",
+    /// and `1.0` means "far". It is declared only if the scene is rendered with depth buffer.
+    /// Color buffer is declared only if the scene is multisampled.
+    /// Both of them have the sample count of the scene.
+    ///
+    targets: TargetGroup,
+
+    /// Render passes which are begun after the scene if it is post processed.
+    post: Option<PostPasses>,
+
+    /// Tracker of allocations of render targets.
+    memory: Arc<MemoryTracker>,
+
+    /// Timer of GPU execution time of frames, if timestamps are supported.
+    gpu_timer: Option<GpuTimer>,
+
+    /// Occlusion queries of the nominated draw, one per slot of GPU timer.
+    occlusion: FrameQueryPool,
+}
+
+/// Render passes of the frame which post processes the scene.
+///
+/// Render pass of the frame then renders only the scene, and the chain of effects
+/// is followed by the render pass of UI, so the scene of empty chain
+/// is still rendered directly into the final image.
+///
+struct PostPasses {
+    /// Render pass of effects, which overwrite ping-pong target or the final image.
+    effect_pass: Arc<RenderPass>,
+
+    /// Render pass of UI, which draws over the final image.
+    ui_pass: Arc<RenderPass>,
 }
 
 impl FrameSystem {
     /// Creates the frame system.
+    ///
+    /// If `depth` is `false`, subpass for objects has no depth attachment.
+    /// If `samples` is greater than one, objects are rendered into multisampled color buffer
+    /// which is resolved into the final image before UI rendering.
+    /// If `post_processing` is `true`, the scene could be processed by effects
+    /// rendering into ping-pong targets before UI rendering.
+    /// GPU execution time and samples of the nominated draw
+    /// are measured in `timer_slots` slots, one per swapchain image.
+    ///
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         graphics_queue: Arc<Queue>,
         final_output_format: Format,
+        depth: bool,
+        samples: SampleCount,
+        clear_color: Color,
+        post_processing: bool,
+        memory: Arc<MemoryTracker>,
+        timer_slots: usize,
     ) -> Result<Self, FrameSystemCreationError> {
         // Check queue for graphics support.
         if !graphics_queue.family().supports_graphics() {
@@ -42,58 +124,168 @@ pub fn new(
         }
 
         let device = graphics_queue.device().clone();
-        let depth_format = utils::suitable_depth_stencil_format(device.physical_device());
+        let depth_format =
+            depth.then(|| utils::suitable_depth_stencil_format(device.physical_device()));
+
+        let post = if post_processing {
+            let overlay = |load| {
+                let desc = RenderPassDescriptor::overlay(final_output_format, load).build()?;
+                let render_pass = RenderPass::new(device.clone(), desc)?;
+                Ok::<_, FrameSystemCreationError>(Arc::new(render_pass))
+            };
+            Some(PostPasses {
+                effect_pass: overlay(LoadOp::DontCare)?,
+                ui_pass: overlay(LoadOp::Load)?,
+            })
+        } else {
+            None
+        };
+        // Scene and UI are rendered in separate render passes if the scene is post processed.
+        let desc = match post {
+            Some(_) => RenderPassDescriptor::offscreen(final_output_format, depth_format, samples),
+            None => RenderPassDescriptor::swapchain(final_output_format, depth_format, samples),
+        };
+        let render_pass = Arc::new(RenderPass::new(device, desc.build()?)?);
+
+        let mut targets = TargetGroup::new("frame");
+        if let Some(depth_format) = depth_format {
+            targets.declare(TargetDesc {
+                name: DEPTH_BUFFER.to_string(),
+                format: depth_format,
+                usage: ImageUsage {
+                    depth_stencil_attachment: true,
+                    transient_attachment: true,
+                    ..ImageUsage::none()
+                },
+                samples,
+                policy: SizePolicy::SwapchainRelative(1.0),
+            })?;
+        }
+        if samples != SampleCount::Sample1 {
+            targets.declare(TargetDesc {
+                name: COLOR_BUFFER.to_string(),
+                format: final_output_format,
+                usage: ImageUsage {
+                    color_attachment: true,
+                    transient_attachment: true,
+                    ..ImageUsage::none()
+                },
+                samples,
+                policy: SizePolicy::SwapchainRelative(1.0),
+            })?;
+        }
+        if post.is_some() {
+            for name in POST_TARGETS {
+                targets.declare(TargetDesc {
+                    name: name.to_string(),
+                    format: final_output_format,
+                    usage: ImageUsage {
+                        color_attachment: true,
+                        sampled: true,
+                        ..ImageUsage::none()
+                    },
+                    samples: SampleCount::Sample1,
+                    policy: SizePolicy::SwapchainRelative(1.0),
+                })?;
+            }
+        }
 
-        // TODO: vulkano error: https://github.com/vulkano-rs/vulkano/issues/1665
-        let render_pass = Arc::new(vulkano::ordered_passes_renderpass! {
+        let gpu_timer = GpuTimer::new(
             graphics_queue.device().clone(),
-            attachments: {
-                color: {
-                    load: Clear,
-                    store: Store,
-                    format: final_output_format,
-                    samples: 1,
-                },
-                depth: {
-                    load: Clear,
-                    store: DontCare,
-                    format: depth_format,
-                    samples: 1,
-                    initial_layout: ImageLayout::Undefined,
-                    final_layout: ImageLayout::DepthStencilAttachmentOptimal,
-                }
-            },
-            passes: [
-                // Subpass for complex rendering.
-                { color: [color], depth_stencil: {depth}, input: [] },
-                // Subpass for UI rendering.
-                { color: [color], depth_stencil: {}, input: [] }
-            ]
-        }?);
+            graphics_queue.family(),
+            timer_slots,
+        )?;
+        if gpu_timer.is_none() {
+            log::warn!("timestamps are not supported by graphics queue, GPU time is not measured");
+        }
+        let occlusion = FrameQueryPool::new(
+            graphics_queue.device().clone(),
+            QueryKind::Occlusion,
+            1,
+            timer_slots,
+        )?;
 
         Ok(Self {
             graphics_queue,
             render_pass,
-            depth_buffer: None,
+            final_output_format,
+            clear_color,
+            targets,
+            post,
+            memory,
+            gpu_timer,
+            occlusion,
         })
     }
 
+    /// Reads GPU execution time of the frame which was previously rendered in provided slot.
+    ///
+    /// Never waits for the device, returns `None` if time is not available yet.
+    ///
+    pub fn read_gpu_time(&mut self, slot: usize) -> Option<GpuTime> {
+        self.gpu_timer.as_mut()?.read(slot)
+    }
+
+    /// Remembers number and counters of the frame which was rendered in provided slot,
+    /// so they are reported along with its GPU execution time.
+    pub fn record_gpu_frame(&mut self, slot: usize, frame: u64, counters: FrameCounters) {
+        if let Some(gpu_timer) = self.gpu_timer.as_mut() {
+            gpu_timer.record(slot, frame, counters);
+        }
+    }
+
+    /// Reads count of samples of the nominated draw of the frame
+    /// which was previously rendered in provided slot.
+    ///
+    /// Never waits for the device, returns `None` if the count is not available yet
+    /// or no draw was nominated in that frame.
+    ///
+    pub fn read_occlusion_samples(&mut self, slot: usize) -> Option<u64> {
+        let results = self.occlusion.results::<u64>(slot)?;
+        results.get(0).map(|values| values[0])
+    }
+
+    /// Color which the final image is cleared with.
+    pub fn clear_color(&self) -> Color {
+        self.clear_color
+    }
+
+    /// Sets color which the final image is cleared with.
+    pub fn set_clear_color(&mut self, clear_color: Color) {
+        self.clear_color = clear_color;
+    }
+
     /// Retrieve subpass for object rendering.
     pub fn object_subpass(&self) -> Subpass {
         Subpass::from(self.render_pass.clone(), 0).unwrap()
     }
 
+    /// Retrieve subpass for post processing, if the scene could be post processed.
+    pub fn post_subpass(&self) -> Option<Subpass> {
+        let post = self.post.as_ref()?;
+        Some(Subpass::from(post.effect_pass.clone(), 0).unwrap())
+    }
+
     /// Retrieve subpass for UI rendering.
     pub fn ui_subpass(&self) -> Subpass {
-        Subpass::from(self.render_pass.clone(), 1).unwrap()
+        match &self.post {
+            Some(post) => Subpass::from(post.ui_pass.clone(), 0).unwrap(),
+            None => Subpass::from(self.render_pass.clone(), 1).unwrap(),
+        }
     }
 
-    /// Starts drawing a new frame.
+    /// Starts drawing a new frame, measuring its GPU execution time in provided slot.
+    ///
+    /// The scene is processed by `post_effects` passes before UI is drawn,
+    /// which are ignored if post processing is disabled.
+    ///
     pub fn frame<F, I>(
         &mut self,
         before_future: F,
         final_image: Arc<I>,
-    ) -> Result<Frame, FrameCreationError>
+        timer_slot: usize,
+        post_effects: usize,
+    ) -> Result<Frame<'_>, FrameCreationError>
     where
         F: GpuFuture + Send + Sync + 'static,
         I: ImageAccess + Send + Sync + 'static,
@@ -101,46 +293,60 @@ pub fn frame<F, I>(
         let device = self.graphics_queue.device().clone();
 
         let dimensions = final_image.dimensions().width_height();
-        let old_dimensions = self
-            .depth_buffer
-            .as_ref()
-            .map(|b| b.dimensions().width_height());
-
-        // If there is no depth buffer (first call after initialization)
-        // or dimensions are incompatible, (re)create buffers.
-        if old_dimensions.is_none() || old_dimensions.unwrap() != dimensions {
-            // (Re)create depth buffer.
-            let depth_buffer = {
-                let depth_format = utils::suitable_depth_stencil_format(device.physical_device());
-                AttachmentImage::with_usage(
-                    device.clone(),
-                    dimensions,
-                    depth_format,
-                    ImageUsage::depth_stencil_attachment(),
-                )?
-            };
-            self.depth_buffer = Some(depth_buffer.clone());
-        }
+        // Targets are (re)created on the first call after initialization
+        // or if dimensions of the final image were changed.
+        self.targets
+            .update(device.clone(), dimensions, &self.memory)?;
+
+        let final_view: Arc<dyn ImageViewAbstract + Send + Sync> = ImageView::new(final_image)?;
+        let post_effects = if self.post.is_some() { post_effects } else { 0 };
+        let post_target = |index| self.targets.view(POST_TARGETS[index]).unwrap();
 
-        // Create framebuffer.
+        // Create framebuffer, attachments are in order of the render pass description.
         let framebuffer = {
-            let image_view = ImageView::new(final_image.clone())?;
-            let depth_buffer_view = {
-                let depth_buffer = self.depth_buffer.as_ref().unwrap().clone();
-                ImageView::new(depth_buffer)?
+            // Scene is rendered into the first ping-pong target if it is processed by effects.
+            let target: Arc<dyn ImageViewAbstract + Send + Sync> = match post_effects {
+                0 => final_view.clone(),
+                _ => post_target(0),
             };
-            Arc::new(
-                Framebuffer::start(self.render_pass.clone())
-                    .add(image_view)?
-                    .add(depth_buffer_view)?
-                    .build()?,
-            )
+            let mut views = vec![target];
+            for name in [DEPTH_BUFFER, COLOR_BUFFER] {
+                if let Some(view) = self.targets.view(name) {
+                    views.push(view);
+                }
+            }
+            render_pass::framebuffer(self.render_pass.clone(), &views)?
         };
 
-        let clear_values = [
-            ClearValue::Float([0.0, 0.0, 0.0, 1.0]),
-            ClearValue::Depth(1.0),
-        ];
+        let clear_color = ClearValue::Float(self.clear_color.for_format(self.final_output_format));
+        let multisampled = self.targets.size(COLOR_BUFFER).is_some();
+        // Final image is fully overwritten by the resolve of multisampled color buffer.
+        let mut clear_values = vec![if multisampled {
+            ClearValue::None
+        } else {
+            clear_color
+        }];
+        if self.targets.size(DEPTH_BUFFER).is_some() {
+            clear_values.push(ClearValue::Depth(1.0));
+        }
+        if multisampled {
+            clear_values.push(clear_color);
+        }
+
+        let mut post_passes = Vec::with_capacity(post_effects);
+        let mut ui_framebuffer = None;
+        if let Some(post) = &self.post {
+            for (input, output) in post::ping_pong(post_effects) {
+                let output: Arc<dyn ImageViewAbstract + Send + Sync> = match output {
+                    Some(output) => post_target(output),
+                    None => final_view.clone(),
+                };
+                let framebuffer = render_pass::framebuffer(post.effect_pass.clone(), &[output])?;
+                post_passes.push((framebuffer, post_target(input)));
+            }
+            let framebuffer = render_pass::framebuffer(post.ui_pass.clone(), &[final_view])?;
+            ui_framebuffer = Some(framebuffer);
+        }
 
         // Build primary command buffer that will execute secondary command buffers
         // in rendering process.
@@ -149,6 +355,12 @@ pub fn frame<F, I>(
             self.graphics_queue.family(),
             CommandBufferUsage::OneTimeSubmit,
         )?;
+        if let Some(gpu_timer) = self.gpu_timer.as_mut() {
+            gpu_timer.begin(&mut builder, timer_slot)?;
+        }
+        self.occlusion.reset(&mut builder, timer_slot)?;
+        let occlusion_query = self.occlusion.frame_query(timer_slot, 0)?;
+        let main_pass_label = debug::begin_label(&mut builder, MAIN_PASS_LABEL, MAIN_PASS_COLOR);
         builder.begin_render_pass(
             framebuffer.clone(),
             SubpassContents::SecondaryCommandBuffers,
@@ -160,58 +372,114 @@ pub fn frame<F, I>(
             subpass_number: 0,
             before_future: Some(Box::new(before_future)),
             framebuffer,
+            post_passes,
+            post_input: None,
+            ui_framebuffer,
             command_buffer_builder: Some(builder),
+            timer_slot,
+            occlusion_query,
+            main_pass_label,
         })
     }
 }
 
+/// Framebuffer of post processing pass with the image which it samples.
+type PostPass = (
+    Arc<dyn FramebufferAbstract + Send + Sync>,
+    Arc<ImageView<Arc<AttachmentImage>>>,
+);
+
 /// Represents the active process of rendering a frame.
 pub struct Frame<'a> {
     /// The borrowed `FrameSystem`.
     system: &'a mut FrameSystem,
 
     /// The active pass we are in. This keeps track of the step we are in.
-    subpass_number: u8,
+    subpass_number: usize,
 
     /// Future to wait upon before the main rendering.
     before_future: Option<Box<dyn GpuFuture + Send + Sync>>,
 
-    /// Framebuffer that was used when starting the render pass.
+    /// Framebuffer that was used when starting the current render pass.
     framebuffer: Arc<dyn FramebufferAbstract + Send + Sync>,
 
+    /// Framebuffers of passes of post processing with ping-pong targets which they sample.
+    post_passes: Vec<PostPass>,
+
+    /// Index of the current pass of post processing with the target which it samples.
+    post_input: Option<(usize, Arc<ImageView<Arc<AttachmentImage>>>)>,
+
+    /// Framebuffer of UI render pass, if UI is not rendered in the render pass of the scene.
+    ui_framebuffer: Option<Arc<dyn FramebufferAbstract + Send + Sync>>,
+
     /// The command buffer builder that will be built during the lifetime of this object.
     command_buffer_builder: Option<AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>>,
+
+    /// Slot of GPU timer which execution time of the frame is measured in.
+    timer_slot: usize,
+
+    /// Occlusion query of the nominated draw of the frame.
+    occlusion_query: FrameQuery,
+
+    /// If debug label of the main pass was opened and must be closed.
+    main_pass_label: bool,
 }
 
 impl<'a> Frame<'a> {
+    /// Occlusion query which the nominated draw of the frame could be wrapped into,
+    /// its count of samples is reported in [frame statistics](crate::app::stats::FrameStats).
+    pub fn occlusion_query(&self) -> FrameQuery {
+        self.occlusion_query.clone()
+    }
+
     /// Returns an enumeration containing the next pass of the rendering.
     pub fn next_pass<'f>(&'f mut self) -> Result<Option<Pass<'f, 'a>>, NextPassError> {
-        match {
-            let current_pass = self.subpass_number;
-            self.subpass_number += 1;
-            current_pass
-        } {
+        let current_pass = self.subpass_number;
+        let post_effects = self.post_passes.len();
+        self.subpass_number += 1;
+        match current_pass {
             // If we are in the pass 0 then we haven't start anything yet.
             // We return an object that will allow the user to draw objects on the scene.
             0 => Ok(Some(Pass::Deferred(DrawPass { frame: self }))),
 
-            // If we are in the pass 1 then we have finished drawing the objects on the scene.
-            1 => {
-                self.command_buffer_builder
-                    .as_mut()
-                    .unwrap()
-                    .next_subpass(SubpassContents::SecondaryCommandBuffers)?;
+            // Previous pass rendered the scene or output of the previous effect,
+            // which is sampled by the next effect of the chain.
+            pass if pass <= post_effects => {
+                let (framebuffer, input) = self.post_passes[pass - 1].clone();
+                self.switch_render_pass(framebuffer)?;
+                self.post_input = Some((pass - 1, input));
+
+                // Returning an object that will allow the user to post process the scene.
+                Ok(Some(Pass::Post(DrawPass { frame: self })))
+            }
+
+            // If we are here then we have finished drawing (and processing) the scene.
+            pass if pass == post_effects + 1 => {
+                self.post_input = None;
+                match self.ui_framebuffer.take() {
+                    Some(framebuffer) => self.switch_render_pass(framebuffer)?,
+                    None => {
+                        self.command_buffer_builder
+                            .as_mut()
+                            .unwrap()
+                            .next_subpass(SubpassContents::SecondaryCommandBuffers)?;
+                    }
+                }
 
                 // Returning an object that will allow the user to render UI.
                 Ok(Some(Pass::UI(DrawPass { frame: self })))
             }
 
-            // If we are in pass 2 then we have finished rendering UI.
-            2 => {
-                self.command_buffer_builder
-                    .as_mut()
-                    .unwrap()
-                    .end_render_pass()?;
+            // If we are here then we have finished rendering UI.
+            pass if pass == post_effects + 2 => {
+                let builder = self.command_buffer_builder.as_mut().unwrap();
+                builder.end_render_pass()?;
+                if self.main_pass_label {
+                    debug::end_label(builder);
+                }
+                if let Some(gpu_timer) = self.system.gpu_timer.as_mut() {
+                    gpu_timer.end(builder, self.timer_slot)?;
+                }
                 let command_buffer = self.command_buffer_builder.take().unwrap().build()?;
 
                 // Extract `before_future` and append the command buffer execution to it.
@@ -229,6 +497,23 @@ pub fn next_pass<'f>(&'f mut self) -> Result<Option<Pass<'f, 'a>>, NextPassError
             _ => Ok(None),
         }
     }
+
+    /// Ends the current render pass and begins the one with provided framebuffer,
+    /// whose only attachment is either overwritten or drawn over, so it is never cleared.
+    fn switch_render_pass(
+        &mut self,
+        framebuffer: Arc<dyn FramebufferAbstract + Send + Sync>,
+    ) -> Result<(), NextPassError> {
+        let builder = self.command_buffer_builder.as_mut().unwrap();
+        builder.end_render_pass()?;
+        builder.begin_render_pass(
+            framebuffer.clone(),
+            SubpassContents::SecondaryCommandBuffers,
+            vec![ClearValue::None],
+        )?;
+        self.framebuffer = framebuffer;
+        Ok(())
+    }
 }
 
 /// Struct provided to the user that allows them to customize or handle the pass.
@@ -237,6 +522,10 @@ pub enum Pass<'f, 's: 'f> {
     /// The `DrawPass` allows the user to draw the objects.
     Deferred(DrawPass<'f, 's>),
 
+    /// We are in one of the passes where we process the scene by effect over the whole screen.
+    /// The `DrawPass` allows the user to sample the scene.
+    Post(DrawPass<'f, 's>),
+
     /// We are in the pass where we draw UI on the screen.
     /// The `DrawPass` allows the user to draw the UI.
     UI(DrawPass<'f, 's>),
@@ -265,6 +554,12 @@ pub fn execute<C>(&mut self, secondary_command_buffer: C) -> Result<(), DrawPass
         Ok(())
     }
 
+    /// Returns index of the effect of the current post processing pass
+    /// with the image which it samples, or `None` if this is not a post processing pass.
+    pub fn post_input(&self) -> Option<(usize, Arc<ImageView<Arc<AttachmentImage>>>)> {
+        self.frame.post_input.clone()
+    }
+
     /// Returns the dimensions in pixels of the viewport.
     pub fn viewport_size(&self) -> Size {
         let dimensions = self.frame.framebuffer.dimensions();
@@ -12,6 +12,7 @@
 use vulkano::image::view::ImageView;
 use vulkano::image::{ImageDimensions, ImageViewAbstract, ImmutableImage, MipmapsCount};
 use vulkano::pipeline::blend::{AttachmentBlend, BlendFactor};
+use vulkano::pipeline::cache::PipelineCache;
 use vulkano::pipeline::viewport::{Scissor, Viewport};
 use vulkano::pipeline::{GraphicsPipeline, PipelineBindPoint};
 use vulkano::render_pass::Subpass;
@@ -21,6 +22,7 @@
 use crate::{
     graphics::{
         frame::ui_draw::error::{UiDrawError, UiDrawSystemCreationError},
+        memory::{self, MemoryTracker, ResourceCategory},
         renderer::error::DescriptorSetCreationError,
         vertex::UiVertex,
     },
@@ -51,6 +53,9 @@ pub struct UiDrawSystem {
     /// Collection of descriptor sets for user textures to be drawn in UI.
     user_texture_descriptor_sets: SlotMap<DefaultKey, Arc<dyn DescriptorSet + Send + Sync>>,
 
+    /// Tracker of allocations of UI textures.
+    memory: Arc<MemoryTracker>,
+
     /// A sampler for textures used in UI rendering.
     sampler: Arc<Sampler>,
 }
@@ -60,6 +65,8 @@ impl UiDrawSystem {
     pub fn new(
         graphics_queue: Arc<Queue>,
         subpass: Subpass,
+        memory: Arc<MemoryTracker>,
+        pipeline_cache: Arc<PipelineCache>,
     ) -> Result<Self, UiDrawSystemCreationError> {
         // Check queue for graphics support.
         if !graphics_queue.family().supports_graphics() {
@@ -88,6 +95,7 @@ pub fn new(
                     .cull_mode_disabled()
                     .blend_collective(blend)
                     .render_pass(subpass)
+                    .build_with_cache(pipeline_cache)
                     .build(device.clone())?,
             )
         };
@@ -121,6 +129,7 @@ pub fn new(
             texture_version: 0,
             texture_descriptor_set: None,
             user_texture_descriptor_sets: SlotMap::default(),
+            memory,
         })
     }
 
@@ -185,13 +194,19 @@ pub fn draw(
                 let data: Vec<_> = texture.pixels.iter().flat_map(|&r| [r, r, r, r]).collect();
 
                 let (image, image_future) = ImmutableImage::from_iter(
-                    data.into_iter(),
+                    data,
                     dimensions,
                     MipmapsCount::One,
                     Format::R8G8B8A8_UNORM,
                     self.graphics_queue.clone(),
                 )?;
                 image_future.flush()?;
+                let size = memory::image_size(
+                    [texture.width as u32, texture.height as u32],
+                    Format::R8G8B8A8_UNORM,
+                );
+                self.memory
+                    .track(&image, "UI font", ResourceCategory::Texture, size);
                 image
             };
 
@@ -0,0 +1,221 @@
+//! Measurement of GPU execution time of frames with timestamp queries.
+//!
+//! Each slot (one per swapchain image) owns a pair of timestamp queries
+//! which are written at the start and at the end of the frame command buffer.
+//! Results are read without waiting right before the slot is reused,
+//! so the measurement never stalls the CPU: if results are not available yet,
+//! the measurement of that frame is skipped.
+//! Each slot remembers the frame which was measured in it, so the time is reported
+//! with the number and counters of that frame rather than of the frame which reads it.
+
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::Duration;
+
+use thiserror::Error;
+use vulkano::command_buffer::{AutoCommandBufferBuilder, ResetQueryPoolError, WriteTimestampError};
+use vulkano::device::physical::QueueFamily;
+use vulkano::device::Device;
+use vulkano::query::{QueryPool, QueryPoolCreationError, QueryResultFlags, QueryType};
+use vulkano::sync::PipelineStage;
+
+use crate::app::spike::FrameCounters;
+
+mod tests;
+
+/// Error that can happen when recording timestamp queries.
+#[derive(Debug, Error)]
+pub enum GpuTimerError {
+    #[error("failed to reset timestamp queries: {0}")]
+    Reset(#[from] ResetQueryPoolError),
+
+    #[error("failed to write timestamp: {0}")]
+    Write(#[from] WriteTimestampError),
+}
+
+/// GPU execution time of the frame which was measured in a slot of the timer.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GpuTime {
+    /// Number of the measured frame.
+    pub frame: u64,
+    /// Counters of the work which was done in the measured frame.
+    pub counters: FrameCounters,
+    /// Execution time of the measured frame.
+    pub duration: Duration,
+}
+
+/// State of queries of one slot of the timer.
+#[derive(Debug, Copy, Clone, Default)]
+struct TimerSlot {
+    /// If queries were written and not read yet.
+    pending: bool,
+    /// Number and counters of the frame which queries were written by.
+    frame: Option<(u64, FrameCounters)>,
+}
+
+impl TimerSlot {
+    /// Forgets the previous frame when queries are reset.
+    fn begin(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Marks queries as written by the current frame.
+    fn end(&mut self) {
+        self.pending = true;
+    }
+
+    /// Remembers number and counters of the frame which has written queries.
+    fn record(&mut self, frame: u64, counters: FrameCounters) {
+        if self.pending {
+            self.frame = Some((frame, counters));
+        }
+    }
+
+    /// Frame which has written queries, if they were not read yet.
+    fn frame(&self) -> Option<(u64, FrameCounters)> {
+        self.frame.filter(|_| self.pending)
+    }
+}
+
+/// Timer of GPU execution time of frames.
+pub struct GpuTimer {
+    pool: Arc<QueryPool>,
+    /// Count of nanoseconds per timestamp tick.
+    period: f32,
+    /// Count of meaningful bits of timestamps.
+    valid_bits: u32,
+    /// State of queries of each slot.
+    slots: Vec<TimerSlot>,
+}
+
+impl GpuTimer {
+    /// Creates new timer with provided count of slots.
+    ///
+    /// Returns `None` if provided queue family does not support timestamps.
+    ///
+    pub fn new(
+        device: Arc<Device>,
+        family: QueueFamily,
+        slots: usize,
+    ) -> Result<Option<Self>, QueryPoolCreationError> {
+        let valid_bits = match family.timestamp_valid_bits() {
+            Some(valid_bits) => valid_bits,
+            None => return Ok(None),
+        };
+        let period = device.physical_device().properties().timestamp_period;
+        let pool = QueryPool::new(device, QueryType::Timestamp, slots.max(1) as u32 * 2)?;
+        Ok(Some(Self {
+            pool: Arc::new(pool),
+            period,
+            valid_bits,
+            slots: vec![TimerSlot::default(); slots.max(1)],
+        }))
+    }
+
+    /// Queries of provided slot, slots wrap around if swapchain has more images than slots.
+    fn queries(&self, slot: usize) -> Range<u32> {
+        let start = (slot % self.slots.len()) as u32 * 2;
+        start..start + 2
+    }
+
+    /// Reads execution time of the frame which was measured in provided slot, if available.
+    ///
+    /// Never waits for the device.
+    ///
+    pub fn read(&mut self, slot: usize) -> Option<GpuTime> {
+        let slot = slot % self.slots.len();
+        let (frame, counters) = self.slots[slot].frame()?;
+        let mut timestamps = [0u64; 2];
+        let flags = QueryResultFlags {
+            wait: false,
+            with_availability: false,
+            partial: false,
+        };
+        let available = self
+            .pool
+            .queries_range(self.queries(slot))?
+            .get_results(&mut timestamps, flags)
+            .unwrap_or_else(|error| {
+                log::warn!("failed to read GPU timestamps: {}", error);
+                false
+            });
+        available.then(|| {
+            self.slots[slot].begin();
+            GpuTime {
+                frame,
+                counters,
+                duration: self::timestamp_delta(
+                    timestamps[0],
+                    timestamps[1],
+                    self.valid_bits,
+                    self.period,
+                ),
+            }
+        })
+    }
+
+    /// Remembers number and counters of the frame which was measured in provided slot,
+    /// so they are reported along with its time.
+    ///
+    /// Must be called after the frame is recorded, when all its counters are known.
+    ///
+    pub fn record(&mut self, slot: usize, frame: u64, counters: FrameCounters) {
+        let slot = slot % self.slots.len();
+        self.slots[slot].record(frame, counters);
+    }
+
+    /// Resets queries of provided slot and writes timestamp of the start of the frame.
+    ///
+    /// Must be called outside of render pass.
+    ///
+    pub fn begin<L, P>(
+        &mut self,
+        builder: &mut AutoCommandBufferBuilder<L, P>,
+        slot: usize,
+    ) -> Result<(), GpuTimerError> {
+        let queries = self.queries(slot);
+        // SAFETY: queries of the slot are not used by other commands of this command buffer,
+        // and the frame which has written them before was submitted earlier to the same queue.
+        unsafe {
+            builder.reset_query_pool(self.pool.clone(), queries.clone())?;
+            builder.write_timestamp(self.pool.clone(), queries.start, PipelineStage::TopOfPipe)?;
+        }
+        let slot = slot % self.slots.len();
+        self.slots[slot].begin();
+        Ok(())
+    }
+
+    /// Writes timestamp of the end of the frame.
+    pub fn end<L, P>(
+        &mut self,
+        builder: &mut AutoCommandBufferBuilder<L, P>,
+        slot: usize,
+    ) -> Result<(), GpuTimerError> {
+        let queries = self.queries(slot);
+        // SAFETY: query was reset by `begin` in this command buffer.
+        unsafe {
+            builder.write_timestamp(
+                self.pool.clone(),
+                queries.end - 1,
+                PipelineStage::BottomOfPipe,
+            )?;
+        }
+        let slot = slot % self.slots.len();
+        self.slots[slot].end();
+        Ok(())
+    }
+}
+
+/// Converts the difference of two timestamps into duration.
+///
+/// Only `valid_bits` lower bits of timestamps are meaningful, so the difference wraps around them.
+/// `period` is count of nanoseconds per timestamp tick.
+///
+pub fn timestamp_delta(start: u64, end: u64, valid_bits: u32, period: f32) -> Duration {
+    let mask = match valid_bits {
+        64.. => u64::MAX,
+        bits => (1 << bits) - 1,
+    };
+    let ticks = end.wrapping_sub(start) & mask;
+    Duration::from_nanos((ticks as f64 * period as f64) as u64)
+}
@@ -0,0 +1,59 @@
+#![cfg(test)]
+
+use std::time::Duration;
+
+use crate::app::spike::FrameCounters;
+
+use super::{timestamp_delta, TimerSlot};
+
+#[test]
+fn timestamp_delta_respects_period() {
+    assert_eq!(
+        timestamp_delta(100, 600, 64, 1.0),
+        Duration::from_nanos(500)
+    );
+    assert_eq!(
+        timestamp_delta(0, 1000, 64, 2.5),
+        Duration::from_nanos(2500)
+    );
+}
+
+#[test]
+fn timestamp_delta_wraps_around_valid_bits() {
+    let max = (1u64 << 36) - 1;
+    assert_eq!(
+        timestamp_delta(max - 9, 10, 36, 1.0),
+        Duration::from_nanos(20)
+    );
+    assert_eq!(
+        timestamp_delta(u64::MAX, 4, 64, 1.0),
+        Duration::from_nanos(5)
+    );
+}
+
+#[test]
+fn slot_keeps_frame_which_was_measured() {
+    let counters = FrameCounters {
+        draw_calls: 7,
+        ..FrameCounters::default()
+    };
+    let mut slot = TimerSlot::default();
+    slot.begin();
+    slot.end();
+    slot.record(3, counters);
+    // Frames 4 and 5 are rendered into other images before this slot is read.
+    assert_eq!(slot.frame(), Some((3, counters)));
+
+    // Queries are reset by the next frame of the slot before it is recorded.
+    slot.begin();
+    assert_eq!(slot.frame(), None);
+    slot.end();
+    assert_eq!(slot.frame(), None);
+}
+
+#[test]
+fn unwritten_slot_is_not_recorded() {
+    let mut slot = TimerSlot::default();
+    slot.record(1, FrameCounters::default());
+    assert_eq!(slot.frame(), None);
+}
@@ -0,0 +1,166 @@
+//! Hot reloading of shaders of game objects during development.
+//!
+//! Watched shader files are polled for modification between frames,
+//! and shaders of modified files are loaded again and swapped into object pipeline.
+//! Available with `hot-reload` feature only.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use thiserror::Error;
+
+use crate::asset::error::AssetError;
+use crate::graphics::spirv::ShaderStage;
+
+mod tests;
+
+/// Default interval between two polls of watched files.
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Error that can happen when enabling hot reloading of shaders.
+#[derive(Debug, Error)]
+pub enum HotReloadError {
+    #[error("stage of shader \"{0}\" cannot be inferred from its extension")]
+    UnknownStage(PathBuf),
+
+    #[error("GLSL shader \"{0}\" cannot be reloaded without `glsl` feature")]
+    GlslDisabled(PathBuf),
+
+    #[error("shader asset resolution failure: {0}")]
+    Asset(#[from] AssetError),
+}
+
+/// Format of watched shader file.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ShaderFormat {
+    /// File contains SPIR-V module.
+    Spirv,
+    /// File contains GLSL source.
+    Glsl,
+}
+
+/// Infers stage and format of shader file from its extension.
+///
+/// Files like `object.vert` contain GLSL source,
+/// and files like `object.vert.spv` contain SPIR-V module compiled from it.
+///
+pub fn shader_file_kind(path: &Path) -> Option<(ShaderStage, ShaderFormat)> {
+    let (path, format) = match path.extension()?.to_str()? {
+        "spv" => (Path::new(path.file_stem()?), ShaderFormat::Spirv),
+        _ => (path, ShaderFormat::Glsl),
+    };
+    let stage = match path.extension()?.to_str()? {
+        "vert" => ShaderStage::Vertex,
+        "frag" => ShaderStage::Fragment,
+        _ => return None,
+    };
+    Some((stage, format))
+}
+
+/// Checks if file was modified, given its previous and current modification times.
+///
+/// Missing file is not considered modified, because editors may remove file before saving it.
+///
+pub fn is_modified(previous: Option<SystemTime>, current: Option<SystemTime>) -> bool {
+    current.is_some() && current != previous
+}
+
+/// Shader file which was modified since the previous poll.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ModifiedShader {
+    /// Path of the shader file.
+    pub path: PathBuf,
+    /// Stage of the shader.
+    pub stage: ShaderStage,
+    /// Format of the shader file.
+    pub format: ShaderFormat,
+}
+
+/// Watched shader file with its last known modification time.
+struct WatchedShader {
+    shader: ModifiedShader,
+    modified: Option<SystemTime>,
+}
+
+/// Watcher of shader files which polls them for modification.
+pub struct ShaderWatcher {
+    shaders: Vec<WatchedShader>,
+    interval: Duration,
+    last_poll: Option<Instant>,
+}
+
+impl ShaderWatcher {
+    /// Creates watcher of provided shader files.
+    ///
+    /// Files which exist now are not reported until they are modified.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if stage or format of any file cannot be inferred
+    /// from its extension, or if its format is not supported.
+    ///
+    pub fn new(paths: &[PathBuf]) -> Result<Self, HotReloadError> {
+        let shaders = paths
+            .iter()
+            .map(|path| {
+                let (stage, format) = self::shader_file_kind(path)
+                    .ok_or_else(|| HotReloadError::UnknownStage(path.clone()))?;
+                if format == ShaderFormat::Glsl && cfg!(not(feature = "glsl")) {
+                    return Err(HotReloadError::GlslDisabled(path.clone()));
+                }
+                let shader = ModifiedShader {
+                    path: path.clone(),
+                    stage,
+                    format,
+                };
+                let modified = self::modified(path);
+                Ok(WatchedShader { shader, modified })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            shaders,
+            interval: DEFAULT_POLL_INTERVAL,
+            last_poll: None,
+        })
+    }
+
+    /// Sets interval between two polls of watched files.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Polls watched files for modification, unless they were polled less than
+    /// [interval](Self::with_interval) ago.
+    ///
+    /// Returns shader files which were modified since the previous poll.
+    ///
+    pub fn poll(&mut self, now: Instant) -> Vec<ModifiedShader> {
+        if let Some(last_poll) = self.last_poll {
+            if now.saturating_duration_since(last_poll) < self.interval {
+                return Vec::new();
+            }
+        }
+        self.last_poll = Some(now);
+
+        self.shaders
+            .iter_mut()
+            .filter_map(|watched| {
+                let current = self::modified(&watched.shader.path);
+                if !self::is_modified(watched.modified, current) {
+                    return None;
+                }
+                watched.modified = current;
+                Some(watched.shader.clone())
+            })
+            .collect()
+    }
+}
+
+/// Modification time of the file, if it exists.
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
@@ -0,0 +1,86 @@
+#![cfg(test)]
+
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant, SystemTime};
+
+use super::{is_modified, shader_file_kind, ShaderFormat, ShaderWatcher};
+use crate::graphics::spirv::ShaderStage;
+
+/// Temporary file which is removed on drop.
+struct TempFile(PathBuf);
+
+impl TempFile {
+    fn new(name: &str) -> Self {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let id = COUNTER.fetch_add(1, Ordering::SeqCst);
+        let name = format!("titan_hot_reload_{}_{}_{}", process::id(), id, name);
+        let path = std::env::temp_dir().join(name);
+        fs::write(&path, [0u8; 4]).unwrap();
+        Self(path)
+    }
+
+    fn touch(&self, modified: SystemTime) {
+        let file = File::options().write(true).open(&self.0).unwrap();
+        file.set_modified(modified).unwrap();
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+#[test]
+fn kind_is_inferred_from_extension() {
+    let kind = |path: &str| shader_file_kind(Path::new(path));
+    assert_eq!(
+        kind("shaders/object.vert.spv"),
+        Some((ShaderStage::Vertex, ShaderFormat::Spirv)),
+    );
+    assert_eq!(
+        kind("object.frag"),
+        Some((ShaderStage::Fragment, ShaderFormat::Glsl)),
+    );
+    assert_eq!(kind("object.spv"), None);
+    assert_eq!(kind("object.comp"), None);
+    assert_eq!(kind("object"), None);
+}
+
+#[test]
+fn missing_file_is_not_modified() {
+    let now = SystemTime::now();
+    let later = now + Duration::from_secs(1);
+    assert!(!is_modified(Some(now), None));
+    assert!(!is_modified(Some(now), Some(now)));
+    assert!(is_modified(Some(now), Some(later)));
+    assert!(is_modified(None, Some(now)));
+}
+
+#[test]
+fn watcher_reports_modified_files() {
+    let file = TempFile::new("object.frag.spv");
+    let mut watcher = ShaderWatcher::new(std::slice::from_ref(&file.0))
+        .unwrap()
+        .with_interval(Duration::from_secs(1));
+    let start = Instant::now();
+    assert!(watcher.poll(start).is_empty());
+
+    file.touch(SystemTime::now() + Duration::from_secs(10));
+    // Files are not polled again until interval has passed.
+    assert!(watcher.poll(start + Duration::from_millis(500)).is_empty());
+
+    let modified = watcher.poll(start + Duration::from_secs(1));
+    assert_eq!(modified.len(), 1);
+    assert_eq!(modified[0].path, file.0);
+    assert_eq!(modified[0].stage, ShaderStage::Fragment);
+    assert!(watcher.poll(start + Duration::from_secs(2)).is_empty());
+}
+
+#[test]
+fn watcher_rejects_unknown_stage() {
+    assert!(ShaderWatcher::new(&[PathBuf::from("object.spv")]).is_err());
+}
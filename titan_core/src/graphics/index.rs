@@ -0,0 +1,156 @@
+//! Index utilities for game engine.
+
+use std::ops::Range;
+use std::sync::Arc;
+
+use thiserror::Error;
+use vulkano::buffer::{BufferAccess, BufferUsage, DeviceLocalBuffer, TypedBufferAccess};
+use vulkano::command_buffer::AutoCommandBufferBuilder;
+
+use crate::graphics::memory::{MemoryTracker, ResourceCategory};
+use crate::graphics::upload::{StagingUploader, UploadError};
+
+mod tests;
+
+/// Indices of mesh vertices with one of index types supported by the engine.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Indices {
+    /// Indices of 16 bits, suitable for meshes with at most 65536 vertices.
+    U16(Vec<u16>),
+    /// Indices of 32 bits.
+    U32(Vec<u32>),
+}
+
+impl Indices {
+    /// Count of indices.
+    pub fn len(&self) -> usize {
+        match self {
+            Self::U16(indices) => indices.len(),
+            Self::U32(indices) => indices.len(),
+        }
+    }
+
+    /// Checks if there are no indices.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterates over all indices converted to 32 bits.
+    pub fn iter(&self) -> impl Iterator<Item = u32> + '_ {
+        let (short, long) = match self {
+            Self::U16(indices) => (indices.as_slice(), [].as_slice()),
+            Self::U32(indices) => ([].as_slice(), indices.as_slice()),
+        };
+        short
+            .iter()
+            .map(|&index| index as u32)
+            .chain(long.iter().copied())
+    }
+
+    /// Maximal value of the index type, which restarts strips of primitives
+    /// if the pipeline enables primitive restart.
+    pub fn restart_index(&self) -> u32 {
+        match self {
+            Self::U16(_) => u16::MAX as u32,
+            Self::U32(_) => u32::MAX,
+        }
+    }
+
+    /// Checks if some indices are [restart indices](Self::restart_index).
+    pub fn contains_restart(&self) -> bool {
+        let restart = self.restart_index();
+        self.iter().any(|index| index == restart)
+    }
+
+    /// Finds the first index which is out of bounds for provided count of vertices.
+    ///
+    /// [Restart indices](Self::restart_index) are not checked, so meshes with them
+    /// should be drawn only with pipelines which enable primitive restart.
+    ///
+    pub fn find_out_of_bounds(&self, vertex_count: usize) -> Option<u32> {
+        let restart = self.restart_index();
+        self.iter()
+            .find(|&index| index != restart && index as usize >= vertex_count)
+    }
+}
+
+impl From<Vec<u16>> for Indices {
+    fn from(indices: Vec<u16>) -> Self {
+        Self::U16(indices)
+    }
+}
+
+impl From<Vec<u32>> for Indices {
+    fn from(indices: Vec<u32>) -> Self {
+        Self::U32(indices)
+    }
+}
+
+/// Error that can happen when range of indices does not match the index buffer.
+#[derive(Debug, Error, Eq, PartialEq)]
+#[error("range of indices {range:?} does not match index buffer of {len} indices")]
+pub struct IndexRangeError {
+    pub range: Range<u32>,
+    pub len: u32,
+}
+
+/// Checks that range of indices to draw is not empty and lies inside of index buffer.
+pub fn check_index_range(range: &Range<u32>, len: u32) -> Result<(), IndexRangeError> {
+    if range.start >= range.end || range.end > len {
+        return Err(IndexRangeError {
+            range: range.clone(),
+            len,
+        });
+    }
+    Ok(())
+}
+
+/// Index buffer with one of index types supported by the engine.
+#[derive(Clone)]
+pub enum IndexBuffer {
+    U16(Arc<DeviceLocalBuffer<[u16]>>),
+    U32(Arc<DeviceLocalBuffer<[u32]>>),
+}
+
+impl IndexBuffer {
+    /// Uploads provided indices into new device local index buffer.
+    pub fn from_indices(
+        indices: &Indices,
+        uploader: &StagingUploader,
+    ) -> Result<Self, UploadError> {
+        let usage = BufferUsage::index_buffer();
+        Ok(match indices {
+            Indices::U16(indices) => Self::U16(uploader.upload(indices, usage)?),
+            Indices::U32(indices) => Self::U32(uploader.upload(indices, usage)?),
+        })
+    }
+
+    /// Count of indices in this buffer.
+    pub fn len(&self) -> u32 {
+        let len = match self {
+            Self::U16(buffer) => buffer.len(),
+            Self::U32(buffer) => buffer.len(),
+        };
+        len as u32
+    }
+
+    /// Starts tracking of the allocation of this buffer.
+    pub fn track(&self, memory: &MemoryTracker, name: &str) {
+        match self {
+            Self::U16(buffer) => {
+                memory.track(buffer, name, ResourceCategory::IndexBuffer, buffer.size())
+            }
+            Self::U32(buffer) => {
+                memory.track(buffer, name, ResourceCategory::IndexBuffer, buffer.size())
+            }
+        }
+    }
+
+    /// Binds this buffer as index buffer of the command buffer.
+    pub fn bind<L>(&self, builder: &mut AutoCommandBufferBuilder<L>) {
+        match self {
+            Self::U16(buffer) => builder.bind_index_buffer(buffer.clone()),
+            Self::U32(buffer) => builder.bind_index_buffer(buffer.clone()),
+        };
+    }
+}
@@ -0,0 +1,49 @@
+#![cfg(test)]
+
+use super::{check_index_range, IndexRangeError, Indices};
+
+#[test]
+fn indices_are_iterated_as_u32() {
+    let short = Indices::from(vec![0u16, 1, 2]);
+    let long = Indices::from(vec![0u32, 1, 2]);
+    assert_eq!(short.len(), 3);
+    assert_eq!(short.iter().collect::<Vec<_>>(), [0, 1, 2]);
+    assert_eq!(
+        short.iter().collect::<Vec<_>>(),
+        long.iter().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn out_of_bounds_index_is_found() {
+    let indices = Indices::from(vec![0u16, 1, 2, 2, 3, 0]);
+    assert_eq!(indices.find_out_of_bounds(4), None);
+    assert_eq!(indices.find_out_of_bounds(3), Some(3));
+    assert!(Indices::U32(Vec::new()).is_empty());
+}
+
+#[test]
+fn index_range_must_fit_buffer() {
+    assert_eq!(check_index_range(&(0..6), 6), Ok(()));
+    assert_eq!(check_index_range(&(6..12), 12), Ok(()));
+    assert_eq!(
+        check_index_range(&(0..6), 4),
+        Err(IndexRangeError {
+            range: 0..6,
+            len: 4
+        }),
+    );
+    assert!(check_index_range(&(3..3), 6).is_err());
+}
+
+#[test]
+fn restart_indices_are_not_out_of_bounds() {
+    let indices = Indices::from(vec![0u16, 1, 2, u16::MAX, 2, 3, 0]);
+    assert_eq!(indices.restart_index(), u16::MAX as u32);
+    assert!(indices.contains_restart());
+    assert_eq!(indices.find_out_of_bounds(4), None);
+
+    let indices = Indices::from(vec![0u32, u16::MAX as u32]);
+    assert!(!indices.contains_restart());
+    assert_eq!(indices.find_out_of_bounds(4), Some(u16::MAX as u32));
+}
@@ -0,0 +1,213 @@
+//! Indirect draws, whose parameters are read by the device from the buffer.
+//!
+//! Buffer of commands could be filled on the host or written by compute shader,
+//! so the count of vertices or instances could be decided on the device
+//! (for example, after culling) without reading it back.
+//! Such buffer should be created with [buffer usage](indirect_buffer_usage) of this module.
+//!
+//! Vulkano tracks accesses to the buffer and inserts a barrier between
+//! writes of compute dispatch and reads of indirect draws recorded into the same command buffer.
+//! Dispatches submitted [separately](crate::graphics::submit) must be waited for
+//! with the future of their submission before the frame samples the buffer.
+//!
+//! Several commands are drawn by one indirect draw only if `multiDrawIndirect` feature
+//! is [enabled](crate::graphics::adapter::DeviceFeatures) on the device,
+//! otherwise each command is drawn by separate indirect draw with the same result.
+
+use std::mem;
+use std::ops::Range;
+use std::sync::Arc;
+
+use thiserror::Error;
+use vulkano::buffer::{BufferSlice, BufferUsage, TypedBufferAccess};
+use vulkano::command_buffer::{
+    AutoCommandBufferBuilder, DrawIndexedIndirectError, DrawIndirectError,
+};
+use vulkano::device::{Device, DeviceOwned};
+use vulkano::DeviceSize;
+
+pub use vulkano::command_buffer::{DrawIndexedIndirectCommand, DrawIndirectCommand};
+
+mod tests;
+
+/// Error that can happen when recording indirect draws into the command buffer.
+#[derive(Debug, Error)]
+pub enum IndirectDrawError {
+    #[error("buffer of indirect commands must be created with indirect buffer usage")]
+    MissingUsage,
+
+    #[error("stride of indirect commands must be {expected} bytes, but {stride} were provided")]
+    UnsupportedStride { stride: u32, expected: u32 },
+
+    #[error("commands {offset}..{end} are out of range of the buffer of {len} commands")]
+    OutOfRange {
+        offset: DeviceSize,
+        end: DeviceSize,
+        len: DeviceSize,
+    },
+
+    #[error("indirect draw failed: {0}")]
+    Draw(#[from] DrawIndirectError),
+
+    #[error("indexed indirect draw failed: {0}")]
+    DrawIndexed(#[from] DrawIndexedIndirectError),
+}
+
+/// Command of indirect draw which is read by the device from the buffer.
+///
+/// # Safety
+///
+/// Type must be `#[repr(C)]` plain old data which has exactly the layout
+/// of the command expected by Vulkan, without any padding.
+///
+pub unsafe trait IndirectCommand: Copy + Send + Sync + 'static {
+    /// Size of one command in the buffer, which is the only stride supported by vulkano.
+    const STRIDE: u32 = mem::size_of::<Self>() as u32;
+}
+
+// SAFETY: both are `#[repr(C)]` structures of `u32` fields declared by Vulkan.
+unsafe impl IndirectCommand for DrawIndirectCommand {}
+unsafe impl IndirectCommand for DrawIndexedIndirectCommand {}
+
+/// Reinterprets commands as bytes in the layout which is read by the device.
+pub fn command_bytes<C>(commands: &[C]) -> &[u8]
+where
+    C: IndirectCommand,
+{
+    let len = mem::size_of_val(commands);
+    // SAFETY: commands are plain old data without padding by the contract of the trait.
+    unsafe { std::slice::from_raw_parts(commands.as_ptr().cast(), len) }
+}
+
+/// Usage of buffer of indirect commands, which could also be written by compute shaders
+/// or copied into from the host.
+pub const fn indirect_buffer_usage() -> BufferUsage {
+    BufferUsage {
+        transfer_destination: true,
+        storage_buffer: true,
+        indirect_buffer: true,
+        ..BufferUsage::none()
+    }
+}
+
+/// Count of commands which could be drawn by one indirect draw on provided device.
+///
+/// It is `maxDrawIndirectCount` limit if `multiDrawIndirect` feature is enabled, otherwise 1.
+///
+pub fn max_draws_per_command(device: &Device) -> u32 {
+    if device.enabled_features().multi_draw_indirect {
+        device
+            .physical_device()
+            .properties()
+            .max_draw_indirect_count
+            .max(1)
+    } else {
+        1
+    }
+}
+
+/// Checks that `draw_count` commands of type `C` starting from command `offset`
+/// with provided stride in bytes could be read from the buffer of `len` commands.
+pub fn check_indirect_draw<C>(
+    len: DeviceSize,
+    offset: DeviceSize,
+    draw_count: u32,
+    stride: u32,
+) -> Result<(), IndirectDrawError>
+where
+    C: IndirectCommand,
+{
+    if stride != C::STRIDE {
+        return Err(IndirectDrawError::UnsupportedStride {
+            stride,
+            expected: C::STRIDE,
+        });
+    }
+    let end = offset.saturating_add(draw_count.into());
+    if end > len {
+        return Err(IndirectDrawError::OutOfRange { offset, end, len });
+    }
+    Ok(())
+}
+
+/// Ranges of commands which are drawn by separate indirect draws
+/// if each of them could draw at most `max_per_draw` commands.
+pub fn indirect_batches(
+    offset: DeviceSize,
+    draw_count: u32,
+    max_per_draw: u32,
+) -> impl Iterator<Item = Range<DeviceSize>> {
+    let end = offset + DeviceSize::from(draw_count);
+    let step = DeviceSize::from(max_per_draw.max(1));
+    (offset..end)
+        .step_by(step as usize)
+        .map(move |start| start..(start + step).min(end))
+}
+
+/// Records draws of non-indexed commands of the buffer starting from command `offset`.
+///
+/// Pipeline, descriptor sets and vertex buffers must be bound as for the ordinary draw.
+/// Commands are split between several indirect draws if the device
+/// cannot draw them [at once](max_draws_per_command).
+///
+pub fn draw_indirect<L, B>(
+    builder: &mut AutoCommandBufferBuilder<L>,
+    buffer: Arc<B>,
+    offset: DeviceSize,
+    draw_count: u32,
+    stride: u32,
+) -> Result<(), IndirectDrawError>
+where
+    B: TypedBufferAccess<Content = [DrawIndirectCommand]> + Send + Sync + 'static,
+{
+    for range in self::batches(builder.device(), &buffer, offset, draw_count, stride)? {
+        let slice = BufferSlice::from_typed_buffer_access(buffer.clone())
+            .slice(range)
+            .expect("range of commands was checked");
+        builder.draw_indirect(slice)?;
+    }
+    Ok(())
+}
+
+/// Records draws of indexed commands of the buffer starting from command `offset`.
+///
+/// Index buffer must be bound in addition to the state of [non-indexed](draw_indirect) draws.
+///
+pub fn draw_indexed_indirect<L, B>(
+    builder: &mut AutoCommandBufferBuilder<L>,
+    buffer: Arc<B>,
+    offset: DeviceSize,
+    draw_count: u32,
+    stride: u32,
+) -> Result<(), IndirectDrawError>
+where
+    B: TypedBufferAccess<Content = [DrawIndexedIndirectCommand]> + Send + Sync + 'static,
+{
+    for range in self::batches(builder.device(), &buffer, offset, draw_count, stride)? {
+        let slice = BufferSlice::from_typed_buffer_access(buffer.clone())
+            .slice(range)
+            .expect("range of commands was checked");
+        builder.draw_indexed_indirect(slice)?;
+    }
+    Ok(())
+}
+
+/// Validates the buffer and splits its commands between indirect draws supported by the device.
+fn batches<C, B>(
+    device: &Device,
+    buffer: &B,
+    offset: DeviceSize,
+    draw_count: u32,
+    stride: u32,
+) -> Result<impl Iterator<Item = Range<DeviceSize>>, IndirectDrawError>
+where
+    C: IndirectCommand,
+    B: TypedBufferAccess<Content = [C]>,
+{
+    if !buffer.inner().buffer.usage().indirect_buffer {
+        return Err(IndirectDrawError::MissingUsage);
+    }
+    self::check_indirect_draw::<C>(buffer.len(), offset, draw_count, stride)?;
+    let max_per_draw = self::max_draws_per_command(device);
+    Ok(self::indirect_batches(offset, draw_count, max_per_draw))
+}
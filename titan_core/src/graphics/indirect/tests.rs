@@ -0,0 +1,81 @@
+#![cfg(test)]
+
+use super::{
+    check_indirect_draw, command_bytes, indirect_batches, DrawIndexedIndirectCommand,
+    DrawIndirectCommand, IndirectCommand, IndirectDrawError,
+};
+
+#[test]
+fn strides_are_sizes_of_vulkan_commands() {
+    assert_eq!(DrawIndirectCommand::STRIDE, 16);
+    assert_eq!(DrawIndexedIndirectCommand::STRIDE, 20);
+}
+
+#[test]
+fn command_bytes_keep_field_order() {
+    let commands = [DrawIndirectCommand {
+        vertex_count: 3,
+        instance_count: 2,
+        first_vertex: 1,
+        first_instance: 0,
+    }];
+    let bytes = command_bytes(&commands);
+    assert_eq!(bytes.len(), 16);
+    let words: Vec<_> = bytes
+        .chunks_exact(4)
+        .map(|word| u32::from_ne_bytes(word.try_into().unwrap()))
+        .collect();
+    assert_eq!(words, [3, 2, 1, 0]);
+}
+
+#[test]
+fn draw_inside_of_buffer_is_accepted() {
+    assert!(check_indirect_draw::<DrawIndirectCommand>(4, 0, 4, 16).is_ok());
+    assert!(check_indirect_draw::<DrawIndirectCommand>(4, 3, 1, 16).is_ok());
+    assert!(check_indirect_draw::<DrawIndexedIndirectCommand>(4, 4, 0, 20).is_ok());
+}
+
+#[test]
+fn draw_outside_of_buffer_is_rejected() {
+    let result = check_indirect_draw::<DrawIndirectCommand>(4, 2, 3, 16);
+    assert!(matches!(
+        result,
+        Err(IndirectDrawError::OutOfRange {
+            offset: 2,
+            end: 5,
+            len: 4
+        })
+    ));
+}
+
+#[test]
+fn stride_other_than_command_size_is_rejected() {
+    let result = check_indirect_draw::<DrawIndexedIndirectCommand>(4, 0, 1, 16);
+    assert!(matches!(
+        result,
+        Err(IndirectDrawError::UnsupportedStride {
+            stride: 16,
+            expected: 20
+        })
+    ));
+}
+
+#[test]
+fn batches_are_single_draws_without_multi_draw() {
+    let batches: Vec<_> = indirect_batches(2, 3, 1).collect();
+    assert_eq!(batches, [2..3, 3..4, 4..5]);
+}
+
+#[test]
+fn batches_are_split_by_draw_count_limit() {
+    let batches: Vec<_> = indirect_batches(0, 5, 2).collect();
+    assert_eq!(batches, [0..2, 2..4, 4..5]);
+    let mut batches = indirect_batches(1, 5, 16);
+    assert_eq!(batches.next(), Some(1..6));
+    assert_eq!(batches.next(), None);
+}
+
+#[test]
+fn empty_draw_has_no_batches() {
+    assert_eq!(indirect_batches(3, 0, 1).count(), 0);
+}
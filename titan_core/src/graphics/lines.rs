@@ -0,0 +1,38 @@
+//! Geometry of debug lines, such as world-space grid, for game objects.
+//!
+//! Lines are drawn as usual mesh of game objects with the [pipeline](line_pipeline)
+//! of [`Topology::LineList`], so each pair of indices is a separate line.
+
+use crate::graphics::{
+    color::Color,
+    index::Indices,
+    pipeline::{CullMode, GraphicsPipelineDescriptor, Topology},
+    vertex::Vertex,
+};
+
+mod tests;
+
+/// Descriptor of the pipeline which draws mesh of debug lines.
+pub const fn line_pipeline() -> GraphicsPipelineDescriptor {
+    GraphicsPipelineDescriptor::new()
+        .with_topology(Topology::LineList)
+        .with_cull_mode(CullMode::None)
+}
+
+/// Creates mesh of square grid on the XY plane centered at the origin,
+/// with provided count of cells along each axis and distance between lines.
+pub fn grid(cells: u16, spacing: f32, color: Color) -> (Vec<Vertex>, Indices) {
+    let color = color.to_srgb();
+    let half_extent = cells as f32 * spacing / 2.0;
+    let lines = cells as usize + 1;
+    let mut vertices = Vec::with_capacity(lines * 4);
+    for line in 0..lines {
+        let offset = line as f32 * spacing - half_extent;
+        vertices.push(Vertex::from_raw([offset, -half_extent, 0.0], color));
+        vertices.push(Vertex::from_raw([offset, half_extent, 0.0], color));
+        vertices.push(Vertex::from_raw([-half_extent, offset, 0.0], color));
+        vertices.push(Vertex::from_raw([half_extent, offset, 0.0], color));
+    }
+    let indices = (0..vertices.len() as u32).collect();
+    (vertices, Indices::U32(indices))
+}
@@ -0,0 +1,35 @@
+#![cfg(test)]
+
+use ultraviolet::Vec3;
+
+use crate::graphics::color::Color;
+use crate::graphics::pipeline::Topology;
+
+use super::{grid, line_pipeline};
+
+#[test]
+fn grid_has_pair_of_indices_per_line() {
+    let (vertices, indices) = grid(4, 0.5, Color::WHITE);
+    // Five lines along each of two axes.
+    assert_eq!(vertices.len(), 20);
+    assert_eq!(indices.len(), 20);
+    assert_eq!(indices.find_out_of_bounds(vertices.len()), None);
+    assert!(!indices.contains_restart());
+}
+
+#[test]
+fn grid_is_centered_at_origin() {
+    let (vertices, _) = grid(2, 1.0, Color::WHITE);
+    let positions: Vec<_> = vertices.iter().map(|vertex| *vertex.position).collect();
+    assert_eq!(positions[0], Vec3::new(-1.0, -1.0, 0.0));
+    assert_eq!(positions[1], Vec3::new(-1.0, 1.0, 0.0));
+    assert_eq!(positions[6], Vec3::new(-1.0, 0.0, 0.0));
+    assert_eq!(positions[11], Vec3::new(1.0, 1.0, 0.0));
+}
+
+#[test]
+fn lines_are_drawn_as_list() {
+    let descriptor = line_pipeline();
+    assert_eq!(descriptor.topology(), Topology::LineList);
+    assert!(!descriptor.primitive_restart());
+}
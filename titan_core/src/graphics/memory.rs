@@ -0,0 +1,480 @@
+//! GPU memory usage utilities for debugging of game engine.
+//!
+//! Vulkano does not expose blocks of its memory pool nor placement of allocations inside them,
+//! so the tracker places allocations of engine resources into its own blocks of each heap
+//! the same way as the memory pool of vulkano does: first fit into blocks of at least
+//! [`MIN_BLOCK_SIZE`] bytes, which are never freed. Offsets of the [memory map](HeapMap)
+//! are offsets of this placement, computed from estimated sizes of the resources.
+
+use std::any::Any;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, Weak};
+use std::time::{Duration, Instant};
+
+use thiserror::Error;
+use vulkano::device::physical::{MemoryType, PhysicalDevice};
+use vulkano::format::Format;
+use vulkano::DeviceSize;
+
+mod tests;
+
+/// Minimal size of memory block, the same as of the memory pool of vulkano.
+pub const MIN_BLOCK_SIZE: DeviceSize = 8 * 1024 * 1024;
+
+/// Minimal interval between two snapshots of memory map.
+const SNAPSHOT_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Properties of memory type which are required for the resource.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct MemoryProperties {
+    pub device_local: bool,
+    pub host_visible: bool,
+    pub host_coherent: bool,
+}
+
+impl MemoryProperties {
+    /// Properties of memory for staging resources which are written by the host.
+    pub const STAGING: Self = Self {
+        device_local: false,
+        host_visible: true,
+        host_coherent: true,
+    };
+
+    /// Properties of memory for resources which are accessed only by the device.
+    pub const GPU_ONLY: Self = Self {
+        device_local: true,
+        host_visible: false,
+        host_coherent: false,
+    };
+
+    /// Properties of provided memory type.
+    pub fn of(memory_type: &MemoryType) -> Self {
+        Self {
+            device_local: memory_type.is_device_local(),
+            host_visible: memory_type.is_host_visible(),
+            host_coherent: memory_type.is_host_coherent(),
+        }
+    }
+
+    /// Checks if all required properties are contained in these properties.
+    pub fn contains(&self, required: &Self) -> bool {
+        (self.device_local || !required.device_local)
+            && (self.host_visible || !required.host_visible)
+            && (self.host_coherent || !required.host_coherent)
+    }
+}
+
+impl fmt::Display for MemoryProperties {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let flags: Vec<_> = [
+            (self.device_local, "DEVICE_LOCAL"),
+            (self.host_visible, "HOST_VISIBLE"),
+            (self.host_coherent, "HOST_COHERENT"),
+        ]
+        .into_iter()
+        .filter_map(|(set, name)| set.then_some(name))
+        .collect();
+        if flags.is_empty() {
+            return write!(f, "NONE");
+        }
+        write!(f, "{}", flags.join(" | "))
+    }
+}
+
+/// Error that can happen when searching for memory type.
+#[derive(Debug, Error)]
+pub enum MemoryTypeError {
+    #[error("no suitable memory type with properties {required} among types {type_bits:#b}")]
+    NotFound {
+        required: MemoryProperties,
+        type_bits: u32,
+    },
+}
+
+/// Selects index of the first memory type which is allowed by `type_bits`
+/// and has all required properties.
+pub fn select_memory_type(
+    types: &[MemoryProperties],
+    type_bits: u32,
+    required: MemoryProperties,
+) -> Result<u32, MemoryTypeError> {
+    types
+        .iter()
+        .enumerate()
+        .find(|&(index, properties)| {
+            let allowed = index < 32 && type_bits & (1 << index) != 0;
+            allowed && properties.contains(&required)
+        })
+        .map(|(index, _)| index as u32)
+        .ok_or(MemoryTypeError::NotFound {
+            required,
+            type_bits,
+        })
+}
+
+/// Finds memory type of physical device which is allowed by `type_bits`
+/// and has all required properties.
+pub fn find_memory_type<'a>(
+    physical_device: PhysicalDevice<'a>,
+    type_bits: u32,
+    required: MemoryProperties,
+) -> Result<MemoryType<'a>, MemoryTypeError> {
+    let types: Vec<_> = physical_device
+        .memory_types()
+        .map(|memory_type| MemoryProperties::of(&memory_type))
+        .collect();
+    let id = self::select_memory_type(&types, type_bits, required)?;
+    Ok(physical_device.memory_type_by_id(id).unwrap())
+}
+
+/// Category of resource which owns the allocation.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ResourceCategory {
+    VertexBuffer,
+    IndexBuffer,
+    UniformBuffer,
+    Texture,
+    RenderTarget,
+    Readback,
+}
+
+impl ResourceCategory {
+    /// Checks if resources of this category are allocated from memory visible by the host.
+    pub const fn host_visible(self) -> bool {
+        matches!(self, Self::Readback)
+    }
+}
+
+/// Single allocation of the resource.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct AllocationInfo {
+    /// Debug name of the resource which owns this allocation.
+    pub name: String,
+    /// Category of the resource which owns this allocation.
+    pub category: ResourceCategory,
+    /// Size of the allocation in bytes.
+    pub size: DeviceSize,
+}
+
+/// Range of the memory block which is used by the allocation.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct UsedRange {
+    /// Offset of the allocation from the start of the block in bytes.
+    pub offset: DeviceSize,
+    /// Allocation which uses this range.
+    pub allocation: AllocationInfo,
+}
+
+/// Range of the memory block which is not used by any allocation.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FreeRange {
+    /// Offset of the range from the start of the block in bytes.
+    pub offset: DeviceSize,
+    /// Size of the range in bytes.
+    pub size: DeviceSize,
+}
+
+/// Single memory block of the heap with its used and free ranges.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MemoryBlock {
+    /// Size of the block in bytes.
+    pub size: DeviceSize,
+    /// Ranges of the block used by allocations, ordered by offset.
+    pub used: Vec<UsedRange>,
+    /// Ranges of the block between allocations, ordered by offset.
+    pub free: Vec<FreeRange>,
+}
+
+/// Memory blocks of the single memory heap.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct HeapMap {
+    /// Index of the heap of physical device.
+    pub index: u32,
+    /// Total size of the heap in bytes.
+    pub size: DeviceSize,
+    /// If the heap is local for the device.
+    pub device_local: bool,
+    /// Blocks which were allocated from this heap.
+    pub blocks: Vec<MemoryBlock>,
+}
+
+impl HeapMap {
+    /// Size of all allocations of this heap in bytes.
+    pub fn used(&self) -> DeviceSize {
+        self.blocks
+            .iter()
+            .flat_map(|block| &block.used)
+            .map(|range| range.allocation.size)
+            .sum()
+    }
+
+    /// Size of all blocks of this heap in bytes.
+    pub fn allocated(&self) -> DeviceSize {
+        self.blocks.iter().map(|block| block.size).sum()
+    }
+}
+
+/// Memory heap of physical device.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct HeapInfo {
+    pub index: u32,
+    pub size: DeviceSize,
+    pub device_local: bool,
+}
+
+/// Allocation of the resource which is alive while the resource is alive.
+struct TrackedAllocation {
+    resource: Weak<dyn Any + Send + Sync>,
+    offset: DeviceSize,
+    info: AllocationInfo,
+}
+
+impl TrackedAllocation {
+    fn end(&self) -> DeviceSize {
+        self.offset + self.info.size
+    }
+}
+
+/// Block of the heap with allocations placed into it.
+struct TrackedBlock {
+    heap: u32,
+    size: DeviceSize,
+    /// Allocations of the block, ordered by offset.
+    allocations: Vec<TrackedAllocation>,
+}
+
+impl TrackedBlock {
+    /// Finds offset of the first range of the block where allocation of provided size fits.
+    fn find_space(&self, size: DeviceSize) -> Option<(usize, DeviceSize)> {
+        let mut start = 0;
+        for (position, allocation) in self.allocations.iter().enumerate() {
+            if start + size <= allocation.offset {
+                return Some((position, start));
+            }
+            start = allocation.end();
+        }
+        (start + size <= self.size).then_some((self.allocations.len(), start))
+    }
+
+    /// Drops allocations whose resources are not alive.
+    fn release_dead(&mut self) {
+        self.allocations
+            .retain(|allocation| allocation.resource.strong_count() > 0);
+    }
+
+    /// Creates map of the block, leaving allocations which are still alive.
+    fn map(&self) -> MemoryBlock {
+        let mut used = Vec::with_capacity(self.allocations.len());
+        let mut free = Vec::new();
+        let mut start = 0;
+        let live = self
+            .allocations
+            .iter()
+            .filter(|allocation| allocation.resource.strong_count() > 0);
+        for allocation in live {
+            if allocation.offset > start {
+                free.push(FreeRange {
+                    offset: start,
+                    size: allocation.offset - start,
+                });
+            }
+            used.push(UsedRange {
+                offset: allocation.offset,
+                allocation: allocation.info.clone(),
+            });
+            start = allocation.end();
+        }
+        if self.size > start {
+            free.push(FreeRange {
+                offset: start,
+                size: self.size - start,
+            });
+        }
+        MemoryBlock {
+            size: self.size,
+            used,
+            free,
+        }
+    }
+}
+
+/// Tracker of allocations of engine resources.
+pub struct MemoryTracker {
+    heaps: Vec<HeapInfo>,
+    device_local_heap: u32,
+    host_visible_heap: u32,
+    blocks: Mutex<Vec<TrackedBlock>>,
+    snapshot: Mutex<Option<(Instant, Vec<HeapMap>)>>,
+}
+
+impl MemoryTracker {
+    /// Creates new tracker for heaps of provided physical device.
+    pub(crate) fn new(physical_device: PhysicalDevice) -> Self {
+        let heaps = physical_device
+            .memory_heaps()
+            .map(|heap| HeapInfo {
+                index: heap.id(),
+                size: heap.size(),
+                device_local: heap.is_device_local(),
+            })
+            .collect();
+        let heap_of = |required| {
+            self::find_memory_type(physical_device, u32::MAX, required)
+                .map(|memory_type| memory_type.heap().id())
+                .unwrap_or_default()
+        };
+        let device_local_heap = heap_of(MemoryProperties::GPU_ONLY);
+        let host_visible_heap = heap_of(MemoryProperties::STAGING);
+        Self::with_heaps(heaps, device_local_heap, host_visible_heap)
+    }
+
+    /// Creates new tracker for provided heaps without allocations.
+    pub(crate) fn with_heaps(
+        heaps: Vec<HeapInfo>,
+        device_local_heap: u32,
+        host_visible_heap: u32,
+    ) -> Self {
+        Self {
+            heaps,
+            device_local_heap,
+            host_visible_heap,
+            blocks: Mutex::new(Vec::new()),
+            snapshot: Mutex::new(None),
+        }
+    }
+
+    /// Starts tracking of the allocation while provided resource is alive.
+    ///
+    /// Allocation is placed into the first block of its heap where it fits,
+    /// or into the new block if there is no such block.
+    ///
+    pub(crate) fn track<T>(
+        &self,
+        resource: &Arc<T>,
+        name: impl Into<String>,
+        category: ResourceCategory,
+        size: DeviceSize,
+    ) where
+        T: Send + Sync + 'static,
+    {
+        let resource = resource.clone() as Arc<dyn Any + Send + Sync>;
+        let heap = if category.host_visible() {
+            self.host_visible_heap
+        } else {
+            self.device_local_heap
+        };
+
+        let mut blocks = self.blocks();
+        let space = blocks
+            .iter_mut()
+            .enumerate()
+            .filter(|(_, block)| block.heap == heap)
+            .find_map(|(index, block)| {
+                block.release_dead();
+                block
+                    .find_space(size)
+                    .map(|(position, offset)| (index, position, offset))
+            });
+        let (index, position, offset) = match space {
+            Some(space) => space,
+            None => {
+                blocks.push(TrackedBlock {
+                    heap,
+                    size: MIN_BLOCK_SIZE.max(size.next_power_of_two()),
+                    allocations: Vec::new(),
+                });
+                (blocks.len() - 1, 0, 0)
+            }
+        };
+        let allocation = TrackedAllocation {
+            resource: Arc::downgrade(&resource),
+            offset,
+            info: AllocationInfo {
+                name: name.into(),
+                category,
+                size,
+            },
+        };
+        blocks[index].allocations.insert(position, allocation);
+    }
+
+    /// Locks the list of blocks.
+    ///
+    /// List stays valid even if some thread has panicked while holding the lock,
+    /// so poisoning is ignored: the tracker is still used on destruction of the renderer.
+    ///
+    fn blocks(&self) -> MutexGuard<'_, Vec<TrackedBlock>> {
+        self.blocks.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Allocations whose resources are still alive.
+    pub(crate) fn live_allocations(&self) -> Vec<AllocationInfo> {
+        let mut blocks = self.blocks();
+        blocks
+            .iter_mut()
+            .flat_map(|block| {
+                block.release_dead();
+                &block.allocations
+            })
+            .map(|allocation| allocation.info.clone())
+            .collect()
+    }
+
+    /// Collects blocks with used and free ranges of all heaps.
+    ///
+    /// Lock of the tracker is held only while blocks are mapped, which is linear
+    /// in count of allocations. Snapshot is regenerated at most once per [`SNAPSHOT_INTERVAL`],
+    /// otherwise the previous one is returned.
+    ///
+    pub(crate) fn memory_map(&self) -> Vec<HeapMap> {
+        let mut snapshot = self.snapshot.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some((time, heaps)) = snapshot.as_ref() {
+            if time.elapsed() < SNAPSHOT_INTERVAL {
+                return heaps.clone();
+            }
+        }
+
+        let blocks: Vec<_> = {
+            let blocks = self.blocks();
+            blocks
+                .iter()
+                .map(|block| (block.heap, block.map()))
+                .collect()
+        };
+        let heaps: Vec<_> = self
+            .heaps
+            .iter()
+            .map(|heap| HeapMap {
+                index: heap.index,
+                size: heap.size,
+                device_local: heap.device_local,
+                blocks: blocks
+                    .iter()
+                    .filter(|(index, _)| *index == heap.index)
+                    .map(|(_, block)| block.clone())
+                    .collect(),
+            })
+            .collect();
+        *snapshot = Some((Instant::now(), heaps.clone()));
+        heaps
+    }
+}
+
+/// Serializes memory map into JSON string.
+#[cfg(feature = "serde")]
+pub fn memory_map_to_json(heaps: &[HeapMap]) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(heaps)
+}
+
+/// Estimates size of 2D image with provided format in bytes.
+pub(crate) fn image_size(dimensions: [u32; 2], format: Format) -> DeviceSize {
+    let [width, height] = dimensions;
+    let texel_size = format.size().unwrap_or(4);
+    width as DeviceSize * height as DeviceSize * texel_size
+}
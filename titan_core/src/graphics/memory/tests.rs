@@ -0,0 +1,150 @@
+#![cfg(test)]
+
+use std::sync::Arc;
+use std::thread;
+
+use super::{
+    select_memory_type, FreeRange, HeapInfo, MemoryProperties, MemoryTracker, MemoryTypeError,
+    ResourceCategory, MIN_BLOCK_SIZE,
+};
+
+const HOST_CACHED: MemoryProperties = MemoryProperties {
+    device_local: false,
+    host_visible: true,
+    host_coherent: false,
+};
+const UNIFIED: MemoryProperties = MemoryProperties {
+    device_local: true,
+    host_visible: true,
+    host_coherent: true,
+};
+
+#[test]
+fn first_suitable_type_is_selected() {
+    let types = [
+        MemoryProperties::GPU_ONLY,
+        HOST_CACHED,
+        MemoryProperties::STAGING,
+        UNIFIED,
+    ];
+    let staging = select_memory_type(&types, u32::MAX, MemoryProperties::STAGING).unwrap();
+    assert_eq!(staging, 2);
+    let gpu_only = select_memory_type(&types, u32::MAX, MemoryProperties::GPU_ONLY).unwrap();
+    assert_eq!(gpu_only, 0);
+}
+
+#[test]
+fn type_bits_are_respected() {
+    let types = [MemoryProperties::GPU_ONLY, UNIFIED];
+    let selected = select_memory_type(&types, 0b10, MemoryProperties::GPU_ONLY).unwrap();
+    assert_eq!(selected, 1);
+}
+
+#[test]
+fn error_names_requested_flags() {
+    let types = [MemoryProperties::GPU_ONLY];
+    let error = select_memory_type(&types, u32::MAX, MemoryProperties::STAGING).unwrap_err();
+    assert!(matches!(error, MemoryTypeError::NotFound { .. }));
+    assert!(error.to_string().contains("HOST_VISIBLE | HOST_COHERENT"));
+}
+
+/// Tracker with device local heap 0 and host visible heap 1.
+fn tracker() -> MemoryTracker {
+    let heaps = vec![
+        HeapInfo {
+            index: 0,
+            size: 1 << 30,
+            device_local: true,
+        },
+        HeapInfo {
+            index: 1,
+            size: 1 << 28,
+            device_local: false,
+        },
+    ];
+    MemoryTracker::with_heaps(heaps, 0, 1)
+}
+
+#[test]
+fn allocations_are_placed_first_fit() {
+    let tracker = tracker();
+    let first = Arc::new(1u32);
+    let second = Arc::new(2u32);
+    tracker.track(&first, "first", ResourceCategory::VertexBuffer, 256);
+    tracker.track(&second, "second", ResourceCategory::IndexBuffer, 128);
+    drop(first);
+    let third = Arc::new(3u32);
+    tracker.track(&third, "third", ResourceCategory::Texture, 64);
+
+    let heaps = tracker.memory_map();
+    assert_eq!(heaps.len(), 2);
+    assert_eq!(heaps[0].blocks.len(), 1);
+    let block = &heaps[0].blocks[0];
+    assert_eq!(block.size, MIN_BLOCK_SIZE);
+    let used: Vec<_> = block
+        .used
+        .iter()
+        .map(|range| (range.offset, range.allocation.name.as_str()))
+        .collect();
+    assert_eq!(used, [(0, "third"), (256, "second")]);
+    assert_eq!(
+        block.free,
+        [
+            FreeRange {
+                offset: 64,
+                size: 192,
+            },
+            FreeRange {
+                offset: 384,
+                size: MIN_BLOCK_SIZE - 384,
+            },
+        ]
+    );
+    assert_eq!(heaps[0].used(), 192);
+    assert!(heaps[1].blocks.is_empty());
+}
+
+#[test]
+fn large_and_host_visible_allocations_get_own_blocks() {
+    let tracker = tracker();
+    let small = Arc::new(1u32);
+    let large = Arc::new(2u32);
+    let readback = Arc::new(3u32);
+    tracker.track(&small, "small", ResourceCategory::UniformBuffer, 16);
+    tracker.track(
+        &large,
+        "large",
+        ResourceCategory::Texture,
+        MIN_BLOCK_SIZE + 1,
+    );
+    tracker.track(&readback, "readback", ResourceCategory::Readback, 16);
+
+    let heaps = tracker.memory_map();
+    let sizes: Vec<_> = heaps[0].blocks.iter().map(|block| block.size).collect();
+    assert_eq!(sizes, [MIN_BLOCK_SIZE, 2 * MIN_BLOCK_SIZE]);
+    assert_eq!(heaps[0].allocated(), 3 * MIN_BLOCK_SIZE);
+    assert_eq!(heaps[1].blocks.len(), 1);
+    assert_eq!(heaps[1].blocks[0].used[0].allocation.name, "readback");
+}
+
+#[test]
+fn tracker_survives_poisoned_lock() {
+    let tracker = Arc::new(self::tracker());
+    let resource = Arc::new(42u32);
+    tracker.track(&resource, "buffer", ResourceCategory::UniformBuffer, 4);
+
+    let poisoner = tracker.clone();
+    let result = thread::spawn(move || {
+        let _guard = poisoner.blocks.lock().unwrap();
+        panic!("panic while holding the lock");
+    })
+    .join();
+    assert!(result.is_err());
+    assert!(tracker.blocks.is_poisoned());
+
+    let live = tracker.live_allocations();
+    assert_eq!(live.len(), 1);
+    assert_eq!(live[0].name, "buffer");
+    drop(resource);
+    assert!(tracker.live_allocations().is_empty());
+}
@@ -1,12 +1,50 @@
 //! Graphics utilities and backend based on Vulkan API for game engine.
 
+pub use self::camera::{ModelTransform, TransformHandle};
+pub use self::frame::external;
+pub use self::frame::picking::{PickResult, PickTicket};
+pub use self::frame::post;
+pub use self::index::Indices;
 pub use self::renderer::*;
+pub use self::vertex::Vertex;
+
+pub(crate) use self::utils::probe_capabilities;
 
 pub(crate) mod camera;
 
-mod debug_callback;
+pub mod adapter;
+pub mod atlas;
+pub mod barrier;
+pub mod color;
+pub mod compute;
+pub mod debug;
+pub mod failure;
+mod fence;
 mod frame;
+mod gpu_timer;
+#[cfg(feature = "hot-reload")]
+pub mod hot_reload;
+mod index;
+pub mod indirect;
+pub mod lines;
+pub mod memory;
+pub mod params;
+pub mod pipeline;
+pub mod pipeline_cache;
+mod present;
+pub mod push;
+pub mod query;
+pub mod readback;
+pub mod render_pass;
 mod renderer;
+pub mod screenshot;
+pub mod semaphore;
 mod shader;
+pub mod spirv;
+pub mod submit;
+pub mod target;
+pub mod texture;
+mod timeline;
+mod upload;
 mod utils;
 mod vertex;
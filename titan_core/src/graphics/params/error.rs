@@ -0,0 +1,112 @@
+use std::fmt;
+
+use thiserror::Error;
+
+use super::ParamType;
+
+/// Error that can happen when building or using parameter block.
+#[derive(Debug, Error)]
+pub enum ParamBlockError {
+    #[error("parameter \"{0}\" is declared more than once")]
+    DuplicateParam(String),
+
+    #[error("array parameter \"{0}\" and its nested arrays must have at least one element")]
+    EmptyArray(String),
+
+    #[error("there is no parameter \"{0}\" in the block")]
+    NoSuchParam(String),
+
+    #[error(
+        "parameter \"{name}\" has type {expected:?}, but value of type {actual:?} was provided"
+    )]
+    TypeMismatch {
+        name: String,
+        expected: ParamType,
+        actual: ParamType,
+    },
+
+    #[error("array parameter \"{name}\" has {len} elements, but {actual} values were provided")]
+    ArrayOverflow {
+        name: String,
+        len: u32,
+        actual: usize,
+    },
+
+    #[error("there is no block \"{0}\" in the shader")]
+    NoSuchBlock(String),
+
+    #[error("parameter block does not match the shader: {}", ParamMismatches(.0))]
+    Mismatch(Vec<ParamMismatch>),
+}
+
+/// Difference between parameter block and its declaration in the shader.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ParamMismatch {
+    /// Parameter is declared in the block, but not in the shader.
+    MissingInShader { name: String },
+    /// Parameter is declared in the shader, but not in the block.
+    MissingInBlock { name: String },
+    /// Parameter has different types in the block and in the shader.
+    Type {
+        name: String,
+        shader: Option<ParamType>,
+        block: ParamType,
+    },
+    /// Parameter has different offsets in the block and in the shader.
+    Offset {
+        name: String,
+        shader: u32,
+        block: u32,
+    },
+}
+
+impl fmt::Display for ParamMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingInShader { name } => write!(f, "\"{}\" is missing in the shader", name),
+            Self::MissingInBlock { name } => write!(f, "\"{}\" is missing in the block", name),
+            Self::Type {
+                name,
+                shader: Some(shader),
+                block,
+            } => write!(
+                f,
+                "\"{}\" is {:?} in the shader, but {:?} in the block",
+                name, shader, block
+            ),
+            Self::Type {
+                name,
+                shader: None,
+                block,
+            } => write!(
+                f,
+                "\"{}\" has unsupported type in the shader, but {:?} in the block",
+                name, block
+            ),
+            Self::Offset {
+                name,
+                shader,
+                block,
+            } => write!(
+                f,
+                "\"{}\" has offset {} in the shader, but {} in the block",
+                name, shader, block
+            ),
+        }
+    }
+}
+
+/// Helper for displaying list of mismatches.
+struct ParamMismatches<'a>(&'a [ParamMismatch]);
+
+impl fmt::Display for ParamMismatches<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, mismatch) in self.0.iter().enumerate() {
+            if index > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{}", mismatch)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,350 @@
+//! Material parameter blocks with automatic layout packing.
+//!
+//! Parameters are declared by name and type,
+//! and their offsets are computed by `std140` (uniform buffers) or `std430` (storage buffers)
+//! layout rules of GLSL, so there is no need to write padded `#[repr(C)]` structs by hand.
+
+use ultraviolet::{Mat3, Mat4, Vec2, Vec3, Vec4};
+
+use error::ParamBlockError;
+
+pub mod error;
+mod reflection;
+mod tests;
+
+/// Layout rules which are used to pack parameters of the block.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum BlockLayout {
+    /// Layout of uniform buffer blocks.
+    Std140,
+    /// Layout of shader storage buffer blocks.
+    Std430,
+}
+
+/// Type of parameter in the block.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ParamType {
+    Float,
+    Int,
+    UInt,
+    Vec2,
+    Vec3,
+    Vec4,
+    Mat3,
+    Mat4,
+    /// Array of elements with the same type and provided length.
+    Array(Box<ParamType>, u32),
+}
+
+impl ParamType {
+    /// Base alignment of the type in bytes.
+    pub fn alignment(&self, layout: BlockLayout) -> u32 {
+        match self {
+            Self::Float | Self::Int | Self::UInt => 4,
+            Self::Vec2 => 8,
+            Self::Vec3 | Self::Vec4 | Self::Mat3 | Self::Mat4 => 16,
+            Self::Array(element, _) => {
+                let alignment = element.alignment(layout);
+                match layout {
+                    BlockLayout::Std140 => round_up(alignment, 16),
+                    BlockLayout::Std430 => alignment,
+                }
+            }
+        }
+    }
+
+    /// Size of the type in bytes.
+    pub fn size(&self, layout: BlockLayout) -> u32 {
+        match self {
+            Self::Float | Self::Int | Self::UInt => 4,
+            Self::Vec2 => 8,
+            Self::Vec3 => 12,
+            Self::Vec4 => 16,
+            // Matrices are stored as arrays of column vectors.
+            Self::Mat3 => 3 * 16,
+            Self::Mat4 => 4 * 16,
+            Self::Array(_, len) => self.array_stride(layout).unwrap_or_default() * len,
+        }
+    }
+
+    /// Array stride in bytes if this type is an array.
+    pub fn array_stride(&self, layout: BlockLayout) -> Option<u32> {
+        match self {
+            Self::Array(element, _) => {
+                let size = element.size(layout);
+                Some(round_up(size, self.alignment(layout)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Checks if this type is an array of no elements or contains one at any nesting level.
+    pub fn has_empty_array(&self) -> bool {
+        match self {
+            Self::Array(_, 0) => true,
+            Self::Array(element, _) => element.has_empty_array(),
+            _ => false,
+        }
+    }
+}
+
+/// Rounds up value to the multiple of alignment.
+const fn round_up(value: u32, alignment: u32) -> u32 {
+    value.div_ceil(alignment) * alignment
+}
+
+/// Single parameter of the block with computed offset.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ParamField {
+    pub name: String,
+    pub ty: ParamType,
+    pub offset: u32,
+}
+
+/// Builder of parameter block.
+#[derive(Debug, Clone)]
+pub struct ParamBlockBuilder {
+    layout: BlockLayout,
+    params: Vec<(String, ParamType)>,
+}
+
+impl ParamBlockBuilder {
+    /// Creates builder of parameter block with provided layout rules.
+    pub fn new(layout: BlockLayout) -> Self {
+        Self {
+            layout,
+            params: Vec::new(),
+        }
+    }
+
+    /// Declares new parameter with given name and type.
+    ///
+    /// Parameters are placed in the block in order of declaration.
+    ///
+    pub fn param(mut self, name: impl Into<String>, ty: ParamType) -> Self {
+        self.params.push((name.into(), ty));
+        self
+    }
+
+    /// Computes offsets of all declared parameters and creates zeroed parameter block.
+    pub fn build(self) -> Result<ParamBlock, ParamBlockError> {
+        let layout = self.layout;
+        let mut fields: Vec<ParamField> = Vec::with_capacity(self.params.len());
+        let mut offset = 0;
+        let mut max_alignment = 4;
+        for (name, ty) in self.params {
+            if fields.iter().any(|field| field.name == name) {
+                return Err(ParamBlockError::DuplicateParam(name));
+            }
+            if ty.has_empty_array() {
+                return Err(ParamBlockError::EmptyArray(name));
+            }
+            let alignment = ty.alignment(layout);
+            max_alignment = max_alignment.max(alignment);
+            offset = round_up(offset, alignment);
+            let size = ty.size(layout);
+            fields.push(ParamField { name, ty, offset });
+            offset += size;
+        }
+        let alignment = match layout {
+            BlockLayout::Std140 => round_up(max_alignment, 16),
+            BlockLayout::Std430 => max_alignment,
+        };
+        let size = round_up(offset, alignment);
+        Ok(ParamBlock {
+            layout,
+            fields,
+            data: vec![0; size as usize],
+        })
+    }
+}
+
+/// Block of named parameters packed by layout rules of GLSL.
+#[derive(Debug, Clone)]
+pub struct ParamBlock {
+    layout: BlockLayout,
+    fields: Vec<ParamField>,
+    data: Vec<u8>,
+}
+
+impl ParamBlock {
+    /// Layout rules of this block.
+    pub fn layout(&self) -> BlockLayout {
+        self.layout
+    }
+
+    /// All parameters of this block in order of declaration.
+    pub fn fields(&self) -> &[ParamField] {
+        &self.fields
+    }
+
+    /// Retrieves parameter of this block by its name.
+    pub fn field(&self, name: &str) -> Option<&ParamField> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+
+    /// Size of this block in bytes.
+    pub fn size(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Packed bytes of this block which can be uploaded into the buffer.
+    pub fn bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Sets the value of parameter with provided name.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if there is no such parameter or if type of the value is wrong.
+    ///
+    pub fn set<V>(&mut self, name: &str, value: V) -> Result<(), ParamBlockError>
+    where
+        V: ParamValue,
+    {
+        let field = self
+            .field(name)
+            .ok_or_else(|| ParamBlockError::NoSuchParam(name.to_string()))?;
+        if field.ty != V::TYPE {
+            return Err(ParamBlockError::TypeMismatch {
+                name: name.to_string(),
+                expected: field.ty.clone(),
+                actual: V::TYPE,
+            });
+        }
+        let offset = field.offset as usize;
+        value.write(&mut self.data[offset..]);
+        Ok(())
+    }
+
+    /// Sets the values of array parameter with provided name, starting from the first element.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if there is no such parameter, if type of the values is wrong
+    /// or if there are more values than elements in the array.
+    ///
+    pub fn set_array<V>(&mut self, name: &str, values: &[V]) -> Result<(), ParamBlockError>
+    where
+        V: ParamValue + Copy,
+    {
+        let field = self
+            .field(name)
+            .ok_or_else(|| ParamBlockError::NoSuchParam(name.to_string()))?;
+        let len = match &field.ty {
+            ParamType::Array(element, len) if **element == V::TYPE => *len,
+            ty => {
+                return Err(ParamBlockError::TypeMismatch {
+                    name: name.to_string(),
+                    expected: ty.clone(),
+                    actual: ParamType::Array(Box::new(V::TYPE), values.len() as u32),
+                })
+            }
+        };
+        if values.len() > len as usize {
+            return Err(ParamBlockError::ArrayOverflow {
+                name: name.to_string(),
+                len,
+                actual: values.len(),
+            });
+        }
+        let stride = field.ty.array_stride(self.layout).unwrap() as usize;
+        let offset = field.offset as usize;
+        for (index, value) in values.iter().enumerate() {
+            value.write(&mut self.data[offset + index * stride..]);
+        }
+        Ok(())
+    }
+}
+
+/// Values of this trait could be stored in the parameter block.
+pub trait ParamValue {
+    /// Type of parameter which matches this value.
+    const TYPE: ParamType;
+
+    /// Writes this value into the start of provided bytes.
+    fn write(&self, bytes: &mut [u8]);
+}
+
+/// Writes floats into the bytes one after another.
+fn write_floats(bytes: &mut [u8], floats: &[f32]) {
+    for (chunk, float) in bytes.chunks_exact_mut(4).zip(floats) {
+        chunk.copy_from_slice(&float.to_ne_bytes());
+    }
+}
+
+/// Writes column-major matrix into the bytes with column stride of 16 bytes.
+fn write_columns(bytes: &mut [u8], columns: &[&[f32]]) {
+    for (chunk, column) in bytes.chunks_mut(16).zip(columns) {
+        self::write_floats(chunk, column);
+    }
+}
+
+impl ParamValue for f32 {
+    const TYPE: ParamType = ParamType::Float;
+
+    fn write(&self, bytes: &mut [u8]) {
+        bytes[..4].copy_from_slice(&self.to_ne_bytes());
+    }
+}
+
+impl ParamValue for i32 {
+    const TYPE: ParamType = ParamType::Int;
+
+    fn write(&self, bytes: &mut [u8]) {
+        bytes[..4].copy_from_slice(&self.to_ne_bytes());
+    }
+}
+
+impl ParamValue for u32 {
+    const TYPE: ParamType = ParamType::UInt;
+
+    fn write(&self, bytes: &mut [u8]) {
+        bytes[..4].copy_from_slice(&self.to_ne_bytes());
+    }
+}
+
+impl ParamValue for Vec2 {
+    const TYPE: ParamType = ParamType::Vec2;
+
+    fn write(&self, bytes: &mut [u8]) {
+        self::write_floats(bytes, self.as_slice())
+    }
+}
+
+impl ParamValue for Vec3 {
+    const TYPE: ParamType = ParamType::Vec3;
+
+    fn write(&self, bytes: &mut [u8]) {
+        self::write_floats(bytes, self.as_slice())
+    }
+}
+
+impl ParamValue for Vec4 {
+    const TYPE: ParamType = ParamType::Vec4;
+
+    fn write(&self, bytes: &mut [u8]) {
+        self::write_floats(bytes, self.as_slice())
+    }
+}
+
+impl ParamValue for Mat3 {
+    const TYPE: ParamType = ParamType::Mat3;
+
+    fn write(&self, bytes: &mut [u8]) {
+        let cols = &self.cols;
+        self::write_columns(
+            bytes,
+            &[cols[0].as_slice(), cols[1].as_slice(), cols[2].as_slice()],
+        )
+    }
+}
+
+impl ParamValue for Mat4 {
+    const TYPE: ParamType = ParamType::Mat4;
+
+    fn write(&self, bytes: &mut [u8]) {
+        self::write_floats(bytes, self.as_slice())
+    }
+}
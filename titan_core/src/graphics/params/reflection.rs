@@ -0,0 +1,160 @@
+//! Validation of parameter blocks against shader reflection data.
+
+use vulkano::spirv::{Decoration, Id, Instruction, Spirv};
+
+use super::{
+    error::{ParamBlockError, ParamMismatch},
+    ParamBlock, ParamType,
+};
+
+/// Member of the block declared in the shader.
+struct ShaderMember {
+    name: String,
+    ty: Option<ParamType>,
+    offset: u32,
+}
+
+impl ParamBlock {
+    /// Validates this block against the block with provided type name declared in the shader.
+    ///
+    /// Checks that names, types and offsets of all parameters are the same
+    /// as in the shader.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if there is no such block in the shader
+    /// or if some of parameters do not match the shader.
+    ///
+    pub fn validate(&self, spirv: &Spirv, block_name: &str) -> Result<(), ParamBlockError> {
+        let members = self::shader_block_members(spirv, block_name)
+            .ok_or_else(|| ParamBlockError::NoSuchBlock(block_name.to_string()))?;
+
+        let mut mismatches = Vec::new();
+        for field in self.fields() {
+            let member = match members.iter().find(|member| member.name == field.name) {
+                Some(member) => member,
+                None => {
+                    let name = field.name.clone();
+                    mismatches.push(ParamMismatch::MissingInShader { name });
+                    continue;
+                }
+            };
+            if member.ty.as_ref() != Some(&field.ty) {
+                mismatches.push(ParamMismatch::Type {
+                    name: field.name.clone(),
+                    shader: member.ty.clone(),
+                    block: field.ty.clone(),
+                });
+            }
+            if member.offset != field.offset {
+                mismatches.push(ParamMismatch::Offset {
+                    name: field.name.clone(),
+                    shader: member.offset,
+                    block: field.offset,
+                });
+            }
+        }
+        for member in members {
+            if self.field(&member.name).is_none() {
+                let name = member.name;
+                mismatches.push(ParamMismatch::MissingInBlock { name });
+            }
+        }
+
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(ParamBlockError::Mismatch(mismatches))
+        }
+    }
+}
+
+/// Retrieves all members of the block with provided type name.
+fn shader_block_members(spirv: &Spirv, block_name: &str) -> Option<Vec<ShaderMember>> {
+    let (struct_id, member_types) = spirv.iter_name().find_map(|instruction| {
+        let target = match instruction {
+            Instruction::Name { target, name } if name == block_name => *target,
+            _ => return None,
+        };
+        match spirv.id(target).instruction() {
+            Instruction::TypeStruct { member_types, .. } => Some((target, member_types)),
+            _ => None,
+        }
+    })?;
+
+    let members = spirv
+        .id(struct_id)
+        .iter_members()
+        .zip(member_types)
+        .enumerate()
+        .map(|(index, (info, &ty))| {
+            let name = info
+                .iter_name()
+                .find_map(|instruction| match instruction {
+                    Instruction::MemberName { name, .. } => Some(name.clone()),
+                    _ => None,
+                })
+                .unwrap_or_else(|| format!("<member {}>", index));
+            let offset = info
+                .iter_decoration()
+                .find_map(|instruction| match instruction {
+                    Instruction::MemberDecorate {
+                        decoration: Decoration::Offset { byte_offset },
+                        ..
+                    } => Some(*byte_offset),
+                    _ => None,
+                })
+                .unwrap_or_default();
+            let ty = self::param_type(spirv, ty);
+            ShaderMember { name, ty, offset }
+        })
+        .collect();
+    Some(members)
+}
+
+/// Converts type of the shader into the parameter type, if possible.
+fn param_type(spirv: &Spirv, id: Id) -> Option<ParamType> {
+    match spirv.id(id).instruction() {
+        Instruction::TypeFloat { width: 32, .. } => Some(ParamType::Float),
+        Instruction::TypeInt {
+            width: 32,
+            signedness,
+            ..
+        } => Some(match signedness {
+            0 => ParamType::UInt,
+            _ => ParamType::Int,
+        }),
+        Instruction::TypeVector {
+            component_type,
+            component_count,
+            ..
+        } => match (self::param_type(spirv, *component_type)?, component_count) {
+            (ParamType::Float, 2) => Some(ParamType::Vec2),
+            (ParamType::Float, 3) => Some(ParamType::Vec3),
+            (ParamType::Float, 4) => Some(ParamType::Vec4),
+            _ => None,
+        },
+        Instruction::TypeMatrix {
+            column_type,
+            column_count,
+            ..
+        } => match (self::param_type(spirv, *column_type)?, column_count) {
+            (ParamType::Vec3, 3) => Some(ParamType::Mat3),
+            (ParamType::Vec4, 4) => Some(ParamType::Mat4),
+            _ => None,
+        },
+        Instruction::TypeArray {
+            element_type,
+            length,
+            ..
+        } => {
+            let element = self::param_type(spirv, *element_type)?;
+            let len = match spirv.id(*length).instruction() {
+                Instruction::Constant { value, .. } => *value.first()?,
+                _ => return None,
+            };
+            Some(ParamType::Array(Box::new(element), len))
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,184 @@
+#![cfg(test)]
+
+use ultraviolet::{Mat3, Vec3};
+
+use super::{error::ParamBlockError, *};
+
+fn offsets(block: &ParamBlock) -> Vec<u32> {
+    block.fields().iter().map(|field| field.offset).collect()
+}
+
+fn float_at(block: &ParamBlock, offset: usize) -> f32 {
+    let bytes = &block.bytes()[offset..offset + 4];
+    f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+fn example(layout: BlockLayout) -> ParamBlock {
+    // Adapted from the std140 example of the OpenGL specification.
+    ParamBlockBuilder::new(layout)
+        .param("a", ParamType::Float)
+        .param("b", ParamType::Vec2)
+        .param("c", ParamType::Vec3)
+        .param("g", ParamType::Float)
+        .param("h", ParamType::Array(Box::new(ParamType::Float), 2))
+        .param("i", ParamType::Mat4)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_std140_offsets() {
+    let block = example(BlockLayout::Std140);
+    assert_eq!(offsets(&block), [0, 8, 16, 28, 32, 64]);
+    assert_eq!(block.size(), 128);
+
+    let h = block.field("h").unwrap();
+    assert_eq!(h.ty.array_stride(BlockLayout::Std140), Some(16));
+}
+
+#[test]
+fn test_std430_offsets() {
+    let block = example(BlockLayout::Std430);
+    assert_eq!(offsets(&block), [0, 8, 16, 28, 32, 48]);
+    assert_eq!(block.size(), 112);
+
+    let h = block.field("h").unwrap();
+    assert_eq!(h.ty.array_stride(BlockLayout::Std430), Some(4));
+}
+
+#[test]
+fn test_vec3_padding() {
+    for layout in [BlockLayout::Std140, BlockLayout::Std430] {
+        let block = ParamBlockBuilder::new(layout)
+            .param("position", ParamType::Vec3)
+            .param("color", ParamType::Vec3)
+            .param("intensity", ParamType::Float)
+            .param("directions", ParamType::Array(Box::new(ParamType::Vec3), 3))
+            .build()
+            .unwrap();
+        // vec3 has alignment of vec4, but scalar can be packed right after it.
+        assert_eq!(offsets(&block), [0, 16, 28, 32]);
+        let directions = block.field("directions").unwrap();
+        assert_eq!(directions.ty.array_stride(layout), Some(16));
+        assert_eq!(block.size(), 80);
+    }
+}
+
+#[test]
+fn test_block_size_rounding() {
+    let std140 = ParamBlockBuilder::new(BlockLayout::Std140)
+        .param("value", ParamType::Float)
+        .build()
+        .unwrap();
+    assert_eq!(std140.size(), 16);
+
+    let std430 = ParamBlockBuilder::new(BlockLayout::Std430)
+        .param("value", ParamType::Float)
+        .build()
+        .unwrap();
+    assert_eq!(std430.size(), 4);
+
+    let std430 = ParamBlockBuilder::new(BlockLayout::Std430)
+        .param("value", ParamType::Vec2)
+        .param("scale", ParamType::Float)
+        .build()
+        .unwrap();
+    assert_eq!(std430.size(), 16);
+}
+
+#[test]
+fn test_mat3_columns() {
+    for layout in [BlockLayout::Std140, BlockLayout::Std430] {
+        let mut block = ParamBlockBuilder::new(layout)
+            .param("scale", ParamType::Float)
+            .param("normal", ParamType::Mat3)
+            .build()
+            .unwrap();
+        assert_eq!(offsets(&block), [0, 16]);
+        assert_eq!(block.size(), 64);
+
+        let matrix = Mat3::new(
+            Vec3::new(1.0, 2.0, 3.0),
+            Vec3::new(4.0, 5.0, 6.0),
+            Vec3::new(7.0, 8.0, 9.0),
+        );
+        block.set("normal", matrix).unwrap();
+        // Each column has a stride of 16 bytes with padding after the third component.
+        assert_eq!(float_at(&block, 16), 1.0);
+        assert_eq!(float_at(&block, 24), 3.0);
+        assert_eq!(float_at(&block, 28), 0.0);
+        assert_eq!(float_at(&block, 32), 4.0);
+        assert_eq!(float_at(&block, 48), 7.0);
+        assert_eq!(float_at(&block, 56), 9.0);
+    }
+}
+
+#[test]
+fn test_array_of_vec2() {
+    let array = ParamType::Array(Box::new(ParamType::Vec2), 4);
+    assert_eq!(array.array_stride(BlockLayout::Std140), Some(16));
+    assert_eq!(array.array_stride(BlockLayout::Std430), Some(8));
+    assert_eq!(array.size(BlockLayout::Std140), 64);
+    assert_eq!(array.size(BlockLayout::Std430), 32);
+    assert_eq!(array.alignment(BlockLayout::Std140), 16);
+    assert_eq!(array.alignment(BlockLayout::Std430), 8);
+}
+
+#[test]
+fn test_set_array() {
+    let mut block = example(BlockLayout::Std140);
+    block.set("g", 0.5f32).unwrap();
+    block.set_array("h", &[1.0f32, 2.0]).unwrap();
+    assert_eq!(float_at(&block, 28), 0.5);
+    assert_eq!(float_at(&block, 32), 1.0);
+    assert_eq!(float_at(&block, 48), 2.0);
+
+    let error = block.set_array("h", &[1.0f32, 2.0, 3.0]).unwrap_err();
+    assert!(matches!(
+        error,
+        ParamBlockError::ArrayOverflow { len: 2, .. }
+    ));
+}
+
+#[test]
+fn test_set_errors() {
+    let mut block = example(BlockLayout::Std430);
+    let error = block.set("a", Vec3::zero()).unwrap_err();
+    assert!(matches!(error, ParamBlockError::TypeMismatch { .. }));
+
+    let error = block.set("unknown", 1.0f32).unwrap_err();
+    assert!(matches!(error, ParamBlockError::NoSuchParam(_)));
+
+    let error = block.set_array("a", &[1.0f32]).unwrap_err();
+    assert!(matches!(error, ParamBlockError::TypeMismatch { .. }));
+}
+
+#[test]
+fn test_build_errors() {
+    let error = ParamBlockBuilder::new(BlockLayout::Std140)
+        .param("a", ParamType::Float)
+        .param("a", ParamType::Vec4)
+        .build()
+        .unwrap_err();
+    assert!(matches!(error, ParamBlockError::DuplicateParam(_)));
+
+    let error = ParamBlockBuilder::new(BlockLayout::Std140)
+        .param("a", ParamType::Array(Box::new(ParamType::Float), 0))
+        .build()
+        .unwrap_err();
+    assert!(matches!(error, ParamBlockError::EmptyArray(_)));
+}
+
+#[test]
+fn test_nested_empty_array() {
+    let empty = ParamType::Array(Box::new(ParamType::Vec4), 0);
+    let nested = ParamType::Array(Box::new(ParamType::Array(Box::new(empty), 2)), 3);
+    for layout in [BlockLayout::Std140, BlockLayout::Std430] {
+        let error = ParamBlockBuilder::new(layout)
+            .param("a", ParamType::Float)
+            .param("nested", nested.clone())
+            .build()
+            .unwrap_err();
+        assert!(matches!(error, ParamBlockError::EmptyArray(name) if name == "nested"));
+    }
+}
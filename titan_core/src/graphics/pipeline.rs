@@ -0,0 +1,400 @@
+//! Configurable state of graphics pipelines of game objects.
+//!
+//! Game objects are drawn with the [default](PipelineId::DEFAULT) pipeline, unless another one
+//! is created from [`GraphicsPipelineDescriptor`] and assigned to them, for example
+//! to draw transparent objects with blending and without writing depth.
+//! All pipelines share shaders and layout, so they are rebuilt when shaders are replaced.
+//!
+//! Blending happens in linear color space: if the attachment has sRGB format
+//! (like the usual swapchain format), the device decodes stored color before blending
+//! and encodes the result after it. So 50% alpha blending of red over blue gives
+//! linear average of them, which is brighter than average of their sRGB encodings.
+//!
+//! Non-solid [polygon modes](PolygonMode) and wide lines need optional features
+//! of the device, which should be requested by device requirements of the configuration.
+//! Rasterization state is baked into pipelines, so changing it creates another pipeline.
+
+use vulkano::pipeline::blend::{AttachmentBlend, BlendFactor, BlendOp};
+
+use crate::graphics::adapter::DeviceFeatures;
+
+mod tests;
+
+/// Topology of primitives which are assembled from vertices.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub enum Topology {
+    /// Each vertex is a separate point.
+    PointList,
+    /// Each pair of vertices is a separate line.
+    LineList,
+    /// Each vertex after the first one forms a line with the previous vertex.
+    LineStrip,
+    /// Each three vertices are a separate triangle.
+    #[default]
+    TriangleList,
+    /// Each vertex after the first two forms a triangle with two previous vertices.
+    TriangleStrip,
+    /// Each vertex after the first two forms a triangle with the previous and the first vertex.
+    ///
+    /// Not supported by devices with portability subset, such as ones on macOS.
+    ///
+    TriangleFan,
+}
+
+impl Topology {
+    /// If strips of primitives with this topology could be restarted by special index.
+    pub const fn supports_primitive_restart(self) -> bool {
+        matches!(
+            self,
+            Self::LineStrip | Self::TriangleStrip | Self::TriangleFan
+        )
+    }
+}
+
+/// Assembly of primitives from vertices and indices of the mesh.
+///
+/// Built-in pipelines which draw the same mesh as pipelines of game objects,
+/// such as the error material and picking of game objects, are built for each of these states.
+///
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub(crate) struct PrimitiveState {
+    /// Topology of primitives.
+    pub topology: Topology,
+    /// If strips are restarted by the maximal value of the index type.
+    pub primitive_restart: bool,
+}
+
+impl PrimitiveState {
+    /// Checks if mesh with or without restart indices could be drawn with this state.
+    pub fn draws(self, restart_indices: bool) -> bool {
+        self.primitive_restart || !restart_indices
+    }
+}
+
+/// Faces of triangles which are discarded before rasterization.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub enum CullMode {
+    /// No triangles are discarded.
+    None,
+    /// Front-facing triangles are discarded.
+    Front,
+    /// Back-facing triangles are discarded.
+    #[default]
+    Back,
+}
+
+/// Rasterization of polygons.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub enum PolygonMode {
+    /// Polygons are filled.
+    #[default]
+    Fill,
+    /// Edges of polygons are drawn as lines, which is useful to debug geometry.
+    ///
+    /// Requires `fill_mode_non_solid` feature of the device.
+    ///
+    Line,
+    /// Vertices of polygons are drawn as points.
+    ///
+    /// Requires `fill_mode_non_solid` feature of the device.
+    ///
+    Point,
+}
+
+/// Orientation of vertices of front-facing triangles on the screen.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub enum FrontFace {
+    /// Triangles with counter-clockwise vertices are front-facing.
+    #[default]
+    CounterClockwise,
+    /// Triangles with clockwise vertices are front-facing.
+    Clockwise,
+}
+
+/// Blending of colors of fragments with colors which are already stored in the attachment.
+///
+/// Results of all modes except [`Opaque`](Self::Opaque) depend on the order of draw calls.
+///
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub enum BlendMode {
+    /// Colors of fragments replace stored colors.
+    #[default]
+    Opaque,
+    /// Colors of fragments are blended by their alpha: `src * src_alpha + dst * (1 - src_alpha)`.
+    AlphaBlend,
+    /// Colors of fragments are multiplied by their alpha and added to stored colors.
+    Additive,
+    /// Colors of fragments are already multiplied by their alpha: `src + dst * (1 - src_alpha)`.
+    PremultipliedAlpha,
+    /// Blending is described by the state of Vulkan API directly.
+    Custom(AttachmentBlend),
+}
+
+impl BlendMode {
+    /// Blend state of the attachment which corresponds to this mode.
+    pub fn attachment_blend(&self) -> AttachmentBlend {
+        let blend = |source, destination| AttachmentBlend {
+            enabled: true,
+            color_op: BlendOp::Add,
+            color_source: source,
+            color_destination: destination,
+            alpha_op: BlendOp::Add,
+            alpha_source: BlendFactor::One,
+            alpha_destination: destination,
+            ..AttachmentBlend::pass_through()
+        };
+        match self {
+            Self::Opaque => AttachmentBlend::pass_through(),
+            Self::AlphaBlend => blend(BlendFactor::SrcAlpha, BlendFactor::OneMinusSrcAlpha),
+            Self::Additive => blend(BlendFactor::SrcAlpha, BlendFactor::One),
+            Self::PremultipliedAlpha => blend(BlendFactor::One, BlendFactor::OneMinusSrcAlpha),
+            Self::Custom(blend) => blend.clone(),
+        }
+    }
+}
+
+/// Variable state of graphics pipeline of game objects.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphicsPipelineDescriptor {
+    topology: Topology,
+    primitive_restart: bool,
+    polygon_mode: PolygonMode,
+    cull_mode: CullMode,
+    front_face: FrontFace,
+    line_width: f32,
+    depth_write: bool,
+    blend_mode: BlendMode,
+    attachment_blend_modes: Vec<BlendMode>,
+}
+
+impl GraphicsPipelineDescriptor {
+    /// Creates descriptor of the default pipeline: opaque triangle list with back face culling
+    /// which writes depth (if the scene has depth buffer).
+    pub const fn new() -> Self {
+        Self {
+            topology: Topology::TriangleList,
+            primitive_restart: false,
+            polygon_mode: PolygonMode::Fill,
+            cull_mode: CullMode::Back,
+            front_face: FrontFace::CounterClockwise,
+            line_width: 1.0,
+            depth_write: true,
+            blend_mode: BlendMode::Opaque,
+            attachment_blend_modes: Vec::new(),
+        }
+    }
+
+    /// Creates descriptor of the pipeline for transparent game objects,
+    /// which blends colors by their alpha and tests depth without writing it.
+    pub fn transparent() -> Self {
+        Self::new()
+            .with_depth_write(false)
+            .with_blend_mode(BlendMode::AlphaBlend)
+    }
+
+    /// Sets topology of primitives. Default is [`Topology::TriangleList`].
+    pub const fn with_topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    /// Enables or disables restart of strips by the maximal value of the index type,
+    /// such as `u16::MAX` for 16-bit indices. Default is `false`.
+    ///
+    /// Could be enabled only for [topologies](Topology::supports_primitive_restart) of strips.
+    ///
+    pub const fn with_primitive_restart(mut self, primitive_restart: bool) -> Self {
+        self.primitive_restart = primitive_restart;
+        self
+    }
+
+    /// Sets rasterization of polygons. Default is [`PolygonMode::Fill`].
+    pub const fn with_polygon_mode(mut self, polygon_mode: PolygonMode) -> Self {
+        self.polygon_mode = polygon_mode;
+        self
+    }
+
+    /// Sets faces of triangles which are discarded. Default is [`CullMode::Back`].
+    pub const fn with_cull_mode(mut self, cull_mode: CullMode) -> Self {
+        self.cull_mode = cull_mode;
+        self
+    }
+
+    /// Sets orientation of front-facing triangles. Default is [`FrontFace::CounterClockwise`].
+    pub const fn with_front_face(mut self, front_face: FrontFace) -> Self {
+        self.front_face = front_face;
+        self
+    }
+
+    /// Sets width of rasterized lines in pixels. Default is `1.0`.
+    ///
+    /// Other widths require `wide_lines` feature of the device.
+    ///
+    pub const fn with_line_width(mut self, line_width: f32) -> Self {
+        self.line_width = line_width;
+        self
+    }
+
+    /// Enables or disables writing of depth of fragments into depth buffer.
+    ///
+    /// Depth test is still enabled if the scene has depth buffer. Default is `true`.
+    ///
+    pub const fn with_depth_write(mut self, depth_write: bool) -> Self {
+        self.depth_write = depth_write;
+        self
+    }
+
+    /// Sets blending of all color attachments. Default is [`BlendMode::Opaque`].
+    pub fn with_blend_mode(mut self, blend_mode: BlendMode) -> Self {
+        self.blend_mode = blend_mode;
+        self
+    }
+
+    /// Sets blending of each color attachment in order of attachments,
+    /// overriding [blend mode](Self::with_blend_mode) of all attachments.
+    ///
+    /// Count of modes must match count of color attachments of the subpass.
+    /// Empty list restores blend mode of all attachments.
+    ///
+    pub fn with_attachment_blend_modes(mut self, blend_modes: Vec<BlendMode>) -> Self {
+        self.attachment_blend_modes = blend_modes;
+        self
+    }
+
+    /// Topology of primitives.
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    /// If strips are restarted by the maximal value of the index type.
+    pub fn primitive_restart(&self) -> bool {
+        self.primitive_restart
+    }
+
+    /// Assembly of primitives of this pipeline.
+    pub(crate) fn primitive_state(&self) -> PrimitiveState {
+        PrimitiveState {
+            topology: self.topology,
+            primitive_restart: self.primitive_restart,
+        }
+    }
+
+    /// Rasterization of polygons.
+    pub fn polygon_mode(&self) -> PolygonMode {
+        self.polygon_mode
+    }
+
+    /// Faces of triangles which are discarded.
+    pub fn cull_mode(&self) -> CullMode {
+        self.cull_mode
+    }
+
+    /// Orientation of front-facing triangles.
+    pub fn front_face(&self) -> FrontFace {
+        self.front_face
+    }
+
+    /// Width of rasterized lines in pixels.
+    pub fn line_width(&self) -> f32 {
+        self.line_width
+    }
+
+    /// Features of the device which are needed to create pipeline with this state.
+    pub fn required_features(&self) -> DeviceFeatures {
+        DeviceFeatures {
+            fill_mode_non_solid: self.polygon_mode != PolygonMode::Fill,
+            wide_lines: self.line_width != 1.0,
+            ..DeviceFeatures::NONE
+        }
+    }
+
+    /// If depth of fragments is written into depth buffer.
+    pub fn depth_write(&self) -> bool {
+        self.depth_write
+    }
+
+    /// Blending of all color attachments.
+    pub fn blend_mode(&self) -> &BlendMode {
+        &self.blend_mode
+    }
+
+    /// Blending of each color attachment, or empty if all of them use the same blend mode.
+    pub fn attachment_blend_modes(&self) -> &[BlendMode] {
+        &self.attachment_blend_modes
+    }
+
+    /// Blend states of provided count of color attachments,
+    /// or `None` if count of attachment blend modes does not match it.
+    pub(crate) fn attachment_blends(&self, color_attachments: u32) -> Option<Vec<AttachmentBlend>> {
+        if self.attachment_blend_modes.is_empty() {
+            let blend = self.blend_mode.attachment_blend();
+            return Some(vec![blend; color_attachments as usize]);
+        }
+        if self.attachment_blend_modes.len() != color_attachments as usize {
+            return None;
+        }
+        let blends = self.attachment_blend_modes.iter();
+        Some(blends.map(BlendMode::attachment_blend).collect())
+    }
+}
+
+impl Default for GraphicsPipelineDescriptor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Identifier of graphics pipeline of game objects.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub struct PipelineId(pub(crate) usize);
+
+impl PipelineId {
+    /// Identifier of the default pipeline, which is created with the default descriptor.
+    pub const DEFAULT: Self = Self(0);
+}
+
+/// Pipelines which are assigned to game objects, in order of their ids.
+///
+/// Objects without assigned pipeline are drawn with the default one.
+///
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PipelineAssignments {
+    pipelines: Vec<PipelineId>,
+}
+
+impl PipelineAssignments {
+    /// Pipeline which is assigned to the object with provided id.
+    pub fn get(&self, object: usize) -> PipelineId {
+        self.pipelines.get(object).copied().unwrap_or_default()
+    }
+
+    /// Assigns provided pipeline to the object with provided id.
+    pub fn set(&mut self, object: usize, pipeline: PipelineId) {
+        if self.pipelines.len() <= object {
+            self.pipelines.resize(object + 1, PipelineId::DEFAULT);
+        }
+        self.pipelines[object] = pipeline;
+    }
+
+    /// Pipeline of the object with provided id and its primitive state,
+    /// or `None` if the object cannot be drawn because the mesh contains restart indices
+    /// which this pipeline does not enable.
+    ///
+    /// Primitive states of pipelines are provided in order of their ids.
+    ///
+    pub fn resolve(
+        &self,
+        object: usize,
+        states: &[PrimitiveState],
+        restart_indices: bool,
+    ) -> Option<(PipelineId, PrimitiveState)> {
+        let pipeline = self.get(object);
+        let state = states[pipeline.0];
+        state.draws(restart_indices).then_some((pipeline, state))
+    }
+
+    /// Draws all objects with the default pipeline.
+    pub fn clear(&mut self) {
+        self.pipelines.clear();
+    }
+}
@@ -0,0 +1,244 @@
+#![cfg(test)]
+
+use vulkano::pipeline::blend::{AttachmentBlend, BlendFactor, BlendOp};
+
+use crate::graphics::adapter::DeviceFeatures;
+use crate::graphics::color::{linear_to_srgb, Color};
+use crate::graphics::index::Indices;
+use crate::graphics::lines;
+
+use super::{
+    BlendMode, CullMode, FrontFace, GraphicsPipelineDescriptor, PipelineAssignments, PipelineId,
+    PolygonMode, PrimitiveState, Topology,
+};
+
+/// Evaluates color blending of the attachment like the device does, for linear colors.
+fn blend(blend: &AttachmentBlend, src: [f32; 4], dst: [f32; 4]) -> [f32; 4] {
+    if !blend.enabled {
+        return src;
+    }
+    let factor = |factor, channel: usize| match factor {
+        BlendFactor::Zero => 0.0,
+        BlendFactor::One => 1.0,
+        BlendFactor::SrcColor => src[channel],
+        BlendFactor::OneMinusSrcColor => 1.0 - src[channel],
+        BlendFactor::DstColor => dst[channel],
+        BlendFactor::OneMinusDstColor => 1.0 - dst[channel],
+        BlendFactor::SrcAlpha => src[3],
+        BlendFactor::OneMinusSrcAlpha => 1.0 - src[3],
+        BlendFactor::DstAlpha => dst[3],
+        BlendFactor::OneMinusDstAlpha => 1.0 - dst[3],
+        // Blend modes use neither blend constants nor dual source blending.
+        other => panic!("unexpected blend factor {:?}", other),
+    };
+    let op = |op, src: f32, dst: f32| match op {
+        BlendOp::Add => src + dst,
+        BlendOp::Subtract => src - dst,
+        BlendOp::ReverseSubtract => dst - src,
+        BlendOp::Min => src.min(dst),
+        BlendOp::Max => src.max(dst),
+    };
+    let mut result = [0.0; 4];
+    for channel in 0..3 {
+        let src = src[channel] * factor(blend.color_source, channel);
+        let dst = dst[channel] * factor(blend.color_destination, channel);
+        result[channel] = op(blend.color_op, src, dst).clamp(0.0, 1.0);
+    }
+    let src_alpha = src[3] * factor(blend.alpha_source, 3);
+    let dst_alpha = dst[3] * factor(blend.alpha_destination, 3);
+    result[3] = op(blend.alpha_op, src_alpha, dst_alpha).clamp(0.0, 1.0);
+    result
+}
+
+/// Draws two overlapping translucent quads, red and blue ones, over black background
+/// in provided order and returns color of their overlapping area.
+fn overlap(mode: &BlendMode, red_first: bool) -> [f32; 4] {
+    let blend_state = mode.attachment_blend();
+    let red = [1.0, 0.0, 0.0, 0.5];
+    let blue = [0.0, 0.0, 1.0, 0.5];
+    let (first, second) = if red_first { (red, blue) } else { (blue, red) };
+    let background = [0.0, 0.0, 0.0, 1.0];
+    let color = self::blend(&blend_state, first, background);
+    self::blend(&blend_state, second, color)
+}
+
+fn assert_color_eq(actual: [f32; 4], expected: [f32; 4]) {
+    let close = actual
+        .iter()
+        .zip(&expected)
+        .all(|(a, e)| (a - e).abs() < 1e-5);
+    assert!(close, "expected {:?}, got {:?}", expected, actual);
+}
+
+#[test]
+fn default_pipeline_is_opaque() {
+    let descriptor = GraphicsPipelineDescriptor::default();
+    assert_eq!(descriptor, GraphicsPipelineDescriptor::new());
+    assert_eq!(descriptor.topology(), Topology::TriangleList);
+    assert_eq!(descriptor.polygon_mode(), PolygonMode::Fill);
+    assert_eq!(descriptor.cull_mode(), CullMode::Back);
+    assert_eq!(descriptor.front_face(), FrontFace::CounterClockwise);
+    assert_eq!(descriptor.line_width(), 1.0);
+    assert!(descriptor.depth_write());
+    assert_eq!(descriptor.blend_mode(), &BlendMode::Opaque);
+    assert!(descriptor.attachment_blend_modes().is_empty());
+}
+
+#[test]
+fn transparent_pipeline_does_not_write_depth() {
+    let descriptor = GraphicsPipelineDescriptor::transparent().with_cull_mode(CullMode::None);
+    assert!(!descriptor.depth_write());
+    assert_eq!(descriptor.blend_mode(), &BlendMode::AlphaBlend);
+    assert_eq!(descriptor.cull_mode(), CullMode::None);
+}
+
+#[test]
+fn only_strips_could_be_restarted() {
+    assert!(Topology::LineStrip.supports_primitive_restart());
+    assert!(Topology::TriangleStrip.supports_primitive_restart());
+    assert!(Topology::TriangleFan.supports_primitive_restart());
+    assert!(!Topology::PointList.supports_primitive_restart());
+    assert!(!Topology::TriangleList.supports_primitive_restart());
+    assert!(!GraphicsPipelineDescriptor::new().primitive_restart());
+}
+
+#[test]
+fn default_pipeline_requires_no_features() {
+    let descriptor = GraphicsPipelineDescriptor::new().with_front_face(FrontFace::Clockwise);
+    assert_eq!(descriptor.required_features(), DeviceFeatures::NONE);
+}
+
+#[test]
+fn wireframe_requires_non_solid_fill_mode() {
+    let descriptor = GraphicsPipelineDescriptor::new()
+        .with_polygon_mode(PolygonMode::Line)
+        .with_cull_mode(CullMode::None);
+    let features = descriptor.required_features();
+    assert_eq!(features.names(), vec!["fill_mode_non_solid"]);
+
+    let descriptor = descriptor.with_line_width(2.0);
+    let features = descriptor.required_features();
+    assert_eq!(features.names(), vec!["fill_mode_non_solid", "wide_lines"]);
+}
+
+#[test]
+fn opaque_mode_replaces_stored_color() {
+    let color = self::overlap(&BlendMode::Opaque, true);
+    assert_color_eq(color, [0.0, 0.0, 1.0, 0.5]);
+}
+
+#[test]
+fn alpha_blending_depends_on_draw_order() {
+    let red_first = self::overlap(&BlendMode::AlphaBlend, true);
+    let blue_first = self::overlap(&BlendMode::AlphaBlend, false);
+    assert_color_eq(red_first, [0.25, 0.0, 0.5, 1.0]);
+    assert_color_eq(blue_first, [0.5, 0.0, 0.25, 1.0]);
+}
+
+#[test]
+fn additive_blending_does_not_depend_on_draw_order() {
+    let red_first = self::overlap(&BlendMode::Additive, true);
+    let blue_first = self::overlap(&BlendMode::Additive, false);
+    assert_color_eq(red_first, [0.5, 0.0, 0.5, 1.0]);
+    assert_color_eq(red_first, blue_first);
+}
+
+#[test]
+fn premultiplied_alpha_matches_alpha_blending() {
+    let src = Color::linear(0.8, 0.4, 0.2, 0.5).to_linear();
+    let premultiplied = [src[0] * src[3], src[1] * src[3], src[2] * src[3], src[3]];
+    let dst = [0.1, 0.2, 0.3, 1.0];
+
+    let straight = self::blend(&BlendMode::AlphaBlend.attachment_blend(), src, dst);
+    let mode = BlendMode::PremultipliedAlpha.attachment_blend();
+    let premultiplied = self::blend(&mode, premultiplied, dst);
+    assert_color_eq(premultiplied, straight);
+}
+
+#[test]
+fn custom_mode_is_used_as_is() {
+    let custom = AttachmentBlend {
+        color_op: BlendOp::Max,
+        ..AttachmentBlend::alpha_blending()
+    };
+    let mode = BlendMode::Custom(custom.clone());
+    assert_eq!(mode.attachment_blend(), custom);
+}
+
+#[test]
+fn srgb_attachment_blends_in_linear_space() {
+    // Device blends decoded colors and encodes the result into sRGB attachment.
+    let linear = self::blend(
+        &BlendMode::AlphaBlend.attachment_blend(),
+        [1.0, 0.0, 0.0, 0.5],
+        [0.0, 0.0, 1.0, 1.0],
+    );
+    let stored = linear_to_srgb(linear[0]);
+    assert!((linear[0] - 0.5).abs() < 1e-5);
+    assert!(stored > 0.7, "encoded half of red is {}", stored);
+}
+
+#[test]
+fn attachment_blend_modes_match_color_attachments() {
+    let descriptor = GraphicsPipelineDescriptor::transparent();
+    let blends = descriptor.attachment_blends(2).unwrap();
+    assert_eq!(blends, vec![BlendMode::AlphaBlend.attachment_blend(); 2]);
+
+    let modes = vec![BlendMode::Additive, BlendMode::Opaque];
+    let descriptor = descriptor.with_attachment_blend_modes(modes);
+    let blends = descriptor.attachment_blends(2).unwrap();
+    assert_eq!(blends[0], BlendMode::Additive.attachment_blend());
+    assert_eq!(blends[1], AttachmentBlend::pass_through());
+    assert_eq!(descriptor.attachment_blends(1), None);
+}
+
+#[test]
+fn objects_use_default_pipeline_until_assigned() {
+    let mut assignments = PipelineAssignments::default();
+    assert_eq!(assignments.get(3), PipelineId::DEFAULT);
+
+    assignments.set(2, PipelineId(1));
+    assert_eq!(assignments.get(0), PipelineId::DEFAULT);
+    assert_eq!(assignments.get(2), PipelineId(1));
+
+    assignments.clear();
+    assert_eq!(assignments.get(2), PipelineId::DEFAULT);
+}
+
+#[test]
+fn picking_of_restart_mesh_uses_restart_state() {
+    let strip = GraphicsPipelineDescriptor::new()
+        .with_topology(Topology::TriangleStrip)
+        .with_primitive_restart(true);
+    let states = [
+        GraphicsPipelineDescriptor::new().primitive_state(),
+        strip.primitive_state(),
+    ];
+    let mesh = Indices::U16(vec![0, 1, 2, 3, u16::MAX, 4, 5, 6]);
+    let restart_indices = mesh.contains_restart();
+
+    // Pick request right after the mesh is set: default pipeline cannot draw it.
+    let mut assignments = PipelineAssignments::default();
+    assert_eq!(assignments.resolve(0, &states, restart_indices), None);
+
+    assignments.set(0, PipelineId(1));
+    let (pipeline, state) = assignments.resolve(0, &states, restart_indices).unwrap();
+    assert_eq!(pipeline, PipelineId(1));
+    assert_eq!(state.topology, Topology::TriangleStrip);
+    assert!(state.primitive_restart);
+}
+
+#[test]
+fn lines_are_picked_as_lines() {
+    let states = [
+        GraphicsPipelineDescriptor::new().primitive_state(),
+        lines::line_pipeline().primitive_state(),
+    ];
+    let mut assignments = PipelineAssignments::default();
+    assignments.set(1, PipelineId(1));
+    let (_, state) = assignments.resolve(1, &states, false).unwrap();
+    assert_eq!(state.topology, Topology::LineList);
+    assert!(!state.primitive_restart);
+    let (_, state) = assignments.resolve(0, &states, false).unwrap();
+    assert_eq!(state, PrimitiveState::default());
+}
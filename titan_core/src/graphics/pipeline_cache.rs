@@ -0,0 +1,216 @@
+//! Pipeline cache of game engine which is persisted to disk between runs.
+//!
+//! Data of the cache is loaded on startup only if its header matches
+//! the current physical device, and is saved back on drop or on request.
+//! Missing, corrupt or stale files result in an empty cache.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use thiserror::Error;
+use vulkano::device::physical::PhysicalDevice;
+use vulkano::device::Device;
+use vulkano::pipeline::cache::PipelineCache as VkPipelineCache;
+use vulkano::OomError;
+
+mod tests;
+
+/// Name of the pipeline cache file in the cache directory of your game.
+pub const PIPELINE_CACHE_FILE: &str = "pipeline_cache.bin";
+
+/// Length in bytes of the header of pipeline cache data (`VK_PIPELINE_CACHE_HEADER_VERSION_ONE`).
+pub const HEADER_LEN: usize = 32;
+
+/// Version of the header of pipeline cache data which is supported.
+const HEADER_VERSION_ONE: u32 = 1;
+
+/// Header of pipeline cache data which identifies the device it was created by.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct PipelineCacheHeader {
+    /// Vendor ID of the physical device.
+    pub vendor_id: u32,
+    /// Device ID of the physical device.
+    pub device_id: u32,
+    /// Pipeline cache UUID of the physical device.
+    pub uuid: [u8; 16],
+}
+
+impl PipelineCacheHeader {
+    /// Header which pipeline cache data of provided physical device must have.
+    pub fn of(physical_device: PhysicalDevice) -> Self {
+        let properties = physical_device.properties();
+        Self {
+            vendor_id: properties.vendor_id,
+            device_id: properties.device_id,
+            uuid: properties.pipeline_cache_uuid,
+        }
+    }
+
+    /// Parses header of provided pipeline cache data.
+    pub fn parse(data: &[u8]) -> Result<Self, CacheDataError> {
+        if data.len() < HEADER_LEN {
+            return Err(CacheDataError::Truncated(data.len()));
+        }
+        let word = |index: usize| {
+            let bytes = [
+                data[index],
+                data[index + 1],
+                data[index + 2],
+                data[index + 3],
+            ];
+            u32::from_ne_bytes(bytes)
+        };
+        let (len, version) = (word(0), word(4));
+        if version != HEADER_VERSION_ONE {
+            return Err(CacheDataError::Version(version));
+        }
+        if (len as usize) < HEADER_LEN || len as usize > data.len() {
+            return Err(CacheDataError::HeaderLength(len));
+        }
+        let mut uuid = [0; 16];
+        uuid.copy_from_slice(&data[16..32]);
+        Ok(Self {
+            vendor_id: word(8),
+            device_id: word(12),
+            uuid,
+        })
+    }
+}
+
+/// Reason why pipeline cache data cannot be used.
+#[derive(Debug, Error, Eq, PartialEq)]
+pub enum CacheDataError {
+    #[error("data of {0} bytes is too short for pipeline cache header")]
+    Truncated(usize),
+
+    #[error("pipeline cache header version {0} is not supported")]
+    Version(u32),
+
+    #[error("pipeline cache header length {0} is invalid")]
+    HeaderLength(u32),
+
+    #[error("pipeline cache was created by another device or driver")]
+    DeviceMismatch,
+}
+
+/// Checks that pipeline cache data has the header of the expected device.
+pub fn validate(data: &[u8], expected: &PipelineCacheHeader) -> Result<(), CacheDataError> {
+    match PipelineCacheHeader::parse(data)? == *expected {
+        true => Ok(()),
+        false => Err(CacheDataError::DeviceMismatch),
+    }
+}
+
+/// Error that can happen when saving pipeline cache.
+#[derive(Debug, Error)]
+pub enum PipelineCacheError {
+    #[error("pipeline cache data retrieval failure: {0}")]
+    OutOfMemory(#[from] OomError),
+
+    #[error("pipeline cache file writing failure: {0}")]
+    Io(#[from] io::Error),
+}
+
+/// Pipeline cache which is loaded from and saved to the file.
+pub struct PipelineCache {
+    cache: Arc<VkPipelineCache>,
+    path: Option<PathBuf>,
+}
+
+impl PipelineCache {
+    /// Creates pipeline cache, loading its data from the file with provided path, if any.
+    ///
+    /// Data is discarded if the file is missing, corrupt or was created by another device.
+    ///
+    pub fn load(device: Arc<Device>, path: Option<PathBuf>) -> Result<Self, OomError> {
+        let header = PipelineCacheHeader::of(device.physical_device());
+        let data = path
+            .as_deref()
+            .and_then(|path| self::read_data(path, &header));
+        let cache = match data {
+            // SAFETY: header of data was checked against the device.
+            Some(data) => unsafe { VkPipelineCache::with_data(device, &data)? },
+            None => VkPipelineCache::empty(device)?,
+        };
+        Ok(Self { cache, path })
+    }
+
+    /// Underlying pipeline cache which pipelines should be built with.
+    pub fn cache(&self) -> Arc<VkPipelineCache> {
+        self.cache.clone()
+    }
+
+    /// Path of the file which pipeline cache is saved to, if any.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Saves data of pipeline cache to the file, if any.
+    pub fn save(&self) -> Result<(), PipelineCacheError> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let data = self.cache.get_data()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // Write whole file at once, so it is never left truncated by interrupted write.
+        let temp = path.with_extension("tmp");
+        fs::write(&temp, data)?;
+        fs::rename(temp, path)?;
+        Ok(())
+    }
+}
+
+impl Drop for PipelineCache {
+    fn drop(&mut self) {
+        match self.save() {
+            Ok(()) => log::info!("pipeline cache was saved"),
+            Err(error) => log::warn!("pipeline cache saving failure: {}", error),
+        }
+    }
+}
+
+/// Reads pipeline cache data from the file, if it is valid for the device.
+fn read_data(path: &Path, header: &PipelineCacheHeader) -> Option<Vec<u8>> {
+    let data = match fs::read(path) {
+        Ok(data) => data,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return None,
+        Err(error) => {
+            log::warn!("pipeline cache file reading failure: {}", error);
+            return None;
+        }
+    };
+    match self::validate(&data, header) {
+        Ok(()) => {
+            log::info!("pipeline cache of {} bytes was loaded", data.len());
+            Some(data)
+        }
+        Err(error) => {
+            log::warn!("pipeline cache is discarded: {}", error);
+            None
+        }
+    }
+}
+
+/// Default directory for cache files of your game with provided name.
+///
+/// It is the platform cache directory: `$XDG_CACHE_HOME` or `~/.cache` on Linux,
+/// `~/Library/Caches` on macOS and `%LOCALAPPDATA%` on Windows.
+///
+pub fn default_cache_dir(name: &str) -> Option<PathBuf> {
+    let var = |key: &str| std::env::var_os(key).filter(|value| !value.is_empty());
+    let base = if cfg!(windows) {
+        var("LOCALAPPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        var("HOME").map(|home| Path::new(&home).join("Library").join("Caches"))
+    } else {
+        var("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| var("HOME").map(|home| Path::new(&home).join(".cache")))
+    };
+    Some(base?.join(name))
+}
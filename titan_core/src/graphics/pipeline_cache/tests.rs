@@ -0,0 +1,68 @@
+#![cfg(test)]
+
+use super::{validate, CacheDataError, PipelineCacheHeader, HEADER_LEN};
+
+const HEADER: PipelineCacheHeader = PipelineCacheHeader {
+    vendor_id: 0x10de,
+    device_id: 0x1f08,
+    uuid: [7; 16],
+};
+
+/// Pipeline cache data with provided header and some payload.
+fn data(header: &PipelineCacheHeader) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend_from_slice(&(HEADER_LEN as u32).to_ne_bytes());
+    data.extend_from_slice(&1u32.to_ne_bytes());
+    data.extend_from_slice(&header.vendor_id.to_ne_bytes());
+    data.extend_from_slice(&header.device_id.to_ne_bytes());
+    data.extend_from_slice(&header.uuid);
+    data.extend_from_slice(&[0xab; 64]);
+    data
+}
+
+#[test]
+fn header_is_parsed() {
+    assert_eq!(PipelineCacheHeader::parse(&data(&HEADER)), Ok(HEADER));
+    assert_eq!(validate(&data(&HEADER), &HEADER), Ok(()));
+}
+
+#[test]
+fn other_device_is_rejected() {
+    let other = PipelineCacheHeader {
+        uuid: [8; 16],
+        ..HEADER
+    };
+    assert_eq!(
+        validate(&data(&other), &HEADER),
+        Err(CacheDataError::DeviceMismatch),
+    );
+    let other = PipelineCacheHeader {
+        device_id: 0x2204,
+        ..HEADER
+    };
+    assert_eq!(
+        validate(&data(&other), &HEADER),
+        Err(CacheDataError::DeviceMismatch),
+    );
+}
+
+#[test]
+fn corrupt_data_is_rejected() {
+    let data = data(&HEADER);
+    assert_eq!(
+        validate(&data[..20], &HEADER),
+        Err(CacheDataError::Truncated(20)),
+    );
+    assert_eq!(validate(&[], &HEADER), Err(CacheDataError::Truncated(0)));
+
+    let mut version = data.clone();
+    version[4..8].copy_from_slice(&2u32.to_ne_bytes());
+    assert_eq!(validate(&version, &HEADER), Err(CacheDataError::Version(2)));
+
+    let mut length = data;
+    length[0..4].copy_from_slice(&16u32.to_ne_bytes());
+    assert_eq!(
+        validate(&length, &HEADER),
+        Err(CacheDataError::HeaderLength(16)),
+    );
+}
@@ -0,0 +1,193 @@
+//! Presentation utilities for graphics backend of game engine.
+
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use vulkano::device::Queue;
+use vulkano::swapchain::{self, AcquireError, Swapchain, SwapchainAcquireFuture};
+use vulkano::sync::{FlushError, GpuFuture};
+use winit::window::Window;
+
+use super::fence::FrameFence;
+use super::renderer::error::PresentThreadError;
+
+/// Future which is returned after presenting of the image.
+pub type PresentFuture = Box<dyn GpuFuture + Send + Sync>;
+
+/// Frame which was submitted for presentation.
+pub struct PresentedFrame {
+    /// Future which signals when presentation of the image is finished.
+    pub future: PresentFuture,
+    /// Fence which is signaled when the frame is finished.
+    pub fence: Arc<dyn FrameFence>,
+}
+
+/// Presents rendered image to the swapchain after provided future.
+pub fn present(
+    future: PresentFuture,
+    present_queue: Arc<Queue>,
+    swapchain: Arc<Swapchain<Arc<Window>>>,
+    image_index: usize,
+) -> Result<PresentedFrame, FlushError> {
+    let future = future
+        .then_swapchain_present(present_queue, swapchain, image_index)
+        .then_signal_fence_and_flush()?;
+    let future = Arc::new(future);
+    Ok(PresentedFrame {
+        future: Box::new(future.clone()),
+        fence: future,
+    })
+}
+
+/// Submits frame which is rendered offscreen, so there is no image to present.
+pub fn submit(future: PresentFuture) -> Result<PresentedFrame, FlushError> {
+    let future = Arc::new(future.then_signal_fence_and_flush()?);
+    Ok(PresentedFrame {
+        future: Box::new(future.clone()),
+        fence: future,
+    })
+}
+
+/// Image of the swapchain acquired for the next frame: its index,
+/// if swapchain is suboptimal and future which signals when the image is ready.
+pub type NextImage = (usize, bool, SwapchainAcquireFuture<Arc<Window>>);
+
+/// Request to present an image which was rendered by the render thread.
+struct PresentRequest {
+    future: PresentFuture,
+    swapchain: Arc<Swapchain<Arc<Window>>>,
+    image_index: usize,
+}
+
+/// Result of presenting an image on the present thread.
+pub struct PresentOutcome {
+    /// Frame which was presented.
+    pub result: Result<PresentedFrame, FlushError>,
+    /// Image acquired for the next frame after successful presentation.
+    pub next_image: Option<Result<NextImage, AcquireError>>,
+    /// Time spent by the present thread to present the image and acquire the next one.
+    pub present_time: Duration,
+}
+
+/// Returns `true` if presentation from the dedicated thread is supported on current platform.
+///
+/// On macOS surface must be accessed only from the main thread,
+/// so presentation cannot be moved onto another thread.
+///
+pub const fn present_thread_supported() -> bool {
+    !cfg!(target_os = "macos")
+}
+
+/// Dedicated thread which presents images to the swapchain.
+///
+/// Render thread hands off completed submissions through the channel,
+/// so it is not blocked by present operation (for example, waiting for vertical sync).
+///
+pub struct PresentThread {
+    sender: Option<Sender<PresentRequest>>,
+    receiver: Receiver<PresentOutcome>,
+    handle: Option<JoinHandle<()>>,
+    pending: bool,
+}
+
+impl PresentThread {
+    /// Spawns new present thread which owns provided present queue.
+    pub fn new(present_queue: Arc<Queue>) -> Result<Self, PresentThreadError> {
+        if !self::present_thread_supported() {
+            return Err(PresentThreadError::Unsupported);
+        }
+
+        let (sender, requests) = mpsc::channel::<PresentRequest>();
+        let (outcomes, receiver) = mpsc::channel();
+        let handle = thread::Builder::new()
+            .name("present".to_string())
+            .spawn(move || {
+                // Thread exits when the render thread drops its sender.
+                for request in requests {
+                    let start = Instant::now();
+                    let result = self::present(
+                        request.future,
+                        present_queue.clone(),
+                        request.swapchain.clone(),
+                        request.image_index,
+                    );
+                    // Acquire could block too, so it is done here instead of the render thread.
+                    let next_image = result
+                        .is_ok()
+                        .then(|| swapchain::acquire_next_image(request.swapchain, None));
+                    let outcome = PresentOutcome {
+                        result,
+                        next_image,
+                        present_time: start.elapsed(),
+                    };
+                    if outcomes.send(outcome).is_err() {
+                        break;
+                    }
+                }
+            })?;
+
+        Ok(Self {
+            sender: Some(sender),
+            receiver,
+            handle: Some(handle),
+            pending: false,
+        })
+    }
+
+    /// Hands off rendered image to the present thread.
+    ///
+    /// Result of the previous presentation must be retrieved before by [`wait`](Self::wait).
+    ///
+    pub fn present(
+        &mut self,
+        future: PresentFuture,
+        swapchain: Arc<Swapchain<Arc<Window>>>,
+        image_index: usize,
+    ) -> Result<(), PresentThreadError> {
+        debug_assert!(!self.pending, "previous presentation was not retrieved");
+        let request = PresentRequest {
+            future,
+            swapchain,
+            image_index,
+        };
+        self.sender
+            .as_ref()
+            .unwrap()
+            .send(request)
+            .map_err(|_| PresentThreadError::Disconnected)?;
+        self.pending = true;
+        Ok(())
+    }
+
+    /// Waits for the result of the last presentation, if any.
+    pub fn wait(&mut self) -> Result<Option<PresentOutcome>, PresentThreadError> {
+        if !self.pending {
+            return Ok(None);
+        }
+        self.pending = false;
+        let outcome = self
+            .receiver
+            .recv()
+            .map_err(|_| PresentThreadError::Disconnected)?;
+        Ok(Some(outcome))
+    }
+}
+
+impl Drop for PresentThread {
+    fn drop(&mut self) {
+        // Drain the last presentation so its future is not dropped while in use.
+        if let Ok(Some(outcome)) = self.wait() {
+            if let Ok(mut frame) = outcome.result {
+                frame.future.cleanup_finished();
+            }
+        }
+        drop(self.sender.take());
+        if let Some(handle) = self.handle.take() {
+            if handle.join().is_err() {
+                log::error!("present thread panicked");
+            }
+        }
+    }
+}
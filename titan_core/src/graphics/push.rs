@@ -0,0 +1,271 @@
+//! Push constants utilities for small per-draw data.
+//!
+//! Vulkano panics if push constants do not match the pipeline layout,
+//! so the data is validated by the engine before recording.
+//!
+//! Expected layout of push constants is validated against shader reflection
+//! when the pipeline is built and stored next to the pipeline,
+//! so each push only compares size and stages of the data with it.
+
+use std::mem;
+use std::sync::Arc;
+
+use thiserror::Error;
+use vulkano::command_buffer::AutoCommandBufferBuilder;
+use vulkano::device::DeviceOwned;
+use vulkano::pipeline::layout::{PipelineLayout, PipelineLayoutPcRange};
+use vulkano::pipeline::shader::ShaderStages;
+use vulkano::spirv::Spirv;
+
+use crate::graphics::params::{error::ParamBlockError, ParamBlock};
+
+mod tests;
+
+/// Error that can happen when pushing constants into the command buffer.
+#[derive(Debug, Error, Eq, PartialEq)]
+pub enum PushConstantsError {
+    #[error("push constants must not be empty")]
+    Empty,
+
+    #[error("offset {offset} and size {size} of push constants must be multiples of 4")]
+    Unaligned { offset: u32, size: u32 },
+
+    #[error("push constants end at {end} bytes, but device supports at most {max} bytes")]
+    TooLarge { end: u32, max: u32 },
+
+    #[error(
+        "bytes {offset}..{end} of push constants are not accessible by {stages:?} in the layout"
+    )]
+    OutOfRange {
+        offset: u32,
+        end: u32,
+        stages: ShaderStages,
+    },
+
+    #[error(
+        "push constants of {entry_point} expect {expected_size} bytes for {expected_stages:?}, \
+        but {size} bytes for {stages:?} were provided"
+    )]
+    LayoutMismatch {
+        entry_point: String,
+        expected_size: u32,
+        expected_stages: ShaderStages,
+        size: u32,
+        stages: ShaderStages,
+    },
+}
+
+/// Error that can happen when validating expected layout of push constants against the shader.
+#[derive(Debug, Error)]
+pub enum PushConstantsLayoutError {
+    #[error(
+        "push constants of {entry_point} are declared as bytes {offset}..{end} for {stages:?}, \
+        but shader declares {shader:?}"
+    )]
+    RangeMismatch {
+        entry_point: String,
+        offset: u32,
+        end: u32,
+        stages: ShaderStages,
+        shader: Vec<PipelineLayoutPcRange>,
+    },
+
+    #[error(
+        "push constants of {entry_point} have size {size}, but parameter block has size {block}"
+    )]
+    SizeMismatch {
+        entry_point: String,
+        size: u32,
+        block: usize,
+    },
+
+    #[error("push constants block of {entry_point} does not match the shader: {source}")]
+    Block {
+        entry_point: String,
+        source: ParamBlockError,
+    },
+}
+
+/// Expected layout of push constants of the pipeline.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PushConstantsLayout {
+    entry_point: String,
+    stages: ShaderStages,
+    size: u32,
+}
+
+impl PushConstantsLayout {
+    /// Creates layout of push constants of provided size in bytes,
+    /// which are pushed for provided stages of the shader entry point.
+    pub fn new(entry_point: impl Into<String>, stages: ShaderStages, size: u32) -> Self {
+        Self {
+            entry_point: entry_point.into(),
+            stages,
+            size,
+        }
+    }
+
+    /// Creates layout of push constants with the size of provided type.
+    pub fn of<T>(entry_point: impl Into<String>, stages: ShaderStages) -> Self {
+        Self::new(entry_point, stages, mem::size_of::<T>() as u32)
+    }
+
+    /// Name of shader entry point which declares these push constants.
+    pub fn entry_point(&self) -> &str {
+        &self.entry_point
+    }
+
+    /// Stages which push constants are accessible by.
+    pub fn stages(&self) -> ShaderStages {
+        self.stages
+    }
+
+    /// Size of push constants in bytes.
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// Validates this layout against push constant ranges reflected from shaders of the pipeline.
+    ///
+    /// Exactly one range must start at zero offset, have the same size
+    /// and be accessible by the same stages.
+    ///
+    pub fn validate(
+        &self,
+        ranges: &[PipelineLayoutPcRange],
+    ) -> Result<(), PushConstantsLayoutError> {
+        let matches = |range: &PipelineLayoutPcRange| {
+            range.offset == 0 && range.size == self.size && range.stages == self.stages
+        };
+        match ranges {
+            [range] if matches(range) => Ok(()),
+            _ => Err(PushConstantsLayoutError::RangeMismatch {
+                entry_point: self.entry_point.clone(),
+                offset: 0,
+                end: self.size,
+                stages: self.stages,
+                shader: ranges.to_vec(),
+            }),
+        }
+    }
+
+    /// Validates this layout against the block with provided type name declared in the shader.
+    ///
+    /// Names, types and offsets of parameters must be the same as in the shader,
+    /// and the size of parameter block must be the size of push constants.
+    ///
+    pub fn validate_block(
+        &self,
+        block: &ParamBlock,
+        spirv: &Spirv,
+        block_name: &str,
+    ) -> Result<(), PushConstantsLayoutError> {
+        if block.size() != self.size as usize {
+            return Err(PushConstantsLayoutError::SizeMismatch {
+                entry_point: self.entry_point.clone(),
+                size: self.size,
+                block: block.size(),
+            });
+        }
+        block
+            .validate(spirv, block_name)
+            .map_err(|source| PushConstantsLayoutError::Block {
+                entry_point: self.entry_point.clone(),
+                source,
+            })
+    }
+
+    /// Checks that data of provided type could be pushed for provided stages with this layout.
+    pub fn check<T>(&self, stages: ShaderStages) -> Result<(), PushConstantsError> {
+        let size = mem::size_of::<T>() as u32;
+        if size != self.size || stages != self.stages {
+            return Err(PushConstantsError::LayoutMismatch {
+                entry_point: self.entry_point.clone(),
+                expected_size: self.size,
+                expected_stages: self.stages,
+                size,
+                stages,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Checks that push constants at provided offset and size could be pushed for provided stages.
+///
+/// Each byte must be covered by some push constant range of the layout which is
+/// accessible by all provided stages, and the data must fit `maxPushConstantsSize` limit.
+///
+pub fn check_push_constants(
+    ranges: &[PipelineLayoutPcRange],
+    stages: ShaderStages,
+    offset: u32,
+    size: u32,
+    max_size: u32,
+) -> Result<(), PushConstantsError> {
+    if size == 0 {
+        return Err(PushConstantsError::Empty);
+    }
+    if !offset.is_multiple_of(4) || !size.is_multiple_of(4) {
+        return Err(PushConstantsError::Unaligned { offset, size });
+    }
+    let end = offset
+        .checked_add(size)
+        .ok_or(PushConstantsError::TooLarge {
+            end: u32::MAX,
+            max: max_size,
+        })?;
+    if end > max_size {
+        return Err(PushConstantsError::TooLarge { end, max: max_size });
+    }
+
+    // Walk over the bytes, jumping to the end of the range which covers the current one.
+    let mut current = offset;
+    while current < end {
+        let covering = ranges
+            .iter()
+            .filter(|range| range.stages.is_superset_of(&stages))
+            .filter(|range| range.offset <= current && current < range.offset + range.size)
+            .map(|range| range.offset + range.size)
+            .max();
+        match covering {
+            Some(range_end) => current = range_end,
+            None => {
+                return Err(PushConstantsError::OutOfRange {
+                    offset,
+                    end,
+                    stages,
+                })
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Pushes constants into the command buffer after checking them against the pipeline layout.
+///
+/// In debug builds type of the data is also checked against expected layout of push constants.
+///
+pub fn push_constants<L, T>(
+    builder: &mut AutoCommandBufferBuilder<L>,
+    layout: Arc<PipelineLayout>,
+    expected: &PushConstantsLayout,
+    stages: ShaderStages,
+    data: T,
+) -> Result<(), PushConstantsError>
+where
+    T: Copy + Send + Sync + 'static,
+{
+    if cfg!(debug_assertions) {
+        expected.check::<T>(stages)?;
+    }
+    let size = mem::size_of::<T>() as u32;
+    let max_size = layout
+        .device()
+        .physical_device()
+        .properties()
+        .max_push_constants_size;
+    self::check_push_constants(layout.push_constant_ranges(), stages, 0, size, max_size)?;
+    builder.push_constants(layout, 0, data);
+    Ok(())
+}
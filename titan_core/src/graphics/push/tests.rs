@@ -0,0 +1,112 @@
+#![cfg(test)]
+
+use vulkano::pipeline::layout::PipelineLayoutPcRange;
+use vulkano::pipeline::shader::ShaderStages;
+
+use super::{check_push_constants, PushConstantsError, PushConstantsLayout};
+
+const MAX_SIZE: u32 = 128;
+
+fn fragment() -> ShaderStages {
+    ShaderStages {
+        fragment: true,
+        ..ShaderStages::none()
+    }
+}
+
+fn vertex() -> ShaderStages {
+    ShaderStages {
+        vertex: true,
+        ..ShaderStages::none()
+    }
+}
+
+fn range(offset: u32, size: u32, stages: ShaderStages) -> PipelineLayoutPcRange {
+    PipelineLayoutPcRange {
+        offset,
+        size,
+        stages,
+    }
+}
+
+#[test]
+fn data_inside_of_range_is_accepted() {
+    let ranges = [range(0, 16, fragment())];
+    assert_eq!(
+        check_push_constants(&ranges, fragment(), 0, 16, MAX_SIZE),
+        Ok(())
+    );
+    assert_eq!(
+        check_push_constants(&ranges, fragment(), 4, 8, MAX_SIZE),
+        Ok(())
+    );
+}
+
+#[test]
+fn adjacent_ranges_cover_data() {
+    let all = ShaderStages {
+        vertex: true,
+        fragment: true,
+        ..ShaderStages::none()
+    };
+    let ranges = [range(0, 8, all), range(8, 8, fragment())];
+    assert_eq!(
+        check_push_constants(&ranges, fragment(), 0, 16, MAX_SIZE),
+        Ok(())
+    );
+    assert_eq!(
+        check_push_constants(&ranges, vertex(), 0, 16, MAX_SIZE),
+        Err(PushConstantsError::OutOfRange {
+            offset: 0,
+            end: 16,
+            stages: vertex(),
+        }),
+    );
+}
+
+#[test]
+fn invalid_data_is_rejected() {
+    let ranges = [range(0, 256, fragment())];
+    assert_eq!(
+        check_push_constants(&ranges, fragment(), 0, 0, MAX_SIZE),
+        Err(PushConstantsError::Empty),
+    );
+    assert_eq!(
+        check_push_constants(&ranges, fragment(), 2, 4, MAX_SIZE),
+        Err(PushConstantsError::Unaligned { offset: 2, size: 4 }),
+    );
+    assert_eq!(
+        check_push_constants(&ranges, fragment(), 0, 256, MAX_SIZE),
+        Err(PushConstantsError::TooLarge {
+            end: 256,
+            max: MAX_SIZE,
+        }),
+    );
+    assert!(check_push_constants(&[], fragment(), 0, 4, MAX_SIZE).is_err());
+}
+
+#[test]
+fn layout_matches_reflected_range() {
+    let layout = PushConstantsLayout::of::<[f32; 4]>("default.frag", fragment());
+    assert!(layout.validate(&[range(0, 16, fragment())]).is_ok());
+    assert!(layout.validate(&[range(0, 12, fragment())]).is_err());
+    assert!(layout.validate(&[range(0, 16, vertex())]).is_err());
+    assert!(layout.validate(&[]).is_err());
+}
+
+#[test]
+fn layout_checks_pushed_type() {
+    let layout = PushConstantsLayout::of::<[f32; 4]>("default.frag", fragment());
+    assert_eq!(layout.check::<[f32; 4]>(fragment()), Ok(()));
+    assert_eq!(
+        layout.check::<u32>(fragment()),
+        Err(PushConstantsError::LayoutMismatch {
+            entry_point: "default.frag".to_string(),
+            expected_size: 16,
+            expected_stages: fragment(),
+            size: 4,
+            stages: fragment(),
+        }),
+    );
+    assert!(layout.check::<[f32; 4]>(vertex()).is_err());
+}
@@ -0,0 +1,366 @@
+//! Occlusion, pipeline statistics and timestamp queries of frames.
+//!
+//! Each slot (one per frame in flight) of [`FrameQueryPool`] owns the same count of queries,
+//! which are reset at the start of the frame and written by the commands of that frame.
+//! Results are read without waiting right before the slot is reused,
+//! so results of frame `N` are read at frame `N + frames in flight`
+//! and reading never stalls the CPU: results which are not available yet are reported as such.
+//!
+//! Pipeline statistics queries require `pipeline_statistics_query` feature
+//! to be [enabled](crate::graphics::adapter::DeviceFeatures) on the device.
+
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use thiserror::Error;
+use vulkano::command_buffer::{
+    AutoCommandBufferBuilder, BeginQueryError, EndQueryError, ResetQueryPoolError,
+    WriteTimestampError,
+};
+use vulkano::device::Device;
+use vulkano::query::{
+    QueryControlFlags, QueryPipelineStatisticFlags, QueryPool, QueryPoolCreationError,
+    QueryResultElement, QueryResultFlags, QueryType,
+};
+use vulkano::sync::PipelineStage;
+
+mod tests;
+
+/// Error that can happen when creating query pool of frames.
+#[derive(Debug, Error)]
+pub enum FrameQueryPoolCreationError {
+    #[error("pipeline statistics queries require `pipeline_statistics_query` feature")]
+    PipelineStatisticsNotEnabled,
+
+    #[error("pipeline statistics queries must gather at least one statistic")]
+    NoStatistics,
+
+    #[error("query pool creation failure: {0}")]
+    QueryPoolCreation(#[from] QueryPoolCreationError),
+}
+
+/// Error that can happen when recording queries into the command buffer.
+#[derive(Debug, Error)]
+pub enum QueryError {
+    #[error("query {index} is out of range of {len} queries of the slot")]
+    OutOfRange { index: u32, len: u32 },
+
+    #[error("operation is not supported by {0:?} queries")]
+    WrongKind(QueryKind),
+
+    #[error("query was already used in this frame")]
+    AlreadyUsed,
+
+    #[error("failed to reset queries: {0}")]
+    Reset(#[from] ResetQueryPoolError),
+
+    #[error("failed to begin query: {0}")]
+    Begin(#[from] BeginQueryError),
+
+    #[error("failed to end query: {0}")]
+    End(#[from] EndQueryError),
+
+    #[error("failed to write timestamp: {0}")]
+    WriteTimestamp(#[from] WriteTimestampError),
+}
+
+/// Kind of queries of the pool.
+#[derive(Debug, Copy, Clone)]
+pub enum QueryKind {
+    /// Count of samples which passed depth and stencil tests between begin and end of the query.
+    Occlusion,
+    /// Provided statistics of the pipeline gathered between begin and end of the query,
+    /// in order of fields of the flags.
+    PipelineStatistics(QueryPipelineStatisticFlags),
+    /// Value of the timestamp when all previous commands completed provided stage.
+    Timestamp,
+}
+
+impl QueryKind {
+    /// Count of values which are written for each query of this kind.
+    pub const fn values_per_query(&self) -> usize {
+        self.to_vk().result_size() as usize
+    }
+
+    /// If queries of this kind are written by begin and end of the query, not at a single point.
+    pub const fn is_scoped(&self) -> bool {
+        !matches!(self, Self::Timestamp)
+    }
+
+    const fn to_vk(self) -> QueryType {
+        match self {
+            Self::Occlusion => QueryType::Occlusion,
+            Self::PipelineStatistics(flags) => QueryType::PipelineStatistics(flags),
+            Self::Timestamp => QueryType::Timestamp,
+        }
+    }
+}
+
+/// Queries of provided slot, slots wrap around if there are more frames in flight than slots.
+pub fn slot_queries(slot: usize, slots: usize, queries_per_slot: u32) -> Range<u32> {
+    let start = (slot % slots.max(1)) as u32 * queries_per_slot;
+    start..start + queries_per_slot
+}
+
+/// Results of queries of one slot which were read without waiting for the device.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QueryResults<T> {
+    values: Vec<T>,
+    available: Vec<bool>,
+    values_per_query: usize,
+}
+
+impl<T> QueryResults<T>
+where
+    T: Copy + Default + PartialEq,
+{
+    /// Splits raw results where each query has `values_per_query` values
+    /// followed by availability value, which is non-zero if the query is available.
+    pub fn from_raw(raw: &[T], values_per_query: usize) -> Self {
+        let (values, available) = raw
+            .chunks_exact(values_per_query + 1)
+            .map(|query| {
+                let (availability, values) = query.split_last().expect("chunk is never empty");
+                (values, *availability != T::default())
+            })
+            .fold(
+                (Vec::new(), Vec::new()),
+                |(mut all_values, mut all_available), (values, available)| {
+                    all_values.extend_from_slice(values);
+                    all_available.push(available);
+                    (all_values, all_available)
+                },
+            );
+        Self {
+            values,
+            available,
+            values_per_query,
+        }
+    }
+
+    /// Count of queries of the slot.
+    pub fn len(&self) -> usize {
+        self.available.len()
+    }
+
+    /// If the slot has no queries.
+    pub fn is_empty(&self) -> bool {
+        self.available.is_empty()
+    }
+
+    /// If results of all queries are available.
+    pub fn all_available(&self) -> bool {
+        self.available.iter().all(|&available| available)
+    }
+
+    /// Values of the query with provided index, or `None` if its result is not available yet.
+    pub fn get(&self, index: usize) -> Option<&[T]> {
+        if !*self.available.get(index)? {
+            return None;
+        }
+        let start = index * self.values_per_query;
+        Some(&self.values[start..start + self.values_per_query])
+    }
+}
+
+/// Pool of queries with slots per frame in flight.
+pub struct FrameQueryPool {
+    pool: Arc<QueryPool>,
+    kind: QueryKind,
+    queries_per_slot: u32,
+    /// If queries of the slot were reset and all of their results were not read yet.
+    pending: Vec<bool>,
+}
+
+impl FrameQueryPool {
+    /// Creates new pool with provided count of queries of each of `slots` slots.
+    pub fn new(
+        device: Arc<Device>,
+        kind: QueryKind,
+        queries_per_slot: u32,
+        slots: usize,
+    ) -> Result<Self, FrameQueryPoolCreationError> {
+        if let QueryKind::PipelineStatistics(flags) = kind {
+            if !device.enabled_features().pipeline_statistics_query {
+                return Err(FrameQueryPoolCreationError::PipelineStatisticsNotEnabled);
+            }
+            if flags.count() == 0 {
+                return Err(FrameQueryPoolCreationError::NoStatistics);
+            }
+        }
+        let queries_per_slot = queries_per_slot.max(1);
+        let slots = slots.max(1);
+        let pool = QueryPool::new(device, kind.to_vk(), slots as u32 * queries_per_slot)?;
+        Ok(Self {
+            pool: Arc::new(pool),
+            kind,
+            queries_per_slot,
+            pending: vec![false; slots],
+        })
+    }
+
+    /// Kind of queries of the pool.
+    pub fn kind(&self) -> QueryKind {
+        self.kind
+    }
+
+    /// Count of queries of each slot.
+    pub fn queries_per_slot(&self) -> u32 {
+        self.queries_per_slot
+    }
+
+    /// Count of slots of the pool.
+    pub fn slot_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Index of the query of provided slot in the underlying pool.
+    fn query(&self, slot: usize, index: u32) -> Result<u32, QueryError> {
+        if index >= self.queries_per_slot {
+            return Err(QueryError::OutOfRange {
+                index,
+                len: self.queries_per_slot,
+            });
+        }
+        let queries = self::slot_queries(slot, self.slot_count(), self.queries_per_slot);
+        Ok(queries.start + index)
+    }
+
+    /// Resets queries of provided slot, so they could be written by the commands of the frame.
+    ///
+    /// Must be called outside of render pass before any query of the slot is used.
+    ///
+    pub fn reset<L, P>(
+        &mut self,
+        builder: &mut AutoCommandBufferBuilder<L, P>,
+        slot: usize,
+    ) -> Result<(), QueryError> {
+        let queries = self::slot_queries(slot, self.slot_count(), self.queries_per_slot);
+        // SAFETY: queries of the slot are not used by other commands of this command buffer,
+        // and the frame which has written them before was submitted earlier to the same queue.
+        unsafe {
+            builder.reset_query_pool(self.pool.clone(), queries)?;
+        }
+        let slot = slot % self.slot_count();
+        self.pending[slot] = true;
+        Ok(())
+    }
+
+    /// Handle of the query of provided slot, which could be passed to the code
+    /// which records the nominated commands.
+    ///
+    /// The query must be [reset](Self::reset) in this frame before the handle is used.
+    ///
+    pub fn frame_query(&self, slot: usize, index: u32) -> Result<FrameQuery, QueryError> {
+        if !self.kind.is_scoped() {
+            return Err(QueryError::WrongKind(self.kind));
+        }
+        Ok(FrameQuery {
+            pool: self.pool.clone(),
+            query: self.query(slot, index)?,
+            used: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    /// Writes timestamp of provided query of the slot
+    /// when all previous commands complete provided stage.
+    pub fn write_timestamp<L, P>(
+        &self,
+        builder: &mut AutoCommandBufferBuilder<L, P>,
+        slot: usize,
+        index: u32,
+        stage: PipelineStage,
+    ) -> Result<(), QueryError> {
+        if self.kind.is_scoped() {
+            return Err(QueryError::WrongKind(self.kind));
+        }
+        let query = self.query(slot, index)?;
+        // SAFETY: query was reset by `reset` before this command.
+        unsafe {
+            builder.write_timestamp(self.pool.clone(), query, stage)?;
+        }
+        Ok(())
+    }
+
+    /// Reads results of queries of provided slot, if the slot was reset since the last read.
+    ///
+    /// Never waits for the device: results which are not available yet are reported as such,
+    /// and the slot could be read again until all of them are available.
+    ///
+    pub fn results<T>(&mut self, slot: usize) -> Option<QueryResults<T>>
+    where
+        T: QueryResultElement + Copy + Default + PartialEq,
+    {
+        let slot = slot % self.slot_count();
+        if !self.pending[slot] {
+            return None;
+        }
+        let values_per_query = self.kind.values_per_query();
+        let mut raw = vec![T::default(); (values_per_query + 1) * self.queries_per_slot as usize];
+        let flags = QueryResultFlags {
+            wait: false,
+            with_availability: true,
+            partial: false,
+        };
+        let queries = self::slot_queries(slot, self.slot_count(), self.queries_per_slot);
+        if let Err(error) = self
+            .pool
+            .queries_range(queries)?
+            .get_results(&mut raw, flags)
+        {
+            log::warn!("failed to read query results: {}", error);
+            return None;
+        }
+        let results = QueryResults::from_raw(&raw, values_per_query);
+        if results.all_available() {
+            self.pending[slot] = false;
+        }
+        Some(results)
+    }
+}
+
+/// Occlusion or pipeline statistics query of the frame, handed to the code
+/// which records the nominated commands.
+///
+/// Clones of the handle refer to the same query, which could be used at most once per frame.
+///
+#[derive(Debug, Clone)]
+pub struct FrameQuery {
+    pool: Arc<QueryPool>,
+    query: u32,
+    used: Arc<AtomicBool>,
+}
+
+impl FrameQuery {
+    /// Begins the query before the nominated commands.
+    ///
+    /// Query must be [ended](Self::end) in the same command buffer,
+    /// and inside of the same subpass if it was begun inside of the render pass.
+    /// `precise` occlusion queries require `occlusion_query_precise` feature.
+    ///
+    pub fn begin<L, P>(
+        &self,
+        builder: &mut AutoCommandBufferBuilder<L, P>,
+        precise: bool,
+    ) -> Result<(), QueryError> {
+        if self.used.swap(true, Ordering::AcqRel) {
+            return Err(QueryError::AlreadyUsed);
+        }
+        let flags = QueryControlFlags { precise };
+        // SAFETY: query was reset at the start of the frame and was not used since then.
+        unsafe {
+            builder.begin_query(self.pool.clone(), self.query, flags)?;
+        }
+        Ok(())
+    }
+
+    /// Ends the query after the nominated commands.
+    pub fn end<L, P>(
+        &self,
+        builder: &mut AutoCommandBufferBuilder<L, P>,
+    ) -> Result<(), QueryError> {
+        builder.end_query(self.pool.clone(), self.query)?;
+        Ok(())
+    }
+}
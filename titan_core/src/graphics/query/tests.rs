@@ -0,0 +1,51 @@
+#![cfg(test)]
+
+use vulkano::query::QueryPipelineStatisticFlags;
+
+use super::{slot_queries, QueryKind, QueryResults};
+
+#[test]
+fn slot_queries_wrap_around_slots() {
+    assert_eq!(slot_queries(0, 3, 2), 0..2);
+    assert_eq!(slot_queries(2, 3, 2), 4..6);
+    assert_eq!(slot_queries(4, 3, 2), 2..4);
+}
+
+#[test]
+fn pipeline_statistics_write_value_per_statistic() {
+    let flags = QueryPipelineStatisticFlags {
+        input_assembly_vertices: true,
+        fragment_shader_invocations: true,
+        ..QueryPipelineStatisticFlags::none()
+    };
+    assert_eq!(QueryKind::PipelineStatistics(flags).values_per_query(), 2);
+    assert_eq!(QueryKind::Occlusion.values_per_query(), 1);
+    assert_eq!(QueryKind::Timestamp.values_per_query(), 1);
+}
+
+#[test]
+fn only_timestamps_are_not_scoped() {
+    assert!(QueryKind::Occlusion.is_scoped());
+    assert!(QueryKind::PipelineStatistics(QueryPipelineStatisticFlags::none()).is_scoped());
+    assert!(!QueryKind::Timestamp.is_scoped());
+}
+
+#[test]
+fn results_are_split_by_availability() {
+    let raw: [u64; 6] = [120, 1, 7, 3, 0, 0];
+    let results = QueryResults::from_raw(&raw, 2);
+    assert_eq!(results.len(), 2);
+    assert_eq!(results.get(0), Some([120, 1].as_slice()));
+    assert_eq!(results.get(1), None);
+    assert!(!results.all_available());
+}
+
+#[test]
+fn results_of_available_queries() {
+    let raw: [u32; 4] = [42, 1, 0, 1];
+    let results = QueryResults::from_raw(&raw, 1);
+    assert!(results.all_available());
+    assert_eq!(results.get(0), Some([42].as_slice()));
+    assert_eq!(results.get(1), Some([0].as_slice()));
+    assert_eq!(results.get(2), None);
+}
@@ -0,0 +1,418 @@
+//! Readback of GPU data by the CPU at the end of the frame.
+//!
+//! Consumers request readback of the buffer or image region, the scheduler copies it
+//! into pooled host visible buffer at the end of the frame, and consumer callback
+//! is invoked with the bytes of the region when the frame is retired by the device.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Arc;
+
+use thiserror::Error;
+use vulkano::buffer::{BufferAccess, BufferUsage, CpuAccessibleBuffer, TypedBufferAccess};
+use vulkano::command_buffer::{
+    AutoCommandBufferBuilder, BuildError, CommandBufferExecError, CommandBufferUsage,
+    CopyBufferError, CopyBufferImageError,
+};
+use vulkano::device::Queue;
+use vulkano::image::ImageAccess;
+use vulkano::memory::DeviceMemoryAllocError;
+use vulkano::sync::GpuFuture;
+use vulkano::OomError;
+
+use crate::graphics::memory::{MemoryTracker, ResourceCategory};
+
+mod tests;
+
+/// Maximal count of free buffers which are kept by the scheduler for reuse.
+pub const MAX_POOLED_BUFFERS: usize = 16;
+
+/// Identifier of the readback request.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ReadbackId(u64);
+
+/// Callback which consumes bytes of the region when the frame is retired.
+pub type ReadbackCallback = Box<dyn FnOnce(&[u8]) + Send>;
+
+/// Host visible buffer which readback region is copied into.
+pub trait ReadbackBuffer {
+    /// Count of bytes which this buffer could contain.
+    fn capacity(&self) -> usize;
+
+    /// Invokes callback with first `len` bytes of this buffer.
+    ///
+    /// Callback is returned back if the buffer is still in use by the device.
+    ///
+    fn consume(&self, len: usize, callback: ReadbackCallback) -> Result<(), ReadbackCallback>;
+}
+
+impl ReadbackBuffer for Arc<CpuAccessibleBuffer<[u8]>> {
+    fn capacity(&self) -> usize {
+        self.len() as usize
+    }
+
+    fn consume(&self, len: usize, callback: ReadbackCallback) -> Result<(), ReadbackCallback> {
+        match self.read() {
+            Ok(content) => {
+                callback(&content[..len]);
+                Ok(())
+            }
+            Err(_) => Err(callback),
+        }
+    }
+}
+
+/// Request which waits to be copied into the readback buffer.
+struct Request<R> {
+    id: ReadbackId,
+    region: R,
+    len: usize,
+    callback: ReadbackCallback,
+}
+
+/// Request which was copied into the readback buffer.
+struct Copied<B, R> {
+    request: Request<R>,
+    buffer: B,
+}
+
+/// Scheduler of readback requests which owns the pool of readback buffers.
+///
+/// Requests are copied by [`record`](Self::record) into the frame which is being recorded,
+/// then they are bound to that frame by [`submit`](Self::submit)
+/// or returned to the queue by [`cancel`](Self::cancel) if the frame was never submitted.
+///
+pub struct ReadbackScheduler<B, R> {
+    pending: VecDeque<Request<R>>,
+    recorded: Vec<Copied<B, R>>,
+    in_flight: BTreeMap<u64, Vec<Copied<B, R>>>,
+    free: Vec<B>,
+    next_id: u64,
+}
+
+impl<B, R> Default for ReadbackScheduler<B, R> {
+    fn default() -> Self {
+        Self {
+            pending: VecDeque::new(),
+            recorded: Vec::new(),
+            in_flight: BTreeMap::new(),
+            free: Vec::new(),
+            next_id: 0,
+        }
+    }
+}
+
+impl<B, R> ReadbackScheduler<B, R>
+where
+    B: ReadbackBuffer,
+{
+    /// Requests readback of `len` bytes of provided region in the next recorded frame.
+    pub fn request(
+        &mut self,
+        region: R,
+        len: usize,
+        callback: impl FnOnce(&[u8]) + Send + 'static,
+    ) -> ReadbackId {
+        let id = ReadbackId(self.next_id);
+        self.next_id += 1;
+        let callback = Box::new(callback);
+        self.pending.push_back(Request {
+            id,
+            region,
+            len,
+            callback,
+        });
+        id
+    }
+
+    /// Removes request which was not bound to the submitted frame yet,
+    /// so its callback is never invoked.
+    ///
+    /// Returns `false` if there is no such request.
+    ///
+    pub fn remove(&mut self, id: ReadbackId) -> bool {
+        if let Some(position) = self.pending.iter().position(|request| request.id == id) {
+            self.pending.remove(position);
+            return true;
+        }
+        match self
+            .recorded
+            .iter()
+            .position(|copied| copied.request.id == id)
+        {
+            Some(position) => {
+                let Copied { buffer, .. } = self.recorded.remove(position);
+                self.recycle(buffer);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Checks if there are requests which will be copied in the next recorded frame.
+    pub fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Count of free buffers which are kept for reuse.
+    pub fn pooled_buffers(&self) -> usize {
+        self.free.len()
+    }
+
+    /// Copies all pending requests into readback buffers.
+    ///
+    /// The smallest free buffer which fits the request is reused,
+    /// otherwise new buffer is created by `allocate`.
+    /// Returns count of copied requests.
+    ///
+    /// # Errors
+    ///
+    /// If allocation or copy fails, the error is returned and all requests copied by this call
+    /// are returned into the queue along with the failed one, because their copy commands
+    /// are never executed.
+    ///
+    pub fn record<E>(
+        &mut self,
+        mut allocate: impl FnMut(usize) -> Result<B, E>,
+        mut copy: impl FnMut(&R, &B) -> Result<(), E>,
+    ) -> Result<usize, E> {
+        let recorded = self.recorded.len();
+        let mut count = 0;
+        while let Some(request) = self.pending.pop_front() {
+            let buffer = match self.take_buffer(request.len) {
+                Some(buffer) => buffer,
+                None => match allocate(request.len) {
+                    Ok(buffer) => buffer,
+                    Err(error) => {
+                        self.pending.push_front(request);
+                        self.requeue(recorded);
+                        return Err(error);
+                    }
+                },
+            };
+            if let Err(error) = copy(&request.region, &buffer) {
+                self.pending.push_front(request);
+                self.recycle(buffer);
+                self.requeue(recorded);
+                return Err(error);
+            }
+            self.recorded.push(Copied { request, buffer });
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Binds all recorded copies to the submitted frame with provided number.
+    pub fn submit(&mut self, frame: u64) {
+        if self.recorded.is_empty() {
+            return;
+        }
+        let recorded = std::mem::take(&mut self.recorded);
+        self.in_flight.entry(frame).or_default().extend(recorded);
+    }
+
+    /// Returns all recorded copies back into the queue because their frame was never submitted.
+    ///
+    /// Requests will be copied again in the next recorded frame.
+    ///
+    pub fn cancel(&mut self) {
+        self.requeue(0)
+    }
+
+    /// Invokes callbacks of copies of the frame with provided number and all previous frames.
+    ///
+    /// Buffers are recycled after their callbacks are invoked.
+    /// Copies whose buffers are still in use by the device are kept until the next call.
+    /// Returns count of invoked callbacks.
+    ///
+    pub fn retire(&mut self, frame: u64) -> usize {
+        let pending = self.in_flight.split_off(&(frame + 1));
+        let retired = std::mem::replace(&mut self.in_flight, pending);
+
+        let mut count = 0;
+        for (frame, copies) in retired {
+            for Copied { request, buffer } in copies {
+                let Request {
+                    id,
+                    region,
+                    len,
+                    callback,
+                } = request;
+                match buffer.consume(len, callback) {
+                    Ok(()) => {
+                        count += 1;
+                        self.recycle(buffer);
+                    }
+                    Err(callback) => {
+                        let request = Request {
+                            id,
+                            region,
+                            len,
+                            callback,
+                        };
+                        let copied = Copied { request, buffer };
+                        self.in_flight.entry(frame).or_default().push(copied);
+                    }
+                }
+            }
+        }
+        count
+    }
+
+    /// Returns copies recorded after the first `recorded` ones back into the queue.
+    fn requeue(&mut self, recorded: usize) {
+        for Copied { request, buffer } in self.recorded.split_off(recorded).into_iter().rev() {
+            self.pending.push_front(request);
+            self.recycle(buffer);
+        }
+    }
+
+    fn take_buffer(&mut self, len: usize) -> Option<B> {
+        let (index, _) = self
+            .free
+            .iter()
+            .enumerate()
+            .filter(|(_, buffer)| buffer.capacity() >= len)
+            .min_by_key(|(_, buffer)| buffer.capacity())?;
+        Some(self.free.swap_remove(index))
+    }
+
+    fn recycle(&mut self, buffer: B) {
+        if self.free.len() < MAX_POOLED_BUFFERS {
+            self.free.push(buffer);
+        }
+    }
+}
+
+/// Region of the device resource which could be read back.
+#[derive(Clone)]
+pub enum ReadbackRegion {
+    /// Rectangle of the first mip level and array layer of the image.
+    Image {
+        image: Arc<dyn ImageAccess + Send + Sync>,
+        origin: [u32; 2],
+        extent: [u32; 2],
+    },
+    /// Whole content of the buffer.
+    Buffer(Arc<dyn TypedBufferAccess<Content = [u8]> + Send + Sync>),
+}
+
+impl ReadbackRegion {
+    /// Count of bytes of the region.
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Image { image, extent, .. } => {
+                let texel_size = image.format().size().unwrap_or(0) as usize;
+                extent[0] as usize * extent[1] as usize * texel_size
+            }
+            Self::Buffer(buffer) => buffer.size() as usize,
+        }
+    }
+
+    /// Checks if the region contains no bytes.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Error that can happen when recording copies of readback regions.
+#[derive(Debug, Error)]
+pub enum ReadbackError {
+    #[error("readback buffer allocation failure: {0}")]
+    Allocation(#[from] DeviceMemoryAllocError),
+
+    #[error("image region copy failure: {0}")]
+    CopyImage(#[from] CopyBufferImageError),
+
+    #[error("buffer region copy failure: {0}")]
+    CopyBuffer(#[from] CopyBufferError),
+
+    #[error("readback command buffer allocation failure: {0}")]
+    OutOfMemory(#[from] OomError),
+
+    #[error("readback command buffer build failure: {0}")]
+    Build(#[from] BuildError),
+
+    #[error("readback command buffer execution failure: {0}")]
+    Execution(#[from] CommandBufferExecError),
+}
+
+/// Scheduler of readbacks of device resources.
+pub type Readbacks = ReadbackScheduler<Arc<CpuAccessibleBuffer<[u8]>>, ReadbackRegion>;
+
+impl Readbacks {
+    /// Requests readback of provided region in the next recorded frame.
+    pub fn request_region(
+        &mut self,
+        region: ReadbackRegion,
+        callback: impl FnOnce(&[u8]) + Send + 'static,
+    ) -> ReadbackId {
+        let len = region.len();
+        self.request(region, len, callback)
+    }
+
+    /// Copies all pending regions on provided queue after provided future.
+    pub fn execute<F>(
+        &mut self,
+        before_future: F,
+        queue: Arc<Queue>,
+        memory: &MemoryTracker,
+    ) -> Result<Box<dyn GpuFuture + Send + Sync>, ReadbackError>
+    where
+        F: GpuFuture + Send + Sync + 'static,
+    {
+        let device = queue.device().clone();
+        let mut builder = AutoCommandBufferBuilder::primary(
+            device.clone(),
+            queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+        )?;
+        let allocate = |len: usize| {
+            let buffer = CpuAccessibleBuffer::from_iter(
+                device.clone(),
+                BufferUsage::transfer_destination(),
+                true,
+                std::iter::repeat_n(0u8, len.max(1)),
+            )?;
+            let size = buffer.size();
+            memory.track(&buffer, "readback", ResourceCategory::Readback, size);
+            Ok(buffer)
+        };
+        let copy = |region: &ReadbackRegion, buffer: &Arc<CpuAccessibleBuffer<[u8]>>| {
+            match region {
+                ReadbackRegion::Image {
+                    image,
+                    origin,
+                    extent,
+                } => {
+                    builder.copy_image_to_buffer_dimensions(
+                        image.clone(),
+                        buffer.clone(),
+                        [origin[0], origin[1], 0],
+                        [extent[0], extent[1], 1],
+                        0,
+                        1,
+                        0,
+                    )?;
+                }
+                ReadbackRegion::Buffer(source) => {
+                    builder.copy_buffer(source.clone(), buffer.clone())?;
+                }
+            }
+            Ok(())
+        };
+        self.record::<ReadbackError>(allocate, copy)?;
+
+        let after_future = builder
+            .build()
+            .map_err(ReadbackError::from)
+            .and_then(|command_buffer| Ok(before_future.then_execute(queue, command_buffer)?));
+        match after_future {
+            Ok(after_future) => Ok(Box::new(after_future)),
+            // Copies were never executed, so they are recorded again in the next frame.
+            Err(error) => {
+                self.cancel();
+                Err(error)
+            }
+        }
+    }
+}
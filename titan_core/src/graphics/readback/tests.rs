@@ -0,0 +1,206 @@
+#![cfg(test)]
+
+use std::sync::{Arc, Mutex};
+
+use super::{ReadbackBuffer, ReadbackCallback, ReadbackScheduler, MAX_POOLED_BUFFERS};
+
+/// Buffer which is written by the fake device and could be locked by it.
+#[derive(Clone, Default)]
+struct FakeBuffer {
+    bytes: Arc<Mutex<Vec<u8>>>,
+    locked: Arc<Mutex<bool>>,
+}
+
+impl ReadbackBuffer for FakeBuffer {
+    fn capacity(&self) -> usize {
+        self.bytes.lock().unwrap().len()
+    }
+
+    fn consume(&self, len: usize, callback: ReadbackCallback) -> Result<(), ReadbackCallback> {
+        if *self.locked.lock().unwrap() {
+            return Err(callback);
+        }
+        callback(&self.bytes.lock().unwrap()[..len]);
+        Ok(())
+    }
+}
+
+type Scheduler = ReadbackScheduler<FakeBuffer, u8>;
+
+/// Creates callback which records consumed bytes into the log.
+fn record(log: &Arc<Mutex<Vec<Vec<u8>>>>) -> impl FnOnce(&[u8]) + Send + 'static {
+    let log = log.clone();
+    move |bytes| log.lock().unwrap().push(bytes.to_vec())
+}
+
+fn allocate(len: usize) -> Result<FakeBuffer, ()> {
+    let buffer = FakeBuffer::default();
+    *buffer.bytes.lock().unwrap() = vec![0; len];
+    Ok(buffer)
+}
+
+/// Fake device copies region which is the value of each byte.
+fn copy(region: &u8, buffer: &FakeBuffer) -> Result<(), ()> {
+    buffer.bytes.lock().unwrap().fill(*region);
+    Ok(())
+}
+
+#[test]
+fn callbacks_are_invoked_when_frame_retires() {
+    let log = Arc::default();
+    let mut scheduler = Scheduler::default();
+    scheduler.request(1, 2, record(&log));
+    assert!(scheduler.has_pending());
+
+    assert_eq!(scheduler.record(allocate, copy), Ok(1));
+    assert!(!scheduler.has_pending());
+    scheduler.submit(0);
+    scheduler.request(2, 3, record(&log));
+    scheduler.record(allocate, copy).unwrap();
+    scheduler.submit(1);
+
+    assert_eq!(scheduler.retire(0), 1);
+    assert_eq!(*log.lock().unwrap(), [vec![1, 1]]);
+    assert_eq!(scheduler.retire(1), 1);
+    assert_eq!(*log.lock().unwrap(), [vec![1, 1], vec![2, 2, 2]]);
+    assert_eq!(scheduler.retire(1), 0);
+}
+
+#[test]
+fn buffers_are_recycled() {
+    let log = Arc::default();
+    let mut scheduler = Scheduler::default();
+    let allocations = Arc::new(Mutex::new(0));
+    let counted = |len| {
+        *allocations.lock().unwrap() += 1;
+        allocate(len)
+    };
+
+    scheduler.request(1, 8, record(&log));
+    scheduler.record(counted, copy).unwrap();
+    scheduler.submit(0);
+    scheduler.retire(0);
+    assert_eq!(scheduler.pooled_buffers(), 1);
+
+    // Smaller request reuses larger buffer, larger request allocates new one.
+    scheduler.request(2, 4, record(&log));
+    scheduler.request(3, 16, record(&log));
+    scheduler.record(counted, copy).unwrap();
+    assert_eq!(*allocations.lock().unwrap(), 2);
+    assert_eq!(scheduler.pooled_buffers(), 0);
+    scheduler.submit(1);
+    scheduler.retire(1);
+    assert_eq!(*log.lock().unwrap(), [vec![1; 8], vec![2; 4], vec![3; 16]]);
+}
+
+#[test]
+fn requests_of_skipped_frame_are_recorded_again() {
+    let log = Arc::default();
+    let mut scheduler = Scheduler::default();
+    scheduler.request(1, 1, record(&log));
+    scheduler.request(2, 1, record(&log));
+    scheduler.record(allocate, copy).unwrap();
+
+    // Frame was never presented, so its number is taken by the next frame.
+    scheduler.cancel();
+    assert!(scheduler.has_pending());
+    assert_eq!(scheduler.pooled_buffers(), 2);
+    assert_eq!(scheduler.retire(0), 0);
+
+    scheduler.request(3, 1, record(&log));
+    assert_eq!(scheduler.record(allocate, copy), Ok(3));
+    scheduler.submit(0);
+    assert_eq!(scheduler.retire(0), 3);
+    assert_eq!(*log.lock().unwrap(), [vec![1], vec![2], vec![3]]);
+}
+
+#[test]
+fn locked_buffers_are_consumed_later() {
+    let log = Arc::default();
+    let mut scheduler = Scheduler::default();
+    let buffer = allocate(1).unwrap();
+    *buffer.locked.lock().unwrap() = true;
+    let shared = buffer.clone();
+
+    scheduler.request(7, 1, record(&log));
+    scheduler.record(|_| Ok(shared.clone()), copy).unwrap();
+    scheduler.submit(0);
+    assert_eq!(scheduler.retire(0), 0);
+    assert!(log.lock().unwrap().is_empty());
+
+    *buffer.locked.lock().unwrap() = false;
+    assert_eq!(scheduler.retire(0), 1);
+    assert_eq!(*log.lock().unwrap(), [vec![7]]);
+}
+
+#[test]
+fn failed_copy_keeps_request_pending() {
+    let log = Arc::default();
+    let mut scheduler = Scheduler::default();
+    scheduler.request(1, 1, record(&log));
+    assert_eq!(scheduler.record(allocate, |_, _| Err(())), Err(()));
+    assert!(scheduler.has_pending());
+    assert_eq!(scheduler.pooled_buffers(), 1);
+    assert_eq!(scheduler.record(allocate, copy), Ok(1));
+}
+
+#[test]
+fn failed_record_returns_copied_requests_into_queue() {
+    let log = Arc::default();
+    let mut scheduler = Scheduler::default();
+    scheduler.request(1, 1, record(&log));
+    scheduler.request(2, 1, record(&log));
+    let failing = |region: &u8, buffer: &FakeBuffer| match region {
+        2 => Err(()),
+        _ => copy(region, buffer),
+    };
+    assert_eq!(scheduler.record(allocate, failing), Err(()));
+    assert!(scheduler.has_pending());
+    assert_eq!(scheduler.pooled_buffers(), 2);
+
+    // Nothing was copied by the failed call, so nothing is bound to the frame.
+    scheduler.submit(0);
+    assert_eq!(scheduler.retire(0), 0);
+    assert!(log.lock().unwrap().is_empty());
+
+    assert_eq!(scheduler.record(allocate, copy), Ok(2));
+    scheduler.submit(1);
+    assert_eq!(scheduler.retire(1), 2);
+    assert_eq!(*log.lock().unwrap(), [vec![1], vec![2]]);
+}
+
+#[test]
+fn removed_requests_are_not_invoked() {
+    let log = Arc::default();
+    let mut scheduler = Scheduler::default();
+    let pending = scheduler.request(1, 1, record(&log));
+    let recorded = scheduler.request(2, 1, record(&log));
+    scheduler.record(allocate, copy).unwrap();
+    let kept = scheduler.request(3, 1, record(&log));
+
+    assert!(scheduler.remove(recorded));
+    assert!(!scheduler.remove(recorded));
+    assert_eq!(scheduler.pooled_buffers(), 1);
+    scheduler.cancel();
+    assert!(scheduler.remove(pending));
+    assert_eq!(scheduler.pooled_buffers(), 2);
+
+    scheduler.record(allocate, copy).unwrap();
+    scheduler.submit(0);
+    assert!(!scheduler.remove(kept));
+    assert_eq!(scheduler.retire(0), 1);
+    assert_eq!(*log.lock().unwrap(), [vec![3]]);
+}
+
+#[test]
+fn pool_is_bounded() {
+    let log = Arc::default();
+    let mut scheduler = Scheduler::default();
+    for region in 0..MAX_POOLED_BUFFERS as u8 + 4 {
+        scheduler.request(region, 1, record(&log));
+    }
+    scheduler.record(allocate, copy).unwrap();
+    scheduler.submit(0);
+    scheduler.retire(0);
+    assert_eq!(scheduler.pooled_buffers(), MAX_POOLED_BUFFERS);
+}
@@ -0,0 +1,383 @@
+//! Render passes with several subpasses and input attachments.
+//!
+//! Render pass is described by [`RenderPassDescriptor`] with its attachments and subpasses
+//! which refer to attachments by their indices, so techniques like deferred shading
+//! could read attachments written by previous subpasses as input attachments.
+//! Descriptor is validated before the render pass is created, and framebuffers
+//! of the render pass are [created](framebuffer) from image views in order of attachments.
+
+use std::sync::Arc;
+
+use thiserror::Error;
+use vulkano::format::Format;
+use vulkano::image::view::ImageViewAbstract;
+use vulkano::image::{ImageLayout, SampleCount};
+use vulkano::render_pass::{
+    AttachmentDesc, Framebuffer, FramebufferAbstract, FramebufferCreationError, LoadOp, RenderPass,
+    RenderPassDesc, StoreOp, SubpassDependencyDesc, SubpassDesc,
+};
+use vulkano::sync::{AccessFlags, PipelineStages};
+
+mod tests;
+
+/// Maximal count of attachments of framebuffers created by [`framebuffer`].
+pub const MAX_FRAMEBUFFER_ATTACHMENTS: usize = 8;
+
+/// References of subpass to attachments of the render pass by their indices.
+///
+/// Layouts of attachments are derived from the way subpass uses them.
+///
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct SubpassDescriptor {
+    /// Attachments which are written by fragment shaders, in order of their locations.
+    pub color_attachments: Vec<usize>,
+    /// Attachments which are read by fragment shaders, in order of their input indices.
+    pub input_attachments: Vec<usize>,
+    /// Attachment which is used for depth and stencil tests.
+    pub depth_stencil: Option<usize>,
+    /// Attachments which each color attachment is resolved into, if it is multisampled.
+    ///
+    /// Must be empty or have the same count as color attachments.
+    ///
+    pub resolve_attachments: Vec<usize>,
+}
+
+/// Description of render pass with several subpasses.
+#[derive(Debug, Clone, Default)]
+pub struct RenderPassDescriptor {
+    /// Attachments of the render pass, in order of image views of its framebuffers.
+    pub attachments: Vec<AttachmentDesc>,
+    /// Subpasses of the render pass, in order of their execution.
+    pub subpasses: Vec<SubpassDescriptor>,
+    /// Dependencies between subpasses.
+    pub dependencies: Vec<SubpassDependencyDesc>,
+}
+
+impl RenderPassDescriptor {
+    /// Describes render pass of the frame which ends with the final image, such as swapchain one.
+    ///
+    /// Attachments are the same as of [offscreen](Self::offscreen) render pass
+    /// with the final image as the target. The first subpass renders objects,
+    /// the second subpass renders UI directly into the final image.
+    ///
+    pub fn swapchain(
+        final_output_format: Format,
+        depth_format: Option<Format>,
+        samples: SampleCount,
+    ) -> Self {
+        let mut descriptor = Self::offscreen(final_output_format, depth_format, samples);
+        descriptor.subpasses.push(SubpassDescriptor {
+            color_attachments: vec![0],
+            ..SubpassDescriptor::default()
+        });
+        descriptor
+            .dependencies
+            .push(self::graphics_dependency(0, 1));
+        descriptor
+    }
+
+    /// Describes render pass with one subpass which draws over the whole target image.
+    ///
+    /// Render passes with the same target format are compatible regardless of `load`,
+    /// so passes which overwrite the target ([`LoadOp::DontCare`]), such as post processing,
+    /// and passes which draw over its contents ([`LoadOp::Load`]), such as UI,
+    /// could share their pipelines.
+    ///
+    pub fn overlay(target_format: Format, load: LoadOp) -> Self {
+        const COLOR_LAYOUT: ImageLayout = ImageLayout::ColorAttachmentOptimal;
+
+        let attachments = vec![AttachmentDesc {
+            format: target_format,
+            samples: SampleCount::Sample1,
+            load,
+            store: StoreOp::Store,
+            stencil_load: LoadOp::DontCare,
+            stencil_store: StoreOp::DontCare,
+            initial_layout: COLOR_LAYOUT,
+            final_layout: COLOR_LAYOUT,
+        }];
+        let subpass = SubpassDescriptor {
+            color_attachments: vec![0],
+            ..SubpassDescriptor::default()
+        };
+        Self {
+            attachments,
+            subpasses: vec![subpass],
+            dependencies: vec![],
+        }
+    }
+
+    /// Describes render pass with one subpass which renders objects into the target image.
+    ///
+    /// Attachments are the target image, then the depth buffer (if any),
+    /// then the multisampled color buffer (if `samples` is greater than one),
+    /// which is resolved into the target image.
+    /// Target image stays in color attachment layout after the render pass,
+    /// it is transitioned before sampling when it is used by descriptor sets.
+    ///
+    pub fn offscreen(
+        target_format: Format,
+        depth_format: Option<Format>,
+        samples: SampleCount,
+    ) -> Self {
+        const COLOR_LAYOUT: ImageLayout = ImageLayout::ColorAttachmentOptimal;
+
+        let multisampled = samples != SampleCount::Sample1;
+        let mut attachments = vec![AttachmentDesc {
+            format: target_format,
+            samples: SampleCount::Sample1,
+            load: if multisampled {
+                LoadOp::DontCare
+            } else {
+                LoadOp::Clear
+            },
+            store: StoreOp::Store,
+            stencil_load: LoadOp::DontCare,
+            stencil_store: StoreOp::DontCare,
+            initial_layout: COLOR_LAYOUT,
+            final_layout: COLOR_LAYOUT,
+        }];
+        let depth_stencil = depth_format.map(|format| {
+            attachments.push(AttachmentDesc {
+                format,
+                samples,
+                load: LoadOp::Clear,
+                store: StoreOp::DontCare,
+                stencil_load: LoadOp::Clear,
+                stencil_store: StoreOp::DontCare,
+                initial_layout: ImageLayout::Undefined,
+                final_layout: ImageLayout::DepthStencilAttachmentOptimal,
+            });
+            attachments.len() - 1
+        });
+        let (color_attachments, resolve_attachments) = if multisampled {
+            attachments.push(AttachmentDesc {
+                format: target_format,
+                samples,
+                load: LoadOp::Clear,
+                store: StoreOp::DontCare,
+                stencil_load: LoadOp::DontCare,
+                stencil_store: StoreOp::DontCare,
+                initial_layout: ImageLayout::Undefined,
+                final_layout: COLOR_LAYOUT,
+            });
+            (vec![attachments.len() - 1], vec![0])
+        } else {
+            (vec![0], vec![])
+        };
+
+        // Subpass for complex rendering.
+        let subpass = SubpassDescriptor {
+            color_attachments,
+            depth_stencil,
+            resolve_attachments,
+            ..SubpassDescriptor::default()
+        };
+        Self {
+            attachments,
+            subpasses: vec![subpass],
+            dependencies: vec![],
+        }
+    }
+
+    /// Checks that subpasses and dependencies refer to existing attachments and subpasses.
+    pub fn validate(&self) -> Result<(), RenderPassDescriptorError> {
+        if self.subpasses.is_empty() {
+            return Err(RenderPassDescriptorError::NoSubpasses);
+        }
+        let attachment = |subpass: usize, attachment: usize| {
+            self.attachments.get(attachment).ok_or(
+                RenderPassDescriptorError::AttachmentOutOfRange {
+                    subpass,
+                    attachment,
+                    count: self.attachments.len(),
+                },
+            )
+        };
+        for (index, subpass) in self.subpasses.iter().enumerate() {
+            let color = subpass.color_attachments.iter();
+            for &id in color.chain(&subpass.resolve_attachments) {
+                if self::is_depth_stencil(attachment(index, id)?.format) {
+                    return Err(RenderPassDescriptorError::NotColor {
+                        subpass: index,
+                        attachment: id,
+                    });
+                }
+            }
+            for &id in &subpass.input_attachments {
+                attachment(index, id)?;
+            }
+            if let Some(id) = subpass.depth_stencil {
+                if !self::is_depth_stencil(attachment(index, id)?.format) {
+                    return Err(RenderPassDescriptorError::NotDepthStencil {
+                        subpass: index,
+                        attachment: id,
+                    });
+                }
+            }
+            let resolve = subpass.resolve_attachments.len();
+            let color = subpass.color_attachments.len();
+            if resolve != 0 && resolve != color {
+                return Err(RenderPassDescriptorError::ResolveCount {
+                    subpass: index,
+                    color,
+                    resolve,
+                });
+            }
+        }
+        for dependency in &self.dependencies {
+            let (source, destination) = (dependency.source_subpass, dependency.destination_subpass);
+            let count = self.subpasses.len();
+            if source >= count || destination >= count {
+                return Err(RenderPassDescriptorError::SubpassOutOfRange {
+                    subpass: source.max(destination),
+                    count,
+                });
+            }
+            if source > destination {
+                return Err(RenderPassDescriptorError::DependencyOrder {
+                    dependency: source,
+                    dependent: destination,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Validates this descriptor and converts it into description of render pass of the backend.
+    pub fn build(&self) -> Result<RenderPassDesc, RenderPassDescriptorError> {
+        self.validate()?;
+
+        let color = |&id: &usize| (id, ImageLayout::ColorAttachmentOptimal);
+        let subpasses = self
+            .subpasses
+            .iter()
+            .map(|subpass| SubpassDesc {
+                color_attachments: subpass.color_attachments.iter().map(color).collect(),
+                depth_stencil: subpass
+                    .depth_stencil
+                    .map(|id| (id, ImageLayout::DepthStencilAttachmentOptimal)),
+                input_attachments: subpass
+                    .input_attachments
+                    .iter()
+                    .map(|&id| (id, self.input_layout(id)))
+                    .collect(),
+                resolve_attachments: subpass.resolve_attachments.iter().map(color).collect(),
+                preserve_attachments: vec![],
+            })
+            .collect();
+        Ok(RenderPassDesc::new(
+            self.attachments.clone(),
+            subpasses,
+            self.dependencies.clone(),
+        ))
+    }
+
+    /// Layout of the attachment with provided index while it is read as input attachment.
+    fn input_layout(&self, attachment: usize) -> ImageLayout {
+        if self::is_depth_stencil(self.attachments[attachment].format) {
+            ImageLayout::DepthStencilReadOnlyOptimal
+        } else {
+            ImageLayout::ShaderReadOnlyOptimal
+        }
+    }
+}
+
+/// Dependency of one subpass on all graphics commands of another subpass.
+fn graphics_dependency(source_subpass: usize, destination_subpass: usize) -> SubpassDependencyDesc {
+    let all_graphics = PipelineStages {
+        all_graphics: true,
+        ..PipelineStages::none()
+    };
+    SubpassDependencyDesc {
+        source_subpass,
+        destination_subpass,
+        source_stages: all_graphics,
+        destination_stages: all_graphics,
+        source_access: AccessFlags::all(),
+        destination_access: AccessFlags::all(),
+        by_region: true,
+    }
+}
+
+/// Checks if images of provided format contain depth or stencil.
+fn is_depth_stencil(format: Format) -> bool {
+    let aspects = format.aspects();
+    aspects.depth || aspects.stencil
+}
+
+/// Error that can happen when validating descriptor of render pass.
+#[derive(Debug, Error, Eq, PartialEq)]
+pub enum RenderPassDescriptorError {
+    #[error("render pass must have at least one subpass")]
+    NoSubpasses,
+
+    #[error(
+        "subpass {subpass} refers to attachment {attachment}, but there are {count} attachments"
+    )]
+    AttachmentOutOfRange {
+        subpass: usize,
+        attachment: usize,
+        count: usize,
+    },
+
+    #[error("subpass {subpass} uses depth/stencil attachment {attachment} as color attachment")]
+    NotColor { subpass: usize, attachment: usize },
+
+    #[error(
+        "subpass {subpass} uses attachment {attachment} without depth and stencil for depth tests"
+    )]
+    NotDepthStencil { subpass: usize, attachment: usize },
+
+    #[error("subpass {subpass} has {resolve} resolve attachments for {color} color attachments")]
+    ResolveCount {
+        subpass: usize,
+        color: usize,
+        resolve: usize,
+    },
+
+    #[error("dependency refers to subpass {subpass}, but there are {count} subpasses")]
+    SubpassOutOfRange { subpass: usize, count: usize },
+
+    #[error("subpass {dependent} cannot depend on later subpass {dependency}")]
+    DependencyOrder { dependency: usize, dependent: usize },
+}
+
+/// Creates framebuffer of the render pass from image views in order of its attachments.
+///
+/// # Errors
+///
+/// An error is returned if count of views does not match count of attachments
+/// or exceeds [`MAX_FRAMEBUFFER_ATTACHMENTS`], or if views do not match attachments.
+///
+pub fn framebuffer(
+    render_pass: Arc<RenderPass>,
+    views: &[Arc<dyn ImageViewAbstract + Send + Sync>],
+) -> Result<Arc<dyn FramebufferAbstract + Send + Sync>, FramebufferCreationError> {
+    // Attachments of the framebuffer builder are typed, so each count is built separately.
+    macro_rules! build {
+        ($([$($view:ident),+]),+ $(,)?) => {
+            match views {
+                $([$($view),+] => {
+                    let builder = Framebuffer::start(render_pass);
+                    $(let builder = builder.add($view.clone())?;)+
+                    Ok(Arc::new(builder.build()?))
+                })+
+                _ => Err(FramebufferCreationError::AttachmentsCountMismatch {
+                    expected: render_pass.desc().attachments().len(),
+                    obtained: views.len(),
+                }),
+            }
+        };
+    }
+    build! {
+        [a],
+        [a, b],
+        [a, b, c],
+        [a, b, c, d],
+        [a, b, c, d, e],
+        [a, b, c, d, e, f],
+        [a, b, c, d, e, f, g],
+        [a, b, c, d, e, f, g, h],
+    }
+}
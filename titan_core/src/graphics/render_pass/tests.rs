@@ -0,0 +1,196 @@
+#![cfg(test)]
+
+use vulkano::format::Format;
+use vulkano::image::{ImageLayout, SampleCount};
+use vulkano::render_pass::{AttachmentDesc, LoadOp, StoreOp, SubpassDependencyDesc};
+use vulkano::sync::{AccessFlags, PipelineStages};
+
+use super::{RenderPassDescriptor, RenderPassDescriptorError, SubpassDescriptor};
+
+fn attachment(format: Format) -> AttachmentDesc {
+    AttachmentDesc {
+        format,
+        samples: SampleCount::Sample1,
+        load: LoadOp::Clear,
+        store: StoreOp::Store,
+        stencil_load: LoadOp::DontCare,
+        stencil_store: StoreOp::DontCare,
+        initial_layout: ImageLayout::Undefined,
+        final_layout: ImageLayout::ColorAttachmentOptimal,
+    }
+}
+
+fn dependency(source_subpass: usize, destination_subpass: usize) -> SubpassDependencyDesc {
+    let all_graphics = PipelineStages {
+        all_graphics: true,
+        ..PipelineStages::none()
+    };
+    SubpassDependencyDesc {
+        source_subpass,
+        destination_subpass,
+        source_stages: all_graphics,
+        destination_stages: all_graphics,
+        source_access: AccessFlags::all(),
+        destination_access: AccessFlags::all(),
+        by_region: true,
+    }
+}
+
+/// Describes deferred shading: G-buffer subpass, then lighting subpass which reads it.
+fn deferred() -> RenderPassDescriptor {
+    RenderPassDescriptor {
+        attachments: vec![
+            self::attachment(Format::B8G8R8A8_SRGB),
+            self::attachment(Format::R16G16B16A16_SFLOAT),
+            self::attachment(Format::D32_SFLOAT),
+        ],
+        subpasses: vec![
+            SubpassDescriptor {
+                color_attachments: vec![1],
+                depth_stencil: Some(2),
+                ..SubpassDescriptor::default()
+            },
+            SubpassDescriptor {
+                color_attachments: vec![0],
+                input_attachments: vec![1, 2],
+                ..SubpassDescriptor::default()
+            },
+        ],
+        dependencies: vec![self::dependency(0, 1)],
+    }
+}
+
+#[test]
+fn swapchain_pass_is_valid() {
+    for samples in [SampleCount::Sample1, SampleCount::Sample4] {
+        for depth in [None, Some(Format::D32_SFLOAT)] {
+            let descriptor = RenderPassDescriptor::swapchain(Format::B8G8R8A8_SRGB, depth, samples);
+            assert_eq!(descriptor.validate(), Ok(()));
+            assert_eq!(descriptor.subpasses.len(), 2);
+        }
+    }
+    let descriptor = RenderPassDescriptor::swapchain(
+        Format::B8G8R8A8_SRGB,
+        Some(Format::D32_SFLOAT),
+        SampleCount::Sample4,
+    );
+    assert_eq!(descriptor.attachments.len(), 3);
+    assert_eq!(descriptor.subpasses[0].color_attachments, [2]);
+    assert_eq!(descriptor.subpasses[0].resolve_attachments, [0]);
+    assert_eq!(descriptor.subpasses[0].depth_stencil, Some(1));
+}
+
+#[test]
+fn input_attachments_are_read_only() {
+    let desc = deferred().build().unwrap();
+    let lighting = &desc.subpasses()[1];
+    assert_eq!(
+        lighting.input_attachments,
+        [
+            (1, ImageLayout::ShaderReadOnlyOptimal),
+            (2, ImageLayout::DepthStencilReadOnlyOptimal),
+        ],
+    );
+    assert_eq!(
+        desc.subpasses()[0].depth_stencil,
+        Some((2, ImageLayout::DepthStencilAttachmentOptimal)),
+    );
+}
+
+#[test]
+fn attachment_references_are_validated() {
+    let mut descriptor = deferred();
+    descriptor.subpasses[1].input_attachments.push(3);
+    assert_eq!(
+        descriptor.validate(),
+        Err(RenderPassDescriptorError::AttachmentOutOfRange {
+            subpass: 1,
+            attachment: 3,
+            count: 3,
+        }),
+    );
+
+    let mut descriptor = deferred();
+    descriptor.subpasses[0].depth_stencil = Some(1);
+    assert_eq!(
+        descriptor.validate(),
+        Err(RenderPassDescriptorError::NotDepthStencil {
+            subpass: 0,
+            attachment: 1,
+        }),
+    );
+
+    let mut descriptor = deferred();
+    descriptor.subpasses[1].color_attachments.push(2);
+    assert_eq!(
+        descriptor.validate(),
+        Err(RenderPassDescriptorError::NotColor {
+            subpass: 1,
+            attachment: 2,
+        }),
+    );
+
+    let mut descriptor = deferred();
+    descriptor.subpasses[0].resolve_attachments = vec![0, 0];
+    assert_eq!(
+        descriptor.validate(),
+        Err(RenderPassDescriptorError::ResolveCount {
+            subpass: 0,
+            color: 1,
+            resolve: 2,
+        }),
+    );
+}
+
+#[test]
+fn dependencies_are_validated() {
+    let mut descriptor = deferred();
+    descriptor.dependencies.push(self::dependency(1, 2));
+    assert_eq!(
+        descriptor.validate(),
+        Err(RenderPassDescriptorError::SubpassOutOfRange {
+            subpass: 2,
+            count: 2,
+        }),
+    );
+
+    let mut descriptor = deferred();
+    descriptor.dependencies = vec![self::dependency(1, 0)];
+    assert_eq!(
+        descriptor.validate(),
+        Err(RenderPassDescriptorError::DependencyOrder {
+            dependency: 1,
+            dependent: 0,
+        }),
+    );
+
+    let descriptor = RenderPassDescriptor::default();
+    assert_eq!(
+        descriptor.validate(),
+        Err(RenderPassDescriptorError::NoSubpasses)
+    );
+}
+
+#[test]
+fn offscreen_pass_has_one_subpass() {
+    let descriptor = RenderPassDescriptor::offscreen(
+        Format::R16G16B16A16_SFLOAT,
+        Some(Format::D32_SFLOAT),
+        SampleCount::Sample1,
+    );
+    assert_eq!(descriptor.validate(), Ok(()));
+    assert_eq!(descriptor.attachments.len(), 2);
+    assert_eq!(descriptor.subpasses.len(), 1);
+    assert!(descriptor.dependencies.is_empty());
+}
+
+#[test]
+fn overlay_pass_keeps_load_op() {
+    for load in [LoadOp::DontCare, LoadOp::Load] {
+        let descriptor = RenderPassDescriptor::overlay(Format::B8G8R8A8_SRGB, load);
+        assert_eq!(descriptor.validate(), Ok(()));
+        assert_eq!(descriptor.attachments.len(), 1);
+        assert_eq!(descriptor.attachments[0].load, load);
+        assert_eq!(descriptor.subpasses.len(), 1);
+    }
+}
@@ -1,25 +1,43 @@
 //! Error types and utilities for graphics backend for game engine.
 
+use std::io;
+
 use thiserror::Error;
-use vulkano::command_buffer::{BuildError, CommandBufferExecError, UpdateBufferError};
+use vulkano::command_buffer::{
+    BlitImageError, BuildError, ClearColorImageError, CommandBufferExecError, UpdateBufferError,
+};
 use vulkano::descriptor_set::DescriptorSetError;
 use vulkano::device::DeviceCreationError;
+use vulkano::format::Format;
 use vulkano::image::view::ImageViewCreationError;
 use vulkano::image::ImageCreationError;
 use vulkano::instance::debug::DebugCallbackCreationError;
 use vulkano::instance::InstanceCreationError;
 use vulkano::memory::DeviceMemoryAllocError;
-use vulkano::swapchain::{AcquireError, CapabilitiesError, SwapchainCreationError};
+use vulkano::swapchain::{
+    AcquireError, CapabilitiesError, SurfaceCreationError, SwapchainCreationError,
+};
 use vulkano::sync::FlushError;
 use vulkano::OomError;
 
+use crate::config::SwapchainUsage;
+use crate::graphics::adapter::{DeviceSelectionError, MissingRequirementsError};
 use crate::graphics::frame::{
+    external::error::ExternalPassError,
     object_draw::error::{ObjectDrawError, ObjectDrawSystemCreationError},
+    picking::error::{PickError, PickingSystemCreationError},
+    post::error::{PostDrawError, PostProcessSystemCreationError},
     system::error::{
         DrawPassExecuteError, FrameCreationError, FrameSystemCreationError, NextPassError,
     },
     ui_draw::error::{UiDrawError, UiDrawSystemCreationError},
 };
+use crate::graphics::readback::ReadbackError;
+use crate::graphics::renderer::uniform_ring::UniformRingError;
+
+pub use crate::graphics::frame::object_draw::error::{
+    MeshError, ObjectPipelineError, ObjectShaderError,
+};
 
 /// Error that can happen when creating the [`Renderer`](super::Renderer) system.
 #[derive(Debug, Error)]
@@ -36,6 +54,12 @@ pub enum RendererCreationError {
     #[error("no suitable physical device were found")]
     NoSuitablePhysicalDevice,
 
+    #[error("physical device selection failure: {0}")]
+    DeviceSelection(#[from] DeviceSelectionError),
+
+    #[error("{0}")]
+    MissingRequirements(#[from] MissingRequirementsError),
+
     #[error("device creation failure: {0}")]
     DeviceCreation(#[from] DeviceCreationError),
 
@@ -56,6 +80,40 @@ pub enum RendererCreationError {
 
     #[error("UI draw system creation failure: {0}")]
     UiDrawSystemCreation(#[from] UiDrawSystemCreationError),
+
+    #[error("picking system creation failure: {0}")]
+    PickingSystemCreation(#[from] PickingSystemCreationError),
+
+    #[error("post process system creation failure: {0}")]
+    PostProcessSystemCreation(#[from] PostProcessSystemCreationError),
+
+    #[error("present thread creation failure: {0}")]
+    PresentThreadCreation(#[from] PresentThreadError),
+
+    #[error("pipeline cache creation failure: {0}")]
+    PipelineCacheCreation(#[from] OomError),
+
+    #[error("invalid count of frames in flight {requested}: must be between 1 and {max}")]
+    FramesInFlight { requested: u32, max: u32 },
+
+    #[error("swapchain usage {0:?} is not supported by the surface")]
+    UnsupportedSwapchainUsage(SwapchainUsage),
+
+    #[error("offscreen image creation failure: {0}")]
+    HeadlessImageCreation(#[from] ImageCreationError),
+}
+
+/// Error that can happen when using dedicated present thread.
+#[derive(Debug, Error)]
+pub enum PresentThreadError {
+    #[error("presentation from dedicated thread is not supported on this platform")]
+    Unsupported,
+
+    #[error("failed to spawn present thread: {0}")]
+    Spawn(#[from] io::Error),
+
+    #[error("present thread was disconnected")]
+    Disconnected,
 }
 
 /// Error that can happen on descriptor set creation.
@@ -70,6 +128,48 @@ pub enum DescriptorSetCreationError {
 pub enum ResizeError {
     #[error("swapchain recreation failure: {0}")]
     SwapchainRecreation(#[from] SwapchainCreationError),
+
+    #[error("failed to wait for device idle: {0}")]
+    DeviceWait(#[from] OomError),
+
+    #[error("failed to get surface capabilities: {0}")]
+    SurfaceCapabilitiesRetrieve(#[from] CapabilitiesError),
+
+    #[error("present thread failure while resizing: {0}")]
+    PresentThread(#[from] PresentThreadError),
+
+    #[error("offscreen image recreation failure: {0}")]
+    HeadlessImageCreation(#[from] ImageCreationError),
+}
+
+/// Error that can happen when the surface of the window of [`Renderer`](super::Renderer) system
+/// is lost or restored.
+///
+#[derive(Debug, Error)]
+pub enum SurfaceError {
+    #[error("present thread failure while waiting for frames in flight: {0}")]
+    PresentThread(#[from] PresentThreadError),
+
+    #[error("failed to wait for device idle: {0}")]
+    DeviceWait(#[from] OomError),
+
+    #[error("surface creation failure: {0}")]
+    SurfaceCreation(#[from] SurfaceCreationError),
+
+    #[error("failed to get surface capabilities: {0}")]
+    SurfaceCapabilitiesRetrieve(#[from] CapabilitiesError),
+
+    #[error("present queue of the device cannot present to the restored surface")]
+    PresentUnsupported,
+
+    #[error("restored surface does not support format {0:?} of the lost one")]
+    FormatUnsupported(Format),
+
+    #[error("restored surface does not support swapchain with {0} images of the lost one")]
+    UnsupportedImageCount(usize),
+
+    #[error("swapchain creation failure: {0}")]
+    SwapchainCreation(#[from] SwapchainCreationError),
 }
 
 /// Error that can happen on transfer command buffer creation
@@ -108,17 +208,100 @@ pub enum RenderError {
     #[error("subpass switching failure: {0}")]
     NextPass(#[from] NextPassError),
 
+    #[error("failed to write parameter block of the frame: {0}")]
+    UniformRing(#[from] UniformRingError),
+
     #[error("failed to draw game objects: {0}")]
     ObjectDraw(#[from] ObjectDrawError),
 
     #[error("failed to draw UI: {0}")]
     UiDraw(#[from] UiDrawError),
 
+    #[error("failed to post process the scene: {0}")]
+    PostDraw(#[from] PostDrawError),
+
+    #[error("failed to pick game objects: {0}")]
+    Pick(#[from] PickError),
+
+    #[error("failed to read back device resources: {0}")]
+    Readback(#[from] ReadbackError),
+
+    #[error("failed to record external pass: {0}")]
+    ExternalPass(#[from] ExternalPassError),
+
     #[error("failed to execute draw command buffer: {0}")]
     DrawPassExecution(#[from] DrawPassExecuteError),
 
     #[error("failed to resize while rendering: {0}")]
     Resize(#[from] ResizeError),
+
+    #[error("present thread failure while rendering: {0}")]
+    PresentThread(#[from] PresentThreadError),
+
+    #[error("failed to wait for the frame in flight: {0}")]
+    FrameWait(FlushError),
+
+    #[error("frame could be rendered into the buffer only by headless renderer")]
+    NotHeadless,
+
+    #[error("frame was not submitted to the device, so its pixels were not read back")]
+    FrameNotSubmitted,
+}
+
+/// Error that can happen when creating additional window of [`Renderer`](super::Renderer) system.
+#[derive(Debug, Error)]
+pub enum WindowCreationError {
+    #[error("additional windows require the renderer to present into a window")]
+    Headless,
+
+    #[error("images of the main window could not be copied into additional windows")]
+    SourceNotCopyable,
+
+    #[error("window or surface creation failure: {0}")]
+    SurfaceCreation(#[from] vulkano_win::CreationError),
+
+    #[error("failed to get surface capabilities: {0}")]
+    SurfaceCapabilitiesRetrieve(#[from] CapabilitiesError),
+
+    #[error("present queue of the device cannot present to the surface of the window")]
+    PresentUnsupported,
+
+    #[error("swapchain images of the window could not be copied into")]
+    TransferUnsupported,
+
+    #[error("format {0:?} does not support scaled copies")]
+    FormatNotBlittable(Format),
+
+    #[error("swapchain creation failure: {0}")]
+    SwapchainCreation(#[from] SwapchainCreationError),
+}
+
+/// Error that can happen when presenting frame into additional window.
+#[derive(Debug, Error)]
+pub enum SecondaryWindowError {
+    #[error("swapchain recreation failure: {0}")]
+    SwapchainRecreation(#[from] SwapchainCreationError),
+
+    #[error("failed to get surface capabilities: {0}")]
+    SurfaceCapabilitiesRetrieve(#[from] CapabilitiesError),
+
+    #[error("acquiring next image failure: {0}")]
+    AcquireNextImage(#[from] AcquireError),
+
+    #[error("failed to allocate command buffer: {0}")]
+    OutOfMemory(#[from] OomError),
+
+    #[error("failed to clear the image: {0}")]
+    Clear(#[from] ClearColorImageError),
+
+    #[error("failed to copy the frame: {0}")]
+    Blit(#[from] BlitImageError),
+
+    #[error("command buffer build failure: {0}")]
+    Build(#[from] BuildError),
+
+    #[error("failed to present the image: {0}")]
+    Present(FlushError),
 }
 
 /// Error of registering an image for UI.
@@ -1,68 +1,284 @@
 //! Render utilities for graphics backend for game engine.
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::ffi::{CStr, CString};
 use std::iter;
-use std::sync::Arc;
+use std::mem;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use egui::{ClippedMesh, Texture, TextureId};
 use image::RgbaImage;
-use vulkano::buffer::{BufferUsage, DeviceLocalBuffer};
+use vulkano::buffer::{BufferAccess, BufferUsage, CpuAccessibleBuffer, DeviceLocalBuffer};
 use vulkano::command_buffer::{
     AutoCommandBufferBuilder, CommandBufferUsage, PrimaryAutoCommandBuffer,
 };
-use vulkano::device::physical::PhysicalDevice;
+use vulkano::device::physical::{PhysicalDevice, QueueFamily};
 use vulkano::device::{Device, DeviceExtensions, Features, Queue};
 use vulkano::format::Format;
 use vulkano::image::view::ImageView;
-use vulkano::image::{ImageDimensions, ImageUsage, ImmutableImage, MipmapsCount, SwapchainImage};
-use vulkano::instance::debug::{DebugCallback, MessageSeverity, MessageType};
+use vulkano::image::{
+    AttachmentImage, ImageAccess, ImageCreateFlags, ImageCreationError, ImageDimensions,
+    ImageUsage, ImmutableImage, MipmapsCount, StorageImage, SwapchainImage,
+};
+use vulkano::instance::debug::DebugCallback;
 use vulkano::instance::Instance;
-use vulkano::swapchain::{AcquireError, PresentMode, Surface, Swapchain};
+use vulkano::memory::DeviceMemoryAllocError;
+use vulkano::swapchain::{
+    AcquireError, ColorSpace, FullscreenExclusive, Surface, Swapchain, SwapchainAcquireFuture,
+    SwapchainCreationError,
+};
 use vulkano::sync::{FlushError, GpuFuture, SharingMode};
-use vulkano::{swapchain, sync};
-use vulkano_win::VkSurfaceBuild;
-use winit::dpi::LogicalSize;
-use winit::event_loop::EventLoop;
-use winit::window::{Window, WindowBuilder};
+use vulkano::{swapchain, sync, DeviceSize, OomError};
+use winit::dpi::{LogicalSize, PhysicalSize};
+use winit::event_loop::{EventLoop, EventLoopWindowTarget};
+use winit::window::{Icon, Window, WindowBuilder, WindowId};
 
 pub use error::RendererCreationError;
-use error::{ImageRegisterError, RenderError, ResizeError, TransferCommandBufferCreationError};
+use error::{
+    ImageRegisterError, MeshError, ObjectPipelineError, ObjectShaderError, PresentThreadError,
+    RenderError, ResizeError, SurfaceError, TransferCommandBufferCreationError,
+    WindowCreationError,
+};
 
-use crate::config::Config;
+use self::per_image::{ImageIndex, PerImage};
+use self::secondary::{SecondaryWindow, WindowCopy};
+use self::uniform_ring::UniformRing;
+use crate::{
+    app::spike::FrameCounters,
+    app::stats::{FrameStats, FrameStatsTracker},
+    config::{CompositeAlpha, Config, PresentMode, SwapchainUsage, WindowSize},
+    window::{platform, Size, WindowDescriptor, WindowHandle},
+};
 
+#[cfg(feature = "hot-reload")]
+use super::hot_reload::{HotReloadError, ModifiedShader, ShaderFormat, ShaderWatcher};
+#[cfg(feature = "glsl")]
+use super::spirv::GlslIncludeResolver;
 use super::{
+    adapter::{self, DeviceFeatures, DeviceInfo, MissingRequirementsError, PhysicalDeviceInfo},
     camera::CameraUBO,
+    color::{Color, ColorHandle},
+    compute::{
+        BindingKind, ComputeDescriptor, ComputePipeline, ComputePipelineCreationError,
+        DispatchError,
+    },
+    debug::Nameable,
+    failure::{AssetFailure, FailedAsset, FailureKind, FailureRegistry, OBJECT_MATERIAL},
+    fence::{self, FrameFence, WaitResult},
     frame::{
+        external::{ExternalPassDesc, ExternalPassId, ExternalPassStage, ExternalPassSystem},
         object_draw::ObjectDrawSystem,
+        picking::{PickResult, PickTicket, PickingSystem},
+        post::{error::PostEffectError, PostProcessChain, PostProcessSystem},
         system::{FrameSystem, Pass},
         ui_draw::UiDrawSystem,
     },
+    gpu_timer::GpuTime,
+    index::Indices,
+    memory::{self, HeapMap, MemoryTracker, ResourceCategory},
+    params::{error::ParamBlockError, ParamBlock, ParamValue},
+    pipeline::{GraphicsPipelineDescriptor, PipelineId},
+    pipeline_cache::{PipelineCache, PipelineCacheError},
+    present::{self, NextImage, PresentFuture, PresentThread, PresentedFrame},
+    readback::{ReadbackRegion, Readbacks},
+    screenshot::{self, Screenshot, ScreenshotCallback, ScreenshotError},
+    semaphore::{TimelineSemaphore, TimelineSemaphoreError},
+    spirv::{Shader, ShaderLoadError, ShaderStage},
+    submit::{self, OneTimeBuilder, SubmitOnceError},
+    texture::{SamplerOptions, TextureError, TextureQuality, TextureStats},
+    timeline::FrameTimeline,
+    upload::StagingUploader,
     utils,
+    vertex::{UiVertex, Vertex},
 };
+#[cfg(feature = "hot-reload")]
+use crate::asset::AssetSource;
 
 pub mod error;
+mod per_image;
+mod secondary;
+mod uniform_ring;
+
+/// How many times acquiring of the image is retried after swapchain recreation
+/// before the frame is skipped.
+const MAX_ACQUIRE_ATTEMPTS: usize = 2;
 
 /// System that renders all game objects and UI.
+///
+/// Fields are declared in reverse order of their creation, so they are dropped
+/// in that order after [`Drop`] waits for frames in flight.
+///
 #[allow(dead_code)]
 pub struct Renderer {
-    previous_frame_end: Option<Box<dyn GpuFuture + Send + Sync>>,
+    previous_frame_end: Option<PresentFuture>,
+    frame_fences: VecDeque<(u64, Arc<dyn FrameFence>)>,
+    timeline: FrameTimeline,
+    frames_in_flight: u32,
+    frame_wait_timeout: Option<Duration>,
+    skipped_frames: u64,
+    next_image: Option<NextImage>,
+    present_thread: Option<PresentThread>,
     recreate_swapchain: bool,
+    present_mode: Option<PresentMode>,
+    swapchain_usage: Option<SwapchainUsage>,
     camera_ubo: CameraUBO,
+    frame_counters: FrameCounters,
+    gpu_time: Option<GpuTime>,
+    frame_stats: FrameStatsTracker,
+    last_render: Option<Instant>,
+    rendered_frame: u64,
+    readbacks: Readbacks,
+    screenshots: Vec<ScreenshotCallback>,
+    failures: FailureRegistry,
+    pipeline_cache: PipelineCache,
+    #[cfg(feature = "hot-reload")]
+    shader_watcher: Option<(ShaderWatcher, AssetSource)>,
 
+    post_process_system: Option<PostProcessSystem>,
     ui_draw_system: UiDrawSystem,
+    picking_system: PickingSystem,
+    external_pass_system: ExternalPassSystem,
+    object_tint: ColorHandle,
+    memory: Arc<MemoryTracker>,
     object_draw_system: ObjectDrawSystem,
     frame_system: FrameSystem,
-    uniform_buffers: Vec<Arc<DeviceLocalBuffer<CameraUBO>>>,
+    uniform_ring: UniformRing,
+    uniform_buffers: PerImage<Arc<DeviceLocalBuffer<CameraUBO>>>,
 
-    swapchain_images: Vec<Arc<SwapchainImage<Window>>>,
-    swapchain: Arc<Swapchain<Window>>,
+    output: RenderOutput,
+    secondary_windows: HashMap<WindowId, SecondaryWindow>,
     graphics_queue: Arc<Queue>,
+    compute_queue: Option<Arc<Queue>>,
     present_queue: Arc<Queue>,
     transfer_queue: Arc<Queue>,
     device: Arc<Device>,
-    surface: Arc<Surface<Window>>,
     debug_callback: Option<DebugCallback>,
     instance: Arc<Instance>,
+    physical_device_info: PhysicalDeviceInfo,
+    device_info: DeviceInfo,
+    leak_check: LeakCheck,
+}
+
+/// Reports tracked resources which outlive the renderer.
+///
+/// It is the last field of the renderer, so it is dropped after all other fields.
+///
+struct LeakCheck(Arc<MemoryTracker>);
+
+impl Drop for LeakCheck {
+    fn drop(&mut self) {
+        let leaked = self.0.live_allocations();
+        if leaked.is_empty() {
+            log::debug!("all tracked resources of the renderer were released");
+            return;
+        }
+        log::warn!("{} tracked resources outlive the renderer:", leaked.len());
+        for allocation in leaked {
+            log::warn!(
+                "  {} ({:?}, {} bytes)",
+                allocation.name,
+                allocation.category,
+                allocation.size,
+            );
+        }
+    }
+}
+
+/// Output which frames of the renderer are rendered into.
+enum RenderOutput {
+    /// Swapchain of the window surface.
+    Window {
+        surface: Arc<Surface<Arc<Window>>>,
+        swapchain: Arc<Swapchain<Arc<Window>>>,
+        images: Vec<Arc<SwapchainImage<Arc<Window>>>>,
+        color_space: ColorSpace,
+    },
+    /// Window whose surface was destroyed (e.g. when the application was suspended),
+    /// nothing is rendered until the surface is restored.
+    Lost(LostSurface),
+    /// Image which is owned by the renderer, without a window.
+    Headless(Arc<AttachmentImage>),
+}
+
+/// Window whose surface was lost, with parameters of the swapchain to restore it with.
+#[derive(Clone)]
+struct LostSurface {
+    window: Arc<Window>,
+    format: Format,
+    color_space: ColorSpace,
+    dimensions: [u32; 2],
+    image_count: usize,
+    usage: ImageUsage,
+    present_mode: swapchain::PresentMode,
+    composite_alpha: swapchain::CompositeAlpha,
+    fullscreen_exclusive: FullscreenExclusive,
+}
+
+impl RenderOutput {
+    /// Dimensions of images which frames are rendered into.
+    fn dimensions(&self) -> [u32; 2] {
+        match self {
+            Self::Window { swapchain, .. } => swapchain.dimensions(),
+            Self::Lost(lost) => lost.dimensions,
+            Self::Headless(image) => ImageAccess::dimensions(&**image).width_height(),
+        }
+    }
+
+    /// Count of images which frames are rendered into.
+    fn image_count(&self) -> usize {
+        match self {
+            Self::Window { images, .. } => images.len(),
+            Self::Lost(lost) => lost.image_count,
+            Self::Headless(_) => 1,
+        }
+    }
+
+    /// Image with provided index which frames are rendered into.
+    fn image(&self, index: ImageIndex) -> Arc<dyn ImageAccess + Send + Sync> {
+        match self {
+            Self::Window { images, .. } => images[index.get()].clone(),
+            Self::Lost(_) => unreachable!("lost surface has no images"),
+            Self::Headless(image) => image.clone(),
+        }
+    }
+
+    /// Format of images which frames are rendered into.
+    fn format(&self) -> Format {
+        match self {
+            Self::Window { swapchain, .. } => swapchain.format(),
+            Self::Lost(lost) => lost.format,
+            Self::Headless(image) => image.format(),
+        }
+    }
+
+    /// Usage of images which frames are rendered into.
+    fn usage(&self) -> ImageUsage {
+        match self {
+            Self::Window { images, .. } => images[0].inner().image.usage(),
+            Self::Lost(lost) => lost.usage,
+            Self::Headless(image) => image.inner().image.usage(),
+        }
+    }
+
+    /// Underlying window, or `None` if frames are rendered offscreen.
+    fn window(&self) -> Option<&Arc<Window>> {
+        match self {
+            Self::Window { surface, .. } => Some(surface.window()),
+            Self::Lost(lost) => Some(&lost.window),
+            Self::Headless(_) => None,
+        }
+    }
+}
+
+/// Index of the image which the frame is rendered into,
+/// with future which signals when the image is acquired from the swapchain.
+type AcquiredImage = (ImageIndex, Option<SwapchainAcquireFuture<Arc<Window>>>);
+
+/// Target which is requested on renderer creation.
+enum OutputRequest {
+    Window(Arc<Surface<Arc<Window>>>),
+    Headless(Size),
 }
 
 impl Renderer {
@@ -71,49 +287,147 @@ pub fn new<T>(config: &Config, event_loop: &EventLoop<T>) -> Result<Self, Render
     where
         T: 'static,
     {
-        let instance = utils::create_instance(config)?;
+        let (instance, debug_callback) = Self::create_instance(config, true)?;
+
+        let mut builder = WindowBuilder::new()
+            .with_title(config.window_title())
+            .with_min_inner_size(LogicalSize::new(250, 100))
+            .with_resizable(config.resizable())
+            .with_decorations(config.decorations())
+            .with_maximized(config.maximized())
+            .with_visible(false);
+        // Logical size is converted by the platform with the scale factor of the monitor.
+        match config.window_size() {
+            Some(WindowSize::Physical(size)) => {
+                builder = builder.with_inner_size(PhysicalSize::new(size.width, size.height))
+            }
+            Some(WindowSize::Logical(size)) => {
+                builder = builder.with_inner_size(LogicalSize::new(size.width, size.height))
+            }
+            None => (),
+        }
+        if let Some(icon) = config.window_icon() {
+            let icon = Icon::from_rgba(icon.rgba.clone(), icon.width, icon.height)
+                .map_err(|error| log::warn!("window icon is ignored: {}", error))
+                .ok();
+            builder = builder.with_window_icon(icon);
+        }
+        let builder = platform::desktop_integration(builder, config);
+        let surface = utils::build_surface(builder, event_loop, instance.clone())?;
+        log::info!("window & surface initialized successfully");
+
+        let output = OutputRequest::Window(surface);
+        Self::with_output(config, instance, debug_callback, output)
+    }
+
+    /// Creates render system without a window, which renders frames into the image
+    /// of provided size owned by the renderer.
+    ///
+    /// Neither surface nor swapchain are created, so this mode works where no display exists
+    /// (for example, in CI or on the server). Rendered pixels could be retrieved
+    /// by [`render_frame_to_buffer`](Self::render_frame_to_buffer).
+    ///
+    pub fn new_headless(config: &Config, size: Size) -> Result<Self, RendererCreationError> {
+        let (instance, debug_callback) = Self::create_instance(config, false)?;
+        let output = OutputRequest::Headless(size);
+        Self::with_output(config, instance, debug_callback, output)
+    }
+
+    fn create_instance(
+        config: &Config,
+        window: bool,
+    ) -> Result<(Arc<Instance>, Option<DebugCallback>), RendererCreationError> {
+        let instance = utils::create_instance(config, window)?;
         log::info!(
             "max version of Vulkan instance is {}",
             instance.max_api_version(),
         );
 
-        let debug_callback = config
-            .enable_validation()
+        // Validation could be requested by config, but be unavailable at runtime.
+        let debug_callback = instance
+            .enabled_extensions()
+            .ext_debug_utils
             .then(|| {
-                use super::debug_callback::create_debug_callback as new;
-                let debug_callback = new(&instance, MessageSeverity::all(), MessageType::all())?;
+                use super::debug::create_debug_callback as new;
+                let debug_callback = new(
+                    &instance,
+                    config.debug_filter(),
+                    config.debug_callback().cloned(),
+                )?;
                 log::info!("debug callback was attached to the instance");
                 Result::<_, RendererCreationError>::Ok(debug_callback)
             })
             .transpose()?;
+        Ok((instance, debug_callback))
+    }
 
-        let surface = WindowBuilder::new()
-            .with_title(config.name())
-            .with_min_inner_size(LogicalSize::new(250, 100))
-            .with_visible(false)
-            .build_vk_surface(event_loop, instance.clone())?;
-        log::info!("window & surface initialized successfully");
-
+    fn with_output(
+        config: &Config,
+        instance: Arc<Instance>,
+        debug_callback: Option<DebugCallback>,
+        output: OutputRequest,
+    ) -> Result<Self, RendererCreationError> {
+        let surface = match &output {
+            OutputRequest::Window(surface) => Some(surface),
+            OutputRequest::Headless(_) => None,
+        };
         let physical_devices = PhysicalDevice::enumerate(&instance);
         log::info!("enumerated {} physical devices", physical_devices.len());
 
         let required_extensions = DeviceExtensions {
-            khr_swapchain: true,
+            khr_swapchain: surface.is_some(),
             ..DeviceExtensions::none()
         };
         let required_features = Features::none();
+        let requirements = config.device_requirements();
+        let mut missing_requirements = Vec::new();
+        let physical_devices: Vec<_> = physical_devices
+            .filter(|physical_device| {
+                let missing = requirements.missing(
+                    DeviceFeatures::from(physical_device.supported_features()),
+                    &utils::extension_names(physical_device.supported_extensions()),
+                );
+                if missing.is_empty() {
+                    return true;
+                }
+                let name = physical_device.properties().device_name.clone();
+                log::info!(r#"device "{}" lacks {}"#, name, missing.join(", "));
+                missing_requirements.push((name, missing));
+                false
+            })
+            .collect();
+        let mut suitable_devices = utils::suitable_physical_devices(
+            physical_devices.into_iter(),
+            surface,
+            &required_extensions,
+            &required_features,
+        );
+        let candidates: Vec<_> = suitable_devices
+            .iter()
+            .map(|suitable| PhysicalDeviceInfo::new(suitable.physical_device))
+            .collect();
+        log::info!("{} suitable physical devices:", candidates.len());
+        for candidate in &candidates {
+            log::info!("  {}", candidate);
+        }
+        let position = match adapter::select_device(&candidates, config.device_preference()) {
+            Some(position) => position?,
+            None if !missing_requirements.is_empty() => {
+                let devices = missing_requirements;
+                return Err(MissingRequirementsError { devices }.into());
+            }
+            None => return Err(RendererCreationError::NoSuitablePhysicalDevice),
+        };
+        let physical_device_info = candidates[position].clone();
+        let device_info = DeviceInfo::new(suitable_devices[position].physical_device);
+        log::debug!("selected device:\n{}", device_info);
         let utils::SuitablePhysicalDevice {
             physical_device,
             graphics_family,
             present_family,
             transfer_family,
-        } = utils::suitable_physical_device(
-            physical_devices,
-            &surface,
-            &required_extensions,
-            &required_features,
-        )
-        .ok_or_else(|| RendererCreationError::NoSuitablePhysicalDevice)?;
+            compute_family,
+        } = suitable_devices.swap_remove(position);
         log::info!(
             r#"using device "{}" of type "{:?}" with Vulkan version {}"#,
             physical_device.properties().device_name,
@@ -121,13 +435,14 @@ pub fn new<T>(config: &Config, event_loop: &EventLoop<T>) -> Result<Self, Render
             physical_device.api_version(),
         );
 
-        let (device, mut queues) = {
+        let (device, queues) = {
             let priorities = 1.0;
             let unique_queue_families = {
                 let unique_queue_families: HashSet<_> = [
                     graphics_family.id(),
                     present_family.unwrap_or(graphics_family).id(),
                     transfer_family.unwrap_or(graphics_family).id(),
+                    compute_family.unwrap_or(graphics_family).id(),
                 ]
                 .iter()
                 .cloned()
@@ -139,9 +454,22 @@ pub fn new<T>(config: &Config, event_loop: &EventLoop<T>) -> Result<Self, Render
                     )
                 })
             };
+            let enabled = requirements.negotiate(
+                DeviceFeatures::from(physical_device.supported_features()),
+                &utils::extension_names(physical_device.supported_extensions()),
+            );
+            log::info!(
+                "enabled requested features {:?} and extensions {:?}",
+                enabled.features.names(),
+                enabled.extensions,
+            );
+            let required_features = enabled.features.to_vk();
             let required_extensions = physical_device
                 .required_extensions()
-                .union(&required_extensions);
+                .union(&required_extensions)
+                .union(&utils::device_extensions(&enabled.extensions))
+                .union(&utils::portability_extensions(physical_device))
+                .union(&utils::full_screen_exclusive_extensions(physical_device));
             Device::new(
                 physical_device,
                 &required_features,
@@ -149,118 +477,985 @@ pub fn new<T>(config: &Config, event_loop: &EventLoop<T>) -> Result<Self, Render
                 unique_queue_families,
             )?
         };
-        let graphics_queue = queues.next().unwrap();
-        let present_queue = queues.next().unwrap_or_else(|| graphics_queue.clone());
-        let transfer_queue = queues.next().unwrap_or_else(|| graphics_queue.clone());
-
-        let (swapchain, swapchain_images) = {
-            let capabilities = surface.capabilities(physical_device)?;
-            let (format, color_space) = utils::suitable_image_format(&capabilities);
-            let present_mode = capabilities
-                .present_modes
+        let memory = Arc::new(MemoryTracker::new(physical_device));
+
+        // Queues are created in arbitrary order of unique families, so they are found by family.
+        let queues: Vec<_> = queues.collect();
+        let queue_of = |family: QueueFamily| {
+            let queue = queues
                 .iter()
-                .find(|&mode| mode == PresentMode::Mailbox)
-                .unwrap_or(PresentMode::Fifo);
-            let dimensions = if let Some(current_extent) = capabilities.current_extent {
-                current_extent
-            } else {
-                let window_size = surface.window().inner_size();
-                let min_width = capabilities.min_image_extent[0];
-                let max_width = capabilities.max_image_extent[0];
-                let min_height = capabilities.min_image_extent[1];
-                let max_height = capabilities.max_image_extent[1];
-                [
-                    window_size.width.clamp(min_width, max_width),
-                    window_size.height.clamp(min_height, max_height),
-                ]
-            };
-            let image_count = {
-                let image_count = capabilities.min_image_count + 1;
-                if let Some(max_image_count) = capabilities.max_image_count {
-                    image_count.max(max_image_count)
-                } else {
-                    image_count
+                .find(|queue| queue.family().id() == family.id());
+            queue.cloned().unwrap()
+        };
+        let graphics_queue = queue_of(graphics_family);
+        let present_queue = queue_of(present_family.unwrap_or(graphics_family));
+        let transfer_queue = queue_of(transfer_family.unwrap_or(graphics_family));
+        let compute_queue = compute_family.map(queue_of);
+
+        let frames_in_flight = config.frames_in_flight();
+        let mut swapchain_usage = None;
+        let output = match output {
+            OutputRequest::Window(surface) => {
+                let capabilities = surface.capabilities(physical_device)?;
+                let (format, color_space, choice) = utils::suitable_image_format(
+                    &capabilities.supported_formats,
+                    config.surface_formats(),
+                );
+                log::info!(
+                    "surface format {:?} in color space {:?} was selected: {}",
+                    format,
+                    color_space,
+                    choice,
+                );
+                let present_mode = utils::suitable_present_mode(
+                    &capabilities.present_modes,
+                    config.present_mode(),
+                );
+                log::info!("present mode {:?} was selected", present_mode);
+                let dimensions =
+                    utils::choose_extent(&capabilities, surface.window().inner_size().into());
+                let desired_image_count = config.swapchain_image_count();
+                let image_count =
+                    utils::choose_image_count(&capabilities, frames_in_flight, desired_image_count)
+                        .ok_or(RendererCreationError::FramesInFlight {
+                            requested: frames_in_flight,
+                            max: capabilities.max_image_count.unwrap_or(u32::MAX),
+                        })?;
+                if let Some(desired) = desired_image_count.filter(|&count| count != image_count) {
+                    log::info!(
+                        "swapchain image count {} was clamped to {} by surface capabilities",
+                        desired,
+                        image_count,
+                    );
                 }
-            };
-            let sharing_mode = present_family
-                .as_ref()
-                .map(|present_family| {
-                    (present_family.id() != graphics_family.id()).then(|| {
-                        let queues = [&graphics_queue, &present_queue];
-                        SharingMode::from(&queues[..])
+                // Screenshots are copied from swapchain images if the surface allows it.
+                let usage = utils::swapchain_usage(
+                    capabilities.supported_usage_flags,
+                    config.swapchain_usage(),
+                )
+                .map_err(RendererCreationError::UnsupportedSwapchainUsage)?;
+                swapchain_usage = Some(SwapchainUsage {
+                    transfer_src: usage.transfer_source,
+                    transfer_dst: usage.transfer_destination,
+                });
+                let composite_alpha = utils::suitable_composite_alpha(
+                    &capabilities.supported_composite_alpha,
+                    config.composite_alpha(),
+                );
+                let sharing_mode = present_family
+                    .as_ref()
+                    .and_then(|present_family| {
+                        (present_family.id() != graphics_family.id()).then(|| {
+                            let queues = [&graphics_queue, &present_queue];
+                            SharingMode::from(&queues[..])
+                        })
                     })
-                })
-                .flatten()
-                .unwrap_or_else(|| SharingMode::from(&graphics_queue));
-            Swapchain::start(device.clone(), surface.clone())
-                .format(format)
-                .color_space(color_space)
-                .present_mode(present_mode)
-                .dimensions(dimensions)
-                .num_images(image_count)
-                .transform(capabilities.current_transform)
-                .sharing_mode(sharing_mode)
-                .usage(ImageUsage::color_attachment())
-                .build()?
+                    .unwrap_or_else(|| SharingMode::from(&graphics_queue));
+                // Vulkano does not provide monitor of Win32 surface which application-controlled
+                // exclusivity requires, so the driver decides when to enter exclusive fullscreen.
+                let fullscreen_exclusive = if device.enabled_extensions().ext_full_screen_exclusive
+                {
+                    log::info!("exclusive fullscreen of the swapchain is allowed");
+                    FullscreenExclusive::Allowed
+                } else {
+                    FullscreenExclusive::Default
+                };
+                let (swapchain, images) = Swapchain::start(device.clone(), surface.clone())
+                    .format(format)
+                    .color_space(color_space)
+                    .present_mode(present_mode)
+                    .dimensions(dimensions)
+                    .num_images(image_count)
+                    .transform(capabilities.current_transform)
+                    .composite_alpha(composite_alpha)
+                    .fullscreen_exclusive(fullscreen_exclusive)
+                    .sharing_mode(sharing_mode)
+                    .usage(usage)
+                    .build()?;
+                self::name_swapchain(&swapchain, &images);
+                RenderOutput::Window {
+                    surface,
+                    swapchain,
+                    images,
+                    color_space,
+                }
+            }
+            OutputRequest::Headless(size) => {
+                let image = self::headless_image(&device, size.into(), &memory)?;
+                log::info!("rendering offscreen into image of size {:?}", size);
+                RenderOutput::Headless(image)
+            }
         };
 
-        let uniform_buffers = swapchain_images
-            .iter()
-            .map(|_| {
-                DeviceLocalBuffer::new(
-                    device.clone(),
-                    BufferUsage::uniform_buffer_transfer_destination(),
-                    iter::once(transfer_queue.family()),
-                )
-            })
-            .collect::<Result<Vec<_>, _>>()?;
+        let uniform_buffers = PerImage::try_new(output.image_count(), |_| {
+            DeviceLocalBuffer::new(
+                device.clone(),
+                BufferUsage::uniform_buffer_transfer_destination(),
+                iter::once(transfer_queue.family()),
+            )
+        })?;
+        for (index, uniform_buffer) in uniform_buffers.iter() {
+            let index = index.get();
+            let name = format!("camera uniform #{}", index);
+            uniform_buffer.inner().buffer.set_name(&name);
+            memory.track(
+                uniform_buffer,
+                format!("camera uniform #{}", index),
+                ResourceCategory::UniformBuffer,
+                mem::size_of::<CameraUBO>() as DeviceSize,
+            );
+        }
+        let uniform_ring = UniformRing::new(
+            device.clone(),
+            transfer_queue.family(),
+            output.image_count(),
+            &memory,
+        )?;
+
+        let msaa_samples = {
+            let properties = physical_device.properties();
+            utils::supported_sample_count(
+                config.msaa_samples(),
+                properties.framebuffer_color_sample_counts,
+                properties.framebuffer_depth_sample_counts,
+            )
+        };
+        if msaa_samples as u32 != config.msaa_samples().max(1) {
+            log::warn!(
+                "{} samples per pixel are not supported, using {} instead",
+                config.msaa_samples(),
+                msaa_samples as u32,
+            );
+        }
+        let frame_system = FrameSystem::new(
+            graphics_queue.clone(),
+            output.format(),
+            config.depth_buffer(),
+            msaa_samples,
+            config.clear_color(),
+            config.post_processing(),
+            memory.clone(),
+            output.image_count(),
+        )?;
+
+        let pipeline_cache = PipelineCache::load(device.clone(), config.pipeline_cache_path())?;
 
-        let frame_system = FrameSystem::new(graphics_queue.clone(), swapchain.format())?;
+        let picking_system =
+            PickingSystem::new(graphics_queue.clone(), memory.clone(), frames_in_flight)?;
+        let external_pass_system = ExternalPassSystem::new(graphics_queue.clone());
+
+        let object_draw_system = ObjectDrawSystem::new(
+            graphics_queue.clone(),
+            frame_system.object_subpass(),
+            picking_system.subpass(),
+            StagingUploader::new(transfer_queue.clone(), graphics_queue.clone()),
+            memory.clone(),
+            pipeline_cache.cache(),
+        )?;
 
-        let object_draw_system =
-            ObjectDrawSystem::new(graphics_queue.clone(), frame_system.object_subpass())?;
+        let ui_draw_system = UiDrawSystem::new(
+            graphics_queue.clone(),
+            frame_system.ui_subpass(),
+            memory.clone(),
+            pipeline_cache.cache(),
+        )?;
+
+        let post_process_system = frame_system
+            .post_subpass()
+            .map(|subpass| {
+                PostProcessSystem::new(graphics_queue.clone(), subpass, pipeline_cache.cache())
+            })
+            .transpose()?;
 
-        let ui_draw_system = UiDrawSystem::new(graphics_queue.clone(), frame_system.ui_subpass())?;
+        let windowed = output.window().is_some();
+        let present_thread = if config.present_thread() && windowed {
+            match PresentThread::new(present_queue.clone()) {
+                Ok(present_thread) => {
+                    log::info!("images will be presented from the dedicated thread");
+                    Some(present_thread)
+                }
+                Err(PresentThreadError::Unsupported) => {
+                    log::warn!("present thread is not supported on this platform, ignoring");
+                    None
+                }
+                Err(error) => return Err(error.into()),
+            }
+        } else {
+            None
+        };
 
         let previous_frame_end = Some(Box::new(sync::now(device.clone())) as Box<_>);
         Ok(Self {
             instance,
+            physical_device_info,
+            device_info,
+            leak_check: LeakCheck(memory.clone()),
             debug_callback,
-            surface,
             device,
             graphics_queue,
+            compute_queue,
             present_queue,
             transfer_queue,
-            swapchain,
-            swapchain_images,
+            output,
+            secondary_windows: HashMap::new(),
             uniform_buffers,
+            uniform_ring,
             frame_system,
             object_draw_system,
             ui_draw_system,
+            post_process_system,
+            picking_system,
+            external_pass_system,
+            object_tint: ColorHandle::default(),
+            memory,
             camera_ubo: CameraUBO::default(),
             previous_frame_end,
+            frame_fences: VecDeque::with_capacity(frames_in_flight as usize),
+            frames_in_flight,
+            frame_wait_timeout: config.frame_wait_timeout(),
+            skipped_frames: 0,
+            timeline: FrameTimeline::default(),
+            present_thread,
+            next_image: None,
             recreate_swapchain: false,
+            present_mode: config.present_mode(),
+            swapchain_usage,
+            frame_counters: FrameCounters::default(),
+            gpu_time: None,
+            frame_stats: FrameStatsTracker::new(config.stats_window()),
+            last_render: None,
+            rendered_frame: 0,
+            readbacks: Readbacks::default(),
+            screenshots: Vec::new(),
+            failures: FailureRegistry::default(),
+            pipeline_cache,
+            #[cfg(feature = "hot-reload")]
+            shader_watcher: None,
+        })
+    }
+
+    /// Count of frames which could be processed by the device at the same time.
+    pub fn frames_in_flight(&self) -> u32 {
+        self.frames_in_flight
+    }
+
+    /// Count of images which frames are rendered into:
+    /// images of the swapchain, or the only image of offscreen output.
+    ///
+    /// It could differ from [count of frames in flight](Self::frames_in_flight),
+    /// so resources of each image are indexed by the acquired image, not by the frame.
+    ///
+    pub fn image_count(&self) -> usize {
+        self.output.image_count()
+    }
+
+    /// Additional usage which swapchain images were created with, or `None` without a window.
+    ///
+    /// Preferences are preserved when the swapchain is recreated.
+    ///
+    pub fn swapchain_usage(&self) -> Option<SwapchainUsage> {
+        self.swapchain_usage
+    }
+
+    /// Way the compositor blends presented images, or `None` without a window.
+    pub fn composite_alpha(&self) -> Option<CompositeAlpha> {
+        match &self.output {
+            RenderOutput::Window { swapchain, .. } => {
+                Some(utils::from_vk_composite_alpha(swapchain.composite_alpha()))
+            }
+            RenderOutput::Lost(lost) => Some(utils::from_vk_composite_alpha(lost.composite_alpha)),
+            RenderOutput::Headless(_) => None,
+        }
+    }
+
+    /// Count of frames which were skipped because the device was behind
+    /// longer than [frame wait timeout](crate::config::Config::with_frame_wait_timeout).
+    pub fn skipped_frames(&self) -> u64 {
+        self.skipped_frames
+    }
+
+    /// Count of frames which were submitted to the device.
+    ///
+    /// Frames are numbered from `0` in order of submission,
+    /// so this is also the number of the next submitted frame.
+    ///
+    pub fn frame_counter(&self) -> u64 {
+        self.timeline.frame_counter()
+    }
+
+    /// Number of the last frame which was rendered.
+    pub fn rendered_frame(&self) -> u64 {
+        self.rendered_frame
+    }
+
+    /// Counters of the work which was done in the last rendered frame.
+    pub fn frame_counters(&self) -> FrameCounters {
+        self.frame_counters
+    }
+
+    /// GPU time which was read while rendering the last frame, if any.
+    ///
+    /// GPU time is read without waiting for the device,
+    /// so it belongs to the frame which was previously rendered into the same image.
+    ///
+    pub fn gpu_time(&self) -> Option<Duration> {
+        self.gpu_time.map(|gpu_time| gpu_time.duration)
+    }
+
+    /// GPU time which was read while rendering the last frame
+    /// with number and counters of the frame which it belongs to.
+    pub(crate) fn measured_gpu_time(&self) -> Option<GpuTime> {
+        self.gpu_time
+    }
+
+    /// Statistics of recently rendered frames: frame time, FPS and GPU time.
+    pub fn frame_stats(&self) -> FrameStats {
+        self.frame_stats.stats()
+    }
+
+    /// Number of the latest frame which is confirmed by fence to be finished by the device.
+    ///
+    /// All frames before it are finished too, so their resources could be safely destroyed.
+    ///
+    pub fn last_retired_frame(&self) -> Option<u64> {
+        self.timeline.last_retired_frame()
+    }
+
+    /// Registers callback which will be invoked when the frame with provided number is finished.
+    ///
+    /// Callbacks are invoked on the render thread while rendering or resizing,
+    /// in order of frame numbers (and in order of registration for the same frame).
+    /// If the frame is already finished, callback is invoked immediately.
+    /// Callbacks of frames which are not finished when renderer is destroyed are never invoked.
+    ///
+    pub fn on_frame_retired(&mut self, frame: u64, callback: impl FnOnce() + Send + 'static) {
+        self.timeline.on_retired(frame, callback)
+    }
+
+    /// Saves pipeline cache to its file right away.
+    ///
+    /// Pipeline cache is also saved when the renderer is destroyed.
+    ///
+    pub fn save_pipeline_cache(&self) -> Result<(), PipelineCacheError> {
+        self.pipeline_cache.save()
+    }
+
+    /// Records failure of the asset.
+    ///
+    /// Returns `true` if the failure should be reported,
+    /// or `false` if it is identical to the previous failure of the asset.
+    ///
+    pub fn record_failure(&mut self, failure: AssetFailure) -> bool {
+        self.failures.record(failure)
+    }
+
+    /// Forgets failure of the asset because it was loaded successfully.
+    pub fn resolve_failure(&mut self, name: &str, kind: FailureKind) {
+        self.failures.resolve(name, kind)
+    }
+
+    /// Enables hot reloading of shaders of game objects from provided files.
+    ///
+    /// Paths are resolved by provided asset source, which also resolves
+    /// `#include` directives of GLSL shaders when they are reloaded.
+    /// Replaces files watched before, if any.
+    ///
+    #[cfg(feature = "hot-reload")]
+    pub fn enable_shader_hot_reload(
+        &mut self,
+        paths: &[std::path::PathBuf],
+        assets: AssetSource,
+    ) -> Result<(), HotReloadError> {
+        let paths = paths
+            .iter()
+            .map(|path| assets.resolve(path))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.shader_watcher = Some((ShaderWatcher::new(&paths)?, assets));
+        Ok(())
+    }
+
+    /// Reloads watched shaders whose files were modified and swaps them into object pipeline.
+    ///
+    /// If shader fails to load, previous one is kept.
+    /// If pipeline fails to build, game objects are drawn with the error material.
+    /// Returns failures which should be reported.
+    ///
+    #[cfg(feature = "hot-reload")]
+    pub fn reload_shaders(&mut self) -> Vec<AssetFailure> {
+        let (modified, assets) = match &mut self.shader_watcher {
+            Some((watcher, assets)) => (watcher.poll(Instant::now()), assets.clone()),
+            None => return Vec::new(),
+        };
+        let mut failures = Vec::new();
+        for shader in modified {
+            let failure = match self.reload_shader(shader, &assets) {
+                Some(failure) => failure,
+                None => continue,
+            };
+            log::error!("shader hot reload failure: {}", failure);
+            if self.failures.record(failure.clone()) {
+                failures.push(failure);
+            }
+        }
+        failures
+    }
+
+    /// Loads modified shader again through provided asset source and swaps it into object pipeline.
+    ///
+    /// GLSL shaders are compiled with the [include resolver](AssetSource::glsl_include)
+    /// of the asset source, the same one which is used when they are compiled from assets
+    /// for the first time.
+    ///
+    #[cfg(feature = "hot-reload")]
+    fn reload_shader(
+        &mut self,
+        modified: ModifiedShader,
+        assets: &AssetSource,
+    ) -> Option<AssetFailure> {
+        let ModifiedShader {
+            path,
+            stage,
+            format,
+        } = modified;
+        let name = path.display().to_string();
+        let shader = match format {
+            ShaderFormat::Spirv => assets
+                .read(&path)
+                .map_err(ShaderLoadError::from)
+                .and_then(|bytes| self.load_shader(&bytes, stage, "main")),
+            #[cfg(feature = "glsl")]
+            ShaderFormat::Glsl => {
+                let resolver =
+                    |requested: &str, including: &str| assets.glsl_include(requested, including);
+                assets
+                    .read_to_string(&path)
+                    .map_err(ShaderLoadError::from)
+                    .and_then(|source| self.compile_shader(&source, stage, &name, Some(&resolver)))
+            }
+            // Watcher rejects GLSL files when they cannot be compiled.
+            #[cfg(not(feature = "glsl"))]
+            ShaderFormat::Glsl => unreachable!("GLSL shader \"{}\" is watched", name),
+        };
+        let shader = match shader {
+            Ok(shader) => shader,
+            Err(error) => return Some(AssetFailure::shader(name, stage, &error)),
+        };
+        self.failures
+            .resolve(&name, FailureKind::ShaderCompile { stage });
+
+        // Old pipeline is destroyed when the last command buffer which uses it is finished.
+        if let Err(error) = self.set_object_shader(shader) {
+            return Some(AssetFailure::pipeline(OBJECT_MATERIAL, &error));
+        }
+        self.failures
+            .resolve(OBJECT_MATERIAL, FailureKind::PipelineBuild);
+        log::info!("{} shader \"{}\" is reloaded", stage, name);
+        None
+    }
+
+    /// Assets whose last attempt to load has failed.
+    pub fn failed_assets(&self) -> &[FailedAsset] {
+        self.failures.failed()
+    }
+
+    /// Requests readback of provided region at the end of the next rendered frame.
+    ///
+    /// Callback is invoked with bytes of the region when that frame is finished by the device.
+    ///
+    pub fn request_readback(
+        &mut self,
+        region: ReadbackRegion,
+        callback: impl FnOnce(&[u8]) + Send + 'static,
+    ) {
+        self.readbacks.request_region(region, callback);
+    }
+
+    /// Requests screenshot of the next rendered frame.
+    ///
+    /// Image of the frame is copied before it is presented, and callback is invoked
+    /// with the screenshot when that frame is finished by the device.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the surface does not allow to copy its images
+    /// or if their format could not be converted into RGBA pixels.
+    ///
+    pub fn capture_screenshot(
+        &mut self,
+        callback: impl FnOnce(Screenshot) + Send + 'static,
+    ) -> Result<(), ScreenshotError> {
+        if !self.output.usage().transfer_source {
+            return Err(ScreenshotError::Unsupported);
+        }
+        let format = self.output.format();
+        if !screenshot::is_supported_format(format) {
+            return Err(ScreenshotError::UnsupportedFormat(format));
+        }
+        self.screenshots.push(Box::new(callback));
+        Ok(())
+    }
+
+    /// Underlying window of render system, or `None` if it renders offscreen.
+    pub fn window(&self) -> Option<&Window> {
+        self.output.window().map(Arc::as_ref)
+    }
+
+    /// Size of images which frames are rendered into.
+    pub fn output_size(&self) -> Size {
+        let [width, height] = self.output.dimensions();
+        Size::new(width, height)
+    }
+
+    /// Description of physical device which is used for rendering.
+    pub fn physical_device_info(&self) -> &PhysicalDeviceInfo {
+        &self.physical_device_info
+    }
+
+    /// Properties, limits and features of physical device which is used for rendering.
+    pub fn device_info(&self) -> &DeviceInfo {
+        &self.device_info
+    }
+
+    /// Features which are enabled on the device.
+    pub fn enabled_features(&self) -> DeviceFeatures {
+        DeviceFeatures::from(self.device.enabled_features())
+    }
+
+    /// Creates timeline semaphore with provided initial value of the counter.
+    pub fn create_timeline_semaphore(
+        &self,
+        initial_value: u64,
+    ) -> Result<TimelineSemaphore, TimelineSemaphoreError> {
+        TimelineSemaphore::new(self.device.clone(), initial_value)
+    }
+
+    /// Creates compute pipeline from provided compute shader
+    /// with provided bindings of its descriptor set.
+    pub fn create_compute_pipeline(
+        &self,
+        shader: &Shader,
+        bindings: &[BindingKind],
+    ) -> Result<ComputePipeline, ComputePipelineCreationError> {
+        let cache = Some(self.pipeline_cache.cache());
+        ComputePipeline::new(self.device.clone(), shader, bindings, cache)
+    }
+
+    /// Creates host visible storage buffer with provided data,
+    /// so it could be read back right after the dispatch.
+    pub fn create_storage_buffer<T>(
+        &self,
+        data: &[T],
+    ) -> Result<Arc<CpuAccessibleBuffer<[T]>>, DeviceMemoryAllocError>
+    where
+        T: Copy + Send + Sync + 'static,
+    {
+        let usage = BufferUsage {
+            storage_buffer: true,
+            ..BufferUsage::none()
+        };
+        CpuAccessibleBuffer::from_iter(self.device.clone(), usage, false, data.iter().copied())
+    }
+
+    /// Creates 2D image with provided format which could be used
+    /// for [storage image](BindingKind::StorageImage) bindings of compute pipelines.
+    pub fn create_storage_image(
+        &self,
+        size: Size,
+        format: Format,
+    ) -> Result<Arc<StorageImage>, ImageCreationError> {
+        let dimensions = ImageDimensions::Dim2d {
+            width: size.width,
+            height: size.height,
+            array_layers: 1,
+        };
+        let usage = ImageUsage {
+            storage: true,
+            transfer_source: true,
+            transfer_destination: true,
+            ..ImageUsage::none()
+        };
+        let mut families = vec![self.graphics_queue.family()];
+        if let Some(queue) = &self.compute_queue {
+            if queue.family().id() != self.graphics_queue.family().id() {
+                families.push(queue.family());
+            }
+        }
+        let flags = ImageCreateFlags::none();
+        let device = self.device.clone();
+        StorageImage::with_usage(device, dimensions, format, usage, flags, families)
+    }
+
+    /// Dispatches compute pipeline with provided count of work groups and descriptors
+    /// on the compute queue and waits until the dispatch is finished.
+    pub fn dispatch_compute(
+        &self,
+        pipeline: &ComputePipeline,
+        groups: [u32; 3],
+        descriptors: Vec<ComputeDescriptor>,
+    ) -> Result<(), DispatchError> {
+        let queue = self
+            .compute_queue
+            .as_ref()
+            .ok_or(DispatchError::Unsupported)?;
+        submit::submit_once(queue, |builder| {
+            pipeline.record_dispatch(builder, groups, descriptors)
         })
     }
 
-    /// Underlying window of render system.
-    pub fn window(&self) -> &Window {
-        self.surface.window()
+    /// Records commands into one-time command buffer, submits it to the graphics queue
+    /// and waits until its execution is finished.
+    pub fn submit_once<E>(
+        &self,
+        record: impl FnOnce(&mut OneTimeBuilder) -> Result<(), E>,
+    ) -> Result<(), E>
+    where
+        E: From<SubmitOnceError>,
+    {
+        submit::submit_once(&self.graphics_queue, record)
+    }
+
+    /// Checks if the extension with provided name is enabled on the device.
+    pub fn is_extension_enabled(&self, name: &CStr) -> bool {
+        Vec::<CString>::from(self.device.enabled_extensions())
+            .iter()
+            .any(|extension| extension.as_c_str() == name)
+    }
+
+    /// Cloneable handle of the underlying window, or `None` if it renders offscreen.
+    pub fn window_handle(&self) -> Option<WindowHandle> {
+        self.output.window().cloned().map(WindowHandle::new)
+    }
+
+    /// Creates additional window which presents the same frames as the main window.
+    ///
+    /// Window only mirrors the main window, nothing is rendered into it separately.
+    /// Window has its own surface and swapchain, while the device and its queues are shared.
+    /// The frame is copied into the window with preserved aspect ratio,
+    /// so images of the main window must support copying from them.
+    ///
+    pub fn create_window<T>(
+        &mut self,
+        config: &Config,
+        event_loop: &EventLoopWindowTarget<T>,
+        descriptor: &WindowDescriptor,
+    ) -> Result<WindowId, WindowCreationError> {
+        let physical_device = self.device.physical_device();
+        if self.output.window().is_none() {
+            return Err(WindowCreationError::Headless);
+        }
+        let source_format = self.output.format().properties(physical_device);
+        let source_copyable = self.swapchain_usage.is_some_and(|usage| usage.transfer_src);
+        if !source_copyable || !source_format.optimal_tiling_features.blit_src {
+            return Err(WindowCreationError::SourceNotCopyable);
+        }
+
+        let mut builder = WindowBuilder::new()
+            .with_title(&descriptor.title)
+            .with_resizable(descriptor.resizable);
+        if let Some(size) = descriptor.size {
+            builder = builder.with_inner_size(PhysicalSize::new(size.width, size.height));
+        }
+        let surface = utils::build_surface(builder, event_loop, self.instance.clone())?;
+        // Device was chosen for the main window, so the new surface is verified separately.
+        if !surface.is_supported(self.present_queue.family())? {
+            return Err(WindowCreationError::PresentUnsupported);
+        }
+        let capabilities = surface.capabilities(physical_device)?;
+        if !capabilities.supported_usage_flags.transfer_destination {
+            return Err(WindowCreationError::TransferUnsupported);
+        }
+        let (format, color_space, _) =
+            utils::suitable_image_format(&capabilities.supported_formats, config.surface_formats());
+        if !format
+            .properties(physical_device)
+            .optimal_tiling_features
+            .blit_dst
+        {
+            return Err(WindowCreationError::FormatNotBlittable(format));
+        }
+        let present_mode =
+            utils::suitable_present_mode(&capabilities.present_modes, self.present_mode);
+        let dimensions = utils::choose_extent(&capabilities, surface.window().inner_size().into());
+        let image_count = utils::choose_image_count(&capabilities, 1, None).unwrap_or(1);
+        let composite_alpha = utils::suitable_composite_alpha(
+            &capabilities.supported_composite_alpha,
+            config.composite_alpha(),
+        );
+        let (swapchain, images) = Swapchain::start(self.device.clone(), surface.clone())
+            .format(format)
+            .color_space(color_space)
+            .present_mode(present_mode)
+            .dimensions(dimensions)
+            .num_images(image_count)
+            .transform(capabilities.current_transform)
+            .composite_alpha(composite_alpha)
+            .sharing_mode(self.swapchain_sharing_mode())
+            .usage(ImageUsage {
+                transfer_destination: true,
+                ..ImageUsage::none()
+            })
+            .build()?;
+        let id = surface.window().id();
+        log::info!(
+            r#"additional window "{}" was created with format {:?}"#,
+            descriptor.title,
+            format,
+        );
+        let window = SecondaryWindow::new(surface, swapchain, images);
+        self.secondary_windows.insert(id, window);
+        Ok(id)
+    }
+
+    /// Checks if additional window with provided identifier exists.
+    pub fn has_window(&self, id: WindowId) -> bool {
+        self.secondary_windows.contains_key(&id)
+    }
+
+    /// Requests recreation of the swapchain of additional window before the next frame,
+    /// for example, after the window was resized.
+    pub fn invalidate_window(&mut self, id: WindowId) {
+        if let Some(window) = self.secondary_windows.get_mut(&id) {
+            window.invalidate();
+        }
+    }
+
+    /// Destroys additional window with its resources,
+    /// returns `false` if there is no such window.
+    ///
+    /// Frames in flight keep resources of the window alive until they are finished.
+    ///
+    pub fn destroy_window(&mut self, id: WindowId) -> bool {
+        self.secondary_windows.remove(&id).is_some()
     }
 
     /// Resize the underlying window and update Vulkan objects.
-    pub fn resize(&mut self) -> Result<(), ResizeError> {
-        let dimensions = self.window().inner_size().into();
+    ///
+    /// Does nothing if the size is unchanged.
+    /// If either dimension is zero (e.g. the window is minimized),
+    /// recreation is deferred until the next non-zero resize.
+    ///
+    /// Waits until the device is idle, so no resources of the old swapchain are in use.
+    /// If the window was resized again while the swapchain was recreated,
+    /// recreation is deferred until the next frame.
+    ///
+    /// Offscreen image is replaced right away, frames in flight keep the old one alive.
+    ///
+    pub fn resize(&mut self, new_size: Size) -> Result<(), ResizeError> {
+        let requested: [u32; 2] = new_size.into();
+        if requested.contains(&0) {
+            self.recreate_swapchain = true;
+            return Ok(());
+        }
+        if !self.recreate_swapchain && self.output.dimensions() == requested {
+            return Ok(());
+        }
+        let (surface, swapchain, color_space) = match &mut self.output {
+            RenderOutput::Window {
+                surface,
+                swapchain,
+                color_space,
+                ..
+            } => (surface.clone(), swapchain.clone(), *color_space),
+            // Swapchain is created with the current size of the window when the surface is restored.
+            RenderOutput::Lost(_) => return Ok(()),
+            RenderOutput::Headless(image) => {
+                *image = self::headless_image(&self.device, requested, &self.memory)?;
+                self.recreate_swapchain = false;
+                return Ok(());
+            }
+        };
 
-        let (swapchain, swapchain_images) =
-            self.swapchain.recreate().dimensions(dimensions).build()?;
-        self.swapchain = swapchain;
-        self.swapchain_images = swapchain_images;
+        // Resize could be called in the middle of the frame, so wait for all frames in flight.
+        self.wait_frames_in_flight::<ResizeError>()?;
+
+        // Capabilities could be changed since the last recreation, so query them again.
+        let capabilities = surface.capabilities(self.device.physical_device())?;
+        let dimensions = utils::choose_extent(&capabilities, requested);
+        if dimensions != requested {
+            log::debug!(
+                "swapchain extent {:?} was clamped to {:?} by surface capabilities",
+                requested,
+                dimensions,
+            );
+        }
+        if dimensions.contains(&0) {
+            self.recreate_swapchain = true;
+            return Ok(());
+        }
+
+        let present_mode =
+            utils::suitable_present_mode(&capabilities.present_modes, self.present_mode);
+        if present_mode != swapchain.present_mode() {
+            log::info!("present mode {:?} was selected", present_mode);
+        }
+        let recreate = swapchain
+            .recreate()
+            .dimensions(dimensions)
+            .present_mode(present_mode);
+        let (swapchain, images) = match recreate.build() {
+            Ok(r) => r,
+            Err(SwapchainCreationError::UnsupportedDimensions) => {
+                self.recreate_swapchain = true;
+                return Ok(());
+            }
+            Err(err) => return Err(err.into()),
+        };
+        self::name_swapchain(&swapchain, &images);
+        self.output = RenderOutput::Window {
+            surface,
+            swapchain,
+            images,
+            color_space,
+        };
 
         self.recreate_swapchain = false;
         Ok(())
     }
 
+    /// Checks if the surface of the window was [lost](Self::suspend_surface)
+    /// and was not restored yet.
+    pub fn is_surface_lost(&self) -> bool {
+        matches!(self.output, RenderOutput::Lost(_))
+    }
+
+    /// Destroys the surface of the window with its swapchain, while the window is kept.
+    ///
+    /// Some platforms (e.g. Android) destroy the native window when the application
+    /// is suspended, so its surface must not be used until it is
+    /// [restored](Self::restore_surface). Frames are skipped while the surface is lost.
+    ///
+    /// Waits until the device is idle, so no resources of the swapchain are in use.
+    /// Returns `false` if there is no surface to destroy.
+    ///
+    pub fn suspend_surface(&mut self) -> Result<bool, SurfaceError> {
+        let lost = match &self.output {
+            RenderOutput::Window {
+                surface,
+                swapchain,
+                images,
+                color_space,
+            } => LostSurface {
+                window: surface.window().clone(),
+                format: swapchain.format(),
+                color_space: *color_space,
+                dimensions: swapchain.dimensions(),
+                image_count: images.len(),
+                usage: self.output.usage(),
+                present_mode: swapchain.present_mode(),
+                composite_alpha: swapchain.composite_alpha(),
+                fullscreen_exclusive: swapchain.fullscreen_exclusive(),
+            },
+            RenderOutput::Lost(_) | RenderOutput::Headless(_) => return Ok(false),
+        };
+        self.wait_frames_in_flight::<SurfaceError>()?;
+        self.output = RenderOutput::Lost(lost);
+        // Future of the last frame holds the swapchain, and it was already finished.
+        self.previous_frame_end = Some(Box::new(sync::now(self.device.clone())));
+        self.recreate_swapchain = false;
+        log::info!("surface of the window was lost");
+        Ok(true)
+    }
+
+    /// Creates new surface of the window after the previous one was [lost](Self::suspend_surface).
+    ///
+    /// Swapchain is created with the same format and count of images as before,
+    /// so resources of the renderer which were created per image are kept.
+    /// Returns `false` if the surface was not lost, or if the window is not ready yet
+    /// (e.g. its size is zero), so restoration should be retried later.
+    ///
+    pub fn restore_surface(&mut self) -> Result<bool, SurfaceError> {
+        let lost = match &self.output {
+            RenderOutput::Lost(lost) => lost.clone(),
+            RenderOutput::Window { .. } | RenderOutput::Headless(_) => return Ok(false),
+        };
+        let physical_device = self.device.physical_device();
+        let surface = vulkano_win::create_vk_surface(lost.window.clone(), self.instance.clone())?;
+        if !surface.is_supported(self.present_queue.family())? {
+            return Err(SurfaceError::PresentUnsupported);
+        }
+        let capabilities = surface.capabilities(physical_device)?;
+        let surface_format = (lost.format, lost.color_space);
+        if !capabilities.supported_formats.contains(&surface_format) {
+            return Err(SurfaceError::FormatUnsupported(lost.format));
+        }
+        if !utils::supports_image_count(&capabilities, lost.image_count as u32) {
+            return Err(SurfaceError::UnsupportedImageCount(lost.image_count));
+        }
+        let dimensions = utils::choose_extent(&capabilities, lost.window.inner_size().into());
+        if dimensions.contains(&0) {
+            return Ok(false);
+        }
+
+        let present_mode =
+            utils::suitable_present_mode(&capabilities.present_modes, self.present_mode);
+        let build = Swapchain::start(self.device.clone(), surface.clone())
+            .format(lost.format)
+            .color_space(lost.color_space)
+            .present_mode(present_mode)
+            .dimensions(dimensions)
+            .num_images(lost.image_count as u32)
+            .transform(capabilities.current_transform)
+            .composite_alpha(lost.composite_alpha)
+            .fullscreen_exclusive(lost.fullscreen_exclusive)
+            .sharing_mode(self.swapchain_sharing_mode())
+            .usage(lost.usage)
+            .build();
+        let (swapchain, images) = match build {
+            Ok(r) => r,
+            Err(SwapchainCreationError::UnsupportedDimensions) => return Ok(false),
+            Err(err) => return Err(err.into()),
+        };
+        // Implementation could create more images than requested.
+        if images.len() != lost.image_count {
+            return Err(SurfaceError::UnsupportedImageCount(lost.image_count));
+        }
+        self::name_swapchain(&swapchain, &images);
+        self.output = RenderOutput::Window {
+            surface,
+            swapchain,
+            images,
+            color_space: lost.color_space,
+        };
+        self.recreate_swapchain = false;
+        log::info!("surface of the window was restored");
+        Ok(true)
+    }
+
+    /// Sharing mode of swapchain images between graphics and present queues.
+    fn swapchain_sharing_mode(&self) -> SharingMode {
+        if self.present_queue.family().id() == self.graphics_queue.family().id() {
+            SharingMode::from(&self.graphics_queue)
+        } else {
+            let queues = [&self.graphics_queue, &self.present_queue];
+            SharingMode::from(&queues[..])
+        }
+    }
+
+    /// Present mode which is currently used by the swapchain.
+    ///
+    /// Nothing is presented when rendering offscreen, so preferred mode
+    /// (or [`Fifo`](PresentMode::Fifo) if there is none) is returned.
+    ///
+    pub fn present_mode(&self) -> PresentMode {
+        match &self.output {
+            RenderOutput::Window { swapchain, .. } => {
+                utils::from_vk_present_mode(swapchain.present_mode())
+            }
+            RenderOutput::Lost(lost) => utils::from_vk_present_mode(lost.present_mode),
+            RenderOutput::Headless(_) => self.present_mode.unwrap_or(PresentMode::Fifo),
+        }
+    }
+
+    /// Sets preferred present mode, or `None` to let the engine choose it.
+    ///
+    /// Swapchain is recreated with the new present mode on the next frame.
+    /// If this mode is not supported by the surface, it will be replaced by supported one.
+    ///
+    pub fn set_present_mode(&mut self, present_mode: Option<PresentMode>) {
+        self.present_mode = present_mode;
+        self.recreate_swapchain = true;
+    }
+
     pub fn set_camera_ubo(&mut self, ubo: CameraUBO) {
         self.camera_ubo = ubo;
     }
@@ -269,7 +1464,7 @@ pub fn set_camera_ubo(&mut self, ubo: CameraUBO) {
     /// before actual rendering.
     fn transfer_cb(
         &self,
-        image_index: usize,
+        image_index: ImageIndex,
     ) -> Result<PrimaryAutoCommandBuffer, TransferCommandBufferCreationError> {
         let uniform_buffer = self.uniform_buffers[image_index].clone();
 
@@ -279,6 +1474,7 @@ fn transfer_cb(
             CommandBufferUsage::OneTimeSubmit,
         )?;
         builder.update_buffer(uniform_buffer, Box::new(self.camera_ubo))?;
+        self.uniform_ring.upload(&mut builder, image_index)?;
         Ok(builder.build()?)
     }
 
@@ -286,6 +1482,7 @@ pub fn register_ui_image(
         &mut self,
         image: &RgbaImage,
     ) -> Result<TextureId, ImageRegisterError> {
+        let dimensions = [image.width(), image.height()];
         let pixels: Vec<_> = image.pixels().flat_map(|p| p.0).collect();
         let (image, future) = ImmutableImage::from_iter(
             pixels,
@@ -299,55 +1496,356 @@ pub fn register_ui_image(
             self.transfer_queue.clone(),
         )?;
         future.flush()?;
+        let size = memory::image_size(dimensions, Format::R8G8B8A8_SRGB);
+        self.memory
+            .track(&image, "UI image", ResourceCategory::Texture, size);
         let image_view = ImageView::new(image)?;
         Ok(self.ui_draw_system.register_texture(image_view)?)
     }
 
+    /// Unregisters previously registered image for UI.
+    pub fn unregister_ui_image(&mut self, texture_id: TextureId) {
+        self.ui_draw_system.unregister_texture(texture_id)
+    }
+
+    /// Replaces mesh of game objects by provided vertices and indices.
+    pub fn set_mesh(&mut self, vertices: Vec<Vertex>, indices: Indices) -> Result<(), MeshError> {
+        self.object_draw_system.set_mesh(vertices, indices)
+    }
+
+    /// Loads shader from provided bytes of SPIR-V module.
+    pub fn load_shader(
+        &self,
+        bytes: &[u8],
+        stage: ShaderStage,
+        entry_point: &str,
+    ) -> Result<Shader, ShaderLoadError> {
+        Shader::from_spirv_bytes(self.device.clone(), bytes, stage, entry_point)
+    }
+
+    /// Compiles shader from GLSL source with provided name.
+    #[cfg(feature = "glsl")]
+    pub fn compile_shader(
+        &self,
+        source: &str,
+        stage: ShaderStage,
+        name: &str,
+        resolver: Option<GlslIncludeResolver>,
+    ) -> Result<Shader, ShaderLoadError> {
+        let device = self.device.clone();
+        match resolver {
+            Some(resolver) => {
+                Shader::from_glsl_source_with_includes(device, source, stage, name, resolver)
+            }
+            None => Shader::from_glsl_source(device, source, stage, name),
+        }
+    }
+
+    /// Replaces built-in shader of game objects by provided shader of the same stage.
+    pub fn set_object_shader(&mut self, shader: Shader) -> Result<(), ObjectShaderError> {
+        self.object_draw_system.set_shader(shader)
+    }
+
+    /// Restores built-in shader of game objects of provided stage.
+    pub fn reset_object_shader(&mut self, stage: ShaderStage) -> Result<(), ObjectShaderError> {
+        self.object_draw_system.reset_shader(stage)
+    }
+
+    /// Creates graphics pipeline of game objects with provided state.
+    pub fn create_object_pipeline(
+        &mut self,
+        descriptor: GraphicsPipelineDescriptor,
+    ) -> Result<PipelineId, ObjectShaderError> {
+        self.object_draw_system.create_pipeline(descriptor)
+    }
+
+    /// Assigns graphics pipeline to the game object with provided id.
+    pub fn set_object_pipeline(
+        &mut self,
+        object: u32,
+        pipeline: PipelineId,
+    ) -> Result<(), ObjectPipelineError> {
+        self.object_draw_system
+            .set_object_pipeline(object, pipeline)
+    }
+
+    /// Replaces texture of game objects by provided image.
+    pub fn set_object_texture(
+        &mut self,
+        image: &RgbaImage,
+        options: SamplerOptions,
+    ) -> Result<(), TextureError> {
+        let dimensions = [image.width(), image.height()];
+        self.object_draw_system
+            .set_texture(dimensions, image.as_raw(), options)
+    }
+
+    /// Sets global quality tier of textures.
+    pub fn set_texture_quality(&mut self, quality: TextureQuality) -> Result<(), TextureError> {
+        self.object_draw_system.set_texture_quality(quality)
+    }
+
+    /// Sets global bias of the level of detail of texture samplers.
+    pub fn set_texture_lod_bias(&mut self, lod_bias: f32) -> Result<(), TextureError> {
+        self.object_draw_system.set_texture_lod_bias(lod_bias)
+    }
+
+    /// Statistics of the texture of game objects.
+    pub fn object_texture_stats(&self) -> TextureStats {
+        self.object_draw_system.texture_stats()
+    }
+
+    /// Sets color which the window is cleared with before drawing.
+    pub fn set_clear_color(&mut self, clear_color: Color) {
+        self.frame_system.set_clear_color(clear_color)
+    }
+
+    /// Replaces the chain of effects which the scene is post processed with.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if post processing is disabled by configuration
+    /// or if pipeline of any effect cannot be built, then the previous chain stays active.
+    ///
+    pub fn set_post_chain(&mut self, chain: &PostProcessChain) -> Result<(), PostEffectError> {
+        self.post_process_system()?.set_chain(chain)
+    }
+
+    /// Replaces parameters of the effect of the post processing chain with provided index.
+    pub fn set_post_params(
+        &mut self,
+        index: usize,
+        params: [f32; 4],
+    ) -> Result<(), PostEffectError> {
+        self.post_process_system()?.set_params(index, params)
+    }
+
+    fn post_process_system(&mut self) -> Result<&mut PostProcessSystem, PostEffectError> {
+        self.post_process_system
+            .as_mut()
+            .ok_or(PostEffectError::Disabled)
+    }
+
+    /// Parameters of the material of game objects.
+    pub fn material_params(&self) -> &ParamBlock {
+        self.object_draw_system.material_params()
+    }
+
+    /// Sets the value of material parameter of game objects with provided name.
+    pub fn set_material_param<V>(&mut self, name: &str, value: V) -> Result<(), ParamBlockError>
+    where
+        V: ParamValue,
+    {
+        self.object_draw_system.set_material_param(name, value)
+    }
+
+    /// Handle of tint which colors of game objects are multiplied by.
+    pub fn object_tint(&self) -> ColorHandle {
+        self.object_tint.clone()
+    }
+
+    /// Registers pass with commands recorded by the user which is executed inside of the frame.
+    ///
+    /// Passes of the same stage are executed in order of registration.
+    /// See [module documentation](super::frame::external) for synchronization contract.
+    ///
+    pub fn register_external_pass(&mut self, desc: ExternalPassDesc) -> ExternalPassId {
+        self.external_pass_system.register(desc)
+    }
+
+    /// Unregisters external pass, returns `false` if it was not registered.
+    pub fn unregister_external_pass(&mut self, id: ExternalPassId) -> bool {
+        self.external_pass_system.unregister(id)
+    }
+
+    /// Schedules picking of the game object under the pixel of the window.
+    ///
+    /// Coordinates are in physical pixels of the window.
+    /// Result could be retrieved by [`pick_result`](Self::pick_result)
+    /// a few frames later, when id of the object is read back from the GPU.
+    ///
+    pub fn pick(&mut self, x: u32, y: u32) -> PickTicket {
+        self.picking_system.request(x, y)
+    }
+
+    /// Retrieves the result of the pick request scheduled by [`pick`](Self::pick).
+    pub fn pick_result(&mut self, ticket: PickTicket) -> PickResult {
+        self.picking_system.result(ticket)
+    }
+
+    /// Collects memory blocks of each heap with ranges used by engine resources and free ranges.
+    ///
+    /// Snapshot is rate-limited, so it could be called every frame.
+    /// See [module documentation](super::memory) for placement of the allocations.
+    ///
+    pub fn memory_map(&self) -> Vec<HeapMap> {
+        self.memory.memory_map()
+    }
+
     /// Render new frame into the underlying window.
     pub fn render(
         &mut self,
         mut ui: Option<(Vec<ClippedMesh>, Arc<Texture>)>,
     ) -> Result<(), RenderError> {
-        self.previous_frame_end.as_mut().unwrap().cleanup_finished();
-        if self.recreate_swapchain {
-            self.resize()?;
+        self.wait_present_thread()?;
+        self.frame_counters = FrameCounters::default();
+        self.gpu_time = None;
+        let now = Instant::now();
+        if let Some(last_render) = self.last_render.replace(now) {
+            self.frame_stats.observe(now.duration_since(last_render));
         }
-
-        let (image_index, suboptimal, acquire_future) =
-            match swapchain::acquire_next_image(self.swapchain.clone(), None) {
-                Ok(r) => r,
-                Err(AcquireError::OutOfDate) => {
-                    self.recreate_swapchain = true;
+        // Limit count of frames which are processed by the device at the same time.
+        while let Some((frame, fence)) = self.frame_fences.front() {
+            let in_flight = self.frame_fences.len() >= self.frames_in_flight as usize;
+            let timeout = if in_flight {
+                self.frame_wait_timeout
+            } else {
+                Some(Duration::ZERO)
+            };
+            let result = fence.wait_timeout(timeout);
+            match result.map_err(RenderError::FrameWait)? {
+                WaitResult::Signaled => {}
+                WaitResult::TimedOut if in_flight => {
+                    log::trace!("device is behind, skipping frame");
+                    self.skipped_frames += 1;
                     return Ok(());
                 }
-                Err(err) => return Err(RenderError::AcquireNextImage(err)),
-            };
-        self.recreate_swapchain = suboptimal;
+                WaitResult::TimedOut => break,
+            }
+            self.frame_counters.retired_callbacks += self.timeline.retire(*frame);
+            self.frame_fences.pop_front();
+        }
+        self.previous_frame_end.as_mut().unwrap().cleanup_finished();
+        // Buffers of retired frames are unlocked only after cleanup of their futures.
+        if let Some(frame) = self.timeline.last_retired_frame() {
+            self.frame_counters.retired_callbacks += self.readbacks.retire(frame);
+        }
+        self.picking_system.prune(self.timeline.frame_counter());
 
+        let (image_index, acquire_future) = match self.acquire_next_image()? {
+            Some(acquired) => acquired,
+            None => return Ok(()),
+        };
+        debug_assert_eq!(self.uniform_buffers.len(), self.output.image_count());
+        // Results of the frame which used this image before are read before its queries are reset.
+        self.gpu_time = self.frame_system.read_gpu_time(image_index.get());
+        if let Some(gpu_time) = self.gpu_time {
+            self.frame_stats.observe_gpu_time(gpu_time.duration);
+        }
+        if let Some(samples) = self.frame_system.read_occlusion_samples(image_index.get()) {
+            self.frame_stats.observe_occlusion_samples(samples);
+        }
+
+        // Parameter blocks of the frame are packed before the transfer command buffer uploads them.
+        self.uniform_ring.begin_frame();
+        let material = self
+            .uniform_ring
+            .push(self.object_draw_system.material_params())?;
+        let material = self.uniform_ring.slice(image_index, material);
         let transfer_command_buffer = self.transfer_cb(image_index)?;
         let previous_frame_end = self.previous_frame_end.take().unwrap();
+        let previous_frame_end = match acquire_future {
+            Some(acquire_future) => Box::new(previous_frame_end.join(acquire_future)),
+            None => previous_frame_end,
+        };
         let before_future = previous_frame_end
-            .join(acquire_future)
             .then_execute(self.transfer_queue.clone(), transfer_command_buffer)?
             .then_signal_semaphore();
 
-        let scale_factor = self.window().scale_factor() as f32;
+        let scale_factor = self.window().map_or(1.0, Window::scale_factor) as f32;
+        let frame_index = self.timeline.frame_counter();
+        self.rendered_frame = frame_index;
+        self.frame_counters.upload_bytes +=
+            mem::size_of::<CameraUBO>() as DeviceSize + self.uniform_ring.frame_size();
+        let object_subpass = self.frame_system.object_subpass();
+        let ui_subpass = self.frame_system.ui_subpass();
+        let post_effects = self
+            .post_process_system
+            .as_ref()
+            .map_or(0, PostProcessSystem::effect_count);
         let graphics_future = {
-            let mut frame = self
-                .frame_system
-                .frame(before_future, self.swapchain_images[image_index].clone())?;
+            let mut frame = match &self.output {
+                RenderOutput::Window { images, .. } => {
+                    let image = images[image_index.get()].clone();
+                    self.frame_system.frame(
+                        before_future,
+                        image,
+                        image_index.get(),
+                        post_effects,
+                    )?
+                }
+                RenderOutput::Lost(_) => unreachable!("frames are skipped while surface is lost"),
+                RenderOutput::Headless(image) => {
+                    let image = image.clone();
+                    self.frame_system.frame(
+                        before_future,
+                        image,
+                        image_index.get(),
+                        post_effects,
+                    )?
+                }
+            };
+            let occlusion_query = frame.occlusion_query();
             let mut graphics_future = Box::new(sync::now(self.device.clone())) as Box<_>;
             while let Some(next_pass) = frame.next_pass()? {
                 match next_pass {
                     Pass::Deferred(mut draw_pass) => {
+                        let viewport_size = draw_pass.viewport_size();
+                        for command_buffer in self.external_pass_system.record(
+                            ExternalPassStage::BeforeScene,
+                            object_subpass.clone(),
+                            viewport_size,
+                            frame_index,
+                            &occlusion_query,
+                        )? {
+                            draw_pass.execute(command_buffer)?;
+                        }
                         let uniform_buffer = self.uniform_buffers[image_index].clone();
-                        let command_buffer = self
-                            .object_draw_system
-                            .draw(draw_pass.viewport_size(), uniform_buffer)?;
+                        let command_buffer = self.object_draw_system.draw(
+                            viewport_size,
+                            uniform_buffer,
+                            material.clone(),
+                            self.object_tint.get(),
+                        )?;
                         draw_pass.execute(command_buffer)?;
+                        let object_count = self.object_draw_system.drawn_object_count();
+                        self.frame_counters.draw_calls += object_count;
+                        if self.object_draw_system.is_failed() {
+                            self.frame_counters.fallback_draws += object_count;
+                            self.failures
+                                .count_fallback_draws(OBJECT_MATERIAL, object_count.into());
+                        }
+                        for command_buffer in self.external_pass_system.record(
+                            ExternalPassStage::AfterScene,
+                            object_subpass.clone(),
+                            viewport_size,
+                            frame_index,
+                            &occlusion_query,
+                        )? {
+                            draw_pass.execute(command_buffer)?;
+                        }
+                    }
+                    Pass::Post(mut post_pass) => {
+                        if let (Some(post_process_system), Some((index, input))) =
+                            (self.post_process_system.as_mut(), post_pass.post_input())
+                        {
+                            let viewport_size = post_pass.viewport_size();
+                            let command_buffer =
+                                post_process_system.draw(index, viewport_size, input)?;
+                            post_pass.execute(command_buffer)?;
+                            self.frame_counters.draw_calls += 1;
+                        }
                     }
                     Pass::UI(mut ui_pass) => {
                         if let Some((meshes, texture)) = ui.take() {
+                            let counters = &mut self.frame_counters;
+                            for mesh in &meshes {
+                                counters.draw_calls += 1;
+                                counters.upload_bytes += (mesh.1.vertices.len()
+                                    * mem::size_of::<UiVertex>()
+                                    + mem::size_of_val(mesh.1.indices.as_slice()))
+                                    as DeviceSize;
+                            }
                             let command_buffer = self.ui_draw_system.draw(
                                 ui_pass.viewport_size(),
                                 scale_factor,
@@ -356,6 +1854,15 @@ pub fn render(
                             )?;
                             ui_pass.execute(command_buffer)?;
                         }
+                        for command_buffer in self.external_pass_system.record(
+                            ExternalPassStage::AfterPost,
+                            ui_subpass.clone(),
+                            ui_pass.viewport_size(),
+                            frame_index,
+                            &occlusion_query,
+                        )? {
+                            ui_pass.execute(command_buffer)?;
+                        }
                     }
                     Pass::Finished(future) => {
                         graphics_future = future;
@@ -365,27 +1872,387 @@ pub fn render(
             graphics_future
         };
 
-        let future = graphics_future
-            .then_swapchain_present(
-                self.present_queue.clone(),
-                self.swapchain.clone(),
-                image_index,
-            )
-            .then_signal_fence_and_flush();
-        match future {
-            Ok(future) => {
-                self.previous_frame_end = Some(Box::new(future));
+        // Picking pass is executed only if somebody requested it.
+        let graphics_future = if self.picking_system.has_requests() {
+            let uniform_buffer = self.uniform_buffers[image_index].clone();
+            let dimensions = self.output.dimensions();
+            let command_buffer = self
+                .object_draw_system
+                .draw_ids(Size::new(dimensions[0], dimensions[1]), uniform_buffer)?;
+            self.frame_counters.draw_calls += self.object_draw_system.drawn_object_count();
+            self.picking_system.execute(
+                graphics_future,
+                frame_index,
+                dimensions,
+                command_buffer,
+                &mut self.readbacks,
+            )?
+        } else {
+            graphics_future
+        };
+        if !self.screenshots.is_empty() {
+            let image = self.output.image(image_index);
+            let format = image.format();
+            let [width, height] = image.dimensions().width_height();
+            let region = ReadbackRegion::Image {
+                image,
+                origin: [0, 0],
+                extent: [width, height],
+            };
+            let callbacks = mem::take(&mut self.screenshots);
+            self.readbacks.request_region(region, move |texels| {
+                let row_pitch = width as usize * 4;
+                match Screenshot::from_texels(width, height, row_pitch, format, texels) {
+                    Ok(screenshot) => callbacks
+                        .into_iter()
+                        .for_each(|callback| callback(screenshot.clone())),
+                    Err(error) => log::error!("screenshot failure: {}", error),
+                }
+            });
+        }
+        // Readbacks are copied after everything else was rendered.
+        let graphics_future = if self.readbacks.has_pending() {
+            self.readbacks
+                .execute(graphics_future, self.graphics_queue.clone(), &self.memory)?
+        } else {
+            graphics_future
+        };
+        // GPU time of this frame is read when the same image is rendered again,
+        // so the frame is remembered along with the time.
+        self.frame_system
+            .record_gpu_frame(image_index.get(), frame_index, self.frame_counters);
+
+        let swapchain = match &self.output {
+            RenderOutput::Window { swapchain, .. } => swapchain.clone(),
+            RenderOutput::Lost(_) => unreachable!("frames are skipped while surface is lost"),
+            RenderOutput::Headless(_) => {
+                let frame = present::submit(graphics_future);
+                return self.finish_present(frame);
+            }
+        };
+        // Frame is copied into additional windows before the main image is presented.
+        let source = self.output.image(image_index);
+        let clear_color = self.frame_system.clear_color();
+        let mut copies = Vec::new();
+        let mut copied = Vec::new();
+        let mut failed = Vec::new();
+        for (&id, window) in self.secondary_windows.iter_mut() {
+            match window.prepare_copy(source.clone(), clear_color, &self.graphics_queue) {
+                Ok(Some(copy)) => {
+                    copied.push((id, copy.image_index));
+                    copies.push(copy);
+                }
+                Ok(None) => {}
+                Err(error) => failed.push((id, error)),
+            }
+        }
+        // Copies are submitted right away, so additional windows could be presented
+        // on their own regardless of presentation of the main window.
+        let graphics_future =
+            match self::submit_copies(graphics_future, copies, &self.graphics_queue) {
+                Ok(graphics_future) => graphics_future,
+                Err(error) => {
+                    // Acquired images are never presented,
+                    // so they are released by recreation of the swapchains.
+                    for (id, _) in &copied {
+                        self.secondary_windows.get_mut(id).unwrap().invalidate();
+                    }
+                    return match error {
+                        RenderError::SubmitQueue(error) => self.finish_present(Err(error)),
+                        error => Err(error),
+                    };
+                }
+            };
+
+        let result = match self.present_thread.as_mut() {
+            Some(present_thread) => present_thread
+                .present(graphics_future, swapchain, image_index.get())
+                .map_err(RenderError::from),
+            None => {
+                let frame = present::present(
+                    graphics_future,
+                    self.present_queue.clone(),
+                    swapchain,
+                    image_index.get(),
+                );
+                self.finish_present(frame)
+            }
+        };
+        for (id, image_index) in copied {
+            let window = self.secondary_windows.get_mut(&id).unwrap();
+            if let Err(error) = window.present(image_index, &self.present_queue) {
+                failed.push((id, error));
+            }
+        }
+        // Failure of additional window does not affect the main window, so it is only destroyed.
+        for (id, error) in failed {
+            log::error!(
+                "additional window {:?} is destroyed after failure: {}",
+                id,
+                error
+            );
+            self.secondary_windows.remove(&id);
+        }
+        result
+    }
+
+    /// Renders new frame offscreen and returns its pixels.
+    ///
+    /// Waits until the frame is finished by the device. Pixels are tightly packed
+    /// rows of RGBA values (in sRGB encoding) of [output size](Self::output_size):
+    /// the image is copied into the buffer without row padding.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the renderer was not created
+    /// by [`new_headless`](Self::new_headless).
+    ///
+    pub fn render_frame_to_buffer(
+        &mut self,
+        ui: Option<(Vec<ClippedMesh>, Arc<Texture>)>,
+    ) -> Result<Vec<u8>, RenderError> {
+        let image = match &self.output {
+            RenderOutput::Headless(image) => image.clone(),
+            RenderOutput::Window { .. } | RenderOutput::Lost(_) => {
+                return Err(RenderError::NotHeadless)
+            }
+        };
+        let extent = ImageAccess::dimensions(&*image).width_height();
+        let pixels = Arc::new(Mutex::new(None));
+        let region = ReadbackRegion::Image {
+            image,
+            origin: [0, 0],
+            extent,
+        };
+        let sink = pixels.clone();
+        let readback = self.readbacks.request_region(region, move |bytes| {
+            *sink.lock().unwrap() = Some(bytes.to_vec());
+        });
+        // Request of the skipped frame must not be bound to the next submitted frame.
+        if let Err(error) = self.render(ui) {
+            self.readbacks.remove(readback);
+            return Err(error);
+        }
+
+        let fences = self.frame_fences.iter().map(|(_, fence)| &**fence);
+        fence::wait_all(fences, true, None).map_err(RenderError::FrameWait)?;
+        for (frame, _) in self.frame_fences.drain(..) {
+            self.frame_counters.retired_callbacks += self.timeline.retire(frame);
+        }
+        self.previous_frame_end.as_mut().unwrap().cleanup_finished();
+        if let Some(frame) = self.timeline.last_retired_frame() {
+            self.frame_counters.retired_callbacks += self.readbacks.retire(frame);
+        }
+        let pixels = pixels.lock().unwrap().take();
+        if pixels.is_none() {
+            self.readbacks.remove(readback);
+        }
+        pixels.ok_or(RenderError::FrameNotSubmitted)
+    }
+
+    /// Acquires the image which the next frame is rendered into,
+    /// recreating the swapchain if needed.
+    ///
+    /// Returns index of the image with future which signals when it is acquired,
+    /// or `None` if the frame should be skipped.
+    ///
+    fn acquire_next_image(&mut self) -> Result<Option<AcquiredImage>, RenderError> {
+        match self.output {
+            RenderOutput::Window { .. } => {}
+            // Nothing could be presented until the surface is restored.
+            RenderOutput::Lost(_) => return Ok(None),
+            RenderOutput::Headless(_) => return Ok(Some((ImageIndex::new(0), None))),
+        }
+        let mut attempts = 0;
+        let (image_index, suboptimal, acquire_future) = loop {
+            if self.recreate_swapchain {
+                let size = self.window().unwrap().inner_size();
+                self.resize(Size::new(size.width, size.height))?;
+                // Window is not ready yet (e.g. minimized), so skip this frame.
+                if self.recreate_swapchain {
+                    return Ok(None);
+                }
+            }
+            let swapchain = match &self.output {
+                RenderOutput::Window { swapchain, .. } => swapchain.clone(),
+                RenderOutput::Lost(_) | RenderOutput::Headless(_) => {
+                    unreachable!("only window output has swapchain")
+                }
+            };
+            // Image acquired by the present thread is stale if swapchain was recreated since.
+            if let Some(next_image) = self.next_image.take() {
+                if Arc::ptr_eq(next_image.2.swapchain(), &swapchain) {
+                    break next_image;
+                }
+            }
+            match swapchain::acquire_next_image(swapchain, None) {
+                Ok(r) => break r,
+                Err(AcquireError::OutOfDate | AcquireError::FullscreenExclusiveLost)
+                    if attempts < MAX_ACQUIRE_ATTEMPTS =>
+                {
+                    attempts += 1;
+                    self.recreate_swapchain = true;
+                }
+                Err(AcquireError::OutOfDate | AcquireError::FullscreenExclusiveLost) => {
+                    self.recreate_swapchain = true;
+                    return Ok(None);
+                }
+                Err(err) => return Err(RenderError::AcquireNextImage(err)),
+            }
+        };
+        // Suboptimal image is still presentable, so recreate swapchain after this frame.
+        self.recreate_swapchain = suboptimal;
+        Ok(Some((ImageIndex::new(image_index), Some(acquire_future))))
+    }
+
+    /// Waits until all frames in flight are finished by the device,
+    /// so no resources of the swapchain are in use.
+    fn wait_frames_in_flight<E>(&mut self) -> Result<(), E>
+    where
+        E: From<PresentThreadError> + From<OomError>,
+    {
+        self.wait_present_thread().or_else(|error| match error {
+            RenderError::PresentThread(error) => Err(error),
+            error => {
+                log::warn!("presentation failure of the frame in flight: {}", error);
+                Ok(())
+            }
+        })?;
+        // Image acquired ahead would keep the old swapchain in use.
+        self.next_image = None;
+        self.previous_frame_end.as_mut().unwrap().cleanup_finished();
+        // SAFETY: no queue of the device is used by another thread at this point.
+        unsafe { self.device.wait()? };
+        if let Some((frame, _)) = self.frame_fences.pop_back() {
+            self.timeline.retire(frame);
+        }
+        self.frame_fences.clear();
+        Ok(())
+    }
+
+    /// Retrieves the result of the previous frame presented by the present thread, if any.
+    fn wait_present_thread(&mut self) -> Result<(), RenderError> {
+        if let Some(present_thread) = self.present_thread.as_mut() {
+            let wait_start = Instant::now();
+            if let Some(outcome) = present_thread.wait()? {
+                self.frame_stats
+                    .observe_present_time(outcome.present_time, wait_start.elapsed());
+                self.finish_present(outcome.result)?;
+                match outcome.next_image {
+                    Some(Ok(next_image)) => self.next_image = Some(next_image),
+                    Some(Err(AcquireError::OutOfDate | AcquireError::FullscreenExclusiveLost)) => {
+                        self.recreate_swapchain = true;
+                    }
+                    Some(Err(err)) => return Err(RenderError::AcquireNextImage(err)),
+                    None => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Handles the result of image presentation.
+    fn finish_present(
+        &mut self,
+        frame: Result<PresentedFrame, FlushError>,
+    ) -> Result<(), RenderError> {
+        match frame {
+            Ok(frame) => {
+                self.previous_frame_end = Some(frame.future);
+                let number = self.timeline.submit();
+                self.readbacks.submit(number);
+                self.frame_fences.push_back((number, frame.fence));
                 Ok(())
             }
-            Err(FlushError::OutOfDate) => {
+            // Swapchain which lost exclusive fullscreen is recreated as if it was out of date.
+            Err(FlushError::OutOfDate | FlushError::FullscreenExclusiveLost) => {
+                // Frame has no fence, so its readbacks are copied again in the next frame.
+                self.readbacks.cancel();
                 self.recreate_swapchain = true;
                 self.previous_frame_end = Some(Box::new(sync::now(self.device.clone())));
                 Ok(())
             }
             Err(err) => {
+                self.readbacks.cancel();
                 self.previous_frame_end = Some(Box::new(sync::now(self.device.clone())));
                 Err(RenderError::SubmitQueue(err))
             }
         }
     }
 }
+
+impl Drop for Renderer {
+    fn drop(&mut self) {
+        // Frames in flight still use resources of the renderer, so wait for them first.
+        if let Err(error) = self.wait_present_thread() {
+            log::warn!("presentation failure on destruction: {}", error);
+        }
+        for (_, fence) in self.frame_fences.drain(..) {
+            if let Err(error) = fence.wait() {
+                log::warn!(
+                    "failed to wait for the frame in flight on destruction: {}",
+                    error
+                );
+            }
+        }
+        // SAFETY: no queue of the device is used by another thread at this point.
+        if let Err(error) = unsafe { self.device.wait() } {
+            log::warn!("failed to wait for device idle on destruction: {}", error);
+        }
+    }
+}
+
+/// Executes copies of the frame into additional windows after provided future
+/// and submits them, if there are any.
+fn submit_copies(
+    mut future: Box<dyn GpuFuture + Send + Sync>,
+    copies: Vec<WindowCopy>,
+    queue: &Arc<Queue>,
+) -> Result<Box<dyn GpuFuture + Send + Sync>, RenderError> {
+    if copies.is_empty() {
+        return Ok(future);
+    }
+    for copy in copies {
+        let after_future = future
+            .join(copy.acquire_future)
+            .then_execute(queue.clone(), copy.command_buffer)?;
+        future = Box::new(after_future);
+    }
+    future.flush()?;
+    Ok(future)
+}
+
+/// Format of the offscreen image, which is read back as RGBA pixels.
+const HEADLESS_FORMAT: Format = Format::R8G8B8A8_SRGB;
+
+/// Creates the image which frames are rendered into without a window.
+fn headless_image(
+    device: &Arc<Device>,
+    dimensions: [u32; 2],
+    memory: &MemoryTracker,
+) -> Result<Arc<AttachmentImage>, ImageCreationError> {
+    let usage = ImageUsage {
+        color_attachment: true,
+        transfer_source: true,
+        ..ImageUsage::none()
+    };
+    let image = AttachmentImage::with_usage(device.clone(), dimensions, HEADLESS_FORMAT, usage)?;
+    image.inner().image.set_name("headless output");
+    let size = memory::image_size(dimensions, HEADLESS_FORMAT);
+    memory.track(
+        &image,
+        "headless output",
+        ResourceCategory::RenderTarget,
+        size,
+    );
+    Ok(image)
+}
+
+/// Gives debug names to the swapchain and its images.
+fn name_swapchain(swapchain: &Swapchain<Arc<Window>>, images: &[Arc<SwapchainImage<Arc<Window>>>]) {
+    swapchain.set_name("swapchain");
+    for (index, image) in images.iter().enumerate() {
+        let name = format!("swapchain image #{}", index);
+        image.inner().image.set_name(&name);
+    }
+}
@@ -0,0 +1,61 @@
+//! Resources which are owned by each image of the output, not by each frame in flight.
+//!
+//! Images are acquired in order chosen by the presentation engine, and the output could have
+//! more images than frames in flight (offscreen output has only one image),
+//! so per-image resources are indexed only by [`ImageIndex`] of the acquired image,
+//! while resources of frames in flight are tracked by the number of the frame.
+
+use std::ops::Index;
+
+mod tests;
+
+/// Index of the image of the output which the frame is rendered into.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ImageIndex(usize);
+
+impl ImageIndex {
+    pub const fn new(index: usize) -> Self {
+        Self(index)
+    }
+
+    /// Index of the image in the swapchain.
+    pub const fn get(self) -> usize {
+        self.0
+    }
+}
+
+/// Resources of each image of the output.
+#[derive(Debug, Clone)]
+pub struct PerImage<T>(Vec<T>);
+
+impl<T> PerImage<T> {
+    /// Creates resources for each of `image_count` images of the output.
+    pub fn try_new<E>(
+        image_count: usize,
+        create: impl FnMut(ImageIndex) -> Result<T, E>,
+    ) -> Result<Self, E> {
+        let resources = (0..image_count).map(ImageIndex).map(create);
+        Ok(Self(resources.collect::<Result<_, E>>()?))
+    }
+
+    /// Count of images of the output.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Resources of all images with their indices.
+    pub fn iter(&self) -> impl Iterator<Item = (ImageIndex, &T)> {
+        self.0
+            .iter()
+            .enumerate()
+            .map(|(index, resource)| (ImageIndex(index), resource))
+    }
+}
+
+impl<T> Index<ImageIndex> for PerImage<T> {
+    type Output = T;
+
+    fn index(&self, index: ImageIndex) -> &T {
+        &self.0[index.0]
+    }
+}
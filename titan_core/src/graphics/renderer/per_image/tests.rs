@@ -0,0 +1,31 @@
+#![cfg(test)]
+
+use std::convert::Infallible;
+
+use super::{ImageIndex, PerImage};
+
+#[test]
+fn resources_are_created_per_image() {
+    let resources = PerImage::try_new(2, |index| Ok::<_, Infallible>(index.get() * 10)).unwrap();
+    assert_eq!(resources.len(), 2);
+    let all: Vec<_> = resources.iter().collect();
+    assert_eq!(all, [(ImageIndex::new(0), &0), (ImageIndex::new(1), &10)]);
+}
+
+#[test]
+fn frames_in_flight_do_not_size_resources() {
+    // Two swapchain images with three frames in flight: only acquired indices are valid.
+    let frames_in_flight = 3;
+    let resources = PerImage::try_new(2, Ok::<_, Infallible>).unwrap();
+    assert_eq!(resources[ImageIndex::new(1)], ImageIndex::new(1));
+    assert!(resources.len() < frames_in_flight);
+}
+
+#[test]
+fn creation_failure_is_returned() {
+    let result = PerImage::try_new(3, |index| match index.get() {
+        1 => Err("out of memory"),
+        index => Ok(index),
+    });
+    assert_eq!(result.unwrap_err(), "out of memory");
+}
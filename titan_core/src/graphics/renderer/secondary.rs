@@ -0,0 +1,243 @@
+//! Additional windows which present frames of the renderer.
+//!
+//! Each window owns its surface and swapchain, while the instance, the device and the queues
+//! are shared with the main window. Frame is rendered once into the image of the main window,
+//! then copied (scaled with preserved aspect ratio) into the acquired image of each window
+//! before the main image is presented. Each window presents its copy on its own,
+//! so failure of one window never drops the frame of another.
+//!
+//! Images of the windows are acquired without waiting, so a window whose images
+//! are all held by the presentation engine skips the frame instead of stalling the others.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use vulkano::command_buffer::{
+    AutoCommandBufferBuilder, CommandBufferUsage, PrimaryAutoCommandBuffer,
+};
+use vulkano::device::{DeviceOwned, Queue};
+use vulkano::format::ClearValue;
+use vulkano::image::{ImageAccess, SwapchainImage};
+use vulkano::sampler::Filter;
+use vulkano::swapchain::{
+    self, AcquireError, Surface, Swapchain, SwapchainAcquireFuture, SwapchainCreationError,
+};
+use vulkano::sync::{self, FlushError, GpuFuture};
+use winit::window::Window;
+
+use super::error::SecondaryWindowError;
+use crate::graphics::color::Color;
+use crate::graphics::debug::Nameable;
+use crate::graphics::utils;
+
+mod tests;
+
+/// Additional window with its own swapchain.
+pub struct SecondaryWindow {
+    surface: Arc<Surface<Arc<Window>>>,
+    swapchain: Arc<Swapchain<Arc<Window>>>,
+    images: Vec<Arc<SwapchainImage<Arc<Window>>>>,
+    recreate_swapchain: bool,
+    /// Presentation of the previous frame, kept until it is finished.
+    last_present: Option<Box<dyn GpuFuture + Send + Sync>>,
+}
+
+impl SecondaryWindow {
+    pub fn new(
+        surface: Arc<Surface<Arc<Window>>>,
+        swapchain: Arc<Swapchain<Arc<Window>>>,
+        images: Vec<Arc<SwapchainImage<Arc<Window>>>>,
+    ) -> Self {
+        self::name_swapchain(&swapchain, &images);
+        Self {
+            surface,
+            swapchain,
+            images,
+            recreate_swapchain: false,
+            last_present: None,
+        }
+    }
+
+    /// Underlying window.
+    pub fn window(&self) -> &Window {
+        self.surface.window()
+    }
+
+    /// Requests recreation of the swapchain before the next frame (for example, after resize).
+    pub fn invalidate(&mut self) {
+        self.recreate_swapchain = true;
+    }
+
+    /// Recreates the swapchain with the current size of the window if it was requested.
+    ///
+    /// Returns `false` if the swapchain could not be recreated yet (e.g. window is minimized),
+    /// so the window should skip this frame.
+    ///
+    fn recreate(&mut self) -> Result<bool, SecondaryWindowError> {
+        if !self.recreate_swapchain {
+            return Ok(true);
+        }
+        let capabilities = self
+            .surface
+            .capabilities(self.swapchain.device().physical_device())?;
+        let dimensions = utils::choose_extent(&capabilities, self.window().inner_size().into());
+        if dimensions.contains(&0) {
+            return Ok(false);
+        }
+        let (swapchain, images) = match self.swapchain.recreate().dimensions(dimensions).build() {
+            Ok(r) => r,
+            Err(SwapchainCreationError::UnsupportedDimensions) => return Ok(false),
+            Err(err) => return Err(err.into()),
+        };
+        self::name_swapchain(&swapchain, &images);
+        self.swapchain = swapchain;
+        self.images = images;
+        self.recreate_swapchain = false;
+        Ok(true)
+    }
+
+    /// Acquires the next image of the swapchain and records the copy of provided image into it.
+    ///
+    /// Returns `None` if the window skips this frame. Recorded copy must be executed
+    /// on the graphics queue before the image is [presented](Self::present).
+    ///
+    pub fn prepare_copy<S>(
+        &mut self,
+        source: S,
+        clear_color: Color,
+        graphics_queue: &Arc<Queue>,
+    ) -> Result<Option<WindowCopy>, SecondaryWindowError>
+    where
+        S: ImageAccess + Send + Sync + 'static,
+    {
+        if let Some(last_present) = self.last_present.as_mut() {
+            last_present.cleanup_finished();
+        }
+        if !self.recreate()? {
+            return Ok(None);
+        }
+        let (image_index, suboptimal, acquire_future) =
+            match swapchain::acquire_next_image(self.swapchain.clone(), Some(Duration::ZERO)) {
+                Ok(r) => r,
+                Err(AcquireError::OutOfDate | AcquireError::FullscreenExclusiveLost) => {
+                    self.recreate_swapchain = true;
+                    return Ok(None);
+                }
+                Err(AcquireError::Timeout) => return Ok(None),
+                Err(err) => return Err(err.into()),
+            };
+        self.recreate_swapchain = suboptimal;
+
+        let target = self.images[image_index].clone();
+        let source_size = source.dimensions().width_height();
+        let target_size = self.swapchain.dimensions();
+        let [offset, extent] = self::letterbox(source_size, target_size);
+        let clear_value = ClearValue::Float(clear_color.for_format(self.swapchain.format()));
+        let mut builder = AutoCommandBufferBuilder::primary(
+            graphics_queue.device().clone(),
+            graphics_queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+        )?;
+        builder
+            .clear_color_image(target.clone(), clear_value)?
+            .blit_image(
+                source,
+                [0, 0, 0],
+                [source_size[0] as i32, source_size[1] as i32, 1],
+                0,
+                0,
+                target,
+                [offset[0] as i32, offset[1] as i32, 0],
+                [
+                    (offset[0] + extent[0]) as i32,
+                    (offset[1] + extent[1]) as i32,
+                    1,
+                ],
+                0,
+                0,
+                1,
+                Filter::Linear,
+            )?;
+        Ok(Some(WindowCopy {
+            image_index,
+            acquire_future,
+            command_buffer: builder.build()?,
+        }))
+    }
+
+    /// Presents the image with provided index after its copy was submitted.
+    ///
+    /// Window is presented on its own, so its failures never affect the main window.
+    /// Swapchain which is out of date is recreated on the next frame.
+    ///
+    pub fn present(
+        &mut self,
+        image_index: usize,
+        present_queue: &Arc<Queue>,
+    ) -> Result<(), SecondaryWindowError> {
+        let device = self.swapchain.device().clone();
+        // Copy was submitted to the same queue before, so presentation waits for nothing else.
+        let present = swapchain::present(
+            self.swapchain.clone(),
+            sync::now(device),
+            present_queue.clone(),
+            image_index,
+        );
+        match present.then_signal_fence_and_flush() {
+            Ok(future) => {
+                self.last_present = Some(Box::new(future));
+                Ok(())
+            }
+            Err(FlushError::OutOfDate | FlushError::FullscreenExclusiveLost) => {
+                self.recreate_swapchain = true;
+                Ok(())
+            }
+            Err(err) => Err(SecondaryWindowError::Present(err)),
+        }
+    }
+}
+
+/// Copy of the frame into the acquired image of additional window.
+pub struct WindowCopy {
+    /// Index of the acquired image.
+    pub image_index: usize,
+    /// Future which signals when the image is acquired.
+    pub acquire_future: SwapchainAcquireFuture<Arc<Window>>,
+    /// Commands which copy the frame into the image.
+    pub command_buffer: PrimaryAutoCommandBuffer,
+}
+
+/// Region of the target of provided size which the source is scaled into,
+/// with the same aspect ratio as the source and centered in the target.
+///
+/// Returns offset and extent of the region in pixels of the target.
+///
+pub fn letterbox(source: [u32; 2], target: [u32; 2]) -> [[u32; 2]; 2] {
+    let [source_width, source_height] = source.map(u64::from);
+    let [target_width, target_height] = target.map(u64::from);
+    if source_width == 0 || source_height == 0 {
+        return [[0, 0], target];
+    }
+    // Compare aspect ratios without division: fits by width if source is wider than target.
+    let extent = if source_width * target_height >= target_width * source_height {
+        [target_width, source_height * target_width / source_width]
+    } else {
+        [source_width * target_height / source_height, target_height]
+    };
+    let offset = [
+        (target_width - extent[0]) / 2,
+        (target_height - extent[1]) / 2,
+    ];
+    [
+        offset.map(|value| value as u32),
+        extent.map(|value| value as u32),
+    ]
+}
+
+fn name_swapchain(swapchain: &Swapchain<Arc<Window>>, images: &[Arc<SwapchainImage<Arc<Window>>>]) {
+    swapchain.set_name("secondary swapchain");
+    for (index, image) in images.iter().enumerate() {
+        let name = format!("secondary swapchain image #{}", index);
+        image.inner().image.set_name(&name);
+    }
+}
@@ -0,0 +1,23 @@
+#![cfg(test)]
+
+use super::letterbox;
+
+#[test]
+fn wider_source_is_fitted_by_width() {
+    assert_eq!(letterbox([1600, 900], [800, 800]), [[0, 175], [800, 450]]);
+}
+
+#[test]
+fn taller_source_is_fitted_by_height() {
+    assert_eq!(letterbox([600, 800], [1200, 800]), [[300, 0], [600, 800]]);
+}
+
+#[test]
+fn same_aspect_ratio_covers_target() {
+    assert_eq!(letterbox([1920, 1080], [1280, 720]), [[0, 0], [1280, 720]]);
+}
+
+#[test]
+fn empty_source_covers_target() {
+    assert_eq!(letterbox([0, 0], [640, 480]), [[0, 0], [640, 480]]);
+}
@@ -0,0 +1,182 @@
+//! Ring of uniform memory where parameter blocks of the frame are uploaded.
+//!
+//! Each image of the output owns its own uniform buffer, so blocks of the frame
+//! never overwrite blocks which are still read by frames in flight.
+//! Blocks are packed one after another at offsets aligned to `minUniformBufferOffsetAlignment`
+//! and uploaded by the transfer command buffer of the frame, together with the camera uniform.
+
+use std::ops::Range;
+use std::sync::Arc;
+
+use thiserror::Error;
+use vulkano::buffer::{BufferAccess, BufferSlice, BufferUsage, DeviceLocalBuffer};
+use vulkano::command_buffer::{AutoCommandBufferBuilder, UpdateBufferError};
+use vulkano::device::physical::QueueFamily;
+use vulkano::device::Device;
+use vulkano::memory::DeviceMemoryAllocError;
+use vulkano::DeviceSize;
+
+use super::per_image::{ImageIndex, PerImage};
+use crate::graphics::debug::Nameable;
+use crate::graphics::memory::{MemoryTracker, ResourceCategory};
+use crate::graphics::params::{BlockLayout, ParamBlock};
+
+mod tests;
+
+/// Size of the uniform buffer of each image in bytes.
+///
+/// Blocks of the frame are uploaded by one update command, which is limited to 64 KiB.
+///
+pub const RING_CAPACITY: DeviceSize = 65536;
+
+/// Error that can happen when writing parameter block into the uniform ring.
+#[derive(Debug, Error, Eq, PartialEq)]
+pub enum UniformRingError {
+    #[error("parameter block has {0:?} layout, but uniform buffers require std140 layout")]
+    Layout(BlockLayout),
+
+    #[error(
+        "parameter block of {size} bytes does not fit into {available} bytes left in the frame"
+    )]
+    Full {
+        size: DeviceSize,
+        available: DeviceSize,
+    },
+}
+
+/// Parameter blocks which were written for the frame, packed as they are uploaded.
+#[derive(Debug, Clone)]
+pub struct FrameBlocks {
+    alignment: DeviceSize,
+    capacity: DeviceSize,
+    bytes: Vec<u8>,
+}
+
+impl FrameBlocks {
+    /// Creates empty blocks of the frame with provided alignment of offsets and capacity in bytes.
+    pub fn new(alignment: DeviceSize, capacity: DeviceSize) -> Self {
+        Self {
+            alignment: alignment.max(1),
+            capacity,
+            bytes: Vec::new(),
+        }
+    }
+
+    /// Discards all blocks, so the next block is written at zero offset.
+    pub fn clear(&mut self) {
+        self.bytes.clear();
+    }
+
+    /// Packs provided block after the previous ones and returns its range in the buffer.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the block does not have `std140` layout
+    /// or if there is not enough space left for it.
+    ///
+    pub fn push(&mut self, block: &ParamBlock) -> Result<Range<DeviceSize>, UniformRingError> {
+        if block.layout() != BlockLayout::Std140 {
+            return Err(UniformRingError::Layout(block.layout()));
+        }
+        let used = self.bytes.len() as DeviceSize;
+        let offset = used.div_ceil(self.alignment) * self.alignment;
+        let size = block.size() as DeviceSize;
+        if offset + size > self.capacity {
+            return Err(UniformRingError::Full {
+                size,
+                available: self.capacity.saturating_sub(offset),
+            });
+        }
+        self.bytes.resize(offset as usize, 0);
+        self.bytes.extend_from_slice(block.bytes());
+        Ok(offset..offset + size)
+    }
+
+    /// Packed bytes of all blocks, including padding between them.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+/// Uniform buffers of each image of the output with blocks of the current frame.
+pub struct UniformRing {
+    buffers: PerImage<Arc<DeviceLocalBuffer<[u8]>>>,
+    blocks: FrameBlocks,
+}
+
+impl UniformRing {
+    /// Creates uniform buffer of [`RING_CAPACITY`] bytes for each of `image_count` images.
+    pub fn new(
+        device: Arc<Device>,
+        queue_family: QueueFamily,
+        image_count: usize,
+        memory: &MemoryTracker,
+    ) -> Result<Self, DeviceMemoryAllocError> {
+        let alignment = device
+            .physical_device()
+            .properties()
+            .min_uniform_buffer_offset_alignment;
+        let buffers = PerImage::try_new(image_count, |_| {
+            DeviceLocalBuffer::array(
+                device.clone(),
+                RING_CAPACITY,
+                BufferUsage::uniform_buffer_transfer_destination(),
+                std::iter::once(queue_family),
+            )
+        })?;
+        for (index, buffer) in buffers.iter() {
+            let name = format!("uniform ring #{}", index.get());
+            buffer.inner().buffer.set_name(&name);
+            memory.track(buffer, name, ResourceCategory::UniformBuffer, RING_CAPACITY);
+        }
+        Ok(Self {
+            buffers,
+            blocks: FrameBlocks::new(alignment, RING_CAPACITY),
+        })
+    }
+
+    /// Discards blocks of the previous frame.
+    pub fn begin_frame(&mut self) {
+        self.blocks.clear();
+    }
+
+    /// Packs provided block into the ring and returns its range in the buffer of the image.
+    pub fn push(&mut self, block: &ParamBlock) -> Result<Range<DeviceSize>, UniformRingError> {
+        self.blocks.push(block)
+    }
+
+    /// Range of the buffer of provided image which could be bound as uniform buffer.
+    pub fn slice(
+        &self,
+        image_index: ImageIndex,
+        range: Range<DeviceSize>,
+    ) -> Arc<dyn BufferAccess> {
+        let buffer = self.buffers[image_index].clone();
+        let slice = BufferSlice::from_typed_buffer_access(buffer)
+            .slice(range)
+            .expect("range of the block is inside of the ring");
+        Arc::new(slice)
+    }
+
+    /// Size in bytes of all blocks of the frame which are uploaded, including padding.
+    pub fn frame_size(&self) -> DeviceSize {
+        self.blocks.bytes().len() as DeviceSize
+    }
+
+    /// Records upload of all blocks of the frame into the buffer of provided image.
+    pub fn upload<L>(
+        &self,
+        builder: &mut AutoCommandBufferBuilder<L>,
+        image_index: ImageIndex,
+    ) -> Result<(), UpdateBufferError> {
+        let bytes = self.blocks.bytes();
+        if bytes.is_empty() {
+            return Ok(());
+        }
+        let buffer = BufferSlice::from_typed_buffer_access(self.buffers[image_index].clone())
+            .slice(0..self.frame_size())
+            .expect("blocks of the frame fit into the ring");
+        builder.update_buffer(buffer, Box::<[u8]>::from(bytes))?;
+        Ok(())
+    }
+}
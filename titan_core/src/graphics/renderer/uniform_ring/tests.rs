@@ -0,0 +1,73 @@
+#![cfg(test)]
+
+use ultraviolet::Vec4;
+
+use super::{FrameBlocks, UniformRingError, RING_CAPACITY};
+use crate::graphics::params::{BlockLayout, ParamBlock, ParamBlockBuilder, ParamType};
+
+fn block(value: f32) -> ParamBlock {
+    let mut block = ParamBlockBuilder::new(BlockLayout::Std140)
+        .param("color", ParamType::Vec4)
+        .build()
+        .unwrap();
+    block.set("color", Vec4::broadcast(value)).unwrap();
+    block
+}
+
+#[test]
+fn blocks_are_uploaded_at_aligned_offsets() {
+    let mut blocks = FrameBlocks::new(256, RING_CAPACITY);
+    assert_eq!(blocks.push(&self::block(1.0)).unwrap(), 0..16);
+    assert_eq!(blocks.push(&self::block(2.0)).unwrap(), 256..272);
+    assert_eq!(blocks.push(&self::block(3.0)).unwrap(), 512..528);
+
+    let bytes = blocks.bytes();
+    assert_eq!(bytes.len(), 528);
+    assert_eq!(&bytes[256..272], self::block(2.0).bytes());
+    // Padding between blocks is zeroed.
+    assert!(bytes[16..256].iter().all(|&byte| byte == 0));
+}
+
+#[test]
+fn small_alignment_packs_blocks_tightly() {
+    let mut blocks = FrameBlocks::new(16, RING_CAPACITY);
+    assert_eq!(blocks.push(&self::block(1.0)).unwrap(), 0..16);
+    assert_eq!(blocks.push(&self::block(2.0)).unwrap(), 16..32);
+}
+
+#[test]
+fn new_frame_starts_from_zero_offset() {
+    let mut blocks = FrameBlocks::new(256, RING_CAPACITY);
+    blocks.push(&self::block(1.0)).unwrap();
+    blocks.push(&self::block(2.0)).unwrap();
+    blocks.clear();
+    assert!(blocks.bytes().is_empty());
+    assert_eq!(blocks.push(&self::block(3.0)).unwrap(), 0..16);
+}
+
+#[test]
+fn full_ring_rejects_block() {
+    let mut blocks = FrameBlocks::new(256, 300);
+    blocks.push(&self::block(1.0)).unwrap();
+    blocks.push(&self::block(2.0)).unwrap();
+    let error = blocks.push(&self::block(3.0)).unwrap_err();
+    assert_eq!(
+        error,
+        UniformRingError::Full {
+            size: 16,
+            available: 0,
+        }
+    );
+    assert_eq!(blocks.bytes().len(), 272);
+}
+
+#[test]
+fn storage_layout_is_rejected() {
+    let block = ParamBlockBuilder::new(BlockLayout::Std430)
+        .param("scale", ParamType::Float)
+        .build()
+        .unwrap();
+    let mut blocks = FrameBlocks::new(256, RING_CAPACITY);
+    let error = blocks.push(&block).unwrap_err();
+    assert_eq!(error, UniformRingError::Layout(BlockLayout::Std430));
+}
@@ -0,0 +1,120 @@
+//! Screenshots of frames rendered by game engine.
+//!
+//! Image of the frame is read back after it was rendered, but before it is presented,
+//! then its texels are converted into RGBA pixels of the screenshot.
+
+use std::path::Path;
+
+use image::{ImageError, ImageFormat, RgbaImage};
+use thiserror::Error;
+use vulkano::format::Format;
+
+mod tests;
+
+/// Callback which receives the screenshot when its frame is finished by the device.
+pub type ScreenshotCallback = Box<dyn FnOnce(Screenshot) + Send>;
+
+/// Error that can happen when requesting the screenshot.
+#[derive(Debug, Error)]
+pub enum ScreenshotError {
+    #[error("images of the swapchain could not be copied (no transfer source usage)")]
+    Unsupported,
+
+    #[error("screenshots of images with format {0:?} are not supported")]
+    UnsupportedFormat(Format),
+}
+
+/// Order of channels of 8-bit texels which screenshot could be created from.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum ChannelOrder {
+    Rgba,
+    Bgra,
+}
+
+impl ChannelOrder {
+    fn of(format: Format) -> Option<Self> {
+        match format {
+            Format::R8G8B8A8_UNORM | Format::R8G8B8A8_SRGB => Some(Self::Rgba),
+            Format::B8G8R8A8_UNORM | Format::B8G8R8A8_SRGB => Some(Self::Bgra),
+            _ => None,
+        }
+    }
+}
+
+/// Checks if the screenshot could be created from the image with provided format.
+pub fn is_supported_format(format: Format) -> bool {
+    ChannelOrder::of(format).is_some()
+}
+
+/// Rendered frame which was read back from the device.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Screenshot {
+    /// Width of the frame in pixels.
+    pub width: u32,
+    /// Height of the frame in pixels.
+    pub height: u32,
+    /// Tightly packed rows of RGBA pixels, in encoding of the image (usually sRGB).
+    pub pixels: Vec<u8>,
+}
+
+impl Screenshot {
+    /// Creates screenshot from rows of texels of the image with provided format.
+    ///
+    /// Rows in `texels` are `row_pitch` bytes apart, padding at the end of each row is skipped.
+    /// Channels of BGRA formats are swizzled into RGBA order.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the format is not 8-bit RGBA or BGRA.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `texels` are shorter than the image
+    /// or if `row_pitch` is less than the width of the row.
+    ///
+    pub fn from_texels(
+        width: u32,
+        height: u32,
+        row_pitch: usize,
+        format: Format,
+        texels: &[u8],
+    ) -> Result<Self, ScreenshotError> {
+        let order = ChannelOrder::of(format).ok_or(ScreenshotError::UnsupportedFormat(format))?;
+        let row_len = width as usize * 4;
+        assert!(row_pitch >= row_len, "row pitch is less than the row");
+
+        let mut pixels = Vec::with_capacity(row_len * height as usize);
+        for row in texels.chunks(row_pitch).take(height as usize) {
+            let row = &row[..row_len];
+            match order {
+                ChannelOrder::Rgba => pixels.extend_from_slice(row),
+                ChannelOrder::Bgra => pixels.extend(
+                    row.chunks_exact(4)
+                        .flat_map(|texel| [texel[2], texel[1], texel[0], texel[3]]),
+                ),
+            }
+        }
+        assert_eq!(
+            pixels.len(),
+            row_len * height as usize,
+            "texels are too short"
+        );
+        Ok(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    /// Converts this screenshot into the image.
+    pub fn into_image(self) -> RgbaImage {
+        RgbaImage::from_raw(self.width, self.height, self.pixels)
+            .expect("pixels must match dimensions of the screenshot")
+    }
+
+    /// Saves this screenshot into the PNG file.
+    pub fn save_png(&self, path: impl AsRef<Path>) -> Result<(), ImageError> {
+        let image = self.clone().into_image();
+        image.save_with_format(path, ImageFormat::Png)
+    }
+}
@@ -0,0 +1,41 @@
+#![cfg(test)]
+
+use vulkano::format::Format;
+
+use super::{is_supported_format, Screenshot, ScreenshotError};
+
+#[test]
+fn rgba_texels_are_copied_as_is() {
+    let texels = [1, 2, 3, 4, 5, 6, 7, 8];
+    let screenshot = Screenshot::from_texels(2, 1, 8, Format::R8G8B8A8_SRGB, &texels).unwrap();
+    assert_eq!(screenshot.pixels, texels);
+    assert_eq!((screenshot.width, screenshot.height), (2, 1));
+}
+
+#[test]
+fn bgra_texels_are_swizzled() {
+    let texels = [3, 2, 1, 4, 7, 6, 5, 8];
+    let screenshot = Screenshot::from_texels(2, 1, 8, Format::B8G8R8A8_UNORM, &texels).unwrap();
+    assert_eq!(screenshot.pixels, [1, 2, 3, 4, 5, 6, 7, 8]);
+}
+
+#[test]
+fn row_padding_is_skipped() {
+    // Two rows of one texel with four bytes of padding each, the last row is not padded.
+    let texels = [1, 2, 3, 4, 0, 0, 0, 0, 5, 6, 7, 8];
+    let screenshot = Screenshot::from_texels(1, 2, 8, Format::R8G8B8A8_UNORM, &texels).unwrap();
+    assert_eq!(screenshot.pixels, [1, 2, 3, 4, 5, 6, 7, 8]);
+    assert_eq!(screenshot.into_image().get_pixel(0, 1).0, [5, 6, 7, 8]);
+}
+
+#[test]
+fn hdr_formats_are_not_supported() {
+    let format = Format::R16G16B16A16_SFLOAT;
+    assert!(!is_supported_format(format));
+    assert!(matches!(
+        Screenshot::from_texels(1, 1, 8, format, &[0; 8]),
+        Err(ScreenshotError::UnsupportedFormat(
+            Format::R16G16B16A16_SFLOAT
+        )),
+    ));
+}
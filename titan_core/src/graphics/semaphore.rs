@@ -0,0 +1,275 @@
+//! Timeline semaphores for synchronization between the host and the device.
+//!
+//! Unlike binary semaphores, which are managed by vulkano futures, timeline semaphore
+//! has monotonically increasing counter: the host or the device signals it with new value,
+//! and waiters are unblocked when the counter reaches the value they wait for.
+//! Vulkano does not support timeline semaphores, so they are created through Vulkan API directly.
+//!
+//! Timeline semaphores require `timeline_semaphore` device feature
+//! (see [`DeviceRequirements`](crate::adapter::DeviceRequirements)),
+//! on Vulkan 1.1 devices `VK_KHR_timeline_semaphore` extension must be requested too.
+
+use std::ffi::c_void;
+use std::ptr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use ash::vk;
+use thiserror::Error;
+use vulkano::command_buffer::PrimaryCommandBuffer;
+use vulkano::device::{Device, Queue};
+use vulkano::{SynchronizedVulkanObject, Version, VulkanObject};
+
+mod tests;
+
+/// Error that can happen when using timeline semaphore.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Error)]
+pub enum TimelineSemaphoreError {
+    #[error("timeline semaphores are not supported: `timeline_semaphore` feature is not enabled")]
+    Unsupported,
+
+    #[error("out of host memory")]
+    OutOfHostMemory,
+
+    #[error("out of device memory")]
+    OutOfDeviceMemory,
+
+    #[error("device was lost")]
+    DeviceLost,
+
+    #[error("unexpected Vulkan result code {0}")]
+    Unexpected(i32),
+}
+
+/// Converts result of Vulkan command into the error, timeout is converted into `false`.
+fn check(result: vk::Result) -> Result<bool, TimelineSemaphoreError> {
+    match result {
+        vk::Result::SUCCESS => Ok(true),
+        vk::Result::TIMEOUT => Ok(false),
+        vk::Result::ERROR_OUT_OF_HOST_MEMORY => Err(TimelineSemaphoreError::OutOfHostMemory),
+        vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => Err(TimelineSemaphoreError::OutOfDeviceMemory),
+        vk::Result::ERROR_DEVICE_LOST => Err(TimelineSemaphoreError::DeviceLost),
+        result => Err(TimelineSemaphoreError::Unexpected(result.as_raw())),
+    }
+}
+
+/// Timeout of the wait in nanoseconds, `None` means that the wait is infinite.
+fn timeout_nanos(timeout: Option<Duration>) -> u64 {
+    timeout.map_or(u64::MAX, |timeout| {
+        u64::try_from(timeout.as_nanos()).unwrap_or(u64::MAX)
+    })
+}
+
+/// Value of the timeline semaphore which is waited for or signaled by the submission.
+#[derive(Copy, Clone)]
+pub struct TimelinePoint<'a> {
+    /// Semaphore which is waited for or signaled.
+    pub semaphore: &'a TimelineSemaphore,
+    /// Value of the counter of the semaphore.
+    pub value: u64,
+}
+
+/// Semaphore with the counter which could be waited for and signaled
+/// by both the host and the device.
+///
+/// Semaphore must not be used by any pending submission when it is dropped.
+///
+pub struct TimelineSemaphore {
+    device: Arc<Device>,
+    handle: vk::Semaphore,
+}
+
+impl TimelineSemaphore {
+    /// Creates timeline semaphore with provided initial value of the counter.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if `timeline_semaphore` feature is not enabled on the device.
+    ///
+    pub fn new(device: Arc<Device>, initial_value: u64) -> Result<Self, TimelineSemaphoreError> {
+        if !device.enabled_features().timeline_semaphore {
+            return Err(TimelineSemaphoreError::Unsupported);
+        }
+        let type_info = vk::SemaphoreTypeCreateInfo {
+            semaphore_type: vk::SemaphoreType::TIMELINE,
+            initial_value,
+            ..Default::default()
+        };
+        let info = vk::SemaphoreCreateInfo {
+            p_next: &type_info as *const _ as *const c_void,
+            ..Default::default()
+        };
+        let mut handle = vk::Semaphore::null();
+        // SAFETY: create info is valid, semaphore is destroyed when dropped.
+        let result = unsafe {
+            let fns = &device.fns().v1_0;
+            fns.create_semaphore(device.internal_object(), &info, ptr::null(), &mut handle)
+        };
+        self::check(result)?;
+        Ok(Self { device, handle })
+    }
+
+    /// Device which owns this semaphore.
+    pub fn device(&self) -> &Arc<Device> {
+        &self.device
+    }
+
+    /// Current value of the counter.
+    pub fn counter_value(&self) -> Result<u64, TimelineSemaphoreError> {
+        let device = self.device.internal_object();
+        let fns = self.device.fns();
+        let mut value = 0;
+        // SAFETY: semaphore is alive and owned by the device.
+        let result = unsafe {
+            if self.is_core() {
+                fns.v1_2
+                    .get_semaphore_counter_value(device, self.handle, &mut value)
+            } else {
+                fns.khr_timeline_semaphore.get_semaphore_counter_value_khr(
+                    device,
+                    self.handle,
+                    &mut value,
+                )
+            }
+        };
+        self::check(result)?;
+        Ok(value)
+    }
+
+    /// Signals the counter with provided value from the host.
+    ///
+    /// Value must be greater than the current value of the counter
+    /// and than values of all pending signal operations.
+    ///
+    pub fn signal(&self, value: u64) -> Result<(), TimelineSemaphoreError> {
+        let info = vk::SemaphoreSignalInfo {
+            semaphore: self.handle,
+            value,
+            ..Default::default()
+        };
+        let device = self.device.internal_object();
+        let fns = self.device.fns();
+        // SAFETY: semaphore is alive and owned by the device.
+        let result = unsafe {
+            if self.is_core() {
+                fns.v1_2.signal_semaphore(device, &info)
+            } else {
+                fns.khr_timeline_semaphore
+                    .signal_semaphore_khr(device, &info)
+            }
+        };
+        self::check(result)?;
+        Ok(())
+    }
+
+    /// Blocks the current thread until the counter reaches provided value.
+    ///
+    /// Returns `false` if the timeout has elapsed before that, `None` means infinite timeout.
+    ///
+    pub fn wait(
+        &self,
+        value: u64,
+        timeout: Option<Duration>,
+    ) -> Result<bool, TimelineSemaphoreError> {
+        let info = vk::SemaphoreWaitInfo {
+            semaphore_count: 1,
+            p_semaphores: &self.handle,
+            p_values: &value,
+            ..Default::default()
+        };
+        let device = self.device.internal_object();
+        let fns = self.device.fns();
+        let timeout = self::timeout_nanos(timeout);
+        // SAFETY: semaphore is alive and owned by the device, wait info points to live values.
+        let result = unsafe {
+            if self.is_core() {
+                fns.v1_2.wait_semaphores(device, &info, timeout)
+            } else {
+                fns.khr_timeline_semaphore
+                    .wait_semaphores_khr(device, &info, timeout)
+            }
+        };
+        self::check(result)
+    }
+
+    /// Submits command buffer to the queue, which waits for and signals provided values
+    /// of timeline semaphores, so submissions could be ordered without any fences.
+    ///
+    /// # Safety
+    ///
+    /// Vulkano does not track this submission, so the caller must ensure that:
+    ///
+    /// - command buffer (and resources used by it) is alive until it is finished by the device,
+    ///   and resources are not used by vulkano futures at the same time;
+    /// - command buffer is not being executed already, unless it allows simultaneous use;
+    /// - queue belongs to the family of the command buffer;
+    /// - semaphores are alive until the submission is finished,
+    ///   and each signaled value is greater than the current value of its counter.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if some semaphore belongs to another device than the queue.
+    ///
+    pub unsafe fn submit(
+        queue: &Queue,
+        command_buffer: &impl PrimaryCommandBuffer,
+        waits: &[TimelinePoint<'_>],
+        signals: &[TimelinePoint<'_>],
+    ) -> Result<(), TimelineSemaphoreError> {
+        let device = queue.device();
+        let same_device = |point: &TimelinePoint| Arc::ptr_eq(&point.semaphore.device, device);
+        assert!(
+            waits.iter().chain(signals).all(same_device),
+            "semaphores must belong to the device of the queue",
+        );
+        let handles = |points: &[TimelinePoint]| -> Vec<_> {
+            points.iter().map(|point| point.semaphore.handle).collect()
+        };
+        let values = |points: &[TimelinePoint]| -> Vec<_> {
+            points.iter().map(|point| point.value).collect()
+        };
+        let (wait_semaphores, wait_values) = (handles(waits), values(waits));
+        let (signal_semaphores, signal_values) = (handles(signals), values(signals));
+        let wait_stages = vec![vk::PipelineStageFlags::ALL_COMMANDS; waits.len()];
+
+        let timeline_info = vk::TimelineSemaphoreSubmitInfo {
+            wait_semaphore_value_count: wait_values.len() as u32,
+            p_wait_semaphore_values: wait_values.as_ptr(),
+            signal_semaphore_value_count: signal_values.len() as u32,
+            p_signal_semaphore_values: signal_values.as_ptr(),
+            ..Default::default()
+        };
+        let command_buffer = command_buffer.inner().internal_object();
+        let info = vk::SubmitInfo {
+            p_next: &timeline_info as *const _ as *const c_void,
+            wait_semaphore_count: wait_semaphores.len() as u32,
+            p_wait_semaphores: wait_semaphores.as_ptr(),
+            p_wait_dst_stage_mask: wait_stages.as_ptr(),
+            command_buffer_count: 1,
+            p_command_buffers: &command_buffer,
+            signal_semaphore_count: signal_semaphores.len() as u32,
+            p_signal_semaphores: signal_semaphores.as_ptr(),
+            ..Default::default()
+        };
+        let queue = queue.internal_object_guard();
+        let fns = &device.fns().v1_0;
+        let result = fns.queue_submit(*queue, 1, &info, vk::Fence::null());
+        self::check(result)?;
+        Ok(())
+    }
+
+    /// Checks if timeline semaphores are part of Vulkan API version of the device.
+    fn is_core(&self) -> bool {
+        self.device.api_version() >= Version::V1_2
+    }
+}
+
+impl Drop for TimelineSemaphore {
+    fn drop(&mut self) {
+        // SAFETY: semaphore is not used by pending submissions, as required by the type docs.
+        unsafe {
+            let fns = &self.device.fns().v1_0;
+            fns.destroy_semaphore(self.device.internal_object(), self.handle, ptr::null());
+        }
+    }
+}
@@ -0,0 +1,37 @@
+#![cfg(test)]
+
+use std::time::Duration;
+
+use ash::vk;
+
+use super::{check, timeout_nanos, TimelineSemaphoreError};
+
+#[test]
+fn timeout_is_not_an_error() {
+    assert_eq!(check(vk::Result::SUCCESS), Ok(true));
+    assert_eq!(check(vk::Result::TIMEOUT), Ok(false));
+}
+
+#[test]
+fn failures_are_converted_into_errors() {
+    assert_eq!(
+        check(vk::Result::ERROR_DEVICE_LOST),
+        Err(TimelineSemaphoreError::DeviceLost),
+    );
+    assert_eq!(
+        check(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY),
+        Err(TimelineSemaphoreError::OutOfDeviceMemory),
+    );
+    let raw = vk::Result::ERROR_UNKNOWN.as_raw();
+    assert_eq!(
+        check(vk::Result::ERROR_UNKNOWN),
+        Err(TimelineSemaphoreError::Unexpected(raw)),
+    );
+}
+
+#[test]
+fn missing_timeout_waits_forever() {
+    assert_eq!(timeout_nanos(None), u64::MAX);
+    assert_eq!(timeout_nanos(Some(Duration::from_millis(2))), 2_000_000);
+    assert_eq!(timeout_nanos(Some(Duration::MAX)), u64::MAX);
+}
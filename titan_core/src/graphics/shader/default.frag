@@ -1,9 +1,20 @@
 #version 450
 
+layout(push_constant) uniform PushConstants {
+    vec4 tint;
+} material;
+
 layout(location = 0) in vec4 color;
+layout(location = 1) in vec2 uv;
 
 layout(location = 0) out vec4 outColor;
 
+layout(binding = 1, set = 0) uniform sampler2D albedo;
+
+layout(binding = 0, set = 1) uniform MaterialParams {
+    vec4 color;
+} params;
+
 void main() {
-    outColor = color;
+    outColor = color * texture(albedo, uv) * params.color * material.tint;
 }
@@ -8,8 +8,10 @@ layout(binding = 0) uniform CameraUBO {
 
 layout(location = 0) in vec3 position;
 layout(location = 1) in vec4 color;
+layout(location = 2) in vec2 uv;
 
 layout(location = 0) out vec4 outColor;
+layout(location = 1) out vec2 outUV;
 
 out gl_PerVertex {
     vec4 gl_Position;
@@ -18,4 +20,5 @@ out gl_PerVertex {
 void main() {
     gl_Position = ubo.projection * ubo.view * ubo.model * vec4(position, 1.0);
     outColor = color;
+    outUV = uv;
 }
@@ -0,0 +1,18 @@
+#version 450
+
+layout(binding = 0) uniform CameraUBO {
+    mat4 projection;
+    mat4 model;
+    mat4 view;
+} ubo;
+
+// Only position is consumed, so any vertex layout with position is compatible.
+layout(location = 0) in vec3 position;
+
+out gl_PerVertex {
+    vec4 gl_Position;
+};
+
+void main() {
+    gl_Position = ubo.projection * ubo.view * ubo.model * vec4(position, 1.0);
+}
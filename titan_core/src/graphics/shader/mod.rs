@@ -37,3 +37,68 @@ pub mod fragment {
         }
     }
 }
+
+/// Shaders which are used in object picking.
+pub mod picking {
+    /// Picking fragment shader utilities.
+    pub mod fragment {
+        vulkano_shaders::shader! {
+            ty: "fragment",
+            path: "src/graphics/shader/picking.frag",
+        }
+    }
+}
+
+/// Shaders of the error material which replaces materials with failed pipelines.
+pub mod error {
+    /// Error vertex shader utilities.
+    pub mod vertex {
+        vulkano_shaders::shader! {
+            ty: "vertex",
+            path: "src/graphics/shader/error.vert",
+        }
+    }
+
+    /// Error fragment shader utilities.
+    pub mod fragment {
+        vulkano_shaders::shader! {
+            ty: "fragment",
+            path: "src/graphics/shader/error.frag",
+        }
+    }
+}
+
+/// Shaders of post processing, which draw the scene over the whole screen.
+pub mod post {
+    /// Vertex shader of post processing which draws one triangle over the whole screen.
+    pub mod vertex {
+        vulkano_shaders::shader! {
+            ty: "vertex",
+            path: "src/graphics/shader/post.vert",
+        }
+    }
+
+    /// Fragment shader of post processing which copies the scene as is.
+    pub mod passthrough {
+        vulkano_shaders::shader! {
+            ty: "fragment",
+            path: "src/graphics/shader/post_passthrough.frag",
+        }
+    }
+
+    /// Fragment shader of post processing which inverts colors of the scene.
+    pub mod invert {
+        vulkano_shaders::shader! {
+            ty: "fragment",
+            path: "src/graphics/shader/post_invert.frag",
+        }
+    }
+
+    /// Fragment shader of post processing which darkens corners of the scene.
+    pub mod vignette {
+        vulkano_shaders::shader! {
+            ty: "fragment",
+            path: "src/graphics/shader/post_vignette.frag",
+        }
+    }
+}
@@ -0,0 +1,11 @@
+#version 450
+
+layout(push_constant) uniform PushConstants {
+    uint id;
+} object;
+
+layout(location = 0) out uint outId;
+
+void main() {
+    outId = object.id;
+}
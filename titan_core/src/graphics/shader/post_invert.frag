@@ -0,0 +1,16 @@
+#version 450
+
+layout(location = 0) in vec2 uv;
+
+layout(location = 0) out vec4 outColor;
+
+layout(binding = 0, set = 0) uniform sampler2D scene;
+
+layout(push_constant) uniform PushConstants {
+    vec4 params;
+} effect;
+
+void main() {
+    vec4 color = texture(scene, uv);
+    outColor = vec4(1.0 - color.rgb, color.a);
+}
@@ -0,0 +1,22 @@
+#version 450
+
+layout(location = 0) in vec2 uv;
+
+layout(location = 0) out vec4 outColor;
+
+layout(binding = 0, set = 0) uniform sampler2D scene;
+
+layout(push_constant) uniform PushConstants {
+    vec4 params;
+} effect;
+
+void main() {
+    vec4 color = texture(scene, uv);
+    // Darken corners of the screen smoothly, keeping its center intact:
+    // first parameter is the outer radius, second one is the softness of the edge.
+    float radius = effect.params.x;
+    float softness = effect.params.y;
+    float distance = length(uv - vec2(0.5));
+    float vignette = smoothstep(radius, radius - softness, distance);
+    outColor = vec4(color.rgb * vignette, color.a);
+}
@@ -0,0 +1,214 @@
+//! Runtime loading of SPIR-V shaders of game engine.
+//!
+//! Built-in shaders are compiled into the engine, but shaders of game objects
+//! could be replaced by SPIR-V modules which are loaded at runtime.
+//! Each module is validated before it reaches the driver.
+
+use std::ffi::{CStr, CString};
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use thiserror::Error;
+use vulkano::device::Device;
+use vulkano::pipeline::shader::ShaderModule;
+use vulkano::spirv::{ExecutionModel, Instruction, Spirv, SpirvError};
+use vulkano::OomError;
+
+use crate::asset::error::AssetError;
+
+#[cfg(feature = "glsl")]
+mod glsl;
+mod tests;
+
+#[cfg(feature = "glsl")]
+pub use self::glsl::{compile_glsl, GlslInclude, GlslIncludeResolver};
+
+/// Magic number which every SPIR-V module starts with.
+pub const SPIRV_MAGIC: u32 = 0x0723_0203;
+
+/// Stage of the pipeline which shader is executed on.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ShaderStage {
+    /// Shader is executed for each vertex.
+    Vertex,
+    /// Shader is executed for each fragment.
+    Fragment,
+    /// Shader is executed for each invocation of compute dispatch.
+    Compute,
+}
+
+impl fmt::Display for ShaderStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Vertex => write!(f, "vertex"),
+            Self::Fragment => write!(f, "fragment"),
+            Self::Compute => write!(f, "compute"),
+        }
+    }
+}
+
+impl ShaderStage {
+    fn execution_model(self) -> ExecutionModel {
+        match self {
+            Self::Vertex => ExecutionModel::Vertex,
+            Self::Fragment => ExecutionModel::Fragment,
+            Self::Compute => ExecutionModel::GLCompute,
+        }
+    }
+}
+
+/// Error that can happen when loading SPIR-V shader.
+#[derive(Debug, Error)]
+pub enum ShaderLoadError {
+    #[error("shader file reading failure: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("shader asset loading failure: {0}")]
+    Asset(#[from] AssetError),
+
+    #[error("SPIR-V module is empty")]
+    Empty,
+
+    #[error("length of SPIR-V module must be multiple of 4, but it is {0}")]
+    Misaligned(usize),
+
+    #[error("SPIR-V module must start with magic number {SPIRV_MAGIC:#010x}, but it starts with {0:#010x}")]
+    Magic(u32),
+
+    #[error("SPIR-V module parsing failure: {0}")]
+    Parse(#[from] SpirvError),
+
+    #[error("SPIR-V module has no {stage} entry point \"{name}\"")]
+    NoEntryPoint { name: String, stage: ShaderStage },
+
+    #[error("entry point name \"{0}\" must not contain nul bytes")]
+    EntryPointName(String),
+
+    #[error("shader module creation failure: {0}")]
+    ModuleCreation(#[from] OomError),
+
+    #[cfg(feature = "glsl")]
+    #[error("GLSL compiler initialization failure")]
+    CompilerInit,
+
+    #[cfg(feature = "glsl")]
+    #[error("GLSL compilation failure: {0}")]
+    Compilation(#[from] shaderc::Error),
+}
+
+/// Shader which was loaded from SPIR-V module at runtime.
+#[derive(Clone)]
+pub struct Shader {
+    module: Arc<ShaderModule>,
+    stage: ShaderStage,
+    entry_point: CString,
+}
+
+impl Shader {
+    /// Loads shader from provided bytes of SPIR-V module.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if bytes are not a valid SPIR-V module
+    /// or if module has no entry point with provided name for provided stage.
+    ///
+    pub fn from_spirv_bytes(
+        device: Arc<Device>,
+        bytes: &[u8],
+        stage: ShaderStage,
+        entry_point: &str,
+    ) -> Result<Self, ShaderLoadError> {
+        let words = self::spirv_words(bytes)?;
+        Self::from_words(device, &words, stage, entry_point)
+    }
+
+    /// Creates shader from words of SPIR-V module of host endianness.
+    fn from_words(
+        device: Arc<Device>,
+        words: &[u32],
+        stage: ShaderStage,
+        entry_point: &str,
+    ) -> Result<Self, ShaderLoadError> {
+        let spirv = Spirv::new(words)?;
+        self::check_entry_point(&spirv, stage, entry_point)?;
+        let entry_point = CString::new(entry_point)
+            .map_err(|_| ShaderLoadError::EntryPointName(entry_point.to_string()))?;
+
+        // SAFETY: module is a valid SPIR-V module with required entry point.
+        let module = unsafe { ShaderModule::from_words(device, words)? };
+        Ok(Self {
+            module,
+            stage,
+            entry_point,
+        })
+    }
+
+    /// Loads shader from SPIR-V module from the file with provided path.
+    ///
+    /// See [`from_spirv_bytes`](Self::from_spirv_bytes) for details.
+    ///
+    pub fn from_spirv_file(
+        device: Arc<Device>,
+        path: impl AsRef<Path>,
+        stage: ShaderStage,
+        entry_point: &str,
+    ) -> Result<Self, ShaderLoadError> {
+        let bytes = std::fs::read(path)?;
+        Self::from_spirv_bytes(device, &bytes, stage, entry_point)
+    }
+
+    /// Stage of the pipeline which this shader is executed on.
+    pub fn stage(&self) -> ShaderStage {
+        self.stage
+    }
+
+    /// Name of the entry point of this shader.
+    pub fn entry_point(&self) -> &CStr {
+        &self.entry_point
+    }
+
+    pub(crate) fn module(&self) -> &ShaderModule {
+        &self.module
+    }
+}
+
+/// Converts bytes of SPIR-V module into words of host endianness.
+///
+/// Module could be stored in either endianness, which is detected by its magic number.
+///
+pub fn spirv_words(bytes: &[u8]) -> Result<Vec<u32>, ShaderLoadError> {
+    if bytes.is_empty() {
+        return Err(ShaderLoadError::Empty);
+    }
+    if !bytes.len().is_multiple_of(4) {
+        return Err(ShaderLoadError::Misaligned(bytes.len()));
+    }
+    let words = bytes.chunks_exact(4).map(|chunk| {
+        let chunk = [chunk[0], chunk[1], chunk[2], chunk[3]];
+        u32::from_le_bytes(chunk)
+    });
+    let words: Vec<_> = match u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) {
+        SPIRV_MAGIC => words.collect(),
+        magic if magic.swap_bytes() == SPIRV_MAGIC => words.map(u32::swap_bytes).collect(),
+        magic => return Err(ShaderLoadError::Magic(magic)),
+    };
+    Ok(words)
+}
+
+/// Checks that SPIR-V module has entry point with provided name for provided stage.
+fn check_entry_point(spirv: &Spirv, stage: ShaderStage, name: &str) -> Result<(), ShaderLoadError> {
+    let found = spirv.iter_entry_point().any(|instruction| {
+        matches!(
+            instruction,
+            Instruction::EntryPoint { execution_model, name: entry_point, .. }
+                if *execution_model == stage.execution_model() && entry_point == name
+        )
+    });
+    if !found {
+        let name = name.to_string();
+        return Err(ShaderLoadError::NoEntryPoint { name, stage });
+    }
+    Ok(())
+}
@@ -0,0 +1,102 @@
+//! Compilation of GLSL shaders into SPIR-V at runtime.
+//!
+//! Available with `glsl` feature only, because it requires native shaderc library.
+//! Compiled modules are validated the same way as precompiled ones.
+
+use std::sync::Arc;
+
+use shaderc::{CompileOptions, Compiler, IncludeType, ResolvedInclude, ShaderKind};
+use vulkano::device::Device;
+
+use super::{Shader, ShaderLoadError, ShaderStage};
+
+mod tests;
+
+/// Name of the entry point of GLSL shaders.
+const GLSL_ENTRY_POINT: &str = "main";
+
+/// Resolver of `#include` directives of GLSL shaders.
+///
+/// Resolver is called with the requested name and the name of the including file,
+/// and returns either included file or the reason why it cannot be included.
+///
+pub type GlslIncludeResolver<'a> = &'a dyn Fn(&str, &str) -> Result<GlslInclude, String>;
+
+/// Source of the file included by GLSL shader.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct GlslInclude {
+    /// Name which uniquely identifies the included file in diagnostics, for example its path.
+    pub name: String,
+    /// GLSL source of the included file.
+    pub source: String,
+}
+
+impl Shader {
+    /// Compiles shader from GLSL source with provided name.
+    ///
+    /// Name of the shader is used in compilation diagnostics.
+    /// Source must not contain `#include` directives,
+    /// see [`from_glsl_source_with_includes`](Self::from_glsl_source_with_includes) for them.
+    ///
+    pub fn from_glsl_source(
+        device: Arc<Device>,
+        source: &str,
+        stage: ShaderStage,
+        name: &str,
+    ) -> Result<Self, ShaderLoadError> {
+        let words = self::compile_glsl(source, stage, name, None)?;
+        Self::from_words(device, &words, stage, GLSL_ENTRY_POINT)
+    }
+
+    /// Compiles shader from GLSL source with provided name,
+    /// resolving its `#include` directives by provided resolver.
+    ///
+    /// See [`GlslIncludeResolver`] for details of the resolver.
+    ///
+    pub fn from_glsl_source_with_includes(
+        device: Arc<Device>,
+        source: &str,
+        stage: ShaderStage,
+        name: &str,
+        resolver: impl Fn(&str, &str) -> Result<GlslInclude, String>,
+    ) -> Result<Self, ShaderLoadError> {
+        let words = self::compile_glsl(source, stage, name, Some(&resolver))?;
+        Self::from_words(device, &words, stage, GLSL_ENTRY_POINT)
+    }
+}
+
+/// Compiles GLSL source with provided name into words of SPIR-V module of host endianness.
+///
+/// Diagnostics of the compiler (file name, line and message) are kept in the error.
+///
+pub fn compile_glsl(
+    source: &str,
+    stage: ShaderStage,
+    name: &str,
+    resolver: Option<GlslIncludeResolver>,
+) -> Result<Vec<u32>, ShaderLoadError> {
+    let mut compiler = Compiler::new().ok_or(ShaderLoadError::CompilerInit)?;
+    let mut options = CompileOptions::new().ok_or(ShaderLoadError::CompilerInit)?;
+    if let Some(resolver) = resolver {
+        options.set_include_callback(move |requested, _: IncludeType, requesting, _depth| {
+            let include = resolver(requested, requesting)?;
+            // Compiler panics if resolved name is empty.
+            let resolved_name = match include.name.is_empty() {
+                true => requested.to_string(),
+                false => include.name,
+            };
+            Ok(ResolvedInclude {
+                resolved_name,
+                content: include.source,
+            })
+        });
+    }
+    let kind = match stage {
+        ShaderStage::Vertex => ShaderKind::Vertex,
+        ShaderStage::Fragment => ShaderKind::Fragment,
+        ShaderStage::Compute => ShaderKind::Compute,
+    };
+    let artifact =
+        compiler.compile_into_spirv(source, kind, name, GLSL_ENTRY_POINT, Some(&options))?;
+    Ok(artifact.as_binary().to_vec())
+}
@@ -0,0 +1,30 @@
+#![cfg(test)]
+
+use vulkano::spirv::Spirv;
+
+use super::compile_glsl;
+use crate::graphics::spirv::{check_entry_point, ShaderLoadError, ShaderStage, SPIRV_MAGIC};
+
+const FRAGMENT: &str = "#version 450
+layout(location = 0) out vec4 color;
+void main() {
+    color = vec4(1.0);
+}
+";
+
+#[test]
+fn compiles_into_valid_module() {
+    let words = compile_glsl(FRAGMENT, ShaderStage::Fragment, "color.frag", None).unwrap();
+    assert_eq!(words[0], SPIRV_MAGIC);
+    let spirv = Spirv::new(&words).unwrap();
+    assert!(check_entry_point(&spirv, ShaderStage::Fragment, "main").is_ok());
+}
+
+#[test]
+fn diagnostics_are_kept() {
+    let source = "#version 450\nvoid main() {\n    undeclared = 1;\n}\n";
+    let error = compile_glsl(source, ShaderStage::Vertex, "broken.vert", None).unwrap_err();
+    assert!(matches!(error, ShaderLoadError::Compilation(_)));
+    let message = error.to_string();
+    assert!(message.contains("undeclared"), "{}", message);
+}
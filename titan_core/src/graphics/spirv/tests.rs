@@ -0,0 +1,79 @@
+#![cfg(test)]
+
+use vulkano::spirv::Spirv;
+
+use super::{check_entry_point, spirv_words, ShaderLoadError, ShaderStage, SPIRV_MAGIC};
+
+/// Minimal SPIR-V module which declares fragment entry point `main`.
+fn module() -> Vec<u32> {
+    let name = u32::from_le_bytes(*b"main");
+    vec![
+        SPIRV_MAGIC,
+        0x0001_0000,
+        0,
+        2,
+        0,
+        // OpCapability Shader
+        (2 << 16) | 17,
+        1,
+        // OpMemoryModel Logical GLSL450
+        (3 << 16) | 14,
+        0,
+        1,
+        // OpEntryPoint Fragment %1 "main"
+        (5 << 16) | 15,
+        4,
+        1,
+        name,
+        0,
+    ]
+}
+
+fn bytes(words: &[u32], to_bytes: fn(u32) -> [u8; 4]) -> Vec<u8> {
+    words.iter().copied().flat_map(to_bytes).collect()
+}
+
+#[test]
+fn words_of_both_endianness() {
+    let words = module();
+    assert_eq!(
+        spirv_words(&bytes(&words, u32::to_le_bytes)).unwrap(),
+        words
+    );
+    assert_eq!(
+        spirv_words(&bytes(&words, u32::to_be_bytes)).unwrap(),
+        words
+    );
+}
+
+#[test]
+fn malformed_bytes_are_rejected() {
+    assert!(matches!(spirv_words(&[]), Err(ShaderLoadError::Empty)));
+    let mut bytes = bytes(&module(), u32::to_le_bytes);
+    bytes.pop();
+    assert!(matches!(
+        spirv_words(&bytes),
+        Err(ShaderLoadError::Misaligned(len)) if len == bytes.len()
+    ));
+    assert!(matches!(
+        spirv_words(b"\x7fELF"),
+        Err(ShaderLoadError::Magic(0x464c_457f))
+    ));
+}
+
+#[test]
+fn entry_point_must_match_stage_and_name() {
+    let spirv = Spirv::new(&module()).unwrap();
+    assert!(check_entry_point(&spirv, ShaderStage::Fragment, "main").is_ok());
+    assert!(matches!(
+        check_entry_point(&spirv, ShaderStage::Vertex, "main"),
+        Err(ShaderLoadError::NoEntryPoint {
+            stage: ShaderStage::Vertex,
+            ..
+        })
+    ));
+    assert!(matches!(
+        check_entry_point(&spirv, ShaderStage::Fragment, "other"),
+        Err(ShaderLoadError::NoEntryPoint { .. })
+    ));
+}
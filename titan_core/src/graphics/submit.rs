@@ -0,0 +1,65 @@
+//! One-time submission utilities for graphics backend of game engine.
+//!
+//! Texture uploads, buffer copies and similar work are recorded into transient command buffer,
+//! which is submitted once and waited for on the host, so the work is finished
+//! when the function returns.
+
+use std::sync::Arc;
+
+use thiserror::Error;
+use vulkano::command_buffer::{
+    AutoCommandBufferBuilder, BuildError, CommandBufferExecError, CommandBufferUsage,
+    PrimaryAutoCommandBuffer, PrimaryCommandBuffer,
+};
+use vulkano::device::Queue;
+use vulkano::sync::{FlushError, GpuFuture};
+use vulkano::OomError;
+
+/// Builder of the command buffer which is submitted only once.
+pub type OneTimeBuilder = AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>;
+
+/// Error that can happen when submitting one-time command buffer.
+#[derive(Debug, Error)]
+pub enum SubmitOnceError {
+    #[error("one-time command buffer allocation failure: {0}")]
+    CommandBufferAllocation(#[from] OomError),
+
+    #[error("one-time command buffer build failure: {0}")]
+    Build(#[from] BuildError),
+
+    #[error("one-time command buffer execution failure: {0}")]
+    Execution(#[from] CommandBufferExecError),
+
+    #[error("one-time command buffer submit failure: {0}")]
+    Flush(#[from] FlushError),
+}
+
+/// Records commands into new one-time command buffer, submits it to the queue
+/// and waits until its execution is finished.
+///
+/// Command buffer is freed when this function returns, even if the recording has failed,
+/// in which case nothing is submitted and the error of the recording is returned.
+///
+pub fn submit_once<E>(
+    queue: &Arc<Queue>,
+    record: impl FnOnce(&mut OneTimeBuilder) -> Result<(), E>,
+) -> Result<(), E>
+where
+    E: From<SubmitOnceError>,
+{
+    let mut builder = AutoCommandBufferBuilder::primary(
+        queue.device().clone(),
+        queue.family(),
+        CommandBufferUsage::OneTimeSubmit,
+    )
+    .map_err(SubmitOnceError::from)?;
+    record(&mut builder)?;
+    let command_buffer = builder.build().map_err(SubmitOnceError::from)?;
+    command_buffer
+        .execute(queue.clone())
+        .map_err(SubmitOnceError::from)?
+        .then_signal_fence_and_flush()
+        .and_then(|future| future.wait(None))
+        .map_err(SubmitOnceError::from)?;
+    Ok(())
+}
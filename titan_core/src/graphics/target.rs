@@ -0,0 +1,415 @@
+//! Groups of offscreen render targets of game engine.
+//!
+//! Targets of the group are declared once with their format and size policy,
+//! then the group recreates all targets whose size has changed after the swapchain resize,
+//! so systems which render offscreen do not need their own resize code.
+//!
+//! [`RenderTarget`] bundles such group with its own render pass and framebuffer,
+//! so scene could be rendered into the texture which is sampled by the next pass.
+
+use std::sync::Arc;
+
+use thiserror::Error;
+use vulkano::device::Device;
+use vulkano::format::{ClearValue, Format};
+use vulkano::image::view::{ImageView, ImageViewAbstract, ImageViewCreationError};
+use vulkano::image::{AttachmentImage, ImageCreationError, ImageUsage, SampleCount};
+use vulkano::render_pass::{
+    FramebufferAbstract, FramebufferCreationError, RenderPass, RenderPassCreationError, Subpass,
+};
+use vulkano::DeviceSize;
+
+use crate::graphics::memory::{self, MemoryTracker, ResourceCategory};
+use crate::graphics::render_pass::{self, RenderPassDescriptor, RenderPassDescriptorError};
+
+mod tests;
+
+/// Policy of the size of render target.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SizePolicy {
+    /// Target has exactly provided size in pixels.
+    Exact([u32; 2]),
+    /// Target has the size of swapchain multiplied by provided scale (at least one pixel).
+    SwapchainRelative(f32),
+    /// Target has the size of swapchain at its creation and is never resized.
+    Fixed,
+}
+
+impl SizePolicy {
+    /// Resolves size of the target from the size of swapchain and current size of the target.
+    pub fn resolve(self, swapchain: [u32; 2], current: Option<[u32; 2]>) -> [u32; 2] {
+        match self {
+            Self::Exact(size) => size,
+            Self::SwapchainRelative(scale) => {
+                swapchain.map(|dimension| ((dimension as f32 * scale).round() as u32).max(1))
+            }
+            Self::Fixed => current.unwrap_or(swapchain),
+        }
+    }
+}
+
+/// Description of render target of the group.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TargetDesc {
+    /// Name of the target which it could be retrieved by.
+    pub name: String,
+    /// Format of the target image.
+    pub format: Format,
+    /// Usage of the target image.
+    pub usage: ImageUsage,
+    /// Count of samples per pixel of the target image.
+    pub samples: SampleCount,
+    /// Policy of the size of the target.
+    pub policy: SizePolicy,
+}
+
+/// Error that can happen when declaring or recreating render targets.
+#[derive(Debug, Error)]
+pub enum TargetError {
+    #[error("render target \"{0}\" is declared more than once")]
+    DuplicateName(String),
+
+    #[error("render target \"{name}\" image creation failure: {source}")]
+    ImageCreation {
+        name: String,
+        source: ImageCreationError,
+    },
+
+    #[error("render target \"{name}\" image view creation failure: {source}")]
+    ImageViewCreation {
+        name: String,
+        source: ImageViewCreationError,
+    },
+}
+
+/// Render target of the group with its image, if it was created.
+struct Target {
+    desc: TargetDesc,
+    size: Option<[u32; 2]>,
+    image: Option<Arc<AttachmentImage>>,
+    view: Option<Arc<ImageView<Arc<AttachmentImage>>>>,
+}
+
+/// Group of render targets which are recreated together.
+pub struct TargetGroup {
+    name: String,
+    targets: Vec<Target>,
+}
+
+impl TargetGroup {
+    /// Creates new empty group with provided debug name.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            targets: Vec::new(),
+        }
+    }
+
+    /// Declares new render target of the group.
+    ///
+    /// Image of the target is created on the next [update](Self::update).
+    ///
+    pub fn declare(&mut self, desc: TargetDesc) -> Result<(), TargetError> {
+        if self
+            .targets
+            .iter()
+            .any(|target| target.desc.name == desc.name)
+        {
+            return Err(TargetError::DuplicateName(desc.name));
+        }
+        self.targets.push(Target {
+            desc,
+            size: None,
+            image: None,
+            view: None,
+        });
+        Ok(())
+    }
+
+    /// Recreates all targets whose size does not match their policy for provided swapchain size.
+    ///
+    /// Targets are recreated in order of declaration.
+    /// Returns `true` if any of targets was recreated.
+    ///
+    pub fn update(
+        &mut self,
+        device: Arc<Device>,
+        swapchain: [u32; 2],
+        memory: &MemoryTracker,
+    ) -> Result<bool, TargetError> {
+        let current: Vec<_> = self
+            .targets
+            .iter()
+            .map(|target| (target.desc.policy, target.size))
+            .collect();
+        let stale = self::stale_targets(&current, swapchain);
+        for &(index, size) in &stale {
+            let target = &mut self.targets[index];
+            let name = &target.desc.name;
+            let image = AttachmentImage::multisampled_with_usage(
+                device.clone(),
+                size,
+                target.desc.samples,
+                target.desc.format,
+                target.desc.usage,
+            )
+            .map_err(|source| TargetError::ImageCreation {
+                name: name.clone(),
+                source,
+            })?;
+            let view =
+                ImageView::new(image.clone()).map_err(|source| TargetError::ImageViewCreation {
+                    name: name.clone(),
+                    source,
+                })?;
+            memory.track(
+                &image,
+                format!("{} {}", self.name, name),
+                ResourceCategory::RenderTarget,
+                self::target_size(size, &target.desc),
+            );
+            target.size = Some(size);
+            target.image = Some(image);
+            target.view = Some(view);
+        }
+        Ok(!stale.is_empty())
+    }
+
+    /// Image of the target with provided name, if it was created.
+    pub fn image(&self, name: &str) -> Option<Arc<AttachmentImage>> {
+        self.target(name).and_then(|target| target.image.clone())
+    }
+
+    /// View of the image of the target with provided name, if it was created.
+    pub fn view(&self, name: &str) -> Option<Arc<ImageView<Arc<AttachmentImage>>>> {
+        self.target(name).and_then(|target| target.view.clone())
+    }
+
+    /// Size of the target with provided name, if it was created.
+    pub fn size(&self, name: &str) -> Option<[u32; 2]> {
+        self.target(name).and_then(|target| target.size)
+    }
+
+    /// Estimated size in bytes of all created targets of the group.
+    pub fn memory_size(&self) -> DeviceSize {
+        self.targets
+            .iter()
+            .filter_map(|target| Some(self::target_size(target.size?, &target.desc)))
+            .sum()
+    }
+
+    fn target(&self, name: &str) -> Option<&Target> {
+        self.targets.iter().find(|target| target.desc.name == name)
+    }
+}
+
+/// Estimates size of the target image in bytes, including all of its samples.
+fn target_size(size: [u32; 2], desc: &TargetDesc) -> DeviceSize {
+    memory::image_size(size, desc.format) * desc.samples as DeviceSize
+}
+
+/// Finds targets which must be recreated for provided swapchain size
+/// in order of their declaration, along with their new sizes.
+///
+/// Each target is described by its size policy and current size, if it was created.
+///
+pub fn stale_targets(
+    targets: &[(SizePolicy, Option<[u32; 2]>)],
+    swapchain: [u32; 2],
+) -> Vec<(usize, [u32; 2])> {
+    targets
+        .iter()
+        .enumerate()
+        .filter_map(|(index, &(policy, current))| {
+            let size = policy.resolve(swapchain, current);
+            (current != Some(size)).then_some((index, size))
+        })
+        .collect()
+}
+
+/// Name of the sampled color image of render target.
+const TARGET_COLOR: &str = "color";
+
+/// Name of the depth buffer of render target.
+const TARGET_DEPTH: &str = "depth";
+
+/// Name of the multisampled color buffer of render target.
+const TARGET_MULTISAMPLED: &str = "multisampled color";
+
+/// Description of [`RenderTarget`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RenderTargetDesc {
+    /// Format of the color image which is sampled after rendering.
+    pub format: Format,
+    /// Format of the depth buffer, or `None` if the target has no depth buffer.
+    pub depth_format: Option<Format>,
+    /// Count of samples per pixel, multisampled color is resolved into the color image.
+    pub samples: SampleCount,
+    /// Policy of the size of all images of the target.
+    pub policy: SizePolicy,
+}
+
+impl RenderTargetDesc {
+    /// Values which attachments of the render target are cleared with,
+    /// in order of attachments of its render pass.
+    pub fn clear_values(&self, color: [f32; 4]) -> Vec<ClearValue> {
+        let multisampled = self.samples != SampleCount::Sample1;
+        // Color image is fully overwritten by the resolve of multisampled color buffer.
+        let mut clear_values = vec![if multisampled {
+            ClearValue::None
+        } else {
+            ClearValue::Float(color)
+        }];
+        if self.depth_format.is_some() {
+            clear_values.push(ClearValue::Depth(1.0));
+        }
+        if multisampled {
+            clear_values.push(ClearValue::Float(color));
+        }
+        clear_values
+    }
+}
+
+/// Error that can happen when creating or updating render target.
+#[derive(Debug, Error)]
+pub enum RenderTargetError {
+    #[error("render pass description failure: {0}")]
+    RenderPassDescriptor(#[from] RenderPassDescriptorError),
+
+    #[error("render pass creation failure: {0}")]
+    RenderPassCreation(#[from] RenderPassCreationError),
+
+    #[error("{0}")]
+    Target(#[from] TargetError),
+
+    #[error("framebuffer creation failure: {0}")]
+    FramebufferCreation(#[from] FramebufferCreationError),
+}
+
+/// Offscreen color image (with optional depth buffer) and render pass which renders into it.
+///
+/// Color image has sampled usage, so it could be read by the next passes
+/// after render pass of the target is ended. Its size is independent of the swapchain
+/// unless the size policy is relative to it.
+///
+pub struct RenderTarget {
+    desc: RenderTargetDesc,
+    render_pass: Arc<RenderPass>,
+    targets: TargetGroup,
+    framebuffer: Option<Arc<dyn FramebufferAbstract + Send + Sync>>,
+}
+
+impl RenderTarget {
+    /// Creates render target with provided debug name and description.
+    ///
+    /// Images of the target are created on the first [update](Self::update).
+    ///
+    pub fn new(
+        device: Arc<Device>,
+        name: impl Into<String>,
+        desc: RenderTargetDesc,
+    ) -> Result<Self, RenderTargetError> {
+        let pass = RenderPassDescriptor::offscreen(desc.format, desc.depth_format, desc.samples);
+        let render_pass = Arc::new(RenderPass::new(device, pass.build()?)?);
+
+        let mut targets = TargetGroup::new(name);
+        targets.declare(TargetDesc {
+            name: TARGET_COLOR.to_string(),
+            format: desc.format,
+            usage: ImageUsage {
+                color_attachment: true,
+                sampled: true,
+                ..ImageUsage::none()
+            },
+            samples: SampleCount::Sample1,
+            policy: desc.policy,
+        })?;
+        if let Some(format) = desc.depth_format {
+            targets.declare(TargetDesc {
+                name: TARGET_DEPTH.to_string(),
+                format,
+                usage: ImageUsage {
+                    depth_stencil_attachment: true,
+                    transient_attachment: true,
+                    ..ImageUsage::none()
+                },
+                samples: desc.samples,
+                policy: desc.policy,
+            })?;
+        }
+        if desc.samples != SampleCount::Sample1 {
+            targets.declare(TargetDesc {
+                name: TARGET_MULTISAMPLED.to_string(),
+                format: desc.format,
+                usage: ImageUsage {
+                    color_attachment: true,
+                    transient_attachment: true,
+                    ..ImageUsage::none()
+                },
+                samples: desc.samples,
+                policy: desc.policy,
+            })?;
+        }
+        Ok(Self {
+            desc,
+            render_pass,
+            targets,
+            framebuffer: None,
+        })
+    }
+
+    /// Recreates images and framebuffer of the target if its size does not match
+    /// its policy for provided swapchain size.
+    ///
+    /// Returns `true` if the target was recreated.
+    ///
+    pub fn update(
+        &mut self,
+        device: Arc<Device>,
+        swapchain: [u32; 2],
+        memory: &MemoryTracker,
+    ) -> Result<bool, RenderTargetError> {
+        let recreated = self.targets.update(device, swapchain, memory)?;
+        if recreated || self.framebuffer.is_none() {
+            let names = [TARGET_COLOR, TARGET_DEPTH, TARGET_MULTISAMPLED];
+            let views: Vec<Arc<dyn ImageViewAbstract + Send + Sync>> = names
+                .iter()
+                .filter_map(|name| self.targets.view(name))
+                .map(|view| view as _)
+                .collect();
+            let framebuffer = render_pass::framebuffer(self.render_pass.clone(), &views)?;
+            self.framebuffer = Some(framebuffer);
+        }
+        Ok(recreated)
+    }
+
+    /// Description of the target.
+    pub fn desc(&self) -> &RenderTargetDesc {
+        &self.desc
+    }
+
+    /// Render pass which renders into the target.
+    pub fn render_pass(&self) -> &Arc<RenderPass> {
+        &self.render_pass
+    }
+
+    /// The only subpass of the render pass of the target.
+    pub fn subpass(&self) -> Subpass {
+        Subpass::from(self.render_pass.clone(), 0).unwrap()
+    }
+
+    /// Framebuffer of the render pass, if images of the target were created.
+    pub fn framebuffer(&self) -> Option<Arc<dyn FramebufferAbstract + Send + Sync>> {
+        self.framebuffer.clone()
+    }
+
+    /// View of the color image which could be sampled, if it was created.
+    pub fn color(&self) -> Option<Arc<ImageView<Arc<AttachmentImage>>>> {
+        self.targets.view(TARGET_COLOR)
+    }
+
+    /// Size of images of the target in pixels, if they were created.
+    pub fn size(&self) -> Option<[u32; 2]> {
+        self.targets.size(TARGET_COLOR)
+    }
+}
@@ -0,0 +1,61 @@
+#![cfg(test)]
+
+use vulkano::format::Format;
+use vulkano::image::{ImageUsage, SampleCount};
+
+use super::{stale_targets, SizePolicy, TargetDesc, TargetError, TargetGroup};
+
+fn desc(name: &str) -> TargetDesc {
+    TargetDesc {
+        name: name.to_string(),
+        format: Format::R8G8B8A8_UNORM,
+        usage: ImageUsage {
+            color_attachment: true,
+            ..ImageUsage::none()
+        },
+        samples: SampleCount::Sample1,
+        policy: SizePolicy::Fixed,
+    }
+}
+
+#[test]
+fn exact_size_ignores_swapchain() {
+    let policy = SizePolicy::Exact([256, 128]);
+    assert_eq!(policy.resolve([1920, 1080], None), [256, 128]);
+    assert_eq!(policy.resolve([800, 600], Some([256, 128])), [256, 128]);
+}
+
+#[test]
+fn relative_size_is_rounded_and_at_least_one_pixel() {
+    let policy = SizePolicy::SwapchainRelative(0.5);
+    assert_eq!(policy.resolve([801, 600], None), [401, 300]);
+
+    let policy = SizePolicy::SwapchainRelative(0.001);
+    assert_eq!(policy.resolve([800, 600], None), [1, 1]);
+}
+
+#[test]
+fn fixed_size_is_kept_after_creation() {
+    let policy = SizePolicy::Fixed;
+    assert_eq!(policy.resolve([800, 600], None), [800, 600]);
+    assert_eq!(policy.resolve([1920, 1080], Some([800, 600])), [800, 600]);
+}
+
+#[test]
+fn only_resized_targets_are_stale() {
+    let targets = [
+        (SizePolicy::Fixed, Some([800, 600])),
+        (SizePolicy::SwapchainRelative(1.0), Some([800, 600])),
+        (SizePolicy::Exact([64, 64]), None),
+    ];
+    let stale = stale_targets(&targets, [1024, 768]);
+    assert_eq!(stale, [(1, [1024, 768]), (2, [64, 64])]);
+}
+
+#[test]
+fn duplicate_name_is_rejected() {
+    let mut group = TargetGroup::new("test");
+    group.declare(self::desc("color")).unwrap();
+    let error = group.declare(self::desc("color")).unwrap_err();
+    assert!(matches!(error, TargetError::DuplicateName(name) if name == "color"));
+}
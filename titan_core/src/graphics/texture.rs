@@ -0,0 +1,275 @@
+//! Texture utilities for game objects of game engine.
+//!
+//! Pixels of the texture are uploaded through staging buffer into device local image.
+//! Vulkano transitions layout of the image from `UNDEFINED` into `TRANSFER_DST_OPTIMAL`
+//! before the copy and then into `SHADER_READ_ONLY_OPTIMAL` for sampling,
+//! so the image is never observed by shaders in other layout.
+//!
+//! Mipmaps of the texture are generated on the GPU by successive linear blits
+//! from each level into the next one, which requires support of linear filtering
+//! and blitting for the format of the texture.
+
+use std::sync::Arc;
+
+use thiserror::Error;
+use vulkano::device::Device;
+use vulkano::format::FormatFeatures;
+use vulkano::image::view::ImageViewCreationError;
+use vulkano::sampler::{Filter, MipmapMode, Sampler, SamplerAddressMode, SamplerCreationError};
+
+use crate::graphics::upload::UploadError;
+
+mod tests;
+
+/// Maximal level of detail of the sampler which allows any level of mipmaps.
+const MAX_LOD: f32 = 1000.0;
+
+/// Filter which is used when texture is magnified or minified.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum TextureFilter {
+    /// Nearest texel is selected (useful for pixel art).
+    Nearest,
+    /// Four nearest texels are linearly interpolated.
+    Linear,
+}
+
+impl From<TextureFilter> for Filter {
+    fn from(filter: TextureFilter) -> Self {
+        match filter {
+            TextureFilter::Nearest => Filter::Nearest,
+            TextureFilter::Linear => Filter::Linear,
+        }
+    }
+}
+
+/// Behaviour of the sampler for texture coordinates outside of range `[0; 1]`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum TextureAddressMode {
+    /// Texture is repeated.
+    Repeat,
+    /// Texture is repeated, mirroring each odd repetition.
+    MirroredRepeat,
+    /// Coordinates are clamped, so edge texels are stretched.
+    ClampToEdge,
+}
+
+impl From<TextureAddressMode> for SamplerAddressMode {
+    fn from(address_mode: TextureAddressMode) -> Self {
+        match address_mode {
+            TextureAddressMode::Repeat => SamplerAddressMode::Repeat,
+            TextureAddressMode::MirroredRepeat => SamplerAddressMode::MirroredRepeat,
+            TextureAddressMode::ClampToEdge => SamplerAddressMode::ClampToEdge,
+        }
+    }
+}
+
+/// Options of the sampler which the texture is sampled with.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct SamplerOptions {
+    mag_filter: TextureFilter,
+    min_filter: TextureFilter,
+    address_mode: TextureAddressMode,
+    mipmaps: bool,
+}
+
+impl SamplerOptions {
+    /// Creates new options with linear filtering, repeating of the texture and mipmaps.
+    pub const fn new() -> Self {
+        Self {
+            mag_filter: TextureFilter::Linear,
+            min_filter: TextureFilter::Linear,
+            address_mode: TextureAddressMode::Repeat,
+            mipmaps: true,
+        }
+    }
+
+    /// Sets filter which is used when texture is magnified.
+    pub const fn with_mag_filter(mut self, mag_filter: TextureFilter) -> Self {
+        self.mag_filter = mag_filter;
+        self
+    }
+
+    /// Sets filter which is used when texture is minified.
+    pub const fn with_min_filter(mut self, min_filter: TextureFilter) -> Self {
+        self.min_filter = min_filter;
+        self
+    }
+
+    /// Sets address mode for both texture coordinates.
+    pub const fn with_address_mode(mut self, address_mode: TextureAddressMode) -> Self {
+        self.address_mode = address_mode;
+        self
+    }
+
+    /// Enables or disables generation of mipmaps for the texture.
+    ///
+    /// Mipmaps are not generated if the format of texture does not support linear blitting.
+    ///
+    pub const fn with_mipmaps(mut self, mipmaps: bool) -> Self {
+        self.mipmaps = mipmaps;
+        self
+    }
+
+    /// Filter which is used when texture is magnified.
+    pub fn mag_filter(&self) -> TextureFilter {
+        self.mag_filter
+    }
+
+    /// Filter which is used when texture is minified.
+    pub fn min_filter(&self) -> TextureFilter {
+        self.min_filter
+    }
+
+    /// Address mode for both texture coordinates.
+    pub fn address_mode(&self) -> TextureAddressMode {
+        self.address_mode
+    }
+
+    /// If mipmaps are generated for the texture.
+    pub fn mipmaps(&self) -> bool {
+        self.mipmaps
+    }
+
+    /// Creates new sampler with these options and provided bias of the level of detail.
+    ///
+    /// Sampler of texture without mipmaps samples only the base level.
+    ///
+    pub(crate) fn create_sampler(
+        &self,
+        device: Arc<Device>,
+        lod_bias: f32,
+    ) -> Result<Arc<Sampler>, SamplerCreationError> {
+        let address_mode = self.address_mode.into();
+        let (mipmap_mode, max_lod) = if self.mipmaps {
+            (MipmapMode::Linear, MAX_LOD)
+        } else {
+            (MipmapMode::Nearest, 0.0)
+        };
+        Sampler::new(
+            device,
+            self.mag_filter.into(),
+            self.min_filter.into(),
+            mipmap_mode,
+            address_mode,
+            address_mode,
+            address_mode,
+            lod_bias,
+            1.0,
+            0.0,
+            max_lod,
+        )
+    }
+}
+
+impl Default for SamplerOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Global quality tier of textures which caps their highest resident mip level.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum TextureQuality {
+    /// Two highest mip levels are skipped.
+    Low,
+    /// Highest mip level is skipped.
+    Medium,
+    /// All mip levels are sampled.
+    #[default]
+    High,
+}
+
+impl TextureQuality {
+    /// Count of highest mip levels which are skipped by this tier.
+    pub fn mip_bias(self) -> u32 {
+        match self {
+            Self::Low => 2,
+            Self::Medium => 1,
+            Self::High => 0,
+        }
+    }
+
+    /// Base mip level which is sampled by this tier for texture with provided count of levels.
+    ///
+    /// The smallest level is always kept, so textures without mipmaps are not affected.
+    ///
+    pub fn base_mip_level(self, mip_levels: u32) -> u32 {
+        self.mip_bias().min(mip_levels.saturating_sub(1))
+    }
+}
+
+/// Statistics of the texture which is sampled by game objects.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct TextureStats {
+    /// Size of the base level of the texture in pixels.
+    pub dimensions: [u32; 2],
+    /// Count of mip levels which are resident in memory.
+    pub mip_levels: u32,
+    /// First mip level which is sampled.
+    pub base_mip_level: u32,
+    /// Quality tier which the texture is sampled with.
+    pub quality: TextureQuality,
+}
+
+/// Error that can happen when creating texture of game objects.
+#[derive(Debug, Error)]
+pub enum TextureError {
+    #[error("texture must not be empty")]
+    Empty,
+
+    #[error("expected {expected} bytes of RGBA8 pixels, but got {actual}")]
+    SizeMismatch { expected: usize, actual: usize },
+
+    #[error("texture upload failure: {0}")]
+    Upload(#[from] UploadError),
+
+    #[error("texture image view creation failure: {0}")]
+    ImageViewCreation(#[from] ImageViewCreationError),
+
+    #[error("texture sampler creation failure: {0}")]
+    SamplerCreation(#[from] SamplerCreationError),
+}
+
+/// Checks that `len` bytes are RGBA8 pixels of the texture with provided dimensions.
+///
+/// Any dimensions are allowed, including non-power-of-two ones.
+///
+pub fn check_rgba8(dimensions: [u32; 2], len: usize) -> Result<(), TextureError> {
+    let [width, height] = dimensions;
+    if width == 0 || height == 0 {
+        return Err(TextureError::Empty);
+    }
+    let expected = (width as usize)
+        .checked_mul(height as usize)
+        .and_then(|texels| texels.checked_mul(4))
+        .unwrap_or(usize::MAX);
+    if len != expected {
+        return Err(TextureError::SizeMismatch {
+            expected,
+            actual: len,
+        });
+    }
+    Ok(())
+}
+
+/// Computes length of full mip chain for texture with provided dimensions.
+///
+/// Each next level is half the size of the previous one (rounded down),
+/// the last level is `1x1`.
+///
+pub fn mip_levels(dimensions: [u32; 2]) -> u32 {
+    let [width, height] = dimensions;
+    let max = width.max(height);
+    if max == 0 {
+        return 0;
+    }
+    u32::BITS - max.leading_zeros()
+}
+
+/// Checks if mipmaps could be generated for textures with format of provided features.
+///
+/// Linear blitting is required for the format, otherwise generated levels would be corrupted.
+///
+pub fn supports_mipmap_generation(features: &FormatFeatures) -> bool {
+    features.sampled_image_filter_linear && features.blit_src && features.blit_dst
+}
@@ -0,0 +1,74 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn default_options_are_linear_repeat() {
+    let options = SamplerOptions::default();
+    assert_eq!(options.mag_filter(), TextureFilter::Linear);
+    assert_eq!(options.min_filter(), TextureFilter::Linear);
+    assert_eq!(options.address_mode(), TextureAddressMode::Repeat);
+    assert!(options.mipmaps());
+}
+
+#[test]
+fn options_setters() {
+    let options = SamplerOptions::new()
+        .with_mag_filter(TextureFilter::Nearest)
+        .with_address_mode(TextureAddressMode::ClampToEdge);
+    assert_eq!(options.mag_filter(), TextureFilter::Nearest);
+    assert_eq!(options.min_filter(), TextureFilter::Linear);
+    assert_eq!(options.address_mode(), TextureAddressMode::ClampToEdge);
+}
+
+#[test]
+fn rgba8_accepts_non_power_of_two() {
+    assert!(check_rgba8([3, 5], 3 * 5 * 4).is_ok());
+    assert!(check_rgba8([1, 1], 4).is_ok());
+}
+
+#[test]
+fn rgba8_rejects_empty_and_mismatch() {
+    assert!(matches!(check_rgba8([0, 4], 0), Err(TextureError::Empty)));
+    assert!(matches!(
+        check_rgba8([2, 2], 15),
+        Err(TextureError::SizeMismatch {
+            expected: 16,
+            actual: 15
+        })
+    ));
+}
+
+#[test]
+fn mip_chain_length() {
+    assert_eq!(mip_levels([1, 1]), 1);
+    assert_eq!(mip_levels([2, 1]), 2);
+    assert_eq!(mip_levels([256, 256]), 9);
+    assert_eq!(mip_levels([300, 17]), 9);
+    assert_eq!(mip_levels([0, 0]), 0);
+}
+
+#[test]
+fn mipmaps_require_linear_blit() {
+    let mut features = FormatFeatures {
+        blit_src: true,
+        blit_dst: true,
+        ..FormatFeatures::default()
+    };
+    assert!(!supports_mipmap_generation(&features));
+    features.sampled_image_filter_linear = true;
+    assert!(supports_mipmap_generation(&features));
+    features.blit_dst = false;
+    assert!(!supports_mipmap_generation(&features));
+}
+
+#[test]
+fn quality_tier_skips_highest_levels() {
+    assert_eq!(TextureQuality::default(), TextureQuality::High);
+    assert_eq!(TextureQuality::High.base_mip_level(9), 0);
+    assert_eq!(TextureQuality::Medium.base_mip_level(9), 1);
+    assert_eq!(TextureQuality::Low.base_mip_level(9), 2);
+    // Smallest level is always kept.
+    assert_eq!(TextureQuality::Low.base_mip_level(2), 1);
+    assert_eq!(TextureQuality::Low.base_mip_level(1), 0);
+}
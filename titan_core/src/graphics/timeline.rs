@@ -0,0 +1,81 @@
+//! Frame timeline utilities for lifetime decisions of GPU resources.
+
+use std::collections::BTreeMap;
+
+mod tests;
+
+/// Callback which is invoked when the frame is retired.
+type RetireCallback = Box<dyn FnOnce() + Send>;
+
+/// Timeline of frames which were submitted to and retired by the device.
+///
+/// Frames are numbered from `0` in order of submission,
+/// and they are retired in the same order.
+///
+#[derive(Default)]
+pub struct FrameTimeline {
+    submitted: u64,
+    last_retired: Option<u64>,
+    callbacks: BTreeMap<u64, Vec<RetireCallback>>,
+}
+
+impl FrameTimeline {
+    /// Count of frames which were submitted to the device.
+    pub fn frame_counter(&self) -> u64 {
+        self.submitted
+    }
+
+    /// Number of the latest frame which is known to be retired by the device, if any.
+    pub fn last_retired_frame(&self) -> Option<u64> {
+        self.last_retired
+    }
+
+    /// Checks if the frame with provided number was retired by the device.
+    pub fn is_retired(&self, frame: u64) -> bool {
+        self.last_retired.is_some_and(|last| frame <= last)
+    }
+
+    /// Registers new submitted frame and returns its number.
+    pub fn submit(&mut self) -> u64 {
+        let frame = self.submitted;
+        self.submitted += 1;
+        frame
+    }
+
+    /// Marks the frame with provided number and all previous frames as retired.
+    ///
+    /// Callbacks of retired frames are invoked in order of frame numbers,
+    /// and callbacks of the same frame are invoked in order of registration.
+    /// Returns count of invoked callbacks.
+    ///
+    pub fn retire(&mut self, frame: u64) -> usize {
+        if self.is_retired(frame) {
+            return 0;
+        }
+        self.last_retired = Some(frame);
+
+        let pending = self.callbacks.split_off(&(frame + 1));
+        let retired = std::mem::replace(&mut self.callbacks, pending);
+        let mut count = 0;
+        for callback in retired.into_values().flatten() {
+            callback();
+            count += 1;
+        }
+        count
+    }
+
+    /// Registers callback which will be invoked when the frame with provided number is retired.
+    ///
+    /// If the frame is already retired, callback is invoked immediately.
+    ///
+    pub fn on_retired(&mut self, frame: u64, callback: impl FnOnce() + Send + 'static) {
+        if self.is_retired(frame) {
+            callback();
+            return;
+        }
+        self.callbacks
+            .entry(frame)
+            .or_default()
+            .push(Box::new(callback));
+    }
+}
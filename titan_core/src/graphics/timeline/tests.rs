@@ -0,0 +1,68 @@
+#![cfg(test)]
+
+use std::sync::{Arc, Mutex};
+
+use super::FrameTimeline;
+
+/// Creates callback which records provided value into the log.
+fn record(log: &Arc<Mutex<Vec<&'static str>>>, value: &'static str) -> impl FnOnce() + Send {
+    let log = log.clone();
+    move || log.lock().unwrap().push(value)
+}
+
+#[test]
+fn frames_are_numbered_in_order() {
+    let mut timeline = FrameTimeline::default();
+    assert_eq!(timeline.frame_counter(), 0);
+    assert_eq!(timeline.submit(), 0);
+    assert_eq!(timeline.submit(), 1);
+    assert_eq!(timeline.frame_counter(), 2);
+    assert_eq!(timeline.last_retired_frame(), None);
+}
+
+#[test]
+fn retire_implies_previous_frames() {
+    let mut timeline = FrameTimeline::default();
+    (0..3).for_each(|_| {
+        timeline.submit();
+    });
+    timeline.retire(1);
+    assert!(timeline.is_retired(0));
+    assert!(timeline.is_retired(1));
+    assert!(!timeline.is_retired(2));
+
+    // Retiring older frame does not move the timeline back.
+    timeline.retire(0);
+    assert_eq!(timeline.last_retired_frame(), Some(1));
+}
+
+#[test]
+fn callbacks_are_invoked_in_order() {
+    let log = Arc::default();
+    let mut timeline = FrameTimeline::default();
+    (0..3).for_each(|_| {
+        timeline.submit();
+    });
+    timeline.on_retired(2, record(&log, "2"));
+    timeline.on_retired(0, record(&log, "0a"));
+    timeline.on_retired(1, record(&log, "1"));
+    timeline.on_retired(0, record(&log, "0b"));
+
+    assert_eq!(timeline.retire(1), 3);
+    assert_eq!(*log.lock().unwrap(), ["0a", "0b", "1"]);
+
+    assert_eq!(timeline.retire(1), 0);
+    timeline.retire(2);
+    assert_eq!(*log.lock().unwrap(), ["0a", "0b", "1", "2"]);
+}
+
+#[test]
+fn callback_of_retired_frame_is_invoked_immediately() {
+    let log = Arc::default();
+    let mut timeline = FrameTimeline::default();
+    timeline.submit();
+    timeline.retire(0);
+
+    timeline.on_retired(0, record(&log, "now"));
+    assert_eq!(*log.lock().unwrap(), ["now"]);
+}
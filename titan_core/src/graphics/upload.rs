@@ -0,0 +1,261 @@
+//! Staging upload utilities for device local buffers of game engine.
+//!
+//! Data is written into temporary host visible buffer and then copied
+//! into device local buffer by one-time command buffer, so static geometry
+//! and textures are placed in the fastest memory of the device.
+
+use std::mem;
+use std::sync::Arc;
+
+use thiserror::Error;
+use vulkano::buffer::{BufferUsage, CpuAccessibleBuffer, DeviceLocalBuffer};
+use vulkano::command_buffer::{
+    BuildError, CommandBufferExecError, CopyBufferError, CopyBufferImageError,
+};
+use vulkano::device::physical::QueueFamily;
+use vulkano::device::Queue;
+use vulkano::format::Format;
+use vulkano::image::{
+    ImageCreateFlags, ImageCreationError, ImageDimensions, ImageLayout, ImageUsage, ImmutableImage,
+    MipmapsCount,
+};
+use vulkano::memory::DeviceMemoryAllocError;
+use vulkano::sync::{FlushError, GpuFuture};
+use vulkano::{DeviceSize, OomError};
+
+use crate::graphics::submit::{self, SubmitOnceError};
+use crate::graphics::texture;
+
+mod tests;
+
+/// Error that can happen when uploading data through the staging buffer.
+#[derive(Debug, Error)]
+pub enum UploadError {
+    #[error("uploaded data must not be empty")]
+    Empty,
+
+    #[error("size of {len} elements of {element_size} bytes overflows device size")]
+    TooLarge { len: usize, element_size: usize },
+
+    #[error("staging or device local buffer allocation failure: {0}")]
+    BufferAllocation(#[from] DeviceMemoryAllocError),
+
+    #[error("transfer command buffer allocation failure: {0}")]
+    CommandBufferAllocation(#[from] OomError),
+
+    #[error("copy buffer command failure: {0}")]
+    CopyBuffer(#[from] CopyBufferError),
+
+    #[error("device local image creation failure: {0}")]
+    ImageCreation(#[from] ImageCreationError),
+
+    #[error("mipmaps cannot be generated for format {0:?} without linear blit support")]
+    MipmapsNotSupported(Format),
+
+    #[error("copy buffer to image command failure: {0}")]
+    CopyBufferImage(#[from] CopyBufferImageError),
+
+    #[error("transfer command buffer build failure: {0}")]
+    CommandBufferBuild(#[from] BuildError),
+
+    #[error("transfer command buffer execution failure: {0}")]
+    CommandBufferExec(#[from] CommandBufferExecError),
+
+    #[error("transfer command buffer submit failure: {0}")]
+    Flush(#[from] FlushError),
+}
+
+impl From<SubmitOnceError> for UploadError {
+    fn from(error: SubmitOnceError) -> Self {
+        match error {
+            SubmitOnceError::CommandBufferAllocation(error) => Self::CommandBufferAllocation(error),
+            SubmitOnceError::Build(error) => Self::CommandBufferBuild(error),
+            SubmitOnceError::Execution(error) => Self::CommandBufferExec(error),
+            SubmitOnceError::Flush(error) => Self::Flush(error),
+        }
+    }
+}
+
+/// Computes size in bytes of the buffer which contains `len` elements of type `T`.
+pub fn buffer_size<T>(len: usize) -> Result<DeviceSize, UploadError> {
+    let element_size = mem::size_of::<T>();
+    if len == 0 || element_size == 0 {
+        return Err(UploadError::Empty);
+    }
+    len.checked_mul(element_size)
+        .and_then(|size| DeviceSize::try_from(size).ok())
+        .ok_or(UploadError::TooLarge { len, element_size })
+}
+
+/// Uploader of data into device local buffers through temporary staging buffers.
+#[derive(Clone)]
+pub struct StagingUploader {
+    /// Queue which executes copy commands.
+    transfer_queue: Arc<Queue>,
+    /// Queue which uses uploaded buffers.
+    target_queue: Arc<Queue>,
+}
+
+impl StagingUploader {
+    /// Creates new uploader which copies data on the transfer queue
+    /// for buffers which are used on the target queue.
+    ///
+    /// Both queues could be the same (for example, if device has no dedicated transfer queue).
+    ///
+    pub fn new(transfer_queue: Arc<Queue>, target_queue: Arc<Queue>) -> Self {
+        Self {
+            transfer_queue,
+            target_queue,
+        }
+    }
+
+    /// Uploads data into new device local buffer and waits until the copy is finished.
+    ///
+    /// Staging buffer and command buffer are destroyed after the upload.
+    /// Usage of the buffer is extended with transfer destination usage.
+    ///
+    pub fn upload<T>(
+        &self,
+        data: &[T],
+        usage: BufferUsage,
+    ) -> Result<Arc<DeviceLocalBuffer<[T]>>, UploadError>
+    where
+        T: Copy + Send + Sync + 'static,
+    {
+        self::buffer_size::<T>(data.len())?;
+        let device = self.transfer_queue.device().clone();
+
+        let staging = CpuAccessibleBuffer::from_iter(
+            device.clone(),
+            BufferUsage::transfer_source(),
+            false,
+            data.iter().copied(),
+        )?;
+
+        let usage = BufferUsage {
+            transfer_destination: true,
+            ..usage
+        };
+        let buffer =
+            DeviceLocalBuffer::array(device, data.len() as DeviceSize, usage, self.families())?;
+
+        submit::submit_once::<UploadError>(&self.transfer_queue, |builder| {
+            builder.copy_buffer(staging, buffer.clone())?;
+            Ok(())
+        })?;
+        Ok(buffer)
+    }
+
+    /// Uploads pixels into new device local 2D image without mipmaps
+    /// and waits until the copy is finished.
+    ///
+    /// Image is created in `UNDEFINED` layout, it is transitioned into `TRANSFER_DST_OPTIMAL`
+    /// for the copy and then into `SHADER_READ_ONLY_OPTIMAL`, so it could be sampled right away.
+    ///
+    pub fn upload_image(
+        &self,
+        dimensions: [u32; 2],
+        format: Format,
+        pixels: &[u8],
+    ) -> Result<Arc<ImmutableImage>, UploadError> {
+        self::buffer_size::<u8>(pixels.len())?;
+        let device = self.transfer_queue.device().clone();
+
+        let staging = CpuAccessibleBuffer::from_iter(
+            device.clone(),
+            BufferUsage::transfer_source(),
+            false,
+            pixels.iter().copied(),
+        )?;
+
+        let [width, height] = dimensions;
+        let usage = ImageUsage {
+            transfer_destination: true,
+            sampled: true,
+            ..ImageUsage::none()
+        };
+        let (image, initializer) = ImmutableImage::uninitialized(
+            device,
+            ImageDimensions::Dim2d {
+                width,
+                height,
+                array_layers: 1,
+            },
+            format,
+            MipmapsCount::One,
+            usage,
+            ImageCreateFlags::none(),
+            ImageLayout::ShaderReadOnlyOptimal,
+            self.families(),
+        )?;
+
+        submit::submit_once::<UploadError>(&self.transfer_queue, |builder| {
+            builder.copy_buffer_to_image(staging, initializer)?;
+            Ok(())
+        })?;
+        Ok(image)
+    }
+
+    /// Uploads pixels into new device local 2D image with full mip chain
+    /// and waits until the upload is finished.
+    ///
+    /// Base level is copied from staging buffer, then each next level is blitted
+    /// with linear filter from the previous one. Blits are recorded on the target queue,
+    /// because transfer queue may not support them.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the format does not support linear blitting,
+    /// so the caller could fall back to [`upload_image`](Self::upload_image).
+    ///
+    pub fn upload_image_with_mipmaps(
+        &self,
+        dimensions: [u32; 2],
+        format: Format,
+        pixels: &[u8],
+    ) -> Result<Arc<ImmutableImage>, UploadError> {
+        self::buffer_size::<u8>(pixels.len())?;
+        let device = self.target_queue.device().clone();
+        let features = format
+            .properties(device.physical_device())
+            .optimal_tiling_features;
+        if !texture::supports_mipmap_generation(&features) {
+            return Err(UploadError::MipmapsNotSupported(format));
+        }
+
+        let staging = CpuAccessibleBuffer::from_iter(
+            device,
+            BufferUsage::transfer_source(),
+            false,
+            pixels.iter().copied(),
+        )?;
+
+        let [width, height] = dimensions;
+        let mip_levels = texture::mip_levels(dimensions);
+        // Vulkano records barriers between levels, so each blit reads fully written level.
+        let (image, future) = ImmutableImage::from_buffer(
+            staging,
+            ImageDimensions::Dim2d {
+                width,
+                height,
+                array_layers: 1,
+            },
+            MipmapsCount::Specific(mip_levels),
+            format,
+            self.target_queue.clone(),
+        )?;
+        future.then_signal_fence_and_flush()?.wait(None)?;
+        Ok(image)
+    }
+
+    /// Queue families which uploaded resources are shared between.
+    fn families(&self) -> Vec<QueueFamily<'_>> {
+        let transfer_family = self.transfer_queue.family();
+        let target_family = self.target_queue.family();
+        if transfer_family.id() == target_family.id() {
+            vec![transfer_family]
+        } else {
+            vec![transfer_family, target_family]
+        }
+    }
+}
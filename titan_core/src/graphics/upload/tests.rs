@@ -0,0 +1,27 @@
+#![cfg(test)]
+
+use super::{buffer_size, UploadError};
+
+#[test]
+fn size_is_computed_from_element_size() {
+    assert_eq!(buffer_size::<u16>(6).unwrap(), 12);
+    assert_eq!(buffer_size::<[f32; 7]>(4).unwrap(), 112);
+}
+
+#[test]
+fn empty_data_is_rejected() {
+    assert!(matches!(buffer_size::<u32>(0), Err(UploadError::Empty)));
+    assert!(matches!(buffer_size::<()>(4), Err(UploadError::Empty)));
+}
+
+#[test]
+fn overflowing_size_is_rejected() {
+    let result = buffer_size::<u64>(usize::MAX);
+    assert!(matches!(
+        result,
+        Err(UploadError::TooLarge {
+            len: usize::MAX,
+            element_size: 8,
+        })
+    ));
+}
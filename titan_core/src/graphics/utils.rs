@@ -1,71 +1,205 @@
 //! General graphics utilities for game engine.
 
+use std::ffi::CString;
+use std::fmt;
 use std::sync::Arc;
 
-use vulkano::device::physical::{PhysicalDevice, PhysicalDeviceType, QueueFamily};
+use vulkano::device::physical::{PhysicalDevice, QueueFamily};
 use vulkano::device::{DeviceExtensions, Features};
 use vulkano::format::Format;
-use vulkano::instance::{ApplicationInfo, Instance, InstanceCreationError};
-use vulkano::swapchain::{Capabilities, ColorSpace, Surface};
+use vulkano::image::{ImageUsage, SampleCount, SampleCounts};
+use vulkano::instance::{
+    layers_list, ApplicationInfo, Instance, InstanceCreationError, InstanceExtensions,
+};
+use vulkano::swapchain::{
+    Capabilities, ColorSpace, CompositeAlpha, PresentMode, SupportedCompositeAlpha,
+    SupportedPresentModes, Surface,
+};
 use vulkano_win::required_extensions;
-use winit::window::Window;
+use winit::event_loop::EventLoopWindowTarget;
+use winit::window::{Window, WindowBuilder};
 
-use crate::config::{Config, ENGINE_NAME, ENGINE_VERSION};
+use crate::config::settings::{AdapterCapabilities, GraphicsCapabilities};
+use crate::config::{self, Config, ENGINE_NAME, ENGINE_VERSION};
+
+use super::present;
+
+mod tests;
+
+/// Name of Khronos validation layer.
+const VALIDATION_LAYER: &str = "VK_LAYER_KHRONOS_validation";
 
 /// Convert [`semver::Version`] Version struct into [`vulkano::Version`] struct.
-#[inline(always)]
-const fn to_vk_version(version: &semver::Version) -> vulkano::Version {
-    vulkano::Version {
+///
+/// Returns `None` if any component overflows the packed representation of Vulkan version.
+///
+fn to_vk_version(version: &semver::Version) -> Option<vulkano::Version> {
+    if !config::fits_vk_version(version) {
+        log::warn!("version {} cannot be packed into Vulkan version", version);
+        return None;
+    }
+    Some(vulkano::Version {
         major: version.major as u32,
         minor: version.minor as u32,
         patch: version.patch as u32,
-    }
+    })
 }
 
 /// Create instance of Vulkan (with low-level vkInstance handle).
 ///
-/// Will enable `VK_EXT_debug_utils` extension if
-/// validation is enabled by config.
+/// Will enable `VK_EXT_debug_utils` extension and validation layer if
+/// validation is enabled by config and the layer is installed, and `VK_EXT_swapchain_colorspace` extension
+/// if HDR surface formats are preferred and the extension is supported.
+/// Extensions of window surfaces are enabled only if `window` is `true`.
 ///
-pub fn create_instance(config: &Config) -> Result<Arc<Instance>, InstanceCreationError> {
+pub fn create_instance(
+    config: &Config,
+    window: bool,
+) -> Result<Arc<Instance>, InstanceCreationError> {
     let info = ApplicationInfo {
         application_name: Some(config.name().into()),
-        application_version: Some(self::to_vk_version(config.version())),
+        application_version: self::to_vk_version(config.version()),
         engine_name: Some(ENGINE_NAME.into()),
-        engine_version: Some(self::to_vk_version(&*ENGINE_VERSION)),
+        engine_version: self::to_vk_version(&ENGINE_VERSION),
+    };
+    let validation = config.enable_validation() && {
+        let available = layers_list()
+            .is_ok_and(|mut layers| layers.any(|layer| layer.name() == VALIDATION_LAYER));
+        if !available {
+            log::warn!(
+                "validation was requested, but {} is not installed, continuing without it",
+                VALIDATION_LAYER,
+            );
+        }
+        available
     };
     let extensions = {
-        let mut extensions = required_extensions();
-        if config.enable_validation() {
-            extensions.ext_debug_utils = true;
+        // Offscreen rendering does not need extensions of window surfaces.
+        let mut extensions = if window {
+            required_extensions()
+        } else {
+            InstanceExtensions::none()
+        };
+        extensions.ext_debug_utils = validation;
+        // Exclusive fullscreen of swapchains needs surface capabilities of the second version.
+        if window && cfg!(windows) {
+            let capabilities2 = InstanceExtensions::supported_by_core().is_ok_and(|supported| {
+                supported.khr_get_physical_device_properties2
+                    && supported.khr_get_surface_capabilities2
+            });
+            extensions.khr_get_physical_device_properties2 = capabilities2;
+            extensions.khr_get_surface_capabilities2 = capabilities2;
+        }
+        if config
+            .surface_formats()
+            .iter()
+            .any(|format| format.is_hdr())
+        {
+            extensions.ext_swapchain_colorspace = InstanceExtensions::supported_by_core()
+                .is_ok_and(|supported| supported.ext_swapchain_colorspace);
         }
         extensions
     };
-    let layers = config
-        .enable_validation()
-        .then(|| "VK_LAYER_KHRONOS_validation");
+    let layers = validation.then_some(VALIDATION_LAYER);
 
     Instance::new(Some(&info), vulkano::Version::V1_2, &extensions, layers)
 }
 
+/// Names of provided device extensions.
+pub fn extension_names(extensions: &DeviceExtensions) -> Vec<String> {
+    Vec::<CString>::from(extensions)
+        .into_iter()
+        .filter_map(|name| name.into_string().ok())
+        .collect()
+}
+
+/// Device extensions with provided names, unknown names are ignored.
+pub fn device_extensions(names: &[String]) -> DeviceExtensions {
+    let names: Vec<_> = names
+        .iter()
+        .filter_map(|name| CString::new(name.as_str()).ok())
+        .collect();
+    DeviceExtensions::from(names.iter().map(CString::as_c_str))
+}
+
+/// Device extensions which portability implementations (such as MoltenVK)
+/// require to be enabled if the physical device advertises them.
+///
+/// Non-Apple platforms have no portability implementations, so nothing is returned there.
+///
+pub fn portability_extensions(physical_device: PhysicalDevice) -> DeviceExtensions {
+    if !cfg!(any(target_os = "macos", target_os = "ios")) {
+        return DeviceExtensions::none();
+    }
+    let khr_portability_subset = physical_device
+        .supported_extensions()
+        .khr_portability_subset;
+    if khr_portability_subset {
+        log::info!("portability mode is active: VK_KHR_portability_subset is enabled");
+    }
+    DeviceExtensions {
+        khr_portability_subset,
+        ..DeviceExtensions::none()
+    }
+}
+
+/// Device extensions which allow swapchains to use exclusive fullscreen
+/// if the physical device advertises them.
+///
+/// Only Windows has the extension, and it requires surface capabilities of the second version
+/// to be enabled on the instance, so nothing is returned otherwise.
+///
+pub fn full_screen_exclusive_extensions(physical_device: PhysicalDevice) -> DeviceExtensions {
+    let instance_extensions = physical_device.instance().enabled_extensions();
+    let available = cfg!(windows)
+        && instance_extensions.khr_get_physical_device_properties2
+        && instance_extensions.khr_get_surface_capabilities2
+        && physical_device
+            .supported_extensions()
+            .ext_full_screen_exclusive;
+    DeviceExtensions {
+        ext_full_screen_exclusive: available,
+        ..DeviceExtensions::none()
+    }
+}
+
 /// Internal struct for representing suitable physical device with its queue families.
 pub struct SuitablePhysicalDevice<'a> {
     pub physical_device: PhysicalDevice<'a>,
     pub graphics_family: QueueFamily<'a>,
     pub present_family: Option<QueueFamily<'a>>,
     pub transfer_family: Option<QueueFamily<'a>>,
+    pub compute_family: Option<QueueFamily<'a>>,
 }
 
-/// Filter suitable physical device from all of them.
+/// Builds the window and its surface.
+///
+/// Surface shares ownership of the window, so the window outlives the surface
+/// and another surface could be created for it later (e.g. when the surface was lost).
+///
+pub fn build_surface<T>(
+    builder: WindowBuilder,
+    event_loop: &EventLoopWindowTarget<T>,
+    instance: Arc<Instance>,
+) -> Result<Arc<Surface<Arc<Window>>>, vulkano_win::CreationError> {
+    let window = builder
+        .build(event_loop)
+        .map_err(vulkano_win::CreationError::WindowCreationError)?;
+    vulkano_win::create_vk_surface(Arc::new(window), instance)
+        .map_err(vulkano_win::CreationError::SurfaceCreationError)
+}
+
+/// Filter suitable physical devices from all of them.
 ///
 /// Will check for provided extensions and features support.
+/// Presentation support is checked only if the surface is provided.
 ///
-pub fn suitable_physical_device<'a>(
+pub fn suitable_physical_devices<'a>(
     physical_devices: impl ExactSizeIterator<Item = PhysicalDevice<'a>>,
-    surface: &Arc<Surface<Window>>,
+    surface: Option<&Arc<Surface<Arc<Window>>>>,
     required_extensions: &DeviceExtensions,
     required_features: &Features,
-) -> Option<SuitablePhysicalDevice<'a>> {
+) -> Vec<SuitablePhysicalDevice<'a>> {
     physical_devices
         .filter(|physical_device| {
             let extensions = physical_device.supported_extensions();
@@ -74,56 +208,83 @@ pub fn suitable_physical_device<'a>(
                 && features.is_superset_of(required_features)
         })
         .filter_map(|physical_device| {
-            let graphics_family = physical_device
-                .queue_families()
-                .find(QueueFamily::supports_graphics);
-            let present_family = physical_device
-                .queue_families()
-                .find(|&queue| surface.is_supported(queue).unwrap_or(false));
-            let transfer_family = physical_device
-                .queue_families()
-                .find(QueueFamily::explicitly_supports_transfers);
-            match (graphics_family, present_family, transfer_family) {
-                (Some(graphics_family), Some(present_family), Some(transfer_family)) => {
-                    Some(SuitablePhysicalDevice {
-                        physical_device,
-                        graphics_family,
-                        present_family: Some(present_family),
-                        transfer_family: Some(transfer_family),
-                    })
-                }
-                (Some(graphics_family), Some(present_family), None) => {
-                    Some(SuitablePhysicalDevice {
-                        physical_device,
-                        graphics_family,
-                        present_family: Some(present_family),
-                        transfer_family: None,
-                    })
-                }
-                (Some(graphics_family), None, None) => Some(SuitablePhysicalDevice {
-                    physical_device,
-                    graphics_family,
-                    present_family: None,
-                    transfer_family: None,
-                }),
-                _ => None,
-            }
+            let families: Vec<_> = physical_device.queue_families().collect();
+            let properties: Vec<_> = families
+                .iter()
+                .map(|family| QueueFamilyProperties {
+                    graphics: family.supports_graphics(),
+                    present: surface
+                        .is_none_or(|surface| surface.is_supported(*family).unwrap_or(false)),
+                    transfer: family.explicitly_supports_transfers(),
+                    compute: family.supports_compute(),
+                })
+                .collect();
+            let selection = self::select_queue_families(&properties)?;
+            let graphics_family = families[selection.graphics];
+            let present_family = families[selection.present];
+            Some(SuitablePhysicalDevice {
+                physical_device,
+                graphics_family,
+                present_family: surface.map(|_| present_family),
+                transfer_family: selection.transfer.map(|index| families[index]),
+                compute_family: selection.compute.map(|index| families[index]),
+            })
         })
-        .max_by_key(|suitable| self::score(&suitable.physical_device))
-}
-
-/// Calculates internal score of given physical device.
-fn score(physical_device: &PhysicalDevice) -> u32 {
-    let properties = physical_device.properties();
-    let mut score = match properties.device_type {
-        PhysicalDeviceType::DiscreteGpu => 10000,
-        PhysicalDeviceType::IntegratedGpu => 1000,
-        PhysicalDeviceType::VirtualGpu => 100,
-        PhysicalDeviceType::Cpu => 10,
-        PhysicalDeviceType::Other => 0,
+        .collect()
+}
+
+/// Capabilities of queue family which are used for queue families selection.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct QueueFamilyProperties {
+    pub graphics: bool,
+    pub present: bool,
+    pub transfer: bool,
+    pub compute: bool,
+}
+
+/// Indices of queue families selected by [`select_queue_families`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct QueueFamilySelection {
+    pub graphics: usize,
+    pub present: usize,
+    pub transfer: Option<usize>,
+    pub compute: Option<usize>,
+}
+
+/// Selects queue families for graphics, presentation, transfer and compute operations.
+///
+/// Family which supports both graphics and presentation is preferred,
+/// otherwise distinct present family is selected.
+/// Transfer family is selected only if there is dedicated one (without graphics support).
+/// Graphics family is reused for compute operations if it supports them,
+/// otherwise distinct compute family is selected, if any.
+///
+/// Returns `None` if there is no graphics family or no family which supports presentation.
+///
+pub fn select_queue_families(families: &[QueueFamilyProperties]) -> Option<QueueFamilySelection> {
+    let graphics = families
+        .iter()
+        .position(|family| family.graphics && family.present)
+        .or_else(|| families.iter().position(|family| family.graphics))?;
+    let present = if families[graphics].present {
+        graphics
+    } else {
+        families.iter().position(|family| family.present)?
+    };
+    let transfer = families
+        .iter()
+        .position(|family| family.transfer && !family.graphics);
+    let compute = if families[graphics].compute {
+        Some(graphics)
+    } else {
+        families.iter().position(|family| family.compute)
     };
-    score += properties.max_image_dimension2_d;
-    score
+    Some(QueueFamilySelection {
+        graphics,
+        present,
+        transfer,
+        compute,
+    })
 }
 
 /// Depth stencil formats which are suitable for rendering backend.
@@ -148,17 +309,315 @@ pub fn suitable_depth_stencil_format(physical_device: PhysicalDevice) -> Format
         .unwrap_or(&Format::D16_UNORM)
 }
 
-/// Image format which is suitable for rendering backend.
-pub const SUITABLE_IMAGE_FORMAT: (Format, ColorSpace) =
-    (Format::B8G8R8A8_SRGB, ColorSpace::SrgbNonLinear);
+/// Selects count of samples per pixel which is supported by both color and depth attachments.
+///
+/// If requested count is not supported, the highest supported count below it is returned.
+///
+pub fn supported_sample_count(
+    requested: u32,
+    color: SampleCounts,
+    depth: SampleCounts,
+) -> SampleCount {
+    let supported = |counts: SampleCounts, count: SampleCount| match count {
+        SampleCount::Sample1 => counts.sample1,
+        SampleCount::Sample2 => counts.sample2,
+        SampleCount::Sample4 => counts.sample4,
+        SampleCount::Sample8 => counts.sample8,
+        SampleCount::Sample16 => counts.sample16,
+        SampleCount::Sample32 => counts.sample32,
+        SampleCount::Sample64 => counts.sample64,
+    };
+    [
+        SampleCount::Sample64,
+        SampleCount::Sample32,
+        SampleCount::Sample16,
+        SampleCount::Sample8,
+        SampleCount::Sample4,
+        SampleCount::Sample2,
+    ]
+    .into_iter()
+    .filter(|&count| count as u32 <= requested)
+    .find(|&count| supported(color, count) && supported(depth, count))
+    .unwrap_or(SampleCount::Sample1)
+}
 
-/// Retrieves suitable image format if supported by physical device.
+/// Probes capabilities of the system which user settings depend on.
+///
+/// Creates temporary instance without a window, so supported present modes remain unknown.
 ///
-/// If none of suitable image formats are supported, returns first supported format.
-pub fn suitable_image_format(capabilities: &Capabilities) -> (Format, ColorSpace) {
-    let formats = &capabilities.supported_formats;
-    *formats
+pub fn probe_capabilities(config: &Config) -> Result<GraphicsCapabilities, InstanceCreationError> {
+    let instance = self::create_instance(config, false)?;
+    let adapters = PhysicalDevice::enumerate(&instance)
+        .map(|physical_device| {
+            let properties = physical_device.properties();
+            let max_msaa_samples = self::supported_sample_count(
+                u32::MAX,
+                properties.framebuffer_color_sample_counts,
+                properties.framebuffer_depth_sample_counts,
+            );
+            AdapterCapabilities {
+                name: properties.device_name.clone(),
+                max_msaa_samples: max_msaa_samples as u32,
+            }
+        })
+        .collect();
+    Ok(GraphicsCapabilities {
+        adapters,
+        present_modes: None,
+        present_thread: present::present_thread_supported(),
+    })
+}
+
+/// Swapchain image formats which correspond to provided surface format, in order of preference.
+pub fn surface_format_candidates(
+    surface_format: config::SurfaceFormat,
+) -> &'static [(Format, ColorSpace)] {
+    use config::SurfaceFormat;
+
+    match surface_format {
+        SurfaceFormat::Srgb => &[
+            (Format::B8G8R8A8_SRGB, ColorSpace::SrgbNonLinear),
+            (Format::R8G8B8A8_SRGB, ColorSpace::SrgbNonLinear),
+        ],
+        SurfaceFormat::Unorm => &[
+            (Format::B8G8R8A8_UNORM, ColorSpace::SrgbNonLinear),
+            (Format::R8G8B8A8_UNORM, ColorSpace::SrgbNonLinear),
+        ],
+        SurfaceFormat::Hdr10 => &[
+            (Format::A2B10G10R10_UNORM_PACK32, ColorSpace::Hdr10St2084),
+            (Format::A2R10G10B10_UNORM_PACK32, ColorSpace::Hdr10St2084),
+        ],
+        SurfaceFormat::ScRgb => &[(Format::R16G16B16A16_SFLOAT, ColorSpace::ExtendedSrgbLinear)],
+    }
+}
+
+/// Reason why image format was selected by [`suitable_image_format`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ImageFormatChoice {
+    /// Surface format at provided index of preference list is supported,
+    /// and all more preferred ones are not.
+    Preferred(usize, config::SurfaceFormat),
+    /// None of preferred surface formats are supported, so the first supported one is used.
+    Fallback,
+}
+
+impl fmt::Display for ImageFormatChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Preferred(0, surface_format) => {
+                write!(
+                    f,
+                    "{:?} is the most preferred surface format",
+                    surface_format
+                )
+            }
+            Self::Preferred(index, surface_format) => write!(
+                f,
+                "{:?} is preferred surface format #{}, more preferred ones are not supported",
+                surface_format,
+                index + 1,
+            ),
+            Self::Fallback => write!(
+                f,
+                "none of preferred surface formats are supported, the first supported one is used",
+            ),
+        }
+    }
+}
+
+/// Selects image format of the swapchain from formats supported by the surface
+/// according to provided preference list.
+///
+/// If none of preferred formats are supported, returns first supported format.
+///
+pub fn suitable_image_format(
+    supported: &[(Format, ColorSpace)],
+    preferred: &[config::SurfaceFormat],
+) -> (Format, ColorSpace, ImageFormatChoice) {
+    preferred
+        .iter()
+        .enumerate()
+        .find_map(|(index, &surface_format)| {
+            self::surface_format_candidates(surface_format)
+                .iter()
+                .find(|candidate| supported.contains(candidate))
+                .map(|&(format, color_space)| {
+                    let choice = ImageFormatChoice::Preferred(index, surface_format);
+                    (format, color_space, choice)
+                })
+        })
+        .unwrap_or_else(|| {
+            let (format, color_space) = supported[0];
+            (format, color_space, ImageFormatChoice::Fallback)
+        })
+}
+
+/// Retrieves preferred present mode if supported by the surface.
+///
+/// If there is no preferred present mode or it is not supported,
+/// returns [`PresentMode::Mailbox`] if supported or [`PresentMode::Fifo`] which is always supported.
+pub fn suitable_present_mode(
+    present_modes: &SupportedPresentModes,
+    preferred: Option<config::PresentMode>,
+) -> PresentMode {
+    if let Some(preferred) = preferred.map(self::to_vk_present_mode) {
+        if present_modes.supports(preferred) {
+            return preferred;
+        }
+        log::warn!("present mode {:?} is not supported, ignoring", preferred);
+    }
+    present_modes
         .iter()
-        .find(|&&format| SUITABLE_IMAGE_FORMAT == format)
-        .unwrap_or_else(|| &formats[0])
+        .find(|&mode| mode == PresentMode::Mailbox)
+        .unwrap_or(PresentMode::Fifo)
+}
+
+/// Converts present mode of configuration into present mode of the swapchain.
+pub fn to_vk_present_mode(present_mode: config::PresentMode) -> PresentMode {
+    match present_mode {
+        config::PresentMode::Immediate => PresentMode::Immediate,
+        config::PresentMode::Mailbox => PresentMode::Mailbox,
+        config::PresentMode::Fifo => PresentMode::Fifo,
+        config::PresentMode::FifoRelaxed => PresentMode::Relaxed,
+    }
+}
+
+/// Converts present mode of the swapchain into present mode of configuration.
+///
+/// Modes which are never selected by the engine are treated as [`Fifo`](config::PresentMode::Fifo).
+///
+pub fn from_vk_present_mode(present_mode: PresentMode) -> config::PresentMode {
+    match present_mode {
+        PresentMode::Immediate => config::PresentMode::Immediate,
+        PresentMode::Mailbox => config::PresentMode::Mailbox,
+        PresentMode::Relaxed => config::PresentMode::FifoRelaxed,
+        _ => config::PresentMode::Fifo,
+    }
+}
+
+/// Chooses extent of swapchain images for desired size by surface capabilities.
+///
+/// If surface defines its current extent, swapchain must be of the same extent.
+/// Otherwise, desired size (size of the window) is clamped by supported extents.
+///
+pub fn choose_extent(capabilities: &Capabilities, desired: [u32; 2]) -> [u32; 2] {
+    if let Some(current_extent) = capabilities.current_extent {
+        return current_extent;
+    }
+    let [min_width, min_height] = capabilities.min_image_extent;
+    let [max_width, max_height] = capabilities.max_image_extent;
+    let [width, height] = desired;
+    [
+        width.max(min_width).min(max_width),
+        height.max(min_height).min(max_height),
+    ]
+}
+
+/// Chooses count of swapchain images for provided count of frames in flight.
+///
+/// Each frame in flight needs its own image. Desired count is clamped by the surface,
+/// without it one more image than the minimum is requested,
+/// so the next image could be acquired while the presentation engine holds others.
+/// Returns `None` if the surface does not support enough images for the frames in flight.
+///
+pub fn choose_image_count(
+    capabilities: &Capabilities,
+    frames_in_flight: u32,
+    desired: Option<u32>,
+) -> Option<u32> {
+    let max_image_count = capabilities.max_image_count.unwrap_or(u32::MAX);
+    if !(1..=max_image_count).contains(&frames_in_flight) {
+        return None;
+    }
+    let desired = desired.unwrap_or(capabilities.min_image_count + 1);
+    let image_count = desired
+        .max(capabilities.min_image_count)
+        .max(frames_in_flight)
+        .min(max_image_count);
+    Some(image_count)
+}
+
+/// Checks if the surface supports swapchain with exactly provided count of images.
+///
+/// Resources of the renderer are created per image, so recreated surface
+/// must support the same count of images as the lost one.
+///
+pub fn supports_image_count(capabilities: &Capabilities, image_count: u32) -> bool {
+    let max_image_count = capabilities.max_image_count.unwrap_or(u32::MAX);
+    (capabilities.min_image_count..=max_image_count).contains(&image_count)
+}
+
+/// Usage of swapchain images with requested additional usage.
+///
+/// Images could always be rendered into, and copied from (for screenshots)
+/// if it is supported by the surface.
+/// Returns requested usage which is not supported by the surface as an error.
+///
+pub fn swapchain_usage(
+    supported: ImageUsage,
+    requested: config::SwapchainUsage,
+) -> Result<ImageUsage, config::SwapchainUsage> {
+    let supported_usage = config::SwapchainUsage {
+        transfer_src: supported.transfer_source,
+        transfer_dst: supported.transfer_destination,
+    };
+    let missing = requested.difference(supported_usage);
+    if missing != config::SwapchainUsage::NONE {
+        return Err(missing);
+    }
+    Ok(ImageUsage {
+        color_attachment: true,
+        transfer_source: supported.transfer_source,
+        transfer_destination: requested.transfer_dst,
+        ..ImageUsage::none()
+    })
+}
+
+/// Retrieves preferred composite alpha mode if supported by the surface.
+///
+/// Otherwise, returns the first supported mode, preferring opaque presentation.
+///
+pub fn suitable_composite_alpha(
+    supported: &SupportedCompositeAlpha,
+    preferred: config::CompositeAlpha,
+) -> CompositeAlpha {
+    let preferred = self::to_vk_composite_alpha(preferred);
+    if supported.supports(preferred) {
+        return preferred;
+    }
+    let fallback = [
+        CompositeAlpha::Opaque,
+        CompositeAlpha::Inherit,
+        CompositeAlpha::PreMultiplied,
+        CompositeAlpha::PostMultiplied,
+    ]
+    .into_iter()
+    .find(|&mode| supported.supports(mode))
+    .unwrap_or(CompositeAlpha::Opaque);
+    log::warn!(
+        "composite alpha {:?} is not supported, using {:?} instead",
+        preferred,
+        fallback,
+    );
+    fallback
+}
+
+/// Converts composite alpha mode of configuration into the mode of the swapchain.
+pub fn to_vk_composite_alpha(composite_alpha: config::CompositeAlpha) -> CompositeAlpha {
+    match composite_alpha {
+        config::CompositeAlpha::Opaque => CompositeAlpha::Opaque,
+        config::CompositeAlpha::PreMultiplied => CompositeAlpha::PreMultiplied,
+        config::CompositeAlpha::PostMultiplied => CompositeAlpha::PostMultiplied,
+        config::CompositeAlpha::Inherit => CompositeAlpha::Inherit,
+    }
+}
+
+/// Converts composite alpha mode of the swapchain into the mode of configuration.
+pub fn from_vk_composite_alpha(composite_alpha: CompositeAlpha) -> config::CompositeAlpha {
+    match composite_alpha {
+        CompositeAlpha::Opaque => config::CompositeAlpha::Opaque,
+        CompositeAlpha::PreMultiplied => config::CompositeAlpha::PreMultiplied,
+        CompositeAlpha::PostMultiplied => config::CompositeAlpha::PostMultiplied,
+        CompositeAlpha::Inherit => config::CompositeAlpha::Inherit,
+    }
 }
@@ -0,0 +1,370 @@
+#![cfg(test)]
+
+use vulkano::format::Format;
+use vulkano::image::{ImageUsage, SampleCount, SampleCounts};
+use vulkano::swapchain::{
+    Capabilities, ColorSpace, CompositeAlpha, PresentMode, SupportedCompositeAlpha,
+    SupportedPresentModes, SupportedSurfaceTransforms, SurfaceTransform,
+};
+
+use super::{
+    choose_extent, choose_image_count, from_vk_present_mode, select_queue_families,
+    suitable_composite_alpha, suitable_image_format, suitable_present_mode, supported_sample_count,
+    supports_image_count, swapchain_usage, to_vk_present_mode, ImageFormatChoice,
+    QueueFamilyProperties, QueueFamilySelection,
+};
+use crate::config;
+
+fn surface_capabilities(
+    current_extent: Option<[u32; 2]>,
+    min_image_extent: [u32; 2],
+    max_image_extent: [u32; 2],
+) -> Capabilities {
+    Capabilities {
+        min_image_count: 2,
+        max_image_count: None,
+        current_extent,
+        min_image_extent,
+        max_image_extent,
+        max_image_array_layers: 1,
+        supported_transforms: SupportedSurfaceTransforms::none(),
+        current_transform: SurfaceTransform::Identity,
+        supported_composite_alpha: SupportedCompositeAlpha::none(),
+        supported_usage_flags: ImageUsage::none(),
+        supported_formats: Vec::new(),
+        present_modes: SupportedPresentModes::none(),
+    }
+}
+
+#[test]
+fn current_extent_is_used() {
+    let capabilities = surface_capabilities(Some([800, 600]), [1, 1], [4096, 4096]);
+    assert_eq!(choose_extent(&capabilities, [1024, 768]), [800, 600]);
+}
+
+#[test]
+fn undefined_current_extent_uses_desired() {
+    // Special value `0xFFFFFFFF` of current extent is represented as `None`.
+    let capabilities = surface_capabilities(None, [1, 1], [4096, 4096]);
+    assert_eq!(choose_extent(&capabilities, [1024, 768]), [1024, 768]);
+}
+
+#[test]
+fn desired_is_clamped_to_max() {
+    let capabilities = surface_capabilities(None, [1, 1], [640, 480]);
+    assert_eq!(choose_extent(&capabilities, [1024, 300]), [640, 300]);
+}
+
+#[test]
+fn desired_is_clamped_to_min() {
+    let capabilities = surface_capabilities(None, [200, 100], [4096, 4096]);
+    assert_eq!(choose_extent(&capabilities, [150, 50]), [200, 100]);
+}
+
+#[test]
+fn zero_sizes() {
+    let capabilities = surface_capabilities(None, [1, 1], [4096, 4096]);
+    assert_eq!(choose_extent(&capabilities, [0, 0]), [1, 1]);
+
+    // Minimized window could report zero current extent.
+    let capabilities = surface_capabilities(Some([0, 0]), [0, 0], [0, 0]);
+    assert_eq!(choose_extent(&capabilities, [800, 600]), [0, 0]);
+}
+
+const GRAPHICS: QueueFamilyProperties = QueueFamilyProperties {
+    graphics: true,
+    present: false,
+    transfer: true,
+    compute: true,
+};
+const PRESENT: QueueFamilyProperties = QueueFamilyProperties {
+    graphics: false,
+    present: true,
+    transfer: false,
+    compute: false,
+};
+const TRANSFER: QueueFamilyProperties = QueueFamilyProperties {
+    graphics: false,
+    present: false,
+    transfer: true,
+    compute: false,
+};
+const UNIVERSAL: QueueFamilyProperties = QueueFamilyProperties {
+    graphics: true,
+    present: true,
+    transfer: true,
+    compute: true,
+};
+
+#[test]
+fn same_graphics_and_present_family() {
+    let selection = select_queue_families(&[UNIVERSAL]).unwrap();
+    assert_eq!(
+        selection,
+        QueueFamilySelection {
+            graphics: 0,
+            present: 0,
+            transfer: None,
+            compute: Some(0),
+        },
+    );
+}
+
+#[test]
+fn universal_family_is_preferred() {
+    let selection = select_queue_families(&[GRAPHICS, PRESENT, UNIVERSAL]).unwrap();
+    assert_eq!(selection.graphics, 2);
+    assert_eq!(selection.present, 2);
+}
+
+#[test]
+fn distinct_present_family() {
+    let selection = select_queue_families(&[GRAPHICS, TRANSFER, PRESENT]).unwrap();
+    assert_eq!(
+        selection,
+        QueueFamilySelection {
+            graphics: 0,
+            present: 2,
+            transfer: Some(1),
+            compute: Some(0),
+        },
+    );
+}
+
+#[test]
+fn distinct_compute_family() {
+    let graphics = QueueFamilyProperties {
+        compute: false,
+        ..UNIVERSAL
+    };
+    let compute = QueueFamilyProperties {
+        compute: true,
+        ..TRANSFER
+    };
+    let selection = select_queue_families(&[graphics, compute]).unwrap();
+    assert_eq!(selection.compute, Some(1));
+    let selection = select_queue_families(&[graphics]).unwrap();
+    assert_eq!(selection.compute, None);
+}
+
+#[test]
+fn no_present_family() {
+    assert_eq!(select_queue_families(&[GRAPHICS, TRANSFER]), None);
+}
+
+#[test]
+fn no_graphics_family() {
+    assert_eq!(select_queue_families(&[PRESENT, TRANSFER]), None);
+    assert_eq!(select_queue_families(&[]), None);
+}
+
+fn sample_counts(max: u32) -> SampleCounts {
+    SampleCounts {
+        sample1: true,
+        sample2: max >= 2,
+        sample4: max >= 4,
+        sample8: max >= 8,
+        sample16: max >= 16,
+        sample32: max >= 32,
+        sample64: max >= 64,
+    }
+}
+
+#[test]
+fn supported_sample_count_is_kept() {
+    let counts = sample_counts(8);
+    assert_eq!(
+        supported_sample_count(1, counts, counts),
+        SampleCount::Sample1
+    );
+    assert_eq!(
+        supported_sample_count(4, counts, counts),
+        SampleCount::Sample4
+    );
+    assert_eq!(
+        supported_sample_count(8, counts, counts),
+        SampleCount::Sample8
+    );
+}
+
+#[test]
+fn unsupported_sample_count_is_clamped() {
+    let color = sample_counts(8);
+    let depth = sample_counts(4);
+    assert_eq!(
+        supported_sample_count(16, color, depth),
+        SampleCount::Sample4
+    );
+    // Count which is not a power of two is rounded down.
+    assert_eq!(
+        supported_sample_count(3, color, depth),
+        SampleCount::Sample2
+    );
+    assert_eq!(
+        supported_sample_count(0, color, depth),
+        SampleCount::Sample1
+    );
+}
+
+#[test]
+fn preferred_present_mode_falls_back_to_fifo() {
+    let mut present_modes = SupportedPresentModes::none();
+    present_modes.fifo = true;
+    present_modes.immediate = true;
+    let immediate = Some(config::PresentMode::Immediate);
+    let mailbox = Some(config::PresentMode::Mailbox);
+    assert_eq!(
+        suitable_present_mode(&present_modes, immediate),
+        PresentMode::Immediate,
+    );
+    assert_eq!(
+        suitable_present_mode(&present_modes, mailbox),
+        PresentMode::Fifo
+    );
+    assert_eq!(
+        suitable_present_mode(&present_modes, None),
+        PresentMode::Fifo
+    );
+    present_modes.mailbox = true;
+    assert_eq!(
+        suitable_present_mode(&present_modes, None),
+        PresentMode::Mailbox
+    );
+}
+
+#[test]
+fn present_mode_conversion_round_trips() {
+    let modes = [
+        config::PresentMode::Immediate,
+        config::PresentMode::Mailbox,
+        config::PresentMode::Fifo,
+        config::PresentMode::FifoRelaxed,
+    ];
+    for mode in modes {
+        assert_eq!(from_vk_present_mode(to_vk_present_mode(mode)), mode);
+    }
+}
+
+#[test]
+fn image_format_follows_preference() {
+    use config::SurfaceFormat;
+
+    let supported = [
+        (Format::B8G8R8A8_UNORM, ColorSpace::SrgbNonLinear),
+        (Format::B8G8R8A8_SRGB, ColorSpace::SrgbNonLinear),
+        (Format::A2B10G10R10_UNORM_PACK32, ColorSpace::Hdr10St2084),
+    ];
+    let preferred = [SurfaceFormat::Srgb, SurfaceFormat::Unorm];
+    assert_eq!(
+        suitable_image_format(&supported, &preferred),
+        (
+            Format::B8G8R8A8_SRGB,
+            ColorSpace::SrgbNonLinear,
+            ImageFormatChoice::Preferred(0, SurfaceFormat::Srgb),
+        ),
+    );
+    let preferred = [SurfaceFormat::Hdr10, SurfaceFormat::Srgb];
+    assert_eq!(
+        suitable_image_format(&supported, &preferred).0,
+        Format::A2B10G10R10_UNORM_PACK32,
+    );
+}
+
+#[test]
+fn image_format_falls_back_without_hdr_support() {
+    use config::SurfaceFormat;
+
+    let supported = [(Format::R8G8B8A8_UNORM, ColorSpace::SrgbNonLinear)];
+    let preferred = [SurfaceFormat::ScRgb, SurfaceFormat::Unorm];
+    let (format, _, choice) = suitable_image_format(&supported, &preferred);
+    assert_eq!(format, Format::R8G8B8A8_UNORM);
+    assert_eq!(
+        choice,
+        ImageFormatChoice::Preferred(1, SurfaceFormat::Unorm)
+    );
+    let (format, _, choice) = suitable_image_format(&supported, &[SurfaceFormat::Hdr10]);
+    assert_eq!(format, Format::R8G8B8A8_UNORM);
+    assert_eq!(choice, ImageFormatChoice::Fallback);
+}
+
+#[test]
+fn image_count_covers_frames_in_flight() {
+    let mut capabilities = surface_capabilities(None, [1, 1], [4096, 4096]);
+    capabilities.min_image_count = 2;
+    assert_eq!(choose_image_count(&capabilities, 1, None), Some(3));
+    assert_eq!(choose_image_count(&capabilities, 4, None), Some(4));
+    capabilities.max_image_count = Some(3);
+    assert_eq!(choose_image_count(&capabilities, 3, None), Some(3));
+}
+
+#[test]
+fn too_many_frames_in_flight_for_images() {
+    // Surface with exactly two images cannot hold three frames in flight.
+    let mut capabilities = surface_capabilities(None, [1, 1], [4096, 4096]);
+    capabilities.min_image_count = 2;
+    capabilities.max_image_count = Some(2);
+    assert_eq!(choose_image_count(&capabilities, 2, None), Some(2));
+    assert_eq!(choose_image_count(&capabilities, 3, None), None);
+    assert_eq!(choose_image_count(&capabilities, 0, None), None);
+}
+
+#[test]
+fn desired_image_count_is_clamped() {
+    let mut capabilities = surface_capabilities(None, [1, 1], [4096, 4096]);
+    capabilities.min_image_count = 2;
+    capabilities.max_image_count = Some(4);
+    assert_eq!(choose_image_count(&capabilities, 2, Some(3)), Some(3));
+    assert_eq!(choose_image_count(&capabilities, 2, Some(1)), Some(2));
+    assert_eq!(choose_image_count(&capabilities, 3, Some(2)), Some(3));
+    assert_eq!(choose_image_count(&capabilities, 2, Some(8)), Some(4));
+}
+
+#[test]
+fn image_count_of_restored_surface() {
+    let mut capabilities = surface_capabilities(None, [1, 1], [4096, 4096]);
+    capabilities.min_image_count = 2;
+    assert!(supports_image_count(&capabilities, 2));
+    assert!(supports_image_count(&capabilities, 8));
+    assert!(!supports_image_count(&capabilities, 1));
+    capabilities.max_image_count = Some(3);
+    assert!(supports_image_count(&capabilities, 3));
+    assert!(!supports_image_count(&capabilities, 4));
+}
+
+#[test]
+fn unsupported_swapchain_usage_is_rejected() {
+    let supported = ImageUsage {
+        color_attachment: true,
+        transfer_source: true,
+        ..ImageUsage::none()
+    };
+    let requested = config::SwapchainUsage {
+        transfer_src: true,
+        transfer_dst: true,
+    };
+    let missing = config::SwapchainUsage {
+        transfer_dst: true,
+        ..config::SwapchainUsage::NONE
+    };
+    assert_eq!(swapchain_usage(supported, requested), Err(missing));
+
+    let usage = swapchain_usage(supported, config::SwapchainUsage::NONE).unwrap();
+    assert!(usage.color_attachment && usage.transfer_source);
+    assert!(!usage.transfer_destination);
+}
+
+#[test]
+fn composite_alpha_falls_back_to_supported() {
+    let supported = SupportedCompositeAlpha {
+        inherit: true,
+        ..SupportedCompositeAlpha::none()
+    };
+    let alpha = suitable_composite_alpha(&supported, config::CompositeAlpha::PreMultiplied);
+    assert_eq!(alpha, CompositeAlpha::Inherit);
+    let supported = SupportedCompositeAlpha {
+        pre_multiplied: true,
+        ..supported
+    };
+    let alpha = suitable_composite_alpha(&supported, config::CompositeAlpha::PreMultiplied);
+    assert_eq!(alpha, CompositeAlpha::PreMultiplied);
+}
@@ -105,18 +105,36 @@ pub struct Vertex {
     pub position: Position3,
     /// Color of this vertex.
     pub color: Color,
+    /// UV position on the texture of game object.
+    pub uv: Position2,
 }
 
-vulkano::impl_vertex!(Vertex, position, color);
+vulkano::impl_vertex!(Vertex, position, color, uv);
 
 impl Vertex {
     /// Creates new vertex with given position and color.
+    ///
+    /// UV position of the vertex is zero.
+    ///
     pub fn new(position: Vec3, color: Srgba) -> Self {
         Self {
             position: Position3(position),
             color: Color(color),
+            uv: Position2::default(),
         }
     }
+
+    /// Sets UV position of the vertex on the texture of game object.
+    pub fn with_uv(mut self, uv: [f32; 2]) -> Self {
+        self.uv = Position2(uv.into());
+        self
+    }
+
+    /// Creates new vertex from raw components of position and color (in RGBA order).
+    pub fn from_raw(position: [f32; 3], color: [f32; 4]) -> Self {
+        let [r, g, b, a] = color;
+        Self::new(position.into(), Srgba::new(r, g, b, a))
+    }
 }
 
 /// Vertex type which is used in vertex buffer.
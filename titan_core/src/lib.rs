@@ -1,8 +1,19 @@
 //! API for simple game engine based on Rust and Vulkan API.
 
-pub use app::init;
+pub use app::{init, init_headless};
+#[cfg(feature = "hot-reload")]
+pub use graphics::hot_reload;
+pub use graphics::{
+    adapter, atlas, barrier, color, compute, debug, external, failure, indirect, lines, memory,
+    params, pipeline, pipeline_cache, post, push, query, readback, render_pass, screenshot,
+    semaphore, spirv, submit, target, texture,
+};
+pub use graphics::{Indices, PickResult, PickTicket, Vertex};
+pub use graphics::{ModelTransform, TransformHandle};
+pub use ultraviolet as math;
 
 pub mod app;
+pub mod asset;
 pub mod config;
 pub mod window;
 
@@ -0,0 +1,319 @@
+//! Control of game engine window after its creation.
+
+use std::cmp::Ordering;
+use std::sync::Arc;
+
+use thiserror::Error;
+use winit::dpi::PhysicalSize;
+use winit::error::ExternalError;
+use winit::monitor::{MonitorHandle, VideoMode};
+use winit::window::{Fullscreen, Window};
+
+use crate::window::Size;
+
+mod tests;
+
+/// Fullscreen mode of the window.
+///
+/// Monitors are identified by their index in the list of [available monitors](WindowHandle::monitors),
+/// `None` means the current monitor of the window.
+///
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum FullscreenMode {
+    /// Window is not fullscreen.
+    Windowed,
+    /// Borderless window which covers the whole monitor, video mode of the monitor is not changed.
+    Borderless { monitor: Option<usize> },
+    /// Exclusive fullscreen with the video mode which is the closest to provided size,
+    /// or to the current size of the window if `None`.
+    Exclusive {
+        monitor: Option<usize>,
+        size: Option<Size>,
+    },
+}
+
+/// Grabbing of the cursor by the window.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum CursorGrabMode {
+    /// Cursor moves freely.
+    None,
+    /// Cursor cannot leave the window.
+    ///
+    /// On macOS cursor is locked in place instead of being confined.
+    ///
+    Grabbed,
+}
+
+/// Video mode of the monitor.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct VideoModeInfo {
+    /// Resolution in physical pixels.
+    pub size: Size,
+    /// Refresh rate in Hz.
+    pub refresh_rate: u16,
+    /// Bit depth of colors.
+    pub bit_depth: u16,
+}
+
+impl From<&VideoMode> for VideoModeInfo {
+    fn from(mode: &VideoMode) -> Self {
+        let size = mode.size();
+        Self {
+            size: Size::new(size.width, size.height),
+            refresh_rate: mode.refresh_rate(),
+            bit_depth: mode.bit_depth(),
+        }
+    }
+}
+
+/// Monitor which is available to the window.
+///
+/// Video modes are sorted from the largest resolution with the highest refresh rate,
+/// their indices could be passed to [`WindowHandle::set_fullscreen_exclusive`].
+///
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MonitorInfo {
+    /// Human-readable name of the monitor, empty if the system does not provide it.
+    pub name: String,
+    /// Resolution of the monitor in physical pixels.
+    pub physical_size: Size,
+    /// Distinct refresh rates of video modes in Hz, from the highest.
+    pub refresh_rates: Vec<u16>,
+    /// Video modes which could be used for exclusive fullscreen.
+    pub video_modes: Vec<VideoModeInfo>,
+}
+
+impl MonitorInfo {
+    /// Creates information of the monitor, sorting its video modes.
+    pub fn new(name: String, physical_size: Size, mut video_modes: Vec<VideoModeInfo>) -> Self {
+        video_modes.sort_by(self::video_mode_order);
+        let mut refresh_rates: Vec<_> = video_modes.iter().map(|mode| mode.refresh_rate).collect();
+        refresh_rates.sort_unstable_by(|a, b| b.cmp(a));
+        refresh_rates.dedup();
+        Self {
+            name,
+            physical_size,
+            refresh_rates,
+            video_modes,
+        }
+    }
+
+    fn from_handle(monitor: &MonitorHandle) -> Self {
+        let size = monitor.size();
+        let video_modes = monitor
+            .video_modes()
+            .map(|mode| VideoModeInfo::from(&mode))
+            .collect();
+        Self::new(
+            monitor.name().unwrap_or_default(),
+            Size::new(size.width, size.height),
+            video_modes,
+        )
+    }
+}
+
+/// Error that can happen when controlling the window.
+#[derive(Debug, Error)]
+pub enum WindowError {
+    #[error("monitor {0} is not available")]
+    NoMonitor(usize),
+
+    #[error("window is not on any monitor")]
+    NoCurrentMonitor,
+
+    #[error("monitor has no video modes for exclusive fullscreen")]
+    NoVideoModes,
+
+    #[error("monitor {monitor} has no video mode {mode}")]
+    NoVideoMode { monitor: usize, mode: usize },
+
+    #[error("cursor grab is not supported: {0}")]
+    CursorGrab(#[from] ExternalError),
+}
+
+/// Cloneable handle of game engine window.
+///
+/// Swapchain is recreated automatically when size of the window is changed,
+/// including transitions from and to fullscreen.
+///
+#[derive(Clone)]
+pub struct WindowHandle {
+    window: Arc<Window>,
+}
+
+impl WindowHandle {
+    pub(crate) fn new(window: Arc<Window>) -> Self {
+        Self { window }
+    }
+
+    fn window(&self) -> &Window {
+        &self.window
+    }
+
+    /// Sets title of the window.
+    pub fn set_title(&self, title: &str) {
+        self.window().set_title(title)
+    }
+
+    /// Sets size of the client area of the window in physical pixels.
+    pub fn set_inner_size(&self, size: Size) {
+        self.window()
+            .set_inner_size(PhysicalSize::new(size.width, size.height))
+    }
+
+    /// Size of the client area of the window in physical pixels.
+    pub fn inner_size(&self) -> Size {
+        let size = self.window().inner_size();
+        Size::new(size.width, size.height)
+    }
+
+    /// Scale factor of the window, which is the count of physical pixels per logical pixel.
+    ///
+    /// For example, it is 1.5 with 150% scaling setting of Windows.
+    ///
+    pub fn scale_factor(&self) -> f64 {
+        self.window().scale_factor()
+    }
+
+    /// Names of monitors which are available, in order of their indices.
+    pub fn monitors(&self) -> Vec<String> {
+        self.window()
+            .available_monitors()
+            .map(|monitor| monitor.name().unwrap_or_default())
+            .collect()
+    }
+
+    /// Information of monitors which are available, in order of their indices.
+    pub fn monitor_infos(&self) -> Vec<MonitorInfo> {
+        self.window()
+            .available_monitors()
+            .map(|monitor| MonitorInfo::from_handle(&monitor))
+            .collect()
+    }
+
+    fn monitor(&self, index: Option<usize>) -> Result<MonitorHandle, WindowError> {
+        match index {
+            Some(index) => self
+                .window()
+                .available_monitors()
+                .nth(index)
+                .ok_or(WindowError::NoMonitor(index)),
+            None => self
+                .window()
+                .current_monitor()
+                .ok_or(WindowError::NoCurrentMonitor),
+        }
+    }
+
+    /// Sets fullscreen mode of the window.
+    ///
+    /// For exclusive fullscreen, returns video mode which was chosen.
+    /// If the monitor has no mode with requested size, the closest one is used.
+    ///
+    pub fn set_fullscreen(
+        &self,
+        mode: FullscreenMode,
+    ) -> Result<Option<VideoModeInfo>, WindowError> {
+        let (fullscreen, chosen) = match mode {
+            FullscreenMode::Windowed => (None, None),
+            FullscreenMode::Borderless { monitor } => {
+                let monitor = self.monitor(monitor)?;
+                (Some(Fullscreen::Borderless(Some(monitor))), None)
+            }
+            FullscreenMode::Exclusive { monitor, size } => {
+                let monitor = self.monitor(monitor)?;
+                let size = size.unwrap_or_else(|| self.inner_size());
+                let modes: Vec<_> = monitor.video_modes().collect();
+                let infos: Vec<_> = modes.iter().map(VideoModeInfo::from).collect();
+                let index =
+                    self::closest_video_mode(&infos, size).ok_or(WindowError::NoVideoModes)?;
+                let chosen = infos[index];
+                if chosen.size != size {
+                    log::warn!(
+                        "monitor has no video mode of {}x{}, using the closest one: {:?}",
+                        size.width,
+                        size.height,
+                        chosen,
+                    );
+                }
+                let mode = modes.into_iter().nth(index).unwrap();
+                (Some(Fullscreen::Exclusive(mode)), Some(chosen))
+            }
+        };
+        self.window().set_fullscreen(fullscreen);
+        Ok(chosen)
+    }
+
+    /// Sets exclusive fullscreen with provided video mode of the monitor,
+    /// as indexed in [monitor information](Self::monitor_infos).
+    ///
+    /// Driver could present the swapchain with exclusive access to the monitor
+    /// for lower latency if the device supports it (on Windows only).
+    ///
+    pub fn set_fullscreen_exclusive(
+        &self,
+        monitor_index: usize,
+        mode_index: usize,
+    ) -> Result<VideoModeInfo, WindowError> {
+        let monitor = self.monitor(Some(monitor_index))?;
+        let mut modes: Vec<_> = monitor
+            .video_modes()
+            .map(|mode| (VideoModeInfo::from(&mode), mode))
+            .collect();
+        modes.sort_by(|(a, _), (b, _)| self::video_mode_order(a, b));
+        let (info, mode) = modes
+            .into_iter()
+            .nth(mode_index)
+            .ok_or(WindowError::NoVideoMode {
+                monitor: monitor_index,
+                mode: mode_index,
+            })?;
+        self.window()
+            .set_fullscreen(Some(Fullscreen::Exclusive(mode)));
+        Ok(info)
+    }
+
+    /// Shows or hides the cursor while it is over the window.
+    pub fn set_cursor_visible(&self, visible: bool) {
+        self.window().set_cursor_visible(visible)
+    }
+
+    /// Sets grabbing of the cursor by the window.
+    pub fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), WindowError> {
+        let grab = mode == CursorGrabMode::Grabbed;
+        Ok(self.window().set_cursor_grab(grab)?)
+    }
+}
+
+/// Order of video modes from the largest resolution, then by higher refresh rate and bit depth.
+///
+/// System could report video modes in arbitrary order, so they are sorted to keep indices stable.
+///
+pub fn video_mode_order(a: &VideoModeInfo, b: &VideoModeInfo) -> Ordering {
+    let key = |mode: &VideoModeInfo| {
+        let pixels = mode.size.width as u64 * mode.size.height as u64;
+        (pixels, mode.size.width, mode.refresh_rate, mode.bit_depth)
+    };
+    key(b).cmp(&key(a))
+}
+
+/// Selects index of the video mode which is the closest to provided size.
+///
+/// Distance is measured between resolutions, modes with the same resolution are preferred
+/// by higher refresh rate, then by higher bit depth.
+///
+pub fn closest_video_mode(modes: &[VideoModeInfo], size: Size) -> Option<usize> {
+    let distance = |mode: &VideoModeInfo| {
+        let dx = mode.size.width.abs_diff(size.width) as u64;
+        let dy = mode.size.height.abs_diff(size.height) as u64;
+        dx * dx + dy * dy
+    };
+    modes
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, mode)| {
+            let preference = (u16::MAX - mode.refresh_rate, u16::MAX - mode.bit_depth);
+            (distance(mode), preference)
+        })
+        .map(|(index, _)| index)
+}
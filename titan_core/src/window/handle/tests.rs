@@ -0,0 +1,61 @@
+#![cfg(test)]
+
+use super::{closest_video_mode, MonitorInfo, VideoModeInfo};
+use crate::window::Size;
+
+fn mode(width: u32, height: u32, refresh_rate: u16) -> VideoModeInfo {
+    VideoModeInfo {
+        size: Size::new(width, height),
+        refresh_rate,
+        bit_depth: 32,
+    }
+}
+
+#[test]
+fn exact_video_mode_with_highest_refresh_rate_is_chosen() {
+    let modes = [
+        mode(1920, 1080, 60),
+        mode(1920, 1080, 144),
+        mode(1280, 720, 240),
+    ];
+    assert_eq!(closest_video_mode(&modes, Size::new(1920, 1080)), Some(1));
+}
+
+#[test]
+fn closest_video_mode_is_chosen_if_size_is_missing() {
+    let modes = [
+        mode(1920, 1080, 60),
+        mode(1280, 720, 60),
+        mode(2560, 1440, 60),
+    ];
+    assert_eq!(closest_video_mode(&modes, Size::new(1366, 768)), Some(1));
+    assert_eq!(closest_video_mode(&[], Size::new(1366, 768)), None);
+}
+
+#[test]
+fn monitor_video_modes_are_sorted_from_the_largest() {
+    let modes = vec![
+        mode(1280, 720, 60),
+        mode(1920, 1080, 60),
+        mode(1920, 1080, 144),
+        mode(1280, 720, 144),
+    ];
+    let monitor = MonitorInfo::new("Monitor".to_owned(), Size::new(1920, 1080), modes);
+    assert_eq!(
+        monitor.video_modes,
+        [
+            mode(1920, 1080, 144),
+            mode(1920, 1080, 60),
+            mode(1280, 720, 144),
+            mode(1280, 720, 60),
+        ]
+    );
+    assert_eq!(monitor.refresh_rates, [144, 60]);
+}
+
+#[test]
+fn monitor_without_video_modes() {
+    let monitor = MonitorInfo::new(String::new(), Size::new(800, 600), Vec::new());
+    assert!(monitor.video_modes.is_empty());
+    assert!(monitor.refresh_rates.is_empty());
+}
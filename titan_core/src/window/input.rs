@@ -0,0 +1,370 @@
+//! Keyboard and mouse input of game engine window.
+//!
+//! Key codes, mouse buttons and modifiers are owned by the engine,
+//! so games do not depend on the windowing backend directly.
+
+use std::collections::HashSet;
+
+use winit::event::{
+    ElementState, KeyboardInput, ModifiersState, MouseButton as WinitMouseButton, MouseScrollDelta,
+    VirtualKeyCode,
+};
+
+use crate::window::Event;
+
+mod tests;
+
+/// Declares [`KeyCode`] with the same variants as [`VirtualKeyCode`] and conversion between them.
+macro_rules! key_codes {
+    ($($(#[$meta:meta])* $name:ident,)*) => {
+        /// Symbolic name of the keyboard key.
+        #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+        pub enum KeyCode {
+            $($(#[$meta])* $name,)*
+            /// Key without symbolic name, identified by its platform-specific scancode.
+            Unknown(u32),
+        }
+
+        impl From<VirtualKeyCode> for KeyCode {
+            fn from(key: VirtualKeyCode) -> Self {
+                match key {
+                    $(VirtualKeyCode::$name => Self::$name,)*
+                }
+            }
+        }
+    };
+}
+
+key_codes! {
+    /// The '1' key over the letters.
+    Key1,
+    /// The '2' key over the letters.
+    Key2,
+    /// The '3' key over the letters.
+    Key3,
+    /// The '4' key over the letters.
+    Key4,
+    /// The '5' key over the letters.
+    Key5,
+    /// The '6' key over the letters.
+    Key6,
+    /// The '7' key over the letters.
+    Key7,
+    /// The '8' key over the letters.
+    Key8,
+    /// The '9' key over the letters.
+    Key9,
+    /// The '0' key over the 'O' and 'P' keys.
+    Key0,
+
+    A,
+    B,
+    C,
+    D,
+    E,
+    F,
+    G,
+    H,
+    I,
+    J,
+    K,
+    L,
+    M,
+    N,
+    O,
+    P,
+    Q,
+    R,
+    S,
+    T,
+    U,
+    V,
+    W,
+    X,
+    Y,
+    Z,
+
+    /// The Escape key, next to F1.
+    Escape,
+
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    F11,
+    F12,
+    F13,
+    F14,
+    F15,
+    F16,
+    F17,
+    F18,
+    F19,
+    F20,
+    F21,
+    F22,
+    F23,
+    F24,
+
+    /// Print Screen/SysRq.
+    Snapshot,
+    /// Scroll Lock.
+    Scroll,
+    /// Pause/Break key, next to Scroll lock.
+    Pause,
+
+    /// `Insert`, next to Backspace.
+    Insert,
+    Home,
+    Delete,
+    End,
+    PageDown,
+    PageUp,
+
+    Left,
+    Up,
+    Right,
+    Down,
+
+    /// The Backspace key, right over Enter.
+    Back,
+    /// The Enter key.
+    Return,
+    /// The space bar.
+    Space,
+
+    /// The "Compose" key on Linux.
+    Compose,
+
+    Caret,
+
+    Numlock,
+    Numpad0,
+    Numpad1,
+    Numpad2,
+    Numpad3,
+    Numpad4,
+    Numpad5,
+    Numpad6,
+    Numpad7,
+    Numpad8,
+    Numpad9,
+    NumpadAdd,
+    NumpadDivide,
+    NumpadDecimal,
+    NumpadComma,
+    NumpadEnter,
+    NumpadEquals,
+    NumpadMultiply,
+    NumpadSubtract,
+
+    AbntC1,
+    AbntC2,
+    Apostrophe,
+    Apps,
+    Asterisk,
+    At,
+    Ax,
+    Backslash,
+    Calculator,
+    Capital,
+    Colon,
+    Comma,
+    Convert,
+    Equals,
+    Grave,
+    Kana,
+    Kanji,
+    LAlt,
+    LBracket,
+    LControl,
+    LShift,
+    LWin,
+    Mail,
+    MediaSelect,
+    MediaStop,
+    Minus,
+    Mute,
+    MyComputer,
+    /// Also called "Next".
+    NavigateForward,
+    /// Also called "Prior".
+    NavigateBackward,
+    NextTrack,
+    NoConvert,
+    OEM102,
+    Period,
+    PlayPause,
+    Plus,
+    Power,
+    PrevTrack,
+    RAlt,
+    RBracket,
+    RControl,
+    RShift,
+    RWin,
+    Semicolon,
+    Slash,
+    Sleep,
+    Stop,
+    Sysrq,
+    Tab,
+    Underline,
+    Unlabeled,
+    VolumeDown,
+    VolumeUp,
+    Wake,
+    WebBack,
+    WebFavorites,
+    WebForward,
+    WebHome,
+    WebRefresh,
+    WebSearch,
+    WebStop,
+    Yen,
+    Copy,
+    Paste,
+    Cut,
+}
+
+impl KeyCode {
+    /// Key code of keyboard input, unknown keys are identified by their scancode.
+    pub fn from_input(input: &KeyboardInput) -> Self {
+        input
+            .virtual_keycode
+            .map_or(Self::Unknown(input.scancode), Self::from)
+    }
+}
+
+/// State of modifier keys.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub struct Modifiers {
+    /// Shift key is pressed.
+    pub shift: bool,
+    /// Control key is pressed.
+    pub ctrl: bool,
+    /// Alt key is pressed.
+    pub alt: bool,
+    /// Logo key (Windows key, Command key) is pressed.
+    pub logo: bool,
+}
+
+impl From<ModifiersState> for Modifiers {
+    fn from(state: ModifiersState) -> Self {
+        Self {
+            shift: state.shift(),
+            ctrl: state.ctrl(),
+            alt: state.alt(),
+            logo: state.logo(),
+        }
+    }
+}
+
+/// Button of the mouse.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+    /// Additional button identified by its platform-specific index.
+    Other(u16),
+}
+
+impl From<WinitMouseButton> for MouseButton {
+    fn from(button: WinitMouseButton) -> Self {
+        match button {
+            WinitMouseButton::Left => Self::Left,
+            WinitMouseButton::Right => Self::Right,
+            WinitMouseButton::Middle => Self::Middle,
+            WinitMouseButton::Other(index) => Self::Other(index),
+        }
+    }
+}
+
+/// Amount of scrolling of the mouse wheel or touchpad.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ScrollDelta {
+    /// Scrolling in lines or rows, as reported by most mouse wheels.
+    Lines { dx: f32, dy: f32 },
+    /// Scrolling in physical pixels, as reported by touchpads.
+    Pixels { dx: f64, dy: f64 },
+}
+
+impl From<MouseScrollDelta> for ScrollDelta {
+    fn from(delta: MouseScrollDelta) -> Self {
+        match delta {
+            MouseScrollDelta::LineDelta(dx, dy) => Self::Lines { dx, dy },
+            MouseScrollDelta::PixelDelta(delta) => Self::Pixels {
+                dx: delta.x,
+                dy: delta.y,
+            },
+        }
+    }
+}
+
+/// Translates button input of the mouse into event of the window.
+pub fn mouse_button_event(button: MouseButton, state: ElementState) -> Event {
+    match state {
+        ElementState::Pressed => Event::MouseButtonPressed(button),
+        ElementState::Released => Event::MouseButtonReleased(button),
+    }
+}
+
+/// Tracker of keyboard state which translates keyboard input into events of the window.
+#[derive(Debug, Clone, Default)]
+pub struct Keyboard {
+    pressed: HashSet<KeyCode>,
+    modifiers: Modifiers,
+}
+
+impl Keyboard {
+    /// Current state of modifier keys.
+    pub fn modifiers(&self) -> Modifiers {
+        self.modifiers
+    }
+
+    /// Checks if provided key is pressed now.
+    pub fn is_pressed(&self, key: KeyCode) -> bool {
+        self.pressed.contains(&key)
+    }
+
+    /// Translates keyboard input into event of the window.
+    ///
+    /// Press of the key which is already pressed is reported as repeat.
+    ///
+    pub fn input(&mut self, key: KeyCode, state: ElementState) -> Event {
+        let modifiers = self.modifiers;
+        match state {
+            ElementState::Pressed => {
+                let is_repeat = !self.pressed.insert(key);
+                Event::KeyPressed {
+                    key,
+                    modifiers,
+                    is_repeat,
+                }
+            }
+            ElementState::Released => {
+                self.pressed.remove(&key);
+                Event::KeyReleased { key, modifiers }
+            }
+        }
+    }
+
+    /// Updates state of modifier keys, returning event of the window.
+    pub fn modifiers_changed(&mut self, modifiers: Modifiers) -> Event {
+        self.modifiers = modifiers;
+        Event::ModifiersChanged(modifiers)
+    }
+
+    /// Releases all keys, for example when the window loses focus.
+    pub fn reset(&mut self) {
+        self.pressed.clear();
+        self.modifiers = Modifiers::default();
+    }
+}
@@ -0,0 +1,85 @@
+#![cfg(test)]
+
+use winit::event::{ElementState, ModifiersState, VirtualKeyCode};
+
+use super::{KeyCode, Keyboard, Modifiers};
+use crate::window::Event;
+
+#[test]
+fn virtual_key_codes_are_mapped() {
+    assert_eq!(KeyCode::from(VirtualKeyCode::A), KeyCode::A);
+    assert_eq!(KeyCode::from(VirtualKeyCode::Escape), KeyCode::Escape);
+    assert_eq!(KeyCode::from(VirtualKeyCode::Cut), KeyCode::Cut);
+}
+
+#[test]
+fn modifiers_are_mapped() {
+    let modifiers = Modifiers::from(ModifiersState::SHIFT | ModifiersState::LOGO);
+    let expected = Modifiers {
+        shift: true,
+        logo: true,
+        ..Modifiers::default()
+    };
+    assert_eq!(modifiers, expected);
+}
+
+#[test]
+fn held_key_is_reported_as_repeat() {
+    let mut keyboard = Keyboard::default();
+    let key = KeyCode::Unknown(42);
+    let repeats: Vec<_> = (0..2)
+        .map(|_| match keyboard.input(key, ElementState::Pressed) {
+            Event::KeyPressed { is_repeat, .. } => is_repeat,
+            _ => panic!("key press must be reported"),
+        })
+        .collect();
+    assert_eq!(repeats, [false, true]);
+    assert!(keyboard.is_pressed(key));
+
+    let released = keyboard.input(key, ElementState::Released);
+    assert!(matches!(
+        released,
+        Event::KeyReleased {
+            key: KeyCode::Unknown(42),
+            ..
+        }
+    ));
+    assert!(!keyboard.is_pressed(key));
+}
+
+#[test]
+fn key_events_carry_modifiers() {
+    let mut keyboard = Keyboard::default();
+    let ctrl = Modifiers {
+        ctrl: true,
+        ..Modifiers::default()
+    };
+    keyboard.modifiers_changed(ctrl);
+    let event = keyboard.input(KeyCode::S, ElementState::Pressed);
+    assert!(matches!(event, Event::KeyPressed { modifiers, .. } if modifiers == ctrl));
+
+    keyboard.reset();
+    assert_eq!(keyboard.modifiers(), Modifiers::default());
+    assert!(!keyboard.is_pressed(KeyCode::S));
+}
+
+#[test]
+fn mouse_input_is_mapped() {
+    use winit::event::{MouseButton as WinitMouseButton, MouseScrollDelta};
+
+    use super::{mouse_button_event, MouseButton, ScrollDelta};
+
+    assert_eq!(
+        MouseButton::from(WinitMouseButton::Other(4)),
+        MouseButton::Other(4)
+    );
+    let event = mouse_button_event(MouseButton::Left, ElementState::Released);
+    assert!(matches!(
+        event,
+        Event::MouseButtonReleased(MouseButton::Left)
+    ));
+    assert_eq!(
+        ScrollDelta::from(MouseScrollDelta::LineDelta(0.0, -1.0)),
+        ScrollDelta::Lines { dx: 0.0, dy: -1.0 }
+    );
+}
@@ -2,7 +2,18 @@
 
 use egui::CtxRef;
 
-use crate::app::DeltaTime;
+use crate::app::{proxy::UserEvent, spike::SpikeReport, timestep::RenderFrameInfo, DeltaTime};
+use crate::graphics::spirv::ShaderStage;
+
+pub use self::handle::{
+    CursorGrabMode, FullscreenMode, MonitorInfo, VideoModeInfo, WindowError, WindowHandle,
+};
+pub use self::input::{KeyCode, Keyboard, Modifiers, MouseButton, ScrollDelta};
+pub use winit::window::WindowId;
+
+pub mod handle;
+pub mod input;
+pub(crate) mod platform;
 
 /// General event of game engine window.
 pub enum Event {
@@ -12,18 +23,149 @@ pub enum Event {
     /// Called when game window was resized.
     Resized(Size),
 
-    /// Called when game window needs updating.
+    /// Called when scale factor of game window was changed.
+    ///
+    /// Window is resized according to [policy](crate::config::ScaleFactorPolicy)
+    /// of the configuration and the swapchain is recreated with its new physical size
+    /// before this event, so UI could be laid out again.
+    ///
+    ScaleFactorChanged { old: f64, new: f64, new_size: Size },
+
+    /// Called when game window gained (`true`) or lost (`false`) input focus.
+    ///
+    /// Unfocused window is not rendered unless
+    /// [configured](crate::config::Config::with_render_when_unfocused) otherwise.
+    ///
+    Focused(bool),
+
+    /// Called when game window was minimized, rendering is paused until it is restored.
+    Minimized,
+
+    /// Called when game window was restored after minimization.
+    Restored,
+
+    /// Called when the application was suspended by the system, rendering is paused.
+    Suspended,
+
+    /// Called when the application was resumed after suspension.
+    Resumed,
+
+    /// Called when the surface of game window was destroyed by the system
+    /// after the application was suspended (e.g. on Android).
+    ///
+    /// Nothing is rendered until the surface is restored.
+    ///
+    SurfaceLost,
+
+    /// Called when the surface of game window was recreated after it was lost.
+    SurfaceRestored,
+
+    /// Called once per frame before fixed steps are simulated and the frame is rendered,
+    /// with real time elapsed since the previous frame.
+    ///
+    /// Time is clamped to [`MAX_FRAME_DELTA`](crate::app::timestep::MAX_FRAME_DELTA),
+    /// so the first frame after startup does not include time of initialization.
+    ///
     Update(DeltaTime),
 
+    /// Called for each fixed step of simulation with duration of the step.
+    ///
+    /// Zero or more fixed steps are simulated before each frame is rendered.
+    ///
+    FixedUpdate(DeltaTime),
+
+    /// Called before the frame is rendered with interpolation state of simulation.
+    Render(RenderFrameInfo),
+
+    /// Called after the frame whose CPU time exceeded
+    /// [threshold](crate::config::Config::with_spike_threshold) of the rolling median.
+    FrameSpike(SpikeReport),
+
+    /// Called when shader could not be loaded.
+    ///
+    /// Identical failures of the same shader in a row are reported once.
+    ///
+    ShaderCompileFailed {
+        name: String,
+        stage: ShaderStage,
+        diagnostics: String,
+    },
+
+    /// Called when pipeline of the material could not be built.
+    ///
+    /// Identical failures of the same material in a row are reported once.
+    ///
+    PipelineBuildFailed { material: String, error: String },
+
+    /// Called when keyboard key was pressed.
+    ///
+    /// Repeated presses of the key which is held down have `is_repeat` flag.
+    ///
+    KeyPressed {
+        key: KeyCode,
+        modifiers: Modifiers,
+        is_repeat: bool,
+    },
+
+    /// Called when keyboard key was released.
+    KeyReleased { key: KeyCode, modifiers: Modifiers },
+
+    /// Called when state of modifier keys was changed.
+    ModifiersChanged(Modifiers),
+
+    /// Called when cursor was moved inside of game window,
+    /// with its position in physical pixels relative to the top left corner of the window.
+    CursorMoved(Position),
+
+    /// Called when cursor entered game window.
+    CursorEntered,
+
+    /// Called when cursor left game window.
+    CursorLeft,
+
+    /// Called when mouse button was pressed.
+    MouseButtonPressed(MouseButton),
+
+    /// Called when mouse button was released.
+    MouseButtonReleased(MouseButton),
+
+    /// Called when mouse wheel or touchpad was scrolled.
+    MouseWheel(ScrollDelta),
+
+    /// Called with raw relative motion of the mouse.
+    ///
+    /// Motion is reported by the device regardless of cursor position,
+    /// so it is not limited by the borders of the window or the screen.
+    /// Use this for camera control instead of [`CursorMoved`](Self::CursorMoved).
+    ///
+    MouseDelta { dx: f64, dy: f64 },
+
+    /// Called when event was posted by the user through [`EventProxy`](crate::app::proxy::EventProxy).
+    User(UserEvent),
+
     /// Called when game UI needs updating.
     UI(CtxRef),
 
+    /// Called when [additional window](crate::app::Application::create_window) was closed.
+    ///
+    /// Resources of the window are destroyed before this event, other windows keep running.
+    ///
+    WindowClosed(WindowId),
+
+    /// Called when input, focus or size of [additional window](crate::app::Application::create_window)
+    /// was changed, with the event which is reported in the same case for game window.
+    ///
+    /// Keyboard state and activity of each window are tracked separately.
+    ///
+    AdditionalWindow { window: WindowId, event: Box<Event> },
+
     /// Called when game window will be destroyed.
     Destroyed,
 }
 
 /// Size of game engine window.
-#[derive(Default, Copy, Clone)]
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Size {
     pub width: u32,
     pub height: u32,
@@ -36,6 +178,64 @@ pub const fn new(width: u32, height: u32) -> Self {
     }
 }
 
+/// Description of additional window of game engine.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct WindowDescriptor {
+    /// Title of the window.
+    pub title: String,
+    /// Initial size of the client area in physical pixels, or `None` to let the platform choose it.
+    pub size: Option<Size>,
+    /// If the window could be resized by the user.
+    pub resizable: bool,
+}
+
+impl WindowDescriptor {
+    /// Creates description of resizable window with provided title.
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            size: None,
+            resizable: true,
+        }
+    }
+
+    /// Sets initial size of the client area in physical pixels.
+    pub const fn with_size(mut self, size: Size) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    /// Sets if the window could be resized by the user.
+    pub const fn with_resizable(mut self, resizable: bool) -> Self {
+        self.resizable = resizable;
+        self
+    }
+}
+
+/// Position in game engine window.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct Position {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Position {
+    /// Creates new position in window.
+    pub const fn new(x: f64, y: f64) -> Self {
+        Self { x, y }
+    }
+
+    /// Converts position in physical pixels into logical pixels with provided scale factor.
+    pub fn to_logical(self, scale_factor: f64) -> Self {
+        Self::new(self.x / scale_factor, self.y / scale_factor)
+    }
+
+    /// Converts position in logical pixels into physical pixels with provided scale factor.
+    pub fn to_physical(self, scale_factor: f64) -> Self {
+        Self::new(self.x * scale_factor, self.y * scale_factor)
+    }
+}
+
 impl From<[u32; 2]> for Size {
     fn from(array: [u32; 2]) -> Self {
         Self::new(array[0], array[1])
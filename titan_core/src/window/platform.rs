@@ -0,0 +1,55 @@
+//! Platform specific integration of game engine window with desktop environment.
+
+use winit::window::WindowBuilder;
+
+use crate::config::Config;
+
+/// Applies desktop integration settings of the configuration to the window.
+///
+/// Settings which are not relevant to the current platform are ignored.
+///
+pub fn desktop_integration(builder: WindowBuilder, config: &Config) -> WindowBuilder {
+    #[cfg(windows)]
+    if let Some(id) = config.windows_app_user_model_id() {
+        self::set_app_user_model_id(id);
+    }
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    if let Some(id) = config.app_id() {
+        use winit::platform::unix::WindowBuilderExtUnix;
+
+        // Only one of them is used, depending on the display server.
+        return builder
+            .with_class(id.to_string(), id.to_string())
+            .with_app_id(id.to_string());
+    }
+    let _ = config;
+    builder
+}
+
+/// Sets application user model ID of the process which taskbar groups windows by.
+///
+/// It must be set before any window is created.
+///
+#[cfg(windows)]
+fn set_app_user_model_id(id: &str) {
+    #[link(name = "shell32")]
+    extern "system" {
+        fn SetCurrentProcessExplicitAppUserModelID(app_id: *const u16) -> i32;
+    }
+
+    let id: Vec<u16> = id.encode_utf16().chain(std::iter::once(0)).collect();
+    // SAFETY: string is nul-terminated and outlives the call.
+    let result = unsafe { SetCurrentProcessExplicitAppUserModelID(id.as_ptr()) };
+    if result < 0 {
+        log::warn!(
+            "application user model ID setting failure: {:#010x}",
+            result
+        );
+    }
+}
@@ -12,6 +12,8 @@ pub struct ComponentManager {
     _storages: HashMap<TypeId, Box<dyn Any>>,
 }
 
+// TODO: remove when ECS will be used by the engine
+#[allow(dead_code)]
 impl ComponentManager {
     /// Creates new component manager.
     pub fn new() -> Self {
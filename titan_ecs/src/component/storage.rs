@@ -7,6 +7,8 @@
 use super::{super::Entity, Component, ComponentID};
 
 /// Storage for statically typed components of ECS.
+// TODO: remove when ECS will be used by the engine
+#[allow(dead_code)]
 #[derive(Default)]
 pub struct ComponentStorage<T>
 where
@@ -18,6 +20,8 @@ pub struct ComponentStorage<T>
     component_to_entity: SecondaryMap<ComponentID, Entity>,
 }
 
+// TODO: remove when ECS will be used by the engine
+#[allow(dead_code)]
 impl<T> ComponentStorage<T>
 where
     T: Component,
@@ -107,6 +111,8 @@ pub fn components_mut(&mut self) -> impl Iterator<Item = &mut T> {
     }
 }
 
+// TODO: remove when ECS will be used by the engine
+#[allow(dead_code)]
 pub struct IntoIter<T>
 where
     T: Component,
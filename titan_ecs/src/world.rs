@@ -4,6 +4,8 @@
 use super::EntityStorage;
 
 /// Storage for entities, components and systems of ECS.
+// TODO: remove when ECS will be used by the engine
+#[allow(dead_code)]
 #[derive(Default)]
 pub struct World {
     /// Storage for all entities.
@@ -4,16 +4,32 @@
 
 use std::error::Error;
 use std::io::Cursor;
+use std::time::Duration;
 
 use egui::{TopBottomPanel, Window};
 
-use titan_core::{app::DeltaTime, config::Config, window::Event};
+use titan_core::{
+    app::{timestep::RenderFrameInfo, DeltaTime},
+    color::Color,
+    config::{settings, Config},
+    lines,
+    math::{Rotor3, Similarity3, Vec3},
+    post::{PostEffect, PostProcessChain},
+    texture::SamplerOptions,
+    window::{Event, FullscreenMode, KeyCode},
+    Vertex,
+};
 
 mod logger;
 
 const APP_NAME: &str = env!("CARGO_CRATE_NAME", "library must be compiled by Cargo");
 const APP_VERSION_STR: &str = env!("CARGO_PKG_VERSION", "library must be compiled by Cargo");
 
+/// Simulation runs at 20 steps per second regardless of display refresh rate,
+/// rendering interpolates between simulation states.
+const SIMULATION_STEP: Duration = Duration::from_millis(50);
+const GRAVITY: f32 = -9.8;
+
 /// Entry point of `titan-rs` game engine
 #[cfg_attr(target_os = "android", ndk_glue::main(backtrace = "on"))]
 fn main() -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
@@ -22,14 +38,46 @@ fn main() -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
 
     let version = APP_VERSION_STR.parse().unwrap();
     let enable_validation = cfg!(debug_assertions);
-    let config = Config::new(APP_NAME.to_string(), version, enable_validation);
+    let mut config = Config::new(APP_NAME.to_string(), version, enable_validation)
+        .with_fixed_timestep(SIMULATION_STEP)
+        .with_clear_color(Color::from_hex("#1e1e28")?)
+        .with_app_id(APP_NAME.to_string())
+        .with_windows_app_user_model_id(format!("tuguzT.{}", APP_NAME));
+    if let Some(path) = settings::default_settings_path(APP_NAME) {
+        if std::env::args().any(|arg| arg == "--reset-settings") {
+            match std::fs::remove_file(&path) {
+                Ok(()) => log::info!("user settings were reset"),
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+                Err(error) => return Err(error.into()),
+            }
+        }
+        let settings = config.load_or_init_user_settings(&path)?;
+        log::info!("using user settings {:?}", settings);
+    }
+    // Presentation is moved off the main thread only where platform supports it.
+    let capabilities = config.probe_capabilities()?;
+    config = config.with_present_thread(capabilities.present_thread);
+
+    // Scene is post processed by requested effects in order of arguments.
+    let post_chain =
+        std::env::args().fold(PostProcessChain::new(), |chain, arg| match arg.as_str() {
+            "--invert" => chain.with_effect(PostEffect::Invert),
+            "--vignette" => chain.with_effect(PostEffect::Vignette),
+            _ => chain,
+        });
+    config = config.with_post_processing(!post_chain.is_empty());
 
     let mut delta_time = DeltaTime::ZERO;
     let mut duration = DeltaTime::ZERO;
     let mut fps = 0;
     let mut prev_fps = 0;
+    let mut frame_info = RenderFrameInfo::default();
+    let (mut height, mut velocity) = (1.0f32, 0.0f32);
 
     let mut application = titan_core::init(config)?;
+    if !post_chain.is_empty() {
+        application.set_post_chain(&post_chain)?;
+    }
 
     let image_data = include_bytes!("../res/angry flop.jpg");
     let image = image::io::Reader::new(Cursor::new(image_data))
@@ -38,7 +86,31 @@ fn main() -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
         .to_rgba8();
     let texture_id = application.register_ui_image(&image)?;
 
-    application.run(move |event| match event {
+    let vertices = vec![
+        Vertex::from_raw([-0.5, -0.5, 0.0], [1.0, 0.0, 0.0, 1.0]).with_uv([0.0, 1.0]),
+        Vertex::from_raw([0.5, -0.5, 0.0], [0.0, 1.0, 0.0, 1.0]).with_uv([1.0, 1.0]),
+        Vertex::from_raw([0.5, 0.5, 0.0], [0.0, 0.0, 1.0, 1.0]).with_uv([1.0, 0.0]),
+        Vertex::from_raw([-0.5, 0.5, 0.0], [1.0, 1.0, 1.0, 1.0]).with_uv([0.0, 0.0]),
+    ];
+    let indices: Vec<u16> = vec![0, 1, 2, 2, 3, 0];
+    application.set_mesh(vertices, indices)?;
+    application.set_object_texture(&image, SamplerOptions::new())?;
+    if std::env::args().any(|arg| arg == "--grid") {
+        // Draw world-space grid of debug lines instead of the textured quad.
+        let (vertices, indices) = lines::grid(10, 0.25, Color::from_hex("#6c6c80")?);
+        application.set_mesh(vertices, indices)?;
+        let pipeline = application.create_object_pipeline(lines::line_pipeline())?;
+        application.set_object_pipeline(0, pipeline)?;
+    }
+
+    let transform = application.model_transform();
+    let tint = application.object_tint();
+    let window = application
+        .window_handle()
+        .ok_or("application has no window")?;
+    let mut fullscreen = false;
+
+    application.run_with_control(move |event, control| match event {
         Event::Created => {
             log::debug!("created");
         }
@@ -46,10 +118,96 @@ fn main() -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
             let size: (u32, u32) = size.into();
             log::debug!("resized with {:?}", size);
         }
+        Event::ScaleFactorChanged { old, new, .. } => {
+            log::debug!("scale factor changed from {} to {}", old, new);
+        }
         Event::Update(new_delta_time) => {
             delta_time = new_delta_time;
             duration += new_delta_time;
         }
+        Event::FixedUpdate(step) => {
+            // Bounce the mesh on the ground plane.
+            let step = step.as_secs_f32();
+            velocity += GRAVITY * step;
+            height += velocity * step;
+            if height < 0.0 {
+                height = -height;
+                velocity = -velocity;
+            }
+            let translation = Vec3::new(0.0, 0.0, height);
+            transform.push(Similarity3::new(translation, Rotor3::identity(), 1.0));
+        }
+        Event::Render(info) => {
+            frame_info = info;
+            // Pulse brightness of the mesh once per two seconds.
+            let time = info.render_time.as_secs_f32();
+            let brightness = 0.75 + 0.25 * (time * std::f32::consts::PI).cos();
+            tint.set(Color::linear(brightness, brightness, brightness, 1.0));
+        }
+        Event::FrameSpike(report) => {
+            log::debug!(
+                "{} time of frame {} took {:.1}x longer than usual",
+                report.source,
+                report.frame,
+                report.ratio()
+            );
+        }
+        Event::ShaderCompileFailed {
+            name,
+            stage,
+            diagnostics,
+        } => {
+            log::warn!("{} shader {} is broken: {}", stage, name, diagnostics);
+        }
+        Event::PipelineBuildFailed { material, error } => {
+            log::warn!("pipeline of material {} is broken: {}", material, error);
+        }
+        Event::KeyPressed {
+            key: KeyCode::Escape,
+            ..
+        } => {
+            control.exit();
+        }
+        Event::KeyPressed {
+            key: KeyCode::F11,
+            is_repeat: false,
+            ..
+        } => {
+            fullscreen = !fullscreen;
+            let mode = if fullscreen {
+                FullscreenMode::Borderless { monitor: None }
+            } else {
+                FullscreenMode::Windowed
+            };
+            if let Err(error) = window.set_fullscreen(mode) {
+                log::warn!("failed to toggle fullscreen: {}", error);
+            }
+        }
+        Event::KeyPressed {
+            key,
+            modifiers,
+            is_repeat: false,
+        } => {
+            log::debug!("key {:?} pressed with {:?}", key, modifiers);
+        }
+        Event::MouseButtonPressed(button) => {
+            log::debug!("mouse button {:?} pressed", button);
+        }
+        Event::KeyPressed { .. }
+        | Event::KeyReleased { .. }
+        | Event::ModifiersChanged(_)
+        | Event::CursorMoved(_)
+        | Event::CursorEntered
+        | Event::CursorLeft
+        | Event::MouseButtonReleased(_)
+        | Event::MouseWheel(_)
+        | Event::MouseDelta { .. }
+        | Event::User(_) => {}
+        Event::Focused(focused) => {
+            log::debug!("focused: {}", focused);
+        }
+        Event::Minimized | Event::Restored | Event::Suspended | Event::Resumed => {}
+        Event::SurfaceLost | Event::SurfaceRestored => {}
         Event::UI(ctx) => {
             const ID: &str = "top_panel";
 
@@ -61,11 +219,18 @@ fn main() -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
                 } else {
                     fps += 1;
                 }
-                let text = format!(
-                    "FPS: {}; average: {:.3}",
+                let mut text = format!(
+                    "FPS: {}; average: {:.3}; fixed steps: {}; alpha: {:.2}",
                     prev_fps,
                     1.0 / delta_time.as_secs_f64(),
+                    frame_info.fixed_steps_this_frame,
+                    frame_info.alpha,
                 );
+                // Present thread reports how much of its work the main thread did not wait for.
+                if let Some(reclaimed) = control.frame_stats().reclaimed_time {
+                    let reclaimed = reclaimed.as_secs_f64() * 1000.0;
+                    text += &format!("; present reclaimed: {:.2} ms", reclaimed);
+                }
                 ui.label(text);
             });
             Window::new("Movable dialog")
@@ -75,6 +240,14 @@ fn main() -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
                     ui.image(texture_id, [300.0, 300.0]);
                 });
         }
+        Event::WindowClosed(id) => {
+            log::debug!("window {:?} closed", id);
+        }
+        Event::AdditionalWindow { window, event } => {
+            if let Event::KeyPressed { key, .. } = *event {
+                log::debug!("key {:?} pressed in window {:?}", key, window);
+            }
+        }
         Event::Destroyed => {
             log::debug!("destroyed");
         }